@@ -28,6 +28,7 @@ pub fn parse_and_execute(
     evm: EvmEngine,
     skipped_tests: Option<&[&str]>,
     re_run_stateless: bool,
+    parallel_execution: bool,
 ) -> datatest_stable::Result<()> {
     let rt = tokio::runtime::Runtime::new().unwrap();
     let tests = parse_tests(path);
@@ -44,7 +45,13 @@ pub fn parse_and_execute(
             continue;
         }
 
-        let result = rt.block_on(run_ef_test(&test_key, &test, evm, re_run_stateless));
+        let result = rt.block_on(run_ef_test(
+            &test_key,
+            &test,
+            evm,
+            re_run_stateless,
+            parallel_execution,
+        ));
 
         if let Err(e) = result {
             eprintln!("Test {test_key} failed: {e:?}");
@@ -65,6 +72,7 @@ pub async fn run_ef_test(
     test: &TestUnit,
     evm: EvmEngine,
     run_stateless: bool,
+    parallel_execution: bool,
 ) -> Result<(), String> {
     // check that the decoded genesis block header matches the deserialized one
     let genesis_rlp = test.genesis_rlp.clone();
@@ -80,7 +88,7 @@ pub async fn run_ef_test(
     // Blockchain EF tests are meant for L1.
     let blockchain_type = BlockchainType::L1;
 
-    let blockchain = Blockchain::new(evm, store.clone(), blockchain_type);
+    let blockchain = Blockchain::new(evm, store.clone(), blockchain_type, parallel_execution);
     // Execute all blocks in test
     for block_fixture in test.blocks.iter() {
         let expects_exception = block_fixture.expect_exception.is_some();
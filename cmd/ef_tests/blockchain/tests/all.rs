@@ -11,12 +11,17 @@ fn parse_and_execute_runner(path: &Path) -> datatest_stable::Result<()> {
         EvmEngine::REVM
     };
 
-    parse_and_execute(path, engine, None, false)
+    parse_and_execute(path, engine, None, false, false)
 }
 
 #[cfg(feature = "levm")]
 fn parse_and_execute_stateless_runner(path: &Path) -> datatest_stable::Result<()> {
-    parse_and_execute(path, EvmEngine::LEVM, None, true)
+    parse_and_execute(path, EvmEngine::LEVM, None, true, false)
+}
+
+#[cfg(feature = "levm")]
+fn parse_and_execute_parallel_runner(path: &Path) -> datatest_stable::Result<()> {
+    parse_and_execute(path, EvmEngine::LEVM, None, false, true)
 }
 #[cfg(feature = "levm")]
 datatest_stable::harness!(
@@ -25,6 +30,9 @@ datatest_stable::harness!(
     r".*",
     parse_and_execute_stateless_runner,
     TEST_FOLDER,
+    r".*",
+    parse_and_execute_parallel_runner,
+    TEST_FOLDER,
     r".*"
 );
 #[cfg(not(feature = "levm"))]
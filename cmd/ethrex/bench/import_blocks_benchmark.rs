@@ -9,7 +9,7 @@ use ethrex_blockchain::BlockchainType;
 use ethrex_vm::EvmEngine;
 
 #[inline]
-fn block_import() {
+fn block_import(parallel_execution: bool) {
     let data_dir = DEFAULT_DATADIR;
     set_datadir(data_dir);
     remove_db(data_dir, true);
@@ -28,6 +28,7 @@ fn block_import() {
         data_dir,
         genesis,
         evm_engine,
+        parallel_execution,
         blockchain_type,
     ))
     .expect("Failed to import blocks on the Tokio runtime");
@@ -36,7 +37,12 @@ fn block_import() {
 pub fn import_blocks_benchmark(c: &mut Criterion) {
     let mut group = c.benchmark_group("Block import");
     group.sample_size(10);
-    group.bench_function("Block import ERC20 transfers", |b| b.iter(block_import));
+    group.bench_function("Block import ERC20 transfers", |b| {
+        b.iter(|| block_import(false))
+    });
+    group.bench_function("Block import ERC20 transfers (parallel)", |b| {
+        b.iter(|| block_import(true))
+    });
     group.finish();
 }
 
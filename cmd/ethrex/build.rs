@@ -130,6 +130,10 @@ fn download_script() {
             &Path::new("../../crates/l2/contracts/src/l2/L2ToL1Messenger.sol"),
             "L2ToL1Messenger",
         ),
+        (
+            &Path::new("../../crates/l2/contracts/src/l2/L1FeeOracle.sol"),
+            "L1FeeOracle",
+        ),
         (
             &Path::new("../../crates/l2/contracts/src/l2/L2Upgradeable.sol"),
             "UpgradeableSystemContract",
@@ -181,6 +185,7 @@ fn write_empty_bytecode_files(output_contracts_path: &Path) {
         "CommonBridge",
         "CommonBridgeL2",
         "L2ToL1Messenger",
+        "L1FeeOracle",
         "UpgradeableSystemContract",
         "SequencerRegistry",
         "OnChainProposerBased",
@@ -267,7 +272,8 @@ use bytes::Bytes;
 use ethrex_common::types::{Genesis, GenesisAccount};
 use ethrex_common::{Address, H160, U256};
 use ethrex_l2_sdk::{
-    COMMON_BRIDGE_L2_ADDRESS, L2_TO_L1_MESSENGER_ADDRESS, address_to_word, get_erc1967_slot,
+    COMMON_BRIDGE_L2_ADDRESS, L1_FEE_ORACLE_ADDRESS, L2_TO_L1_MESSENGER_ADDRESS, address_to_word,
+    get_erc1967_slot,
 };
 use genesis_tool::genesis::write_genesis_as_json;
 
@@ -300,6 +306,12 @@ fn l2_to_l1_messenger_runtime(out_dir: &Path) -> Vec<u8> {
     fs::read(path).expect("Failed to read bytecode file")
 }
 
+/// Bytecode of the L1FeeOracle contract.
+fn l1_fee_oracle_runtime(out_dir: &Path) -> Vec<u8> {
+    let path = out_dir.join("contracts/solc_out/L1FeeOracle.bytecode");
+    fs::read(path).expect("Failed to read bytecode file")
+}
+
 /// Bytecode of the L2Upgradeable contract.
 fn l2_upgradeable_runtime(out_dir: &Path) -> Vec<u8> {
     let path = out_dir.join("contracts/solc_out/UpgradeableSystemContract.bytecode");
@@ -387,6 +399,13 @@ pub fn update_genesis_file(
         out_dir,
     )?;
 
+    add_with_proxy(
+        &mut genesis,
+        L1_FEE_ORACLE_ADDRESS,
+        l1_fee_oracle_runtime(out_dir),
+        out_dir,
+    )?;
+
     write_genesis_as_json(genesis, Path::new(l2_genesis_path)).map_err(std::io::Error::other)?;
 
     Ok(())
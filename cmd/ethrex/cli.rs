@@ -6,9 +6,16 @@ use std::{
 };
 
 use clap::{ArgAction, Parser as ClapParser, Subcommand as ClapSubcommand};
-use ethrex_blockchain::{BlockchainType, error::ChainError};
-use ethrex_common::types::{Block, Genesis};
-use ethrex_p2p::{sync::SyncMode, types::Node};
+use ethrex_blockchain::{BlockchainType, error::ChainError, validate_block_stateless};
+use ethrex_common::types::{Block, Genesis, block_execution_witness::ExecutionWitnessResult};
+use ethrex_p2p::{
+    peer_limits::{
+        DEFAULT_MAX_INBOUND_ATTEMPTS_PER_IP, DEFAULT_MAX_PEERS, DEFAULT_MAX_PEERS_PER_SUBNET,
+        DEFAULT_RESERVED_OUTBOUND_RATIO,
+    },
+    sync::SyncMode,
+    types::Node,
+};
 use ethrex_rlp::encode::RLPEncode;
 use ethrex_storage::error::StoreError;
 use ethrex_vm::EvmEngine;
@@ -70,6 +77,14 @@ pub struct Options {
     pub force: bool,
     #[arg(long = "syncmode", default_value = "full", value_name = "SYNC_MODE", value_parser = utils::parse_sync_mode, help = "The way in which the node will sync its state.", long_help = "Can be either \"full\" or \"snap\" with \"full\" as default value.", help_heading = "P2P options")]
     pub syncmode: SyncMode,
+    #[arg(
+        long = "sync.skeleton",
+        help = "Download headers backwards from the consensus layer's head before processing them.",
+        long_help = "Headers are downloaded backwards from the head given by the consensus layer, and only processed once they link with the local chain. The download progress is kept across restarts.",
+        action = clap::ArgAction::SetTrue,
+        help_heading = "P2P options"
+    )]
+    pub sync_skeleton: bool,
     #[arg(
         long = "metrics.addr",
         value_name = "ADDRESS",
@@ -92,6 +107,25 @@ pub struct Options {
         help_heading = "Node options"
     )]
     pub metrics_enabled: bool,
+    #[arg(
+        long = "metrics.opcodes",
+        action = ArgAction::SetTrue,
+        help = "Profile the opcodes and precompiles executed by imported blocks.",
+        long_help = "The execution count, gas and time of every opcode and precompile are published as metrics. Only supported by LEVM, it slows down block execution. Blocks are executed sequentially while profiling, even with `--evm.parallel`.",
+        help_heading = "Node options",
+        env = "ETHREX_METRICS_OPCODES"
+    )]
+    pub metrics_opcodes: bool,
+    #[arg(
+        long = "witness.retention",
+        value_name = "BLOCKS",
+        value_parser = clap::value_parser!(u64).range(1..),
+        help = "Record the execution witness of imported blocks, keeping the ones of the last BLOCKS blocks.",
+        long_help = "The trie nodes, codes and headers accessed while importing each block are stored, so `debug_executionWitness` can return the witness of recent blocks without re-executing them. Witnesses are recorded with LEVM.",
+        help_heading = "Node options",
+        env = "ETHREX_WITNESS_RETENTION"
+    )]
+    pub witness_retention: Option<u64>,
     #[arg(
         long = "dev",
         action = ArgAction::SetTrue,
@@ -104,11 +138,29 @@ pub struct Options {
         long = "evm",
         default_value_t = EvmEngine::default(),
         value_name = "EVM_BACKEND",
-        help = "Has to be `levm` or `revm`",
+        help = "Has to be `levm`, `revm` or `differential`",
         value_parser = utils::parse_evm_engine,
         help_heading = "Node options",
         env = "ETHREX_EVM")]
     pub evm: EvmEngine,
+    #[arg(
+        long = "evm.parallel",
+        action = ArgAction::SetTrue,
+        help = "Execute the transactions of blocks in parallel.",
+        long_help = "Transactions are executed optimistically in parallel and re-executed if they conflict, the results are the same as the sequential execution. Only supported by LEVM, and not used while profiling with `--metrics.opcodes`.",
+        help_heading = "Node options",
+        env = "ETHREX_EVM_PARALLEL"
+    )]
+    pub evm_parallel: bool,
+    #[arg(
+        long = "binary-trie",
+        action = ArgAction::SetTrue,
+        help = "Also store the genesis state in an EIP-7864 binary trie.",
+        long_help = "The nodes of the binary trie prototype are kept in their own table, the node keeps using the Merkle Patricia Trie. Requires the binary to be built with the `binary-trie` feature.",
+        help_heading = "Node options",
+        env = "ETHREX_BINARY_TRIE"
+    )]
+    pub binary_trie: bool,
     #[arg(
         long = "log.level",
         default_value_t = Level::INFO,
@@ -191,6 +243,56 @@ pub struct Options {
         help_heading = "P2P options"
     )]
     pub discovery_port: String,
+    #[arg(
+        long = "p2p.maxpeers",
+        default_value_t = DEFAULT_MAX_PEERS,
+        value_name = "MAX_PEERS",
+        help = "Maximum number of connected peers, inbound and outbound combined.",
+        help_heading = "P2P options"
+    )]
+    pub p2p_max_peers: usize,
+    #[arg(
+        long = "p2p.outbound-ratio",
+        default_value_t = DEFAULT_RESERVED_OUTBOUND_RATIO,
+        value_name = "RATIO",
+        value_parser = utils::parse_ratio,
+        help = "Fraction of the peer slots reserved for outbound connections.",
+        long_help = "Fraction of the peer slots reserved for connections initiated by this node. Inbound connections can't take these slots. Has to be between 0 and 1.",
+        help_heading = "P2P options"
+    )]
+    pub p2p_outbound_ratio: f64,
+    #[arg(
+        long = "p2p.max-peers-per-subnet",
+        default_value_t = DEFAULT_MAX_PEERS_PER_SUBNET,
+        value_name = "MAX_PEERS",
+        help = "Maximum number of connected peers from the same /24 (IPv4) or /64 (IPv6) subnet.",
+        long_help = "Maximum number of connected peers from the same /24 (IPv4) or /64 (IPv6) subnet. Loopback and private addresses are not limited.",
+        help_heading = "P2P options"
+    )]
+    pub p2p_max_peers_per_subnet: usize,
+    #[arg(
+        long = "p2p.max-inbound-attempts",
+        default_value_t = DEFAULT_MAX_INBOUND_ATTEMPTS_PER_IP,
+        value_name = "ATTEMPTS",
+        help = "Maximum number of inbound connection attempts accepted from a single IP every 30 seconds.",
+        help_heading = "P2P options"
+    )]
+    pub p2p_max_inbound_attempts: usize,
+    #[arg(
+        long = "txpool.nobroadcast",
+        action = ArgAction::SetTrue,
+        help = "Don't propagate transactions to peers.",
+        long_help = "Don't propagate any transaction to peers, neither in full nor by announcing their hashes. Peers can still request transactions from the pool.",
+        help_heading = "P2P options"
+    )]
+    pub txpool_no_broadcast: bool,
+    #[arg(
+        long = "txpool.nolocals",
+        action = ArgAction::SetTrue,
+        help = "Don't propagate transactions submitted through the RPC to peers.",
+        help_heading = "P2P options"
+    )]
+    pub txpool_no_locals: bool,
 }
 
 impl Options {
@@ -250,15 +352,26 @@ impl Default for Options {
             p2p_port: Default::default(),
             discovery_addr: Default::default(),
             discovery_port: Default::default(),
+            p2p_max_peers: DEFAULT_MAX_PEERS,
+            p2p_outbound_ratio: DEFAULT_RESERVED_OUTBOUND_RATIO,
+            p2p_max_peers_per_subnet: DEFAULT_MAX_PEERS_PER_SUBNET,
+            p2p_max_inbound_attempts: DEFAULT_MAX_INBOUND_ATTEMPTS_PER_IP,
+            txpool_no_broadcast: false,
+            txpool_no_locals: false,
             network: Default::default(),
             bootnodes: Default::default(),
             datadir: Default::default(),
             syncmode: Default::default(),
+            sync_skeleton: false,
             metrics_addr: "0.0.0.0".to_owned(),
             metrics_port: Default::default(),
             metrics_enabled: Default::default(),
+            metrics_opcodes: false,
+            witness_retention: None,
             dev: Default::default(),
             evm: Default::default(),
+            evm_parallel: false,
+            binary_trie: false,
             force: false,
         }
     }
@@ -324,6 +437,34 @@ pub enum Subcommand {
         )]
         genesis_path: PathBuf,
     },
+    #[command(
+        name = "validate-stateless",
+        about = "Validate a block using only its execution witness, without a local state DB"
+    )]
+    ValidateStateless {
+        #[arg(
+            required = true,
+            long = "block",
+            value_name = "BLOCK_FILE_PATH",
+            help = "Path to the rlp encoded block"
+        )]
+        block_path: String,
+        #[arg(
+            required = true,
+            long = "witness",
+            value_name = "WITNESS_FILE_PATH",
+            help = "Path to the execution witness json, as returned by debug_executionWitness"
+        )]
+        witness_path: PathBuf,
+        #[arg(
+            long = "network",
+            value_name = "GENESIS_FILE_PATH",
+            help = "Network whose chain config is used to validate the block.",
+            long_help = "A genesis file or the name of a known network (holesky, sepolia, hoodi or mainnet). The chain config included in the witness is ignored. If not specified, the node's `--network` is used.",
+            value_parser = clap::value_parser!(Network),
+        )]
+        network: Option<Network>,
+    },
     #[command(name = "l2")]
     L2(l2::L2Command),
 }
@@ -351,7 +492,15 @@ impl Subcommand {
                 } else {
                     BlockchainType::L1
                 };
-                import_blocks(&path, &opts.datadir, genesis, opts.evm, blockchain_type).await?;
+                import_blocks(
+                    &path,
+                    &opts.datadir,
+                    genesis,
+                    opts.evm,
+                    opts.evm_parallel,
+                    blockchain_type,
+                )
+                .await?;
             }
             Subcommand::Export { path, first, last } => {
                 export_blocks(&path, &opts.datadir, first, last).await
@@ -361,6 +510,24 @@ impl Subcommand {
                 let state_root = genesis.compute_state_root();
                 println!("{state_root:#x}");
             }
+            Subcommand::ValidateStateless {
+                block_path,
+                witness_path,
+                network,
+            } => {
+                let network = network.unwrap_or_else(|| get_network(opts));
+                let chain_config = network.get_genesis()?.config;
+                let block = utils::read_block_file(&block_path);
+                let witness: ExecutionWitnessResult =
+                    serde_json::from_reader(File::open(witness_path)?)?;
+                let state_root = validate_block_stateless(&block, witness, chain_config)?;
+                info!(
+                    "Block {} ({:#x}) is valid",
+                    block.header.number,
+                    block.hash()
+                );
+                println!("{state_root:#x}");
+            }
             Subcommand::L2(command) => command.run().await?,
         }
 
@@ -400,11 +567,19 @@ pub async fn import_blocks(
     data_dir: &str,
     genesis: Genesis,
     evm: EvmEngine,
+    parallel_execution: bool,
     blockchain_type: BlockchainType,
 ) -> Result<(), ChainError> {
     let data_dir = set_datadir(data_dir);
-    let store = init_store(&data_dir, genesis).await;
-    let blockchain = init_blockchain(evm, store.clone(), blockchain_type);
+    let store = init_store(&data_dir, genesis, false).await;
+    let blockchain = init_blockchain(
+        evm,
+        parallel_execution,
+        false,
+        None,
+        store.clone(),
+        blockchain_type,
+    );
     let path_metadata = metadata(path).expect("Failed to read path");
 
     // If it's an .rlp file it will be just one chain, but if it's a directory there can be multiple chains.
@@ -15,8 +15,10 @@ use ethrex_p2p::{
     kademlia::KademliaTable,
     network::{P2PContext, peer_table, public_key_from_signing_key},
     peer_handler::PeerHandler,
+    peer_limits::PeerLimits,
     rlpx::l2::l2_connection::P2PBasedContext,
     sync_manager::SyncManager,
+    tx_gossip::TxGossipPolicy,
     types::{Node, NodeRecord},
 };
 use ethrex_storage::{EngineType, Store};
@@ -74,8 +76,8 @@ pub fn init_metrics(opts: &Options, tracker: TaskTracker) {
 }
 
 /// Opens a new or pre-existing Store and loads the initial state provided by the network
-pub async fn init_store(data_dir: &str, genesis: Genesis) -> Store {
-    let store = open_store(data_dir);
+pub async fn init_store(data_dir: &str, genesis: Genesis, binary_trie: bool) -> Store {
+    let store = with_binary_trie(open_store(data_dir), binary_trie);
     store
         .add_initial_state(genesis)
         .await
@@ -83,6 +85,22 @@ pub async fn init_store(data_dir: &str, genesis: Genesis) -> Store {
     store
 }
 
+#[cfg(feature = "binary-trie")]
+fn with_binary_trie(store: Store, binary_trie: bool) -> Store {
+    store.with_binary_trie(binary_trie)
+}
+
+#[cfg(not(feature = "binary-trie"))]
+fn with_binary_trie(store: Store, binary_trie: bool) -> Store {
+    if binary_trie {
+        error!("Binary wasn't built with The feature flag `binary-trie` enabled.");
+        panic!(
+            "Build the binary with the `binary-trie` feature in order to use the `--binary-trie` cli's argument."
+        );
+    }
+    store
+}
+
 /// Initializes a pre-existing Store
 pub async fn load_store(data_dir: &str) -> Store {
     let store = open_store(data_dir);
@@ -115,11 +133,20 @@ pub fn open_store(data_dir: &str) -> Store {
 
 pub fn init_blockchain(
     evm_engine: EvmEngine,
+    parallel_execution: bool,
+    opcode_profiling: bool,
+    witness_retention: Option<u64>,
     store: Store,
     blockchain_type: BlockchainType,
 ) -> Arc<Blockchain> {
-    info!("Initiating blockchain with EVM: {}", evm_engine);
-    Blockchain::new(evm_engine, store, blockchain_type).into()
+    info!(
+        "Initiating blockchain with EVM: {} (parallel execution: {})",
+        evm_engine, parallel_execution
+    );
+    Blockchain::new(evm_engine, store, blockchain_type, parallel_execution)
+        .with_opcode_profiling(opcode_profiling)
+        .with_witness_retention(witness_retention)
+        .into()
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -142,6 +169,7 @@ pub async fn init_rpc_api(
         cancel_token,
         blockchain.clone(),
         store.clone(),
+        opts.sync_skeleton,
     )
     .await;
 
@@ -195,6 +223,8 @@ pub async fn init_network(
         blockchain,
         get_client_version(),
         based_context,
+        get_peer_limits(opts),
+        get_tx_gossip_policy(opts),
     );
 
     context.set_fork_id().await.expect("Set fork id");
@@ -246,6 +276,22 @@ pub fn get_network(opts: &Options) -> Network {
     opts.network.clone().unwrap_or(default)
 }
 
+pub fn get_peer_limits(opts: &Options) -> PeerLimits {
+    PeerLimits {
+        max_peers: opts.p2p_max_peers,
+        reserved_outbound_ratio: opts.p2p_outbound_ratio,
+        max_peers_per_subnet: opts.p2p_max_peers_per_subnet,
+        max_inbound_attempts_per_ip: opts.p2p_max_inbound_attempts,
+    }
+}
+
+pub fn get_tx_gossip_policy(opts: &Options) -> TxGossipPolicy {
+    TxGossipPolicy {
+        no_broadcast: opts.txpool_no_broadcast,
+        no_locals: opts.txpool_no_locals,
+    }
+}
+
 #[allow(dead_code)]
 pub fn get_bootnodes(opts: &Options, network: &Network, data_dir: &str) -> Vec<Node> {
     let mut bootnodes: Vec<Node> = opts.bootnodes.clone();
@@ -384,12 +430,19 @@ pub async fn init_l1(
     let network = get_network(&opts);
 
     let genesis = network.get_genesis()?;
-    let store = init_store(&data_dir, genesis).await;
+    let store = init_store(&data_dir, genesis, opts.binary_trie).await;
 
     #[cfg(feature = "sync-test")]
     set_sync_block(&store).await;
 
-    let blockchain = init_blockchain(opts.evm, store.clone(), BlockchainType::L1);
+    let blockchain = init_blockchain(
+        opts.evm,
+        opts.evm_parallel,
+        opts.metrics_opcodes,
+        opts.witness_retention,
+        store.clone(),
+        BlockchainType::L1,
+    );
 
     let signer = get_signer(&data_dir);
 
@@ -5,7 +5,7 @@ use crate::{
     l2::{
         self,
         deployer::{DeployerOptions, deploy_l1_contracts},
-        options::{Options, ProverClientOptions},
+        options::{Options, ProverClientOptions, ValidiumDataAvailability},
     },
     networks::Network,
     utils::{parse_private_key, set_datadir},
@@ -13,13 +13,18 @@ use crate::{
 use clap::{FromArgMatches, Parser, Subcommand};
 use ethrex_common::{
     Address, H256, U256,
-    types::{BYTES_PER_BLOB, BlobsBundle, BlockHeader, batch::Batch, bytes_from_blob},
+    types::{BlobsBundle, BlockHeader, batch::Batch},
 };
-use ethrex_l2_common::{calldata::Value, l1_messages::get_l1_message_hash, state_diff::StateDiff};
-use ethrex_l2_sdk::call_contract;
-use ethrex_rpc::{
-    EthClient, clients::beacon::BeaconClient, types::block_identifier::BlockIdentifier,
+use ethrex_l2::data_availability::{
+    BlobsDataAvailability, CalldataDataAvailability, CommitteeDataAvailability, DataAvailability,
+    batch_number_from_calldata, start_da_server,
+};
+use ethrex_l2_common::{
+    calldata::Value, compression::decode_state_diff, l1_messages::get_l1_message_hash,
 };
+use ethrex_l2_rpc::signer::LocalSigner;
+use ethrex_l2_sdk::call_contract;
+use ethrex_rpc::{EthClient, clients::beacon::BeaconClient};
 use ethrex_storage::{EngineType, Store, UpdateBatch};
 use ethrex_storage_rollup::StoreRollup;
 use eyre::OptionExt;
@@ -29,6 +34,7 @@ use reqwest::Url;
 use secp256k1::SecretKey;
 use std::{
     fs::{create_dir_all, read_dir},
+    net::{IpAddr, SocketAddr},
     path::PathBuf,
     time::Duration,
 };
@@ -106,7 +112,9 @@ pub enum Command {
         #[arg(long = "force", required = false, action = clap::ArgAction::SetTrue)]
         force: bool,
     },
-    #[command(about = "Launch a server that listens for Blobs submissions and saves them offline.")]
+    #[command(
+        about = "Launch a server that listens for batch commitments and saves their data offline."
+    )]
     BlobsSaver {
         #[arg(
             short = 'c',
@@ -114,24 +122,68 @@ pub enum Command {
             help = "The contract address to listen to."
         )]
         contract_address: Address,
-        #[arg(short = 'd', long, help = "The directory to save the blobs.")]
+        #[arg(short = 'd', long, help = "The directory to save the batch data.")]
         data_dir: PathBuf,
         #[arg(short = 'e', long)]
         l1_eth_rpc: Url,
-        #[arg(short = 'b', long)]
-        l1_beacon_rpc: Url,
+        #[arg(
+            short = 'b',
+            long,
+            required_unless_present = "validium_data_availability",
+            help = "Beacon node to get the blobs from."
+        )]
+        l1_beacon_rpc: Option<Url>,
+        #[arg(
+            long = "validium.data-availability",
+            value_name = "MODE",
+            value_enum,
+            help = "Where the validium publishes the data of its batches. The blobs are saved if not set."
+        )]
+        validium_data_availability: Option<ValidiumDataAvailability>,
+        #[arg(
+            long = "validium.da-committee-urls",
+            value_name = "URLs",
+            value_delimiter = ',',
+            num_args = 1..,
+            help = "URLs of the servers of the data availability committee."
+        )]
+        da_committee_urls: Vec<Url>,
     },
-    #[command(about = "Reconstructs the L2 state from L1 blobs.")]
+    #[command(about = "Reconstructs the L2 state from the data of its batches.")]
     Reconstruct {
         #[arg(short = 'g', long, help = "The genesis file for the L2 network.")]
         genesis: PathBuf,
-        #[arg(short = 'b', long, help = "The directory to read the blobs from.")]
+        #[arg(
+            short = 'b',
+            long,
+            help = "The directory to read the batch data from, as saved by the blobs saver."
+        )]
         blobs_dir: PathBuf,
         #[arg(short = 's', long, help = "The path to the store.")]
         store_path: PathBuf,
         #[arg(short = 'c', long, help = "Address of the L2 proposer coinbase")]
         coinbase: Address,
     },
+    #[command(
+        name = "da-server",
+        about = "Launch a server of a data availability committee, which stores the batch data and signs attestations of it."
+    )]
+    DAServer {
+        #[arg(long, value_parser = parse_private_key, env = "ETHREX_DA_SERVER_PRIVATE_KEY", help = "The private key the attestations are signed with.")]
+        private_key: SecretKey,
+        #[arg(
+            long,
+            env = "ETHREX_DA_SERVER_SEQUENCER_ADDRESS",
+            help = "The address of the sequencer, only the data it signs is stored."
+        )]
+        sequencer_address: Address,
+        #[arg(long, default_value = "127.0.0.1", help = "The address to listen on.")]
+        addr: IpAddr,
+        #[arg(long, default_value = "3910", help = "The port to listen on.")]
+        port: u16,
+        #[arg(short = 'd', long, help = "The directory to store the batch data.")]
+        data_dir: PathBuf,
+    },
     #[command(about = "Reverts unverified batches.")]
     RevertBatch {
         #[arg(help = "ID of the batch to revert to")]
@@ -196,11 +248,28 @@ impl Command {
                 l1_beacon_rpc,
                 contract_address,
                 data_dir,
+                validium_data_availability,
+                da_committee_urls,
             } => {
                 create_dir_all(data_dir.clone())?;
 
                 let eth_client = EthClient::new(l1_eth_rpc.as_str())?;
-                let beacon_client = BeaconClient::new(l1_beacon_rpc);
+                let data_availability: Box<dyn DataAvailability> = match validium_data_availability
+                {
+                    None => {
+                        let beacon_client =
+                            BeaconClient::new(l1_beacon_rpc.ok_or_eyre("A beacon node is needed")?);
+                        // The batch size limit is only used to publish
+                        Box::new(BlobsDataAvailability::new(0).with_beacon_client(beacon_client))
+                    }
+                    Some(ValidiumDataAvailability::Calldata) => Box::new(CalldataDataAvailability),
+                    Some(ValidiumDataAvailability::Committee) => {
+                        Box::new(CommitteeDataAvailability::new(da_committee_urls, 0, 0))
+                    }
+                    Some(ValidiumDataAvailability::None) => {
+                        eyre::bail!("The validium doesn't publish the data of its batches")
+                    }
+                };
 
                 // Keep delay for finality
                 let mut current_block = U256::zero();
@@ -227,50 +296,22 @@ impl Command {
                         )
                         .await?;
 
-                    if !logs.is_empty() {
-                        // Get parent beacon block root hash from block
-                        let block = eth_client
-                            .get_block_by_number(BlockIdentifier::Number(current_block.as_u64()))
-                            .await?;
-                        let parent_beacon_hash = block
-                            .header
-                            .parent_beacon_block_root
-                            .ok_or_eyre("Unknown parent beacon root")?;
-
-                        // Get block slot from parent beacon block
-                        let parent_beacon_block =
-                            beacon_client.get_block_by_hash(parent_beacon_hash).await?;
-                        let target_slot = parent_beacon_block.message.slot + 1;
-
-                        // Get versioned hashes from transactions
-                        let mut l2_blob_hashes = vec![];
-                        for log in logs {
-                            let tx = eth_client
-                                .get_transaction_by_hash(log.transaction_hash)
-                                .await?
-                                .ok_or_eyre(format!(
-                                    "Transaction {:#x} not found",
-                                    log.transaction_hash
-                                ))?;
-                            l2_blob_hashes.extend(tx.blob_versioned_hashes.ok_or_eyre(format!(
-                                "Blobs not found in transaction {:#x}",
+                    for log in logs {
+                        let tx = eth_client
+                            .get_transaction_by_hash(log.transaction_hash)
+                            .await?
+                            .ok_or_eyre(format!(
+                                "Transaction {:#x} not found",
                                 log.transaction_hash
-                            ))?);
-                        }
+                            ))?;
+                        let batch_number = batch_number_from_calldata(&tx.data)?;
 
-                        // Get blobs from block's slot and only keep L2 commitment's blobs
-                        for blob in beacon_client
-                            .get_blobs_by_slot(target_slot)
-                            .await?
-                            .into_iter()
-                            .filter(|blob| l2_blob_hashes.contains(&blob.versioned_hash()))
-                        {
-                            let blob_path =
-                                data_dir.join(format!("{target_slot}-{}.blob", blob.index));
-                            std::fs::write(blob_path, blob.blob)?;
-                        }
-
-                        println!("Saved blobs for slot {target_slot}");
+                        let data = data_availability
+                            .retrieve(&eth_client, log.transaction_hash)
+                            .await?;
+                        std::fs::write(data_dir.join(format!("{batch_number}.batch")), data)?;
+
+                        println!("Saved data of batch {batch_number}");
                     }
 
                     current_block += U256::one();
@@ -333,23 +374,32 @@ impl Command {
                 let mut last_block_number = 0;
                 let mut new_canonical_blocks = vec![];
 
-                // Iterate over each blob
-                let files: Vec<std::fs::DirEntry> = read_dir(blobs_dir)?.try_collect()?;
-                for (file_number, file) in files
-                    .into_iter()
-                    .sorted_by_key(|f| f.file_name())
-                    .enumerate()
-                {
-                    let batch_number = file_number as u64 + 1;
-                    let blob = std::fs::read(file.path())?;
+                // Read the data of the batches in the order they were committed
+                let mut batch_files = Vec::new();
+                for file in read_dir(blobs_dir)? {
+                    let path = file?.path();
+                    if path
+                        .extension()
+                        .is_some_and(|extension| extension == "batch")
+                    {
+                        let batch_number: u64 = path
+                            .file_stem()
+                            .and_then(|stem| stem.to_str())
+                            .ok_or_eyre("Invalid batch file name")?
+                            .parse()?;
+                        batch_files.push((batch_number, path));
+                    }
+                }
 
-                    if blob.len() != BYTES_PER_BLOB {
-                        panic!("Invalid blob size");
+                let mut expected_batch_number = 0;
+                for (batch_number, path) in batch_files.into_iter().sorted() {
+                    expected_batch_number += 1;
+                    if batch_number != expected_batch_number {
+                        eyre::bail!("Missing data of batch {expected_batch_number}");
                     }
 
-                    // Decode state diff from blob
-                    let blob = bytes_from_blob(blob.into());
-                    let state_diff = StateDiff::decode(&blob)?;
+                    // Decode state diff from the batch data
+                    let state_diff = decode_state_diff(&std::fs::read(path)?)?;
 
                     // Apply all account updates to trie
                     let account_updates = state_diff.to_account_updates(&new_trie)?;
@@ -415,7 +465,7 @@ impl Command {
                         .await?;
                     new_canonical_blocks.push((state_diff.last_header.number, new_block_hash));
                     println!(
-                        "Stored last block of blob. Block {}. State root {}",
+                        "Stored last block of batch {batch_number}. Block {}. State root {}",
                         new_block.number, new_block.state_root
                     );
 
@@ -441,7 +491,7 @@ impl Command {
                         .unwrap();
                 }
                 let Some((last_number, last_hash)) = new_canonical_blocks.pop() else {
-                    return Err(eyre::eyre!("No blocks found in blobs directory"));
+                    return Err(eyre::eyre!("No batches found in the data directory"));
                 };
                 store
                     .forkchoice_update(
@@ -453,6 +503,22 @@ impl Command {
                     )
                     .await?;
             }
+            Command::DAServer {
+                private_key,
+                sequencer_address,
+                addr,
+                port,
+                data_dir,
+            } => {
+                let signer = LocalSigner::new(private_key).into();
+                start_da_server(
+                    SocketAddr::new(addr, port),
+                    signer,
+                    sequencer_address,
+                    data_dir,
+                )
+                .await?;
+            }
             Command::RevertBatch {
                 batch,
                 contract_address,
@@ -490,7 +556,7 @@ impl Command {
                     .unwrap_or(0);
 
                 let genesis = network.get_genesis()?;
-                let store = init_store(&data_dir, genesis).await;
+                let store = init_store(&data_dir, genesis, false).await;
 
                 rollup_store.revert_to_batch(batch).await?;
 
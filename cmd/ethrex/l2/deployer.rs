@@ -35,7 +35,10 @@ use ethrex_common::H160;
 use hex::FromHexError;
 use secp256k1::SecretKey;
 
-use crate::networks::{LOCAL_DEVNET_GENESIS_CONTENTS, LOCAL_DEVNETL2_GENESIS_CONTENTS};
+use crate::{
+    l2::options::ValidiumDataAvailability,
+    networks::{LOCAL_DEVNET_GENESIS_CONTENTS, LOCAL_DEVNETL2_GENESIS_CONTENTS},
+};
 
 #[derive(Parser)]
 pub struct DeployerOptions {
@@ -243,6 +246,35 @@ pub struct DeployerOptions {
         help = "If true, L2 will run on validium mode as opposed to the default rollup mode, meaning it will not publish state diffs to the L1."
     )]
     pub validium: bool,
+    #[arg(
+        long,
+        default_value = "none",
+        value_name = "MODE",
+        value_enum,
+        env = "ETHREX_L2_VALIDIUM_DATA_AVAILABILITY",
+        help_heading = "Deployer options",
+        help = "Where the validium publishes the data of its batches. Only used in validium mode."
+    )]
+    pub validium_data_availability: ValidiumDataAvailability,
+    #[arg(
+        long,
+        value_name = "ADDRESSES",
+        value_delimiter = ',',
+        num_args = 1..,
+        env = "ETHREX_DEPLOYER_DA_COMMITTEE",
+        help_heading = "Deployer options",
+        help = "Addresses of the members of the data availability committee, in committee mode."
+    )]
+    pub da_committee: Vec<Address>,
+    #[arg(
+        long,
+        default_value = "1",
+        value_name = "UINT64",
+        env = "ETHREX_DEPLOYER_DA_COMMITTEE_THRESHOLD",
+        help_heading = "Deployer options",
+        help = "Number of committee members that must attest the data of a batch, in committee mode."
+    )]
+    pub da_committee_threshold: u64,
     #[arg(
         long,
         value_name = "ADDRESS",
@@ -313,6 +345,14 @@ pub struct DeployerOptions {
         help = "Deadline in seconds for the sequencer to process a privileged transaction."
     )]
     pub inclusion_max_wait: u64,
+    #[arg(
+        long,
+        value_name = "ADDRESS",
+        env = "ETHREX_DEPLOYER_NATIVE_TOKEN",
+        help_heading = "Deployer options",
+        help = "Address of the L1 ERC20 used as the native gas token of the L2. If not set, the native token is ETH."
+    )]
+    pub native_token: Option<Address>,
     #[arg(
         long,
         default_value = "false",
@@ -378,6 +418,9 @@ impl Default for DeployerOptions {
             ]),
             randomize_contract_deployment: false,
             validium: false,
+            validium_data_availability: ValidiumDataAvailability::None,
+            da_committee: Vec::new(),
+            da_committee_threshold: 1,
             // 0x4417092b70a3e5f10dc504d0947dd256b965fc62
             // Private Key: 0x941e103320615d394a55708be13e45994c7d93b932b064dbcb2b511fe3254e2e
             // (also found on fixtures/keys/private_keys_l1.txt)
@@ -402,6 +445,7 @@ impl Default for DeployerOptions {
             deploy_based_contracts: false,
             sequencer_registry_owner: None,
             inclusion_max_wait: 3000,
+            native_token: None,
             use_compiled_genesis: true,
         }
     }
@@ -481,13 +525,14 @@ const SP1_VERIFIER_BYTECODE: &[u8] = include_bytes!(concat!(
     "/contracts/solc_out/SP1Verifier.bytecode"
 ));
 
-const INITIALIZE_ON_CHAIN_PROPOSER_SIGNATURE_BASED: &str = "initialize(bool,address,address,address,address,address,bytes32,bytes32,bytes32,address,uint256)";
-const INITIALIZE_ON_CHAIN_PROPOSER_SIGNATURE: &str = "initialize(bool,address,address,address,address,address,bytes32,bytes32,bytes32,address[],uint256)";
+const INITIALIZE_ON_CHAIN_PROPOSER_SIGNATURE_BASED: &str = "initialize(bool,address,address,address,address,address,bytes32,bytes32,bytes32,address,uint256,bytes32)";
+const INITIALIZE_ON_CHAIN_PROPOSER_SIGNATURE: &str = "initialize(bool,address,address,address,address,address,bytes32,bytes32,bytes32,address[],uint256,bytes32)";
 
 const INITIALIZE_BRIDGE_ADDRESS_SIGNATURE: &str = "initializeBridgeAddress(address)";
+const SET_DATA_AVAILABILITY_SIGNATURE: &str = "setDataAvailability(uint8,address[],uint256)";
 const TRANSFER_OWNERSHIP_SIGNATURE: &str = "transferOwnership(address)";
 const ACCEPT_OWNERSHIP_SIGNATURE: &str = "acceptOwnership()";
-const BRIDGE_INITIALIZER_SIGNATURE: &str = "initialize(address,address,uint256)";
+const BRIDGE_INITIALIZER_SIGNATURE: &str = "initialize(address,address,uint256,address)";
 
 #[derive(Clone, Copy)]
 pub struct ContractAddresses {
@@ -520,7 +565,11 @@ pub async fn deploy_l1_contracts(
 
     initialize_contracts(contract_addresses, &eth_client, &opts, &signer).await?;
 
-    if opts.deposit_rich {
+    if opts.deposit_rich && opts.native_token.is_some() {
+        warn!(
+            "Skipping deposits of the rich accounts, the bridge doesn't accept ETH with a native token"
+        );
+    } else if opts.deposit_rich {
         let _ = make_deposits(contract_addresses.bridge_address, &eth_client, &opts)
             .await
             .inspect_err(|err| {
@@ -746,6 +795,12 @@ async fn initialize_contracts(
     let risc0_vk = read_vk(&opts.risc0_vk_path);
 
     let deployer_address = get_address_from_secret_key(&opts.private_key)?;
+    // Same as the one the committer sends with each batch
+    let fee_config_hash = genesis
+        .config
+        .fee_config
+        .map(|fee_config| fee_config.hash())
+        .unwrap_or_default();
 
     info!("Initializing OnChainProposer");
 
@@ -763,6 +818,7 @@ async fn initialize_contracts(
             Value::FixedBytes(genesis.compute_state_root().0.to_vec().into()),
             Value::Address(contract_addresses.sequencer_registry_address),
             Value::Uint(genesis.config.chain_id.into()),
+            Value::FixedBytes(fee_config_hash.0.to_vec().into()),
         ];
 
         trace!(calldata_values = ?calldata_values, "OnChainProposer initialization calldata values");
@@ -820,6 +876,7 @@ async fn initialize_contracts(
                 Value::Address(opts.proof_sender_l1_address),
             ]),
             Value::Uint(genesis.config.chain_id.into()),
+            Value::FixedBytes(fee_config_hash.0.to_vec().into()),
         ];
         trace!(calldata_values = ?calldata_values, "OnChainProposer initialization calldata values");
         let on_chain_proposer_initialization_calldata =
@@ -854,6 +911,43 @@ async fn initialize_contracts(
         "OnChainProposer bridge address initialized"
     );
 
+    // The based OnChainProposer only supports blobs
+    if opts.validium
+        && !opts.deploy_based_contracts
+        && opts.validium_data_availability != ValidiumDataAvailability::None
+    {
+        // Values of the DataAvailabilityMode enum of the OnChainProposer
+        let (mode, committee, threshold) = match opts.validium_data_availability {
+            ValidiumDataAvailability::Committee => {
+                (2_u8, opts.da_committee.clone(), opts.da_committee_threshold)
+            }
+            ValidiumDataAvailability::Calldata | ValidiumDataAvailability::None => {
+                (1_u8, Vec::new(), 0)
+            }
+        };
+        let set_data_availability_tx_hash = {
+            let calldata_values = vec![
+                Value::Uint(mode.into()),
+                Value::Array(committee.into_iter().map(Value::Address).collect()),
+                Value::Uint(threshold.into()),
+            ];
+            let set_data_availability_calldata =
+                encode_calldata(SET_DATA_AVAILABILITY_SIGNATURE, &calldata_values)?;
+
+            initialize_contract(
+                contract_addresses.on_chain_proposer_address,
+                set_data_availability_calldata,
+                initializer,
+                eth_client,
+            )
+            .await?
+        };
+        info!(
+            tx_hash = %format!("{set_data_availability_tx_hash:#x}"),
+            "OnChainProposer data availability set"
+        );
+    }
+
     if opts.on_chain_proposer_owner != initializer.address() {
         let transfer_ownership_tx_hash = {
             let owener_transfer_calldata = encode_calldata(
@@ -906,6 +1000,7 @@ async fn initialize_contracts(
             Value::Address(opts.bridge_owner),
             Value::Address(contract_addresses.on_chain_proposer_address),
             Value::Uint(opts.inclusion_max_wait.into()),
+            Value::Address(opts.native_token.unwrap_or_default()),
         ];
         let bridge_initialization_calldata =
             encode_calldata(BRIDGE_INITIALIZER_SIGNATURE, &calldata_values)?;
@@ -6,16 +6,17 @@ use std::time::Duration;
 use ethrex_blockchain::{Blockchain, BlockchainType};
 use ethrex_common::Address;
 use ethrex_l2::SequencerConfig;
+use ethrex_l2_rpc::signer::Signer;
 use ethrex_p2p::kademlia::KademliaTable;
 use ethrex_p2p::network::peer_table;
 use ethrex_p2p::peer_handler::PeerHandler;
 use ethrex_p2p::rlpx::l2::l2_connection::P2PBasedContext;
+use ethrex_p2p::rlpx::l2::lead_sequencers::LeadSequencers;
 use ethrex_p2p::sync_manager::SyncManager;
 use ethrex_p2p::types::{Node, NodeRecord};
 use ethrex_storage::Store;
 use ethrex_storage_rollup::{EngineTypeRollup, StoreRollup};
 use ethrex_vm::EvmEngine;
-use secp256k1::SecretKey;
 use tokio::sync::Mutex;
 use tokio::task::JoinSet;
 use tokio_util::sync::CancellationToken;
@@ -57,6 +58,7 @@ async fn init_rpc_api(
         cancel_token,
         blockchain.clone(),
         store.clone(),
+        opts.sync_skeleton,
     )
     .await;
 
@@ -147,8 +149,11 @@ pub fn init_tracing(opts: &L2Options) {
 }
 
 pub async fn init_l2(opts: L2Options) -> eyre::Result<()> {
-    if opts.node_opts.evm == EvmEngine::REVM {
-        panic!("L2 Doesn't support REVM, use LEVM instead.");
+    if opts.node_opts.evm != EvmEngine::LEVM {
+        panic!(
+            "L2 Doesn't support {}, use LEVM instead.",
+            opts.node_opts.evm
+        );
     }
 
     let data_dir = set_datadir(&opts.node_opts.datadir);
@@ -157,10 +162,17 @@ pub async fn init_l2(opts: L2Options) -> eyre::Result<()> {
     let network = get_network(&opts.node_opts);
 
     let genesis = network.get_genesis()?;
-    let store = init_store(&data_dir, genesis).await;
+    let store = init_store(&data_dir, genesis, opts.node_opts.binary_trie).await;
     let rollup_store = init_rollup_store(&rollup_store_dir).await;
 
-    let blockchain = init_blockchain(opts.node_opts.evm, store.clone(), BlockchainType::L2);
+    let blockchain = init_blockchain(
+        opts.node_opts.evm,
+        opts.node_opts.evm_parallel,
+        opts.node_opts.metrics_opcodes,
+        opts.node_opts.witness_retention,
+        store.clone(),
+        BlockchainType::L2,
+    );
 
     let signer = get_signer(&data_dir);
 
@@ -203,12 +215,28 @@ pub async fn init_l2(opts: L2Options) -> eyre::Result<()> {
         error!("{err}");
     })?;
     let cancellation_token = CancellationToken::new();
+    let lead_sequencers = LeadSequencers::new();
 
     // TODO: This should be handled differently, the current problem
     // with using opts.node_opts.p2p_enabled is that with the removal
     // of the l2 feature flag, p2p_enabled is set to true by default
     // prioritizing the L1 UX.
-    if l2_sequencer_cfg.based.enabled {
+    if l2_sequencer_cfg.based.enabled || l2_sequencer_cfg.ha.enabled {
+        // Peers only accept the blocks and batches signed by the lead sequencer, so they
+        // are signed with the committer key, which is registered in the SequencerRegistry.
+        // The P2P crate can't sign with a remote signer because of cyclic dependencies.
+        let Signer::Local(committer_signer) = &l2_sequencer_cfg.l1_committer.signer else {
+            return Err(eyre::eyre!(
+                "Based and HA modes need a local committer signer to sign the P2P messages"
+            ));
+        };
+        // The nodes of a HA cluster share the committer key, the only one that
+        // sequences the chain
+        if l2_sequencer_cfg.ha.enabled {
+            lead_sequencers
+                .set_lead(0, l2_sequencer_cfg.l1_committer.signer.address())
+                .await;
+        }
         init_network(
             &opts.node_opts,
             &network,
@@ -222,19 +250,8 @@ pub async fn init_l2(opts: L2Options) -> eyre::Result<()> {
             blockchain.clone(),
             Some(P2PBasedContext {
                 store_rollup: rollup_store.clone(),
-                // TODO: The Web3Signer refactor introduced a limitation where the committer key cannot be accessed directly because the signer could be either Local or Remote.
-                // The Signer enum cannot be used in the P2PBasedContext struct due to cyclic dependencies between the l2-rpc and p2p crates.
-                // As a temporary solution, a dummy committer key is used until a proper mechanism to utilize the Signer enum is implemented.
-                // This should be replaced with the Signer enum once the refactor is complete.
-                committer_key: Arc::new(
-                    SecretKey::from_slice(
-                        &hex::decode(
-                            "385c546456b6a603a1cfcaa9ec9494ba4832da08dd6bcf4de9a71e4a01b74924",
-                        )
-                        .expect("Invalid committer key"),
-                    )
-                    .expect("Failed to create committer key"),
-                ),
+                committer_key: Arc::new(committer_signer.private_key),
+                lead_sequencers: lead_sequencers.clone(),
             }),
         )
         .await;
@@ -247,6 +264,7 @@ pub async fn init_l2(opts: L2Options) -> eyre::Result<()> {
         rollup_store,
         blockchain,
         l2_sequencer_cfg,
+        lead_sequencers,
         cancellation_token.clone(),
         #[cfg(feature = "metrics")]
         format!(
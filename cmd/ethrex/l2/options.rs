@@ -2,11 +2,12 @@ use crate::{
     cli::Options as NodeOptions,
     utils::{self},
 };
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use ethrex_common::Address;
 use ethrex_l2::{
-    BasedConfig, BlockFetcherConfig, BlockProducerConfig, CommitterConfig, EthConfig,
-    L1WatcherConfig, ProofCoordinatorConfig, SequencerConfig, StateUpdaterConfig,
+    BasedConfig, BlockFetcherConfig, BlockProducerConfig, CommitterConfig, DataAvailabilityConfig,
+    EthConfig, HAConfig, L1FeeConfig, L1WatcherConfig, ProofCoordinatorConfig, SequencerConfig,
+    StateUpdaterConfig,
     sequencer::{
         configs::{AlignedConfig, MonitorConfig},
         utils::resolve_aligned_network,
@@ -14,8 +15,9 @@ use ethrex_l2::{
 };
 use ethrex_l2_rpc::signer::{LocalSigner, RemoteSigner, Signer};
 use ethrex_prover_lib::{backends::Backend, config::ProverConfig};
-use ethrex_rpc::clients::eth::{
-    BACKOFF_FACTOR, MAX_NUMBER_OF_RETRIES, MAX_RETRY_DELAY, MIN_RETRY_DELAY,
+use ethrex_rpc::{
+    clients::eth::{BACKOFF_FACTOR, MAX_NUMBER_OF_RETRIES, MAX_RETRY_DELAY, MIN_RETRY_DELAY},
+    types::block_identifier::BlockTag,
 };
 use reqwest::Url;
 use secp256k1::{PublicKey, SecretKey};
@@ -25,6 +27,9 @@ use std::{
 };
 use tracing::Level;
 
+/// Block timestamps are in seconds, so blocks can't be produced faster than this.
+const MIN_BLOCK_TIME_MS: u64 = 1000;
+
 #[derive(Parser, Debug)]
 #[group(id = "L2Options")]
 pub struct Options {
@@ -73,9 +78,13 @@ pub struct SequencerOptions {
     #[command(flatten)]
     pub based_opts: BasedOptions,
     #[command(flatten)]
+    pub ha_opts: HAOptions,
+    #[command(flatten)]
     pub aligned_opts: AlignedOptions,
     #[command(flatten)]
     pub monitor_opts: MonitorOptions,
+    #[command(flatten)]
+    pub l1_fee_opts: L1FeeOptions,
     #[arg(
         long = "validium",
         default_value = "false",
@@ -85,6 +94,35 @@ pub struct SequencerOptions {
         long_help = "If true, L2 will run on validium mode as opposed to the default rollup mode, meaning it will not publish state diffs to the L1."
     )]
     pub validium: bool,
+    #[arg(
+        long = "validium.data-availability",
+        default_value = "none",
+        value_name = "MODE",
+        value_enum,
+        env = "ETHREX_L2_VALIDIUM_DATA_AVAILABILITY",
+        help_heading = "L2 options",
+        help = "Where a validium publishes the data of its batches. It must match the mode set in the OnChainProposer."
+    )]
+    pub validium_data_availability: ValidiumDataAvailability,
+    #[arg(
+        long = "validium.da-committee-urls",
+        value_name = "URLs",
+        value_delimiter = ',',
+        num_args = 1..,
+        env = "ETHREX_L2_VALIDIUM_DA_COMMITTEE_URLS",
+        help_heading = "L2 options",
+        help = "URLs of the servers of the data availability committee."
+    )]
+    pub da_committee_urls: Vec<Url>,
+    #[arg(
+        long = "validium.da-committee-threshold",
+        default_value = "1",
+        value_name = "UINT64",
+        env = "ETHREX_L2_VALIDIUM_DA_COMMITTEE_THRESHOLD",
+        help_heading = "L2 options",
+        help = "Number of committee members that must attest the data of a batch."
+    )]
+    pub da_committee_threshold: usize,
     #[clap(
         long,
         default_value = "false",
@@ -103,6 +141,18 @@ pub struct SequencerOptions {
     pub no_monitor: bool,
 }
 
+/// Where a validium publishes the data of its batches, a rollup always publishes it in blobs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum ValidiumDataAvailability {
+    /// The data stays in the sequencer's database
+    #[default]
+    None,
+    /// The data is published in the calldata of the commit transactions
+    Calldata,
+    /// The data is stored by a committee of DA servers, which sign attestations of it
+    Committee,
+}
+
 pub fn parse_signer(
     private_key: Option<SecretKey>,
     url: Option<Url>,
@@ -135,6 +185,22 @@ pub enum SequencerOptionsError {
     NoProofCoorditanorTdxPrivateKey,
     #[error("No bridge address was provided")]
     NoBridgeAddress,
+    #[error("A data availability mode was set, but the L2 isn't a validium")]
+    DataAvailabilityWithoutValidium,
+    #[error("Based sequencers only support blobs as data availability")]
+    UnsupportedBasedDataAvailability,
+    #[error("Invalid data availability committee: {0}")]
+    InvalidDACommittee(String),
+    #[error(
+        "The block time must be at least 1000 ms, as block timestamps are in seconds. Use preconfirmations for faster feedback"
+    )]
+    BlockTimeUnderASecond,
+    #[error("The preconfirmation interval must be greater than 0 and less than the block time")]
+    InvalidPreconfirmationInterval,
+    #[error("Based sequencers don't support high availability mode")]
+    UnsupportedBasedHA,
+    #[error("The election timeout must be at least 4 times the heartbeat interval")]
+    InvalidElectionTimeout,
 }
 
 impl TryFrom<SequencerOptions> for SequencerConfig {
@@ -153,6 +219,56 @@ impl TryFrom<SequencerOptions> for SequencerConfig {
             opts.proof_coordinator_opts.remote_signer_public_key,
         )?;
 
+        let data_availability = match (opts.validium, opts.validium_data_availability) {
+            (false, ValidiumDataAvailability::None) => DataAvailabilityConfig::Blobs,
+            (false, _) => return Err(SequencerOptionsError::DataAvailabilityWithoutValidium),
+            (true, ValidiumDataAvailability::None) => DataAvailabilityConfig::None,
+            (true, _) if opts.based => {
+                return Err(SequencerOptionsError::UnsupportedBasedDataAvailability);
+            }
+            (true, ValidiumDataAvailability::Calldata) => DataAvailabilityConfig::Calldata,
+            (true, ValidiumDataAvailability::Committee) => {
+                if opts.da_committee_threshold == 0
+                    || opts.da_committee_threshold > opts.da_committee_urls.len()
+                {
+                    return Err(SequencerOptionsError::InvalidDACommittee(format!(
+                        "threshold {} with {} members",
+                        opts.da_committee_threshold,
+                        opts.da_committee_urls.len()
+                    )));
+                }
+                DataAvailabilityConfig::Committee {
+                    members: opts.da_committee_urls,
+                    threshold: opts.da_committee_threshold,
+                }
+            }
+        };
+
+        // Blocks produced in the same second would have the same timestamp, only the
+        // preconfirmations of a block can be announced more often
+        if opts.block_producer_opts.block_time_ms < MIN_BLOCK_TIME_MS {
+            return Err(SequencerOptionsError::BlockTimeUnderASecond);
+        }
+
+        if let Some(interval) = opts.block_producer_opts.preconfirmation_interval_ms {
+            if interval == 0 || interval >= opts.block_producer_opts.block_time_ms {
+                return Err(SequencerOptionsError::InvalidPreconfirmationInterval);
+            }
+        }
+
+        if opts.ha_opts.ha {
+            if opts.based {
+                return Err(SequencerOptionsError::UnsupportedBasedHA);
+            }
+            // The leader steps down before its lease (half the election timeout) is
+            // renewed by another leader
+            if opts.ha_opts.election_timeout_ms
+                < opts.ha_opts.heartbeat_interval_ms.saturating_mul(4)
+            {
+                return Err(SequencerOptionsError::InvalidElectionTimeout);
+            }
+        }
+
         Ok(Self {
             block_producer: BlockProducerConfig {
                 block_time_ms: opts.block_producer_opts.block_time_ms,
@@ -161,6 +277,7 @@ impl TryFrom<SequencerOptions> for SequencerConfig {
                     .coinbase_address
                     .ok_or(SequencerOptionsError::NoCoinbaseAddress)?,
                 elasticity_multiplier: opts.block_producer_opts.elasticity_multiplier,
+                preconfirmation_interval_ms: opts.block_producer_opts.preconfirmation_interval_ms,
             },
             l1_committer: CommitterConfig {
                 on_chain_proposer_address: opts
@@ -169,6 +286,8 @@ impl TryFrom<SequencerOptions> for SequencerConfig {
                     .ok_or(SequencerOptionsError::NoOnChainProposerAddress)?,
                 commit_time_ms: opts.committer_opts.commit_time_ms,
                 arbitrary_base_blob_gas_price: opts.committer_opts.arbitrary_base_blob_gas_price,
+                max_blobs_per_batch: opts.committer_opts.max_blobs_per_batch,
+                data_availability,
                 signer: committer_signer,
                 validium: opts.validium,
             },
@@ -191,6 +310,7 @@ impl TryFrom<SequencerOptions> for SequencerConfig {
                 check_interval_ms: opts.watcher_opts.watch_interval_ms,
                 max_block_step: opts.watcher_opts.max_block_step.into(),
                 watcher_block_delay: opts.watcher_opts.watcher_block_delay,
+                block_tag: opts.watcher_opts.watcher_block_tag.clone(),
             },
             proof_coordinator: ProofCoordinatorConfig {
                 listen_ip: opts.proof_coordinator_opts.listen_ip,
@@ -202,6 +322,7 @@ impl TryFrom<SequencerOptions> for SequencerConfig {
                     .proof_coordinator_tdx_private_key
                     .ok_or(SequencerOptionsError::NoProofCoorditanorTdxPrivateKey)?,
                 validium: opts.validium,
+                prover_timeout_ms: opts.proof_coordinator_opts.prover_timeout_ms,
             },
             based: BasedConfig {
                 enabled: opts.based,
@@ -218,6 +339,15 @@ impl TryFrom<SequencerOptions> for SequencerConfig {
                     fetch_block_step: opts.based_opts.block_fetcher.fetch_block_step,
                 },
             },
+            ha: HAConfig {
+                enabled: opts.ha_opts.ha,
+                node_id: opts.ha_opts.node_id,
+                listen_ip: opts.ha_opts.listen_ip,
+                listen_port: opts.ha_opts.listen_port,
+                peers: opts.ha_opts.peers,
+                heartbeat_interval_ms: opts.ha_opts.heartbeat_interval_ms,
+                election_timeout_ms: opts.ha_opts.election_timeout_ms,
+            },
             aligned: AlignedConfig {
                 aligned_mode: opts.aligned_opts.aligned,
                 aligned_verifier_interval_ms: opts.aligned_opts.aligned_verifier_interval_ms,
@@ -233,6 +363,15 @@ impl TryFrom<SequencerOptions> for SequencerConfig {
                 tick_rate: opts.monitor_opts.tick_rate,
                 batch_widget_height: opts.monitor_opts.batch_widget_height,
             },
+            l1_fee: L1FeeConfig {
+                signer: opts
+                    .l1_fee_opts
+                    .coinbase_private_key
+                    .map(|private_key| LocalSigner::new(private_key).into()),
+                fee_vault: opts.l1_fee_opts.fee_vault_address,
+                fee_scalar: opts.l1_fee_opts.fee_scalar,
+                update_interval_ms: opts.l1_fee_opts.update_interval_ms,
+            },
         })
     }
 }
@@ -348,6 +487,17 @@ pub struct WatcherOptions {
         help_heading = "L1 Watcher options"
     )]
     pub watcher_block_delay: u64,
+    #[arg(
+        long = "watcher.block-tag",
+        default_value = "latest",
+        value_name = "BLOCK_TAG",
+        value_parser = utils::parse_block_tag,
+        env = "ETHREX_WATCHER_BLOCK_TAG",
+        help = "L1 block the watcher follows, the block delay is counted from it.",
+        long_help = "Can be either \"latest\", \"safe\" or \"finalized\" with \"latest\" as default value. The block delay is counted from the block with the given tag.",
+        help_heading = "L1 Watcher options"
+    )]
+    pub watcher_block_tag: BlockTag,
 }
 
 impl Default for WatcherOptions {
@@ -357,6 +507,7 @@ impl Default for WatcherOptions {
             watch_interval_ms: 1000,
             max_block_step: 5000,
             watcher_block_delay: 0,
+            watcher_block_tag: BlockTag::Latest,
         }
     }
 }
@@ -368,7 +519,7 @@ pub struct BlockProducerOptions {
         default_value = "5000",
         value_name = "UINT64",
         env = "ETHREX_BLOCK_PRODUCER_BLOCK_TIME",
-        help = "How often does the sequencer produce new blocks to the L1 in milliseconds.",
+        help = "How often does the sequencer produce new blocks to the L1 in milliseconds. It must be at least 1000, as block timestamps are in seconds.",
         help_heading = "Block producer options"
     )]
     pub block_time_ms: u64,
@@ -388,6 +539,14 @@ pub struct BlockProducerOptions {
         help_heading = "Proposer options"
     )]
     pub elasticity_multiplier: u64,
+    #[arg(
+        long = "block-producer.preconfirmation-interval",
+        value_name = "UINT64",
+        env = "ETHREX_BLOCK_PRODUCER_PRECONFIRMATION_INTERVAL",
+        help = "How often, in milliseconds, the sequencer executes the pending transactions and announces them as preconfirmations while it builds a block. Preconfirmations are disabled if it's not set.",
+        help_heading = "Block producer options"
+    )]
+    pub preconfirmation_interval_ms: Option<u64>,
 }
 
 impl Default for BlockProducerOptions {
@@ -400,6 +559,7 @@ impl Default for BlockProducerOptions {
                     .unwrap(),
             ),
             elasticity_multiplier: 2,
+            preconfirmation_interval_ms: None,
         }
     }
 }
@@ -464,6 +624,15 @@ pub struct CommitterOptions {
         help_heading = "L1 Committer options"
     )]
     pub arbitrary_base_blob_gas_price: u64,
+    #[arg(
+        long = "committer.max-blobs-per-batch",
+        default_value = "6",
+        value_name = "UINT64",
+        env = "ETHREX_COMMITTER_MAX_BLOBS_PER_BATCH",
+        help_heading = "L1 Committer options",
+        help = "Maximum number of blobs the data of a batch can span. It can't exceed the blobs per transaction allowed by the L1."
+    )]
+    pub max_blobs_per_batch: usize,
 }
 
 impl Default for CommitterOptions {
@@ -476,6 +645,7 @@ impl Default for CommitterOptions {
             on_chain_proposer_address: None,
             commit_time_ms: 60000,
             arbitrary_base_blob_gas_price: 1_000_000_000,
+            max_blobs_per_batch: 6,
             committer_remote_signer_url: None,
             committer_remote_signer_public_key: None,
         }
@@ -553,6 +723,15 @@ pub struct ProofCoordinatorOptions {
         help_heading = "Proof coordinator options"
     )]
     pub proof_send_interval_ms: u64,
+    #[arg(
+        long = "proof-coordinator.prover-timeout",
+        default_value = "600000",
+        value_name = "UINT64",
+        env = "ETHREX_PROOF_COORDINATOR_PROVER_TIMEOUT",
+        help = "Time in milliseconds a prover has to submit the proof of a batch before it's assigned to another prover.",
+        help_heading = "Proof coordinator options"
+    )]
+    pub prover_timeout_ms: u64,
 }
 
 impl Default for ProofCoordinatorOptions {
@@ -568,6 +747,7 @@ impl Default for ProofCoordinatorOptions {
             listen_ip: IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)),
             listen_port: 3900,
             proof_send_interval_ms: 5000,
+            prover_timeout_ms: 600000,
             proof_coordinator_tdx_private_key: Some(
                 utils::parse_private_key(
                     "0x39725efee3fb28614de3bacaffe4cc4bd8c436257e2c8bb887c4b5c4be45e76d",
@@ -716,6 +896,137 @@ impl Default for BlockFetcherOptions {
     }
 }
 
+#[derive(Parser, Debug)]
+pub struct HAOptions {
+    #[arg(
+        long = "ha",
+        default_value = "false",
+        value_name = "BOOLEAN",
+        env = "ETHREX_HA",
+        help_heading = "High availability options",
+        long_help = "Run the sequencer in a high availability cluster. The nodes elect a leader that produces and commits the blocks, the others follow it over P2P and take over if it fails. All of them must use the same committer key."
+    )]
+    pub ha: bool,
+    #[arg(
+        long = "ha.node-id",
+        default_value = "0",
+        value_name = "UINT64",
+        env = "ETHREX_HA_NODE_ID",
+        help_heading = "High availability options",
+        help = "Identifier of the node, unique in the cluster."
+    )]
+    pub node_id: u64,
+    #[arg(
+        long = "ha.addr",
+        default_value = "127.0.0.1",
+        value_name = "IP_ADDRESS",
+        env = "ETHREX_HA_LISTEN_ADDRESS",
+        help_heading = "High availability options",
+        help = "Address the leader elector listens on for the other nodes of the cluster."
+    )]
+    pub listen_ip: IpAddr,
+    #[arg(
+        long = "ha.port",
+        default_value = "3910",
+        value_name = "UINT16",
+        env = "ETHREX_HA_LISTEN_PORT",
+        help_heading = "High availability options"
+    )]
+    pub listen_port: u16,
+    #[arg(
+        long = "ha.peers",
+        value_name = "URLs",
+        value_delimiter = ',',
+        num_args = 1..,
+        env = "ETHREX_HA_PEERS",
+        help_heading = "High availability options",
+        help = "URLs of the leader electors of the other nodes of the cluster."
+    )]
+    pub peers: Vec<Url>,
+    #[arg(
+        long = "ha.heartbeat-interval",
+        default_value = "500",
+        value_name = "UINT64",
+        env = "ETHREX_HA_HEARTBEAT_INTERVAL",
+        help_heading = "High availability options",
+        help = "How often the leader renews its lease, in milliseconds."
+    )]
+    pub heartbeat_interval_ms: u64,
+    #[arg(
+        long = "ha.election-timeout",
+        default_value = "3000",
+        value_name = "UINT64",
+        env = "ETHREX_HA_ELECTION_TIMEOUT",
+        help_heading = "High availability options",
+        help = "Time without hearing from the leader after which a node runs for leader, in milliseconds."
+    )]
+    pub election_timeout_ms: u64,
+}
+
+impl Default for HAOptions {
+    fn default() -> Self {
+        Self {
+            ha: false,
+            node_id: 0,
+            listen_ip: IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)),
+            listen_port: 3910,
+            peers: Vec::new(),
+            heartbeat_interval_ms: 500,
+            election_timeout_ms: 3000,
+        }
+    }
+}
+
+#[derive(Parser, Debug)]
+pub struct L1FeeOptions {
+    #[arg(
+        long = "l1-fee.coinbase-private-key",
+        value_name = "PRIVATE_KEY",
+        value_parser = utils::parse_private_key,
+        env = "ETHREX_L1_FEE_COINBASE_PRIVATE_KEY",
+        help_heading = "L1 fee options",
+        long_help = "Private key of the coinbase address, used to update the L1 fee oracle on the L2. If it's not set the oracle isn't updated and transactions don't pay for the L1 data."
+    )]
+    pub coinbase_private_key: Option<SecretKey>,
+    #[arg(
+        long = "l1-fee.vault-address",
+        value_name = "ADDRESS",
+        env = "ETHREX_L1_FEE_VAULT_ADDRESS",
+        help_heading = "L1 fee options",
+        help = "Address receiving the L1 data fees, the coinbase if it's not set."
+    )]
+    pub fee_vault_address: Option<Address>,
+    #[arg(
+        long = "l1-fee.scalar",
+        default_value = "100",
+        value_name = "UINT64",
+        env = "ETHREX_L1_FEE_SCALAR",
+        help_heading = "L1 fee options",
+        help = "Percentage of the L1 blob base fee charged per compressed byte of each transaction."
+    )]
+    pub fee_scalar: u64,
+    #[arg(
+        long = "l1-fee.update-interval",
+        default_value = "12000",
+        value_name = "UINT64",
+        env = "ETHREX_L1_FEE_UPDATE_INTERVAL",
+        help_heading = "L1 fee options",
+        help = "How often the sequencer updates the L1 fee oracle, in milliseconds."
+    )]
+    pub update_interval_ms: u64,
+}
+
+impl Default for L1FeeOptions {
+    fn default() -> Self {
+        Self {
+            coinbase_private_key: None,
+            fee_vault_address: None,
+            fee_scalar: 100,
+            update_interval_ms: 12000,
+        }
+    }
+}
+
 #[derive(Parser, Debug)]
 pub struct MonitorOptions {
     /// time in ms between two ticks.
@@ -8,6 +8,7 @@ use ethrex_p2p::{
     types::{Node, NodeRecord},
 };
 use ethrex_rlp::decode::RLPDecode;
+use ethrex_rpc::types::block_identifier::BlockTag;
 use ethrex_vm::EvmEngine;
 use hex::FromHexError;
 use secp256k1::{PublicKey, SecretKey};
@@ -94,6 +95,27 @@ pub fn parse_sync_mode(s: &str) -> eyre::Result<SyncMode> {
     }
 }
 
+pub fn parse_block_tag(s: &str) -> eyre::Result<BlockTag> {
+    match s {
+        "latest" => Ok(BlockTag::Latest),
+        "safe" => Ok(BlockTag::Safe),
+        "finalized" => Ok(BlockTag::Finalized),
+        other => Err(eyre::eyre!(
+            "Invalid block tag {other:?} expected either latest, safe or finalized",
+        )),
+    }
+}
+
+pub fn parse_ratio(s: &str) -> eyre::Result<f64> {
+    let ratio: f64 = s.parse()?;
+    if !(0.0..=1.0).contains(&ratio) {
+        return Err(eyre::eyre!(
+            "Invalid ratio {ratio}, expected a value between 0 and 1"
+        ));
+    }
+    Ok(ratio)
+}
+
 pub fn parse_socket_addr(addr: &str, port: &str) -> io::Result<SocketAddr> {
     // NOTE: this blocks until hostname can be resolved
     format!("{addr}:{port}")
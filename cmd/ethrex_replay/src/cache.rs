@@ -1,19 +1,13 @@
-use ethrex_common::types::blobs_bundle;
-use ethrex_common::types::{Block, block_execution_witness::ExecutionWitnessResult};
+use ethrex_common::types::{BlobsBundle, Block, block_execution_witness::ExecutionWitnessResult};
 use serde::{Deserialize, Serialize};
-use serde_with::serde_as;
 use std::{
     fs::File,
     io::{BufReader, BufWriter},
 };
 
-#[serde_as]
 #[derive(Serialize, Deserialize)]
 pub struct L2Fields {
-    #[serde_as(as = "[_; 48]")]
-    pub blob_commitment: blobs_bundle::Commitment,
-    #[serde_as(as = "[_; 48]")]
-    pub blob_proof: blobs_bundle::Proof,
+    pub blobs_bundle: BlobsBundle,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -6,12 +6,15 @@ use ethrex_common::{
 use ethrex_prover_lib::backends::Backend;
 use ethrex_rpc::types::block_identifier::BlockTag;
 use ethrex_rpc::{EthClient, types::block_identifier::BlockIdentifier};
+use ethrex_vm::Profile;
 use reqwest::Url;
+use std::path::PathBuf;
 
+use crate::cache::Cache;
 use crate::constants::get_chain_config;
 use crate::fetcher::{get_blockdata, get_rangedata};
 use crate::plot_composition::plot;
-use crate::run::{exec, prove, run_tx};
+use crate::run::{differential, exec, profile, prove, run_tx};
 use crate::{bench::run_and_measure, fetcher::get_batchdata};
 
 pub const VERSION_STRING: &str = env!("CARGO_PKG_VERSION");
@@ -102,6 +105,56 @@ enum SubcommandExecute {
         #[arg(long, required = false)]
         bench: bool,
     },
+    #[command(
+        about = "Execute blocks with both LEVM and REVM, reporting the first transaction in which they diverge."
+    )]
+    Differential {
+        #[arg(help = "Starting block. Uses the latest if not specified.")]
+        start: Option<usize>,
+        #[arg(help = "Ending block, executes a single block if not specified. (Inclusive)")]
+        end: Option<usize>,
+        #[arg(long, env = "RPC_URL", required = true)]
+        rpc_url: Url,
+        #[arg(
+            long,
+            default_value = "mainnet",
+            env = "NETWORK",
+            required = false,
+            help = "Name or ChainID of the network to use"
+        )]
+        network: String,
+        #[arg(
+            long,
+            default_value = "divergence.json",
+            help = "File where the report of the divergence is written"
+        )]
+        output: PathBuf,
+    },
+    #[command(
+        about = "Execute blocks with LEVM, profiling the count, gas and time of each opcode, precompile and contract."
+    )]
+    Profile {
+        #[arg(help = "Starting block. Uses the latest if not specified.")]
+        start: Option<usize>,
+        #[arg(help = "Ending block, executes a single block if not specified. (Inclusive)")]
+        end: Option<usize>,
+        #[arg(long, env = "RPC_URL", required = true)]
+        rpc_url: Url,
+        #[arg(
+            long,
+            default_value = "mainnet",
+            env = "NETWORK",
+            required = false,
+            help = "Name or ChainID of the network to use"
+        )]
+        network: String,
+        #[arg(
+            long,
+            default_value_t = 20,
+            help = "Amount of opcodes, precompiles and contracts to show"
+        )]
+        top: usize,
+    },
 }
 
 impl SubcommandExecute {
@@ -191,6 +244,38 @@ impl SubcommandExecute {
                 };
                 run_and_measure(future, bench).await?;
             }
+            SubcommandExecute::Differential {
+                start,
+                end,
+                rpc_url,
+                network,
+                output,
+            } => {
+                let cache = get_blocks_cache(start, end, rpc_url, &network).await?;
+                match differential(cache).await? {
+                    Some(divergence) => {
+                        std::fs::write(&output, serde_json::to_string_pretty(&divergence)?)?;
+                        println!(
+                            "LEVM and REVM diverged in block {} (transaction index: {:?}), report written to {}",
+                            divergence.block_number,
+                            divergence.tx_index,
+                            output.display()
+                        );
+                    }
+                    None => println!("No divergences found between LEVM and REVM"),
+                }
+            }
+            SubcommandExecute::Profile {
+                start,
+                end,
+                rpc_url,
+                network,
+                top,
+            } => {
+                let cache = get_blocks_cache(start, end, rpc_url, &network).await?;
+                let profile = profile(cache).await?;
+                print_profile(&profile, top);
+            }
         }
         Ok(())
     }
@@ -405,6 +490,70 @@ fn print_transition(update: AccountUpdate) {
     }
 }
 
+/// Fetches a single block, or a range of blocks if the end is given
+async fn get_blocks_cache(
+    start: Option<usize>,
+    end: Option<usize>,
+    rpc_url: Url,
+    network: &str,
+) -> eyre::Result<Cache> {
+    let chain_config = get_chain_config(network)?;
+    let eth_client = EthClient::new(rpc_url.as_str())?;
+    match (start, end) {
+        (Some(start), Some(end)) => {
+            if start >= end {
+                return Err(eyre::Error::msg(
+                    "starting point can't be greater than ending point",
+                ));
+            }
+            get_rangedata(eth_client, chain_config, start, end).await
+        }
+        (None, Some(_)) => Err(eyre::Error::msg(
+            "starting point is required when an ending point is given",
+        )),
+        (start, None) => get_blockdata(eth_client, chain_config, or_latest(start)?).await,
+    }
+}
+
+fn print_profile(profile: &Profile, top: usize) {
+    let mut opcodes: Vec<_> = profile.opcodes().collect();
+    opcodes.sort_by_key(|(_, entry)| std::cmp::Reverse(entry.time));
+    println!("Opcodes:");
+    println!(
+        "  {:<16} {:>12} {:>16} {:>14}",
+        "opcode", "count", "gas", "time"
+    );
+    for (opcode, entry) in opcodes.into_iter().take(top) {
+        println!(
+            "  {:<16} {:>12} {:>16} {:>14?}",
+            format!("{opcode:?}"),
+            entry.count,
+            entry.gas,
+            entry.time
+        );
+    }
+
+    let mut precompiles: Vec<_> = profile.precompiles.iter().collect();
+    precompiles.sort_by_key(|(_, entry)| std::cmp::Reverse(entry.time));
+    println!("Precompiles:");
+    for (address, entry) in precompiles.into_iter().take(top) {
+        println!(
+            "  {:#x} count: {} gas: {} time: {:?}",
+            address, entry.count, entry.gas, entry.time
+        );
+    }
+
+    let mut contracts: Vec<_> = profile.contracts.iter().collect();
+    contracts.sort_by_key(|(_, entry)| std::cmp::Reverse(entry.time));
+    println!("Contracts (by code hash):");
+    for (code_hash, entry) in contracts.into_iter().take(top) {
+        println!(
+            "  {:#x} opcodes: {} gas: {} time: {:?}",
+            code_hash, entry.count, entry.gas, entry.time
+        );
+    }
+}
+
 fn print_receipt(receipt: Receipt) {
     if receipt.succeeded {
         println!("Transaction succeeded.")
@@ -4,7 +4,7 @@ use std::str::FromStr;
 
 use ethrex_common::{
     H160,
-    types::{BlobSchedule, ChainConfig},
+    types::{BlobSchedule, ChainConfig, CustomPrecompiles},
 };
 
 // Chain config for different forks as defined on https://ethereum.github.io/execution-spec-tests/v3.0.0/consuming_tests/common_types/#fork
@@ -37,6 +37,8 @@ pub fn make_chainconfig(chain_id: u64) -> ChainConfig {
         // Mainnet address
         deposit_contract_address: H160::from_str("0x00000000219ab540356cbb839cbe05303d7705fa")
             .expect("Invalid deposit contract address"),
+        fee_config: None,
+        custom_precompiles: CustomPrecompiles::default(),
     }
 }
 
@@ -106,18 +106,7 @@ pub async fn get_batchdata(
 
     // If the l2 node is in validium it does not return blobs to prove
     cache.l2_fields = Some(L2Fields {
-        blob_commitment: *rpc_batch
-            .batch
-            .blobs_bundle
-            .commitments
-            .first()
-            .unwrap_or(&[0_u8; 48]),
-        blob_proof: *rpc_batch
-            .batch
-            .blobs_bundle
-            .proofs
-            .first()
-            .unwrap_or(&[0_u8; 48]),
+        blobs_bundle: rpc_batch.batch.blobs_bundle,
     });
 
     write_cache(&cache, &file_name).expect("failed to write cache");
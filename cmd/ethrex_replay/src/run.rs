@@ -5,7 +5,11 @@ use ethrex_common::{
 };
 use ethrex_levm::{db::gen_db::GeneralizedDatabase, vm::VMType};
 use ethrex_prover_lib::backends::Backend;
-use ethrex_vm::{DynVmDatabase, Evm, EvmEngine, ExecutionWitnessWrapper, backends::levm::LEVM};
+use ethrex_vm::{
+    DynVmDatabase, Evm, EvmEngine, ExecutionWitnessWrapper, Profile, Profiler,
+    backends::levm::LEVM,
+    differential::{Divergence, execute_block_differential},
+};
 use eyre::Ok;
 use std::sync::Arc;
 use zkvm_interface::io::ProgramInput;
@@ -62,6 +66,39 @@ pub async fn run_tx(
     Err(eyre::Error::msg("transaction not found inside block"))
 }
 
+/// Executes the blocks with both LEVM and REVM, returning the first divergence found.
+pub async fn differential(cache: Cache) -> eyre::Result<Option<Divergence>> {
+    let mut prover_db = cache.witness;
+    prover_db.rebuild_tries()?;
+    let mut wrapped_db = ExecutionWitnessWrapper::new(prover_db);
+
+    for block in &cache.blocks {
+        let execution = execute_block_differential(block, wrapped_db.clone())?;
+        if execution.divergence.is_some() {
+            return Ok(execution.divergence);
+        }
+        let (_, account_updates) = execution.levm_result?;
+        wrapped_db.apply_account_updates(&account_updates)?;
+    }
+    Ok(None)
+}
+
+/// Executes the blocks with LEVM, profiling their opcodes and precompiles.
+pub async fn profile(cache: Cache) -> eyre::Result<Profile> {
+    let mut prover_db = cache.witness;
+    prover_db.rebuild_tries()?;
+    let mut wrapped_db = ExecutionWitnessWrapper::new(prover_db);
+    let mut profiler = Profiler::default();
+
+    for block in &cache.blocks {
+        let mut vm = Evm::new_for_l1(EvmEngine::LEVM, wrapped_db.clone());
+        vm.inspect_block(block, &mut profiler)?;
+        let account_updates = vm.get_state_transitions()?;
+        wrapped_db.apply_account_updates(&account_updates)?;
+    }
+    Ok(profiler.into_profile())
+}
+
 /// Returns the input based on whether the feature "l2" is enabled or not.
 /// If the feature is enabled, it includes L2 fields (blob commitment and proof).
 fn get_input(cache: Cache) -> eyre::Result<ProgramInput> {
@@ -81,7 +118,7 @@ fn get_input(cache: Cache) -> eyre::Result<ProgramInput> {
             blocks,
             db,
             elasticity_multiplier: ELASTICITY_MULTIPLIER,
-            // The L2 specific fields (blob_commitment, blob_proof)
+            // The L2 specific fields (blobs_bundle)
             // will be filled by Default::default() if the 'l2' feature of
             // 'zkvm_interface' is active (due to workspace compilation).
             // If 'zkvm_interface' is compiled without 'l2' (e.g. standalone build),
@@ -101,8 +138,8 @@ fn get_input(cache: Cache) -> eyre::Result<ProgramInput> {
             blocks,
             db,
             elasticity_multiplier: ELASTICITY_MULTIPLIER,
-            blob_commitment: l2_fields.blob_commitment,
-            blob_proof: l2_fields.blob_proof,
+            blobs_bundle: l2_fields.blobs_bundle,
+            ..Default::default()
         })
     }
 }
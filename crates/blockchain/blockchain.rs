@@ -3,11 +3,12 @@ pub mod error;
 pub mod fork_choice;
 pub mod mempool;
 pub mod payload;
+pub mod preconfirmations;
 mod smoke_test;
 pub mod tracing;
 pub mod vm;
 
-use ::tracing::{debug, info};
+use ::tracing::{debug, info, warn};
 use constants::{MAX_INITCODE_SIZE, MAX_TRANSACTION_DATA_SIZE};
 use error::MempoolError;
 use error::{ChainError, InvalidBlockError};
@@ -22,14 +23,19 @@ use ethrex_common::types::{
 };
 use ethrex_common::types::{ELASTICITY_MULTIPLIER, P2PTransaction};
 use ethrex_common::types::{Fork, MempoolTransaction};
-use ethrex_common::{Address, H256, TrieLogger};
+use ethrex_common::{Address, Bytes, H256, TrieLogger, TrieWitness};
 use ethrex_metrics::metrics;
 use ethrex_storage::{
     AccountUpdatesList, Store, UpdateBatch, error::StoreError, hash_address, hash_key,
 };
+use ethrex_trie::Trie;
+use ethrex_vm::Profiler;
 use ethrex_vm::backends::levm::db::DatabaseLogger;
-use ethrex_vm::{BlockExecutionResult, DynVmDatabase, Evm, EvmEngine, EvmError};
+use ethrex_vm::{
+    BlockExecutionResult, DynVmDatabase, Evm, EvmEngine, EvmError, ExecutionWitnessWrapper,
+};
 use mempool::Mempool;
+use preconfirmations::PreconfirmationPool;
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
@@ -40,6 +46,8 @@ use vm::StoreVmDatabase;
 
 #[cfg(feature = "metrics")]
 use ethrex_metrics::metrics_blocks::METRICS_BLOCKS;
+#[cfg(feature = "metrics")]
+use ethrex_metrics::metrics_opcodes::METRICS_OPCODES;
 
 #[cfg(feature = "c-kzg")]
 use ethrex_common::types::BlobsBundle;
@@ -59,11 +67,34 @@ pub struct Blockchain {
     pub evm_engine: EvmEngine,
     storage: Store,
     pub mempool: Mempool,
+    /// Transactions announced by the L2 sequencer before sealing their block
+    pub preconfirmations: PreconfirmationPool,
     /// Whether the node's chain is in or out of sync with the current chain
     /// This will be set to true once the initial sync has taken place and wont be set to false after
     /// This does not reflect whether there is an ongoing sync process
     is_synced: AtomicBool,
     pub r#type: BlockchainType,
+    /// Execute the transactions of blocks and payloads optimistically in parallel
+    pub parallel_execution: bool,
+    /// Profile the opcodes and precompiles executed by imported blocks, publishing them as metrics.
+    /// Only supported by LEVM, blocks are executed sequentially while enabled.
+    pub opcode_profiling: bool,
+    /// Amount of recent blocks whose execution witness is recorded during import and stored.
+    /// Witnesses aren't recorded if it's `None`.
+    pub witness_retention: Option<u64>,
+}
+
+/// State accessed while executing a range of blocks, used to build their execution witness
+struct WitnessRecorder {
+    parent_block_header: BlockHeader,
+    state_trie_witness: TrieWitness,
+    /// State trie after the blocks executed so far
+    trie: Trie,
+    /// Encoded root of the state trie, in case the blocks don't access any node
+    root_node: Option<Vec<u8>>,
+    encoded_storage_tries: HashMap<Address, Vec<Vec<u8>>>,
+    block_hashes: HashMap<u64, H256>,
+    codes: HashMap<H256, Bytes>,
 }
 
 #[derive(Debug, Clone)]
@@ -85,31 +116,62 @@ fn log_batch_progress(batch_size: usize, current_block: usize) {
 }
 
 impl Blockchain {
-    pub fn new(evm_engine: EvmEngine, store: Store, blockchain_type: BlockchainType) -> Self {
+    pub fn new(
+        evm_engine: EvmEngine,
+        store: Store,
+        blockchain_type: BlockchainType,
+        parallel_execution: bool,
+    ) -> Self {
         Self {
             evm_engine,
             storage: store,
             mempool: Mempool::new(),
+            preconfirmations: PreconfirmationPool::new(),
             is_synced: AtomicBool::new(false),
             r#type: blockchain_type,
+            parallel_execution,
+            opcode_profiling: false,
+            witness_retention: None,
         }
     }
 
+    pub fn with_opcode_profiling(mut self, opcode_profiling: bool) -> Self {
+        self.opcode_profiling = opcode_profiling;
+        self
+    }
+
+    pub fn with_witness_retention(mut self, witness_retention: Option<u64>) -> Self {
+        self.witness_retention = witness_retention;
+        self
+    }
+
     pub fn default_with_store(store: Store) -> Self {
         Self {
             evm_engine: EvmEngine::default(),
             storage: store,
             mempool: Mempool::new(),
+            preconfirmations: PreconfirmationPool::new(),
             is_synced: AtomicBool::new(false),
             r#type: BlockchainType::default(),
+            parallel_execution: false,
+            opcode_profiling: false,
+            witness_retention: None,
         }
     }
 
-    /// Executes a block withing a new vm instance and state
+    /// Executes a block withing a new vm instance and state.
+    /// If witnesses are retained, the state accessed is recorded by the returned logger.
     async fn execute_block(
         &self,
         block: &Block,
-    ) -> Result<(BlockExecutionResult, Vec<AccountUpdate>), ChainError> {
+    ) -> Result<
+        (
+            BlockExecutionResult,
+            Vec<AccountUpdate>,
+            Option<Arc<DatabaseLogger>>,
+        ),
+        ChainError,
+    > {
         // Validate if it can be the new head and find the parent
         let Ok(parent_header) = find_parent_header(&block.header, &self.storage) else {
             // If the parent is not present, we store it as pending.
@@ -123,17 +185,29 @@ impl Blockchain {
         validate_block(block, &parent_header, &chain_config, ELASTICITY_MULTIPLIER)?;
 
         let vm_db = StoreVmDatabase::new(self.storage.clone(), block.header.parent_hash);
-        let mut vm = self.new_evm(vm_db)?;
-
-        let execution_result = vm.execute_block(block)?;
-        let account_updates = vm.get_state_transitions()?;
+        let (execution_result, account_updates, logger) =
+            if self.evm_engine == EvmEngine::Differential {
+                let (execution_result, account_updates) =
+                    Self::execute_block_differential(block, vm_db)?;
+                (execution_result, account_updates, None)
+            } else if self.witness_retention.is_some() {
+                let vm_db: DynVmDatabase = Box::new(vm_db);
+                let logger = Arc::new(DatabaseLogger::new(Arc::new(Mutex::new(Box::new(vm_db)))));
+                let mut vm = self.new_evm_with_logger(logger.clone());
+                let execution_result = self.execute_block_on_vm(block, &mut vm)?;
+                (execution_result, vm.get_state_transitions()?, Some(logger))
+            } else {
+                let mut vm = self.new_evm(vm_db)?;
+                let execution_result = self.execute_block_on_vm(block, &mut vm)?;
+                (execution_result, vm.get_state_transitions()?, None)
+            };
 
         // Validate execution went alright
         validate_gas_used(&execution_result.receipts, &block.header)?;
         validate_receipts_root(&block.header, &execution_result.receipts)?;
         validate_requests_hash(&block.header, &chain_config, &execution_result.requests)?;
 
-        Ok((execution_result, account_updates))
+        Ok((execution_result, account_updates, logger))
     }
 
     /// Executes a block from a given vm instance an does not clear its state
@@ -146,7 +220,7 @@ impl Blockchain {
     ) -> Result<BlockExecutionResult, ChainError> {
         // Validate the block pre-execution
         validate_block(block, parent_header, chain_config, ELASTICITY_MULTIPLIER)?;
-        let execution_result = vm.execute_block(block)?;
+        let execution_result = self.execute_block_on_vm(block, vm)?;
         // Validate execution went alright
         validate_gas_used(&execution_result.receipts, &block.header)?;
         validate_receipts_root(&block.header, &execution_result.receipts)?;
@@ -155,18 +229,118 @@ impl Blockchain {
         Ok(execution_result)
     }
 
+    /// Executes the block with both LEVM and REVM, logging a report of the first divergence if any.
+    /// The results of LEVM are returned.
+    fn execute_block_differential(
+        block: &Block,
+        vm_db: StoreVmDatabase,
+    ) -> Result<(BlockExecutionResult, Vec<AccountUpdate>), EvmError> {
+        let execution = ethrex_vm::differential::execute_block_differential(block, vm_db)?;
+        if let Some(divergence) = &execution.divergence {
+            let report =
+                serde_json::to_string(divergence).unwrap_or_else(|_| format!("{divergence:?}"));
+            warn!(
+                block_number = block.header.number,
+                tx_index = ?divergence.tx_index,
+                "LEVM and REVM diverged: {report}"
+            );
+        }
+        execution.levm_result
+    }
+
+    /// Executes the block with the opcode profiler, publishing the results as metrics.
+    /// The transactions are executed sequentially, the profiler can't follow a parallel execution.
+    fn execute_block_profiled(
+        block: &Block,
+        vm: &mut Evm,
+    ) -> Result<BlockExecutionResult, EvmError> {
+        let mut profiler = Profiler::default();
+        let execution_result = vm.inspect_block(block, &mut profiler)?;
+
+        #[cfg(feature = "metrics")]
+        {
+            let profile = profiler.profile();
+            for (opcode, entry) in profile.opcodes() {
+                METRICS_OPCODES.observe_opcode(
+                    &format!("{opcode:?}"),
+                    entry.count,
+                    entry.gas,
+                    entry.time.as_secs_f64(),
+                );
+            }
+            for (address, entry) in &profile.precompiles {
+                METRICS_OPCODES.observe_precompile(
+                    &format!("{address:#x}"),
+                    entry.count,
+                    entry.gas,
+                    entry.time.as_secs_f64(),
+                );
+            }
+        }
+
+        Ok(execution_result)
+    }
+
+    /// Executes the block on the given vm, in parallel if enabled and not profiling
+    fn execute_block_on_vm(
+        &self,
+        block: &Block,
+        vm: &mut Evm,
+    ) -> Result<BlockExecutionResult, EvmError> {
+        if self.opcode_profiling && matches!(vm, Evm::LEVM { .. }) {
+            return Self::execute_block_profiled(block, vm);
+        }
+        if self.parallel_execution {
+            vm.execute_block_parallel(block)
+        } else {
+            vm.execute_block(block)
+        }
+    }
+
     pub async fn generate_witness_for_blocks(
         &self,
         blocks: &[Block],
     ) -> Result<ExecutionWitnessResult, ChainError> {
-        let first_block_header = blocks
+        let first_block_header = &blocks
             .first()
             .ok_or(ChainError::WitnessGeneration(
                 "Empty block batch".to_string(),
             ))?
-            .header
-            .clone();
+            .header;
+        let mut recorder = self.start_witness(first_block_header)?;
 
+        for block in blocks {
+            let parent_hash = block.header.parent_hash;
+            let vm_db: DynVmDatabase =
+                Box::new(StoreVmDatabase::new(self.storage.clone(), parent_hash));
+            let logger = Arc::new(DatabaseLogger::new(Arc::new(Mutex::new(Box::new(vm_db)))));
+            let mut vm = self.new_evm_with_logger(logger.clone());
+
+            // Re-execute block with logger
+            vm.execute_block(block)?;
+            // Gather account updates
+            let account_updates = vm.get_state_transitions()?;
+
+            self.record_witness(&mut recorder, block, &logger, &account_updates)
+                .await?;
+        }
+
+        self.finish_witness(recorder, blocks)
+    }
+
+    /// Creates a VM whose database accesses are recorded by the logger, in order to build witnesses
+    fn new_evm_with_logger(&self, logger: Arc<DatabaseLogger>) -> Evm {
+        match self.r#type {
+            BlockchainType::L1 => Evm::new_from_db_for_l1(logger),
+            BlockchainType::L2 => Evm::new_from_db_for_l2(logger),
+        }
+    }
+
+    /// Opens the state at the parent of the first block of the witness
+    fn start_witness(
+        &self,
+        first_block_header: &BlockHeader,
+    ) -> Result<WitnessRecorder, ChainError> {
         let parent_block_header = self
             .storage
             .get_block_header_by_hash(first_block_header.parent_hash)?
@@ -178,147 +352,152 @@ impl Blockchain {
             .state_trie(first_block_header.parent_hash)
             .map_err(|_| ChainError::ParentStateNotFound)?
             .ok_or(ChainError::ParentStateNotFound)?;
-        let (state_trie_witness, mut trie) = TrieLogger::open_trie(trie);
+        let (state_trie_witness, trie) = TrieLogger::open_trie(trie);
 
         // Store the root node in case the block is empty and the witness does not record any nodes
-        let root_node = trie.root_node().map_err(|_| {
-            ChainError::WitnessGeneration("Failed to get root state node".to_string())
-        })?;
-
-        let mut encoded_storage_tries: HashMap<ethrex_common::H160, Vec<Vec<u8>>> = HashMap::new();
-        let mut block_hashes = HashMap::new();
-        let mut codes = HashMap::new();
-
-        for block in blocks {
-            let parent_hash = block.header.parent_hash;
-            let vm_db: DynVmDatabase =
-                Box::new(StoreVmDatabase::new(self.storage.clone(), parent_hash));
-            let logger = Arc::new(DatabaseLogger::new(Arc::new(Mutex::new(Box::new(vm_db)))));
-            let mut vm = match self.r#type {
-                BlockchainType::L1 => Evm::new_from_db_for_l1(logger.clone()),
-                BlockchainType::L2 => Evm::new_from_db_for_l2(logger.clone()),
-            };
-
-            // Re-execute block with logger
-            vm.execute_block(block)?;
-            // Gather account updates
-            let account_updates = vm.get_state_transitions()?;
+        let root_node = trie
+            .root_node()
+            .map_err(|_| {
+                ChainError::WitnessGeneration("Failed to get root state node".to_string())
+            })?
+            .map(|root| root.encode_raw());
+
+        Ok(WitnessRecorder {
+            parent_block_header,
+            state_trie_witness,
+            trie,
+            root_node,
+            encoded_storage_tries: HashMap::new(),
+            block_hashes: HashMap::new(),
+            codes: HashMap::new(),
+        })
+    }
 
-            let mut used_storage_tries = HashMap::new();
-            // Get the used block hashes from the logger
-            let logger_block_hashes = logger
-                .block_hashes_accessed
-                .lock()
-                .map_err(|_e| {
-                    ChainError::WitnessGeneration("Failed to get block hashes".to_string())
-                })?
-                .clone();
-            block_hashes.extend(logger_block_hashes);
-            // Access all the accounts needed for withdrawals
-            if let Some(withdrawals) = block.body.withdrawals.as_ref() {
-                for withdrawal in withdrawals {
-                    trie.get(&hash_address(&withdrawal.address)).map_err(|_e| {
-                        ChainError::Custom("Failed to access account from trie".to_string())
-                    })?;
-                }
+    /// Records the trie nodes, codes and block hashes accessed by the execution of a block,
+    /// and the trie nodes needed to apply its account updates
+    async fn record_witness(
+        &self,
+        recorder: &mut WitnessRecorder,
+        block: &Block,
+        logger: &DatabaseLogger,
+        account_updates: &[AccountUpdate],
+    ) -> Result<(), ChainError> {
+        let parent_hash = block.header.parent_hash;
+        let trie = std::mem::take(&mut recorder.trie);
+        let mut used_storage_tries = HashMap::new();
+        // Get the used block hashes from the logger
+        let logger_block_hashes = logger
+            .block_hashes_accessed
+            .lock()
+            .map_err(|_e| ChainError::WitnessGeneration("Failed to get block hashes".to_string()))?
+            .clone();
+        recorder.block_hashes.extend(logger_block_hashes);
+        // Access all the accounts needed for withdrawals
+        if let Some(withdrawals) = block.body.withdrawals.as_ref() {
+            for withdrawal in withdrawals {
+                trie.get(&hash_address(&withdrawal.address)).map_err(|_e| {
+                    ChainError::Custom("Failed to access account from trie".to_string())
+                })?;
             }
+        }
 
-            // Access all the accounts from the initial trie
-            // Record all the storage nodes for the initial state
-            for (account, keys) in logger
-                .state_accessed
-                .lock()
-                .map_err(|_e| {
-                    ChainError::WitnessGeneration("Failed to execute with witness".to_string())
-                })?
-                .iter()
-            {
-                // Access the account from the state trie to record the nodes used to access it
-                trie.get(&hash_address(account)).map_err(|_e| {
-                    ChainError::WitnessGeneration("Failed to access account from trie".to_string())
-                })?;
-                // Get storage trie at before updates
-                if !keys.is_empty() {
-                    if let Ok(Some(storage_trie)) = self.storage.storage_trie(parent_hash, *account)
-                    {
-                        let (storage_trie_witness, storage_trie) =
-                            TrieLogger::open_trie(storage_trie);
-                        // Access all the keys
-                        for storage_key in keys {
-                            let hashed_key = hash_key(storage_key);
-                            storage_trie.get(&hashed_key).map_err(|_e| {
-                                ChainError::WitnessGeneration(
-                                    "Failed to access storage key".to_string(),
-                                )
-                            })?;
-                        }
-                        // Store the tries to reuse when applying account updates
-                        used_storage_tries.insert(*account, (storage_trie_witness, storage_trie));
+        // Access all the accounts from the initial trie
+        // Record all the storage nodes for the initial state
+        for (account, keys) in logger
+            .state_accessed
+            .lock()
+            .map_err(|_e| {
+                ChainError::WitnessGeneration("Failed to execute with witness".to_string())
+            })?
+            .iter()
+        {
+            // Access the account from the state trie to record the nodes used to access it
+            trie.get(&hash_address(account)).map_err(|_e| {
+                ChainError::WitnessGeneration("Failed to access account from trie".to_string())
+            })?;
+            // Get storage trie at before updates
+            if !keys.is_empty() {
+                if let Ok(Some(storage_trie)) = self.storage.storage_trie(parent_hash, *account) {
+                    let (storage_trie_witness, storage_trie) = TrieLogger::open_trie(storage_trie);
+                    // Access all the keys
+                    for storage_key in keys {
+                        let hashed_key = hash_key(storage_key);
+                        storage_trie.get(&hashed_key).map_err(|_e| {
+                            ChainError::WitnessGeneration(
+                                "Failed to access storage key".to_string(),
+                            )
+                        })?;
                     }
+                    // Store the tries to reuse when applying account updates
+                    used_storage_tries.insert(*account, (storage_trie_witness, storage_trie));
                 }
             }
-            // Store all the accessed evm bytecodes
-            for code_hash in logger
-                .code_accessed
-                .lock()
+        }
+        // Store all the accessed evm bytecodes
+        for code_hash in logger
+            .code_accessed
+            .lock()
+            .map_err(|_e| {
+                ChainError::WitnessGeneration("Failed to gather used bytecodes".to_string())
+            })?
+            .iter()
+        {
+            let code = self
+                .storage
+                .get_account_code(*code_hash)
                 .map_err(|_e| {
-                    ChainError::WitnessGeneration("Failed to gather used bytecodes".to_string())
+                    ChainError::WitnessGeneration("Failed to get account code".to_string())
                 })?
-                .iter()
-            {
-                let code = self
-                    .storage
-                    .get_account_code(*code_hash)
-                    .map_err(|_e| {
-                        ChainError::WitnessGeneration("Failed to get account code".to_string())
-                    })?
-                    .ok_or(ChainError::WitnessGeneration(
-                        "Failed to get account code".to_string(),
-                    ))?;
-                codes.insert(*code_hash, code);
-            }
+                .ok_or(ChainError::WitnessGeneration(
+                    "Failed to get account code".to_string(),
+                ))?;
+            recorder.codes.insert(*code_hash, code);
+        }
 
-            // Apply account updates to the trie recording all the necessary nodes to do so
-            let (updated_trie, storage_tries_after_update) = self
-                .storage
-                .apply_account_updates_from_trie_with_witness(
-                    trie,
-                    &account_updates,
-                    used_storage_tries,
-                )
-                .await?;
-            for (address, (witness, _storage_trie)) in storage_tries_after_update {
-                let mut witness = witness.lock().map_err(|_| {
-                    ChainError::WitnessGeneration("Failed to lock storage trie witness".to_string())
-                })?;
-                let witness = std::mem::take(&mut *witness);
-                let witness = witness.into_iter().collect::<Vec<_>>();
-                match encoded_storage_tries.entry(address) {
-                    std::collections::hash_map::Entry::Occupied(mut entry) => {
-                        entry.get_mut().extend(witness);
-                    }
-                    std::collections::hash_map::Entry::Vacant(vacant) => {
-                        vacant.insert(witness);
-                    }
+        // Apply account updates to the trie recording all the necessary nodes to do so
+        let (updated_trie, storage_tries_after_update) = self
+            .storage
+            .apply_account_updates_from_trie_with_witness(trie, account_updates, used_storage_tries)
+            .await?;
+        for (address, (witness, _storage_trie)) in storage_tries_after_update {
+            let mut witness = witness.lock().map_err(|_| {
+                ChainError::WitnessGeneration("Failed to lock storage trie witness".to_string())
+            })?;
+            let witness = std::mem::take(&mut *witness);
+            let witness = witness.into_iter().collect::<Vec<_>>();
+            match recorder.encoded_storage_tries.entry(address) {
+                std::collections::hash_map::Entry::Occupied(mut entry) => {
+                    entry.get_mut().extend(witness);
+                }
+                std::collections::hash_map::Entry::Vacant(vacant) => {
+                    vacant.insert(witness);
                 }
             }
-            trie = updated_trie;
         }
+        recorder.trie = updated_trie;
+        Ok(())
+    }
 
+    /// Builds the witness of the blocks from everything recorded while executing them
+    fn finish_witness(
+        &self,
+        recorder: WitnessRecorder,
+        blocks: &[Block],
+    ) -> Result<ExecutionWitnessResult, ChainError> {
         // Get the witness for the state trie
-        let mut state_trie_witness = state_trie_witness.lock().map_err(|_| {
+        let mut state_trie_witness = recorder.state_trie_witness.lock().map_err(|_| {
             ChainError::WitnessGeneration("Failed to lock state trie witness".to_string())
         })?;
         let state_trie_witness = std::mem::take(&mut *state_trie_witness);
         let mut used_trie_nodes = Vec::from_iter(state_trie_witness.into_iter());
         // If the witness is empty at least try to store the root
         if used_trie_nodes.is_empty() {
-            if let Some(root) = root_node {
-                used_trie_nodes.push(root.encode_raw());
+            if let Some(root) = recorder.root_node {
+                used_trie_nodes.push(root);
             }
         }
 
-        let mut needed_block_numbers = block_hashes.keys().collect::<Vec<_>>();
+        let mut needed_block_numbers = recorder.block_hashes.keys().collect::<Vec<_>>();
         needed_block_numbers.sort();
         // The last block number we need is the parent of the last block we execute
         let last_needed_block_number = blocks
@@ -328,7 +507,7 @@ impl Blockchain {
             .number
             .saturating_sub(1);
         // The first block number we need is either the parent of the first block number or the earliest block number used by BLOCKHASH
-        let mut first_needed_block_number = first_block_header.number.saturating_sub(1);
+        let mut first_needed_block_number = recorder.parent_block_header.number;
         if let Some(block_number_from_logger) = needed_block_numbers.first() {
             if **block_number_from_logger < first_needed_block_number {
                 first_needed_block_number = **block_number_from_logger;
@@ -346,16 +525,40 @@ impl Blockchain {
 
         Ok(ExecutionWitnessResult {
             state_trie_nodes: Some(used_trie_nodes),
-            storage_trie_nodes: Some(encoded_storage_tries),
-            codes,
+            storage_trie_nodes: Some(recorder.encoded_storage_tries),
+            codes: recorder.codes,
             state_trie: None,
             storage_tries: None,
             block_headers,
-            parent_block_header,
+            parent_block_header: recorder.parent_block_header,
             chain_config,
         })
     }
 
+    /// Builds the witness of an imported block from the accesses recorded during its execution,
+    /// storing it and discarding the witnesses that fall out of the retention window
+    async fn store_block_witness(
+        &self,
+        block: &Block,
+        logger: &DatabaseLogger,
+        account_updates: &[AccountUpdate],
+        witness_retention: u64,
+    ) -> Result<(), ChainError> {
+        let mut recorder = self.start_witness(&block.header)?;
+        self.record_witness(&mut recorder, block, logger, account_updates)
+            .await?;
+        let witness = self.finish_witness(recorder, std::slice::from_ref(block))?;
+        self.storage
+            .add_execution_witness(block.header.number, block.hash(), &witness)
+            .await?;
+        if let Some(expired) = block.header.number.checked_sub(witness_retention) {
+            self.storage
+                .remove_execution_witnesses_up_to(expired)
+                .await?;
+        }
+        Ok(())
+    }
+
     pub async fn store_block(
         &self,
         block: &Block,
@@ -382,7 +585,7 @@ impl Blockchain {
 
     pub async fn add_block(&self, block: &Block) -> Result<(), ChainError> {
         let since = Instant::now();
-        let (res, updates) = self.execute_block(block).await?;
+        let (res, updates, logger) = self.execute_block(block).await?;
         let executed = Instant::now();
 
         // Apply the account updates over the last block's state and compute the new state root
@@ -396,6 +599,21 @@ impl Blockchain {
         let result = self.store_block(block, account_updates_list, res).await;
         let stored = Instant::now();
         Self::print_add_block_logs(block, since, executed, merkleized, stored);
+
+        if let (Ok(()), Some(logger), Some(witness_retention)) =
+            (&result, logger, self.witness_retention)
+        {
+            // The block is valid even if its witness can't be stored
+            if let Err(error) = self
+                .store_block_witness(block, &logger, &updates, witness_retention)
+                .await
+            {
+                warn!(
+                    block_number = block.header.number,
+                    "Failed to store the execution witness: {error}"
+                );
+            }
+        }
         result
     }
 
@@ -453,8 +671,53 @@ impl Blockchain {
     /// - The error type ([`ChainError`]).
     /// - [`BatchProcessingFailure`] (if the error was caused by block processing).
     ///
-    /// Note: only the last block's state trie is stored in the db
+    /// Note: only the last block's state trie is stored in the db.
+    /// If witnesses are retained, the blocks whose witness falls in the retention window are added one by one
+    /// after the rest of the batch, recording their witnesses.
     pub async fn add_blocks_in_batch(
+        &self,
+        mut blocks: Vec<Block>,
+        cancellation_token: CancellationToken,
+    ) -> Result<(), (ChainError, Option<BatchBlockProcessingFailure>)> {
+        let Some(witness_retention) = self.witness_retention else {
+            return self
+                .add_blocks_in_single_batch(blocks, cancellation_token)
+                .await;
+        };
+        let retained = usize::try_from(witness_retention).unwrap_or(usize::MAX);
+        let recorded_blocks = blocks.split_off(blocks.len().saturating_sub(retained));
+
+        let mut last_valid_hash = match blocks.last() {
+            Some(last_block) => {
+                let last_block_hash = last_block.hash();
+                self.add_blocks_in_single_batch(blocks, cancellation_token.clone())
+                    .await?;
+                last_block_hash
+            }
+            None => H256::default(),
+        };
+        for block in recorded_blocks {
+            if cancellation_token.is_cancelled() {
+                info!("Received shutdown signal, aborting");
+                return Err((ChainError::Custom(String::from("shutdown signal")), None));
+            }
+            let block_hash = block.hash();
+            self.add_block(&block).await.map_err(|err| {
+                (
+                    err,
+                    Some(BatchBlockProcessingFailure {
+                        failed_block_hash: block_hash,
+                        last_valid_hash,
+                    }),
+                )
+            })?;
+            last_valid_hash = block_hash;
+        }
+        Ok(())
+    }
+
+    /// Executes the blocks on a single vm and stores them with the state after the last one
+    async fn add_blocks_in_single_batch(
         &self,
         blocks: Vec<Block>,
         cancellation_token: CancellationToken,
@@ -960,6 +1223,63 @@ pub fn validate_block(
     Ok(())
 }
 
+/// Validates and executes a block using only the state provided by its execution witness,
+/// without accessing a local state DB. Returns the post-state root after checking it matches the header.
+/// The block is validated with the given chain config, the one included in the witness isn't trusted.
+pub fn validate_block_stateless(
+    block: &Block,
+    mut witness: ExecutionWitnessResult,
+    chain_config: ChainConfig,
+) -> Result<H256, ChainError> {
+    witness
+        .rebuild_tries()
+        .map_err(|e| ChainError::InvalidWitness(e.to_string()))?;
+    witness.chain_config = chain_config;
+    let mut witness_db = ExecutionWitnessWrapper::new(witness);
+
+    // The headers used by BLOCKHASH have to be a chain ending in the parent block
+    if let Some(block_number) = witness_db
+        .get_first_invalid_block_hash()
+        .map_err(|e| ChainError::InvalidWitness(e.to_string()))?
+    {
+        return Err(ChainError::InvalidWitness(format!(
+            "Invalid hash for block header {block_number}"
+        )));
+    }
+    let parent_header = witness_db
+        .get_block_parent_header(block.header.number)
+        .map_err(|e| ChainError::InvalidWitness(e.to_string()))?;
+    if parent_header.hash() != block.header.parent_hash {
+        return Err(ChainError::ParentNotFound);
+    }
+    let initial_state_root = witness_db
+        .state_trie_root()
+        .map_err(|e| ChainError::InvalidWitness(e.to_string()))?;
+    if initial_state_root != parent_header.state_root {
+        return Err(ChainError::InvalidWitness(
+            "State trie root doesn't match the parent block state root".to_string(),
+        ));
+    }
+
+    validate_block(block, &parent_header, &chain_config, ELASTICITY_MULTIPLIER)?;
+    let mut vm = Evm::new_for_l1(EvmEngine::LEVM, witness_db.clone());
+    let execution_result = vm.execute_block(block)?;
+    let account_updates = vm.get_state_transitions()?;
+
+    validate_gas_used(&execution_result.receipts, &block.header)?;
+    validate_receipts_root(&block.header, &execution_result.receipts)?;
+    validate_requests_hash(&block.header, &chain_config, &execution_result.requests)?;
+
+    witness_db
+        .apply_account_updates(&account_updates)
+        .map_err(|e| ChainError::InvalidWitness(e.to_string()))?;
+    let state_root = witness_db
+        .state_trie_root()
+        .map_err(|e| ChainError::InvalidWitness(e.to_string()))?;
+    validate_state_root(&block.header, state_root)?;
+    Ok(state_root)
+}
+
 pub async fn is_canonical(
     store: &Store,
     block_number: BlockNumber,
@@ -22,6 +22,8 @@ pub enum ChainError {
     InvalidTransaction(String),
     #[error("Failed to generate witness: {0}")]
     WitnessGeneration(String),
+    #[error("Invalid execution witness: {0}")]
+    InvalidWitness(String),
     #[error("{0}")]
     Custom(String),
 }
@@ -37,6 +39,7 @@ impl ChainError {
             ChainError::EvmError(_) => "evm_error",
             ChainError::InvalidTransaction(_) => "invalid_transaction",
             ChainError::WitnessGeneration(_) => "witness_generation",
+            ChainError::InvalidWitness(_) => "invalid_witness",
             ChainError::Custom(_) => "custom_error",
         }
     }
@@ -22,6 +22,8 @@ pub struct Mempool {
     transaction_pool: RwLock<HashMap<H256, MempoolTransaction>>,
     blobs_bundle_pool: Mutex<HashMap<H256, BlobsBundle>>,
     txs_by_sender_nonce: RwLock<BTreeMap<(H160, u64), H256>>,
+    /// Hashes of the transactions submitted to this node through the RPC
+    local_txs: RwLock<HashSet<H256>>,
 }
 impl Mempool {
     pub fn new() -> Self {
@@ -89,10 +91,43 @@ impl Mempool {
                 .remove(&(tx.sender(), tx.nonce()));
             tx_pool.remove(hash);
         };
+        self.local_txs
+            .write()
+            .map_err(|error| StoreError::MempoolWriteLock(error.to_string()))?
+            .remove(hash);
 
         Ok(())
     }
 
+    /// Marks a transaction as submitted to this node through the RPC
+    /// This can be done before the transaction is added to the pool
+    /// Returns false if the transaction was already marked
+    pub fn mark_as_local(&self, hash: H256) -> Result<bool, StoreError> {
+        Ok(self
+            .local_txs
+            .write()
+            .map_err(|error| StoreError::MempoolWriteLock(error.to_string()))?
+            .insert(hash))
+    }
+
+    /// Removes the local mark of a transaction that didn't make it into the pool
+    pub fn unmark_as_local(&self, hash: &H256) -> Result<(), StoreError> {
+        self.local_txs
+            .write()
+            .map_err(|error| StoreError::MempoolWriteLock(error.to_string()))?
+            .remove(hash);
+        Ok(())
+    }
+
+    /// Returns true if the transaction was submitted to this node through the RPC
+    pub fn is_local(&self, hash: &H256) -> Result<bool, StoreError> {
+        Ok(self
+            .local_txs
+            .read()
+            .map_err(|error| StoreError::MempoolReadLock(error.to_string()))?
+            .contains(hash))
+    }
+
     /// Applies the filter and returns a set of suitable transactions from the mempool.
     /// These transactions will be grouped by sender and sorted by nonce
     pub fn filter_transactions(
@@ -781,6 +816,21 @@ mod tests {
         assert_eq!(txs, HashMap::from([(blob_tx.sender(), vec![blob_tx])]));
     }
 
+    #[test]
+    fn local_mark_is_cleared_on_removal() {
+        let tx_decoded = Transaction::decode_canonical(&hex::decode("f86d80843baa0c4082f618946177843db3138ae69679a54b95cf345ed759450d870aa87bee538000808360306ba0151ccc02146b9b11adf516e6787b59acae3e76544fdcd75e77e67c6b598ce65da064c5dd5aae2fbb535830ebbdad0234975cd7ece3562013b63ea18cc0df6c97d4").unwrap()).unwrap();
+        let tx_sender = tx_decoded.sender().unwrap();
+        let tx = MempoolTransaction::new(tx_decoded, tx_sender);
+        let tx_hash = tx.compute_hash();
+        let mempool = Mempool::new();
+        assert!(mempool.mark_as_local(tx_hash).unwrap());
+        assert!(!mempool.mark_as_local(tx_hash).unwrap());
+        mempool.add_transaction(tx_hash, tx).unwrap();
+        assert!(mempool.is_local(&tx_hash).unwrap());
+        mempool.remove_transaction(&tx_hash).unwrap();
+        assert!(!mempool.is_local(&tx_hash).unwrap());
+    }
+
     #[test]
     fn blobs_bundle_loadtest() {
         // Write a bundle of 6 blobs 10 times
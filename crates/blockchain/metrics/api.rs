@@ -2,7 +2,10 @@ use axum::{Router, routing::get};
 
 use crate::profiling::gather_profiling_metrics;
 
-use crate::{MetricsApiError, metrics_blocks::METRICS_BLOCKS, metrics_transactions::METRICS_TX};
+use crate::{
+    MetricsApiError, metrics_blocks::METRICS_BLOCKS, metrics_opcodes::METRICS_OPCODES,
+    metrics_p2p::METRICS_P2P, metrics_transactions::METRICS_TX,
+};
 
 pub async fn start_prometheus_metrics_api(
     address: String,
@@ -47,5 +50,23 @@ pub(crate) async fn get_metrics() -> String {
         }
     }
 
+    ret_string.push('\n');
+    match METRICS_OPCODES.gather_metrics() {
+        Ok(string) => ret_string.push_str(&string),
+        Err(_) => {
+            tracing::error!("Failed to register METRICS_OPCODES");
+            return String::new();
+        }
+    }
+
+    ret_string.push('\n');
+    match METRICS_P2P.gather_metrics() {
+        Ok(string) => ret_string.push_str(&string),
+        Err(_) => {
+            tracing::error!("Failed to register METRICS_P2P");
+            return String::new();
+        }
+    }
+
     ret_string
 }
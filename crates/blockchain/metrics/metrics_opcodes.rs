@@ -0,0 +1,130 @@
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, Registry, TextEncoder, exponential_buckets,
+};
+use std::sync::LazyLock;
+
+use crate::MetricsError;
+
+pub static METRICS_OPCODES: LazyLock<MetricsOpcodes> = LazyLock::new(MetricsOpcodes::default);
+
+/// Per-block profile of the LEVM execution, only recorded when opcode profiling is enabled.
+#[derive(Debug, Clone)]
+pub struct MetricsOpcodes {
+    opcode_count: HistogramVec,
+    opcode_gas: HistogramVec,
+    opcode_duration: HistogramVec,
+    precompile_count: HistogramVec,
+    precompile_gas: HistogramVec,
+    precompile_duration: HistogramVec,
+}
+
+impl Default for MetricsOpcodes {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn histogram_vec(name: &str, help: &str, label: &str, buckets: Vec<f64>) -> HistogramVec {
+    HistogramVec::new(HistogramOpts::new(name, help).buckets(buckets), &[label]).unwrap()
+}
+
+impl MetricsOpcodes {
+    pub fn new() -> Self {
+        let count_buckets = exponential_buckets(1.0, 4.0, 12).unwrap();
+        let gas_buckets = exponential_buckets(100.0, 4.0, 12).unwrap();
+        let duration_buckets = exponential_buckets(0.000_001, 4.0, 14).unwrap();
+        MetricsOpcodes {
+            opcode_count: histogram_vec(
+                "opcode_count",
+                "Histogram of the times each opcode is executed in a block",
+                "opcode",
+                count_buckets.clone(),
+            ),
+            opcode_gas: histogram_vec(
+                "opcode_gas",
+                "Histogram of the gas consumed by each opcode in a block",
+                "opcode",
+                gas_buckets.clone(),
+            ),
+            opcode_duration: histogram_vec(
+                "opcode_duration_seconds",
+                "Histogram of the time spent executing each opcode in a block",
+                "opcode",
+                duration_buckets.clone(),
+            ),
+            precompile_count: histogram_vec(
+                "precompile_count",
+                "Histogram of the times each precompile is executed in a block",
+                "precompile",
+                count_buckets,
+            ),
+            precompile_gas: histogram_vec(
+                "precompile_gas",
+                "Histogram of the gas consumed by each precompile in a block",
+                "precompile",
+                gas_buckets,
+            ),
+            precompile_duration: histogram_vec(
+                "precompile_duration_seconds",
+                "Histogram of the time spent executing each precompile in a block",
+                "precompile",
+                duration_buckets,
+            ),
+        }
+    }
+
+    /// Records the totals of an opcode for a block
+    pub fn observe_opcode(&self, opcode: &str, count: u64, gas: u64, seconds: f64) {
+        self.opcode_count
+            .with_label_values(&[opcode])
+            .observe(count as f64);
+        self.opcode_gas
+            .with_label_values(&[opcode])
+            .observe(gas as f64);
+        self.opcode_duration
+            .with_label_values(&[opcode])
+            .observe(seconds);
+    }
+
+    /// Records the totals of a precompile for a block
+    pub fn observe_precompile(&self, precompile: &str, count: u64, gas: u64, seconds: f64) {
+        self.precompile_count
+            .with_label_values(&[precompile])
+            .observe(count as f64);
+        self.precompile_gas
+            .with_label_values(&[precompile])
+            .observe(gas as f64);
+        self.precompile_duration
+            .with_label_values(&[precompile])
+            .observe(seconds);
+    }
+
+    pub fn gather_metrics(&self) -> Result<String, MetricsError> {
+        let r = Registry::new();
+
+        r.register(Box::new(self.opcode_count.clone()))
+            .map_err(|e| MetricsError::PrometheusErr(e.to_string()))?;
+        r.register(Box::new(self.opcode_gas.clone()))
+            .map_err(|e| MetricsError::PrometheusErr(e.to_string()))?;
+        r.register(Box::new(self.opcode_duration.clone()))
+            .map_err(|e| MetricsError::PrometheusErr(e.to_string()))?;
+        r.register(Box::new(self.precompile_count.clone()))
+            .map_err(|e| MetricsError::PrometheusErr(e.to_string()))?;
+        r.register(Box::new(self.precompile_gas.clone()))
+            .map_err(|e| MetricsError::PrometheusErr(e.to_string()))?;
+        r.register(Box::new(self.precompile_duration.clone()))
+            .map_err(|e| MetricsError::PrometheusErr(e.to_string()))?;
+
+        let encoder = TextEncoder::new();
+        let metric_families = r.gather();
+
+        let mut buffer = Vec::new();
+        encoder
+            .encode(&metric_families, &mut buffer)
+            .map_err(|e| MetricsError::PrometheusErr(e.to_string()))?;
+
+        let res = String::from_utf8(buffer)?;
+
+        Ok(res)
+    }
+}
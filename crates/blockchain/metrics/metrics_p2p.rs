@@ -0,0 +1,67 @@
+use prometheus::{Encoder, IntCounterVec, Opts, Registry, TextEncoder};
+use std::sync::LazyLock;
+
+use crate::MetricsError;
+
+pub static METRICS_P2P: LazyLock<MetricsP2P> = LazyLock::new(MetricsP2P::default);
+
+#[derive(Debug, Clone)]
+pub struct MetricsP2P {
+    rejected_connections: IntCounterVec,
+}
+
+impl Default for MetricsP2P {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetricsP2P {
+    pub fn new() -> Self {
+        MetricsP2P {
+            rejected_connections: IntCounterVec::new(
+                Opts::new(
+                    "p2p_rejected_connections",
+                    "Keeps track of the RLPx connections rejected by the peer limits, by reason and direction",
+                ),
+                &["reason", "direction"],
+            )
+            .unwrap(),
+        }
+    }
+
+    pub fn inc_rejected_connections(&self, reason: &str, inbound: bool) {
+        let direction = if inbound { "inbound" } else { "outbound" };
+        let builder = match self
+            .rejected_connections
+            .get_metric_with_label_values(&[reason, direction])
+        {
+            Ok(builder) => builder,
+            Err(e) => {
+                tracing::error!("Failed to build Metric: {e}");
+                return;
+            }
+        };
+
+        builder.inc();
+    }
+
+    pub fn gather_metrics(&self) -> Result<String, MetricsError> {
+        let r = Registry::new();
+
+        r.register(Box::new(self.rejected_connections.clone()))
+            .map_err(|e| MetricsError::PrometheusErr(e.to_string()))?;
+
+        let encoder = TextEncoder::new();
+        let metric_families = r.gather();
+
+        let mut buffer = Vec::new();
+        encoder
+            .encode(&metric_families, &mut buffer)
+            .map_err(|e| MetricsError::PrometheusErr(e.to_string()))?;
+
+        let res = String::from_utf8(buffer)?;
+
+        Ok(res)
+    }
+}
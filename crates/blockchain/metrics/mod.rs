@@ -4,6 +4,10 @@ pub mod api;
 pub mod l2;
 #[cfg(any(feature = "api", feature = "metrics"))]
 pub mod metrics_blocks;
+#[cfg(any(feature = "api", feature = "metrics"))]
+pub mod metrics_opcodes;
+#[cfg(any(feature = "api", feature = "p2p"))]
+pub mod metrics_p2p;
 #[cfg(any(feature = "api", feature = "transactions"))]
 pub mod metrics_transactions;
 #[cfg(feature = "api")]
@@ -17,7 +17,7 @@ use ethrex_common::{
     },
 };
 
-use ethrex_vm::{Evm, EvmEngine, EvmError};
+use ethrex_vm::{Evm, EvmEngine, EvmError, SpeculativeExecution};
 
 use ethrex_rlp::encode::RLPEncode;
 use ethrex_storage::{Store, error::StoreError};
@@ -181,6 +181,8 @@ pub struct PayloadBuildContext {
     pub store: Store,
     pub vm: Evm,
     pub account_updates: Vec<AccountUpdate>,
+    /// Speculative executions of pending transactions, indexed by transaction hash
+    speculations: HashMap<H256, SpeculativeExecution>,
 }
 
 impl PayloadBuildContext {
@@ -220,11 +222,31 @@ impl PayloadBuildContext {
             store: storage.clone(),
             vm,
             account_updates: Vec::new(),
+            speculations: HashMap::new(),
         })
     }
 }
 
 impl PayloadBuildContext {
+    /// Executes the next transaction of each sender in parallel on top of the current state.
+    /// [apply_plain_transaction] commits these executions directly if the state they read didn't change meanwhile.
+    pub fn speculate_transactions(&mut self, txs: &TransactionQueue) {
+        let heads: Vec<(&Transaction, Address)> = txs
+            .heads
+            .iter()
+            .filter(|head| !matches!(head.tx.transaction(), Transaction::EIP4844Transaction(_)))
+            .map(|head| (head.tx.transaction(), head.tx.sender()))
+            .collect();
+        let speculations = self
+            .vm
+            .execute_txs_speculatively(&heads, &self.payload.header);
+        self.speculations = heads
+            .iter()
+            .zip(speculations)
+            .filter_map(|((tx, _), speculation)| Some((tx.compute_hash(), speculation?)))
+            .collect();
+    }
+
     fn parent_hash(&self) -> BlockHash {
         self.payload.header.parent_hash
     }
@@ -381,6 +403,9 @@ impl Blockchain {
         debug!("Fetching transactions from mempool");
         // Fetch mempool transactions
         let (mut plain_txs, mut blob_txs) = self.fetch_mempool_transactions(context)?;
+        if self.parallel_execution {
+            context.speculate_transactions(&plain_txs);
+        }
         // Execute and add transactions to payload (if suitable)
         loop {
             // Check if we have enough gas to run more transactions
@@ -564,11 +589,17 @@ pub fn apply_plain_transaction(
     head: &HeadTransaction,
     context: &mut PayloadBuildContext,
 ) -> Result<Receipt, ChainError> {
-    let (report, gas_used) = context.vm.execute_tx(
+    let speculation = if context.speculations.is_empty() {
+        None
+    } else {
+        context.speculations.remove(&head.tx.compute_hash())
+    };
+    let (report, gas_used) = context.vm.execute_speculated_tx(
         &head.tx,
         &context.payload.header,
         &mut context.remaining_gas,
         head.tx.sender(),
+        speculation,
     )?;
     context.block_value += U256::from(gas_used) * head.tip;
     Ok(report)
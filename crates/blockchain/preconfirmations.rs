@@ -0,0 +1,254 @@
+use std::{collections::BTreeMap, ops::Bound, sync::RwLock};
+
+use ethrex_common::{
+    H256, Signature,
+    types::{
+        BlockNumber,
+        preconfirmation::{Preconfirmation, PreconfirmationId},
+    },
+};
+use ethrex_storage::error::StoreError;
+
+/// Preconfirmations announced by the L2 sequencer for the blocks it's building.
+/// They are kept until the block after theirs is sealed.
+#[derive(Debug, Default)]
+pub struct PreconfirmationPool {
+    /// Preconfirmations along with the signature of the sequencer that announced them,
+    /// once they are sent to or received from a peer
+    preconfirmations: RwLock<BTreeMap<PreconfirmationId, (Preconfirmation, Option<Signature>)>>,
+}
+
+impl PreconfirmationPool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a preconfirmation to the pool, replacing the one with the same id if any
+    pub fn add_preconfirmation(
+        &self,
+        preconfirmation: Preconfirmation,
+        signature: Option<Signature>,
+    ) -> Result<(), StoreError> {
+        self.preconfirmations
+            .write()
+            .map_err(|error| StoreError::Custom(error.to_string()))?
+            .insert(preconfirmation.id(), (preconfirmation, signature));
+        Ok(())
+    }
+
+    /// Set the signature of a preconfirmation of the pool
+    pub fn set_signature(
+        &self,
+        id: PreconfirmationId,
+        signature: Signature,
+    ) -> Result<(), StoreError> {
+        if let Some((_, stored_signature)) = self
+            .preconfirmations
+            .write()
+            .map_err(|error| StoreError::Custom(error.to_string()))?
+            .get_mut(&id)
+        {
+            *stored_signature = Some(signature);
+        }
+        Ok(())
+    }
+
+    pub fn contains(&self, id: PreconfirmationId) -> Result<bool, StoreError> {
+        Ok(self
+            .preconfirmations
+            .read()
+            .map_err(|error| StoreError::Custom(error.to_string()))?
+            .contains_key(&id))
+    }
+
+    /// Id of the latest preconfirmation in the pool
+    pub fn last_id(&self) -> Result<Option<PreconfirmationId>, StoreError> {
+        Ok(self
+            .preconfirmations
+            .read()
+            .map_err(|error| StoreError::Custom(error.to_string()))?
+            .last_key_value()
+            .map(|(id, _)| *id))
+    }
+
+    /// Preconfirmations announced after the given one, or all of them if it's `None`
+    pub fn get_preconfirmations_after(
+        &self,
+        id: Option<PreconfirmationId>,
+    ) -> Result<Vec<(Preconfirmation, Option<Signature>)>, StoreError> {
+        let preconfirmations = self
+            .preconfirmations
+            .read()
+            .map_err(|error| StoreError::Custom(error.to_string()))?;
+        // The given one may have been removed already, so it's excluded from the range
+        // instead of skipped
+        let start = match id {
+            Some(id) => Bound::Excluded(id),
+            None => Bound::Unbounded,
+        };
+        Ok(preconfirmations
+            .range((start, Bound::Unbounded))
+            .map(|(_, preconfirmation)| preconfirmation.clone())
+            .collect())
+    }
+
+    /// Preconfirmations of a block, in order
+    pub fn get_block_preconfirmations(
+        &self,
+        block_number: BlockNumber,
+    ) -> Result<Vec<Preconfirmation>, StoreError> {
+        Ok(self
+            .preconfirmations
+            .read()
+            .map_err(|error| StoreError::Custom(error.to_string()))?
+            .range((block_number, 0)..=(block_number, u64::MAX))
+            .map(|(_, (preconfirmation, _))| preconfirmation.clone())
+            .collect())
+    }
+
+    /// Number of the block that includes the given transaction, if it was preconfirmed
+    pub fn get_transaction_block(&self, tx_hash: H256) -> Result<Option<BlockNumber>, StoreError> {
+        Ok(self
+            .preconfirmations
+            .read()
+            .map_err(|error| StoreError::Custom(error.to_string()))?
+            .values()
+            .find(|(preconfirmation, _)| {
+                preconfirmation
+                    .transactions
+                    .iter()
+                    .any(|tx| tx.compute_hash() == tx_hash)
+            })
+            .map(|(preconfirmation, _)| preconfirmation.block_number))
+    }
+
+    /// Remove the preconfirmations of the blocks before the given one, called when it's sealed
+    pub fn remove_preconfirmations_before(
+        &self,
+        block_number: BlockNumber,
+    ) -> Result<(), StoreError> {
+        let mut preconfirmations = self
+            .preconfirmations
+            .write()
+            .map_err(|error| StoreError::Custom(error.to_string()))?;
+        *preconfirmations = preconfirmations.split_off(&(block_number, 0));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethrex_common::types::{EIP1559Transaction, Transaction};
+
+    fn preconfirmation(block_number: BlockNumber, index: u64) -> Preconfirmation {
+        Preconfirmation {
+            block_number,
+            index,
+            timestamp: block_number,
+            base_fee_per_gas: 1,
+            // The nonce makes the hash of each transaction different
+            transactions: vec![Transaction::EIP1559Transaction(EIP1559Transaction {
+                nonce: block_number * 100 + index,
+                ..Default::default()
+            })],
+            receipts: Vec::new(),
+        }
+    }
+
+    fn ids(preconfirmations: Vec<(Preconfirmation, Option<Signature>)>) -> Vec<PreconfirmationId> {
+        preconfirmations
+            .into_iter()
+            .map(|(preconfirmation, _)| preconfirmation.id())
+            .collect()
+    }
+
+    /// Pool with the preconfirmations of blocks 5 and 6, added out of order
+    fn pool() -> PreconfirmationPool {
+        let pool = PreconfirmationPool::new();
+        for (block_number, index) in [(6, 0), (5, 1), (5, 0), (6, 1)] {
+            pool.add_preconfirmation(preconfirmation(block_number, index), None)
+                .unwrap();
+        }
+        pool
+    }
+
+    #[test]
+    fn preconfirmations_are_returned_in_order() {
+        let pool = pool();
+
+        assert_eq!(pool.last_id().unwrap(), Some((6, 1)));
+        assert_eq!(
+            ids(pool.get_preconfirmations_after(None).unwrap()),
+            vec![(5, 0), (5, 1), (6, 0), (6, 1)]
+        );
+        assert_eq!(
+            ids(pool.get_preconfirmations_after(Some((5, 1))).unwrap()),
+            vec![(6, 0), (6, 1)]
+        );
+        assert!(
+            pool.get_preconfirmations_after(Some((6, 1)))
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn block_preconfirmations_are_in_range() {
+        let pool = pool();
+
+        let block_preconfirmations: Vec<_> = pool
+            .get_block_preconfirmations(5)
+            .unwrap()
+            .iter()
+            .map(Preconfirmation::id)
+            .collect();
+        assert_eq!(block_preconfirmations, vec![(5, 0), (5, 1)]);
+        assert!(pool.get_block_preconfirmations(7).unwrap().is_empty());
+    }
+
+    #[test]
+    fn preconfirmations_before_a_sealed_block_are_removed() {
+        let pool = pool();
+        let removed_tx_hash = preconfirmation(5, 1)
+            .transactions
+            .first()
+            .unwrap()
+            .compute_hash();
+        let kept_tx_hash = preconfirmation(6, 0)
+            .transactions
+            .first()
+            .unwrap()
+            .compute_hash();
+
+        pool.remove_preconfirmations_before(6).unwrap();
+
+        assert!(!pool.contains((5, 0)).unwrap());
+        assert!(!pool.contains((5, 1)).unwrap());
+        assert_eq!(pool.get_transaction_block(removed_tx_hash).unwrap(), None);
+        assert_eq!(pool.get_transaction_block(kept_tx_hash).unwrap(), Some(6));
+        // A cursor on a removed preconfirmation still gets the ones after it
+        assert_eq!(
+            ids(pool.get_preconfirmations_after(Some((5, 1))).unwrap()),
+            vec![(6, 0), (6, 1)]
+        );
+    }
+
+    #[test]
+    fn signatures_are_set_on_stored_preconfirmations() {
+        let pool = pool();
+        let signature = Signature::repeat_byte(1);
+
+        pool.set_signature((5, 0), signature).unwrap();
+        pool.set_signature((7, 0), signature).unwrap();
+
+        let signatures: Vec<_> = pool
+            .get_preconfirmations_after(None)
+            .unwrap()
+            .into_iter()
+            .map(|(_, signature)| signature)
+            .collect();
+        assert_eq!(signatures, vec![Some(signature), None, None, None]);
+        assert!(!pool.contains((7, 0)).unwrap());
+    }
+}
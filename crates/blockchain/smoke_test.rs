@@ -3,18 +3,27 @@ mod blockchain_integration_test {
     use std::{fs::File, io::BufReader};
 
     use crate::{
-        Blockchain,
+        Blockchain, BlockchainType,
         error::{ChainError, InvalidForkChoice},
         fork_choice::apply_fork_choice,
         is_canonical, latest_canonical_block_hash,
         payload::{BuildPayloadArgs, create_payload},
+        validate_block_stateless,
     };
 
     use ethrex_common::{
-        H160, H256,
-        types::{Block, BlockHeader, ELASTICITY_MULTIPLIER},
+        Address, Bytes, H160, H256, U256,
+        types::{
+            Block, BlockHeader, EIP1559Transaction, ELASTICITY_MULTIPLIER, FeeConfig, Genesis,
+            GenesisAccount, Transaction, TxKind, TxType,
+        },
     };
+    use ethrex_rlp::encode::PayloadRLPEncode;
     use ethrex_storage::{EngineType, Store};
+    use ethrex_vm::EvmEngine;
+    use secp256k1::{Message, SECP256K1, SecretKey};
+    use sha3::{Digest, Keccak256};
+    use tokio_util::sync::CancellationToken;
 
     #[tokio::test]
     async fn test_small_to_long_reorg() {
@@ -295,6 +304,239 @@ mod blockchain_integration_test {
         assert_eq!(latest_canonical_block_hash(&store).await.unwrap(), hash_b);
     }
 
+    #[tokio::test]
+    async fn stateless_validation_from_execution_witness() {
+        let store = test_store().await;
+        let genesis_header = store.get_block_header(0).unwrap().unwrap();
+        let blockchain = Blockchain::default_with_store(store.clone());
+
+        let block_1 = new_block(&store, &genesis_header).await;
+        blockchain.add_block(&block_1).await.unwrap();
+
+        let witness = blockchain
+            .generate_witness_for_blocks(&[block_1.clone()])
+            .await
+            .unwrap();
+        let state_root =
+            validate_block_stateless(&block_1, witness, store.get_chain_config().unwrap()).unwrap();
+        assert_eq!(state_root, block_1.header.state_root);
+
+        // A block claiming a different post-state is rejected
+        let mut invalid_block = block_1.clone();
+        invalid_block.header.state_root = H256::repeat_byte(1);
+        let witness = blockchain
+            .generate_witness_for_blocks(&[block_1])
+            .await
+            .unwrap();
+        assert!(matches!(
+            validate_block_stateless(&invalid_block, witness, store.get_chain_config().unwrap()),
+            Err(ChainError::InvalidBlock(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn witnesses_are_recorded_during_import() {
+        let store = test_store().await;
+        let genesis_header = store.get_block_header(0).unwrap().unwrap();
+        let blockchain =
+            Blockchain::default_with_store(store.clone()).with_witness_retention(Some(1));
+
+        let block_1 = new_block(&store, &genesis_header).await;
+        blockchain.add_block(&block_1).await.unwrap();
+        let witness = store
+            .get_execution_witness(block_1.hash())
+            .await
+            .unwrap()
+            .expect("witness recorded during import");
+        let state_root =
+            validate_block_stateless(&block_1, witness, store.get_chain_config().unwrap()).unwrap();
+        assert_eq!(state_root, block_1.header.state_root);
+
+        // Only the witness of the latest block is retained
+        let block_2 = new_block(&store, &block_1.header).await;
+        blockchain.add_block(&block_2).await.unwrap();
+        assert!(
+            store
+                .get_execution_witness(block_1.hash())
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            store
+                .get_execution_witness(block_2.hash())
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[tokio::test]
+    async fn witnesses_are_recorded_during_batch_import() {
+        // Build the chain on a separate store
+        let source_store = test_store().await;
+        let source_blockchain = Blockchain::default_with_store(source_store.clone());
+        let mut parent = source_store.get_block_header(0).unwrap().unwrap();
+        let mut blocks = Vec::new();
+        for _ in 0..3 {
+            let block = new_block(&source_store, &parent).await;
+            source_blockchain.add_block(&block).await.unwrap();
+            parent = block.header.clone();
+            blocks.push(block);
+        }
+
+        let store = test_store().await;
+        let blockchain =
+            Blockchain::default_with_store(store.clone()).with_witness_retention(Some(2));
+        blockchain
+            .add_blocks_in_batch(blocks.clone(), CancellationToken::new())
+            .await
+            .unwrap();
+
+        // Only the witnesses of the last two blocks are recorded
+        assert!(
+            store
+                .get_execution_witness(blocks[0].hash())
+                .await
+                .unwrap()
+                .is_none()
+        );
+        for block in &blocks[1..] {
+            let witness = store
+                .get_execution_witness(block.hash())
+                .await
+                .unwrap()
+                .expect("witness recorded during batch import");
+            let state_root =
+                validate_block_stateless(block, witness, store.get_chain_config().unwrap())
+                    .unwrap();
+            assert_eq!(state_root, block.header.state_root);
+        }
+    }
+
+    #[tokio::test]
+    async fn parallel_l2_payload_matches_sequential() {
+        const BASE_FEE_VAULT: Address = H160([0xba; 20]);
+        const RECIPIENT: Address = H160([0xee; 20]);
+        const COINBASE_READER: Address = H160([0xc1; 20]);
+        const VAULT_READER: Address = H160([0xc2; 20]);
+
+        let keys: Vec<SecretKey> = (1..=4)
+            .map(|seed| SecretKey::from_slice(&[seed; 32]).unwrap())
+            .collect();
+        let mut genesis = test_genesis();
+        genesis.config.fee_config = Some(FeeConfig {
+            base_fee_vault: Some(BASE_FEE_VAULT),
+            operator_fee: None,
+        });
+        // Both readers store the balance they read in slot 0
+        let coinbase_reader = Bytes::from(vec![0x41, 0x31, 0x60, 0x00, 0x55, 0x00]);
+        let vault_reader = Bytes::from(
+            [
+                &[0x73][..],
+                BASE_FEE_VAULT.as_bytes(),
+                &[0x31, 0x60, 0x00, 0x55, 0x00],
+            ]
+            .concat(),
+        );
+        for (address, code) in keys.iter().map(|key| (address(key), Bytes::new())).chain([
+            (COINBASE_READER, coinbase_reader),
+            (VAULT_READER, vault_reader),
+        ]) {
+            genesis.alloc.insert(
+                address,
+                GenesisAccount {
+                    code,
+                    storage: Default::default(),
+                    balance: U256::from(10).pow(18.into()),
+                    nonce: 0,
+                },
+            );
+        }
+        let chain_id = genesis.config.chain_id;
+        let store = test_store_with_genesis(genesis).await;
+        let genesis_header = store.get_block_header(0).unwrap().unwrap();
+
+        // The tips are different so both payloads include the transactions in the same order.
+        // The first sender conflicts with itself, all of them send to the same recipient, and
+        // the last two read values that are only updated when the transactions before are committed.
+        let txs = vec![
+            signed_tx(&keys[0], chain_id, 0, 4, RECIPIENT),
+            signed_tx(&keys[0], chain_id, 1, 4, RECIPIENT),
+            signed_tx(&keys[1], chain_id, 0, 3, RECIPIENT),
+            signed_tx(&keys[2], chain_id, 0, 2, COINBASE_READER),
+            signed_tx(&keys[3], chain_id, 0, 1, VAULT_READER),
+        ];
+        let args = BuildPayloadArgs {
+            parent: genesis_header.hash(),
+            timestamp: genesis_header.timestamp + 12,
+            fee_recipient: H160([0xc0; 20]),
+            random: H256::zero(),
+            withdrawals: Some(Vec::new()),
+            beacon_root: Some(H256::zero()),
+            version: 1,
+            elasticity_multiplier: ELASTICITY_MULTIPLIER,
+        };
+
+        let mut results = Vec::new();
+        for parallel_execution in [false, true] {
+            let blockchain = Blockchain::new(
+                EvmEngine::default(),
+                store.clone(),
+                BlockchainType::L2,
+                parallel_execution,
+            );
+            for tx in &txs {
+                blockchain
+                    .add_transaction_to_pool(tx.clone())
+                    .await
+                    .unwrap();
+            }
+            let block = create_payload(&args, &store).unwrap();
+            results.push(blockchain.build_payload(block).await.unwrap());
+        }
+        let (sequential, parallel) = (&results[0], &results[1]);
+
+        assert_eq!(sequential.payload.body.transactions.len(), txs.len());
+        assert_eq!(parallel.payload, sequential.payload);
+        assert_eq!(parallel.receipts, sequential.receipts);
+        assert_eq!(parallel.block_value, sequential.block_value);
+    }
+
+    fn address(key: &SecretKey) -> Address {
+        let public_key = key.public_key(SECP256K1).serialize_uncompressed();
+        Address::from_slice(&Keccak256::digest(&public_key[1..])[12..])
+    }
+
+    fn signed_tx(
+        key: &SecretKey,
+        chain_id: u64,
+        nonce: u64,
+        tip_gwei: u64,
+        to: Address,
+    ) -> Transaction {
+        let mut tx = EIP1559Transaction {
+            chain_id,
+            nonce,
+            max_priority_fee_per_gas: tip_gwei * 1_000_000_000,
+            max_fee_per_gas: 100_000_000_000,
+            gas_limit: 100_000,
+            to: TxKind::Call(to),
+            value: U256::one(),
+            ..Default::default()
+        };
+        let mut payload = vec![TxType::EIP1559 as u8];
+        payload.append(&mut tx.encode_payload_to_vec());
+        let message = Message::from_digest(Keccak256::digest(payload).into());
+        let (recovery_id, signature) = SECP256K1
+            .sign_ecdsa_recoverable(&message, key)
+            .serialize_compact();
+        tx.signature_r = U256::from_big_endian(&signature[..32]);
+        tx.signature_s = U256::from_big_endian(&signature[32..]);
+        tx.signature_y_parity = recovery_id.to_i32() != 0;
+        Transaction::EIP1559Transaction(tx)
+    }
+
     async fn new_block(store: &Store, parent: &BlockHeader) -> Block {
         let args = BuildPayloadArgs {
             parent: parent.hash(),
@@ -315,13 +557,18 @@ mod blockchain_integration_test {
         result.payload
     }
 
-    async fn test_store() -> Store {
-        // Get genesis
+    fn test_genesis() -> Genesis {
         let file = File::open("../../fixtures/genesis/execution-api.json")
             .expect("Failed to open genesis file");
         let reader = BufReader::new(file);
-        let genesis = serde_json::from_reader(reader).expect("Failed to deserialize genesis file");
+        serde_json::from_reader(reader).expect("Failed to deserialize genesis file")
+    }
+
+    async fn test_store() -> Store {
+        test_store_with_genesis(test_genesis()).await
+    }
 
+    async fn test_store_with_genesis(genesis: Genesis) -> Store {
         // Build store with genesis
         let store =
             Store::new("store.db", EngineType::InMemory).expect("Failed to build DB for testing");
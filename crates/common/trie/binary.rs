@@ -0,0 +1,749 @@
+//! Prototype of the binary trie of EIP-7864, which replaces the hexary Merkle Patricia Trie and the
+//! per-account storage tries with a single tree merkleized with SHA-256.
+//!
+//! Keys are 32 bytes long: the first 31 bytes (the stem) select a stem node through a binary tree of
+//! internal nodes, and the last byte selects one of the 256 values of the stem node.
+//! The spec is still a draft, so this lives behind the `binary-trie` feature.
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::OnceLock,
+};
+
+use ethereum_types::{Address, H256, U256};
+use sha2::{Digest, Sha256};
+
+use crate::{NodeHash, TrieDB, TrieError};
+
+pub type Stem = [u8; 31];
+pub type BinaryKey = [u8; 32];
+pub type BinaryValue = [u8; 32];
+
+pub const BASIC_DATA_LEAF_KEY: u8 = 0;
+pub const CODE_HASH_LEAF_KEY: u8 = 1;
+pub const HEADER_STORAGE_OFFSET: u64 = 64;
+pub const CODE_OFFSET: u64 = 128;
+pub const STEM_SUBTREE_WIDTH: u64 = 256;
+/// Code is split in chunks of 31 bytes, prefixed by the amount of push data bytes they start with
+const CODE_CHUNK_SIZE: usize = 31;
+
+const INTERNAL_NODE_PREFIX: u8 = 0;
+const STEM_NODE_PREFIX: u8 = 1;
+
+// ================== Key derivation ======================
+
+/// Derives the key of a value of the account, `sha256(address32 || tree_index)[..31] || sub_index`
+pub fn get_tree_key(address: &Address, tree_index: U256, sub_index: u8) -> BinaryKey {
+    let mut input = [0u8; 64];
+    input[12..32].copy_from_slice(address.as_bytes());
+    input[32..].copy_from_slice(&tree_index.to_little_endian());
+    let mut key: BinaryKey = Sha256::digest(input).into();
+    key[31] = sub_index;
+    key
+}
+
+pub fn get_tree_key_for_basic_data(address: &Address) -> BinaryKey {
+    get_tree_key(address, U256::zero(), BASIC_DATA_LEAF_KEY)
+}
+
+pub fn get_tree_key_for_code_hash(address: &Address) -> BinaryKey {
+    get_tree_key(address, U256::zero(), CODE_HASH_LEAF_KEY)
+}
+
+/// The first 64 storage slots share the stem of the account header, the rest are spread in the main storage
+pub fn get_tree_key_for_storage_slot(address: &Address, storage_key: U256) -> BinaryKey {
+    if storage_key < U256::from(CODE_OFFSET - HEADER_STORAGE_OFFSET) {
+        let sub_index = HEADER_STORAGE_OFFSET as u8 + storage_key.low_u32() as u8;
+        get_tree_key(address, U256::zero(), sub_index)
+    } else {
+        // The main storage starts at 256^31, so the tree index is 256^30 + storage_key / 256
+        let tree_index = (U256::one() << 240) + (storage_key >> 8);
+        get_tree_key(address, tree_index, storage_key.byte(0))
+    }
+}
+
+pub fn get_tree_key_for_code_chunk(address: &Address, chunk_id: u64) -> BinaryKey {
+    let position = U256::from(CODE_OFFSET) + chunk_id;
+    get_tree_key(address, position / STEM_SUBTREE_WIDTH, position.byte(0))
+}
+
+/// Packs the version, code size, nonce and balance of an account in a single leaf
+pub fn basic_data_leaf(code_size: u32, nonce: u64, balance: U256) -> BinaryValue {
+    let mut leaf = [0u8; 32];
+    // leaf[0] is the version and leaf[1..5] are reserved
+    leaf[5..8].copy_from_slice(&code_size.to_be_bytes()[1..]);
+    leaf[8..16].copy_from_slice(&nonce.to_be_bytes());
+    leaf[16..].copy_from_slice(&balance.to_big_endian()[16..]);
+    leaf
+}
+
+/// Splits the code in 32 byte chunks, made of the amount of leading bytes that are push data
+/// followed by 31 bytes of code
+pub fn chunkify_code(code: &[u8]) -> Vec<BinaryValue> {
+    const PUSH1: u8 = 0x60;
+    const PUSH32: u8 = 0x7f;
+
+    let mut padded = code.to_vec();
+    padded.resize(code.len().div_ceil(CODE_CHUNK_SIZE) * CODE_CHUNK_SIZE, 0);
+
+    // Amount of push data bytes remaining at each position
+    let mut push_data = vec![0u8; padded.len() + 32];
+    let mut pc = 0;
+    while pc < padded.len() {
+        let opcode = padded[pc];
+        pc += 1;
+        if (PUSH1..=PUSH32).contains(&opcode) {
+            let push_bytes = opcode - PUSH1 + 1;
+            for offset in 0..push_bytes {
+                push_data[pc + offset as usize] = push_bytes - offset;
+            }
+            pc += push_bytes as usize;
+        }
+    }
+
+    padded
+        .chunks(CODE_CHUNK_SIZE)
+        .enumerate()
+        .map(|(index, chunk)| {
+            let mut leaf = [0u8; 32];
+            leaf[0] = push_data[index * CODE_CHUNK_SIZE].min(CODE_CHUNK_SIZE as u8);
+            leaf[1..].copy_from_slice(chunk);
+            leaf
+        })
+        .collect()
+}
+
+// ================== Merkleization ======================
+
+/// SHA-256 of the data, except for 64 zero bytes (two empty subtrees) which hash to zero
+fn hash(data: &[u8]) -> H256 {
+    if data.len() == 64 && data.iter().all(|byte| *byte == 0) {
+        return H256::zero();
+    }
+    H256::from_slice(&Sha256::digest(data))
+}
+
+fn hash_pair(left: H256, right: H256) -> H256 {
+    let mut data = [0u8; 64];
+    data[..32].copy_from_slice(left.as_bytes());
+    data[32..].copy_from_slice(right.as_bytes());
+    hash(&data)
+}
+
+// ================== Nodes ======================
+
+/// Stem node, holding up to 256 values sharing the same 31 byte stem
+#[derive(Debug, Clone)]
+pub struct StemNode {
+    pub stem: Stem,
+    values: BTreeMap<u8, BinaryValue>,
+    /// Hash of the node, computed once until its values change
+    hash: OnceLock<H256>,
+}
+
+impl StemNode {
+    fn new(stem: Stem) -> Self {
+        Self {
+            stem,
+            values: BTreeMap::new(),
+            hash: OnceLock::new(),
+        }
+    }
+
+    pub fn values(&self) -> &BTreeMap<u8, BinaryValue> {
+        &self.values
+    }
+
+    fn insert(&mut self, index: u8, value: BinaryValue) {
+        self.hash.take();
+        self.values.insert(index, value);
+    }
+
+    fn remove(&mut self, index: u8) -> Option<BinaryValue> {
+        self.hash.take();
+        self.values.remove(&index)
+    }
+
+    fn hash(&self) -> H256 {
+        *self.hash.get_or_init(|| self.compute_hash())
+    }
+
+    fn compute_hash(&self) -> H256 {
+        let mut level: Vec<H256> = (0..=u8::MAX)
+            .map(|index| {
+                self.values
+                    .get(&index)
+                    .map(|value| hash(value))
+                    .unwrap_or_default()
+            })
+            .collect();
+        while level.len() > 1 {
+            level = level
+                .chunks(2)
+                .map(|pair| hash_pair(pair[0], pair[1]))
+                .collect();
+        }
+
+        let mut data = [0u8; 64];
+        data[..31].copy_from_slice(&self.stem);
+        data[32..].copy_from_slice(level[0].as_bytes());
+        hash(&data)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+enum BinaryNode {
+    #[default]
+    Empty,
+    Internal {
+        left: Box<BinaryNode>,
+        right: Box<BinaryNode>,
+        /// Hash of the node, internal nodes aren't modified once built
+        hash: OnceLock<H256>,
+    },
+    Stem(Box<StemNode>),
+    /// Node stored in the database that wasn't loaded yet
+    Hash(H256),
+}
+
+impl BinaryNode {
+    fn internal(left: BinaryNode, right: BinaryNode) -> Self {
+        BinaryNode::Internal {
+            left: Box::new(left),
+            right: Box::new(right),
+            hash: OnceLock::new(),
+        }
+    }
+
+    fn from_hash(hash: H256) -> Self {
+        if hash.is_zero() {
+            BinaryNode::Empty
+        } else {
+            BinaryNode::Hash(hash)
+        }
+    }
+
+    fn hash(&self) -> H256 {
+        match self {
+            BinaryNode::Empty => H256::zero(),
+            BinaryNode::Internal { left, right, hash } => {
+                *hash.get_or_init(|| hash_pair(left.hash(), right.hash()))
+            }
+            BinaryNode::Stem(stem_node) => stem_node.hash(),
+            BinaryNode::Hash(hash) => *hash,
+        }
+    }
+
+    /// Encodes the node, the children of internal nodes are referenced by hash
+    fn encode(&self) -> Vec<u8> {
+        match self {
+            BinaryNode::Internal { left, right, .. } => {
+                let mut encoded = Vec::with_capacity(65);
+                encoded.push(INTERNAL_NODE_PREFIX);
+                encoded.extend_from_slice(left.hash().as_bytes());
+                encoded.extend_from_slice(right.hash().as_bytes());
+                encoded
+            }
+            BinaryNode::Stem(stem_node) => {
+                let mut encoded = Vec::with_capacity(32 + stem_node.values.len() * 33);
+                encoded.push(STEM_NODE_PREFIX);
+                encoded.extend_from_slice(&stem_node.stem);
+                for (index, value) in &stem_node.values {
+                    encoded.push(*index);
+                    encoded.extend_from_slice(value);
+                }
+                encoded
+            }
+            BinaryNode::Empty | BinaryNode::Hash(_) => Vec::new(),
+        }
+    }
+
+    fn decode(encoded: &[u8]) -> Result<Self, TrieError> {
+        let invalid = || TrieError::Verify("Invalid binary trie node encoding".to_string());
+        match encoded.split_first() {
+            Some((&INTERNAL_NODE_PREFIX, children)) if children.len() == 64 => {
+                Ok(BinaryNode::internal(
+                    BinaryNode::from_hash(H256::from_slice(&children[..32])),
+                    BinaryNode::from_hash(H256::from_slice(&children[32..])),
+                ))
+            }
+            Some((&STEM_NODE_PREFIX, data)) if data.len() >= 31 && (data.len() - 31) % 33 == 0 => {
+                let mut stem_node = StemNode::new(data[..31].try_into().map_err(|_| invalid())?);
+                for entry in data[31..].chunks(33) {
+                    let value = entry[1..].try_into().map_err(|_| invalid())?;
+                    stem_node.insert(entry[0], value);
+                }
+                Ok(BinaryNode::Stem(Box::new(stem_node)))
+            }
+            _ => Err(invalid()),
+        }
+    }
+
+    /// Loads the node from the database if it wasn't loaded yet
+    fn resolve(self, db: &dyn TrieDB) -> Result<Self, TrieError> {
+        match self {
+            BinaryNode::Hash(hash) => {
+                let encoded = db
+                    .get(NodeHash::Hashed(hash))?
+                    .ok_or(TrieError::InconsistentTree)?;
+                BinaryNode::decode(&encoded)
+            }
+            node => Ok(node),
+        }
+    }
+}
+
+/// Bit of the stem that selects the child of an internal node at the given depth
+fn stem_bit(stem: &Stem, depth: usize) -> bool {
+    stem[depth / 8] & (0x80 >> (depth % 8)) != 0
+}
+
+fn split_key(key: &BinaryKey) -> (Stem, u8) {
+    let mut stem = [0u8; 31];
+    stem.copy_from_slice(&key[..31]);
+    (stem, key[31])
+}
+
+/// Builds the internal nodes needed to hold two stem nodes with different stems
+fn split_stems(existing: Box<StemNode>, new: Box<StemNode>, depth: usize) -> BinaryNode {
+    let existing_bit = stem_bit(&existing.stem, depth);
+    let (left, right) = if existing_bit == stem_bit(&new.stem, depth) {
+        let child = split_stems(existing, new, depth + 1);
+        if existing_bit {
+            (BinaryNode::Empty, child)
+        } else {
+            (child, BinaryNode::Empty)
+        }
+    } else if existing_bit {
+        (BinaryNode::Stem(new), BinaryNode::Stem(existing))
+    } else {
+        (BinaryNode::Stem(existing), BinaryNode::Stem(new))
+    };
+    BinaryNode::internal(left, right)
+}
+
+// ================== Trie ======================
+
+/// Binary trie of EIP-7864 backed by a [TrieDB], nodes are stored by their SHA-256 hash.
+pub struct BinaryTrie {
+    db: Box<dyn TrieDB>,
+    root: BinaryNode,
+}
+
+impl BinaryTrie {
+    /// Creates a new empty trie
+    pub fn new(db: Box<dyn TrieDB>) -> Self {
+        Self {
+            db,
+            root: BinaryNode::Empty,
+        }
+    }
+
+    /// Opens a trie with the given root, whose nodes are in the database
+    pub fn open(db: Box<dyn TrieDB>, root: H256) -> Self {
+        Self {
+            db,
+            root: BinaryNode::from_hash(root),
+        }
+    }
+
+    /// Rebuilds a trie from a witness, with nodes encoded as returned by [BinaryTrie::get_proof]
+    pub fn from_nodes(root: H256, nodes: &[Vec<u8>]) -> Result<Self, TrieError> {
+        let db = crate::InMemoryTrieDB::new_empty();
+        let mut key_values = Vec::with_capacity(nodes.len());
+        for encoded in nodes {
+            let hash = BinaryNode::decode(encoded)?.hash();
+            key_values.push((NodeHash::Hashed(hash), encoded.clone()));
+        }
+        db.put_batch(key_values)?;
+        Ok(Self::open(Box::new(db), root))
+    }
+
+    /// Returns the value of the key, if present
+    pub fn get(&self, key: &BinaryKey) -> Result<Option<BinaryValue>, TrieError> {
+        let (stem, index) = split_key(key);
+        let mut node = self.root.clone();
+        let mut depth = 0;
+        loop {
+            node = match node.resolve(self.db.as_ref())? {
+                BinaryNode::Empty | BinaryNode::Hash(_) => return Ok(None),
+                BinaryNode::Stem(stem_node) => {
+                    return Ok(if stem_node.stem == stem {
+                        stem_node.values.get(&index).copied()
+                    } else {
+                        None
+                    });
+                }
+                BinaryNode::Internal { left, right, .. } => {
+                    let child = if stem_bit(&stem, depth) { right } else { left };
+                    depth += 1;
+                    *child
+                }
+            };
+        }
+    }
+
+    pub fn insert(&mut self, key: &BinaryKey, value: BinaryValue) -> Result<(), TrieError> {
+        let (stem, index) = split_key(key);
+        let root = std::mem::take(&mut self.root);
+        self.root = Self::insert_at(self.db.as_ref(), root, 0, stem, index, value)?;
+        Ok(())
+    }
+
+    fn insert_at(
+        db: &dyn TrieDB,
+        node: BinaryNode,
+        depth: usize,
+        stem: Stem,
+        index: u8,
+        value: BinaryValue,
+    ) -> Result<BinaryNode, TrieError> {
+        Ok(match node.resolve(db)? {
+            BinaryNode::Empty | BinaryNode::Hash(_) => {
+                let mut stem_node = StemNode::new(stem);
+                stem_node.insert(index, value);
+                BinaryNode::Stem(Box::new(stem_node))
+            }
+            BinaryNode::Stem(mut stem_node) if stem_node.stem == stem => {
+                stem_node.insert(index, value);
+                BinaryNode::Stem(stem_node)
+            }
+            BinaryNode::Stem(stem_node) => {
+                let mut new_stem_node = StemNode::new(stem);
+                new_stem_node.insert(index, value);
+                split_stems(stem_node, Box::new(new_stem_node), depth)
+            }
+            BinaryNode::Internal { left, right, .. } => {
+                if stem_bit(&stem, depth) {
+                    let right = Self::insert_at(db, *right, depth + 1, stem, index, value)?;
+                    BinaryNode::internal(*left, right)
+                } else {
+                    let left = Self::insert_at(db, *left, depth + 1, stem, index, value)?;
+                    BinaryNode::internal(left, *right)
+                }
+            }
+        })
+    }
+
+    /// Removes the value of the key, returning it if it was present.
+    /// Internal nodes left with a single stem node are collapsed, so the root doesn't depend on removed values.
+    pub fn remove(&mut self, key: &BinaryKey) -> Result<Option<BinaryValue>, TrieError> {
+        let (stem, index) = split_key(key);
+        let root = std::mem::take(&mut self.root);
+        let (root, removed) = Self::remove_at(self.db.as_ref(), root, 0, stem, index)?;
+        self.root = root;
+        Ok(removed)
+    }
+
+    fn remove_at(
+        db: &dyn TrieDB,
+        node: BinaryNode,
+        depth: usize,
+        stem: Stem,
+        index: u8,
+    ) -> Result<(BinaryNode, Option<BinaryValue>), TrieError> {
+        Ok(match node.resolve(db)? {
+            BinaryNode::Stem(mut stem_node) if stem_node.stem == stem => {
+                let removed = stem_node.remove(index);
+                if stem_node.values.is_empty() {
+                    (BinaryNode::Empty, removed)
+                } else {
+                    (BinaryNode::Stem(stem_node), removed)
+                }
+            }
+            BinaryNode::Internal { left, right, .. } => {
+                let (left, right, removed) = if stem_bit(&stem, depth) {
+                    let (right, removed) = Self::remove_at(db, *right, depth + 1, stem, index)?;
+                    (*left, right, removed)
+                } else {
+                    let (left, removed) = Self::remove_at(db, *left, depth + 1, stem, index)?;
+                    (left, *right, removed)
+                };
+                // A stem node left alone is moved up, its position only depends on the stems around it
+                let left = if matches!(right, BinaryNode::Empty) {
+                    left.resolve(db)?
+                } else {
+                    left
+                };
+                let right = if matches!(left, BinaryNode::Empty) {
+                    right.resolve(db)?
+                } else {
+                    right
+                };
+                let node = match (left, right) {
+                    (BinaryNode::Empty, BinaryNode::Empty) => BinaryNode::Empty,
+                    (BinaryNode::Empty, stem_node @ BinaryNode::Stem(_))
+                    | (stem_node @ BinaryNode::Stem(_), BinaryNode::Empty) => stem_node,
+                    (left, right) => BinaryNode::internal(left, right),
+                };
+                (node, removed)
+            }
+            node => (node, None),
+        })
+    }
+
+    /// Computes the root of the trie
+    pub fn hash(&self) -> H256 {
+        self.root.hash()
+    }
+
+    /// Stores the modified nodes in the database and returns the root
+    pub fn commit(&mut self) -> Result<H256, TrieError> {
+        let mut key_values = Vec::new();
+        let root = std::mem::take(&mut self.root);
+        self.root = Self::collect_nodes(root, &mut key_values);
+        self.db.put_batch(key_values)?;
+        Ok(self.hash())
+    }
+
+    /// Collects the encoded nodes loaded in memory, replacing them by their hash
+    fn collect_nodes(node: BinaryNode, key_values: &mut Vec<(NodeHash, Vec<u8>)>) -> BinaryNode {
+        let node = match node {
+            BinaryNode::Internal { left, right, hash } => BinaryNode::Internal {
+                left: Box::new(Self::collect_nodes(*left, key_values)),
+                right: Box::new(Self::collect_nodes(*right, key_values)),
+                hash,
+            },
+            node @ BinaryNode::Stem(_) => node,
+            node @ (BinaryNode::Empty | BinaryNode::Hash(_)) => return node,
+        };
+        let hash = node.hash();
+        key_values.push((NodeHash::Hashed(hash), node.encode()));
+        BinaryNode::Hash(hash)
+    }
+
+    /// Returns the encoded nodes from the root to the stem node of the key, or to the node in which
+    /// the path diverges from the key if it's not present
+    pub fn get_proof(&self, key: &BinaryKey) -> Result<Vec<Vec<u8>>, TrieError> {
+        let (stem, _) = split_key(key);
+        let mut proof = Vec::new();
+        let mut node = self.root.clone();
+        let mut depth = 0;
+        loop {
+            node = node.resolve(self.db.as_ref())?;
+            if matches!(node, BinaryNode::Empty) {
+                return Ok(proof);
+            }
+            proof.push(node.encode());
+            match node {
+                BinaryNode::Internal { left, right, .. } => {
+                    node = if stem_bit(&stem, depth) {
+                        *right
+                    } else {
+                        *left
+                    };
+                    depth += 1;
+                }
+                _ => return Ok(proof),
+            }
+        }
+    }
+
+    /// Returns the nodes needed to access all the keys, deduplicated
+    pub fn get_witness(&self, keys: &[BinaryKey]) -> Result<Vec<Vec<u8>>, TrieError> {
+        let mut witness = BTreeSet::new();
+        for key in keys {
+            witness.extend(self.get_proof(key)?);
+        }
+        Ok(witness.into_iter().collect())
+    }
+}
+
+/// Verifies the proof of a key against the root, returning its value if it's present
+pub fn verify_proof(
+    root: H256,
+    key: &BinaryKey,
+    proof: &[Vec<u8>],
+) -> Result<Option<BinaryValue>, TrieError> {
+    let (stem, index) = split_key(key);
+    let mut expected_hash = root;
+    for (depth, encoded) in proof.iter().enumerate() {
+        let node = BinaryNode::decode(encoded)?;
+        if node.hash() != expected_hash {
+            return Err(TrieError::Verify(format!(
+                "Proof node at depth {depth} doesn't match its expected hash"
+            )));
+        }
+        match node {
+            BinaryNode::Internal { left, right, .. } => {
+                let child = if stem_bit(&stem, depth) { right } else { left };
+                expected_hash = child.hash();
+            }
+            BinaryNode::Stem(stem_node) => {
+                return Ok(if stem_node.stem == stem {
+                    stem_node.values.get(&index).copied()
+                } else {
+                    None
+                });
+            }
+            BinaryNode::Empty | BinaryNode::Hash(_) => break,
+        }
+    }
+    if expected_hash.is_zero() {
+        Ok(None)
+    } else {
+        Err(TrieError::Verify(
+            "Proof ends before reaching the key".to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::InMemoryTrieDB;
+
+    fn key(stem_byte: u8, index: u8) -> BinaryKey {
+        let mut key = [0u8; 32];
+        key[0] = stem_byte;
+        key[31] = index;
+        key
+    }
+
+    fn new_trie() -> BinaryTrie {
+        BinaryTrie::new(Box::new(InMemoryTrieDB::new_empty()))
+    }
+
+    #[test]
+    fn empty_trie_has_zero_root() {
+        assert_eq!(new_trie().hash(), H256::zero());
+    }
+
+    #[test]
+    fn insert_and_get() {
+        let mut trie = new_trie();
+        trie.insert(&key(0x00, 1), [1; 32]).unwrap();
+        trie.insert(&key(0x80, 1), [2; 32]).unwrap();
+        trie.insert(&key(0x81, 2), [3; 32]).unwrap();
+
+        assert_eq!(trie.get(&key(0x00, 1)).unwrap(), Some([1; 32]));
+        assert_eq!(trie.get(&key(0x80, 1)).unwrap(), Some([2; 32]));
+        assert_eq!(trie.get(&key(0x81, 2)).unwrap(), Some([3; 32]));
+        assert_eq!(trie.get(&key(0x81, 1)).unwrap(), None);
+        assert_eq!(trie.get(&key(0x40, 1)).unwrap(), None);
+    }
+
+    #[test]
+    fn root_is_independent_of_insertion_order_and_commits() {
+        let entries = [
+            (key(0x12, 0), [1; 32]),
+            (key(0x13, 5), [2; 32]),
+            (key(0xf0, 9), [3; 32]),
+        ];
+        let mut trie = new_trie();
+        for (key, value) in entries {
+            trie.insert(&key, value).unwrap();
+        }
+        let root = trie.commit().unwrap();
+
+        let mut reversed = new_trie();
+        for (key, value) in entries.iter().rev() {
+            reversed.insert(key, *value).unwrap();
+        }
+        assert_eq!(reversed.hash(), root);
+
+        // The committed trie can be modified after loading its nodes from the database
+        trie.insert(&key(0x14, 0), [4; 32]).unwrap();
+        reversed.insert(&key(0x14, 0), [4; 32]).unwrap();
+        assert_eq!(trie.hash(), reversed.hash());
+    }
+
+    #[test]
+    fn cached_hashes_follow_modifications() {
+        let mut trie = new_trie();
+        trie.insert(&key(0x00, 1), [1; 32]).unwrap();
+        trie.insert(&key(0x80, 1), [2; 32]).unwrap();
+        let root = trie.hash();
+        assert_eq!(trie.hash(), root);
+
+        // Modifying a stem node already hashed changes the root
+        trie.insert(&key(0x80, 2), [3; 32]).unwrap();
+        let mut expected = new_trie();
+        expected.insert(&key(0x00, 1), [1; 32]).unwrap();
+        expected.insert(&key(0x80, 1), [2; 32]).unwrap();
+        expected.insert(&key(0x80, 2), [3; 32]).unwrap();
+        assert_ne!(trie.hash(), root);
+        assert_eq!(trie.hash(), expected.hash());
+
+        trie.remove(&key(0x80, 2)).unwrap();
+        assert_eq!(trie.hash(), root);
+    }
+
+    #[test]
+    fn remove_restores_previous_root() {
+        let mut trie = new_trie();
+        trie.insert(&key(0x00, 1), [1; 32]).unwrap();
+        let root = trie.commit().unwrap();
+
+        trie.insert(&key(0x01, 1), [2; 32]).unwrap();
+        trie.commit().unwrap();
+        assert_eq!(trie.remove(&key(0x01, 1)).unwrap(), Some([2; 32]));
+        assert_eq!(trie.hash(), root);
+
+        assert_eq!(trie.remove(&key(0x00, 1)).unwrap(), Some([1; 32]));
+        assert_eq!(trie.hash(), H256::zero());
+    }
+
+    #[test]
+    fn proofs_and_witnesses() {
+        let mut trie = new_trie();
+        trie.insert(&key(0x00, 1), [1; 32]).unwrap();
+        trie.insert(&key(0x01, 1), [2; 32]).unwrap();
+        trie.insert(&key(0x80, 7), [3; 32]).unwrap();
+        let root = trie.commit().unwrap();
+
+        let proof = trie.get_proof(&key(0x01, 1)).unwrap();
+        assert_eq!(
+            verify_proof(root, &key(0x01, 1), &proof).unwrap(),
+            Some([2; 32])
+        );
+        let proof = trie.get_proof(&key(0x01, 2)).unwrap();
+        assert_eq!(verify_proof(root, &key(0x01, 2), &proof).unwrap(), None);
+        assert!(verify_proof(H256::repeat_byte(1), &key(0x01, 1), &proof).is_err());
+
+        let witness = trie.get_witness(&[key(0x00, 1), key(0x80, 7)]).unwrap();
+        let stateless = BinaryTrie::from_nodes(root, &witness).unwrap();
+        assert_eq!(stateless.get(&key(0x00, 1)).unwrap(), Some([1; 32]));
+        assert_eq!(stateless.get(&key(0x80, 7)).unwrap(), Some([3; 32]));
+    }
+
+    #[test]
+    fn account_keys_share_the_header_stem() {
+        let address = Address::repeat_byte(0xaa);
+        let basic_data = get_tree_key_for_basic_data(&address);
+        let code_hash = get_tree_key_for_code_hash(&address);
+        let first_slot = get_tree_key_for_storage_slot(&address, U256::zero());
+        let first_chunk = get_tree_key_for_code_chunk(&address, 0);
+        assert_eq!(basic_data[..31], code_hash[..31]);
+        assert_eq!(basic_data[..31], first_slot[..31]);
+        assert_eq!(basic_data[..31], first_chunk[..31]);
+        assert_eq!(first_slot[31], 64);
+        assert_eq!(first_chunk[31], 128);
+
+        let main_slot = get_tree_key_for_storage_slot(&address, U256::from(64));
+        assert_ne!(basic_data[..31], main_slot[..31]);
+        assert_eq!(main_slot[31], 64);
+    }
+
+    #[test]
+    fn code_chunks_track_push_data() {
+        // PUSH32 followed by 32 bytes of data, the second chunk starts with 2 bytes of push data
+        let mut code = vec![0x7f];
+        code.extend_from_slice(&[0xff; 32]);
+        code.push(0x00);
+        let chunks = chunkify_code(&code);
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0][0], 0);
+        assert_eq!(chunks[0][1], 0x7f);
+        assert_eq!(chunks[1][0], 2);
+        assert_eq!(chunks[1][3], 0x00);
+    }
+
+    #[test]
+    fn basic_data_layout() {
+        let leaf = basic_data_leaf(0x010203, 7, U256::from(0x0a0b));
+        assert_eq!(leaf[..5], [0; 5]);
+        assert_eq!(leaf[5..8], [1, 2, 3]);
+        assert_eq!(leaf[15], 7);
+        assert_eq!(leaf[30..], [0x0a, 0x0b]);
+    }
+}
@@ -1,3 +1,5 @@
+#[cfg(feature = "binary-trie")]
+pub mod binary;
 pub mod db;
 pub mod error;
 pub mod logger;
@@ -90,6 +92,11 @@ impl Trie {
         self.db.as_ref()
     }
 
+    /// Consumes the trie, returning its internal database.
+    pub fn into_db(self) -> Box<dyn TrieDB> {
+        self.db
+    }
+
     /// Retrieve an RLP-encoded value from the trie given its RLP-encoded path.
     pub fn get(&self, path: &PathRLP) -> Result<Option<ValueRLP>, TrieError> {
         Ok(match self.root {
@@ -57,6 +57,14 @@ pub fn blob_from_bytes(bytes: Bytes) -> Result<Blob, BlobsBundleError> {
     Ok(buf)
 }
 
+/// Splits the bytes in as many blobs as needed to hold them, see `blob_from_bytes`.
+pub fn blobs_from_bytes(bytes: Bytes) -> Result<Vec<Blob>, BlobsBundleError> {
+    bytes
+        .chunks(SAFE_BYTES_PER_BLOB)
+        .map(|chunk| blob_from_bytes(Bytes::copy_from_slice(chunk)))
+        .collect()
+}
+
 pub fn bytes_from_blob(blob: Bytes) -> [u8; SAFE_BYTES_PER_BLOB] {
     let mut buf = [0u8; SAFE_BYTES_PER_BLOB];
     buf.copy_from_slice(
@@ -177,6 +177,107 @@ pub struct ChainConfig {
     pub blob_schedule: BlobSchedule,
     // Deposits system contract address
     pub deposit_contract_address: Address,
+    /// Where the fees of an L2 go, the base fee is burned if it's not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_config: Option<FeeConfig>,
+    /// Precompiles enabled in an L2 on top of the standard ones
+    #[serde(default, skip_serializing_if = "CustomPrecompiles::is_empty")]
+    pub custom_precompiles: CustomPrecompiles,
+}
+
+/// Precompiles an L2 can enable in its genesis, they're implemented by the VM.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum CustomPrecompile {
+    /// SHA-512 hash of the calldata
+    Sha512,
+}
+
+impl CustomPrecompile {
+    pub const ALL: [CustomPrecompile; 1] = [CustomPrecompile::Sha512];
+
+    fn bit(self) -> u64 {
+        match self {
+            CustomPrecompile::Sha512 => 1,
+        }
+    }
+}
+
+/// Set of custom precompiles enabled in an L2, serialized as the list of their names.
+/// It's a bitset so the chain config stays `Copy`.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(from = "Vec<CustomPrecompile>", into = "Vec<CustomPrecompile>")]
+pub struct CustomPrecompiles(u64);
+
+impl CustomPrecompiles {
+    pub fn insert(&mut self, precompile: CustomPrecompile) {
+        self.0 |= precompile.bit();
+    }
+
+    pub fn contains(&self, precompile: CustomPrecompile) -> bool {
+        self.0 & precompile.bit() != 0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = CustomPrecompile> + '_ {
+        CustomPrecompile::ALL
+            .into_iter()
+            .filter(|precompile| self.contains(*precompile))
+    }
+}
+
+impl From<Vec<CustomPrecompile>> for CustomPrecompiles {
+    fn from(precompiles: Vec<CustomPrecompile>) -> Self {
+        let mut set = CustomPrecompiles::default();
+        for precompile in precompiles {
+            set.insert(precompile);
+        }
+        set
+    }
+}
+
+impl From<CustomPrecompiles> for Vec<CustomPrecompile> {
+    fn from(precompiles: CustomPrecompiles) -> Self {
+        precompiles.iter().collect()
+    }
+}
+
+/// Fee rules of an L2 chain, they're committed with each batch so the provers apply the same ones.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeConfig {
+    /// Receives the base fee instead of burning it
+    #[serde(default)]
+    pub base_fee_vault: Option<Address>,
+    /// Fee charged by the operator on top of the gas
+    #[serde(default)]
+    pub operator_fee: Option<OperatorFeeConfig>,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OperatorFeeConfig {
+    pub vault: Address,
+    /// Wei charged to every transaction
+    pub fixed_fee: u64,
+    /// Wei charged per unit of gas used
+    pub fee_per_gas: u64,
+}
+
+impl FeeConfig {
+    /// Hash committed to the L1 along with each batch, it's also part of the proofs public inputs.
+    pub fn hash(&self) -> H256 {
+        let operator_fee = self.operator_fee.unwrap_or_default();
+        let mut hasher = Keccak256::new();
+        hasher.update(self.base_fee_vault.unwrap_or_default().as_bytes());
+        hasher.update(operator_fee.vault.as_bytes());
+        hasher.update(operator_fee.fixed_fee.to_be_bytes());
+        hasher.update(operator_fee.fee_per_gas.to_be_bytes());
+        H256::from_slice(&hasher.finalize())
+    }
 }
 
 #[repr(u8)]
@@ -790,4 +891,63 @@ mod tests {
         let error_message = result.unwrap_err().to_string();
         assert!(error_message.contains("missing field `depositContractAddress`"),);
     }
+
+    #[test]
+    fn deserialize_chain_config_fee_config() {
+        let json = r#"
+            {
+                "chainId": 123,
+                "depositContractAddress": "0x4242424242424242424242424242424242424242",
+                "feeConfig": {
+                  "baseFeeVault": "0x000000000000000000000000000000000000ffa1",
+                  "operatorFee": {
+                    "vault": "0x000000000000000000000000000000000000ffa2",
+                    "fixedFee": 1000,
+                    "feePerGas": 10
+                  }
+                }
+            }
+            "#;
+
+        let config: ChainConfig =
+            serde_json::from_str(json).expect("Failed to deserialize ChainConfig");
+        let expected_fee_config = FeeConfig {
+            base_fee_vault: Some(
+                H160::from_str("0x000000000000000000000000000000000000ffa1").unwrap(),
+            ),
+            operator_fee: Some(OperatorFeeConfig {
+                vault: H160::from_str("0x000000000000000000000000000000000000ffa2").unwrap(),
+                fixed_fee: 1000,
+                fee_per_gas: 10,
+            }),
+        };
+        assert_eq!(config.fee_config, Some(expected_fee_config));
+    }
+
+    #[test]
+    fn chain_config_custom_precompiles_roundtrip() {
+        let json = r#"
+            {
+                "chainId": 123,
+                "depositContractAddress": "0x4242424242424242424242424242424242424242",
+                "customPrecompiles": ["sha512"]
+            }
+            "#;
+
+        let config: ChainConfig =
+            serde_json::from_str(json).expect("Failed to deserialize ChainConfig");
+        assert!(config.custom_precompiles.contains(CustomPrecompile::Sha512));
+        assert_eq!(
+            config.custom_precompiles.iter().collect::<Vec<_>>(),
+            vec![CustomPrecompile::Sha512]
+        );
+
+        let serialized = serde_json::to_string(&config).unwrap();
+        let deserialized: ChainConfig = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized, config);
+
+        // Chains without custom precompiles keep their serialization
+        let serialized = serde_json::to_value(ChainConfig::default()).unwrap();
+        assert!(serialized.get("customPrecompiles").is_none());
+    }
 }
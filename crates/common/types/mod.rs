@@ -8,6 +8,7 @@ mod constants;
 mod fork_id;
 mod genesis;
 pub mod payload;
+pub mod preconfirmation;
 mod receipt;
 pub mod requests;
 pub mod transaction;
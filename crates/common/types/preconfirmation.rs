@@ -0,0 +1,78 @@
+use ethrex_rlp::{
+    decode::RLPDecode,
+    encode::RLPEncode,
+    error::RLPDecodeError,
+    structs::{Decoder, Encoder},
+};
+use keccak_hash::keccak;
+
+use crate::H256;
+
+use super::{BlockNumber, Receipt, Transaction};
+
+/// Transactions the sequencer executed on top of the block it's building, announced
+/// before the block is sealed. A block contains the transactions of all its
+/// preconfirmations, in order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Preconfirmation {
+    /// Number of the block the transactions will be included in
+    pub block_number: BlockNumber,
+    /// Position of the preconfirmation among the ones of its block, starting from 0
+    pub index: u64,
+    pub timestamp: u64,
+    pub base_fee_per_gas: u64,
+    pub transactions: Vec<Transaction>,
+    /// Receipts of the transactions, with the gas used cumulative over the whole block
+    pub receipts: Vec<Receipt>,
+}
+
+impl Preconfirmation {
+    pub fn id(&self) -> PreconfirmationId {
+        (self.block_number, self.index)
+    }
+
+    /// Hash signed by the sequencer when it announces the preconfirmation.
+    pub fn hash(&self) -> H256 {
+        keccak(self.encode_to_vec())
+    }
+}
+
+/// Identifies a preconfirmation by the number of its block and its index in it,
+/// ordered as they were announced.
+pub type PreconfirmationId = (BlockNumber, u64);
+
+impl RLPEncode for Preconfirmation {
+    fn encode(&self, buf: &mut dyn bytes::BufMut) {
+        Encoder::new(buf)
+            .encode_field(&self.block_number)
+            .encode_field(&self.index)
+            .encode_field(&self.timestamp)
+            .encode_field(&self.base_fee_per_gas)
+            .encode_field(&self.transactions)
+            .encode_field(&self.receipts)
+            .finish();
+    }
+}
+
+impl RLPDecode for Preconfirmation {
+    fn decode_unfinished(rlp: &[u8]) -> Result<(Self, &[u8]), RLPDecodeError> {
+        let decoder = Decoder::new(rlp)?;
+        let (block_number, decoder) = decoder.decode_field("block_number")?;
+        let (index, decoder) = decoder.decode_field("index")?;
+        let (timestamp, decoder) = decoder.decode_field("timestamp")?;
+        let (base_fee_per_gas, decoder) = decoder.decode_field("base_fee_per_gas")?;
+        let (transactions, decoder) = decoder.decode_field("transactions")?;
+        let (receipts, decoder) = decoder.decode_field("receipts")?;
+        Ok((
+            Preconfirmation {
+                block_number,
+                index,
+                timestamp,
+                base_fee_per_gas,
+                transactions,
+                receipts,
+            },
+            decoder.finish()?,
+        ))
+    }
+}
@@ -8,11 +8,11 @@ use ethrex_common::{
     },
 };
 use ethrex_l2_common::{
+    compression::decode_blocks,
     l1_messages::{L1Message, get_block_l1_messages, get_l1_message_hash},
     privileged_transactions::compute_privileged_transactions_hash,
     state_diff::prepare_state_diff,
 };
-use ethrex_rlp::decode::RLPDecode;
 use ethrex_rpc::{EthClient, types::receipt::RpcLog};
 use ethrex_storage::Store;
 use ethrex_storage_rollup::{RollupStoreError, StoreRollup};
@@ -52,6 +52,8 @@ pub enum BlockFetcherError {
     UtilsError(#[from] crate::utils::error::UtilsError),
     #[error("Missing bytes from calldata: {0}")]
     WrongBatchCalldata(String),
+    #[error("Failed to decode the blocks of the batch: {0}")]
+    CompressionError(#[from] ethrex_l2_common::compression::CompressionError),
     #[error("Failed due to an EVM error: {0}")]
     EvmError(#[from] ethrex_vm::EvmError),
     #[error("Failed to produce the blob bundle")]
@@ -245,22 +247,31 @@ impl BlockFetcher {
         missing_batches_logs.sort_by_key(|(_log, batch_number)| *batch_number);
 
         for (batch_committed_log, batch_number) in missing_batches_logs {
-            let batch_commit_tx_calldata = self
+            let batch_commit_tx = self
                 .eth_client
                 .get_transaction_by_hash(batch_committed_log.transaction_hash)
                 .await?
                 .ok_or(BlockFetcherError::InternalError(format!(
                     "Failed to get the receipt for transaction {:x}",
                     batch_committed_log.transaction_hash
-                )))?
-                .data;
+                )))?;
+            // The state diff of the batch spans as many blobs as the commit transaction has
+            let blob_count = batch_commit_tx
+                .blob_versioned_hashes
+                .map(|hashes| hashes.len())
+                .unwrap_or_default();
 
-            let batch = decode_batch_from_calldata(&batch_commit_tx_calldata)?;
+            let batch = decode_batch_from_calldata(&batch_commit_tx.data)?;
 
             self.store_batch(&batch).await?;
 
-            self.seal_batch(&batch, batch_number, batch_committed_log.transaction_hash)
-                .await?;
+            self.seal_batch(
+                &batch,
+                batch_number,
+                batch_committed_log.transaction_hash,
+                blob_count,
+            )
+            .await?;
         }
         Ok(())
     }
@@ -296,8 +307,11 @@ impl BlockFetcher {
         batch: &[Block],
         batch_number: U256,
         commit_tx: H256,
+        blob_count: usize,
     ) -> Result<(), BlockFetcherError> {
-        let batch = self.get_batch(batch, batch_number, commit_tx).await?;
+        let batch = self
+            .get_batch(batch, batch_number, commit_tx, blob_count)
+            .await?;
 
         self.rollup_store.seal_batch(batch).await?;
 
@@ -311,6 +325,7 @@ impl BlockFetcher {
         batch: &[Block],
         batch_number: U256,
         commit_tx: H256,
+        blob_count: usize,
     ) -> Result<Batch, BlockFetcherError> {
         let privileged_transactions: Vec<PrivilegedL2Transaction> = batch
             .iter()
@@ -386,8 +401,8 @@ impl BlockFetcher {
         )
         .map_err(|_| BlockFetcherError::BlobBundleError)?;
 
-        let (blobs_bundle, _) =
-            generate_blobs_bundle(&state_diff).map_err(|_| BlockFetcherError::BlobBundleError)?;
+        let (blobs_bundle, _) = generate_blobs_bundle(&state_diff, blob_count)
+            .map_err(|_| BlockFetcherError::BlobBundleError)?;
 
         Ok(Batch {
             number: batch_number.as_u64(),
@@ -542,27 +557,31 @@ fn decode_batch_from_calldata(calldata: &[u8]) -> Result<Vec<Block>, BlockFetche
     // function commitBatch(
     //     uint256 batchNumber,
     //     bytes32 newStateRoot,
-    //     bytes32 stateDiffKZGVersionedHash,
-    //     bytes32 messagesLogsMerkleRoot,
+    //     bytes32 withdrawalsLogsMerkleRoot,
     //     bytes32 processedPrivilegedTransactionsRollingHash,
+    //     bytes32 lastBlockHash,
+    //     bytes32 feeConfigHash,
     //     bytes[] calldata _rlpEncodedBlocks
     // ) external;
 
     // data =   4 bytes (function selector) 0..4
-    //          || 8 bytes (batch number)   4..36
+    //          || 32 bytes (batch number)   4..36
     //          || 32 bytes (new state root) 36..68
-    //          || 32 bytes (state diff KZG versioned hash) 68..100
-    //          || 32 bytes (messages logs merkle root) 100..132
-    //          || 32 bytes (processed privileged transactions rolling hash) 132..164
-
-    let batch_length_in_blocks = U256::from_big_endian(calldata.get(196..228).ok_or(
+    //          || 32 bytes (withdrawals logs merkle root) 68..100
+    //          || 32 bytes (processed privileged transactions rolling hash) 100..132
+    //          || 32 bytes (last block hash) 132..164
+    //          || 32 bytes (fee config hash) 164..196
+    //          || 32 bytes (offset of the encoded blocks) 196..228
+    //          || 32 bytes (number of encoded blocks) 228..260
+
+    let batch_length_in_blocks = U256::from_big_endian(calldata.get(228..260).ok_or(
         BlockFetcherError::WrongBatchCalldata("Couldn't get batch length bytes".to_owned()),
     )?)
     .as_usize();
 
-    let base = 228;
+    let base = 260;
 
-    let mut batch = Vec::new();
+    let mut encoded_blocks = Vec::new();
 
     for block_i in 0..batch_length_in_blocks {
         let block_length_offset = base + block_i * 32;
@@ -587,16 +606,14 @@ fn decode_batch_from_calldata(calldata: &[u8]) -> Result<Vec<Block>, BlockFetche
 
         let block_offset = base + dynamic_offset + 32;
 
-        let block = Block::decode(
+        encoded_blocks.push(
             calldata
                 .get(block_offset..block_offset + block_length_in_bytes)
                 .ok_or(BlockFetcherError::WrongBatchCalldata(
                     "Couldn't get block bytes".to_owned(),
                 ))?,
-        )?;
-
-        batch.push(block);
+        );
     }
 
-    Ok(batch)
+    Ok(decode_blocks(&encoded_blocks)?)
 }
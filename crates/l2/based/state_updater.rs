@@ -1,9 +1,14 @@
 use std::{sync::Arc, time::Duration};
 
 use ethrex_blockchain::Blockchain;
-use ethrex_common::{Address, types::Block};
+use ethrex_common::{Address, U256};
+use ethrex_l2_common::calldata::Value;
 use ethrex_l2_sdk::calldata::encode_calldata;
-use ethrex_rpc::{EthClient, clients::Overrides};
+use ethrex_p2p::rlpx::l2::lead_sequencers::LeadSequencers;
+use ethrex_rpc::{
+    EthClient,
+    clients::{Overrides, eth::from_hex_string_to_u256},
+};
 use ethrex_storage::Store;
 use ethrex_storage_rollup::{RollupStoreError, StoreRollup};
 use spawned_concurrency::{
@@ -11,12 +16,12 @@ use spawned_concurrency::{
     messages::Unused,
     tasks::{CastResponse, GenServer, GenServerHandle, send_after},
 };
-use tracing::{debug, error, info, warn};
+use tracing::{error, info, warn};
 
 use crate::{
     SequencerConfig,
     based::sequencer_state::{SequencerState, SequencerStatus},
-    sequencer::utils::node_is_up_to_date,
+    sequencer::utils::{node_is_up_to_date, revert_uncommitted_state},
     utils::parse::hash_to_address,
 };
 
@@ -63,6 +68,10 @@ pub struct StateUpdater {
     check_interval_ms: u64,
     sequencer_state: SequencerState,
     blockchain: Arc<Blockchain>,
+    lead_sequencers: LeadSequencers,
+    /// Number of consecutive batches each sequencer leads, read once from the
+    /// `SequencerRegistry`
+    batches_per_sequencer: Option<u64>,
 }
 
 impl StateUpdater {
@@ -72,6 +81,7 @@ impl StateUpdater {
         blockchain: Arc<Blockchain>,
         store: Store,
         rollup_store: StoreRollup,
+        lead_sequencers: LeadSequencers,
     ) -> Result<Self, StateUpdaterError> {
         Ok(Self {
             on_chain_proposer_address: sequencer_cfg.l1_committer.on_chain_proposer_address,
@@ -85,6 +95,8 @@ impl StateUpdater {
             check_interval_ms: sequencer_cfg.based.state_updater.check_interval_ms,
             sequencer_state,
             blockchain,
+            lead_sequencers,
+            batches_per_sequencer: None,
         })
     }
 
@@ -94,6 +106,7 @@ impl StateUpdater {
         blockchain: Arc<Blockchain>,
         store: Store,
         rollup_store: StoreRollup,
+        lead_sequencers: LeadSequencers,
     ) -> Result<(), StateUpdaterError> {
         let mut state_updater = Self::new(
             sequencer_cfg,
@@ -101,6 +114,7 @@ impl StateUpdater {
             blockchain,
             store,
             rollup_store,
+            lead_sequencers,
         )?
         .start();
         state_updater
@@ -126,6 +140,8 @@ impl StateUpdater {
                 })?,
         );
 
+        self.update_lead_sequencers().await?;
+
         let node_is_up_to_date = node_is_up_to_date::<StateUpdaterError>(
             &self.eth_client,
             self.on_chain_proposer_address,
@@ -146,12 +162,24 @@ impl StateUpdater {
 
             if current_state == SequencerStatus::Sequencing {
                 info!("Stopping sequencing.");
-                self.revert_uncommitted_state().await?;
+                revert_uncommitted_state::<StateUpdaterError>(
+                    &self.eth_client,
+                    self.on_chain_proposer_address,
+                    &self.store,
+                    &self.rollup_store,
+                )
+                .await?;
             }
 
             if new_status == SequencerStatus::Sequencing {
                 info!("Starting sequencing as lead sequencer.");
-                self.revert_uncommitted_state().await?;
+                revert_uncommitted_state::<StateUpdaterError>(
+                    &self.eth_client,
+                    self.on_chain_proposer_address,
+                    &self.store,
+                    &self.rollup_store,
+                )
+                .await?;
             }
 
             match new_status {
@@ -176,72 +204,83 @@ impl StateUpdater {
         Ok(())
     }
 
-    /// Reverts state to the last committed batch if known.
-    async fn revert_uncommitted_state(&mut self) -> Result<(), StateUpdaterError> {
-        let last_l2_committed_batch = self
+    /// Records the lead sequencers of the current batch and of the first batch of the
+    /// next sequencer, used to verify the signer of the messages received from peers.
+    async fn update_lead_sequencers(&mut self) -> Result<(), StateUpdaterError> {
+        let batches_per_sequencer = self.batches_per_sequencer().await?;
+        let current_batch = self
             .eth_client
             .get_last_committed_batch(self.on_chain_proposer_address)
-            .await?;
-
-        debug!("Last committed batch: {last_l2_committed_batch}");
-
-        let Some(last_l2_committed_batch_blocks) = self
-            .rollup_store
-            .get_block_numbers_by_batch(last_l2_committed_batch)
-            .await?
-        else {
-            // Node is not up to date. There is no uncommitted state to revert.
-            info!("No uncommitted state to revert. Node is not up to date.");
-            return Ok(());
-        };
-
-        debug!(
-            "Last committed batch blocks: {:?}",
-            last_l2_committed_batch_blocks
-        );
-
-        let Some(last_l2_committed_block_number) = last_l2_committed_batch_blocks.last() else {
-            return Err(StateUpdaterError::InternalError(format!(
-                "No blocks found for the last committed batch {last_l2_committed_batch}"
-            )));
-        };
-
-        debug!("Last committed batch block number: {last_l2_committed_block_number}");
-
-        let last_l2_committed_block_body = self
-            .store
-            .get_block_body(*last_l2_committed_block_number)
             .await?
-            .ok_or(StateUpdaterError::InternalError(
-                "No block body found for the last committed batch block number".to_string(),
-            ))?;
-
-        let last_l2_committed_block_header = self
-            .store
-            .get_block_header(*last_l2_committed_block_number)?
-            .ok_or(StateUpdaterError::InternalError(
-                "No block header found for the last committed batch block number".to_string(),
-            ))?;
-
-        let last_l2_committed_batch_block =
-            Block::new(last_l2_committed_block_header, last_l2_committed_block_body);
-
-        let last_l2_committed_batch_block_hash = last_l2_committed_batch_block.hash();
+            .saturating_add(1);
+        let next_slot_batch = (current_batch / batches_per_sequencer)
+            .saturating_add(1)
+            .saturating_mul(batches_per_sequencer);
+
+        let current_lead = self.lead_sequencer_for_batch(current_batch).await?;
+        let next_lead = self.lead_sequencer_for_batch(next_slot_batch).await?;
+
+        self.lead_sequencers.set_current_batch(current_batch).await;
+        self.lead_sequencers
+            .set_lead(current_batch, current_lead)
+            .await;
+        self.lead_sequencers
+            .set_lead(next_slot_batch, next_lead)
+            .await;
+        Ok(())
+    }
 
-        info!(
-            "Reverting uncommitted state to the last committed batch block {last_l2_committed_block_number} with hash {last_l2_committed_batch_block_hash:#x}"
-        );
-        self.store
-            .forkchoice_update(
-                None,
-                *last_l2_committed_block_number,
-                last_l2_committed_batch_block_hash,
-                None,
-                None,
-            )
-            .await?;
+    /// Number of consecutive batches each sequencer leads. It's a constant of the
+    /// `SequencerRegistry`, so it's only read the first time.
+    async fn batches_per_sequencer(&mut self) -> Result<u64, StateUpdaterError> {
+        if let Some(batches_per_sequencer) = self.batches_per_sequencer {
+            return Ok(batches_per_sequencer);
+        }
+        let batches_per_sequencer = from_hex_string_to_u256(
+            &self
+                .eth_client
+                .call(
+                    self.sequencer_registry_address,
+                    encode_calldata("BATCHES_PER_SEQUENCER()", &[])?.into(),
+                    Overrides::default(),
+                )
+                .await?,
+        )?;
+        let batches_per_sequencer = u64::try_from(batches_per_sequencer)
+            .ok()
+            .filter(|batches| *batches > 0)
+            .ok_or_else(|| {
+                StateUpdaterError::CalldataParsingError(format!(
+                    "Invalid BATCHES_PER_SEQUENCER() return data: {batches_per_sequencer}"
+                ))
+            })?;
+        self.batches_per_sequencer = Some(batches_per_sequencer);
+        Ok(batches_per_sequencer)
+    }
 
-        Ok(())
+    async fn lead_sequencer_for_batch(
+        &self,
+        batch_number: u64,
+    ) -> Result<Address, StateUpdaterError> {
+        Ok(hash_to_address(
+            self.eth_client
+                .call(
+                    self.sequencer_registry_address,
+                    encode_calldata(
+                        "leadSequencerForBatch(uint256)",
+                        &[Value::Uint(U256::from(batch_number))],
+                    )?
+                    .into(),
+                    Overrides::default(),
+                )
+                .await?
+                .parse()
+                .map_err(|err| {
+                    StateUpdaterError::CalldataParsingError(format!(
+                        "Failed to parse leadSequencerForBatch() return data: {err}"
+                    ))
+                })?,
+        ))
     }
 }
 
@@ -0,0 +1,335 @@
+//! Encoding of the data the L1 Committer publishes for each batch.
+//!
+//! The first byte of the data identifies how it was encoded, so nodes can decode
+//! batches committed before compression was introduced:
+//! - `RAW_DATA_VERSION`: the data as is, a raw `StateDiff` starts with its version.
+//! - `BROTLI_DATA_VERSION`: the data compressed with brotli, prefixed by the version
+//!   and the length of the compressed data as a big endian u32.
+
+use std::io::{Read, Write};
+
+use bytes::Bytes;
+use ethereum_types::H256;
+use ethrex_common::types::{Blob, Block, bytes_from_blob};
+use ethrex_rlp::{decode::RLPDecode, encode::RLPEncode, error::RLPDecodeError};
+use keccak_hash::keccak;
+
+use crate::state_diff::{StateDiff, StateDiffError};
+
+/// Data stored as is. It's also the version of the `StateDiff` encoding.
+pub const RAW_DATA_VERSION: u8 = 0x01;
+/// Data compressed with brotli.
+pub const BROTLI_DATA_VERSION: u8 = 0x02;
+
+/// Version byte and compressed length
+const BROTLI_HEADER_LEN: usize = 5;
+const BROTLI_QUALITY: u32 = 11;
+const BROTLI_WINDOW_SIZE: u32 = 22;
+const BROTLI_BUFFER_SIZE: usize = 4096;
+/// Limit of the decompressed data, so malformed data can't exhaust the memory
+const MAX_DECOMPRESSED_LEN: u64 = 64 * 1024 * 1024;
+
+#[derive(Debug, thiserror::Error)]
+pub enum CompressionError {
+    #[error("Batch data is empty")]
+    EmptyData,
+    #[error("Unsupported batch data version: {0}")]
+    UnsupportedVersion(u8),
+    #[error("Batch data is shorter than its declared length")]
+    TruncatedData,
+    #[error("Decompressed batch data is bigger than {MAX_DECOMPRESSED_LEN} bytes")]
+    DecompressedDataTooBig,
+    #[error("The length of the data doesn't fit: {0}")]
+    LengthTooBig(#[from] core::num::TryFromIntError),
+    #[error("Brotli error: {0}")]
+    Brotli(#[from] std::io::Error),
+    #[error("StateDiff error: {0}")]
+    StateDiff(#[from] StateDiffError),
+    #[error("Failed to RLP decode the batch blocks: {0}")]
+    RLPDecode(#[from] RLPDecodeError),
+}
+
+/// Encodes the state diff of a batch to be published in blobs, compressed unless
+/// that makes it bigger.
+pub fn encode_state_diff(state_diff: &StateDiff) -> Result<Bytes, CompressionError> {
+    let raw = state_diff.encode()?;
+    let compressed = compress(&raw)?;
+    if compressed.len() < raw.len() {
+        Ok(compressed.into())
+    } else {
+        Ok(raw)
+    }
+}
+
+/// Decodes a state diff encoded by `encode_state_diff`, trailing bytes are ignored.
+pub fn decode_state_diff(data: &[u8]) -> Result<StateDiff, CompressionError> {
+    match data.first() {
+        Some(&RAW_DATA_VERSION) => Ok(StateDiff::decode(data)?),
+        Some(&BROTLI_DATA_VERSION) => Ok(StateDiff::decode(&decompress(data)?)?),
+        Some(version) => Err(CompressionError::UnsupportedVersion(*version)),
+        None => Err(CompressionError::EmptyData),
+    }
+}
+
+/// Decodes the state diff published in the blobs of a batch.
+pub fn decode_state_diff_from_blobs(blobs: &[Blob]) -> Result<StateDiff, CompressionError> {
+    let data: Vec<u8> = blobs
+        .iter()
+        .flat_map(|blob| bytes_from_blob(Bytes::copy_from_slice(blob)))
+        .collect();
+    decode_state_diff(&data)
+}
+
+/// Data of a batch stored in blobs, without the padding of the last of them.
+pub fn data_from_blobs(blobs: &[Blob]) -> Result<Bytes, CompressionError> {
+    let data: Vec<u8> = blobs
+        .iter()
+        .flat_map(|blob| bytes_from_blob(Bytes::copy_from_slice(blob)))
+        .collect();
+    let len = match data.first() {
+        Some(&RAW_DATA_VERSION) => StateDiff::decode(&data)?.encode()?.len(),
+        Some(&BROTLI_DATA_VERSION) => BROTLI_HEADER_LEN
+            .checked_add(compressed_len(&data)?)
+            .ok_or(CompressionError::TruncatedData)?,
+        Some(version) => return Err(CompressionError::UnsupportedVersion(*version)),
+        None => return Err(CompressionError::EmptyData),
+    };
+    data.get(..len)
+        .map(Bytes::copy_from_slice)
+        .ok_or(CompressionError::TruncatedData)
+}
+
+/// Hash of the versioned hashes of the blobs of a batch, as the `OnChainProposer`
+/// computes it when the batch is committed.
+pub fn compute_blobs_versioned_hash(versioned_hashes: &[H256]) -> H256 {
+    let concatenated: Vec<u8> = versioned_hashes.iter().flat_map(|hash| hash.0).collect();
+    keccak(concatenated)
+}
+
+/// Hash of the data a validium publishes through calldata or a data availability
+/// committee, as the `OnChainProposer` commits it.
+pub fn compute_data_hash(data: &[u8]) -> H256 {
+    keccak(data)
+}
+
+/// Encodes the blocks of a batch for the calldata of the based `commitBatch`. Either
+/// one RLP encoded block per element or, if it's smaller, a single element with all
+/// of them compressed.
+pub fn encode_blocks(blocks: &[Block]) -> Result<Vec<Bytes>, CompressionError> {
+    let raw: Vec<Bytes> = blocks
+        .iter()
+        .map(|block| block.encode_to_vec().into())
+        .collect();
+    let compressed = compress(&blocks.to_vec().encode_to_vec())?;
+    if compressed.len() < raw.iter().map(Bytes::len).sum() {
+        Ok(vec![compressed.into()])
+    } else {
+        Ok(raw)
+    }
+}
+
+/// Decodes the blocks encoded by `encode_blocks`. An RLP encoded block always starts
+/// with a list prefix, so it can't be mistaken for compressed data.
+pub fn decode_blocks(encoded_blocks: &[&[u8]]) -> Result<Vec<Block>, CompressionError> {
+    let mut blocks = Vec::new();
+    for encoded in encoded_blocks {
+        if encoded.first() == Some(&BROTLI_DATA_VERSION) {
+            blocks.extend(Vec::<Block>::decode(&decompress(encoded)?)?);
+        } else {
+            blocks.push(Block::decode(encoded)?);
+        }
+    }
+    Ok(blocks)
+}
+
+fn compress(data: &[u8]) -> Result<Vec<u8>, CompressionError> {
+    let mut writer = brotli::CompressorWriter::new(
+        Vec::new(),
+        BROTLI_BUFFER_SIZE,
+        BROTLI_QUALITY,
+        BROTLI_WINDOW_SIZE,
+    );
+    writer.write_all(data)?;
+    let compressed = writer.into_inner();
+
+    let compressed_len: u32 = compressed.len().try_into()?;
+    let mut encoded = Vec::with_capacity(BROTLI_HEADER_LEN + compressed.len());
+    encoded.push(BROTLI_DATA_VERSION);
+    encoded.extend(compressed_len.to_be_bytes());
+    encoded.extend(compressed);
+    Ok(encoded)
+}
+
+fn decompress(data: &[u8]) -> Result<Vec<u8>, CompressionError> {
+    let compressed_len = compressed_len(data)?;
+    let compressed_end = BROTLI_HEADER_LEN
+        .checked_add(compressed_len)
+        .ok_or(CompressionError::TruncatedData)?;
+    let compressed = data
+        .get(BROTLI_HEADER_LEN..compressed_end)
+        .ok_or(CompressionError::TruncatedData)?;
+
+    let mut decompressed = Vec::new();
+    brotli::Decompressor::new(compressed, BROTLI_BUFFER_SIZE)
+        .take(MAX_DECOMPRESSED_LEN + 1)
+        .read_to_end(&mut decompressed)?;
+    if u64::try_from(decompressed.len())? > MAX_DECOMPRESSED_LEN {
+        return Err(CompressionError::DecompressedDataTooBig);
+    }
+    Ok(decompressed)
+}
+
+fn compressed_len(data: &[u8]) -> Result<usize, CompressionError> {
+    let len_bytes: [u8; 4] = data
+        .get(1..BROTLI_HEADER_LEN)
+        .ok_or(CompressionError::TruncatedData)?
+        .try_into()
+        .map_err(|_| CompressionError::TruncatedData)?;
+    Ok(u32::from_be_bytes(len_bytes).try_into()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use ethereum_types::{Address, U256};
+    use ethrex_common::types::{BlockHeader, blobs_from_bytes};
+
+    use super::*;
+    use crate::state_diff::AccountStateDiff;
+
+    /// Deterministic bytes that don't compress
+    fn pseudo_random_bytes(seed: u64, len: usize) -> Bytes {
+        let mut bytes = Vec::with_capacity(len);
+        let mut hash = keccak(seed.to_be_bytes());
+        while bytes.len() < len {
+            bytes.extend_from_slice(hash.as_bytes());
+            hash = keccak(hash.as_bytes());
+        }
+        bytes.truncate(len);
+        bytes.into()
+    }
+
+    fn state_diff(modified_accounts: BTreeMap<Address, AccountStateDiff>) -> StateDiff {
+        StateDiff {
+            version: RAW_DATA_VERSION,
+            last_header: BlockHeader {
+                transactions_root: keccak([1]),
+                receipts_root: keccak([2]),
+                parent_hash: keccak([3]),
+                gas_limit: 0x1c9c380,
+                gas_used: 0x5208,
+                timestamp: 0x6853a1f2,
+                number: 0x2a,
+                base_fee_per_gas: Some(0x3b9aca00),
+                ..Default::default()
+            },
+            modified_accounts,
+            l1_messages: Vec::new(),
+            privileged_transactions: Vec::new(),
+        }
+    }
+
+    /// Accounts whose code is the given bytecode
+    fn accounts_with_code(bytecodes: &[Bytes]) -> BTreeMap<Address, AccountStateDiff> {
+        bytecodes
+            .iter()
+            .zip(1..)
+            .map(|(bytecode, address)| {
+                (
+                    Address::from_low_u64_be(address),
+                    AccountStateDiff {
+                        bytecode: Some(bytecode.clone()),
+                        ..Default::default()
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// Publishes the state diff in blobs and reads it back, checking it didn't change
+    fn assert_round_trip(state_diff: &StateDiff) -> (Bytes, Vec<Blob>) {
+        let encoded = encode_state_diff(state_diff).unwrap();
+        let blobs = blobs_from_bytes(encoded.clone()).unwrap();
+
+        let decoded = decode_state_diff_from_blobs(&blobs).unwrap();
+        assert_eq!(decoded.encode().unwrap(), state_diff.encode().unwrap());
+        assert_eq!(data_from_blobs(&blobs).unwrap(), encoded);
+        (encoded, blobs)
+    }
+
+    #[test]
+    fn compressible_state_diff_round_trip() {
+        let storage = (0..500)
+            .map(|slot| (H256::from_low_u64_be(slot), U256::from(slot)))
+            .collect();
+        let modified_accounts = BTreeMap::from([(
+            Address::from_low_u64_be(1),
+            AccountStateDiff {
+                new_balance: Some(U256::from(10).pow(18.into())),
+                nonce_diff: 1,
+                storage,
+                ..Default::default()
+            },
+        )]);
+        let state_diff = state_diff(modified_accounts);
+
+        let (encoded, blobs) = assert_round_trip(&state_diff);
+        assert_eq!(encoded.first(), Some(&BROTLI_DATA_VERSION));
+        assert!(encoded.len() < state_diff.encode().unwrap().len());
+        assert_eq!(blobs.len(), 1);
+    }
+
+    #[test]
+    fn incompressible_state_diff_is_published_raw() {
+        let state_diff = state_diff(accounts_with_code(&[pseudo_random_bytes(1, 2000)]));
+
+        let (encoded, blobs) = assert_round_trip(&state_diff);
+        assert_eq!(encoded.first(), Some(&RAW_DATA_VERSION));
+        assert_eq!(encoded, state_diff.encode().unwrap());
+        assert_eq!(blobs.len(), 1);
+    }
+
+    #[test]
+    fn raw_state_diff_spanning_several_blobs_round_trip() {
+        let bytecodes: Vec<Bytes> = (1..=3)
+            .map(|seed| pseudo_random_bytes(seed, 60_000))
+            .collect();
+        let state_diff = state_diff(accounts_with_code(&bytecodes));
+
+        let (encoded, blobs) = assert_round_trip(&state_diff);
+        assert_eq!(encoded.first(), Some(&RAW_DATA_VERSION));
+        assert_eq!(blobs.len(), 2);
+    }
+
+    #[test]
+    fn compressed_state_diff_spanning_several_blobs_round_trip() {
+        // Every bytecode is deployed twice, so the data compresses to about half its size
+        let bytecodes: Vec<Bytes> = (1..=3)
+            .map(|seed| pseudo_random_bytes(seed, 60_000))
+            .collect();
+        let bytecodes = [bytecodes.clone(), bytecodes].concat();
+        let state_diff = state_diff(accounts_with_code(&bytecodes));
+
+        let (encoded, blobs) = assert_round_trip(&state_diff);
+        assert_eq!(encoded.first(), Some(&BROTLI_DATA_VERSION));
+        assert_eq!(blobs.len(), 2);
+    }
+
+    #[test]
+    fn unknown_data_versions_are_rejected() {
+        assert!(matches!(
+            decode_state_diff(&[]),
+            Err(CompressionError::EmptyData)
+        ));
+        assert!(matches!(
+            decode_state_diff(&[0x03, 0x00]),
+            Err(CompressionError::UnsupportedVersion(0x03))
+        ));
+        // The declared compressed length is bigger than the data
+        assert!(matches!(
+            decode_state_diff(&[BROTLI_DATA_VERSION, 0x00, 0x00, 0x01, 0x00, 0x00]),
+            Err(CompressionError::TruncatedData)
+        ));
+    }
+}
@@ -6,6 +6,7 @@ import "@openzeppelin/contracts-upgradeable/proxy/utils/Initializable.sol";
 import "@openzeppelin/contracts-upgradeable/access/Ownable2StepUpgradeable.sol";
 import "@openzeppelin/contracts-upgradeable/utils/ReentrancyGuardUpgradeable.sol";
 import "@openzeppelin/contracts/token/ERC20/IERC20.sol";
+import "@openzeppelin/contracts/token/ERC20/extensions/IERC20Metadata.sol";
 import "@openzeppelin/contracts/token/ERC20/utils/SafeERC20.sol";
 import "@openzeppelin/contracts/proxy/transparent/TransparentUpgradeableProxy.sol";
 import {MerkleProof} from "@openzeppelin/contracts/utils/cryptography/MerkleProof.sol";
@@ -84,6 +85,12 @@ contract CommonBridge is
     /// @notice Deadline for the sequencer to include the transaction.
     mapping(bytes32 => uint256) public privilegedTxDeadline;
 
+    /// @notice L1 ERC20 token used as the native currency of the L2.
+    /// @dev If it's the zero address, the native currency is ETH.
+    /// @dev Its deposits mint native L2 balance and its withdrawals are
+    /// claimed through claimWithdrawal(), tracked as deposits[ETH_TOKEN][ETH_TOKEN].
+    address public NATIVE_TOKEN_L1;
+
     modifier onlyOnChainProposer() {
         require(
             msg.sender == ON_CHAIN_PROPOSER,
@@ -97,10 +104,14 @@ contract CommonBridge is
     /// @dev It sets the OnChainProposer address.
     /// @param owner the address of the owner who can perform upgrades.
     /// @param onChainProposer the address of the OnChainProposer contract.
+    /// @param inclusionMaxWait deadline in seconds for the sequencer to process a privileged transaction.
+    /// @param nativeToken the L1 ERC20 used as the native currency of the L2, or the zero address for ETH.
+    /// It must have 18 decimals, as one unit of it mints one wei of native L2 balance.
     function initialize(
         address owner,
         address onChainProposer,
-        uint256 inclusionMaxWait
+        uint256 inclusionMaxWait,
+        address nativeToken
     ) public initializer {
         require(
             onChainProposer != address(0),
@@ -113,6 +124,17 @@ contract CommonBridge is
 
         PRIVILEGED_TX_MAX_WAIT_BEFORE_INCLUSION = inclusionMaxWait;
 
+        require(
+            nativeToken != ETH_TOKEN,
+            "CommonBridge: use the zero address for an ETH native token"
+        );
+        require(
+            nativeToken == address(0) ||
+                IERC20Metadata(nativeToken).decimals() == 18,
+            "CommonBridge: the native token must have 18 decimals"
+        );
+        NATIVE_TOKEN_L1 = nativeToken;
+
         OwnableUpgradeable.__Ownable_init(owner);
         ReentrancyGuardUpgradeable.__ReentrancyGuard_init();
     }
@@ -202,7 +224,17 @@ contract CommonBridge is
     }
 
     function _deposit(address l2Recipient) private {
-        deposits[ETH_TOKEN][ETH_TOKEN] += msg.value;
+        require(
+            NATIVE_TOKEN_L1 == address(0),
+            "CommonBridge: the native token is an ERC20, use depositERC20()"
+        );
+        _mintNative(l2Recipient, msg.value);
+    }
+
+    /// @notice Mints native L2 balance, backed by ETH or the native ERC20
+    /// already held by the bridge.
+    function _mintNative(address l2Recipient, uint256 amount) private {
+        deposits[ETH_TOKEN][ETH_TOKEN] += amount;
         bytes memory callData = abi.encodeCall(
             ICommonBridgeL2.mintETH,
             (l2Recipient)
@@ -210,7 +242,7 @@ contract CommonBridge is
         SendValues memory sendValues = SendValues({
             to: L2_BRIDGE_ADDRESS,
             gasLimit: 21000 * 5,
-            value: msg.value,
+            value: amount,
             data: callData
         });
         _sendToL2(L2_BRIDGE_ADDRESS, sendValues);
@@ -225,8 +257,23 @@ contract CommonBridge is
         address tokenL2,
         address destination,
         uint256 amount
-    ) external {
+    ) external nonReentrant {
         require(amount > 0, "CommonBridge: amount to deposit is zero");
+        if (NATIVE_TOKEN_L1 != address(0) && tokenL1 == NATIVE_TOKEN_L1) {
+            require(
+                tokenL2 == ETH_TOKEN,
+                "CommonBridge: the native token must be deposited to ETH_TOKEN"
+            );
+            // Tokens with transfer fees deliver less than the amount, only
+            // what the bridge received backs the minted balance
+            uint256 balanceBefore = IERC20(tokenL1).balanceOf(address(this));
+            IERC20(tokenL1).safeTransferFrom(msg.sender, address(this), amount);
+            uint256 received = IERC20(tokenL1).balanceOf(address(this)) -
+                balanceBefore;
+            require(received > 0, "CommonBridge: no native token received");
+            _mintNative(destination, received);
+            return;
+        }
         deposits[tokenL1][tokenL2] += amount;
         IERC20(tokenL1).safeTransferFrom(msg.sender, address(this), amount);
 
@@ -321,7 +368,7 @@ contract CommonBridge is
         uint256 withdrawalBatchNumber,
         uint256 withdrawalMessageId,
         bytes32[] calldata withdrawalProof
-    ) public {
+    ) public nonReentrant {
         _claimWithdrawal(
             ETH_TOKEN,
             ETH_TOKEN,
@@ -330,6 +377,10 @@ contract CommonBridge is
             withdrawalMessageId,
             withdrawalProof
         );
+        if (NATIVE_TOKEN_L1 != address(0)) {
+            IERC20(NATIVE_TOKEN_L1).safeTransfer(msg.sender, claimedAmount);
+            return;
+        }
         (bool success, ) = payable(msg.sender).call{value: claimedAmount}("");
         require(success, "CommonBridge: failed to send the claimed amount");
     }
@@ -35,6 +35,7 @@ contract OnChainProposer is
         bytes32 processedPrivilegedTransactionsRollingHash;
         bytes32 withdrawalsLogsMerkleRoot;
         bytes32 lastBlockHash;
+        bytes32 feeConfigHash;
     }
 
     /// @notice The commitments of the committed batches.
@@ -91,6 +92,20 @@ contract OnChainProposer is
     /// @notice Chain ID of the network
     uint256 public CHAIN_ID;
 
+    /// @notice How the validium publishes the data of its batches.
+    DataAvailabilityMode public DA_MODE;
+
+    /// @notice Members of the data availability committee.
+    address[] public daCommitteeMembers;
+    mapping(address => bool) public isDACommitteeMember;
+
+    /// @notice Amount of members that must attest the data of a batch.
+    uint256 public daCommitteeThreshold;
+
+    /// @notice Hash of the fee configuration the L2 batches must be executed with.
+    /// @dev The committed batches must use it, and their proofs are checked against it.
+    bytes32 public FEE_CONFIG_HASH;
+
     modifier onlySequencer() {
         require(
             authorizedSequencerAddresses[msg.sender],
@@ -106,6 +121,7 @@ contract OnChainProposer is
     /// @param alignedProofAggregator the address of the alignedProofAggregatorService contract.
     /// @param r0verifier the address of the risc0 groth16 verifier.
     /// @param sp1verifier the address of the sp1 groth16 verifier.
+    /// @param feeConfigHash the hash of the L2 fee configuration, see `FeeConfig::hash`.
     function initialize(
         bool _validium,
         address owner,
@@ -117,7 +133,8 @@ contract OnChainProposer is
         bytes32 risc0Vk,
         bytes32 genesisStateRoot,
         address[] calldata sequencerAddresses,
-        uint256 chainId
+        uint256 chainId,
+        bytes32 feeConfigHash
     ) public initializer {
         VALIDIUM = _validium;
 
@@ -158,6 +175,7 @@ contract OnChainProposer is
             bytes32(0),
             bytes32(0),
             bytes32(0),
+            bytes32(0),
             bytes32(0)
         );
 
@@ -166,6 +184,7 @@ contract OnChainProposer is
         }
 
         CHAIN_ID = chainId;
+        FEE_CONFIG_HASH = feeConfigHash;
 
         OwnableUpgradeable.__Ownable_init(owner);
     }
@@ -179,13 +198,70 @@ contract OnChainProposer is
         BRIDGE = bridge;
     }
 
+    /// @inheritdoc IOnChainProposer
+    function setFeeConfigHash(
+        bytes32 feeConfigHash
+    ) external override onlyOwner {
+        FEE_CONFIG_HASH = feeConfigHash;
+        emit FeeConfigHashUpdated(feeConfigHash);
+    }
+
+    /// @inheritdoc IOnChainProposer
+    function setDataAvailability(
+        DataAvailabilityMode mode,
+        address[] calldata committee,
+        uint256 threshold
+    ) external override onlyOwner {
+        require(
+            VALIDIUM || mode == DataAvailabilityMode.None,
+            "OnChainProposer: a rollup publishes its data in blobs"
+        );
+
+        for (uint256 i = 0; i < daCommitteeMembers.length; i++) {
+            isDACommitteeMember[daCommitteeMembers[i]] = false;
+        }
+        delete daCommitteeMembers;
+
+        if (mode == DataAvailabilityMode.Committee) {
+            require(
+                threshold > 0 && threshold <= committee.length,
+                "OnChainProposer: invalid data availability committee threshold"
+            );
+            for (uint256 i = 0; i < committee.length; i++) {
+                require(
+                    committee[i] != address(0),
+                    "OnChainProposer: data availability committee member is the zero address"
+                );
+                require(
+                    !isDACommitteeMember[committee[i]],
+                    "OnChainProposer: duplicated data availability committee member"
+                );
+                isDACommitteeMember[committee[i]] = true;
+                daCommitteeMembers.push(committee[i]);
+            }
+        } else {
+            require(
+                committee.length == 0 && threshold == 0,
+                "OnChainProposer: a data availability committee needs the Committee mode"
+            );
+        }
+
+        DA_MODE = mode;
+        daCommitteeThreshold = threshold;
+        emit DataAvailabilityUpdated(mode, committee, threshold);
+    }
+
     /// @inheritdoc IOnChainProposer
     function commitBatch(
         uint256 batchNumber,
         bytes32 newStateRoot,
         bytes32 withdrawalsLogsMerkleRoot,
         bytes32 processedPrivilegedTransactionsRollingHash,
-        bytes32 lastBlockHash
+        bytes32 lastBlockHash,
+        bytes32 feeConfigHash,
+        bytes calldata stateDiffData,
+        bytes32 stateDiffHash,
+        bytes[] calldata daSignatures
     ) external override onlySequencer whenNotPaused {
         // TODO: Refactor validation
         require(
@@ -200,6 +276,10 @@ contract OnChainProposer is
             lastBlockHash != bytes32(0),
             "OnChainProposer: lastBlockHash cannot be zero"
         );
+        require(
+            feeConfigHash == FEE_CONFIG_HASH,
+            "OnChainProposer: feeConfigHash doesn't match the L2 fee configuration"
+        );
 
         if (processedPrivilegedTransactionsRollingHash != bytes32(0)) {
             bytes32 claimedProcessedTransactions = ICommonBridge(BRIDGE)
@@ -219,13 +299,18 @@ contract OnChainProposer is
             );
         }
 
-        // Blob is published in the (EIP-4844) transaction that calls this function.
-        bytes32 blobVersionedHash = blobhash(0);
+        // Blobs are published in the (EIP-4844) transaction that calls this function.
+        bytes32 blobVersionedHash = _blobsVersionedHash();
         if (VALIDIUM) {
             require(
                 blobVersionedHash == 0,
                 "L2 running as validium but blob was published"
             );
+            blobVersionedHash = _validiumDataHash(
+                stateDiffData,
+                stateDiffHash,
+                daSignatures
+            );
         } else {
             require(
                 blobVersionedHash != 0,
@@ -238,7 +323,8 @@ contract OnChainProposer is
             blobVersionedHash,
             processedPrivilegedTransactionsRollingHash,
             withdrawalsLogsMerkleRoot,
-            lastBlockHash
+            lastBlockHash,
+            feeConfigHash
         );
         emit BatchCommitted(newStateRoot);
 
@@ -397,12 +483,100 @@ contract OnChainProposer is
         emit BatchVerified(lastVerifiedBatch);
     }
 
+    /// @notice Hash of the versioned hashes of the blobs published in the
+    /// transaction, zero if it has none.
+    /// @dev The state diff of a batch can span several blobs.
+    function _blobsVersionedHash() internal view returns (bytes32) {
+        if (blobhash(0) == 0) {
+            return bytes32(0);
+        }
+        bytes memory versionedHashes;
+        for (uint256 i = 0; blobhash(i) != 0; i++) {
+            versionedHashes = abi.encodePacked(versionedHashes, blobhash(i));
+        }
+        return keccak256(versionedHashes);
+    }
+
+    /// @notice Hash of the data a validium publishes for a batch, zero if it
+    /// doesn't publish it.
+    /// @dev The prover commits to the same hash, so the published data must be
+    /// the state diff of the batch.
+    function _validiumDataHash(
+        bytes calldata stateDiffData,
+        bytes32 stateDiffHash,
+        bytes[] calldata daSignatures
+    ) internal view returns (bytes32) {
+        if (DA_MODE == DataAvailabilityMode.Calldata) {
+            require(
+                stateDiffData.length > 0,
+                "OnChainProposer: state diff data was not published"
+            );
+            return keccak256(stateDiffData);
+        }
+        if (DA_MODE == DataAvailabilityMode.Committee) {
+            require(
+                stateDiffHash != bytes32(0),
+                "OnChainProposer: state diff hash cannot be zero"
+            );
+            _verifyDAAttestations(stateDiffHash, daSignatures);
+            return stateDiffHash;
+        }
+        return bytes32(0);
+    }
+
+    /// @notice Checks that `daCommitteeThreshold` different committee members
+    /// signed the hash of the data.
+    /// @dev The signatures must be sorted by signer address, so duplicates are
+    /// rejected without extra storage.
+    function _verifyDAAttestations(
+        bytes32 stateDiffHash,
+        bytes[] calldata daSignatures
+    ) internal view {
+        require(
+            daSignatures.length >= daCommitteeThreshold,
+            "OnChainProposer: not enough data availability attestations"
+        );
+        bytes32 digest = keccak256(abi.encodePacked(stateDiffHash));
+        address lastSigner = address(0);
+        for (uint256 i = 0; i < daSignatures.length; i++) {
+            address signer = _recoverSigner(digest, daSignatures[i]);
+            require(
+                isDACommitteeMember[signer],
+                "OnChainProposer: attestation signer is not a committee member"
+            );
+            require(
+                signer > lastSigner,
+                "OnChainProposer: attestations must be sorted by signer"
+            );
+            lastSigner = signer;
+        }
+    }
+
+    function _recoverSigner(
+        bytes32 digest,
+        bytes calldata signature
+    ) internal pure returns (address) {
+        require(
+            signature.length == 65,
+            "OnChainProposer: invalid signature length"
+        );
+        bytes32 r = bytes32(signature[0:32]);
+        bytes32 s = bytes32(signature[32:64]);
+        uint8 v = uint8(signature[64]);
+        if (v < 27) {
+            v += 27;
+        }
+        address signer = ecrecover(digest, v, r, s);
+        require(signer != address(0), "OnChainProposer: invalid signature");
+        return signer;
+    }
+
     function _verifyPublicData(
         uint256 batchNumber,
         bytes calldata publicData
     ) internal view {
         require(
-            publicData.length == 256,
+            publicData.length == 288,
             "OnChainProposer: invalid public data length"
         );
         bytes32 initialStateRoot = bytes32(publicData[0:32]);
@@ -453,6 +627,11 @@ contract OnChainProposer is
                 nonPrivilegedTransactions == 0,
             "OnChainProposer: exceeded privileged transaction inclusion deadline, can't include non-privileged transactions"
         );
+        bytes32 feeConfigHash = bytes32(publicData[256:288]);
+        require(
+            batchCommitments[batchNumber].feeConfigHash == feeConfigHash,
+            "OnChainProposer: fee config hash public input does not match with committed fee config"
+        );
     }
 
     /// @inheritdoc IOnChainProposer
@@ -34,6 +34,7 @@ contract OnChainProposer is
         bytes32 processedPrivilegedTransactionsRollingHash;
         bytes32 withdrawalsLogsMerkleRoot;
         bytes32 lastBlockHash;
+        bytes32 feeConfigHash;
     }
 
     /// @notice The commitments of the committed batches.
@@ -85,6 +86,10 @@ contract OnChainProposer is
     /// @notice Chain ID of the network
     uint256 public CHAIN_ID;
 
+    /// @notice Hash of the fee configuration the L2 batches must be executed with.
+    /// @dev The committed batches must use it, and their proofs are checked against it.
+    bytes32 public FEE_CONFIG_HASH;
+
     modifier onlyLeaderSequencer() {
         require(
             msg.sender ==
@@ -102,6 +107,7 @@ contract OnChainProposer is
     /// @param alignedProofAggregator the address of the alignedProofAggregatorService contract.
     /// @param r0verifier the address of the risc0 groth16 verifier.
     /// @param sp1verifier the address of the sp1 groth16 verifier.
+    /// @param feeConfigHash the hash of the L2 fee configuration, see `FeeConfig::hash`.
     function initialize(
         bool _validium,
         address owner,
@@ -113,7 +119,8 @@ contract OnChainProposer is
         bytes32 risc0Vk,
         bytes32 genesisStateRoot,
         address sequencer_registry,
-        uint256 chainId
+        uint256 chainId,
+        bytes32 feeConfigHash
     ) public initializer {
         VALIDIUM = _validium;
 
@@ -187,6 +194,7 @@ contract OnChainProposer is
             bytes32(0),
             bytes32(0),
             bytes32(0),
+            bytes32(0),
             bytes32(0)
         );
 
@@ -206,6 +214,7 @@ contract OnChainProposer is
         SEQUENCER_REGISTRY = sequencer_registry;
 
         CHAIN_ID = chainId;
+        FEE_CONFIG_HASH = feeConfigHash;
 
         OwnableUpgradeable.__Ownable_init(owner);
     }
@@ -227,6 +236,14 @@ contract OnChainProposer is
         BRIDGE = bridge;
     }
 
+    /// @inheritdoc IOnChainProposer
+    function setFeeConfigHash(
+        bytes32 feeConfigHash
+    ) external override onlyOwner {
+        FEE_CONFIG_HASH = feeConfigHash;
+        emit FeeConfigHashUpdated(feeConfigHash);
+    }
+
     /// @inheritdoc IOnChainProposer
     function commitBatch(
         uint256 batchNumber,
@@ -234,6 +251,7 @@ contract OnChainProposer is
         bytes32 withdrawalsLogsMerkleRoot,
         bytes32 processedPrivilegedTransactionsRollingHash,
         bytes32 lastBlockHash,
+        bytes32 feeConfigHash,
         bytes[] calldata //rlpEncodedBlocks
     ) external override onlyLeaderSequencer {
         // TODO: Refactor validation
@@ -249,6 +267,10 @@ contract OnChainProposer is
             lastBlockHash != bytes32(0),
             "OnChainProposer: lastBlockHash cannot be zero"
         );
+        require(
+            feeConfigHash == FEE_CONFIG_HASH,
+            "OnChainProposer: feeConfigHash doesn't match the L2 fee configuration"
+        );
 
         // Check if commitment is equivalent to blob's KZG commitment.
 
@@ -270,8 +292,8 @@ contract OnChainProposer is
             );
         }
 
-        // Blob is published in the (EIP-4844) transaction that calls this function.
-        bytes32 blobVersionedHash = blobhash(0);
+        // Blobs are published in the (EIP-4844) transaction that calls this function.
+        bytes32 blobVersionedHash = _blobsVersionedHash();
         if (VALIDIUM) {
             require(
                 blobVersionedHash == 0,
@@ -289,7 +311,8 @@ contract OnChainProposer is
             blobVersionedHash,
             processedPrivilegedTransactionsRollingHash,
             withdrawalsLogsMerkleRoot,
-            lastBlockHash
+            lastBlockHash,
+            feeConfigHash
         );
         emit BatchCommitted(batchNumber, newStateRoot);
 
@@ -448,12 +471,26 @@ contract OnChainProposer is
         emit BatchVerified(lastVerifiedBatch);
     }
 
+    /// @notice Hash of the versioned hashes of the blobs published in the
+    /// transaction, zero if it has none.
+    /// @dev The state diff of a batch can span several blobs.
+    function _blobsVersionedHash() internal view returns (bytes32) {
+        if (blobhash(0) == 0) {
+            return bytes32(0);
+        }
+        bytes memory versionedHashes;
+        for (uint256 i = 0; blobhash(i) != 0; i++) {
+            versionedHashes = abi.encodePacked(versionedHashes, blobhash(i));
+        }
+        return keccak256(versionedHashes);
+    }
+
     function _verifyPublicData(
         uint256 batchNumber,
         bytes calldata publicData
     ) internal view {
         require(
-            publicData.length == 224,
+            publicData.length == 256,
             "OnChainProposer: invalid public data length"
         );
         bytes32 initialStateRoot = bytes32(publicData[0:32]);
@@ -498,6 +535,11 @@ contract OnChainProposer is
                 nonPrivilegedTransactions == 0,
             "OnChainProposer: exceeded privileged transaction inclusion deadline, can't include non-privileged transactions"
         );
+        bytes32 feeConfigHash = bytes32(publicData[224:256]);
+        require(
+            batchCommitments[batchNumber].feeConfigHash == feeConfigHash,
+            "OnChainProposer: fee config hash public input does not match with committed fee config"
+        );
     }
 
     /// @notice Allow owner to upgrade the contract.
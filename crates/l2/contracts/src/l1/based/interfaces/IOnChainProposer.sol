@@ -23,6 +23,11 @@ interface IOnChainProposer {
     /// @dev Event emitted when a batch is verified.
     event BatchVerified(uint256 indexed lastVerifiedBatch);
 
+    /// @notice The fee configuration of the L2 has been updated.
+    /// @dev Event emitted when the owner sets the fee configuration hash.
+    /// @param feeConfigHash The hash of the new L2 fee configuration.
+    event FeeConfigHashUpdated(bytes32 indexed feeConfigHash);
+
     /// @notice Set the bridge address for the first time.
     /// @dev This method is separated from initialize because both the CommonBridge
     /// and the OnChainProposer need to know the address of the other. This solves
@@ -30,6 +35,12 @@ interface IOnChainProposer {
     /// @param bridge the address of the bridge contract.
     function initializeBridgeAddress(address bridge) external;
 
+    /// @notice Sets the hash of the fee configuration the L2 batches must be executed with.
+    /// @dev Only the owner can call it, the sequencers must update their fee configuration
+    /// at the same time, otherwise their commitments are rejected.
+    /// @param feeConfigHash the hash of the new L2 fee configuration.
+    function setFeeConfigHash(bytes32 feeConfigHash) external;
+
     /// @notice Commits to a batch of L2 blocks.
     /// @dev Committing to an L2 batch means to store the batch's commitment
     /// and to publish withdrawals if any.
@@ -40,6 +51,7 @@ interface IOnChainProposer {
     /// @param processedDepositLogsRollingHash the rolling hash of the processed
     /// deposits logs of the batch to be committed.
     /// @param lastBlockHash the hash of the last block of the batch to be committed.
+    /// @param feeConfigHash the hash of the L2 fee configuration used to execute the batch.
     /// @param _rlpEncodedBlocks the list of RLP-encoded blocks in the batch.
     function commitBatch(
         uint256 batchNumber,
@@ -47,6 +59,7 @@ interface IOnChainProposer {
         bytes32 withdrawalsLogsMerkleRoot,
         bytes32 processedDepositLogsRollingHash,
         bytes32 lastBlockHash,
+        bytes32 feeConfigHash,
         bytes[] calldata _rlpEncodedBlocks
     ) external;
 
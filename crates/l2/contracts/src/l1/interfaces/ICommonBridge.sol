@@ -62,6 +62,8 @@ interface ICommonBridge {
     /// @dev The deposit process starts here by emitting a L1ToL2Message
     /// event. This event will later be intercepted by the L2 operator to
     /// finalize the deposit.
+    /// @dev Reverts if the native currency of the L2 is an ERC20, which is
+    /// deposited through depositERC20() instead.
     /// @param l2Recipient the address on L2 that will receive the deposit.
     function deposit(address l2Recipient) external payable;
 
@@ -110,6 +112,7 @@ interface ICommonBridge {
     /// - The withdrawal proof is valid. This is, there exists a merkle path
     /// from the withdrawal log to the withdrawal root, hence the claimed
     /// withdrawal exists.
+    /// @dev It releases ETH, or the native ERC20 if the L2 uses one.
     /// @dev We do not need to check that the claimee is the same as the
     /// beneficiary of the withdrawal, because the withdrawal proof already
     /// contains the beneficiary.
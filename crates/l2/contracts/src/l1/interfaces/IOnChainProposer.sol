@@ -6,6 +6,17 @@ pragma solidity =0.8.29;
 /// @notice A OnChainProposer contract ensures the advancement of the L2. It is used
 /// by the proposer to commit batches of l2 blocks and verify proofs.
 interface IOnChainProposer {
+    /// @notice How a validium publishes the data of its batches.
+    /// @dev A rollup always publishes it in blobs.
+    enum DataAvailabilityMode {
+        /// The data isn't published, it stays in the sequencer.
+        None,
+        /// The data is published in the calldata of the commit.
+        Calldata,
+        /// The data is stored by the members of a committee, which sign attestations of it.
+        Committee
+    }
+
     /// @notice The latest committed batch number.
     /// @return The latest committed batch number as a uint256.
     function lastCommittedBatch() external view returns (uint256);
@@ -23,10 +34,23 @@ interface IOnChainProposer {
     /// @dev Event emitted when a batch is verified.
     event BatchVerified(uint256 indexed lastVerifiedBatch);
 
+    /// @notice The fee configuration of the L2 has been updated.
+    /// @dev Event emitted when the owner sets the fee configuration hash.
+    /// @param feeConfigHash The hash of the new L2 fee configuration.
+    event FeeConfigHashUpdated(bytes32 indexed feeConfigHash);
+
     /// @notice A batch has been reverted.
     /// @dev Event emitted when a batch is reverted.
     event BatchReverted(bytes32 indexed newStateRoot);
 
+    /// @notice The data availability of the validium has been updated.
+    /// @dev Event emitted when the owner sets the data availability mode.
+    event DataAvailabilityUpdated(
+        DataAvailabilityMode mode,
+        address[] committee,
+        uint256 threshold
+    );
+
     /// @notice Set the bridge address for the first time.
     /// @dev This method is separated from initialize because both the CommonBridge
     /// and the OnChainProposer need to know the address of the other. This solves
@@ -34,6 +58,24 @@ interface IOnChainProposer {
     /// @param bridge the address of the bridge contract.
     function initializeBridgeAddress(address bridge) external;
 
+    /// @notice Sets the hash of the fee configuration the L2 batches must be executed with.
+    /// @dev Only the owner can call it, the sequencers must update their fee configuration
+    /// at the same time, otherwise their commitments are rejected.
+    /// @param feeConfigHash the hash of the new L2 fee configuration.
+    function setFeeConfigHash(bytes32 feeConfigHash) external;
+
+    /// @notice Sets how the validium publishes the data of its batches.
+    /// @dev Only the owner can call it, and only in validium mode.
+    /// @param mode the data availability mode.
+    /// @param committee the members of the data availability committee, only
+    /// in `Committee` mode.
+    /// @param threshold the amount of members that must attest the data of a batch.
+    function setDataAvailability(
+        DataAvailabilityMode mode,
+        address[] calldata committee,
+        uint256 threshold
+    ) external;
+
     /// @notice Commits to a batch of L2 blocks.
     /// @dev Committing to an L2 batch means to store the batch's commitment
     /// and to publish withdrawals if any.
@@ -44,12 +86,21 @@ interface IOnChainProposer {
     /// @param processedPrivilegedTransactionsRollingHash the rolling hash of the processed
     /// privileged transactions of the batch to be committed.
     /// @param lastBlockHash the hash of the last block of the batch to be committed.
+    /// @param feeConfigHash the hash of the L2 fee configuration used to execute the batch.
+    /// @param stateDiffData the state diff of the batch, in `Calldata` mode.
+    /// @param stateDiffHash the hash of the state diff of the batch, in `Committee` mode.
+    /// @param daSignatures the attestations of the committee members over `stateDiffHash`,
+    /// sorted by signer address, in `Committee` mode.
     function commitBatch(
         uint256 batchNumber,
         bytes32 newStateRoot,
         bytes32 withdrawalsLogsMerkleRoot,
         bytes32 processedPrivilegedTransactionsRollingHash,
-        bytes32 lastBlockHash
+        bytes32 lastBlockHash,
+        bytes32 feeConfigHash,
+        bytes calldata stateDiffData,
+        bytes32 stateDiffHash,
+        bytes[] calldata daSignatures
     ) external;
 
     /// @notice Method used to verify a batch of L2 blocks.
@@ -0,0 +1,53 @@
+// SPDX-License-Identifier: MIT
+pragma solidity =0.8.29;
+
+import "./interfaces/IL1FeeOracle.sol";
+
+/// @title L1FeeOracle contract.
+/// @author LambdaClass
+/// @notice The storage layout is read directly by the L2 VM, don't reorder it.
+contract L1FeeOracle is IL1FeeOracle {
+    /// @notice Price in wei per compressed byte, from the block after `lastUpdateBlock`.
+    uint256 public l1FeePerByte;
+
+    /// @notice Recipient of the L1 fees, the coinbase if zero.
+    address public feeVault;
+
+    /// @notice Price in effect during `lastUpdateBlock`.
+    uint256 public previousL1FeePerByte;
+
+    /// @notice Block in which `l1FeePerByte` was last set.
+    uint256 public lastUpdateBlock;
+
+    modifier onlySequencer() {
+        require(
+            msg.sender == block.coinbase,
+            "L1FeeOracle: caller is not the sequencer"
+        );
+        _;
+    }
+
+    /// @inheritdoc IL1FeeOracle
+    function currentL1FeePerByte() external view returns (uint256) {
+        if (block.number == lastUpdateBlock) {
+            return previousL1FeePerByte;
+        }
+        return l1FeePerByte;
+    }
+
+    /// @inheritdoc IL1FeeOracle
+    function setL1FeePerByte(uint256 _l1FeePerByte) external onlySequencer {
+        if (block.number != lastUpdateBlock) {
+            previousL1FeePerByte = l1FeePerByte;
+            lastUpdateBlock = block.number;
+        }
+        l1FeePerByte = _l1FeePerByte;
+        emit L1FeePerByteUpdated(_l1FeePerByte);
+    }
+
+    /// @inheritdoc IL1FeeOracle
+    function setFeeVault(address _feeVault) external onlySequencer {
+        feeVault = _feeVault;
+        emit FeeVaultUpdated(_feeVault);
+    }
+}
@@ -0,0 +1,31 @@
+// SPDX-License-Identifier: MIT
+pragma solidity =0.8.29;
+
+/// @title Interface for the L1FeeOracle contract.
+/// @author LambdaClass
+/// @notice The L1FeeOracle holds the price the L2 transactions pay for the
+/// L1 data availability, it's updated by the sequencer as the L1 blob fees change.
+interface IL1FeeOracle {
+    /// @notice The L1 fee per byte was updated.
+    /// @param l1FeePerByte the new price, in wei per compressed byte
+    event L1FeePerByteUpdated(uint256 l1FeePerByte);
+
+    /// @notice The fee vault was updated.
+    /// @param feeVault the new recipient of the L1 fees
+    event FeeVaultUpdated(address feeVault);
+
+    /// @notice Price in wei per compressed byte charged to transactions of this block.
+    /// @dev Updates only take effect from the next block, so the fee of a
+    /// transaction doesn't depend on its position in the block.
+    function currentL1FeePerByte() external view returns (uint256);
+
+    /// @notice Sets the price in wei per compressed byte, starting from the next block.
+    /// @dev Only the sequencer of the current block can call it.
+    /// @param l1FeePerByte the new price
+    function setL1FeePerByte(uint256 l1FeePerByte) external;
+
+    /// @notice Sets the address receiving the L1 fees, the coinbase if zero.
+    /// @dev Only the sequencer of the current block can call it.
+    /// @param feeVault the new recipient
+    function setFeeVault(address feeVault) external;
+}
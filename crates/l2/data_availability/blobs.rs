@@ -0,0 +1,95 @@
+use bytes::Bytes;
+use ethrex_common::{
+    H256, U256,
+    types::{Blob, BlobsBundle, blobs_bundle},
+};
+use ethrex_l2_common::compression::data_from_blobs;
+use ethrex_rpc::{
+    clients::{EthClient, beacon::BeaconClient},
+    types::block_identifier::BlockIdentifier,
+};
+
+use super::{DataAvailability, DataAvailabilityError, Publication};
+
+/// Publishes the data of the batches in EIP-4844 blobs. The blobs are pruned by the
+/// beacon nodes after a few weeks, so they have to be saved to reconstruct the state.
+pub struct BlobsDataAvailability {
+    max_blobs_per_batch: usize,
+    /// Needed only to retrieve the blobs
+    beacon_client: Option<BeaconClient>,
+}
+
+impl BlobsDataAvailability {
+    pub fn new(max_blobs_per_batch: usize) -> Self {
+        Self {
+            max_blobs_per_batch,
+            beacon_client: None,
+        }
+    }
+
+    pub fn with_beacon_client(mut self, beacon_client: BeaconClient) -> Self {
+        self.beacon_client = Some(beacon_client);
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl DataAvailability for BlobsDataAvailability {
+    fn max_blobs_per_batch(&self) -> usize {
+        self.max_blobs_per_batch
+    }
+
+    async fn publish(
+        &self,
+        _batch_number: u64,
+        data: Bytes,
+    ) -> Result<Publication, DataAvailabilityError> {
+        let blobs = blobs_bundle::blobs_from_bytes(data)?;
+        Ok(Publication::Blobs(BlobsBundle::create_from_blobs(&blobs)?))
+    }
+
+    async fn retrieve(
+        &self,
+        eth_client: &EthClient,
+        commit_tx_hash: H256,
+    ) -> Result<Bytes, DataAvailabilityError> {
+        let beacon_client = self
+            .beacon_client
+            .as_ref()
+            .ok_or(DataAvailabilityError::MissingBeaconClient)?;
+
+        let tx = eth_client
+            .get_transaction_by_hash(commit_tx_hash)
+            .await?
+            .ok_or(DataAvailabilityError::TransactionNotFound(commit_tx_hash))?;
+        let versioned_hashes = tx
+            .blob_versioned_hashes
+            .ok_or(DataAvailabilityError::BlobsNotFound(commit_tx_hash))?;
+
+        // Get block slot from parent beacon block
+        let block = eth_client
+            .get_block_by_number(BlockIdentifier::Number(tx.block_number.as_u64()))
+            .await?;
+        let parent_beacon_hash = block
+            .header
+            .parent_beacon_block_root
+            .ok_or(DataAvailabilityError::BlobsNotFound(commit_tx_hash))?;
+        let parent_beacon_block = beacon_client.get_block_by_hash(parent_beacon_hash).await?;
+        let slot = parent_beacon_block.message.slot + U256::one();
+
+        // Keep the blobs of the transaction, in the order they were published
+        let sidecars = beacon_client.get_blobs_by_slot(slot).await?;
+        let mut blobs = Vec::new();
+        for versioned_hash in versioned_hashes {
+            let sidecar = sidecars
+                .iter()
+                .find(|sidecar| sidecar.versioned_hash() == versioned_hash)
+                .ok_or(DataAvailabilityError::BlobsNotFound(commit_tx_hash))?;
+            let blob = Blob::try_from(sidecar.blob.as_ref())
+                .map_err(|_| DataAvailabilityError::BlobsNotFound(commit_tx_hash))?;
+            blobs.push(blob);
+        }
+
+        Ok(data_from_blobs(&blobs)?)
+    }
+}
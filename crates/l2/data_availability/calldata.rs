@@ -0,0 +1,40 @@
+use bytes::Bytes;
+use ethrex_common::H256;
+use ethrex_rpc::clients::EthClient;
+
+use super::{DataAvailability, DataAvailabilityError, Publication, state_diff_data_from_calldata};
+
+/// Publishes the data of the batches in the calldata of the commit transactions. It's
+/// kept by every L1 node, but the data of a batch has to fit in a single transaction.
+pub struct CalldataDataAvailability;
+
+/// Transactions bigger than 128 KiB aren't relayed by the L1 nodes, one blob worth of
+/// data leaves room for the rest of the commit transaction.
+const MAX_BLOBS_PER_BATCH: usize = 1;
+
+#[async_trait::async_trait]
+impl DataAvailability for CalldataDataAvailability {
+    fn max_blobs_per_batch(&self) -> usize {
+        MAX_BLOBS_PER_BATCH
+    }
+
+    async fn publish(
+        &self,
+        _batch_number: u64,
+        data: Bytes,
+    ) -> Result<Publication, DataAvailabilityError> {
+        Ok(Publication::Calldata(data))
+    }
+
+    async fn retrieve(
+        &self,
+        eth_client: &EthClient,
+        commit_tx_hash: H256,
+    ) -> Result<Bytes, DataAvailabilityError> {
+        let tx = eth_client
+            .get_transaction_by_hash(commit_tx_hash)
+            .await?
+            .ok_or(DataAvailabilityError::TransactionNotFound(commit_tx_hash))?;
+        state_diff_data_from_calldata(&tx.data)
+    }
+}
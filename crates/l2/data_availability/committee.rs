@@ -0,0 +1,429 @@
+use std::{net::SocketAddr, path::PathBuf};
+
+use bytes::Bytes;
+use ethereum_types::Signature;
+use ethrex_common::{Address, H256, types::recover_address};
+use ethrex_l2_common::compression::compute_data_hash;
+use ethrex_l2_rpc::signer::Signer;
+use ethrex_rpc::clients::EthClient;
+use keccak_hash::keccak;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+use tracing::{debug, error, info, warn};
+
+use super::{DataAvailability, DataAvailabilityError, Publication, state_diff_hash_from_calldata};
+
+/// Messages between the sequencer and the servers of a data availability committee.
+#[derive(Serialize, Deserialize)]
+pub enum DAServerMessage {
+    /// Request to store the data of a batch, signed by the sequencer
+    Store {
+        batch_number: u64,
+        data: Bytes,
+        signature: Signature,
+    },
+    /// Signature of a server over the hash of the data it stored
+    Attestation {
+        signer: Address,
+        signature: Signature,
+    },
+    /// Request of the data with the given hash
+    Get { data_hash: H256 },
+    /// The requested data, if the server has it
+    Data { data: Option<Bytes> },
+}
+
+/// Maximum size of a message between the sequencer and a DA server. The data of a
+/// batch is JSON encoded as hex, so this leaves room for batches of several MB.
+const MAX_MESSAGE_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Message the sequencer signs to authenticate its request to store the data with
+/// the given hash.
+fn store_request_message(batch_number: u64, data_hash: H256) -> Bytes {
+    [&batch_number.to_be_bytes()[..], data_hash.as_bytes()]
+        .concat()
+        .into()
+}
+
+/// Publishes the data of the batches to a committee of data availability servers. Each
+/// of them stores it and signs an attestation, the commit is accepted by the
+/// `OnChainProposer` only with the attestations of `threshold` members.
+pub struct CommitteeDataAvailability {
+    members: Vec<Url>,
+    threshold: usize,
+    max_blobs_per_batch: usize,
+    /// Signer of the sequencer, the servers only store the data it sends
+    signer: Option<Signer>,
+}
+
+impl CommitteeDataAvailability {
+    pub fn new(members: Vec<Url>, threshold: usize, max_blobs_per_batch: usize) -> Self {
+        Self {
+            members,
+            threshold,
+            max_blobs_per_batch,
+            signer: None,
+        }
+    }
+
+    pub fn with_signer(mut self, signer: Signer) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
+    async fn store(
+        member: &Url,
+        request: &DAServerMessage,
+        data_hash: H256,
+    ) -> Result<(Address, Signature), DataAvailabilityError> {
+        let DAServerMessage::Attestation { signer, signature } =
+            send_request(member, request).await?
+        else {
+            return Err(DataAvailabilityError::UnexpectedResponse);
+        };
+        // The OnChainProposer recovers the signer from the signature, a wrong one
+        // would make the commit fail
+        let recovered = recover_address(signature, keccak(data_hash))
+            .map_err(|_| DataAvailabilityError::UnexpectedResponse)?;
+        if recovered != signer {
+            return Err(DataAvailabilityError::UnexpectedResponse);
+        }
+        Ok((signer, signature))
+    }
+}
+
+#[async_trait::async_trait]
+impl DataAvailability for CommitteeDataAvailability {
+    fn max_blobs_per_batch(&self) -> usize {
+        self.max_blobs_per_batch
+    }
+
+    async fn publish(
+        &self,
+        batch_number: u64,
+        data: Bytes,
+    ) -> Result<Publication, DataAvailabilityError> {
+        let signer = self
+            .signer
+            .as_ref()
+            .ok_or(DataAvailabilityError::MissingSigner)?;
+        let data_hash = compute_data_hash(&data);
+        let request = DAServerMessage::Store {
+            batch_number,
+            signature: signer
+                .sign(store_request_message(batch_number, data_hash))
+                .await?,
+            data,
+        };
+
+        let mut attestations = Vec::new();
+        for member in &self.members {
+            match Self::store(member, &request, data_hash).await {
+                Ok(attestation) => attestations.push(attestation),
+                Err(err) => warn!("DA server {member} didn't attest batch {batch_number}: {err}"),
+            }
+        }
+
+        // The OnChainProposer checks the signers are different by requiring them sorted,
+        // so repeated members don't count towards the threshold
+        attestations.sort_by_key(|(signer, _)| *signer);
+        attestations.dedup_by_key(|(signer, _)| *signer);
+        if attestations.len() < self.threshold {
+            return Err(DataAvailabilityError::NotEnoughAttestations(
+                attestations.len(),
+                self.threshold,
+            ));
+        }
+
+        Ok(Publication::Attestations {
+            data_hash,
+            signatures: attestations
+                .into_iter()
+                .map(|(_, signature)| signature)
+                .collect(),
+        })
+    }
+
+    async fn retrieve(
+        &self,
+        eth_client: &EthClient,
+        commit_tx_hash: H256,
+    ) -> Result<Bytes, DataAvailabilityError> {
+        let tx = eth_client
+            .get_transaction_by_hash(commit_tx_hash)
+            .await?
+            .ok_or(DataAvailabilityError::TransactionNotFound(commit_tx_hash))?;
+        let data_hash = state_diff_hash_from_calldata(&tx.data)?;
+
+        for member in &self.members {
+            match send_request(member, &DAServerMessage::Get { data_hash }).await {
+                Ok(DAServerMessage::Data { data: Some(data) })
+                    if compute_data_hash(&data) == data_hash =>
+                {
+                    return Ok(data);
+                }
+                Ok(_) => warn!("DA server {member} doesn't have the data {data_hash:#x}"),
+                Err(err) => warn!("Failed to get the data {data_hash:#x} from {member}: {err}"),
+            }
+        }
+        Err(DataAvailabilityError::DataNotFound(data_hash))
+    }
+}
+
+async fn send_request(
+    url: &Url,
+    request: &DAServerMessage,
+) -> Result<DAServerMessage, DataAvailabilityError> {
+    let mut stream = TcpStream::connect(&*url.socket_addrs(|| None)?).await?;
+
+    stream.write_all(&serde_json::to_vec(request)?).await?;
+    stream.shutdown().await?;
+
+    read_message(&mut stream).await
+}
+
+/// Reads a whole message from the stream, rejecting those over `MAX_MESSAGE_SIZE`.
+async fn read_message(stream: &mut TcpStream) -> Result<DAServerMessage, DataAvailabilityError> {
+    let mut buffer = Vec::new();
+    stream
+        .take(MAX_MESSAGE_SIZE + 1)
+        .read_to_end(&mut buffer)
+        .await?;
+    if u64::try_from(buffer.len()).unwrap_or(u64::MAX) > MAX_MESSAGE_SIZE {
+        return Err(DataAvailabilityError::MessageTooLarge(MAX_MESSAGE_SIZE));
+    }
+    Ok(serde_json::from_slice(&buffer)?)
+}
+
+/// Runs a server of a data availability committee. It stores the data the sequencer
+/// sends in `data_dir`, signing an attestation for each batch, and serves it back to
+/// whoever reconstructs the state. Requests to store data not signed by `sequencer`
+/// are rejected.
+pub async fn start_da_server(
+    listen_addr: SocketAddr,
+    signer: Signer,
+    sequencer: Address,
+    data_dir: PathBuf,
+) -> Result<(), DataAvailabilityError> {
+    tokio::fs::create_dir_all(&data_dir).await?;
+    let listener = TcpListener::bind(listen_addr).await?;
+    info!(
+        "DA server {:#x} listening on {listen_addr}",
+        signer.address()
+    );
+    serve(listener, signer, sequencer, data_dir).await
+}
+
+async fn serve(
+    listener: TcpListener,
+    signer: Signer,
+    sequencer: Address,
+    data_dir: PathBuf,
+) -> Result<(), DataAvailabilityError> {
+    loop {
+        let (stream, addr) = listener.accept().await?;
+        let signer = signer.clone();
+        let data_dir = data_dir.clone();
+        tokio::spawn(async move {
+            if let Err(err) = handle_connection(stream, &signer, sequencer, &data_dir).await {
+                error!("Error handling connection from {addr}: {err}");
+            } else {
+                debug!("Connection from {addr} handled successfully");
+            }
+        });
+    }
+}
+
+async fn handle_connection(
+    mut stream: TcpStream,
+    signer: &Signer,
+    sequencer: Address,
+    data_dir: &std::path::Path,
+) -> Result<(), DataAvailabilityError> {
+    let response = match read_message(&mut stream).await? {
+        DAServerMessage::Store {
+            batch_number,
+            data,
+            signature,
+        } => {
+            let data_hash = compute_data_hash(&data);
+            let request_signer = recover_address(
+                signature,
+                keccak(store_request_message(batch_number, data_hash)),
+            )
+            .map_err(|_| DataAvailabilityError::UnauthorizedRequest)?;
+            if request_signer != sequencer {
+                return Err(DataAvailabilityError::UnauthorizedRequest);
+            }
+            tokio::fs::write(data_dir.join(format!("{data_hash:#x}")), &data).await?;
+            let signature = signer
+                .sign(Bytes::copy_from_slice(data_hash.as_bytes()))
+                .await?;
+            info!("Stored data {data_hash:#x} of batch {batch_number}");
+            DAServerMessage::Attestation {
+                signer: signer.address(),
+                signature,
+            }
+        }
+        DAServerMessage::Get { data_hash } => {
+            let data = match tokio::fs::read(data_dir.join(format!("{data_hash:#x}"))).await {
+                Ok(data) => Some(Bytes::from(data)),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+                Err(err) => return Err(err.into()),
+            };
+            DAServerMessage::Data { data }
+        }
+        _ => {
+            warn!("Invalid request");
+            return Ok(());
+        }
+    };
+
+    stream.write_all(&serde_json::to_vec(&response)?).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethrex_l2_rpc::signer::LocalSigner;
+    use secp256k1::SecretKey;
+
+    fn signer(key: u8) -> Signer {
+        LocalSigner::new(SecretKey::from_slice(&[key; 32]).unwrap()).into()
+    }
+
+    /// Spawns a committee member with the given key, returning its URL.
+    async fn start_member(key: u8, sequencer: Address, test_name: &str) -> Url {
+        let signer = signer(key);
+        let data_dir =
+            std::env::temp_dir().join(format!("ethrex-da-{test_name}-{:#x}", signer.address()));
+        tokio::fs::create_dir_all(&data_dir).await.unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = Url::parse(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+        tokio::spawn(serve(listener, signer, sequencer, data_dir));
+        url
+    }
+
+    /// URL nobody listens on.
+    async fn unreachable_member() -> Url {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        Url::parse(&format!("http://{}", listener.local_addr().unwrap())).unwrap()
+    }
+
+    fn attestation_signers(publication: Publication) -> Vec<Address> {
+        let Publication::Attestations {
+            data_hash,
+            signatures,
+        } = publication
+        else {
+            unreachable!("the committee publishes attestations");
+        };
+        signatures
+            .into_iter()
+            .map(|signature| recover_address(signature, keccak(data_hash)).unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn attestations_are_sorted_by_signer() {
+        let sequencer = signer(0xaa);
+        let mut members = Vec::new();
+        for key in [3, 1, 2] {
+            members.push(start_member(key, sequencer.address(), "sorted").await);
+        }
+        let committee = CommitteeDataAvailability::new(members, 3, 1).with_signer(sequencer);
+
+        let publication = committee
+            .publish(1, Bytes::from_static(b"data"))
+            .await
+            .unwrap();
+        let signers = attestation_signers(publication);
+
+        let mut expected: Vec<_> = [1, 2, 3].map(|key| signer(key).address()).to_vec();
+        expected.sort();
+        assert_eq!(signers, expected);
+    }
+
+    #[tokio::test]
+    async fn repeated_members_count_once_towards_the_threshold() {
+        let sequencer = signer(0xaa);
+        let a = start_member(1, sequencer.address(), "repeated").await;
+        let b = start_member(2, sequencer.address(), "repeated").await;
+        let members = vec![a.clone(), a, b];
+
+        let committee =
+            CommitteeDataAvailability::new(members.clone(), 3, 1).with_signer(sequencer.clone());
+        assert!(matches!(
+            committee.publish(1, Bytes::from_static(b"data")).await,
+            Err(DataAvailabilityError::NotEnoughAttestations(2, 3))
+        ));
+
+        let committee = CommitteeDataAvailability::new(members, 2, 1).with_signer(sequencer);
+        let publication = committee
+            .publish(1, Bytes::from_static(b"data"))
+            .await
+            .unwrap();
+        assert_eq!(attestation_signers(publication).len(), 2);
+    }
+
+    #[tokio::test]
+    async fn publishing_fails_under_the_threshold() {
+        let sequencer = signer(0xaa);
+        let members = vec![
+            start_member(1, sequencer.address(), "threshold").await,
+            unreachable_member().await,
+        ];
+        let committee = CommitteeDataAvailability::new(members, 2, 1).with_signer(sequencer);
+
+        assert!(matches!(
+            committee.publish(1, Bytes::from_static(b"data")).await,
+            Err(DataAvailabilityError::NotEnoughAttestations(1, 2))
+        ));
+    }
+
+    #[tokio::test]
+    async fn members_only_store_data_signed_by_the_sequencer() {
+        let sequencer = signer(0xaa);
+        let member = start_member(1, sequencer.address(), "unauthorized").await;
+        let data = Bytes::from_static(b"unauthorized data");
+
+        let committee =
+            CommitteeDataAvailability::new(vec![member.clone()], 1, 1).with_signer(signer(0xbb));
+        assert!(matches!(
+            committee.publish(1, data.clone()).await,
+            Err(DataAvailabilityError::NotEnoughAttestations(0, 1))
+        ));
+
+        let response = send_request(
+            &member,
+            &DAServerMessage::Get {
+                data_hash: compute_data_hash(&data),
+            },
+        )
+        .await
+        .unwrap();
+        assert!(matches!(response, DAServerMessage::Data { data: None }));
+    }
+
+    #[tokio::test]
+    async fn oversized_requests_are_rejected() {
+        let member = start_member(1, signer(0xaa).address(), "oversized").await;
+        let mut stream = TcpStream::connect(&*member.socket_addrs(|| None).unwrap())
+            .await
+            .unwrap();
+
+        // The server stops reading once over the limit, so the write may fail
+        let request = vec![b' '; usize::try_from(MAX_MESSAGE_SIZE).unwrap() + 1];
+        let _ = stream.write_all(&request).await;
+        let _ = stream.shutdown().await;
+
+        let mut response = Vec::new();
+        let _ = stream.read_to_end(&mut response).await;
+        assert!(response.is_empty());
+    }
+}
@@ -0,0 +1,307 @@
+//! Backends where the sequencer publishes the data of its batches, so anyone can
+//! reconstruct the L2 state from it.
+//!
+//! A rollup always publishes it in EIP-4844 blobs. A validium can publish it in the
+//! calldata of the commit transaction, or give it to a committee of data availability
+//! servers whose signed attestations the `OnChainProposer` verifies.
+
+use std::sync::Arc;
+
+use bytes::Bytes;
+use ethereum_types::Signature;
+use ethrex_common::{H256, types::BlobsBundle};
+use ethrex_l2_common::compression::CompressionError;
+use ethrex_l2_rpc::signer::{Signer, SignerError};
+use ethrex_rpc::clients::{EthClient, EthClientError, beacon::errors::BeaconClientError};
+
+use crate::sequencer::configs::DataAvailabilityConfig;
+
+pub mod blobs;
+pub mod calldata;
+pub mod committee;
+
+pub use blobs::BlobsDataAvailability;
+pub use calldata::CalldataDataAvailability;
+pub use committee::{CommitteeDataAvailability, start_da_server};
+
+/// What the commit transaction of a batch carries to prove its data is available.
+#[derive(Clone, Debug)]
+pub enum Publication {
+    /// Blobs sent along the commit transaction
+    Blobs(BlobsBundle),
+    /// The data itself, in the calldata of the commit transaction
+    Calldata(Bytes),
+    /// Hash of the data and signatures of the committee members storing it, sorted
+    /// by the address of the signer
+    Attestations {
+        data_hash: H256,
+        signatures: Vec<Signature>,
+    },
+}
+
+// We need async_trait because the stabilized feature lacks support for object safety
+// (i.e. dyn DataAvailability)
+#[async_trait::async_trait]
+pub trait DataAvailability: Send + Sync {
+    /// Maximum number of blobs the data of a batch can span.
+    fn max_blobs_per_batch(&self) -> usize;
+
+    /// Makes the data of a batch available.
+    async fn publish(
+        &self,
+        batch_number: u64,
+        data: Bytes,
+    ) -> Result<Publication, DataAvailabilityError>;
+
+    /// Retrieves the data published by the given commit transaction.
+    async fn retrieve(
+        &self,
+        eth_client: &EthClient,
+        commit_tx_hash: H256,
+    ) -> Result<Bytes, DataAvailabilityError>;
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DataAvailabilityError {
+    #[error("DataAvailability failed because of an EthClient error: {0}")]
+    EthClientError(#[from] EthClientError),
+    #[error("DataAvailability failed because of a BeaconClient error: {0}")]
+    BeaconClientError(#[from] BeaconClientError),
+    #[error("DataAvailability failed to create the blobs bundle: {0}")]
+    BlobsBundleError(#[from] ethrex_common::types::BlobsBundleError),
+    #[error("DataAvailability failed to decode the batch data: {0}")]
+    CompressionError(#[from] CompressionError),
+    #[error("DataAvailability failed to sign: {0}")]
+    SignerError(#[from] SignerError),
+    #[error("DataAvailability connection failed: {0}")]
+    ConnectionError(#[from] std::io::Error),
+    #[error("DataAvailability failed when (de)serializing JSON: {0}")]
+    JsonError(#[from] serde_json::Error),
+    #[error("Transaction {0:#x} not found")]
+    TransactionNotFound(H256),
+    #[error("Blobs not found for transaction {0:#x}")]
+    BlobsNotFound(H256),
+    #[error("A beacon node is needed to retrieve blobs")]
+    MissingBeaconClient,
+    #[error("The sequencer signer is needed to publish to the committee")]
+    MissingSigner,
+    #[error("Malformed commit transaction calldata")]
+    MalformedCalldata,
+    #[error("Got {0} attestations, {1} are needed")]
+    NotEnoughAttestations(usize, usize),
+    #[error("No committee member has the data with hash {0:#x}")]
+    DataNotFound(H256),
+    #[error("Unexpected DA server response")]
+    UnexpectedResponse,
+    #[error("DA server request not signed by the sequencer")]
+    UnauthorizedRequest,
+    #[error("DA server message larger than {0} bytes")]
+    MessageTooLarge(u64),
+}
+
+/// Builds the backend a sequencer with the given config publishes its data to, if any.
+pub fn from_config(
+    config: &DataAvailabilityConfig,
+    max_blobs_per_batch: usize,
+    signer: &Signer,
+) -> Option<Arc<dyn DataAvailability>> {
+    match config {
+        DataAvailabilityConfig::Blobs => {
+            Some(Arc::new(BlobsDataAvailability::new(max_blobs_per_batch)))
+        }
+        DataAvailabilityConfig::Calldata => Some(Arc::new(CalldataDataAvailability)),
+        DataAvailabilityConfig::Committee { members, threshold } => Some(Arc::new(
+            CommitteeDataAvailability::new(members.clone(), *threshold, max_blobs_per_batch)
+                .with_signer(signer.clone()),
+        )),
+        DataAvailabilityConfig::None => None,
+    }
+}
+
+// Layout of the `commitBatch` calldata, after the function selector:
+// [batchNumber, newStateRoot, withdrawalsLogsMerkleRoot, processedPrivilegedTransactionsRollingHash,
+//  lastBlockHash, feeConfigHash, stateDiffData offset, stateDiffHash, daSignatures offset, ...]
+const SELECTOR_LEN: usize = 4;
+const WORD_LEN: usize = 32;
+const BATCH_NUMBER_INDEX: usize = 0;
+const STATE_DIFF_DATA_OFFSET_INDEX: usize = 6;
+const STATE_DIFF_HASH_INDEX: usize = 7;
+
+fn calldata_word(calldata: &[u8], offset: usize) -> Result<&[u8], DataAvailabilityError> {
+    let start = SELECTOR_LEN
+        .checked_add(offset)
+        .ok_or(DataAvailabilityError::MalformedCalldata)?;
+    let end = start
+        .checked_add(WORD_LEN)
+        .ok_or(DataAvailabilityError::MalformedCalldata)?;
+    calldata
+        .get(start..end)
+        .ok_or(DataAvailabilityError::MalformedCalldata)
+}
+
+fn calldata_word_as_usize(calldata: &[u8], offset: usize) -> Result<usize, DataAvailabilityError> {
+    let word = calldata_word(calldata, offset)?;
+    let (high, low) = word.split_at(WORD_LEN - size_of::<u64>());
+    if high.iter().any(|byte| *byte != 0) {
+        return Err(DataAvailabilityError::MalformedCalldata);
+    }
+    let low: [u8; 8] = low
+        .try_into()
+        .map_err(|_| DataAvailabilityError::MalformedCalldata)?;
+    usize::try_from(u64::from_be_bytes(low)).map_err(|_| DataAvailabilityError::MalformedCalldata)
+}
+
+/// Number of the batch committed by a `commitBatch` call.
+pub fn batch_number_from_calldata(calldata: &[u8]) -> Result<u64, DataAvailabilityError> {
+    let batch_number = calldata_word_as_usize(calldata, BATCH_NUMBER_INDEX * WORD_LEN)?;
+    u64::try_from(batch_number).map_err(|_| DataAvailabilityError::MalformedCalldata)
+}
+
+/// Data published in the calldata of a `commitBatch` call.
+pub(crate) fn state_diff_data_from_calldata(
+    calldata: &[u8],
+) -> Result<Bytes, DataAvailabilityError> {
+    let offset = calldata_word_as_usize(calldata, STATE_DIFF_DATA_OFFSET_INDEX * WORD_LEN)?;
+    let len = calldata_word_as_usize(calldata, offset)?;
+    let start = SELECTOR_LEN
+        .checked_add(offset)
+        .and_then(|start| start.checked_add(WORD_LEN))
+        .ok_or(DataAvailabilityError::MalformedCalldata)?;
+    let end = start
+        .checked_add(len)
+        .ok_or(DataAvailabilityError::MalformedCalldata)?;
+    calldata
+        .get(start..end)
+        .map(Bytes::copy_from_slice)
+        .ok_or(DataAvailabilityError::MalformedCalldata)
+}
+
+/// Hash of the data attested by the committee in a `commitBatch` call.
+pub(crate) fn state_diff_hash_from_calldata(
+    calldata: &[u8],
+) -> Result<H256, DataAvailabilityError> {
+    Ok(H256::from_slice(calldata_word(
+        calldata,
+        STATE_DIFF_HASH_INDEX * WORD_LEN,
+    )?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sequencer::l1_committer::COMMIT_FUNCTION_SIGNATURE;
+    use ethrex_common::U256;
+    use ethrex_l2_common::calldata::Value;
+    use ethrex_l2_sdk::calldata::encode_calldata;
+
+    fn commit_calldata(
+        batch_number: u64,
+        state_diff_data: Bytes,
+        state_diff_hash: H256,
+        signatures: usize,
+    ) -> Vec<u8> {
+        let word = |byte: u8| Value::FixedBytes(vec![byte; WORD_LEN].into());
+        encode_calldata(
+            COMMIT_FUNCTION_SIGNATURE,
+            &[
+                Value::Uint(U256::from(batch_number)),
+                word(1),
+                word(2),
+                word(3),
+                word(4),
+                word(5),
+                Value::Bytes(state_diff_data),
+                Value::FixedBytes(state_diff_hash.0.to_vec().into()),
+                Value::Array(
+                    (0..signatures)
+                        .map(|_| Value::Bytes(vec![0xff; 65].into()))
+                        .collect(),
+                ),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn commit_calldata_is_parsed() {
+        // Not a multiple of the word length, to check the padding is left out
+        let data = Bytes::from((0..100).collect::<Vec<u8>>());
+        let hash = H256::repeat_byte(0xab);
+        let calldata = commit_calldata(7, data.clone(), hash, 2);
+
+        assert_eq!(batch_number_from_calldata(&calldata).unwrap(), 7);
+        assert_eq!(state_diff_data_from_calldata(&calldata).unwrap(), data);
+        assert_eq!(state_diff_hash_from_calldata(&calldata).unwrap(), hash);
+    }
+
+    #[test]
+    fn commit_calldata_without_state_diff_data_is_parsed() {
+        let hash = H256::repeat_byte(0xcd);
+        let calldata = commit_calldata(u64::MAX, Bytes::new(), hash, 0);
+
+        assert_eq!(batch_number_from_calldata(&calldata).unwrap(), u64::MAX);
+        assert!(state_diff_data_from_calldata(&calldata).unwrap().is_empty());
+        assert_eq!(state_diff_hash_from_calldata(&calldata).unwrap(), hash);
+    }
+
+    #[test]
+    fn truncated_commit_calldata_is_rejected() {
+        let calldata = commit_calldata(7, Bytes::from(vec![1; 100]), H256::zero(), 0);
+
+        // Cut in the middle of the state diff data
+        let truncated = calldata.get(..calldata.len() - 40).unwrap();
+        assert!(matches!(
+            state_diff_data_from_calldata(truncated),
+            Err(DataAvailabilityError::MalformedCalldata)
+        ));
+
+        // Cut before the state diff hash
+        let truncated = calldata
+            .get(..SELECTOR_LEN + STATE_DIFF_HASH_INDEX * WORD_LEN)
+            .unwrap();
+        assert!(matches!(
+            state_diff_hash_from_calldata(truncated),
+            Err(DataAvailabilityError::MalformedCalldata)
+        ));
+        assert!(matches!(
+            batch_number_from_calldata(&[]),
+            Err(DataAvailabilityError::MalformedCalldata)
+        ));
+    }
+
+    #[test]
+    fn out_of_range_offsets_are_rejected() {
+        let calldata = commit_calldata(7, Bytes::from(vec![1; 100]), H256::zero(), 0);
+        let offset_start = SELECTOR_LEN + STATE_DIFF_DATA_OFFSET_INDEX * WORD_LEN;
+
+        // An offset past the end of the calldata
+        let mut past_the_end = calldata.clone();
+        past_the_end
+            .get_mut(offset_start..offset_start + WORD_LEN)
+            .unwrap()
+            .copy_from_slice(&U256::from(calldata.len()).to_big_endian());
+        assert!(matches!(
+            state_diff_data_from_calldata(&past_the_end),
+            Err(DataAvailabilityError::MalformedCalldata)
+        ));
+
+        // An offset that doesn't fit in a usize
+        let mut too_big = calldata.clone();
+        too_big
+            .get_mut(offset_start..offset_start + WORD_LEN)
+            .unwrap()
+            .fill(0xff);
+        assert!(matches!(
+            state_diff_data_from_calldata(&too_big),
+            Err(DataAvailabilityError::MalformedCalldata)
+        ));
+
+        // A batch number that doesn't fit in a u64
+        let mut huge_batch_number = calldata;
+        *huge_batch_number.get_mut(SELECTOR_LEN).unwrap() = 1;
+        assert!(matches!(
+            batch_number_from_calldata(&huge_batch_number),
+            Err(DataAvailabilityError::MalformedCalldata)
+        ));
+    }
+}
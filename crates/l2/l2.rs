@@ -1,4 +1,5 @@
 pub mod based;
+pub mod data_availability;
 pub mod errors;
 pub mod monitor;
 pub mod sequencer;
@@ -6,7 +7,8 @@ pub mod utils;
 
 pub use based::{block_fetcher::BlockFetcher, state_updater::StateUpdater};
 pub use sequencer::configs::{
-    BasedConfig, BlockFetcherConfig, BlockProducerConfig, CommitterConfig, EthConfig,
-    L1WatcherConfig, ProofCoordinatorConfig, SequencerConfig, StateUpdaterConfig,
+    BasedConfig, BlockFetcherConfig, BlockProducerConfig, CommitterConfig, DataAvailabilityConfig,
+    EthConfig, HAConfig, L1FeeConfig, L1WatcherConfig, ProofCoordinatorConfig, SequencerConfig,
+    StateUpdaterConfig,
 };
 pub use sequencer::start_l2;
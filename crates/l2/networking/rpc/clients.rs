@@ -113,9 +113,30 @@ pub async fn send_tx_bump_gas_exponential_backoff(
     wrapped_tx: &mut WrappedTransaction,
     signer: &Signer,
 ) -> Result<H256, EthClientError> {
+    send_tx_bump_gas_exponential_backoff_while(client, wrapped_tx, signer, || async { true }).await
+}
+
+/// Same as [`send_tx_bump_gas_exponential_backoff`], but checks `may_send` before each
+/// broadcast of the transaction, giving up as soon as it returns false.
+pub async fn send_tx_bump_gas_exponential_backoff_while<F, Fut>(
+    client: &EthClient,
+    wrapped_tx: &mut WrappedTransaction,
+    signer: &Signer,
+    may_send: F,
+) -> Result<H256, EthClientError>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = bool>,
+{
     let mut number_of_retries = 0;
 
     'outer: while number_of_retries < client.max_number_of_retries {
+        if !may_send().await {
+            return Err(EthClientError::Custom(
+                "Stopped sending the transaction, it's no longer allowed".to_owned(),
+            ));
+        }
+
         if let Some(max_fee_per_gas) = client.maximum_allowed_max_fee_per_gas {
             let (tx_max_fee, tx_max_priority_fee) = match wrapped_tx {
                 WrappedTransaction::EIP4844(tx) => (
@@ -1,3 +1,4 @@
 pub mod batch;
 pub mod l1_message;
+pub mod preconfirmation;
 pub mod transaction;
@@ -0,0 +1,498 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use ethrex_common::{
+    H256, U256, serde_utils,
+    types::{BlockNumber, preconfirmation::PreconfirmationId},
+};
+use ethrex_levm::hooks::l2_hook::{L1_FEE_ORACLE_ADDRESS, L1_FEE_PER_BYTE_SLOT, calculate_l1_fee};
+use ethrex_rpc::types::receipt::{RpcReceipt, RpcReceiptBlockInfo, RpcReceiptTxInfo};
+use serde::Serialize;
+use serde_json::Value;
+use tracing::{debug, error};
+
+use crate::{
+    rpc::{RpcApiContext, RpcHandler},
+    utils::{RpcErr, parse_json_hex},
+};
+
+/// Maps the ids of the preconfirmation subscriptions to the time they were last polled
+/// and the last preconfirmation they got.
+pub type PreconfirmationSubscriptions =
+    Arc<Mutex<HashMap<u64, (Instant, Option<PreconfirmationId>)>>>;
+
+/// Removes the subscriptions that weren't polled in `subscription_duration`.
+pub fn clean_outdated_subscriptions(
+    subscriptions: PreconfirmationSubscriptions,
+    subscription_duration: Duration,
+) {
+    match subscriptions.lock() {
+        Ok(mut subscriptions) => {
+            subscriptions.retain(|_, (last_poll, _)| last_poll.elapsed() <= subscription_duration)
+        }
+        Err(err) => error!("Failed to clean the preconfirmation subscriptions: {err}"),
+    }
+}
+
+/// Transactions preconfirmed by the sequencer, with their receipts in the block that
+/// will include them. Pending receipts have a zero block hash.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcPreconfirmation {
+    #[serde(with = "serde_utils::u64::hex_str")]
+    pub block_number: BlockNumber,
+    #[serde(with = "serde_utils::u64::hex_str")]
+    pub index: u64,
+    #[serde(with = "serde_utils::u64::hex_str")]
+    pub timestamp: u64,
+    pub receipts: Vec<RpcReceipt>,
+}
+
+/// Builds the receipts of the preconfirmed transactions of a block that isn't sealed yet.
+async fn get_block_rpc_preconfirmations(
+    block_number: BlockNumber,
+    context: &RpcApiContext,
+) -> Result<Vec<(PreconfirmationId, RpcPreconfirmation)>, RpcErr> {
+    let preconfirmations = context
+        .l1_ctx
+        .blockchain
+        .preconfirmations
+        .get_block_preconfirmations(block_number)?;
+    // L2 transactions pay the L1 fee per byte set in the oracle by the end of the previous block
+    let l1_fee_per_byte = context
+        .l1_ctx
+        .storage
+        .get_storage_at(
+            block_number.saturating_sub(1),
+            L1_FEE_ORACLE_ADDRESS,
+            L1_FEE_PER_BYTE_SLOT,
+        )
+        .await?
+        .unwrap_or_default();
+    let block_info = RpcReceiptBlockInfo {
+        block_hash: H256::zero(),
+        block_number,
+    };
+
+    let mut rpc_preconfirmations = Vec::new();
+    let mut transaction_index = 0;
+    let mut last_cumulative_gas_used = 0;
+    let mut current_log_index = 0;
+    for preconfirmation in preconfirmations {
+        let mut receipts = Vec::new();
+        for (tx, receipt) in preconfirmation
+            .transactions
+            .iter()
+            .zip(preconfirmation.receipts.iter())
+        {
+            let gas_used = receipt
+                .cumulative_gas_used
+                .saturating_sub(last_cumulative_gas_used);
+            let mut tx_info = RpcReceiptTxInfo::from_transaction(
+                tx.clone(),
+                transaction_index,
+                gas_used,
+                0,
+                Some(preconfirmation.base_fee_per_gas),
+            )?;
+            tx_info.l1_fee = Some(if tx.is_privileged() || tx_info.effective_gas_price == 0 {
+                U256::zero()
+            } else {
+                calculate_l1_fee(tx, l1_fee_per_byte)
+                    .map_err(|error| RpcErr::Internal(error.to_string()))?
+            });
+            let receipt = RpcReceipt::new(
+                receipt.clone(),
+                tx_info,
+                block_info.clone(),
+                current_log_index,
+            );
+            transaction_index += 1;
+            last_cumulative_gas_used = receipt.receipt.cumulative_gas_used;
+            current_log_index += u64::try_from(receipt.logs.len())
+                .map_err(|error| RpcErr::Internal(error.to_string()))?;
+            receipts.push(receipt);
+        }
+        rpc_preconfirmations.push((
+            preconfirmation.id(),
+            RpcPreconfirmation {
+                block_number,
+                index: preconfirmation.index,
+                timestamp: preconfirmation.timestamp,
+                receipts,
+            },
+        ));
+    }
+    Ok(rpc_preconfirmations)
+}
+
+fn parse_subscription_id(params: &Option<Vec<Value>>) -> Result<u64, RpcErr> {
+    match params.as_deref() {
+        Some([param]) => {
+            parse_json_hex(param).map_err(|_err| ethrex_rpc::RpcErr::BadHexFormat(0).into())
+        }
+        Some(_) => Err(ethrex_rpc::RpcErr::BadParams(
+            "Expected an array with a single hex encoded id".to_string(),
+        )
+        .into()),
+        None => Err(ethrex_rpc::RpcErr::MissingParam("0".to_string()).into()),
+    }
+}
+
+pub struct SubscribePreconfirmationsRequest;
+
+impl RpcHandler for SubscribePreconfirmationsRequest {
+    fn parse(_params: &Option<Vec<Value>>) -> Result<Self, RpcErr> {
+        Ok(SubscribePreconfirmationsRequest)
+    }
+
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+        // Subscribers only get the preconfirmations announced after subscribing
+        let last_preconfirmation = context.l1_ctx.blockchain.preconfirmations.last_id()?;
+        let id: u64 = rand::random();
+        context
+            .preconfirmation_subscriptions
+            .lock()
+            .map_err(|error| RpcErr::Internal(error.to_string()))?
+            .insert(id, (Instant::now(), last_preconfirmation));
+        debug!("New preconfirmation subscription {id:#x}");
+        Ok(Value::String(format!("{id:#x}")))
+    }
+}
+
+/// Preconfirmations announced since the last time the subscription was polled.
+pub struct GetPreconfirmationChangesRequest {
+    pub id: u64,
+}
+
+impl RpcHandler for GetPreconfirmationChangesRequest {
+    fn parse(params: &Option<Vec<Value>>) -> Result<Self, RpcErr> {
+        Ok(GetPreconfirmationChangesRequest {
+            id: parse_subscription_id(params)?,
+        })
+    }
+
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+        let last_preconfirmation = context
+            .preconfirmation_subscriptions
+            .lock()
+            .map_err(|error| RpcErr::Internal(error.to_string()))?
+            .get(&self.id)
+            .map(|(_, last_preconfirmation)| *last_preconfirmation)
+            .ok_or(ethrex_rpc::RpcErr::BadParams(
+                "Subscription not found".to_string(),
+            ))?;
+
+        let new_preconfirmations = context
+            .l1_ctx
+            .blockchain
+            .preconfirmations
+            .get_preconfirmations_after(last_preconfirmation)?;
+        let mut block_numbers: Vec<_> = new_preconfirmations
+            .iter()
+            .map(|(preconfirmation, _)| preconfirmation.block_number)
+            .collect();
+        block_numbers.dedup();
+
+        let mut changes = Vec::new();
+        let mut new_last_preconfirmation = last_preconfirmation;
+        for block_number in block_numbers {
+            for (id, rpc_preconfirmation) in
+                get_block_rpc_preconfirmations(block_number, &context).await?
+            {
+                if last_preconfirmation.is_some_and(|last| id <= last) {
+                    continue;
+                }
+                new_last_preconfirmation = Some(id);
+                changes.push(rpc_preconfirmation);
+            }
+        }
+
+        if let Some(subscription) = context
+            .preconfirmation_subscriptions
+            .lock()
+            .map_err(|error| RpcErr::Internal(error.to_string()))?
+            .get_mut(&self.id)
+        {
+            *subscription = (Instant::now(), new_last_preconfirmation);
+        }
+        Ok(serde_json::to_value(changes)?)
+    }
+}
+
+pub struct UnsubscribePreconfirmationsRequest {
+    pub id: u64,
+}
+
+impl RpcHandler for UnsubscribePreconfirmationsRequest {
+    fn parse(params: &Option<Vec<Value>>) -> Result<Self, RpcErr> {
+        Ok(UnsubscribePreconfirmationsRequest {
+            id: parse_subscription_id(params)?,
+        })
+    }
+
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+        let removed = context
+            .preconfirmation_subscriptions
+            .lock()
+            .map_err(|error| RpcErr::Internal(error.to_string()))?
+            .remove(&self.id)
+            .is_some();
+        Ok(Value::Bool(removed))
+    }
+}
+
+/// Receipt of a transaction preconfirmed in a block that isn't sealed yet.
+pub struct GetPendingTransactionReceiptRequest {
+    pub transaction_hash: H256,
+}
+
+impl RpcHandler for GetPendingTransactionReceiptRequest {
+    fn parse(params: &Option<Vec<Value>>) -> Result<Self, RpcErr> {
+        let params = params.as_ref().ok_or(ethrex_rpc::RpcErr::BadParams(
+            "No params provided".to_owned(),
+        ))?;
+        let [transaction_hash] = params.as_slice() else {
+            return Err(ethrex_rpc::RpcErr::BadParams(format!(
+                "Expected one param and {} were provided",
+                params.len()
+            ))
+            .into());
+        };
+        Ok(GetPendingTransactionReceiptRequest {
+            transaction_hash: serde_json::from_value(transaction_hash.clone())?,
+        })
+    }
+
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+        let Some(block_number) = context
+            .l1_ctx
+            .blockchain
+            .preconfirmations
+            .get_transaction_block(self.transaction_hash)?
+        else {
+            return Ok(Value::Null);
+        };
+        let receipt = get_block_rpc_preconfirmations(block_number, &context)
+            .await?
+            .into_iter()
+            .flat_map(|(_, rpc_preconfirmation)| rpc_preconfirmation.receipts)
+            .find(|receipt| receipt.tx_info.transaction_hash == self.transaction_hash);
+        Ok(serde_json::to_value(receipt)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signer::{LocalSigner, Signable, Signer};
+    use ethrex_blockchain::Blockchain;
+    use ethrex_common::{
+        H512,
+        types::{
+            EIP1559Transaction, Receipt, Transaction, TxKind, TxType,
+            preconfirmation::Preconfirmation,
+        },
+    };
+    use ethrex_p2p::{
+        peer_handler::PeerHandler,
+        sync_manager::SyncManager,
+        types::{Node, NodeRecord},
+    };
+    use ethrex_rpc::{GasTipEstimator, NodeData, utils::RpcRequest};
+    use ethrex_storage::{EngineType, Store};
+    use ethrex_storage_rollup::{EngineTypeRollup, StoreRollup};
+    use secp256k1::SecretKey;
+    use serde_json::json;
+    use tokio::sync::Mutex as TokioMutex;
+
+    const TX_GAS: u64 = 21_000;
+
+    async fn context() -> RpcApiContext {
+        let storage = Store::new("", EngineType::InMemory).unwrap();
+        let rollup_store = StoreRollup::new("", EngineTypeRollup::InMemory).unwrap();
+        rollup_store.init().await.unwrap();
+        let node_key = SecretKey::from_slice(&[1; 32]).unwrap();
+        let local_p2p_node = Node::new("127.0.0.1".parse().unwrap(), 30303, 30303, H512::zero());
+        RpcApiContext {
+            l1_ctx: ethrex_rpc::RpcApiContext {
+                blockchain: Arc::new(Blockchain::default_with_store(storage.clone())),
+                storage,
+                active_filters: Default::default(),
+                syncer: Arc::new(SyncManager::dummy()),
+                peer_handler: PeerHandler::dummy(),
+                node_data: NodeData {
+                    jwt_secret: Default::default(),
+                    local_node_record: NodeRecord::from_node(&local_p2p_node, 1, &node_key)
+                        .unwrap(),
+                    local_p2p_node,
+                    client_version: "ethrex/test".to_string(),
+                },
+                gas_tip_estimator: Arc::new(TokioMutex::new(GasTipEstimator::new())),
+            },
+            valid_delegation_addresses: Vec::new(),
+            sponsor_pk: node_key,
+            rollup_store,
+            preconfirmation_subscriptions: Default::default(),
+        }
+    }
+
+    /// Preconfirmation with a single transfer, the `index`-th of its block
+    async fn preconfirmation(block_number: BlockNumber, index: u64) -> Preconfirmation {
+        let signer: Signer = LocalSigner::new(SecretKey::from_slice(&[2; 32]).unwrap()).into();
+        let tx = Transaction::EIP1559Transaction(EIP1559Transaction {
+            chain_id: 1,
+            nonce: block_number * 100 + index,
+            max_priority_fee_per_gas: 1,
+            max_fee_per_gas: 10,
+            gas_limit: TX_GAS,
+            to: TxKind::Call(Default::default()),
+            ..Default::default()
+        })
+        .sign(&signer)
+        .await
+        .unwrap();
+        Preconfirmation {
+            block_number,
+            index,
+            timestamp: block_number,
+            base_fee_per_gas: 1,
+            transactions: vec![tx],
+            receipts: vec![Receipt::new(
+                TxType::EIP1559,
+                true,
+                TX_GAS * (index + 1),
+                Vec::new(),
+            )],
+        }
+    }
+
+    fn tx_hash(preconfirmation: &Preconfirmation) -> H256 {
+        preconfirmation.transactions.first().unwrap().compute_hash()
+    }
+
+    async fn preconfirm(context: &RpcApiContext, block_number: BlockNumber, index: u64) -> H256 {
+        let preconfirmation = preconfirmation(block_number, index).await;
+        let hash = tx_hash(&preconfirmation);
+        context
+            .l1_ctx
+            .blockchain
+            .preconfirmations
+            .add_preconfirmation(preconfirmation, None)
+            .unwrap();
+        hash
+    }
+
+    /// Block number and index of each preconfirmation in the changes, along with the
+    /// index of the transaction of its receipt
+    async fn get_changes(context: &RpcApiContext, id: u64) -> Vec<(Value, Value, Value)> {
+        let changes = GetPreconfirmationChangesRequest { id }
+            .handle(context.clone())
+            .await
+            .unwrap();
+        changes
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|change| {
+                let receipt = change["receipts"].as_array().unwrap().first().unwrap();
+                (
+                    change["blockNumber"].clone(),
+                    change["index"].clone(),
+                    receipt["transactionIndex"].clone(),
+                )
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn preconfirmation_changes_are_returned_once() {
+        let context = context().await;
+        preconfirm(&context, 5, 0).await;
+
+        let id = SubscribePreconfirmationsRequest
+            .handle(context.clone())
+            .await
+            .unwrap();
+        let id = parse_json_hex(&id).unwrap();
+        // Subscribers only get the preconfirmations announced after subscribing
+        assert!(get_changes(&context, id).await.is_empty());
+
+        preconfirm(&context, 5, 1).await;
+        preconfirm(&context, 6, 0).await;
+        assert_eq!(
+            get_changes(&context, id).await,
+            vec![
+                (json!("0x5"), json!("0x1"), json!("0x1")),
+                (json!("0x6"), json!("0x0"), json!("0x0")),
+            ]
+        );
+        assert!(get_changes(&context, id).await.is_empty());
+
+        // The cursor of the subscription is removed along with its block
+        context
+            .l1_ctx
+            .blockchain
+            .preconfirmations
+            .remove_preconfirmations_before(6)
+            .unwrap();
+        preconfirm(&context, 6, 1).await;
+        assert_eq!(
+            get_changes(&context, id).await,
+            vec![(json!("0x6"), json!("0x1"), json!("0x1"))]
+        );
+
+        let removed = UnsubscribePreconfirmationsRequest { id }
+            .handle(context.clone())
+            .await
+            .unwrap();
+        assert_eq!(removed, Value::Bool(true));
+        assert!(
+            GetPreconfirmationChangesRequest { id }
+                .handle(context.clone())
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn pending_receipts_are_served_until_the_block_is_sealed() {
+        let context = context().await;
+        preconfirm(&context, 5, 0).await;
+        let tx_hash = preconfirm(&context, 5, 1).await;
+        let get_receipt = |tx_hash: H256| RpcRequest {
+            id: ethrex_rpc::utils::RpcRequestId::Number(1),
+            jsonrpc: "2.0".to_string(),
+            method: "eth_getTransactionReceipt".to_string(),
+            params: Some(vec![json!(format!("{tx_hash:#x}"))]),
+        };
+
+        let receipt = crate::rpc::map_eth_requests(&get_receipt(tx_hash), context.clone())
+            .await
+            .unwrap();
+        assert_eq!(receipt["transactionHash"], json!(format!("{tx_hash:#x}")));
+        assert_eq!(receipt["blockHash"], json!(format!("{:#x}", H256::zero())));
+        assert_eq!(receipt["blockNumber"], json!("0x5"));
+        assert_eq!(receipt["transactionIndex"], json!("0x1"));
+        assert_eq!(receipt["gasUsed"], json!(format!("{TX_GAS:#x}")));
+
+        let unknown = crate::rpc::map_eth_requests(&get_receipt(H256::zero()), context.clone())
+            .await
+            .unwrap();
+        assert!(unknown.is_null());
+
+        context
+            .l1_ctx
+            .blockchain
+            .preconfirmations
+            .remove_preconfirmations_before(6)
+            .unwrap();
+        let removed = crate::rpc::map_eth_requests(&get_receipt(tx_hash), context.clone())
+            .await
+            .unwrap();
+        assert!(removed.is_null());
+    }
+}
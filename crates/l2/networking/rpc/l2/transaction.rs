@@ -28,6 +28,25 @@ pub struct SponsoredTx {
     pub to: Address,
 }
 
+/// Estimates the L1 data fee a transaction pays on top of its gas, takes the same params as `eth_estimateGas`
+pub struct EstimateL1FeeRequest(ethrex_rpc::EstimateGasRequest);
+
+impl RpcHandler for EstimateL1FeeRequest {
+    fn parse(params: &Option<Vec<Value>>) -> Result<Self, RpcErr> {
+        Ok(Self(
+            <ethrex_rpc::EstimateGasRequest as ethrex_rpc::RpcHandler>::parse(params)?,
+        ))
+    }
+
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+        let Some(l1_fee) = self.0.l1_fee(&context.l1_ctx).await? else {
+            return Ok(Value::Null);
+        };
+        serde_json::to_value(format!("{l1_fee:#x}"))
+            .map_err(|error| RpcErr::Internal(error.to_string()))
+    }
+}
+
 // This endpoint is inspired by the work of Ithaca in Odyssey
 // https://ithaca.xyz/updates/exp-0000
 // You can check the reference implementation here
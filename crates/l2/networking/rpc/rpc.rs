@@ -1,5 +1,10 @@
 use crate::l2::batch::GetBatchByBatchNumberRequest;
 use crate::l2::l1_message::GetL1MessageProof;
+use crate::l2::preconfirmation::{
+    GetPendingTransactionReceiptRequest, GetPreconfirmationChangesRequest,
+    PreconfirmationSubscriptions, SubscribePreconfirmationsRequest,
+    UnsubscribePreconfirmationsRequest, clean_outdated_subscriptions,
+};
 use crate::utils::{RpcErr, RpcNamespace, resolve_namespace};
 use axum::extract::State;
 use axum::{Json, Router, http::StatusCode, routing::post};
@@ -29,7 +34,7 @@ use tokio::{net::TcpListener, sync::Mutex as TokioMutex};
 use tower_http::cors::CorsLayer;
 use tracing::{debug, info};
 
-use crate::l2::transaction::SponsoredTx;
+use crate::l2::transaction::{EstimateL1FeeRequest, SponsoredTx};
 use ethrex_common::Address;
 use ethrex_storage_rollup::StoreRollup;
 use secp256k1::SecretKey;
@@ -40,6 +45,7 @@ pub struct RpcApiContext {
     pub valid_delegation_addresses: Vec<Address>,
     pub sponsor_pk: SecretKey,
     pub rollup_store: StoreRollup,
+    pub preconfirmation_subscriptions: PreconfirmationSubscriptions,
 }
 
 pub trait RpcHandler: Sized {
@@ -80,6 +86,7 @@ pub async fn start_api(
     // TODO: Refactor how filters are handled,
     // filters are used by the filters endpoints (eth_newFilter, eth_getFilterChanges, ...etc)
     let active_filters = Arc::new(Mutex::new(HashMap::new()));
+    let preconfirmation_subscriptions = Arc::new(Mutex::new(HashMap::new()));
     let service_context = RpcApiContext {
         l1_ctx: ethrex_rpc::RpcApiContext {
             storage,
@@ -98,6 +105,7 @@ pub async fn start_api(
         valid_delegation_addresses,
         sponsor_pk,
         rollup_store,
+        preconfirmation_subscriptions: preconfirmation_subscriptions.clone(),
     };
 
     // Periodically clean up the active filters for the filters endpoints.
@@ -108,6 +116,7 @@ pub async fn start_api(
             interval.tick().await;
             tracing::info!("Running filter clean task");
             ethrex_rpc::clean_outdated_filters(filters.clone(), FILTER_DURATION);
+            clean_outdated_subscriptions(preconfirmation_subscriptions.clone(), FILTER_DURATION);
             tracing::info!("Filter clean task complete");
         }
     });
@@ -198,6 +207,16 @@ pub async fn map_eth_requests(req: &RpcRequest, context: RpcApiContext) -> Resul
                 .await
                 .map_err(RpcErr::L1RpcErr)
         }
+        "eth_getTransactionReceipt" => {
+            let receipt = ethrex_rpc::map_eth_requests(req, context.l1_ctx.clone())
+                .await
+                .map_err(RpcErr::L1RpcErr)?;
+            if !receipt.is_null() {
+                return Ok(receipt);
+            }
+            // The transaction may be preconfirmed in a block that isn't sealed yet
+            GetPendingTransactionReceiptRequest::call(req, context).await
+        }
         _other_eth_method => ethrex_rpc::map_eth_requests(req, context.l1_ctx)
             .await
             .map_err(RpcErr::L1RpcErr),
@@ -209,6 +228,16 @@ pub async fn map_l2_requests(req: &RpcRequest, context: RpcApiContext) -> Result
         "ethrex_sendTransaction" => SponsoredTx::call(req, context).await,
         "ethrex_getMessageProof" => GetL1MessageProof::call(req, context).await,
         "ethrex_getBatchByNumber" => GetBatchByBatchNumberRequest::call(req, context).await,
+        "ethrex_estimateL1Fee" => EstimateL1FeeRequest::call(req, context).await,
+        "ethrex_subscribePreconfirmations" => {
+            SubscribePreconfirmationsRequest::call(req, context).await
+        }
+        "ethrex_getPreconfirmationChanges" => {
+            GetPreconfirmationChangesRequest::call(req, context).await
+        }
+        "ethrex_unsubscribePreconfirmations" => {
+            UnsubscribePreconfirmationsRequest::call(req, context).await
+        }
         unknown_ethrex_l2_method => {
             Err(ethrex_rpc::RpcErr::MethodNotFound(unknown_ethrex_l2_method.to_owned()).into())
         }
@@ -1,6 +1,6 @@
 use crate::{backends::Backend, config::ProverConfig, prove, to_batch_proof};
 use ethrex_l2::sequencer::proof_coordinator::{ProofData, get_commit_hash};
-use ethrex_l2_common::prover::BatchProof;
+use ethrex_l2_common::prover::{BatchProof, ProverType};
 use std::time::Duration;
 use tokio::{
     io::{AsyncReadExt, AsyncWriteExt},
@@ -75,9 +75,22 @@ impl Prover {
         }
     }
 
+    /// Type of the proofs generated, the proof coordinator assigns batches that don't have one yet
+    fn prover_type(&self) -> ProverType {
+        match self.backend {
+            Backend::Exec => ProverType::Exec,
+            #[cfg(feature = "sp1")]
+            Backend::SP1 if self.aligned_mode => ProverType::Aligned,
+            #[cfg(feature = "sp1")]
+            Backend::SP1 => ProverType::SP1,
+            #[cfg(feature = "risc0")]
+            Backend::RISC0 => ProverType::RISC0,
+        }
+    }
+
     async fn request_new_input(&self) -> Result<Option<ProverData>, String> {
         // Request the input with the correct batch_number
-        let request = ProofData::batch_request(self.commit_hash.clone());
+        let request = ProofData::batch_request(self.commit_hash.clone(), self.prover_type());
         let response = connect_to_prover_server_wr(&self.proof_coordinator_endpoint, &request)
             .await
             .map_err(|e| format!("Failed to get Response: {e}"))?;
@@ -111,9 +124,9 @@ impl Prover {
                 db: input.db,
                 elasticity_multiplier: input.elasticity_multiplier,
                 #[cfg(feature = "l2")]
-                blob_commitment: input.blob_commitment,
+                blobs_bundle: input.blobs_bundle,
                 #[cfg(feature = "l2")]
-                blob_proof: input.blob_proof,
+                validium_data: input.validium_data,
             },
         }))
     }
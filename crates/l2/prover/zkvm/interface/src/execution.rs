@@ -21,10 +21,15 @@ use std::collections::HashMap;
 use ethrex_common::{
     kzg::KzgError,
     types::{
-        BlobsBundleError, Commitment, PrivilegedL2Transaction, Proof, Receipt, blob_from_bytes,
+        BlobsBundle, BlobsBundleError, PrivilegedL2Transaction, Receipt,
         kzg_commitment_to_versioned_hash,
     },
 };
+#[cfg(feature = "l2")]
+use ethrex_l2_common::compression::{
+    CompressionError, compute_blobs_versioned_hash, compute_data_hash, decode_state_diff,
+    decode_state_diff_from_blobs,
+};
 use ethrex_l2_common::{
     l1_messages::get_block_l1_messages,
     privileged_transactions::{
@@ -58,6 +63,9 @@ pub enum StatelessExecutionError {
     #[error("Blobs bundle error: {0}")]
     BlobsBundleError(#[from] BlobsBundleError),
     #[cfg(feature = "l2")]
+    #[error("Failed to decode the published state diff: {0}")]
+    CompressionError(#[from] CompressionError),
+    #[cfg(feature = "l2")]
     #[error("KZG error (proof couldn't be verified): {0}")]
     KzgError(#[from] KzgError),
     #[cfg(feature = "l2")]
@@ -99,9 +107,9 @@ pub fn execution_program(input: ProgramInput) -> Result<ProgramOutput, Stateless
         db,
         elasticity_multiplier,
         #[cfg(feature = "l2")]
-        blob_commitment,
+        blobs_bundle,
         #[cfg(feature = "l2")]
-        blob_proof,
+        validium_data,
     } = input;
     if cfg!(feature = "l2") {
         #[cfg(feature = "l2")]
@@ -109,8 +117,8 @@ pub fn execution_program(input: ProgramInput) -> Result<ProgramOutput, Stateless
             &blocks,
             db,
             elasticity_multiplier,
-            blob_commitment,
-            blob_proof,
+            blobs_bundle,
+            validium_data,
             chain_id,
         );
     }
@@ -142,6 +150,8 @@ pub fn stateless_validation_l1(
         last_block_hash,
         chain_id: chain_id.into(),
         non_privileged_count,
+        #[cfg(feature = "l2")]
+        fee_config_hash: H256::zero(),
     })
 }
 
@@ -150,8 +160,8 @@ pub fn stateless_validation_l2(
     blocks: &[Block],
     db: ExecutionWitnessResult,
     elasticity_multiplier: u64,
-    blob_commitment: Commitment,
-    blob_proof: Proof,
+    blobs_bundle: BlobsBundle,
+    validium_data: Vec<u8>,
     chain_id: u64,
 ) -> Result<ProgramOutput, StatelessExecutionError> {
     let mut initial_db = ExecutionWitnessResult {
@@ -164,6 +174,12 @@ pub fn stateless_validation_l2(
         state_trie: None,
         storage_tries: None,
     };
+    // The fee rules used to execute the batch, checked against the ones committed to the L1
+    let fee_config_hash = db
+        .chain_config
+        .fee_config
+        .map(|fee_config| fee_config.hash())
+        .unwrap_or_default();
 
     let StatelessResult {
         receipts,
@@ -184,10 +200,10 @@ pub fn stateless_validation_l2(
         )?;
 
     // TODO: this could be replaced with something like a ProverConfig in the future.
-    let validium = (blob_commitment, blob_proof) == ([0; 48], [0; 48]);
+    let validium = blobs_bundle.blobs.is_empty();
 
-    // Check state diffs are valid
-    let blob_versioned_hash = if !validium {
+    // Check state diffs are valid, unless a validium doesn't publish them
+    let blob_versioned_hash = if !validium || !validium_data.is_empty() {
         initial_db
             .rebuild_tries()
             .map_err(|_| StatelessExecutionError::InvalidInitialStateTrie)?;
@@ -199,7 +215,11 @@ pub fn stateless_validation_l2(
             &privileged_transactions,
             account_updates.values().cloned().collect(),
         )?;
-        verify_blob(state_diff, blob_commitment, blob_proof)?
+        if !validium {
+            verify_blobs(state_diff, blobs_bundle)?
+        } else {
+            verify_validium_data(state_diff, &validium_data)?
+        }
     } else {
         H256::zero()
     };
@@ -213,6 +233,7 @@ pub fn stateless_validation_l2(
         last_block_hash,
         chain_id: chain_id.into(),
         non_privileged_count,
+        fee_config_hash,
     })
 }
 
@@ -395,19 +416,93 @@ fn compute_l1messages_and_privileged_transactions_digests(
 }
 
 #[cfg(feature = "l2")]
-fn verify_blob(
+fn verify_blobs(
     state_diff: StateDiff,
-    commitment: Commitment,
-    proof: Proof,
+    blobs_bundle: BlobsBundle,
 ) -> Result<H256, StatelessExecutionError> {
     use ethrex_common::kzg::verify_blob_kzg_proof;
 
-    let encoded_state_diff = state_diff.encode()?;
-    let blob_data = blob_from_bytes(encoded_state_diff)?;
+    let BlobsBundle {
+        blobs,
+        commitments,
+        proofs,
+    } = blobs_bundle;
+    if blobs.len() != commitments.len() || blobs.len() != proofs.len() {
+        return Err(BlobsBundleError::BlobsBundleWrongLen.into());
+    }
+
+    for ((blob, commitment), proof) in blobs.iter().zip(&commitments).zip(&proofs) {
+        if !verify_blob_kzg_proof(*blob, *commitment, *proof)? {
+            return Err(StatelessExecutionError::InvalidBlobProof);
+        }
+    }
+
+    // The published state diff may be compressed, compare it decoded with the executed one
+    let published_state_diff = decode_state_diff_from_blobs(&blobs)?;
+    if published_state_diff.encode()? != state_diff.encode()? {
+        return Err(StatelessExecutionError::InvalidStateDiff);
+    }
+
+    let versioned_hashes: Vec<H256> = commitments
+        .iter()
+        .map(kzg_commitment_to_versioned_hash)
+        .collect();
+    Ok(compute_blobs_versioned_hash(&versioned_hashes))
+}
+
+#[cfg(feature = "l2")]
+fn verify_validium_data(
+    state_diff: StateDiff,
+    validium_data: &[u8],
+) -> Result<H256, StatelessExecutionError> {
+    let published_state_diff = decode_state_diff(validium_data)?;
+    if published_state_diff.encode()? != state_diff.encode()? {
+        return Err(StatelessExecutionError::InvalidStateDiff);
+    }
+    Ok(compute_data_hash(validium_data))
+}
 
-    if !verify_blob_kzg_proof(blob_data, commitment, proof)? {
-        return Err(StatelessExecutionError::InvalidBlobProof);
+#[cfg(all(test, feature = "l2"))]
+mod tests {
+    use super::*;
+    use ethrex_l2_common::{compression::encode_state_diff, state_diff::AccountStateDiff};
+
+    fn state_diff(balance: u64) -> StateDiff {
+        StateDiff {
+            modified_accounts: [(
+                Address::from_low_u64_be(1),
+                AccountStateDiff {
+                    new_balance: Some(U256::from(balance)),
+                    ..Default::default()
+                },
+            )]
+            .into(),
+            ..Default::default()
+        }
     }
 
-    Ok(kzg_commitment_to_versioned_hash(&commitment))
+    #[test]
+    fn validium_data_matching_the_execution_is_accepted() {
+        let data = encode_state_diff(&state_diff(1)).unwrap();
+
+        assert_eq!(
+            verify_validium_data(state_diff(1), &data).unwrap(),
+            compute_data_hash(&data)
+        );
+    }
+
+    #[test]
+    fn validium_data_not_matching_the_execution_is_rejected() {
+        let data = encode_state_diff(&state_diff(1)).unwrap();
+
+        assert!(matches!(
+            verify_validium_data(state_diff(2), &data),
+            Err(StatelessExecutionError::InvalidStateDiff)
+        ));
+    }
+
+    #[test]
+    fn malformed_validium_data_is_rejected() {
+        assert!(verify_validium_data(state_diff(1), &[0xff, 1, 2, 3]).is_err());
+    }
 }
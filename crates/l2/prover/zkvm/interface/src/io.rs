@@ -3,13 +3,12 @@ use ethrex_common::{
     types::{Block, block_execution_witness::ExecutionWitnessResult},
 };
 use serde::{Deserialize, Serialize};
-use serde_with::{DeserializeAs, SerializeAs, serde_as};
+use serde_with::{DeserializeAs, SerializeAs};
 
 #[cfg(feature = "l2")]
-use ethrex_common::types::blobs_bundle;
+use ethrex_common::types::BlobsBundle;
 
 /// Private input variables passed into the zkVM execution program.
-#[serde_as]
 #[derive(Serialize, Deserialize)]
 pub struct ProgramInput {
     /// blocks to execute
@@ -19,13 +18,13 @@ pub struct ProgramInput {
     /// value used to calculate base fee
     pub elasticity_multiplier: u64,
     #[cfg(feature = "l2")]
-    /// KZG commitment to the blob data
-    #[serde_as(as = "[_; 48]")]
-    pub blob_commitment: blobs_bundle::Commitment,
+    /// blobs holding the state diff of the batch, with their KZG commitments and proofs.
+    /// Empty in validium mode
+    pub blobs_bundle: BlobsBundle,
     #[cfg(feature = "l2")]
-    /// KZG opening for a challenge over the blob commitment
-    #[serde_as(as = "[_; 48]")]
-    pub blob_proof: blobs_bundle::Proof,
+    /// state diff of the batch as a validium published it through calldata or a data
+    /// availability committee. Empty in rollup mode or if the data isn't published
+    pub validium_data: Vec<u8>,
 }
 
 /// JSON serializable program input. This struct is forced to serialize into JSON format.
@@ -42,9 +41,9 @@ impl Default for ProgramInput {
             db: Default::default(),
             elasticity_multiplier: Default::default(),
             #[cfg(feature = "l2")]
-            blob_commitment: [0; 48],
+            blobs_bundle: BlobsBundle::default(),
             #[cfg(feature = "l2")]
-            blob_proof: [0; 48],
+            validium_data: Vec::new(),
         }
     }
 }
@@ -86,7 +85,8 @@ pub struct ProgramOutput {
     /// hash of all the privileged transactions made in a batch
     pub privileged_transactions_hash: H256,
     #[cfg(feature = "l2")]
-    /// blob commitment versioned hash
+    /// hash of the versioned hashes of the batch blobs. In validium mode, the hash of
+    /// the published data, or zero if it isn't published
     pub blob_versioned_hash: H256,
     /// hash of the last block in a batch
     pub last_block_hash: H256,
@@ -94,6 +94,9 @@ pub struct ProgramOutput {
     pub chain_id: U256,
     /// amount of non-privileged transactions
     pub non_privileged_count: U256,
+    #[cfg(feature = "l2")]
+    /// hash of the fee config of the chain, zero if it has none
+    pub fee_config_hash: H256,
 }
 
 impl ProgramOutput {
@@ -110,6 +113,8 @@ impl ProgramOutput {
             self.last_block_hash.to_fixed_bytes(),
             self.chain_id.to_big_endian(),
             self.non_privileged_count.to_big_endian(),
+            #[cfg(feature = "l2")]
+            self.fee_config_hash.to_fixed_bytes(),
         ]
         .concat()
     }
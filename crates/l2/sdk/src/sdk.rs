@@ -45,12 +45,22 @@ pub const L2_TO_L1_MESSENGER_ADDRESS: Address = H160([
     0x00, 0x00, 0xff, 0xfe,
 ]);
 
+// 0x000000000000000000000000000000000000fffd
+pub const L1_FEE_ORACLE_ADDRESS: Address = H160([
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0xff, 0xfd,
+]);
+
 // 0xee110000000000000000000000000000000011ff
 pub const ADDRESS_ALIASING: Address = H160([
     0xee, 0x11, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
     0x00, 0x00, 0x11, 0xff,
 ]);
 
+// 0xeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee
+/// Token address used by the bridges to represent the native token of the L2.
+pub const ETH_TOKEN_ADDRESS: Address = H160([0xee; 20]);
+
 pub const L2_WITHDRAW_SIGNATURE: &str = "withdraw(address)";
 
 /// Bytecode of the OpenZeppelin's ERC1967Proxy contract.
@@ -155,6 +165,8 @@ pub async fn deposit_through_transfer(
     .await
 }
 
+/// Withdraws native L2 balance, claimed on the L1 with [`claim_withdraw`] as ETH
+/// or as the native ERC20 of the bridge.
 pub async fn withdraw(
     amount: U256,
     from: Address,
@@ -279,6 +291,32 @@ pub async fn claim_erc20withdraw(
     send_eip1559_transaction(eth_client, &claim_tx, from_signer).await
 }
 
+/// Deposits the ERC20 set as native token of the bridge, minting native L2 balance.
+/// The bridge must be approved to transfer `amount` beforehand.
+pub async fn deposit_native_token(
+    amount: U256,
+    from: Address,
+    from_signer: &Signer,
+    eth_client: &EthClient,
+) -> Result<H256, EthClientError> {
+    let bridge = bridge_address().map_err(|err| EthClientError::Custom(err.to_string()))?;
+    let native_token = eth_client
+        .get_native_token(bridge)
+        .await?
+        .ok_or(EthClientError::Custom(
+            "The native token of the bridge is ETH, deposit it with a transfer".to_owned(),
+        ))?;
+    deposit_erc20(
+        native_token,
+        ETH_TOKEN_ADDRESS,
+        amount,
+        from,
+        from_signer,
+        eth_client,
+    )
+    .await
+}
+
 pub async fn deposit_erc20(
     token_l1: Address,
     token_l2: Address,
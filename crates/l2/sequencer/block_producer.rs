@@ -1,7 +1,7 @@
 mod payload_builder;
 use std::{
     sync::Arc,
-    time::{Duration, SystemTime, UNIX_EPOCH},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
 use ethrex_blockchain::{
@@ -16,7 +16,7 @@ use ethrex_storage::Store;
 use ethrex_storage_rollup::StoreRollup;
 use ethrex_vm::BlockExecutionResult;
 use keccak_hash::H256;
-use payload_builder::build_payload;
+use payload_builder::{build_payload, build_payload_with_preconfirmations};
 use spawned_concurrency::{
     messages::Unused,
     tasks::{CastResponse, GenServer, GenServerHandle, send_after},
@@ -52,6 +52,7 @@ pub struct BlockProducer {
     block_time_ms: u64,
     coinbase_address: Address,
     elasticity_multiplier: u64,
+    preconfirmation_interval_ms: Option<u64>,
     rollup_store: StoreRollup,
 }
 
@@ -67,6 +68,7 @@ impl BlockProducer {
             block_time_ms,
             coinbase_address,
             elasticity_multiplier,
+            preconfirmation_interval_ms,
         } = config;
         Self {
             store,
@@ -75,6 +77,7 @@ impl BlockProducer {
             block_time_ms: *block_time_ms,
             coinbase_address: *coinbase_address,
             elasticity_multiplier: *elasticity_multiplier,
+            preconfirmation_interval_ms: *preconfirmation_interval_ms,
             rollup_store,
         }
     }
@@ -132,13 +135,28 @@ impl BlockProducer {
         let payload = create_payload(&args, &self.store)?;
 
         // Blockchain builds the payload from mempool txs and executes them
-        let payload_build_result = build_payload(
-            self.blockchain.clone(),
-            payload,
-            &self.store,
-            &self.rollup_store,
-        )
-        .await?;
+        let payload_build_result = match self.preconfirmation_interval_ms {
+            Some(preconfirmation_interval_ms) => {
+                build_payload_with_preconfirmations(
+                    self.blockchain.clone(),
+                    payload,
+                    &self.store,
+                    &self.rollup_store,
+                    Duration::from_millis(self.block_time_ms),
+                    Duration::from_millis(preconfirmation_interval_ms),
+                )
+                .await?
+            }
+            None => {
+                build_payload(
+                    self.blockchain.clone(),
+                    payload,
+                    &self.store,
+                    &self.rollup_store,
+                )
+                .await?
+            }
+        };
         info!(
             "Built payload for new block {}",
             payload_build_result.payload.header.number
@@ -180,6 +198,12 @@ impl BlockProducer {
         // Make the new head be part of the canonical chain
         apply_fork_choice(&self.store, block.hash(), block.hash(), block.hash()).await?;
 
+        // The preconfirmations of this block are kept until the next one is sealed,
+        // so the subscribers that poll them less often than blocks are sealed don't miss them
+        self.blockchain
+            .preconfirmations
+            .remove_preconfirmations_before(block.header.number)?;
+
         metrics!(
             let _ = METRICS_BLOCKS
             .set_block_number(block.header.number)
@@ -207,17 +231,19 @@ impl GenServer for BlockProducer {
         handle: &GenServerHandle<Self>,
     ) -> CastResponse<Self> {
         // Right now we only have the Produce message, so we ignore the message
+        let mut next_block_in = Duration::from_millis(self.block_time_ms);
         if let SequencerStatus::Sequencing = self.sequencer_state.status().await {
+            let started_at = Instant::now();
             let _ = self
                 .produce_block()
                 .await
                 .inspect_err(|e| error!("Block Producer Error: {e}"));
+            // With preconfirmations the block time is spent building the block
+            if self.preconfirmation_interval_ms.is_some() {
+                next_block_in = next_block_in.saturating_sub(started_at.elapsed());
+            }
         }
-        send_after(
-            Duration::from_millis(self.block_time_ms),
-            handle.clone(),
-            Self::CastMsg::Produce,
-        );
+        send_after(next_block_in, handle.clone(), Self::CastMsg::Produce);
         CastResponse::NoReply(self)
     }
 }
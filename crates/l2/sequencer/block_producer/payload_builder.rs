@@ -9,7 +9,9 @@ use ethrex_blockchain::{
 };
 use ethrex_common::{
     Address,
-    types::{Block, Receipt, SAFE_BYTES_PER_BLOB, Transaction, TxType},
+    types::{
+        Block, Receipt, SAFE_BYTES_PER_BLOB, Transaction, TxType, preconfirmation::Preconfirmation,
+    },
 };
 use ethrex_l2_common::l1_messages::get_block_l1_messages;
 use ethrex_l2_common::state_diff::{
@@ -28,7 +30,8 @@ use ethrex_vm::{Evm, EvmError};
 use std::collections::{BTreeMap, HashMap};
 use std::ops::Div;
 use std::sync::Arc;
-use tokio::time::Instant;
+use std::time::Duration;
+use tokio::time::{Instant, sleep_until};
 use tracing::{debug, error};
 
 /// Max privileged tx to allow per batch
@@ -54,7 +57,15 @@ pub async fn build_payload(
         blockchain.r#type.clone(),
     )?;
 
-    fill_transactions(blockchain.clone(), &mut context, store, rollup_store).await?;
+    let mut state_diff = PayloadStateDiff::default();
+    fill_transactions(
+        blockchain.clone(),
+        &mut context,
+        &mut state_diff,
+        store,
+        rollup_store,
+    )
+    .await?;
     blockchain.finalize_payload(&mut context).await?;
 
     let interval = Instant::now().duration_since(since).as_millis();
@@ -93,18 +104,113 @@ pub async fn build_payload(
     Ok(context.into())
 }
 
+/// Builds the payload in slices: every `preconfirmation_interval` it adds the transactions
+/// of the mempool and announces them as a preconfirmation, until the block time is over.
+pub async fn build_payload_with_preconfirmations(
+    blockchain: Arc<Blockchain>,
+    payload: Block,
+    store: &Store,
+    rollup_store: &StoreRollup,
+    block_time: Duration,
+    preconfirmation_interval: Duration,
+) -> Result<PayloadBuildResult, BlockProducerError> {
+    let deadline = Instant::now() + block_time;
+    let block_number = payload.header.number;
+    let timestamp = payload.header.timestamp;
+    let base_fee_per_gas = payload.header.base_fee_per_gas.unwrap_or_default();
+
+    let mut context = PayloadBuildContext::new(
+        payload,
+        blockchain.evm_engine,
+        store,
+        blockchain.r#type.clone(),
+    )?;
+    let mut state_diff = PayloadStateDiff::default();
+    let mut index = 0;
+    loop {
+        let first_tx = context.payload.body.transactions.len();
+        fill_transactions(
+            blockchain.clone(),
+            &mut context,
+            &mut state_diff,
+            store,
+            rollup_store,
+        )
+        .await?;
+
+        let transactions = context
+            .payload
+            .body
+            .transactions
+            .get(first_tx..)
+            .unwrap_or_default();
+        if !transactions.is_empty() {
+            let preconfirmation = Preconfirmation {
+                block_number,
+                index,
+                timestamp,
+                base_fee_per_gas,
+                transactions: transactions.to_vec(),
+                receipts: context
+                    .receipts
+                    .get(first_tx..)
+                    .unwrap_or_default()
+                    .to_vec(),
+            };
+            debug!(
+                "Preconfirmed {} transactions of block {block_number}",
+                preconfirmation.transactions.len()
+            );
+            blockchain
+                .preconfirmations
+                .add_preconfirmation(preconfirmation, None)?;
+            index += 1;
+        }
+
+        let next_slice = Instant::now() + preconfirmation_interval;
+        if next_slice >= deadline {
+            break;
+        }
+        sleep_until(next_slice).await;
+    }
+    blockchain.finalize_payload(&mut context).await?;
+
+    Ok(context.into())
+}
+
+/// Size of the state diff of the transactions added to a payload, kept between the
+/// calls to `fill_transactions` that add transactions to the same payload.
+pub struct PayloadStateDiff {
+    acc_size_without_accounts: usize,
+    size_accounts_diffs: usize,
+    account_diffs: HashMap<Address, AccountStateDiff>,
+}
+
+impl Default for PayloadStateDiff {
+    fn default() -> Self {
+        Self {
+            // version (u8) + header fields (struct) + messages_len (u16) + privileged_tx_len (u16) + accounts_diffs_len (u16)
+            acc_size_without_accounts: 1 + *BLOCK_HEADER_LEN + 2 + 2 + 2,
+            size_accounts_diffs: 0,
+            account_diffs: HashMap::new(),
+        }
+    }
+}
+
 /// Same as `blockchain::fill_transactions` but enforces that the `StateDiff` size
 /// stays within the blob size limit after processing each transaction.
 pub async fn fill_transactions(
     blockchain: Arc<Blockchain>,
     context: &mut PayloadBuildContext,
+    state_diff: &mut PayloadStateDiff,
     store: &Store,
     rollup_store: &StoreRollup,
 ) -> Result<(), BlockProducerError> {
-    // version (u8) + header fields (struct) + messages_len (u16) + privileged_tx_len (u16) + accounts_diffs_len (u16)
-    let mut acc_size_without_accounts = 1 + *BLOCK_HEADER_LEN + 2 + 2 + 2;
-    let mut size_accounts_diffs = 0;
-    let mut account_diffs = HashMap::new();
+    let PayloadStateDiff {
+        acc_size_without_accounts,
+        size_accounts_diffs,
+        account_diffs,
+    } = state_diff;
 
     let chain_config = store.get_chain_config()?;
 
@@ -112,6 +218,9 @@ pub async fn fill_transactions(
     // Fetch mempool transactions
     let latest_block_number = store.get_latest_block_number().await?;
     let mut txs = fetch_mempool_transactions(blockchain.as_ref(), context)?;
+    if blockchain.parallel_execution {
+        context.speculate_transactions(&txs);
+    }
     // Inserting an excessive number may prevent the commitment from being sent
     let mut privileged_range = rollup_store.precommit_privileged().await?;
     // Execute and add transactions to payload (if suitable)
@@ -123,7 +232,7 @@ pub async fn fill_transactions(
         };
 
         // Check if we have enough space for the StateDiff to run more transactions
-        if acc_size_without_accounts + size_accounts_diffs + SIMPLE_TX_STATE_DIFF_SIZE
+        if *acc_size_without_accounts + *size_accounts_diffs + SIMPLE_TX_STATE_DIFF_SIZE
             > SAFE_BYTES_PER_BLOB
         {
             debug!("No more StateDiff space to run transactions");
@@ -205,7 +314,7 @@ pub async fn fill_transactions(
         };
 
         let account_diffs_in_tx = get_account_diffs_in_tx(context)?;
-        let merged_diffs = merge_diffs(&account_diffs, account_diffs_in_tx);
+        let merged_diffs = merge_diffs(account_diffs, account_diffs_in_tx);
 
         let (tx_size_without_accounts, new_accounts_diff_size) = calculate_tx_diff_size(
             &merged_diffs,
@@ -215,7 +324,7 @@ pub async fn fill_transactions(
             *L1MESSAGE_LOG_LEN,
         )?;
 
-        if acc_size_without_accounts + tx_size_without_accounts + new_accounts_diff_size
+        if *acc_size_without_accounts + tx_size_without_accounts + new_accounts_diff_size
             > SAFE_BYTES_PER_BLOB
         {
             debug!(
@@ -235,10 +344,10 @@ pub async fn fill_transactions(
         blockchain.remove_transaction_from_pool(&head_tx.tx.compute_hash())?;
 
         // We only add the messages and privileged transaction length because the accounts diffs may change
-        acc_size_without_accounts += tx_size_without_accounts;
-        size_accounts_diffs = new_accounts_diff_size;
+        *acc_size_without_accounts += tx_size_without_accounts;
+        *size_accounts_diffs = new_accounts_diff_size;
         // Include the new accounts diffs
-        account_diffs = merged_diffs;
+        *account_diffs = merged_diffs;
         // Add transaction to block
         debug!("Adding transaction: {} to payload", tx_hash);
         context.payload.body.transactions.push(head_tx.into());
@@ -1,6 +1,7 @@
 use aligned_sdk::common::types::Network;
 use ethrex_common::{Address, U256};
 use ethrex_l2_rpc::signer::Signer;
+use ethrex_rpc::types::block_identifier::BlockTag;
 use reqwest::Url;
 use secp256k1::SecretKey;
 use std::net::IpAddr;
@@ -13,8 +14,10 @@ pub struct SequencerConfig {
     pub l1_watcher: L1WatcherConfig,
     pub proof_coordinator: ProofCoordinatorConfig,
     pub based: BasedConfig,
+    pub ha: HAConfig,
     pub aligned: AlignedConfig,
     pub monitor: MonitorConfig,
+    pub l1_fee: L1FeeConfig,
 }
 
 // TODO: Move to blockchain/dev
@@ -23,6 +26,9 @@ pub struct BlockProducerConfig {
     pub block_time_ms: u64,
     pub coinbase_address: Address,
     pub elasticity_multiplier: u64,
+    /// How often the transactions of the block being built are announced as
+    /// preconfirmations, if they are
+    pub preconfirmation_interval_ms: Option<u64>,
 }
 
 #[derive(Clone, Debug)]
@@ -31,9 +37,25 @@ pub struct CommitterConfig {
     pub commit_time_ms: u64,
     pub arbitrary_base_blob_gas_price: u64,
     pub validium: bool,
+    /// Maximum number of blobs a batch can span
+    pub max_blobs_per_batch: usize,
+    /// Where the data of the batches is published, always blobs for a rollup
+    pub data_availability: DataAvailabilityConfig,
     pub signer: Signer,
 }
 
+#[derive(Clone, Debug)]
+pub enum DataAvailabilityConfig {
+    /// EIP-4844 blobs
+    Blobs,
+    /// Calldata of the commit transactions
+    Calldata,
+    /// Servers of a committee that store the data and sign attestations of it
+    Committee { members: Vec<Url>, threshold: usize },
+    /// Nowhere, the data only stays in the sequencer's database
+    None,
+}
+
 #[derive(Clone, Debug)]
 pub struct EthConfig {
     pub rpc_url: Vec<String>,
@@ -51,6 +73,7 @@ pub struct L1WatcherConfig {
     pub check_interval_ms: u64,
     pub max_block_step: U256,
     pub watcher_block_delay: u64,
+    pub block_tag: BlockTag,
 }
 
 #[derive(Clone, Debug)]
@@ -61,6 +84,17 @@ pub struct ProofCoordinatorConfig {
     pub signer: Signer,
     pub validium: bool,
     pub tdx_private_key: SecretKey,
+    pub prover_timeout_ms: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct L1FeeConfig {
+    /// Signer of the coinbase, the oracle updates are disabled without it
+    pub signer: Option<Signer>,
+    pub fee_vault: Option<Address>,
+    /// Percentage of the L1 blob base fee charged per compressed byte
+    pub fee_scalar: u64,
+    pub update_interval_ms: u64,
 }
 
 #[derive(Clone, Debug)]
@@ -82,6 +116,22 @@ pub struct BlockFetcherConfig {
     pub fetch_block_step: u64,
 }
 
+/// High availability mode: several sequencers share the committer key and elect the
+/// one that produces and commits blocks, the others follow it over P2P.
+#[derive(Clone, Debug)]
+pub struct HAConfig {
+    pub enabled: bool,
+    /// Identifier of this node, unique in the cluster
+    pub node_id: u64,
+    pub listen_ip: IpAddr,
+    pub listen_port: u16,
+    /// URLs of the leader elector of the other nodes of the cluster
+    pub peers: Vec<Url>,
+    pub heartbeat_interval_ms: u64,
+    /// Time without hearing from the leader after which a node runs for leader
+    pub election_timeout_ms: u64,
+}
+
 #[derive(Clone, Debug)]
 pub struct AlignedConfig {
     pub aligned_mode: bool,
@@ -1,10 +1,12 @@
 use crate::based::block_fetcher::BlockFetcherError;
 use crate::based::state_updater::StateUpdaterError;
+use crate::data_availability::DataAvailabilityError;
 use crate::utils::error::UtilsError;
 use ethereum_types::FromStrRadixErr;
-use ethrex_blockchain::error::{ChainError, InvalidForkChoice};
+use ethrex_blockchain::error::{ChainError, InvalidForkChoice, MempoolError};
 use ethrex_common::Address;
 use ethrex_common::types::{BlobsBundleError, FakeExponentialError};
+use ethrex_l2_common::compression::CompressionError;
 use ethrex_l2_common::privileged_transactions::PrivilegedTransactionError;
 use ethrex_l2_common::prover::ProverType;
 use ethrex_l2_common::state_diff::StateDiffError;
@@ -47,6 +49,10 @@ pub enum SequencerError {
     AlignedNetworkError(String),
     #[error("Failed to start EthrexMonitor: {0}")]
     MonitorError(#[from] MonitorError),
+    #[error("Failed to start L1FeeUpdater: {0}")]
+    L1FeeUpdaterError(#[from] L1FeeUpdaterError),
+    #[error("Failed to start LeaderElector: {0}")]
+    LeaderElectorError(#[from] LeaderElectorError),
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -115,6 +121,8 @@ pub enum ProofCoordinatorError {
     ProverDBError(#[from] ProverDBError),
     #[error("Missing blob for batch {0}")]
     MissingBlob(u64),
+    #[error("ProofCoordinator failed to decode the batch data: {0}")]
+    CompressionError(#[from] CompressionError),
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -223,6 +231,8 @@ pub enum CommitterError {
     FailedToGetInformationFromStorage(String),
     #[error("Committer failed to encode state diff: {0}")]
     FailedToEncodeStateDiff(#[from] StateDiffError),
+    #[error("Committer failed to compress batch data: {0}")]
+    FailedToCompressBatchData(#[from] CompressionError),
     #[error("Committer failed to open Points file: {0}")]
     FailedToOpenPointsFile(#[from] std::io::Error),
     #[error("Committer failed to re-execute block: {0}")]
@@ -247,6 +257,30 @@ pub enum CommitterError {
     FailedToSignError(#[from] SignerError),
     #[error("Privileged Transaction error: {0}")]
     PrivilegedTransactionError(#[from] PrivilegedTransactionError),
+    #[error("Failed to publish the batch data: {0}")]
+    DataAvailabilityError(#[from] DataAvailabilityError),
+    // TODO: Avoid propagating GenServerErrors outside GenServer modules
+    // See https://github.com/lambdaclass/ethrex/issues/3376
+    #[error("Spawned GenServer Error")]
+    GenServerError(GenServerError),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum L1FeeUpdaterError {
+    #[error("L1FeeUpdater failed because of an EthClient error: {0}")]
+    EthClientError(#[from] EthClientError),
+    #[error("L1FeeUpdater failed to access Store: {0}")]
+    StoreError(#[from] StoreError),
+    #[error("L1FeeUpdater failed to add the update to the mempool: {0}")]
+    MempoolError(#[from] MempoolError),
+    #[error("L1FeeUpdater failed to encode calldata: {0}")]
+    CalldataEncodeError(#[from] CalldataEncodeError),
+    #[error("L1FeeUpdater failed to sign the update: {0}")]
+    SignerError(#[from] SignerError),
+    #[error("L1FeeUpdater failed to compute the blob base fee: {0}")]
+    FakeExponentialError(#[from] FakeExponentialError),
+    #[error("L1FeeUpdater signer {0:#x} is not the coinbase, the oracle would reject its updates")]
+    SignerIsNotCoinbase(Address),
     // TODO: Avoid propagating GenServerErrors outside GenServer modules
     // See https://github.com/lambdaclass/ethrex/issues/3376
     #[error("Spawned GenServer Error")]
@@ -354,3 +388,33 @@ pub enum MonitorError {
     #[error("Error converting batch window")]
     BatchWindow,
 }
+
+#[derive(Debug, thiserror::Error)]
+pub enum LeaderElectorError {
+    #[error("LeaderElector failed because of an EthClient error: {0}")]
+    EthClientError(#[from] EthClientError),
+    #[error("LeaderElector failed to access Store: {0}")]
+    StoreError(#[from] StoreError),
+    #[error("LeaderElector failed to access RollupStore: {0}")]
+    RollupStoreError(#[from] RollupStoreError),
+    #[error("LeaderElector connection failed: {0}")]
+    ConnectionError(#[from] std::io::Error),
+    #[error("LeaderElector failed when (de)serializing JSON: {0}")]
+    JsonError(#[from] serde_json::Error),
+    #[error("Peer {0} didn't answer in time")]
+    Timeout(String),
+    #[error("Unexpected response from peer {0}")]
+    UnexpectedResponse(String),
+    #[error("LeaderElector failed to sign a message: {0}")]
+    SignerError(#[from] SignerError),
+    #[error("Election message not signed with the committer key or too old")]
+    UnauthorizedMessage,
+    #[error("Election message larger than {0} bytes")]
+    MessageTooLarge(u64),
+    #[error("LeaderElector failed to read the system time: {0}")]
+    SystemTimeError(#[from] std::time::SystemTimeError),
+    // TODO: Avoid propagating GenServerErrors outside GenServer modules
+    // See https://github.com/lambdaclass/ethrex/issues/3376
+    #[error("Spawned GenServer Error")]
+    GenServerError(GenServerError),
+}
@@ -1,6 +1,7 @@
 use crate::{
     CommitterConfig, EthConfig, SequencerConfig,
     based::sequencer_state::{SequencerState, SequencerStatus},
+    data_availability::{self, DataAvailability, Publication},
     sequencer::errors::CommitterError,
 };
 
@@ -9,12 +10,14 @@ use ethrex_blockchain::{Blockchain, vm::StoreVmDatabase};
 use ethrex_common::{
     Address, H256, U256,
     types::{
-        AccountUpdate, BLOB_BASE_FEE_UPDATE_FRACTION, BlobsBundle, Block, BlockNumber,
-        MIN_BASE_FEE_PER_BLOB_GAS, batch::Batch, blobs_bundle, fake_exponential_checked,
+        AccountUpdate, BLOB_BASE_FEE_UPDATE_FRACTION, BlobsBundle, BlobsBundleError, Block,
+        BlockNumber, MIN_BASE_FEE_PER_BLOB_GAS, batch::Batch, blobs_bundle,
+        fake_exponential_checked,
     },
 };
 use ethrex_l2_common::{
     calldata::Value,
+    compression::{data_from_blobs, encode_blocks, encode_state_diff},
     l1_messages::{get_block_l1_messages, get_l1_message_hash},
     merkle_tree::compute_merkle_root,
     privileged_transactions::{
@@ -22,13 +25,12 @@ use ethrex_l2_common::{
     },
     state_diff::{StateDiff, prepare_state_diff},
 };
-use ethrex_l2_rpc::clients::send_tx_bump_gas_exponential_backoff;
+use ethrex_l2_rpc::clients::send_tx_bump_gas_exponential_backoff_while;
 use ethrex_l2_rpc::signer::Signer;
 use ethrex_l2_sdk::calldata::encode_calldata;
 #[cfg(feature = "metrics")]
 use ethrex_metrics::l2::metrics::{METRICS, MetricsBlockType};
 use ethrex_metrics::metrics;
-use ethrex_rlp::encode::RLPEncode;
 use ethrex_rpc::{
     clients::eth::{EthClient, Overrides, WrappedTransaction},
     types::block_identifier::{BlockIdentifier, BlockTag},
@@ -38,15 +40,16 @@ use ethrex_storage_rollup::StoreRollup;
 use std::{collections::HashMap, sync::Arc};
 use tracing::{debug, error, info, warn};
 
-use super::{errors::BlobEstimationError, utils::random_duration};
+use super::{errors::BlobEstimationError, leader_elector::LeaderLease, utils::random_duration};
 use spawned_concurrency::{
     messages::Unused,
     tasks::{CastResponse, GenServer, GenServerHandle, send_after},
 };
 
 const COMMIT_FUNCTION_SIGNATURE_BASED: &str =
-    "commitBatch(uint256,bytes32,bytes32,bytes32,bytes32,bytes[])";
-const COMMIT_FUNCTION_SIGNATURE: &str = "commitBatch(uint256,bytes32,bytes32,bytes32,bytes32)";
+    "commitBatch(uint256,bytes32,bytes32,bytes32,bytes32,bytes32,bytes[])";
+pub(crate) const COMMIT_FUNCTION_SIGNATURE: &str =
+    "commitBatch(uint256,bytes32,bytes32,bytes32,bytes32,bytes32,bytes,bytes32,bytes[])";
 
 #[derive(Clone)]
 pub enum InMessage {
@@ -69,10 +72,13 @@ pub struct L1Committer {
     rollup_store: StoreRollup,
     commit_time_ms: u64,
     arbitrary_base_blob_gas_price: u64,
-    validium: bool,
+    /// Where the batch data is published, `None` if it's kept only in the rollup store
+    data_availability: Option<Arc<dyn DataAvailability>>,
     signer: Signer,
     based: bool,
     sequencer_state: SequencerState,
+    /// Leadership of the node in high availability mode, `None` otherwise
+    leader_lease: Option<LeaderLease>,
 }
 
 impl L1Committer {
@@ -85,6 +91,7 @@ impl L1Committer {
         rollup_store: StoreRollup,
         based: bool,
         sequencer_state: SequencerState,
+        leader_lease: Option<LeaderLease>,
     ) -> Result<Self, CommitterError> {
         Ok(Self {
             eth_client: EthClient::new_with_config(
@@ -102,10 +109,15 @@ impl L1Committer {
             rollup_store,
             commit_time_ms: committer_config.commit_time_ms,
             arbitrary_base_blob_gas_price: committer_config.arbitrary_base_blob_gas_price,
-            validium: committer_config.validium,
+            data_availability: data_availability::from_config(
+                &committer_config.data_availability,
+                committer_config.max_blobs_per_batch,
+                &committer_config.signer,
+            ),
             signer: committer_config.signer.clone(),
             based,
             sequencer_state,
+            leader_lease,
         })
     }
 
@@ -115,6 +127,7 @@ impl L1Committer {
         rollup_store: StoreRollup,
         cfg: SequencerConfig,
         sequencer_state: SequencerState,
+        leader_lease: Option<LeaderLease>,
     ) -> Result<(), CommitterError> {
         let state = Self::new(
             &cfg.l1_committer,
@@ -124,6 +137,7 @@ impl L1Committer {
             rollup_store.clone(),
             cfg.based.enabled,
             sequencer_state,
+            leader_lease,
         )?;
         let mut l1_committer = state.start();
         l1_committer
@@ -197,6 +211,16 @@ impl L1Committer {
             }
         };
 
+        // The sequencer may have lost its leadership while the batch was prepared, the
+        // new leader commits its own batches
+        if !self.may_commit().await {
+            warn!(
+                "Not sending commitment for batch {}, the node stopped sequencing",
+                batch.number
+            );
+            return Ok(());
+        }
+
         info!(
             first_block = batch.first_block,
             last_block = batch.last_block,
@@ -347,7 +371,7 @@ impl L1Committer {
                 .parent_hash;
             let parent_db = StoreVmDatabase::new(self.store.clone(), parent_block_hash);
 
-            let result = if !self.validium {
+            let result = if let Some(data_availability) = &self.data_availability {
                 // Prepare current state diff.
                 let state_diff = prepare_state_diff(
                     block_to_commit_header,
@@ -356,7 +380,7 @@ impl L1Committer {
                     &acc_privileged_txs,
                     acc_account_updates.clone().into_values().collect(),
                 )?;
-                generate_blobs_bundle(&state_diff)
+                generate_blobs_bundle(&state_diff, data_availability.max_blobs_per_batch())
             } else {
                 Ok((BlobsBundle::default(), 0_usize))
             };
@@ -403,8 +427,10 @@ impl L1Committer {
                         tracing::error!("Failed to update operations metric: {}", e.to_string())
                     });
             }
+            // Usage of the blobs the batch spans
             #[allow(clippy::as_conversions)]
-            let blob_usage_percentage = _blob_size as f64 * 100_f64 / ethrex_common::types::BYTES_PER_BLOB_F64;
+            let blob_usage_percentage = _blob_size as f64 * 100_f64
+                / (ethrex_common::types::BYTES_PER_BLOB_F64 * blobs_bundle.blobs.len().max(1) as f64);
             METRICS.set_blob_usage_percentage(blob_usage_percentage);
         );
 
@@ -425,6 +451,12 @@ impl L1Committer {
     async fn send_commitment(&mut self, batch: &Batch) -> Result<H256, CommitterError> {
         let messages_merkle_root = compute_merkle_root(&batch.message_hashes);
         let last_block_hash = get_last_block_hash(&self.store, batch.last_block)?;
+        let fee_config_hash = self
+            .store
+            .get_chain_config()?
+            .fee_config
+            .map(|fee_config| fee_config.hash())
+            .unwrap_or_default();
 
         let mut calldata_values = vec![
             Value::Uint(U256::from(batch.number)),
@@ -432,10 +464,20 @@ impl L1Committer {
             Value::FixedBytes(messages_merkle_root.0.to_vec().into()),
             Value::FixedBytes(batch.privileged_transactions_hash.0.to_vec().into()),
             Value::FixedBytes(last_block_hash.0.to_vec().into()),
+            Value::FixedBytes(fee_config_hash.0.to_vec().into()),
         ];
 
+        // The batch data is cached in the rollup store as blobs, whatever its backend
+        let publication = match &self.data_availability {
+            Some(data_availability) => {
+                let data = data_from_blobs(&batch.blobs_bundle.blobs)?;
+                Some(data_availability.publish(batch.number, data).await?)
+            }
+            None => None,
+        };
+
         let (commit_function_signature, values) = if self.based {
-            let mut encoded_blocks: Vec<Bytes> = Vec::new();
+            let mut blocks = Vec::new();
 
             for i in batch.first_block..=batch.last_block {
                 let block_header = self
@@ -451,17 +493,34 @@ impl L1Committer {
                     .map_err(CommitterError::from)?
                     .ok_or(CommitterError::FailedToRetrieveDataFromStorage)?;
 
-                let block = Block::new(block_header, block_body);
-
-                encoded_blocks.push(block.encode_to_vec().into());
+                blocks.push(Block::new(block_header, block_body));
             }
 
+            let encoded_blocks = encode_blocks(&blocks)?;
+
             calldata_values.push(Value::Array(
                 encoded_blocks.into_iter().map(Value::Bytes).collect(),
             ));
 
             (COMMIT_FUNCTION_SIGNATURE_BASED, calldata_values)
         } else {
+            let (state_diff_data, state_diff_hash, da_signatures) = match &publication {
+                Some(Publication::Calldata(data)) => (data.clone(), H256::zero(), Vec::new()),
+                Some(Publication::Attestations {
+                    data_hash,
+                    signatures,
+                }) => (Bytes::new(), *data_hash, signatures.clone()),
+                Some(Publication::Blobs(_)) | None => (Bytes::new(), H256::zero(), Vec::new()),
+            };
+            calldata_values.push(Value::Bytes(state_diff_data));
+            calldata_values.push(Value::FixedBytes(state_diff_hash.0.to_vec().into()));
+            calldata_values.push(Value::Array(
+                da_signatures
+                    .iter()
+                    .map(|signature| Value::Bytes(signature.as_bytes().to_vec().into()))
+                    .collect(),
+            ));
+
             (COMMIT_FUNCTION_SIGNATURE, calldata_values)
         };
 
@@ -478,7 +537,7 @@ impl L1Committer {
 
         // Validium: EIP1559 Transaction.
         // Rollup: EIP4844 Transaction -> For on-chain Data Availability.
-        let mut tx = if !self.validium {
+        let mut tx = if let Some(Publication::Blobs(blobs_bundle)) = publication {
             info!("L2 is in rollup mode, sending EIP-4844 (including blob) tx to commit block");
             let le_bytes = estimate_blob_gas(
                 &self.eth_client,
@@ -503,7 +562,7 @@ impl L1Committer {
                         max_priority_fee_per_gas: Some(gas_price),
                         ..Default::default()
                     },
-                    batch.blobs_bundle.clone(),
+                    blobs_bundle,
                 )
                 .await
                 .map_err(CommitterError::from)?;
@@ -534,13 +593,32 @@ impl L1Committer {
             .set_gas_for_wrapped_tx(&mut tx, self.signer.address())
             .await?;
 
-        let commit_tx_hash =
-            send_tx_bump_gas_exponential_backoff(&self.eth_client, &mut tx, &self.signer).await?;
+        // The leadership is checked again before each broadcast, so a node whose lease
+        // expired doesn't keep bumping a commitment the new leader may conflict with
+        let commit_tx_hash = send_tx_bump_gas_exponential_backoff_while(
+            &self.eth_client,
+            &mut tx,
+            &self.signer,
+            || self.may_commit(),
+        )
+        .await?;
 
         info!("Commitment sent: {commit_tx_hash:#x}");
 
         Ok(commit_tx_hash)
     }
+
+    /// Whether the node may send commitments: it's sequencing and, in high
+    /// availability mode, still holds the lease of its leadership.
+    async fn may_commit(&self) -> bool {
+        if self.sequencer_state.status().await != SequencerStatus::Sequencing {
+            return false;
+        }
+        match &self.leader_lease {
+            Some(leader_lease) => leader_lease.is_held().await,
+            None => true,
+        }
+    }
 }
 
 impl GenServer for L1Committer {
@@ -569,17 +647,24 @@ impl GenServer for L1Committer {
 }
 
 /// Generate the blob bundle necessary for the EIP-4844 transaction.
+/// Fails if the encoded state diff doesn't fit in `max_blobs` blobs.
 pub fn generate_blobs_bundle(
     state_diff: &StateDiff,
+    max_blobs: usize,
 ) -> Result<(BlobsBundle, usize), CommitterError> {
-    let blob_data = state_diff.encode().map_err(CommitterError::from)?;
+    let blob_data = encode_state_diff(state_diff).map_err(CommitterError::from)?;
 
     let blob_size = blob_data.len();
 
-    let blob = blobs_bundle::blob_from_bytes(blob_data).map_err(CommitterError::from)?;
+    let blobs = blobs_bundle::blobs_from_bytes(blob_data).map_err(CommitterError::from)?;
+    if blobs.len() > max_blobs {
+        return Err(CommitterError::FailedToGenerateBlobsBundle(
+            BlobsBundleError::MaxBlobsExceeded,
+        ));
+    }
 
     Ok((
-        BlobsBundle::create_from_blobs(&vec![blob]).map_err(CommitterError::from)?,
+        BlobsBundle::create_from_blobs(&blobs).map_err(CommitterError::from)?,
         blob_size,
     ))
 }
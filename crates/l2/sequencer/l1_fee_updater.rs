@@ -0,0 +1,230 @@
+use std::sync::Arc;
+
+use ethrex_blockchain::Blockchain;
+use ethrex_common::{
+    Address, H256, U256,
+    types::{
+        BLOB_BASE_FEE_UPDATE_FRACTION, EIP1559Transaction, MIN_BASE_FEE_PER_BLOB_GAS, Transaction,
+        TxKind, fake_exponential_checked,
+    },
+};
+use ethrex_l2_common::calldata::Value;
+use ethrex_l2_rpc::signer::{Signable, Signer};
+use ethrex_l2_sdk::calldata::encode_calldata;
+use ethrex_levm::hooks::l2_hook::{FEE_VAULT_SLOT, L1_FEE_ORACLE_ADDRESS, L1_FEE_PER_BYTE_SLOT};
+use ethrex_rpc::{
+    EthClient,
+    types::block_identifier::{BlockIdentifier, BlockTag},
+};
+use ethrex_storage::Store;
+use spawned_concurrency::{
+    messages::Unused,
+    tasks::{CastResponse, GenServer, GenServerHandle, send_after},
+};
+use tracing::{debug, error, info};
+
+use crate::{
+    SequencerConfig,
+    based::sequencer_state::{SequencerState, SequencerStatus},
+    sequencer::{errors::L1FeeUpdaterError, utils::random_duration},
+};
+
+const SET_L1_FEE_PER_BYTE_SIGNATURE: &str = "setL1FeePerByte(uint256)";
+const SET_FEE_VAULT_SIGNATURE: &str = "setFeeVault(address)";
+/// Gas limit of the oracle updates, they only write a couple of storage slots
+const UPDATE_GAS_LIMIT: u64 = 100_000;
+
+#[derive(Clone)]
+pub enum InMessage {
+    Update,
+}
+
+#[derive(Clone, PartialEq)]
+pub enum OutMessage {
+    Done,
+}
+
+/// Keeps the `L1FeeOracle` contract in sync with the L1 blob base fee, so the
+/// L2 transactions pay for the data the `L1Committer` posts.
+pub struct L1FeeUpdater {
+    eth_client: EthClient,
+    store: Store,
+    blockchain: Arc<Blockchain>,
+    signer: Signer,
+    fee_vault: Option<Address>,
+    fee_scalar: u64,
+    update_interval_ms: u64,
+    sequencer_state: SequencerState,
+}
+
+impl L1FeeUpdater {
+    pub async fn spawn(
+        store: Store,
+        blockchain: Arc<Blockchain>,
+        cfg: SequencerConfig,
+        sequencer_state: SequencerState,
+    ) -> Result<(), L1FeeUpdaterError> {
+        let Some(signer) = cfg.l1_fee.signer else {
+            info!("L1 fee updater disabled, no coinbase signer was set up");
+            return Ok(());
+        };
+        if cfg.l1_committer.validium {
+            info!("L1 fee updater disabled, validium mode doesn't post data to the L1");
+            return Ok(());
+        }
+        // The oracle only accepts updates from the coinbase of the block
+        if signer.address() != cfg.block_producer.coinbase_address {
+            return Err(L1FeeUpdaterError::SignerIsNotCoinbase(signer.address()));
+        }
+        let eth_client = EthClient::new_with_multiple_urls(cfg.eth.rpc_url.clone())?;
+        // The fee is priced in ETH wei, which doesn't convert to an ERC20 native token
+        if let Some(native_token) = eth_client
+            .get_native_token(cfg.l1_watcher.bridge_address)
+            .await?
+        {
+            info!(
+                "L1 fee updater disabled, the L1 fee is priced in ETH and the native token is {native_token:#x}"
+            );
+            return Ok(());
+        }
+
+        let state = Self {
+            eth_client,
+            store,
+            blockchain,
+            signer,
+            fee_vault: cfg.l1_fee.fee_vault,
+            fee_scalar: cfg.l1_fee.fee_scalar,
+            update_interval_ms: cfg.l1_fee.update_interval_ms,
+            sequencer_state,
+        };
+        let mut l1_fee_updater = L1FeeUpdater::start(state);
+        l1_fee_updater
+            .cast(InMessage::Update)
+            .await
+            .map_err(L1FeeUpdaterError::GenServerError)
+    }
+
+    async fn update_oracle(&mut self) -> Result<(), L1FeeUpdaterError> {
+        let sender = self.signer.address();
+        // Wait for the previous update to be included before sending another one
+        if self.blockchain.mempool.get_nonce(&sender)?.is_some() {
+            debug!("L1 fee update still pending, skipping");
+            return Ok(());
+        }
+
+        let latest_block_number = self.store.get_latest_block_number().await?;
+
+        let l1_fee_per_byte = self.estimate_l1_fee_per_byte().await?;
+        let current_l1_fee_per_byte = self
+            .store
+            .get_storage_at(
+                latest_block_number,
+                L1_FEE_ORACLE_ADDRESS,
+                L1_FEE_PER_BYTE_SLOT,
+            )
+            .await?
+            .unwrap_or_default();
+        if l1_fee_per_byte != current_l1_fee_per_byte {
+            let calldata = encode_calldata(
+                SET_L1_FEE_PER_BYTE_SIGNATURE,
+                &[Value::Uint(l1_fee_per_byte)],
+            )?;
+            self.send_update(calldata, latest_block_number).await?;
+            info!("Updated the L1 fee per byte to {l1_fee_per_byte}");
+        }
+
+        if let Some(fee_vault) = self.fee_vault {
+            let current_fee_vault = self
+                .store
+                .get_storage_at(latest_block_number, L1_FEE_ORACLE_ADDRESS, FEE_VAULT_SLOT)
+                .await?
+                .unwrap_or_default();
+            if current_fee_vault != U256::from_big_endian(H256::from(fee_vault).as_bytes()) {
+                let calldata =
+                    encode_calldata(SET_FEE_VAULT_SIGNATURE, &[Value::Address(fee_vault)])?;
+                self.send_update(calldata, latest_block_number).await?;
+                info!("Updated the L1 fee vault to {fee_vault:#x}");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Price of a blob byte in the latest L1 block, scaled by `fee_scalar` percent
+    /// to account for the commitment transaction and the blob padding.
+    async fn estimate_l1_fee_per_byte(&self) -> Result<U256, L1FeeUpdaterError> {
+        let latest_l1_block = self
+            .eth_client
+            .get_block_by_number(BlockIdentifier::Tag(BlockTag::Latest))
+            .await?;
+        let excess_blob_gas = latest_l1_block.header.excess_blob_gas.unwrap_or(0);
+        let blob_base_fee = fake_exponential_checked(
+            MIN_BASE_FEE_PER_BLOB_GAS,
+            excess_blob_gas,
+            BLOB_BASE_FEE_UPDATE_FRACTION,
+        )?;
+        Ok(U256::from(blob_base_fee) * self.fee_scalar / 100)
+    }
+
+    async fn send_update(
+        &self,
+        calldata: Vec<u8>,
+        latest_block_number: u64,
+    ) -> Result<(), L1FeeUpdaterError> {
+        let sender = self.signer.address();
+        let chain_id = self.store.get_chain_config()?.chain_id;
+        // The fee and the vault can be updated in the same round, the second one follows the mempool
+        let nonce = match self.blockchain.mempool.get_nonce(&sender)? {
+            Some(nonce) => nonce,
+            None => self
+                .store
+                .get_nonce_by_account_address(latest_block_number, sender)
+                .await?
+                .unwrap_or_default(),
+        };
+        let base_fee = self
+            .store
+            .get_block_header(latest_block_number)?
+            .and_then(|header| header.base_fee_per_gas)
+            .unwrap_or_default();
+
+        let mut tx = Transaction::EIP1559Transaction(EIP1559Transaction {
+            chain_id,
+            nonce,
+            max_priority_fee_per_gas: 0,
+            max_fee_per_gas: base_fee * 2,
+            gas_limit: UPDATE_GAS_LIMIT,
+            to: TxKind::Call(L1_FEE_ORACLE_ADDRESS),
+            data: calldata.into(),
+            ..Default::default()
+        });
+        tx.sign_inplace(&self.signer).await?;
+        self.blockchain.add_transaction_to_pool(tx).await?;
+        Ok(())
+    }
+}
+
+impl GenServer for L1FeeUpdater {
+    type CallMsg = Unused;
+    type CastMsg = InMessage;
+    type OutMsg = OutMessage;
+
+    type Error = L1FeeUpdaterError;
+
+    async fn handle_cast(
+        mut self,
+        _message: Self::CastMsg,
+        handle: &GenServerHandle<Self>,
+    ) -> CastResponse<Self> {
+        if let SequencerStatus::Sequencing = self.sequencer_state.status().await {
+            let _ = self
+                .update_oracle()
+                .await
+                .inspect_err(|err| error!("L1 Fee Updater: {err}"));
+        }
+        let check_interval = random_duration(self.update_interval_ms);
+        send_after(check_interval, handle.clone(), Self::CastMsg::Update);
+        CastResponse::NoReply(self)
+    }
+}
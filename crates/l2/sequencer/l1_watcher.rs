@@ -11,7 +11,10 @@ use ethrex_rpc::clients::EthClientError;
 use ethrex_rpc::types::receipt::RpcLog;
 use ethrex_rpc::{
     clients::eth::{EthClient, Overrides},
-    types::receipt::RpcLogInfo,
+    types::{
+        block_identifier::{BlockIdentifier, BlockTag},
+        receipt::RpcLogInfo,
+    },
 };
 use ethrex_storage::Store;
 use keccak_hash::keccak;
@@ -19,9 +22,13 @@ use spawned_concurrency::messages::Unused;
 use spawned_concurrency::tasks::{
     CastResponse, GenServer, GenServerHandle, InitResult, Success, send_after,
 };
-use std::{cmp::min, sync::Arc};
+use std::{cmp::min, collections::VecDeque, sync::Arc};
 use tracing::{debug, error, info, warn};
 
+/// Amount of fetched L1 blocks whose hash is kept to detect reorgs.
+/// Each one is the last block of a range of fetched logs.
+const MAX_TRACKED_L1_BLOCKS: usize = 64;
+
 #[derive(Clone)]
 pub enum InMessage {
     Watch,
@@ -45,6 +52,9 @@ pub struct L1Watcher {
     pub last_block_fetched: U256,
     pub check_interval: u64,
     pub l1_block_delay: u64,
+    pub block_tag: BlockTag,
+    /// Number and hash of the last blocks fetched, the last one is `last_block_fetched`
+    pub fetched_blocks: VecDeque<(U256, H256)>,
     pub sequencer_state: SequencerState,
 }
 
@@ -69,6 +79,8 @@ impl L1Watcher {
             last_block_fetched,
             check_interval: watcher_config.check_interval_ms,
             l1_block_delay: watcher_config.watcher_block_delay,
+            block_tag: watcher_config.block_tag.clone(),
+            fetched_blocks: VecDeque::new(),
             sequencer_state,
         })
     }
@@ -109,17 +121,23 @@ impl L1Watcher {
     }
 
     pub async fn get_privileged_transactions(&mut self) -> Result<Vec<RpcLog>, L1WatcherError> {
+        self.rewind_reorged_blocks().await?;
+
         if self.last_block_fetched.is_zero() {
-            self.last_block_fetched = self
+            let last_block_fetched = self
                 .eth_client
                 .get_last_fetched_l1_block(self.address)
                 .await?
                 .into();
+            let Some(hash) = self.get_l1_block_hash(last_block_fetched).await? else {
+                warn!("Last block fetched by the bridge is not in the L1 yet");
+                return Ok(vec![]);
+            };
+            self.track_fetched_block(last_block_fetched, hash);
         }
 
         let Some(latest_block_to_check) = self
-            .eth_client
-            .get_block_number()
+            .get_latest_block_number()
             .await?
             .checked_sub(self.l1_block_delay.into())
         else {
@@ -128,8 +146,9 @@ impl L1Watcher {
         };
 
         debug!(
-            "Latest possible block number with {} blocks of delay: {latest_block_to_check} ({latest_block_to_check:#x})",
+            "Latest possible block number with {} blocks of delay from {}: {latest_block_to_check} ({latest_block_to_check:#x})",
             self.l1_block_delay,
+            BlockIdentifier::Tag(self.block_tag.clone()),
         );
 
         // last_block_fetched could be greater than latest_block_to_check:
@@ -140,10 +159,19 @@ impl L1Watcher {
             return Ok(vec![]);
         }
 
+        if self.last_block_fetched == latest_block_to_check {
+            debug!("No new L1 blocks to fetch");
+            return Ok(vec![]);
+        }
+
         let new_last_block = min(
             self.last_block_fetched + self.max_block_step,
             latest_block_to_check,
         );
+        let Some(new_last_block_hash) = self.get_l1_block_hash(new_last_block).await? else {
+            warn!("Block {new_last_block} is not in the L1 anymore, retrying");
+            return Ok(vec![]);
+        };
 
         debug!(
             "Looking logs from block {:#x} to {:#x}",
@@ -154,6 +182,9 @@ impl L1Watcher {
         let topic =
             keccak(b"PrivilegedTxSent(address,address,address,uint256,uint256,uint256,bytes)");
 
+        // On error the range is requested again in the next watch, so no privileged transaction is skipped.
+        // We may get an error if the RPC doesn't has the logs for the requested
+        // block interval. For example, Light Nodes.
         let logs = self
             .eth_client
             .get_logs(
@@ -163,22 +194,92 @@ impl L1Watcher {
                 vec![topic],
             )
             .await
-            .inspect_err(|error| {
-                // We may get an error if the RPC doesn't has the logs for the requested
-                // block interval. For example, Light Nodes.
-                warn!("Error when getting logs from L1: {}", error);
-            })
-            .unwrap_or_default();
+            .inspect_err(|error| warn!("Error when getting logs from L1: {}", error))?;
 
         debug!("Logs: {:#?}", logs);
 
+        // The logs are only valid if both ends of the range are still part of the L1 chain,
+        // otherwise the L1 reorged while fetching them and they may belong to an abandoned fork.
+        let previous_block = self.fetched_blocks.back().copied();
+        for (number, hash) in previous_block
+            .into_iter()
+            .chain([(new_last_block, new_last_block_hash)])
+        {
+            if self.get_l1_block_hash(number).await? != Some(hash) {
+                warn!("L1 reorg while fetching logs up to block {new_last_block}, retrying");
+                return Ok(vec![]);
+            }
+        }
+
         // If we have an error adding the tx to the mempool we may assign it to the next
         // block to fetch, but we may lose a privileged tx.
-        self.last_block_fetched = new_last_block;
+        self.track_fetched_block(new_last_block, new_last_block_hash);
 
         Ok(logs)
     }
 
+    /// Forgets the fetched blocks that are not part of the L1 chain anymore, so that the logs
+    /// after the last block still in the chain are fetched again.
+    /// Privileged transactions that were already processed are skipped when fetched again.
+    async fn rewind_reorged_blocks(&mut self) -> Result<(), L1WatcherError> {
+        if self.fetched_blocks.is_empty() {
+            return Ok(());
+        }
+        while let Some((number, hash)) = self.fetched_blocks.back().copied() {
+            if self.get_l1_block_hash(number).await? == Some(hash) {
+                if number != self.last_block_fetched {
+                    warn!(
+                        "L1 reorg detected, rewinding from block {} to block {number}",
+                        self.last_block_fetched
+                    );
+                    self.last_block_fetched = number;
+                }
+                return Ok(());
+            }
+            self.fetched_blocks.pop_back();
+        }
+        warn!(
+            "L1 reorg deeper than the last {MAX_TRACKED_L1_BLOCKS} fetched ranges, restarting from the last block fetched by the bridge"
+        );
+        self.last_block_fetched = U256::zero();
+        Ok(())
+    }
+
+    fn track_fetched_block(&mut self, number: U256, hash: H256) {
+        self.last_block_fetched = number;
+        self.fetched_blocks.push_back((number, hash));
+        if self.fetched_blocks.len() > MAX_TRACKED_L1_BLOCKS {
+            self.fetched_blocks.pop_front();
+        }
+    }
+
+    /// Number of the L1 block with the configured tag
+    async fn get_latest_block_number(&self) -> Result<U256, L1WatcherError> {
+        if self.block_tag == BlockTag::Latest {
+            return Ok(self.eth_client.get_block_number().await?);
+        }
+        let block = self
+            .eth_client
+            .get_block_by_number(BlockIdentifier::Tag(self.block_tag.clone()))
+            .await?;
+        Ok(block.header.number.into())
+    }
+
+    /// Hash of the L1 block with the given number, `None` if the L1 doesn't have it
+    async fn get_l1_block_hash(&self, number: U256) -> Result<Option<H256>, L1WatcherError> {
+        if number > self.eth_client.get_block_number().await? {
+            return Ok(None);
+        }
+        let number = number
+            .try_into()
+            .map_err(|_| L1WatcherError::Custom(format!("Invalid L1 block number {number}")))?;
+        let block = self
+            .eth_client
+            .get_block_by_number(BlockIdentifier::Number(number))
+            .await?;
+        Ok(Some(block.hash))
+    }
+
     pub async fn process_privileged_transactions(
         &mut self,
         logs: Vec<RpcLog>,
@@ -302,6 +403,8 @@ impl GenServer for L1Watcher {
 }
 
 pub struct PrivilegedTransactionData {
+    /// Amount of the native token, ETH or the native ERC20 of the bridge,
+    /// minted when sent by the bridge
     pub value: U256,
     pub to_address: H160,
     pub transaction_id: U256,
@@ -418,3 +521,282 @@ impl PrivilegedTransactionData {
             .await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::based::sequencer_state::SequencerStatus;
+    use axum::{Json, Router, extract::State, routing::post};
+    use ethrex_common::types::{BlockBody, BlockHeader};
+    use ethrex_rpc::types::block::RpcBlock;
+    use ethrex_storage::EngineType;
+    use serde_json::{Value, json};
+    use tokio::{net::TcpListener, sync::Mutex};
+
+    /// L1 stand-in serving the JSON-RPC methods used by the watcher, its chain can be reorged on demand.
+    struct FakeL1 {
+        /// Canonical chain by block number, with whether the block has a privileged transaction
+        blocks: Vec<(BlockHeader, bool)>,
+        /// Amount of blocks the safe and finalized blocks are behind the head
+        safe_distance: u64,
+        /// Amount of eth_getLogs requests to fail before answering them again
+        failing_get_logs: usize,
+        bridge_deployment_block: u64,
+        forks: u64,
+    }
+
+    impl FakeL1 {
+        fn new(length: u64, privileged_tx_blocks: &[u64]) -> Self {
+            let mut l1 = Self {
+                blocks: Vec::new(),
+                safe_distance: 0,
+                failing_get_logs: 0,
+                bridge_deployment_block: 1,
+                forks: 0,
+            };
+            for number in 0..length {
+                l1.push_block(privileged_tx_blocks.contains(&number));
+            }
+            l1
+        }
+
+        fn push_block(&mut self, has_privileged_tx: bool) {
+            let header = BlockHeader {
+                number: self.blocks.len().try_into().unwrap_or_default(),
+                parent_hash: self
+                    .blocks
+                    .last()
+                    .map(|(parent, _)| parent.hash())
+                    .unwrap_or_default(),
+                // Makes the blocks of each fork differ
+                extra_data: self.forks.to_be_bytes().to_vec().into(),
+                ..Default::default()
+            };
+            self.blocks.push((header, has_privileged_tx));
+        }
+
+        /// Replaces the last `depth` blocks with the given ones
+        fn reorg(&mut self, depth: usize, has_privileged_tx: &[bool]) {
+            self.blocks
+                .truncate(self.blocks.len().saturating_sub(depth));
+            self.forks += 1;
+            for has_privileged_tx in has_privileged_tx {
+                self.push_block(*has_privileged_tx);
+            }
+        }
+
+        fn head(&self) -> u64 {
+            self.blocks
+                .len()
+                .saturating_sub(1)
+                .try_into()
+                .unwrap_or_default()
+        }
+
+        fn block(&self, number: u64) -> Option<&(BlockHeader, bool)> {
+            self.blocks.get(usize::try_from(number).ok()?)
+        }
+
+        fn block_hash(&self, number: u64) -> Option<H256> {
+            self.block(number).map(|(header, _)| header.hash())
+        }
+
+        fn answer(&mut self, method: &str, params: &[Value]) -> Result<Value, String> {
+            let parse_number =
+                |value: &str| u64::from_str_radix(value.trim_start_matches("0x"), 16).ok();
+            match method {
+                "eth_blockNumber" => Ok(json!(format!("{:#x}", self.head()))),
+                "eth_gasPrice" => Ok(json!("0x1")),
+                // The only call is to lastFetchedL1Block()
+                "eth_call" => Ok(json!(format!("{:#066x}", self.bridge_deployment_block))),
+                "eth_getBlockByNumber" => {
+                    let number = match params.first().and_then(Value::as_str) {
+                        Some("latest") => Some(self.head()),
+                        Some("safe" | "finalized") => self.head().checked_sub(self.safe_distance),
+                        Some(number) => parse_number(number),
+                        None => None,
+                    };
+                    let Some((header, _)) = number.and_then(|number| self.block(number)) else {
+                        return Ok(Value::Null);
+                    };
+                    let block =
+                        RpcBlock::build(header.clone(), BlockBody::default(), header.hash(), false)
+                            .map_err(|err| err.to_string())?;
+                    serde_json::to_value(block).map_err(|err| err.to_string())
+                }
+                "eth_getLogs" => {
+                    if self.failing_get_logs > 0 {
+                        self.failing_get_logs -= 1;
+                        return Err("Logs not available".to_string());
+                    }
+                    let bound = |key: &str| {
+                        params
+                            .first()
+                            .and_then(|filter| filter.get(key))
+                            .and_then(Value::as_str)
+                            .and_then(parse_number)
+                            .unwrap_or_default()
+                    };
+                    let logs: Vec<RpcLog> = (bound("fromBlock")..=bound("toBlock"))
+                        .filter_map(|number| self.block(number))
+                        .filter(|(_, has_privileged_tx)| *has_privileged_tx)
+                        .map(|(header, _)| RpcLog {
+                            log: RpcLogInfo {
+                                address: Address::zero(),
+                                topics: vec![],
+                                data: Bytes::new(),
+                            },
+                            log_index: 0,
+                            removed: false,
+                            transaction_hash: H256::zero(),
+                            transaction_index: 0,
+                            block_hash: header.hash(),
+                            block_number: header.number,
+                        })
+                        .collect();
+                    serde_json::to_value(logs).map_err(|err| err.to_string())
+                }
+                other => Err(format!("Method {other} not supported")),
+            }
+        }
+    }
+
+    async fn handle_rpc_request(
+        State(l1): State<Arc<Mutex<FakeL1>>>,
+        Json(request): Json<Value>,
+    ) -> Json<Value> {
+        let method = request
+            .get("method")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let params = request
+            .get("params")
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default();
+        let id = request.get("id").cloned().unwrap_or_default();
+        let response = match l1.lock().await.answer(method, &params) {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(message) => {
+                json!({ "jsonrpc": "2.0", "id": id, "error": { "code": -32000, "message": message } })
+            }
+        };
+        Json(response)
+    }
+
+    /// Serves the fake L1 and returns a watcher connected to it
+    async fn start_watcher(
+        l1: &Arc<Mutex<FakeL1>>,
+        block_tag: BlockTag,
+    ) -> anyhow::Result<L1Watcher> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}", listener.local_addr()?);
+        let router = Router::new()
+            .route("/", post(handle_rpc_request))
+            .with_state(l1.clone());
+        tokio::spawn(async move { axum::serve(listener, router).await });
+
+        let store = Store::new("", EngineType::InMemory)?;
+        Ok(L1Watcher {
+            blockchain: Arc::new(Blockchain::default_with_store(store.clone())),
+            store,
+            eth_client: EthClient::new(&url)?,
+            l2_client: EthClient::new(&url)?,
+            address: Address::zero(),
+            max_block_step: U256::from(100),
+            last_block_fetched: U256::zero(),
+            check_interval: 1000,
+            l1_block_delay: 0,
+            block_tag,
+            fetched_blocks: VecDeque::new(),
+            sequencer_state: SequencerStatus::Sequencing.into(),
+        })
+    }
+
+    fn log_blocks(logs: &[RpcLog]) -> Vec<(u64, H256)> {
+        logs.iter()
+            .map(|log| (log.block_number, log.block_hash))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn failed_log_fetches_are_retried() -> anyhow::Result<()> {
+        let mut fake_l1 = FakeL1::new(21, &[5]);
+        fake_l1.failing_get_logs = 1;
+        let l1 = Arc::new(Mutex::new(fake_l1));
+        let mut watcher = start_watcher(&l1, BlockTag::Latest).await?;
+
+        assert!(watcher.get_privileged_transactions().await.is_err());
+        assert_eq!(watcher.last_block_fetched, U256::from(1));
+
+        let logs = watcher.get_privileged_transactions().await?;
+        let block_5 = l1.lock().await.block_hash(5).unwrap_or_default();
+        assert_eq!(log_blocks(&logs), vec![(5, block_5)]);
+        assert_eq!(watcher.last_block_fetched, U256::from(20));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn reorged_blocks_are_fetched_again() -> anyhow::Result<()> {
+        let l1 = Arc::new(Mutex::new(FakeL1::new(11, &[8])));
+        let mut watcher = start_watcher(&l1, BlockTag::Latest).await?;
+
+        let logs = watcher.get_privileged_transactions().await?;
+        let old_block_8 = l1.lock().await.block_hash(8).unwrap_or_default();
+        assert_eq!(log_blocks(&logs), vec![(8, old_block_8)]);
+
+        // Blocks 7 to 10 are replaced, the privileged transaction is now in block 9
+        l1.lock()
+            .await
+            .reorg(4, &[false, false, true, false, false, false]);
+        let logs = watcher.get_privileged_transactions().await?;
+        let new_block_9 = l1.lock().await.block_hash(9).unwrap_or_default();
+        assert_eq!(log_blocks(&logs), vec![(9, new_block_9)]);
+        assert_eq!(watcher.last_block_fetched, U256::from(12));
+
+        // Without reorgs only the new blocks are fetched
+        l1.lock().await.push_block(false);
+        assert!(watcher.get_privileged_transactions().await?.is_empty());
+        assert_eq!(watcher.last_block_fetched, U256::from(13));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn reorg_rewinds_to_the_last_block_still_in_the_chain() -> anyhow::Result<()> {
+        let l1 = Arc::new(Mutex::new(FakeL1::new(11, &[])));
+        let mut watcher = start_watcher(&l1, BlockTag::Latest).await?;
+        watcher.get_privileged_transactions().await?;
+        l1.lock().await.push_block(false);
+        l1.lock().await.push_block(false);
+        watcher.get_privileged_transactions().await?;
+        assert_eq!(watcher.last_block_fetched, U256::from(12));
+
+        // Block 10 is still in the chain, so only the blocks after it are fetched again
+        l1.lock().await.reorg(1, &[true]);
+        watcher.rewind_reorged_blocks().await?;
+        assert_eq!(watcher.last_block_fetched, U256::from(10));
+        let logs = watcher.get_privileged_transactions().await?;
+        let new_block_12 = l1.lock().await.block_hash(12).unwrap_or_default();
+        assert_eq!(log_blocks(&logs), vec![(12, new_block_12)]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn safe_tag_ignores_unsafe_blocks() -> anyhow::Result<()> {
+        let mut fake_l1 = FakeL1::new(21, &[10, 18]);
+        fake_l1.safe_distance = 5;
+        let l1 = Arc::new(Mutex::new(fake_l1));
+        let mut watcher = start_watcher(&l1, BlockTag::Safe).await?;
+
+        let logs = watcher.get_privileged_transactions().await?;
+        let block_10 = l1.lock().await.block_hash(10).unwrap_or_default();
+        assert_eq!(log_blocks(&logs), vec![(10, block_10)]);
+        assert_eq!(watcher.last_block_fetched, U256::from(15));
+
+        // A reorg of the unsafe blocks doesn't affect the fetched ones
+        l1.lock().await.reorg(3, &[false, false, false]);
+        assert!(watcher.get_privileged_transactions().await?.is_empty());
+        assert_eq!(watcher.last_block_fetched, U256::from(15));
+        Ok(())
+    }
+}
@@ -0,0 +1,993 @@
+use std::{
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use bytes::Bytes;
+use ethrex_blockchain::Blockchain;
+use ethrex_common::{Address, Signature, types::recover_address};
+use ethrex_l2_rpc::signer::Signer;
+use ethrex_rpc::{
+    EthClient,
+    types::block_identifier::{BlockIdentifier, BlockTag},
+};
+use ethrex_storage::Store;
+use ethrex_storage_rollup::StoreRollup;
+use keccak_hash::keccak;
+use rand::Rng;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use spawned_concurrency::{
+    messages::Unused,
+    tasks::{CastResponse, GenServer, GenServerHandle, send_after},
+};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::Mutex,
+    task::JoinSet,
+    time::{Instant, timeout},
+};
+use tracing::{debug, error, info, warn};
+
+use crate::{
+    SequencerConfig,
+    based::sequencer_state::{SequencerState, SequencerStatus},
+    sequencer::{
+        errors::LeaderElectorError,
+        utils::{node_is_up_to_date, revert_uncommitted_state},
+    },
+};
+
+/// Messages between the leader electors of the nodes of a cluster.
+#[derive(Debug, Serialize, Deserialize)]
+pub enum ElectionMessage {
+    /// Request of a candidate to be elected leader of a term
+    RequestVote {
+        term: u64,
+        candidate_id: u64,
+        last_block: u64,
+    },
+    Vote {
+        term: u64,
+        candidate_id: u64,
+        granted: bool,
+    },
+    /// Sent by the leader to renew its lease
+    Heartbeat { term: u64, leader_id: u64 },
+    HeartbeatAck {
+        term: u64,
+        leader_id: u64,
+        success: bool,
+    },
+}
+
+/// Maximum size of a message between leader electors, which are a few hundred bytes.
+const MAX_MESSAGE_SIZE: u64 = 4096;
+
+/// Election message signed with the committer key shared by the nodes of the
+/// cluster, so no one else can take part in the elections.
+#[derive(Debug, Serialize, Deserialize)]
+struct SignedElectionMessage {
+    message: ElectionMessage,
+    /// Unix time in milliseconds the message was signed at, so it can't be replayed later
+    timestamp_ms: u64,
+    signature: Signature,
+}
+
+impl SignedElectionMessage {
+    async fn sign(message: ElectionMessage, signer: &Signer) -> Result<Self, LeaderElectorError> {
+        let timestamp_ms = unix_time_ms()?;
+        let signature = signer.sign(signed_payload(&message, timestamp_ms)?).await?;
+        Ok(Self {
+            message,
+            timestamp_ms,
+            signature,
+        })
+    }
+
+    /// Checks the message was signed with the committer key at most `max_age` ago.
+    fn verify(
+        self,
+        committer: Address,
+        max_age: Duration,
+    ) -> Result<ElectionMessage, LeaderElectorError> {
+        let signer = recover_address(
+            self.signature,
+            keccak(signed_payload(&self.message, self.timestamp_ms)?),
+        )
+        .map_err(|_| LeaderElectorError::UnauthorizedMessage)?;
+        let age = Duration::from_millis(unix_time_ms()?.abs_diff(self.timestamp_ms));
+        if signer != committer || age > max_age {
+            return Err(LeaderElectorError::UnauthorizedMessage);
+        }
+        Ok(self.message)
+    }
+}
+
+fn signed_payload(
+    message: &ElectionMessage,
+    timestamp_ms: u64,
+) -> Result<Bytes, LeaderElectorError> {
+    let mut payload = serde_json::to_vec(message)?;
+    payload.extend_from_slice(&timestamp_ms.to_be_bytes());
+    Ok(payload.into())
+}
+
+fn unix_time_ms() -> Result<u64, LeaderElectorError> {
+    let elapsed = SystemTime::now().duration_since(UNIX_EPOCH)?;
+    Ok(u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Role {
+    Follower,
+    Candidate,
+    Leader,
+}
+
+/// Election state of a node, shared between the elector and the server answering
+/// the other nodes.
+#[derive(Debug)]
+struct Election {
+    term: u64,
+    voted_for: Option<u64>,
+    role: Role,
+    /// Last time this node heard from the leader or granted a vote
+    last_heartbeat: Instant,
+    /// When this node runs for leader if it doesn't hear from one before
+    election_deadline: Instant,
+    /// Until when the leader can act as such without a majority renewing its lease
+    lease_until: Instant,
+}
+
+impl Election {
+    fn new(election_timeout: Duration) -> Self {
+        let now = Instant::now();
+        let mut election = Self {
+            term: 0,
+            voted_for: None,
+            role: Role::Follower,
+            // A restarted node doesn't remember its votes, so it waits a whole
+            // election timeout before granting any
+            last_heartbeat: now,
+            election_deadline: now,
+            lease_until: now,
+        };
+        election.reset_election_deadline(election_timeout);
+        election
+    }
+
+    /// Randomized so the nodes don't run for leader at the same time
+    fn reset_election_deadline(&mut self, election_timeout: Duration) {
+        let jitter = rand::thread_rng().gen_range(Duration::ZERO..=election_timeout);
+        self.election_deadline = Instant::now() + election_timeout + jitter;
+    }
+
+    /// Whether the node heard from a leader recently, whose lease may still be valid.
+    fn leader_alive(&self, election_timeout: Duration) -> bool {
+        self.last_heartbeat.elapsed() < election_timeout
+    }
+
+    fn step_down(&mut self, term: u64, election_timeout: Duration) {
+        if term > self.term {
+            self.term = term;
+            self.voted_for = None;
+        }
+        self.role = Role::Follower;
+        self.reset_election_deadline(election_timeout);
+    }
+
+    /// Answers the vote request of a candidate. Votes are only granted once the lease
+    /// of the current leader expired, and to candidates with all of our blocks.
+    fn request_vote(
+        &mut self,
+        term: u64,
+        candidate_id: u64,
+        candidate_last_block: u64,
+        last_block: u64,
+        election_timeout: Duration,
+    ) -> bool {
+        let granted = term >= self.term
+            && self.role != Role::Leader
+            && !self.leader_alive(election_timeout)
+            && candidate_last_block >= last_block
+            && (term > self.term || self.voted_for.is_none_or(|vote| vote == candidate_id));
+        if granted {
+            if term > self.term {
+                self.step_down(term, election_timeout);
+            }
+            self.voted_for = Some(candidate_id);
+            self.last_heartbeat = Instant::now();
+            self.reset_election_deadline(election_timeout);
+            info!("Voted node {candidate_id} as leader of term {term}");
+        }
+        granted
+    }
+
+    /// Answers the heartbeat of a leader, following it unless it's from a past term.
+    fn heartbeat(&mut self, term: u64, leader_id: u64, election_timeout: Duration) -> bool {
+        let success = term >= self.term;
+        if success {
+            if term > self.term || self.role != Role::Follower {
+                info!("Following node {leader_id}, leader of term {term}");
+                self.step_down(term, election_timeout);
+            }
+            self.voted_for = Some(leader_id);
+            self.last_heartbeat = Instant::now();
+            self.reset_election_deadline(election_timeout);
+        }
+        success
+    }
+
+    fn holds_lease(&self) -> bool {
+        self.role == Role::Leader && Instant::now() < self.lease_until
+    }
+
+    /// Steps down if the lease expired without hearing from a majority, returning
+    /// whether the node still leads.
+    fn check_lease(&mut self, election_timeout: Duration) -> bool {
+        if self.role == Role::Leader && !self.holds_lease() {
+            warn!(
+                "Lease of term {} expired without hearing from a majority",
+                self.term
+            );
+            let term = self.term;
+            self.step_down(term, election_timeout);
+        }
+        self.role == Role::Leader
+    }
+}
+
+/// Whether the votes are a majority of a cluster of `cluster_size` nodes.
+fn is_majority(votes: usize, cluster_size: usize) -> bool {
+    votes > cluster_size / 2
+}
+
+/// Leadership of this node, checked by the committer before sending each transaction
+/// to the L1 so it stops as soon as the lease expires.
+#[derive(Debug, Clone)]
+pub struct LeaderLease(Arc<Mutex<Election>>);
+
+impl LeaderLease {
+    /// Whether the node leads the cluster and its lease didn't expire.
+    pub async fn is_held(&self) -> bool {
+        self.0.lock().await.holds_lease()
+    }
+}
+
+#[derive(Clone)]
+pub enum InMessage {
+    Tick,
+}
+
+#[derive(Clone, PartialEq)]
+pub enum OutMessage {
+    Done,
+}
+
+/// Elects which node of a high availability cluster runs the sequencer, the others
+/// follow its blocks over P2P as hot standbys.
+///
+/// The election follows Raft without a log: the nodes vote a leader for each term,
+/// which keeps its lease while a majority acknowledges its heartbeats. A node only
+/// votes when it hasn't heard from the leader for a whole election timeout, and the
+/// lease lasts half of it, so a new leader is only elected once the lease of the
+/// previous one expired. A leader stops sequencing as soon as its lease expires and
+/// reverts the blocks and batches it didn't commit, and the committer checks the
+/// lease before each transaction it sends, so two leaders never commit conflicting
+/// batches. The messages are signed with the committer key shared by the cluster.
+pub struct LeaderElector {
+    node_id: u64,
+    peers: Vec<Url>,
+    heartbeat_interval: Duration,
+    election_timeout: Duration,
+    election: Arc<Mutex<Election>>,
+    signer: Signer,
+    eth_client: EthClient,
+    on_chain_proposer_address: Address,
+    store: Store,
+    rollup_store: StoreRollup,
+    sequencer_state: SequencerState,
+}
+
+impl LeaderElector {
+    pub async fn spawn(
+        cfg: SequencerConfig,
+        sequencer_state: SequencerState,
+        blockchain: Arc<Blockchain>,
+        store: Store,
+        rollup_store: StoreRollup,
+    ) -> Result<LeaderLease, LeaderElectorError> {
+        let election_timeout = Duration::from_millis(cfg.ha.election_timeout_ms);
+        let election = Arc::new(Mutex::new(Election::new(election_timeout)));
+        let signer = cfg.l1_committer.signer.clone();
+
+        let listener =
+            TcpListener::bind(format!("{}:{}", cfg.ha.listen_ip, cfg.ha.listen_port)).await?;
+        info!(
+            "Leader elector of node {} listening on {}:{}",
+            cfg.ha.node_id, cfg.ha.listen_ip, cfg.ha.listen_port
+        );
+        tokio::spawn(serve_election(
+            listener,
+            election.clone(),
+            store.clone(),
+            signer.clone(),
+            election_timeout,
+        ));
+
+        // Standbys process the blocks the leader broadcasts over P2P
+        blockchain.set_synced();
+
+        let state = Self {
+            node_id: cfg.ha.node_id,
+            peers: cfg.ha.peers,
+            heartbeat_interval: Duration::from_millis(cfg.ha.heartbeat_interval_ms),
+            election_timeout,
+            election: election.clone(),
+            signer,
+            eth_client: EthClient::new_with_multiple_urls(cfg.eth.rpc_url.clone())?,
+            on_chain_proposer_address: cfg.l1_committer.on_chain_proposer_address,
+            store,
+            rollup_store,
+            sequencer_state,
+        };
+        let mut leader_elector = LeaderElector::start(state);
+        leader_elector
+            .cast(InMessage::Tick)
+            .await
+            .map_err(LeaderElectorError::GenServerError)?;
+        Ok(LeaderLease(election))
+    }
+
+    async fn tick(&mut self) -> Result<(), LeaderElectorError> {
+        let (role, election_due) = {
+            let election = self.election.lock().await;
+            (election.role, Instant::now() >= election.election_deadline)
+        };
+        match role {
+            Role::Leader => self.renew_lease().await?,
+            Role::Follower | Role::Candidate => {
+                // A node missing committed batches can't take over the sequencing
+                if election_due
+                    && node_is_up_to_date::<LeaderElectorError>(
+                        &self.eth_client,
+                        self.on_chain_proposer_address,
+                        &self.rollup_store,
+                    )
+                    .await?
+                {
+                    self.run_election().await?;
+                }
+            }
+        }
+        self.update_status().await
+    }
+
+    async fn run_election(&mut self) -> Result<(), LeaderElectorError> {
+        let last_block = self.store.get_latest_block_number().await?;
+        let started_at = Instant::now();
+        let term = {
+            let mut election = self.election.lock().await;
+            election.term += 1;
+            election.voted_for = Some(self.node_id);
+            election.role = Role::Candidate;
+            election.reset_election_deadline(self.election_timeout);
+            election.term
+        };
+        info!("Running for leader in term {term}");
+
+        let responses = self
+            .send_to_peers(ElectionMessage::RequestVote {
+                term,
+                candidate_id: self.node_id,
+                last_block,
+            })
+            .await;
+
+        let mut votes = 1;
+        let mut election = self.election.lock().await;
+        for (peer, response) in responses {
+            match response {
+                Ok(ElectionMessage::Vote {
+                    term: peer_term,
+                    candidate_id,
+                    granted,
+                }) if candidate_id == self.node_id => {
+                    if peer_term > election.term {
+                        election.step_down(peer_term, self.election_timeout);
+                        return Ok(());
+                    }
+                    if granted && peer_term == term {
+                        votes += 1;
+                    }
+                }
+                Ok(_) => warn!("Unexpected response from {peer} to a vote request"),
+                Err(err) => debug!("Failed to request the vote of {peer}: {err}"),
+            }
+        }
+
+        // A heartbeat of another leader may have arrived in the meantime
+        if election.term != term || election.role != Role::Candidate {
+            return Ok(());
+        }
+        if is_majority(votes, self.cluster_size()) {
+            info!("Elected leader in term {term} with {votes} votes");
+            election.role = Role::Leader;
+            // The voters won't vote again for an election timeout
+            election.lease_until = started_at + self.lease_duration();
+        } else {
+            debug!("Lost the election of term {term} with {votes} votes");
+            election.role = Role::Follower;
+        }
+        Ok(())
+    }
+
+    async fn renew_lease(&mut self) -> Result<(), LeaderElectorError> {
+        let sent_at = Instant::now();
+        let term = self.election.lock().await.term;
+        let responses = self
+            .send_to_peers(ElectionMessage::Heartbeat {
+                term,
+                leader_id: self.node_id,
+            })
+            .await;
+
+        let mut acks = 1;
+        let mut election = self.election.lock().await;
+        for (peer, response) in responses {
+            match response {
+                Ok(ElectionMessage::HeartbeatAck {
+                    term: peer_term,
+                    leader_id,
+                    success,
+                }) if leader_id == self.node_id => {
+                    if peer_term > election.term {
+                        info!("Node {peer} is in term {peer_term}, stepping down");
+                        election.step_down(peer_term, self.election_timeout);
+                        return Ok(());
+                    }
+                    if success {
+                        acks += 1;
+                    }
+                }
+                Ok(_) => warn!("Unexpected response from {peer} to a heartbeat"),
+                Err(err) => debug!("Failed to send a heartbeat to {peer}: {err}"),
+            }
+        }
+
+        if election.term == term
+            && election.role == Role::Leader
+            && is_majority(acks, self.cluster_size())
+        {
+            election.lease_until = sent_at + self.lease_duration();
+        }
+        Ok(())
+    }
+
+    /// Starts or stops sequencing when the node wins or loses the leadership.
+    async fn update_status(&mut self) -> Result<(), LeaderElectorError> {
+        let is_leader = self
+            .election
+            .lock()
+            .await
+            .check_lease(self.election_timeout);
+
+        match (self.sequencer_state.status().await, is_leader) {
+            (SequencerStatus::Sequencing, false) => {
+                info!("Lost the leadership, following the new leader");
+                self.sequencer_state
+                    .new_status(SequencerStatus::Following)
+                    .await;
+                // The new leader builds on top of the last committed batch
+                let last_committed_batch = self
+                    .eth_client
+                    .get_last_committed_batch(self.on_chain_proposer_address)
+                    .await?;
+                self.rollup_store
+                    .revert_to_batch(last_committed_batch)
+                    .await?;
+                revert_uncommitted_state::<LeaderElectorError>(
+                    &self.eth_client,
+                    self.on_chain_proposer_address,
+                    &self.store,
+                    &self.rollup_store,
+                )
+                .await?;
+            }
+            (status, true) if status != SequencerStatus::Sequencing => {
+                if self.sync_to_l1().await? {
+                    info!("Leading the cluster, starting sequencing");
+                    self.sequencer_state
+                        .new_status(SequencerStatus::Sequencing)
+                        .await;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Re-syncs to the L1 before sequencing as the new leader: waits for the
+    /// transactions the previous leader sent with the shared committer key to be
+    /// included, then discards the blocks and batches that weren't committed. Returns
+    /// whether the node is ready to sequence; it steps down if it misses a committed
+    /// batch, so a node that has it takes over.
+    async fn sync_to_l1(&self) -> Result<bool, LeaderElectorError> {
+        let committer = self.signer.address();
+        let pending_nonce = self
+            .eth_client
+            .get_nonce(committer, BlockIdentifier::Tag(BlockTag::Pending))
+            .await?;
+        let latest_nonce = self
+            .eth_client
+            .get_nonce(committer, BlockIdentifier::Tag(BlockTag::Latest))
+            .await?;
+        if pending_nonce > latest_nonce {
+            info!("Waiting for the transactions of the previous leader to be included");
+            return Ok(false);
+        }
+
+        if !node_is_up_to_date::<LeaderElectorError>(
+            &self.eth_client,
+            self.on_chain_proposer_address,
+            &self.rollup_store,
+        )
+        .await?
+        {
+            warn!("Elected leader without the last committed batch, stepping down");
+            let mut election = self.election.lock().await;
+            let term = election.term;
+            election.step_down(term, self.election_timeout);
+            return Ok(false);
+        }
+
+        let last_committed_batch = self
+            .eth_client
+            .get_last_committed_batch(self.on_chain_proposer_address)
+            .await?;
+        self.rollup_store
+            .revert_to_batch(last_committed_batch)
+            .await?;
+        revert_uncommitted_state::<LeaderElectorError>(
+            &self.eth_client,
+            self.on_chain_proposer_address,
+            &self.store,
+            &self.rollup_store,
+        )
+        .await?;
+        Ok(true)
+    }
+
+    async fn send_to_peers(
+        &self,
+        message: ElectionMessage,
+    ) -> Vec<(Url, Result<ElectionMessage, LeaderElectorError>)> {
+        let message = match SignedElectionMessage::sign(message, &self.signer).await {
+            Ok(message) => Arc::new(message),
+            Err(err) => {
+                error!("Failed to sign election message: {err}");
+                return Vec::new();
+            }
+        };
+        let mut requests = JoinSet::new();
+        for peer in &self.peers {
+            let peer = peer.clone();
+            let message = message.clone();
+            let committer = self.signer.address();
+            let request_timeout = self.heartbeat_interval;
+            let max_age = self.election_timeout;
+            requests.spawn(async move {
+                let response = send_request(&peer, &message, request_timeout)
+                    .await
+                    .and_then(|response| response.verify(committer, max_age));
+                (peer, response)
+            });
+        }
+
+        let mut responses = Vec::new();
+        while let Some(response) = requests.join_next().await {
+            match response {
+                Ok(response) => responses.push(response),
+                Err(err) => error!("Election request task failed: {err}"),
+            }
+        }
+        responses
+    }
+
+    fn lease_duration(&self) -> Duration {
+        self.election_timeout / 2
+    }
+
+    fn cluster_size(&self) -> usize {
+        self.peers.len() + 1
+    }
+}
+
+impl GenServer for LeaderElector {
+    type CallMsg = Unused;
+    type CastMsg = InMessage;
+    type OutMsg = OutMessage;
+    type Error = LeaderElectorError;
+
+    async fn handle_cast(
+        mut self,
+        _message: Self::CastMsg,
+        handle: &GenServerHandle<Self>,
+    ) -> CastResponse<Self> {
+        let _ = self
+            .tick()
+            .await
+            .inspect_err(|err| error!("Leader Elector Error: {err}"));
+        send_after(self.heartbeat_interval, handle.clone(), Self::CastMsg::Tick);
+        CastResponse::NoReply(self)
+    }
+}
+
+async fn send_request(
+    url: &Url,
+    request: &SignedElectionMessage,
+    request_timeout: Duration,
+) -> Result<SignedElectionMessage, LeaderElectorError> {
+    timeout(request_timeout, async {
+        let mut stream = TcpStream::connect(&*url.socket_addrs(|| None)?).await?;
+
+        stream.write_all(&serde_json::to_vec(request)?).await?;
+        stream.shutdown().await?;
+
+        read_message(&mut stream).await
+    })
+    .await
+    .map_err(|_| LeaderElectorError::Timeout(url.to_string()))?
+}
+
+async fn read_message(
+    stream: &mut (impl AsyncRead + Unpin),
+) -> Result<SignedElectionMessage, LeaderElectorError> {
+    let mut buffer = Vec::new();
+    stream
+        .take(MAX_MESSAGE_SIZE + 1)
+        .read_to_end(&mut buffer)
+        .await?;
+    if u64::try_from(buffer.len()).unwrap_or(u64::MAX) > MAX_MESSAGE_SIZE {
+        return Err(LeaderElectorError::MessageTooLarge(MAX_MESSAGE_SIZE));
+    }
+    Ok(serde_json::from_slice(&buffer)?)
+}
+
+/// Answers the vote requests and heartbeats of the other nodes of the cluster.
+async fn serve_election(
+    listener: TcpListener,
+    election: Arc<Mutex<Election>>,
+    store: Store,
+    signer: Signer,
+    election_timeout: Duration,
+) {
+    loop {
+        let (stream, addr) = match listener.accept().await {
+            Ok(connection) => connection,
+            Err(err) => {
+                error!("Failed to accept election connection: {err}");
+                continue;
+            }
+        };
+        let election = election.clone();
+        let store = store.clone();
+        let signer = signer.clone();
+        tokio::spawn(async move {
+            if let Err(err) =
+                handle_connection(stream, &election, &store, &signer, election_timeout).await
+            {
+                debug!("Error handling election connection from {addr}: {err}");
+            }
+        });
+    }
+}
+
+async fn handle_connection(
+    mut stream: TcpStream,
+    election: &Mutex<Election>,
+    store: &Store,
+    signer: &Signer,
+    election_timeout: Duration,
+) -> Result<(), LeaderElectorError> {
+    let request = read_message(&mut stream)
+        .await?
+        .verify(signer.address(), election_timeout)?;
+
+    let response = match request {
+        ElectionMessage::RequestVote {
+            term,
+            candidate_id,
+            last_block,
+        } => {
+            let our_last_block = store.get_latest_block_number().await?;
+            let mut election = election.lock().await;
+            let granted = election.request_vote(
+                term,
+                candidate_id,
+                last_block,
+                our_last_block,
+                election_timeout,
+            );
+            ElectionMessage::Vote {
+                term: election.term,
+                candidate_id,
+                granted,
+            }
+        }
+        ElectionMessage::Heartbeat { term, leader_id } => {
+            let mut election = election.lock().await;
+            let success = election.heartbeat(term, leader_id, election_timeout);
+            ElectionMessage::HeartbeatAck {
+                term: election.term,
+                leader_id,
+                success,
+            }
+        }
+        _ => {
+            warn!("Invalid election request");
+            return Ok(());
+        }
+    };
+
+    let response = SignedElectionMessage::sign(response, signer).await?;
+    stream.write_all(&serde_json::to_vec(&response)?).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethrex_l2_rpc::signer::LocalSigner;
+    use ethrex_storage::EngineType;
+    use ethrex_storage_rollup::EngineTypeRollup;
+    use secp256k1::SecretKey;
+    use tokio::{task::JoinHandle, time::sleep};
+
+    const HEARTBEAT_INTERVAL: Duration = Duration::from_millis(50);
+    const ELECTION_TIMEOUT: Duration = Duration::from_millis(300);
+
+    fn signer(key: u8) -> Signer {
+        LocalSigner::new(SecretKey::from_slice(&[key; 32]).unwrap()).into()
+    }
+
+    /// Election of a node that didn't hear from a leader for an election timeout.
+    fn idle_election() -> Election {
+        let mut election = Election::new(ELECTION_TIMEOUT);
+        election.last_heartbeat = Instant::now().checked_sub(ELECTION_TIMEOUT).unwrap();
+        election
+    }
+
+    struct TestNode {
+        elector: LeaderElector,
+        server: JoinHandle<()>,
+    }
+
+    /// Nodes of a cluster answering each other on local ports, all sharing the
+    /// committer key.
+    async fn cluster(size: u64) -> Vec<TestNode> {
+        let mut listeners = Vec::new();
+        for _ in 0..size {
+            listeners.push(TcpListener::bind("127.0.0.1:0").await.unwrap());
+        }
+        let urls: Vec<Url> = listeners
+            .iter()
+            .map(|listener| {
+                Url::parse(&format!("tcp://{}", listener.local_addr().unwrap())).unwrap()
+            })
+            .collect();
+
+        let mut nodes = Vec::new();
+        for (node_id, listener) in (0..size).zip(listeners) {
+            let election = Arc::new(Mutex::new(Election::new(ELECTION_TIMEOUT)));
+            let store = Store::new("", EngineType::InMemory).unwrap();
+            let server = tokio::spawn(serve_election(
+                listener,
+                election.clone(),
+                store.clone(),
+                signer(1),
+                ELECTION_TIMEOUT,
+            ));
+            let elector = LeaderElector {
+                node_id,
+                peers: (0..size)
+                    .zip(&urls)
+                    .filter(|(peer_id, _)| *peer_id != node_id)
+                    .map(|(_, url)| url.clone())
+                    .collect(),
+                heartbeat_interval: HEARTBEAT_INTERVAL,
+                election_timeout: ELECTION_TIMEOUT,
+                election,
+                signer: signer(1),
+                eth_client: EthClient::new("http://localhost:8545").unwrap(),
+                on_chain_proposer_address: Address::zero(),
+                store,
+                rollup_store: StoreRollup::new("", EngineTypeRollup::InMemory).unwrap(),
+                sequencer_state: SequencerStatus::Following.into(),
+            };
+            nodes.push(TestNode { elector, server });
+        }
+        nodes
+    }
+
+    async fn role(node: &TestNode) -> (Role, u64) {
+        let election = node.elector.election.lock().await;
+        (election.role, election.term)
+    }
+
+    #[test]
+    fn majority_of_the_cluster() {
+        assert!(is_majority(1, 1));
+        assert!(!is_majority(1, 2));
+        assert!(is_majority(2, 2));
+        assert!(!is_majority(1, 3));
+        assert!(is_majority(2, 3));
+        assert!(!is_majority(2, 4));
+        assert!(is_majority(3, 5));
+    }
+
+    #[test]
+    fn vote_granted_once_per_term() {
+        let mut election = idle_election();
+        assert!(election.request_vote(1, 7, 10, 10, ELECTION_TIMEOUT));
+        assert_eq!((election.term, election.voted_for), (1, Some(7)));
+
+        // Granting a vote counts as hearing from the leader
+        assert!(!election.request_vote(1, 7, 10, 10, ELECTION_TIMEOUT));
+        election.last_heartbeat = Instant::now().checked_sub(ELECTION_TIMEOUT).unwrap();
+        assert!(!election.request_vote(1, 8, 10, 10, ELECTION_TIMEOUT));
+        assert!(election.request_vote(1, 7, 10, 10, ELECTION_TIMEOUT));
+
+        election.last_heartbeat = Instant::now().checked_sub(ELECTION_TIMEOUT).unwrap();
+        assert!(election.request_vote(2, 8, 10, 10, ELECTION_TIMEOUT));
+        assert_eq!((election.term, election.voted_for), (2, Some(8)));
+    }
+
+    #[test]
+    fn vote_refused() {
+        // While the leader may still hold its lease
+        let mut election = Election::new(ELECTION_TIMEOUT);
+        assert!(!election.request_vote(1, 7, 10, 10, ELECTION_TIMEOUT));
+
+        let mut election = idle_election();
+        election.term = 3;
+        // For a past term
+        assert!(!election.request_vote(2, 7, 10, 10, ELECTION_TIMEOUT));
+        // To a candidate missing some of our blocks
+        assert!(!election.request_vote(4, 7, 9, 10, ELECTION_TIMEOUT));
+        // By the leader
+        election.role = Role::Leader;
+        assert!(!election.request_vote(4, 7, 10, 10, ELECTION_TIMEOUT));
+        assert_eq!((election.term, election.voted_for), (3, None));
+    }
+
+    #[test]
+    fn heartbeat_of_newer_leader_followed() {
+        let mut election = idle_election();
+        election.term = 2;
+        election.role = Role::Leader;
+
+        assert!(!election.heartbeat(1, 7, ELECTION_TIMEOUT));
+        assert_eq!(election.role, Role::Leader);
+
+        assert!(election.heartbeat(3, 7, ELECTION_TIMEOUT));
+        assert_eq!(election.role, Role::Follower);
+        assert_eq!((election.term, election.voted_for), (3, Some(7)));
+        assert!(election.leader_alive(ELECTION_TIMEOUT));
+    }
+
+    #[tokio::test]
+    async fn leader_steps_down_when_lease_expires() {
+        let mut election = idle_election();
+        election.term = 2;
+        election.role = Role::Leader;
+        election.lease_until = Instant::now() + HEARTBEAT_INTERVAL;
+        let lease = LeaderLease(Arc::new(Mutex::new(election)));
+        assert!(lease.is_held().await);
+        assert!(lease.0.lock().await.check_lease(ELECTION_TIMEOUT));
+
+        sleep(HEARTBEAT_INTERVAL).await;
+        assert!(!lease.is_held().await);
+        let mut election = lease.0.lock().await;
+        assert!(!election.check_lease(ELECTION_TIMEOUT));
+        assert_eq!((election.role, election.term), (Role::Follower, 2));
+    }
+
+    #[tokio::test]
+    async fn messages_signed_by_others_rejected() {
+        let message = || ElectionMessage::Heartbeat {
+            term: u64::MAX,
+            leader_id: 7,
+        };
+        let committer = signer(1).address();
+
+        let signed = SignedElectionMessage::sign(message(), &signer(1))
+            .await
+            .unwrap();
+        assert!(signed.verify(committer, ELECTION_TIMEOUT).is_ok());
+
+        let signed = SignedElectionMessage::sign(message(), &signer(2))
+            .await
+            .unwrap();
+        assert!(matches!(
+            signed.verify(committer, ELECTION_TIMEOUT),
+            Err(LeaderElectorError::UnauthorizedMessage)
+        ));
+
+        // A message from long ago can't be replayed
+        let mut signed = SignedElectionMessage::sign(message(), &signer(1))
+            .await
+            .unwrap();
+        signed.timestamp_ms -= 1000;
+        signed.signature = signer(1)
+            .sign(signed_payload(&signed.message, signed.timestamp_ms).unwrap())
+            .await
+            .unwrap();
+        assert!(matches!(
+            signed.verify(committer, ELECTION_TIMEOUT),
+            Err(LeaderElectorError::UnauthorizedMessage)
+        ));
+    }
+
+    #[tokio::test]
+    async fn leader_steps_down_without_majority() {
+        let mut nodes = cluster(2).await;
+        // Restarted nodes don't vote for an election timeout
+        sleep(ELECTION_TIMEOUT).await;
+
+        let [leader, follower] = nodes.as_mut_slice() else {
+            unreachable!()
+        };
+        leader.elector.run_election().await.unwrap();
+        assert_eq!(role(leader).await, (Role::Leader, 1));
+        assert_eq!(role(follower).await, (Role::Follower, 1));
+
+        leader.elector.renew_lease().await.unwrap();
+        assert!(leader.elector.election.lock().await.holds_lease());
+
+        // Without the acks of the follower the lease isn't renewed
+        follower.server.abort();
+        sleep(HEARTBEAT_INTERVAL).await;
+        leader.elector.renew_lease().await.unwrap();
+        sleep(ELECTION_TIMEOUT / 2).await;
+        assert!(
+            !leader
+                .elector
+                .election
+                .lock()
+                .await
+                .check_lease(ELECTION_TIMEOUT)
+        );
+        assert_eq!(role(leader).await, (Role::Follower, 1));
+    }
+
+    #[tokio::test]
+    async fn leadership_fails_over() {
+        let mut nodes = cluster(3).await;
+        sleep(ELECTION_TIMEOUT).await;
+
+        let [old_leader, new_leader, follower] = nodes.as_mut_slice() else {
+            unreachable!()
+        };
+        old_leader.elector.run_election().await.unwrap();
+        old_leader.elector.renew_lease().await.unwrap();
+        assert_eq!(role(old_leader).await, (Role::Leader, 1));
+
+        // Nobody can be elected while the followers hear from the leader
+        new_leader.elector.run_election().await.unwrap();
+        assert_eq!(role(new_leader).await, (Role::Follower, 2));
+        assert_eq!(role(follower).await, (Role::Follower, 1));
+
+        // The leader stops, a follower takes over once its lease expired
+        old_leader.server.abort();
+        sleep(ELECTION_TIMEOUT).await;
+        assert!(!old_leader.elector.election.lock().await.holds_lease());
+
+        new_leader.elector.run_election().await.unwrap();
+        assert_eq!(role(new_leader).await, (Role::Leader, 3));
+        assert_eq!(role(follower).await, (Role::Follower, 3));
+        assert_eq!(follower.elector.election.lock().await.voted_for, Some(1));
+    }
+}
@@ -7,11 +7,14 @@ use crate::{BlockFetcher, SequencerConfig, StateUpdater};
 use block_producer::BlockProducer;
 use ethrex_blockchain::Blockchain;
 use ethrex_l2_common::prover::ProverType;
+use ethrex_p2p::rlpx::l2::lead_sequencers::LeadSequencers;
 use ethrex_storage::Store;
 use ethrex_storage_rollup::StoreRollup;
 use l1_committer::L1Committer;
+use l1_fee_updater::L1FeeUpdater;
 use l1_proof_sender::L1ProofSender;
 use l1_watcher::L1Watcher;
+use leader_elector::LeaderElector;
 #[cfg(feature = "metrics")]
 use metrics::MetricsGatherer;
 use proof_coordinator::ProofCoordinator;
@@ -21,9 +24,11 @@ use utils::get_needed_proof_types;
 
 pub mod block_producer;
 pub mod l1_committer;
+pub mod l1_fee_updater;
 pub mod l1_proof_sender;
 pub mod l1_proof_verifier;
 pub mod l1_watcher;
+pub mod leader_elector;
 #[cfg(feature = "metrics")]
 pub mod metrics;
 pub mod proof_coordinator;
@@ -38,11 +43,15 @@ pub async fn start_l2(
     rollup_store: StoreRollup,
     blockchain: Arc<Blockchain>,
     cfg: SequencerConfig,
+    lead_sequencers: LeadSequencers,
     cancellation_token: CancellationToken,
     #[cfg(feature = "metrics")] l2_url: String,
 ) -> Result<(), errors::SequencerError> {
     let initial_status = if cfg.based.enabled {
         SequencerStatus::default()
+    } else if cfg.ha.enabled {
+        // The leader elector starts sequencing once the node is elected
+        SequencerStatus::Following
     } else {
         SequencerStatus::Sequencing
     };
@@ -77,12 +86,30 @@ pub async fn start_l2(
     .inspect_err(|err| {
         error!("Error starting Watcher: {err}");
     });
+    // The committer checks the lease of the leadership before sending each commitment
+    let leader_lease = if cfg.ha.enabled {
+        LeaderElector::spawn(
+            cfg.clone(),
+            shared_state.clone(),
+            blockchain.clone(),
+            store.clone(),
+            rollup_store.clone(),
+        )
+        .await
+        .inspect_err(|err| {
+            error!("Error starting Leader Elector: {err}");
+        })
+        .ok()
+    } else {
+        None
+    };
     let _ = L1Committer::spawn(
         store.clone(),
         blockchain.clone(),
         rollup_store.clone(),
         cfg.clone(),
         shared_state.clone(),
+        leader_lease,
     )
     .await
     .inspect_err(|err| {
@@ -110,6 +137,16 @@ pub async fn start_l2(
     .inspect_err(|err| {
         error!("Error starting L1 Proof Sender: {err}");
     });
+    let _ = L1FeeUpdater::spawn(
+        store.clone(),
+        blockchain.clone(),
+        cfg.clone(),
+        shared_state.clone(),
+    )
+    .await
+    .inspect_err(|err| {
+        error!("Error starting L1 Fee Updater: {err}");
+    });
     let _ = BlockProducer::spawn(
         store.clone(),
         rollup_store.clone(),
@@ -143,6 +180,7 @@ pub async fn start_l2(
             blockchain.clone(),
             store.clone(),
             rollup_store.clone(),
+            lead_sequencers,
         )
         .await
         .inspect_err(|err| {
@@ -8,39 +8,37 @@ use bytes::Bytes;
 use ethrex_blockchain::Blockchain;
 use ethrex_common::types::BlobsBundle;
 use ethrex_common::types::block_execution_witness::ExecutionWitnessResult;
-use ethrex_common::{
-    Address,
-    types::{Block, blobs_bundle},
+use ethrex_common::{Address, types::Block};
+use ethrex_l2_common::{
+    compression::data_from_blobs,
+    prover::{BatchProof, ProverType},
 };
-use ethrex_l2_common::prover::{BatchProof, ProverType};
 use ethrex_rpc::clients::eth::EthClient;
 use ethrex_storage::Store;
 use ethrex_storage_rollup::StoreRollup;
 use secp256k1::SecretKey;
 use serde::{Deserialize, Serialize};
-use serde_with::serde_as;
 use spawned_concurrency::messages::Unused;
 use spawned_concurrency::tasks::{CastResponse, GenServer, GenServerHandle};
+use std::collections::HashMap;
 use std::net::{IpAddr, SocketAddr};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
 use tokio::{
     io::{AsyncReadExt, AsyncWriteExt},
     net::{TcpListener, TcpStream},
 };
 use tracing::{debug, error, info, warn};
 
-#[serde_as]
 #[derive(Serialize, Deserialize)]
 pub struct ProverInputData {
     pub blocks: Vec<Block>,
     pub db: ExecutionWitnessResult,
     pub elasticity_multiplier: u64,
     #[cfg(feature = "l2")]
-    #[serde_as(as = "[_; 48]")]
-    pub blob_commitment: blobs_bundle::Commitment,
+    pub blobs_bundle: BlobsBundle,
     #[cfg(feature = "l2")]
-    #[serde_as(as = "[_; 48]")]
-    pub blob_proof: blobs_bundle::Proof,
+    pub validium_data: Vec<u8>,
 }
 
 /// Enum for the ProverServer <--> ProverClient Communication Protocol.
@@ -63,7 +61,14 @@ pub enum ProofData {
     /// The Client initiates the connection with a BatchRequest.
     /// Asking for the ProverInputData the prover_server considers/needs.
     /// The commit hash is used to ensure the client and server are compatible.
-    BatchRequest { commit_hash: String },
+    /// The prover type is used to hand out batches that don't have a proof of that type yet.
+    /// It defaults to None so that requests from older provers are still parsed and answered
+    /// with an InvalidCodeVersion.
+    BatchRequest {
+        commit_hash: String,
+        #[serde(default)]
+        prover_type: Option<ProverType>,
+    },
 
     /// 4.
     /// The Server responds with an InvalidCodeVersion if the code version is not compatible.
@@ -106,8 +111,11 @@ impl ProofData {
     }
 
     /// Builder function for creating a BatchRequest
-    pub fn batch_request(commit_hash: String) -> Self {
-        ProofData::BatchRequest { commit_hash }
+    pub fn batch_request(commit_hash: String, prover_type: ProverType) -> Self {
+        ProofData::BatchRequest {
+            commit_hash,
+            prover_type: Some(prover_type),
+        }
     }
 
     /// Builder function for creating a InvalidCodeVersion
@@ -173,6 +181,10 @@ pub struct ProofCoordinator {
     validium: bool,
     needed_proof_types: Vec<ProverType>,
     commit_hash: String,
+    prover_timeout: Duration,
+    /// Batches assigned to a prover, by batch number and prover type, with the time their lease expires.
+    /// Shared by all the connection handlers so that each prover gets a different batch.
+    batch_leases: Arc<Mutex<HashMap<(u64, ProverType), Instant>>>,
 }
 
 impl ProofCoordinator {
@@ -220,6 +232,8 @@ impl ProofCoordinator {
             validium: config.validium,
             needed_proof_types,
             commit_hash: get_commit_hash(),
+            prover_timeout: Duration::from_millis(config.prover_timeout_ms),
+            batch_leases: Arc::new(Mutex::new(HashMap::new())),
         })
     }
 
@@ -280,6 +294,7 @@ impl ProofCoordinator {
         &mut self,
         stream: &mut TcpStream,
         commit_hash: String,
+        prover_type: Option<ProverType>,
     ) -> Result<(), ProofCoordinatorError> {
         info!("BatchRequest received");
 
@@ -295,7 +310,14 @@ impl ProofCoordinator {
             return Ok(());
         }
 
-        let batch_to_verify = 1 + get_latest_sent_batch(
+        let Some(prover_type) = prover_type else {
+            warn!("BatchRequest without a prover type, sending empty BatchResponse");
+            send_response(stream, &ProofData::empty_batch_response()).await?;
+            return Ok(());
+        };
+        debug!("BatchRequest is from a {prover_type} prover");
+
+        let first_unsent_batch = 1 + get_latest_sent_batch(
             self.needed_proof_types.clone(),
             &self.rollup_store,
             &self.eth_client,
@@ -304,33 +326,93 @@ impl ProofCoordinator {
         .await
         .map_err(|err| ProofCoordinatorError::InternalError(err.to_string()))?;
 
-        let mut all_proofs_exist = true;
-        for proof_type in &self.needed_proof_types {
+        // Batches already sent to the L1 don't need to be proven anymore
+        self.batch_leases()?
+            .retain(|(batch_number, _), _| *batch_number >= first_unsent_batch);
+
+        let Some(batch_to_verify) = self
+            .next_batch_to_prove(first_unsent_batch, prover_type)
+            .await?
+        else {
+            debug!("Sending empty BatchResponse");
+            send_response(stream, &ProofData::empty_batch_response()).await?;
+            info!("Empty BatchResponse sent");
+            return Ok(());
+        };
+
+        let input = match self.create_prover_input(batch_to_verify).await {
+            Ok(input) => input,
+            Err(err) => {
+                // Let another prover take the batch
+                self.batch_leases()?.remove(&(batch_to_verify, prover_type));
+                return Err(err);
+            }
+        };
+        debug!("Sending BatchResponse for block_number: {batch_to_verify}");
+        let response = ProofData::batch_response(batch_to_verify, input);
+
+        send_response(stream, &response).await?;
+        info!("BatchResponse sent for batch number: {batch_to_verify}");
+
+        Ok(())
+    }
+
+    /// Returns the first committed batch, starting from `first_unsent_batch`, that doesn't have
+    /// a proof of the given type nor a prover of that type working on it, leasing it to the caller.
+    /// Batches whose lease expired are assigned again.
+    /// Provers of a type that isn't needed don't get any batch.
+    async fn next_batch_to_prove(
+        &self,
+        first_unsent_batch: u64,
+        prover_type: ProverType,
+    ) -> Result<Option<u64>, ProofCoordinatorError> {
+        if !self.needed_proof_types.contains(&prover_type) {
+            warn!("Batch requested by a {prover_type} prover, but its proofs aren't needed");
+            return Ok(None);
+        }
+        let mut batch_number = first_unsent_batch;
+        while self.rollup_store.contains_batch(&batch_number).await? {
             if self
                 .rollup_store
-                .get_proof_by_batch_and_type(batch_to_verify, *proof_type)
+                .get_proof_by_batch_and_type(batch_number, prover_type)
                 .await?
                 .is_none()
+                && self.try_lease(batch_number, prover_type)?
             {
-                all_proofs_exist = false;
-                break;
+                return Ok(Some(batch_number));
             }
+            batch_number += 1;
         }
+        Ok(None)
+    }
 
-        let response =
-            if all_proofs_exist || !self.rollup_store.contains_batch(&batch_to_verify).await? {
-                debug!("Sending empty BatchResponse");
-                ProofData::empty_batch_response()
-            } else {
-                let input = self.create_prover_input(batch_to_verify).await?;
-                debug!("Sending BatchResponse for block_number: {batch_to_verify}");
-                ProofData::batch_response(batch_to_verify, input)
-            };
-
-        send_response(stream, &response).await?;
-        info!("BatchResponse sent for batch number: {batch_to_verify}");
+    /// Leases the batch to a prover of the given type, unless another one holds a lease that didn't expire.
+    fn try_lease(
+        &self,
+        batch_number: u64,
+        prover_type: ProverType,
+    ) -> Result<bool, ProofCoordinatorError> {
+        let now = Instant::now();
+        let mut leases = self.batch_leases()?;
+        match leases.get(&(batch_number, prover_type)) {
+            Some(expiration) if *expiration > now => return Ok(false),
+            Some(_) => {
+                warn!(
+                    "Lease of batch {batch_number} for {prover_type} prover expired, reassigning it"
+                );
+            }
+            None => {}
+        }
+        leases.insert((batch_number, prover_type), now + self.prover_timeout);
+        Ok(true)
+    }
 
-        Ok(())
+    fn batch_leases(
+        &self,
+    ) -> Result<MutexGuard<'_, HashMap<(u64, ProverType), Instant>>, ProofCoordinatorError> {
+        self.batch_leases
+            .lock()
+            .map_err(|err| ProofCoordinatorError::Custom(format!("Batch leases poisoned: {err}")))
     }
 
     async fn handle_submit(
@@ -360,6 +442,7 @@ impl ProofCoordinator {
                 .store_proof_by_batch_and_type(batch_number, prover_type, batch_proof)
                 .await?;
         }
+        self.batch_leases()?.remove(&(batch_number, prover_type));
         let response = ProofData::proof_submit_ack(batch_number);
         send_response(stream, &response).await?;
         info!("ProofSubmit ACK sent");
@@ -429,24 +512,23 @@ impl ProofCoordinator {
             .await
             .map_err(ProofCoordinatorError::from)?;
 
-        // Get blobs bundle cached by the L1 Committer (blob, commitment, proof)
-        let (blob_commitment, blob_proof) = if self.validium {
-            ([0; 48], [0; 48])
+        // Get the batch data cached by the L1 Committer. A rollup publishes it in blobs
+        // (blobs, commitments, proofs), a validium may publish it somewhere else or not at all.
+        let blobs = self
+            .rollup_store
+            .get_blobs_by_batch(batch_number)
+            .await?
+            .filter(|blobs| !blobs.is_empty());
+        let (blobs_bundle, validium_data) = if self.validium {
+            let validium_data = blobs
+                .map(|blobs| data_from_blobs(&blobs))
+                .transpose()?
+                .map(Vec::from)
+                .unwrap_or_default();
+            (BlobsBundle::default(), validium_data)
         } else {
-            let blob = self
-                .rollup_store
-                .get_blobs_by_batch(batch_number)
-                .await?
-                .ok_or(ProofCoordinatorError::MissingBlob(batch_number))?;
-            let BlobsBundle {
-                mut commitments,
-                mut proofs,
-                ..
-            } = BlobsBundle::create_from_blobs(&blob)?;
-            match (commitments.pop(), proofs.pop()) {
-                (Some(commitment), Some(proof)) => (commitment, proof),
-                _ => return Err(ProofCoordinatorError::MissingBlob(batch_number)),
-            }
+            let blobs = blobs.ok_or(ProofCoordinatorError::MissingBlob(batch_number))?;
+            (BlobsBundle::create_from_blobs(&blobs)?, Vec::new())
         };
 
         debug!("Created prover input for batch {batch_number}");
@@ -456,9 +538,9 @@ impl ProofCoordinator {
             blocks,
             elasticity_multiplier: self.elasticity_multiplier,
             #[cfg(feature = "l2")]
-            blob_commitment,
+            blobs_bundle,
             #[cfg(feature = "l2")]
-            blob_proof,
+            validium_data,
         })
     }
 
@@ -540,10 +622,13 @@ impl ConnectionHandler {
 
             let data: Result<ProofData, _> = serde_json::from_slice(&buffer);
             match data {
-                Ok(ProofData::BatchRequest { commit_hash }) => {
+                Ok(ProofData::BatchRequest {
+                    commit_hash,
+                    prover_type,
+                }) => {
                     if let Err(e) = self
                         .proof_coordinator
-                        .handle_request(&mut stream, commit_hash)
+                        .handle_request(&mut stream, commit_hash, prover_type)
                         .await
                     {
                         error!("Failed to handle BatchRequest: {e}");
@@ -636,3 +721,164 @@ async fn send_response(
         .map_err(ProofCoordinatorError::ConnectionError)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethrex_common::types::batch::Batch;
+    use ethrex_l2_common::prover::ProofCalldata;
+    use ethrex_storage::EngineType;
+    use ethrex_storage_rollup::EngineTypeRollup;
+
+    async fn test_coordinator(
+        needed_proof_types: Vec<ProverType>,
+        prover_timeout: Duration,
+    ) -> ProofCoordinator {
+        let store = Store::new("", EngineType::InMemory).unwrap();
+        let rollup_store = StoreRollup::new("", EngineTypeRollup::InMemory).unwrap();
+        rollup_store.init().await.unwrap();
+        for number in 1..=3 {
+            rollup_store
+                .seal_batch(Batch {
+                    number,
+                    first_block: number,
+                    last_block: number,
+                    ..Default::default()
+                })
+                .await
+                .unwrap();
+        }
+        ProofCoordinator {
+            listen_ip: IpAddr::from([127, 0, 0, 1]),
+            port: 0,
+            blockchain: Arc::new(Blockchain::default_with_store(store.clone())),
+            store,
+            eth_client: EthClient::new("http://localhost:1").unwrap(),
+            on_chain_proposer_address: Address::zero(),
+            elasticity_multiplier: 2,
+            rollup_store,
+            rpc_url: "http://localhost:1".to_string(),
+            tdx_private_key: SecretKey::from_slice(&[1; 32]).unwrap(),
+            validium: false,
+            needed_proof_types,
+            commit_hash: get_commit_hash(),
+            prover_timeout,
+            batch_leases: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    fn proof(prover_type: ProverType) -> BatchProof {
+        BatchProof::ProofCalldata(ProofCalldata {
+            prover_type,
+            calldata: Vec::new(),
+        })
+    }
+
+    #[tokio::test]
+    async fn provers_get_different_batches() {
+        let coordinator = test_coordinator(
+            vec![ProverType::SP1, ProverType::RISC0],
+            Duration::from_secs(60),
+        )
+        .await;
+
+        // Provers of the same type work on different batches
+        for expected in 1..=3 {
+            assert_eq!(
+                coordinator
+                    .next_batch_to_prove(1, ProverType::SP1)
+                    .await
+                    .unwrap(),
+                Some(expected)
+            );
+        }
+        assert_eq!(
+            coordinator
+                .next_batch_to_prove(1, ProverType::SP1)
+                .await
+                .unwrap(),
+            None
+        );
+
+        // Leases are per prover type
+        assert_eq!(
+            coordinator
+                .next_batch_to_prove(1, ProverType::RISC0)
+                .await
+                .unwrap(),
+            Some(1)
+        );
+
+        // Batches already proven by that type are skipped
+        coordinator
+            .rollup_store
+            .store_proof_by_batch_and_type(2, ProverType::RISC0, proof(ProverType::RISC0))
+            .await
+            .unwrap();
+        assert_eq!(
+            coordinator
+                .next_batch_to_prove(1, ProverType::RISC0)
+                .await
+                .unwrap(),
+            Some(3)
+        );
+
+        // Batches before the first unsent one are never assigned
+        assert_eq!(
+            coordinator
+                .next_batch_to_prove(4, ProverType::RISC0)
+                .await
+                .unwrap(),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn provers_of_unneeded_types_get_no_batch() {
+        let coordinator = test_coordinator(vec![ProverType::SP1], Duration::from_secs(60)).await;
+        assert_eq!(
+            coordinator
+                .next_batch_to_prove(1, ProverType::RISC0)
+                .await
+                .unwrap(),
+            None
+        );
+        assert!(coordinator.batch_leases().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn expired_leases_are_reassigned() {
+        let coordinator = test_coordinator(vec![ProverType::SP1], Duration::from_millis(100)).await;
+
+        assert!(coordinator.try_lease(1, ProverType::SP1).unwrap());
+        assert!(!coordinator.try_lease(1, ProverType::SP1).unwrap());
+        assert!(coordinator.try_lease(1, ProverType::RISC0).unwrap());
+
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        assert!(coordinator.try_lease(1, ProverType::SP1).unwrap());
+        assert!(!coordinator.try_lease(1, ProverType::SP1).unwrap());
+
+        // Once the lease expires the batch is handed out again
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        assert_eq!(
+            coordinator
+                .next_batch_to_prove(1, ProverType::SP1)
+                .await
+                .unwrap(),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn batch_requests_without_prover_type_are_parsed() {
+        let request: ProofData =
+            serde_json::from_str(r#"{"BatchRequest":{"commit_hash":"abc"}}"#).unwrap();
+        assert!(matches!(
+            request,
+            ProofData::BatchRequest {
+                commit_hash,
+                prover_type: None,
+            } if commit_hash == "abc"
+        ));
+    }
+}
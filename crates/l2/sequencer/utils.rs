@@ -1,5 +1,5 @@
 use aligned_sdk::common::types::Network;
-use ethrex_common::{Address, H160, H256};
+use ethrex_common::{Address, H160, H256, types::Block};
 use ethrex_l2_common::prover::ProverType;
 use ethrex_l2_rpc::clients::send_tx_bump_gas_exponential_backoff;
 use ethrex_l2_rpc::signer::Signer;
@@ -7,13 +7,14 @@ use ethrex_rpc::{
     EthClient,
     clients::{EthClientError, Overrides, eth::WrappedTransaction},
 };
+use ethrex_storage::{Store, error::StoreError};
 use ethrex_storage_rollup::{RollupStoreError, StoreRollup};
 use keccak_hash::keccak;
 use rand::Rng;
 use std::str::FromStr;
 use std::time::Duration;
 use tokio::time::sleep;
-use tracing::info;
+use tracing::{debug, info};
 
 const DEV_MODE_ADDRESS: H160 = H160([
     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
@@ -153,3 +154,77 @@ where
 
     Ok(is_up_to_date)
 }
+
+/// Reverts state to the last committed batch if known, discarding the blocks built
+/// on top of it.
+pub async fn revert_uncommitted_state<E>(
+    eth_client: &EthClient,
+    on_chain_proposer_address: Address,
+    store: &Store,
+    rollup_store: &StoreRollup,
+) -> Result<(), E>
+where
+    E: From<EthClientError> + From<RollupStoreError> + From<StoreError>,
+{
+    let last_l2_committed_batch = eth_client
+        .get_last_committed_batch(on_chain_proposer_address)
+        .await?;
+
+    debug!("Last committed batch: {last_l2_committed_batch}");
+
+    let Some(last_l2_committed_batch_blocks) = rollup_store
+        .get_block_numbers_by_batch(last_l2_committed_batch)
+        .await?
+    else {
+        // Node is not up to date. There is no uncommitted state to revert.
+        info!("No uncommitted state to revert. Node is not up to date.");
+        return Ok(());
+    };
+
+    debug!(
+        "Last committed batch blocks: {:?}",
+        last_l2_committed_batch_blocks
+    );
+
+    let Some(last_l2_committed_block_number) = last_l2_committed_batch_blocks.last() else {
+        return Err(StoreError::Custom(format!(
+            "No blocks found for the last committed batch {last_l2_committed_batch}"
+        ))
+        .into());
+    };
+
+    debug!("Last committed batch block number: {last_l2_committed_block_number}");
+
+    let last_l2_committed_block_body = store
+        .get_block_body(*last_l2_committed_block_number)
+        .await?
+        .ok_or(StoreError::Custom(
+            "No block body found for the last committed batch block number".to_string(),
+        ))?;
+
+    let last_l2_committed_block_header = store
+        .get_block_header(*last_l2_committed_block_number)?
+        .ok_or(StoreError::Custom(
+        "No block header found for the last committed batch block number".to_string(),
+    ))?;
+
+    let last_l2_committed_batch_block =
+        Block::new(last_l2_committed_block_header, last_l2_committed_block_body);
+
+    let last_l2_committed_batch_block_hash = last_l2_committed_batch_block.hash();
+
+    info!(
+        "Reverting uncommitted state to the last committed batch block {last_l2_committed_block_number} with hash {last_l2_committed_batch_block_hash:#x}"
+    );
+    store
+        .forkchoice_update(
+            None,
+            *last_l2_committed_block_number,
+            last_l2_committed_batch_block_hash,
+            None,
+            None,
+        )
+        .await?;
+
+    Ok(())
+}
@@ -13,9 +13,10 @@ const SERVER_URL: &str = "172.17.0.1:3900";
 const SERVER_URL_DEV: &str = "localhost:3900";
 
 pub async fn get_batch(commit_hash: String) -> Result<(u64, ProgramInput), String> {
-    let batch = connect_to_prover_server_wr(&ProofData::BatchRequest {
-        commit_hash: commit_hash.clone(),
-    })
+    let batch = connect_to_prover_server_wr(&ProofData::batch_request(
+        commit_hash.clone(),
+        ProverType::TDX,
+    ))
     .await
     .map_err(|e| format!("Failed to get Response: {e}"))?;
     match batch {
@@ -30,9 +31,9 @@ pub async fn get_batch(commit_hash: String) -> Result<(u64, ProgramInput), Strin
                     db: input.db,
                     elasticity_multiplier: input.elasticity_multiplier,
                     #[cfg(feature = "l2")]
-                    blob_commitment: input.blob_commitment,
+                    blobs_bundle: input.blobs_bundle,
                     #[cfg(feature = "l2")]
-                    blob_proof: input.blob_proof,
+                    validium_data: input.validium_data,
                 },
             )),
             _ => Err("No blocks to prove.".to_owned()),
@@ -6,6 +6,7 @@ use ethrex_l2::monitor::widget::l2_to_l1_messages::{L2ToL1MessageKind, L2ToL1Mes
 use ethrex_l2::monitor::widget::{L2ToL1MessagesTable, l2_to_l1_messages::L2ToL1MessageRow};
 use ethrex_l2::sequencer::l1_watcher::PrivilegedTransactionData;
 use ethrex_l2_common::calldata::Value;
+use ethrex_l2_common::l1_messages::{L1Message, get_l1_message_hash};
 use ethrex_l2_rpc::{
     clients::{deploy, send_eip1559_transaction},
     signer::{LocalSigner, Signer},
@@ -13,9 +14,9 @@ use ethrex_l2_rpc::{
 use ethrex_l2_sdk::calldata::encode_calldata;
 use ethrex_l2_sdk::l1_to_l2_tx_data::L1ToL2TransactionData;
 use ethrex_l2_sdk::{
-    COMMON_BRIDGE_L2_ADDRESS, bridge_address, claim_erc20withdraw, claim_withdraw,
-    compile_contract, deposit_erc20, get_address_alias, get_address_from_secret_key,
-    get_erc1967_slot, git_clone, wait_for_transaction_receipt,
+    COMMON_BRIDGE_L2_ADDRESS, ETH_TOKEN_ADDRESS, bridge_address, claim_erc20withdraw,
+    claim_withdraw, compile_contract, deposit_erc20, get_address_alias,
+    get_address_from_secret_key, get_erc1967_slot, git_clone, wait_for_transaction_receipt,
 };
 use ethrex_rpc::{
     clients::eth::{EthClient, L1MessageProof, Overrides, from_hex_string_to_u256},
@@ -95,6 +96,10 @@ async fn l2_integration_test() -> Result<(), Box<dyn std::error::Error>> {
 
     test_upgrade(&l1_client, &l2_client).await?;
 
+    test_da_attestations(&l1_client).await?;
+
+    test_native_token_bridge(&l1_client).await?;
+
     test_deposit(
         &l1_client,
         &l2_client,
@@ -237,6 +242,422 @@ async fn test_upgrade(
     Ok(())
 }
 
+/// In this test we deploy an OnChainProposer with a data availability committee on L1 and
+/// check which attestations its `commitBatch` accepts.
+async fn test_da_attestations(l1_client: &EthClient) -> Result<(), Box<dyn std::error::Error>> {
+    println!("Testing data availability attestations");
+    let private_key = l1_rich_wallet_private_key();
+    let owner = get_address_from_secret_key(&private_key)?;
+
+    // The openzeppelin contracts are downloaded by test_upgrade
+    let contracts_path = Path::new("contracts");
+    let remappings = [
+        (
+            "@openzeppelin/contracts",
+            contracts_path.join(
+                "lib/openzeppelin-contracts-upgradeable/lib/openzeppelin-contracts/contracts",
+            ),
+        ),
+        (
+            "@openzeppelin/contracts-upgradeable",
+            contracts_path.join("lib/openzeppelin-contracts-upgradeable/contracts"),
+        ),
+    ];
+
+    println!("test da attestations: Compiling OnChainProposer contract");
+    compile_contract(
+        contracts_path,
+        Path::new("contracts/src/l1/OnChainProposer.sol"),
+        false,
+        Some(&remappings),
+        &[contracts_path],
+    )?;
+    let on_chain_proposer_code =
+        hex::decode(std::fs::read("contracts/solc_out/OnChainProposer.bin")?)?;
+
+    println!("test da attestations: Deploying OnChainProposer contract");
+    let on_chain_proposer =
+        test_deploy_l1(l1_client, &on_chain_proposer_code, &private_key).await?;
+
+    let placeholder = Address::from_low_u64_be(1);
+    let receipt = test_send(
+        l1_client,
+        &private_key,
+        on_chain_proposer,
+        "initialize(bool,address,address,address,address,address,bytes32,bytes32,bytes32,address[],uint256,bytes32)",
+        &[
+            Value::Bool(true),
+            Value::Address(owner),
+            Value::Address(placeholder),
+            Value::Address(placeholder),
+            Value::Address(placeholder),
+            Value::Address(placeholder),
+            Value::FixedBytes(H256::zero().0.to_vec().into()),
+            Value::FixedBytes(H256::zero().0.to_vec().into()),
+            Value::FixedBytes(H256::repeat_byte(1).0.to_vec().into()),
+            Value::Array(vec![Value::Address(owner)]),
+            Value::Uint(U256::one()),
+            Value::FixedBytes(H256::zero().0.to_vec().into()),
+        ],
+    )
+    .await;
+    assert!(receipt.receipt.status, "Initialize transaction failed");
+
+    let mut members: Vec<LocalSigner> = (1..=3)
+        .map(|key| LocalSigner::new(SecretKey::from_slice(&[key; 32]).unwrap()))
+        .collect();
+    members.sort_by_key(|member| member.address);
+    let non_member = LocalSigner::new(SecretKey::from_slice(&[4; 32])?);
+
+    // DataAvailabilityMode.Committee
+    let receipt = test_send(
+        l1_client,
+        &private_key,
+        on_chain_proposer,
+        "setDataAvailability(uint8,address[],uint256)",
+        &[
+            Value::Uint(U256::from(2)),
+            Value::Array(
+                members
+                    .iter()
+                    .map(|member| Value::Address(member.address))
+                    .collect(),
+            ),
+            Value::Uint(U256::from(2)),
+        ],
+    )
+    .await;
+    assert!(
+        receipt.receipt.status,
+        "setDataAvailability transaction failed"
+    );
+
+    let state_diff_hash = H256::repeat_byte(0xda);
+    let commit = async |signers: &[&LocalSigner]| {
+        let signatures = signers
+            .iter()
+            .map(|signer| {
+                let signature = signer.sign(Bytes::copy_from_slice(state_diff_hash.as_bytes()));
+                Value::Bytes(signature.as_bytes().to_vec().into())
+            })
+            .collect();
+        let calldata = encode_calldata(
+            "commitBatch(uint256,bytes32,bytes32,bytes32,bytes32,bytes32,bytes,bytes32,bytes[])",
+            &[
+                Value::Uint(U256::one()),
+                Value::FixedBytes(H256::repeat_byte(2).0.to_vec().into()),
+                Value::FixedBytes(H256::zero().0.to_vec().into()),
+                Value::FixedBytes(H256::zero().0.to_vec().into()),
+                Value::FixedBytes(H256::repeat_byte(3).0.to_vec().into()),
+                Value::FixedBytes(H256::zero().0.to_vec().into()),
+                Value::Bytes(Bytes::new()),
+                Value::FixedBytes(state_diff_hash.0.to_vec().into()),
+                Value::Array(signatures),
+            ],
+        )
+        .unwrap();
+        l1_client
+            .call(
+                on_chain_proposer,
+                calldata.into(),
+                Overrides {
+                    from: Some(owner),
+                    ..Default::default()
+                },
+            )
+            .await
+    };
+
+    let [first, second, third] = members.as_slice() else {
+        return Err("The committee should have 3 members".into());
+    };
+    assert!(
+        commit(&[first, third]).await.is_ok(),
+        "Sorted attestations of different members should be accepted"
+    );
+    assert!(
+        commit(&[third, first]).await.is_err(),
+        "Unsorted attestations should be rejected"
+    );
+    assert!(
+        commit(&[second, second]).await.is_err(),
+        "Duplicated attestations should be rejected"
+    );
+    assert!(
+        commit(&[first, &non_member]).await.is_err(),
+        "Attestations of non members should be rejected"
+    );
+    assert!(
+        commit(&[second]).await.is_err(),
+        "Attestations under the threshold should be rejected"
+    );
+
+    Ok(())
+}
+
+/// In this test we deploy a CommonBridge with an ERC20 native token on L1 and check that ETH
+/// deposits are rejected, that `depositERC20` locks the token and that `claimWithdrawal` releases it.
+async fn test_native_token_bridge(l1_client: &EthClient) -> Result<(), Box<dyn std::error::Error>> {
+    println!("Testing the native token bridge");
+    let private_key = l1_rich_wallet_private_key();
+    let owner = get_address_from_secret_key(&private_key)?;
+    let amount = U256::from(1000);
+
+    // The openzeppelin contracts are downloaded by test_upgrade
+    let contracts_path = Path::new("contracts");
+    let remappings = [
+        (
+            "@openzeppelin/contracts",
+            contracts_path.join(
+                "lib/openzeppelin-contracts-upgradeable/lib/openzeppelin-contracts/contracts",
+            ),
+        ),
+        (
+            "@openzeppelin/contracts-upgradeable",
+            contracts_path.join("lib/openzeppelin-contracts-upgradeable/contracts"),
+        ),
+    ];
+
+    println!("test native token bridge: Compiling OnChainProposer and CommonBridge contracts");
+    for contract in [
+        "contracts/src/l1/OnChainProposer.sol",
+        "contracts/src/l1/CommonBridge.sol",
+    ] {
+        compile_contract(
+            contracts_path,
+            Path::new(contract),
+            false,
+            Some(&remappings),
+            &[contracts_path],
+        )?;
+    }
+    let on_chain_proposer_code =
+        hex::decode(std::fs::read("contracts/solc_out/OnChainProposer.bin")?)?;
+    let bridge_code = hex::decode(std::fs::read("contracts/solc_out/CommonBridge.bin")?)?;
+    let token_code = hex::decode(std::fs::read(
+        "../../fixtures/contracts/ERC20/ERC20.bin/TestToken.bin",
+    )?)?;
+
+    println!("test native token bridge: Deploying contracts");
+    let token = test_deploy_l1(l1_client, &token_code, &private_key).await?;
+    let on_chain_proposer =
+        test_deploy_l1(l1_client, &on_chain_proposer_code, &private_key).await?;
+    let bridge = test_deploy_l1(l1_client, &bridge_code, &private_key).await?;
+
+    // The verifiers are in dev mode, so batches are verified without proofs
+    let dev_mode = Address::from_low_u64_be(0xAA);
+    let receipt = test_send(
+        l1_client,
+        &private_key,
+        on_chain_proposer,
+        "initialize(bool,address,address,address,address,address,bytes32,bytes32,bytes32,address[],uint256,bytes32)",
+        &[
+            Value::Bool(true),
+            Value::Address(owner),
+            Value::Address(dev_mode),
+            Value::Address(dev_mode),
+            Value::Address(dev_mode),
+            Value::Address(dev_mode),
+            Value::FixedBytes(H256::zero().0.to_vec().into()),
+            Value::FixedBytes(H256::zero().0.to_vec().into()),
+            Value::FixedBytes(H256::repeat_byte(1).0.to_vec().into()),
+            Value::Array(vec![Value::Address(owner)]),
+            Value::Uint(U256::one()),
+            Value::FixedBytes(H256::zero().0.to_vec().into()),
+        ],
+    )
+    .await;
+    assert!(receipt.receipt.status, "Initialize transaction failed");
+
+    let receipt = test_send(
+        l1_client,
+        &private_key,
+        bridge,
+        "initialize(address,address,uint256,address)",
+        &[
+            Value::Address(owner),
+            Value::Address(on_chain_proposer),
+            Value::Uint(U256::from(3000)),
+            Value::Address(token),
+        ],
+    )
+    .await;
+    assert!(
+        receipt.receipt.status,
+        "Bridge initialize transaction failed"
+    );
+
+    let receipt = test_send(
+        l1_client,
+        &private_key,
+        on_chain_proposer,
+        "initializeBridgeAddress(address)",
+        &[Value::Address(bridge)],
+    )
+    .await;
+    assert!(
+        receipt.receipt.status,
+        "initializeBridgeAddress transaction failed"
+    );
+
+    println!("test native token bridge: Checking that ETH deposits are rejected");
+    let eth_deposit = |calldata: Bytes| {
+        l1_client.call(
+            bridge,
+            calldata,
+            Overrides {
+                from: Some(owner),
+                value: Some(amount),
+                ..Default::default()
+            },
+        )
+    };
+    assert!(
+        eth_deposit(encode_calldata("deposit(address)", &[Value::Address(owner)])?.into())
+            .await
+            .is_err(),
+        "deposit() should revert with an ERC20 native token"
+    );
+    assert!(
+        eth_deposit(Bytes::new()).await.is_err(),
+        "ETH transfers to the bridge should revert with an ERC20 native token"
+    );
+
+    println!("test native token bridge: Depositing the native token");
+    let receipt = test_send(
+        l1_client,
+        &private_key,
+        token,
+        "approve(address,uint256)",
+        &[Value::Address(bridge), Value::Uint(amount)],
+    )
+    .await;
+    assert!(receipt.receipt.status, "Approve transaction failed");
+
+    let receipt = test_send(
+        l1_client,
+        &private_key,
+        bridge,
+        "depositERC20(address,address,address,uint256)",
+        &[
+            Value::Address(token),
+            Value::Address(ETH_TOKEN_ADDRESS),
+            Value::Address(owner),
+            Value::Uint(amount),
+        ],
+    )
+    .await;
+    assert!(receipt.receipt.status, "depositERC20 transaction failed");
+    assert_eq!(
+        test_balance_of(l1_client, token, bridge).await,
+        amount,
+        "The bridge should hold the deposited native token"
+    );
+    let native_deposits = l1_client
+        .call(
+            bridge,
+            encode_calldata(
+                "deposits(address,address)",
+                &[
+                    Value::Address(ETH_TOKEN_ADDRESS),
+                    Value::Address(ETH_TOKEN_ADDRESS),
+                ],
+            )?
+            .into(),
+            Overrides::default(),
+        )
+        .await?;
+    assert_eq!(
+        from_hex_string_to_u256(&native_deposits)?,
+        amount,
+        "The native token deposit should back the minted L2 balance"
+    );
+
+    println!("test native token bridge: Claiming a native token withdrawal");
+    // A single withdrawal, so the merkle root is its leaf and the proof is empty
+    let message_id = U256::one();
+    let withdrawal_hash = keccak(
+        [
+            ETH_TOKEN_ADDRESS.as_bytes(),
+            ETH_TOKEN_ADDRESS.as_bytes(),
+            owner.as_bytes(),
+            amount.to_big_endian().as_slice(),
+        ]
+        .concat(),
+    );
+    let withdrawal_leaf = get_l1_message_hash(&L1Message {
+        from: COMMON_BRIDGE_L2_ADDRESS,
+        data_hash: withdrawal_hash,
+        message_id,
+    });
+
+    let receipt = test_send(
+        l1_client,
+        &private_key,
+        on_chain_proposer,
+        "commitBatch(uint256,bytes32,bytes32,bytes32,bytes32,bytes32,bytes,bytes32,bytes[])",
+        &[
+            Value::Uint(U256::one()),
+            Value::FixedBytes(H256::repeat_byte(2).0.to_vec().into()),
+            Value::FixedBytes(withdrawal_leaf.0.to_vec().into()),
+            Value::FixedBytes(H256::zero().0.to_vec().into()),
+            Value::FixedBytes(H256::repeat_byte(3).0.to_vec().into()),
+            Value::FixedBytes(H256::zero().0.to_vec().into()),
+            Value::Bytes(Bytes::new()),
+            Value::FixedBytes(H256::zero().0.to_vec().into()),
+            Value::Array(vec![]),
+        ],
+    )
+    .await;
+    assert!(receipt.receipt.status, "commitBatch transaction failed");
+
+    let receipt = test_send(
+        l1_client,
+        &private_key,
+        on_chain_proposer,
+        "verifyBatch(uint256,bytes,bytes,bytes,bytes,bytes,bytes)",
+        &[
+            Value::Uint(U256::one()),
+            Value::Bytes(Bytes::new()),
+            Value::Bytes(Bytes::new()),
+            Value::Bytes(Bytes::new()),
+            Value::Bytes(Bytes::new()),
+            Value::Bytes(Bytes::new()),
+            Value::Bytes(Bytes::new()),
+        ],
+    )
+    .await;
+    assert!(receipt.receipt.status, "verifyBatch transaction failed");
+
+    let owner_balance_before_claim = test_balance_of(l1_client, token, owner).await;
+    let receipt = test_send(
+        l1_client,
+        &private_key,
+        bridge,
+        "claimWithdrawal(uint256,uint256,uint256,bytes32[])",
+        &[
+            Value::Uint(amount),
+            Value::Uint(U256::one()),
+            Value::Uint(message_id),
+            Value::Array(vec![]),
+        ],
+    )
+    .await;
+    assert!(receipt.receipt.status, "claimWithdrawal transaction failed");
+    assert_eq!(
+        test_balance_of(l1_client, token, owner).await,
+        owner_balance_before_claim + amount,
+        "claimWithdrawal should release the native token"
+    );
+    assert_eq!(
+        test_balance_of(l1_client, token, bridge).await,
+        U256::zero(),
+        "The bridge should not hold the claimed native token"
+    );
+
+    Ok(())
+}
+
 /// In this test we deploy a contract on L2 and call it from L1 using the CommonBridge contract.
 /// We call the contract by making a deposit from L1 to L2 with the recipient being the rich account.
 /// The deposit will trigger the call to the contract.
@@ -10,11 +10,11 @@ use super::{
 };
 use crate::{
     kademlia::{KademliaTable, MAX_NODES_PER_BUCKET},
-    network::P2PContext,
+    network::{P2PContext, record_rejected_connection},
     rlpx::{connection::server::RLPxConnection, utils::node_id},
     types::{Endpoint, Node},
 };
-use ethrex_common::H256;
+use ethrex_common::{H256, types::ForkId};
 use secp256k1::{PublicKey, ecdsa::Signature};
 use std::{
     collections::HashSet,
@@ -27,7 +27,6 @@ use tracing::{debug, error};
 
 const MAX_DISC_PACKET_SIZE: usize = 1280;
 const PROOF_EXPIRATION_IN_HS: u64 = 12;
-pub const MAX_PEERS_TCP_CONNECTIONS: usize = 100;
 
 // These interval times are arbitrary numbers, maybe we should read them from a cfg or a cli param
 const REVALIDATION_INTERVAL_IN_SECONDS: u64 = 30;
@@ -69,6 +68,25 @@ impl Discv4Server {
         })
     }
 
+    /// Checks whether an outbound connection to `node` fits within the peer limits.
+    /// Rejections are recorded in the metrics but don't trigger any message to the node.
+    async fn can_dial(&self, node: &Node) -> bool {
+        let check = {
+            let table = self.ctx.table.lock().await;
+            self.ctx
+                .peer_limits
+                .check_new_connection(&table, node, false)
+        };
+        match check {
+            Ok(()) => true,
+            Err(reason) => {
+                debug!("Not dialing node {:?}: {reason}", node.public_key);
+                record_rejected_connection(reason, false);
+                false
+            }
+        }
+    }
+
     /// Initializes the discovery server. It:
     /// - Spawns tasks to handle incoming messages and revalidate known nodes.
     /// - Loads bootnodes to establish initial peer connections.
@@ -216,12 +234,28 @@ impl Discv4Server {
                     return Ok(());
                 }
 
-                // We won't initiate a connection if we have reached the maximum number of peers.
-                let active_connections = {
-                    let table = self.ctx.table.lock().await;
-                    table.count_connected_peers()
+                // We only dial nodes whose ENR advertises a compatible eth fork id.
+                // If we don't have their ENR yet we request it, and the connection is started
+                // once the response is validated.
+                if peer.record.pairs.is_empty() {
+                    self.send_enr_request(&peer.node, self.ctx.table.lock().await)
+                        .await?;
+                    return Ok(());
+                }
+                let Some(remote_fork_id) = peer.record.decode_pairs().eth else {
+                    return Ok(());
                 };
-                if active_connections >= MAX_PEERS_TCP_CONNECTIONS {
+                if !self.is_fork_id_compatible(remote_fork_id).await? {
+                    self.ctx
+                        .table
+                        .lock()
+                        .await
+                        .replace_peer(peer.node.node_id());
+                    return Ok(());
+                }
+
+                // We won't initiate a connection if it doesn't fit within the peer limits.
+                if !self.can_dial(&peer.node).await {
                     return Ok(());
                 }
 
@@ -368,7 +402,7 @@ impl Discv4Server {
                 Ok(())
             }
             Message::ENRResponse(msg) => {
-                {
+                let advertises_eth = {
                     let mut table_lock = self.ctx.table.lock().await;
                     let peer = table_lock.get_by_node_id_mut(packet.get_node_id());
                     let Some(peer) = peer else {
@@ -437,55 +471,17 @@ impl Discv4Server {
                         ));
                     }
 
-                    //https://github.com/ethereum/devp2p/blob/master/enr-entries/eth.md
-                    if let Some(eth) = record.eth {
-                        //update node_record
-                        if self.ctx.set_fork_id().await.is_err() {
-                            return Err(DiscoveryError::StorageAccessError(
-                                "Could not set fork id".into(),
+                    // https://github.com/ethereum/devp2p/blob/master/enr-entries/eth.md
+                    // Peers on other networks or incompatible forks are dropped before we spend
+                    // an RLPx handshake on them
+                    if let Some(eth) = &record.eth {
+                        if !self.is_fork_id_compatible(eth.clone()).await? {
+                            table_lock.replace_peer(packet.get_node_id());
+                            return Err(DiscoveryError::InvalidMessage(
+                                "Could not validate fork id from new node".into(),
                             ));
-                        };
-                        let pairs = self.ctx.local_node_record.lock().await.decode_pairs();
-
-                        if let Some(fork_id) = pairs.eth {
-                            let Ok(block_number) = self.ctx.storage.get_latest_block_number().await
-                            else {
-                                return Err(DiscoveryError::StorageAccessError(
-                                    "Could not get last block number".into(),
-                                ));
-                            };
-                            let Ok(Some(block_header)) =
-                                self.ctx.storage.get_block_header(block_number)
-                            else {
-                                return Err(DiscoveryError::StorageAccessError(
-                                    "Could not get last block number".into(),
-                                ));
-                            };
-                            let Ok(chain_config) = self.ctx.storage.get_chain_config() else {
-                                return Err(DiscoveryError::StorageAccessError(
-                                    "Could not getchaing config".into(),
-                                ));
-                            };
-                            let Ok(Some(genesis_header)) = self.ctx.storage.get_block_header(0)
-                            else {
-                                return Err(DiscoveryError::StorageAccessError(
-                                    "Could not get genesis block number".into(),
-                                ));
-                            };
-                            if !fork_id.is_valid(
-                                eth,
-                                block_number,
-                                block_header.timestamp,
-                                chain_config,
-                                genesis_header,
-                            ) {
-                                table_lock.replace_peer(packet.get_node_id());
-                                return Err(DiscoveryError::InvalidMessage(
-                                    "Could not validate fork id from new node".into(),
-                                ));
-                            }
-                            debug!("ENR eth pair validated");
                         }
+                        debug!("ENR eth pair validated");
                     }
 
                     if let Some(ip) = record.ip {
@@ -502,7 +498,8 @@ impl Discv4Server {
                         "Node with id {:?} record has been successfully updated",
                         peer.node.public_key
                     );
-                }
+                    record.eth.is_some()
+                };
                 let peer = {
                     let table = self.ctx.table.lock().await;
                     table.get_by_node_id(packet.get_node_id()).cloned()
@@ -515,12 +512,17 @@ impl Discv4Server {
                     return Ok(());
                 }
 
-                // We won't initiate a connection if we have reached the maximum number of peers.
-                let active_connections = {
-                    let table = self.ctx.table.lock().await;
-                    table.count_connected_peers()
-                };
-                if active_connections >= MAX_PEERS_TCP_CONNECTIONS {
+                // Nodes that don't advertise the eth protocol in their ENR are kept for discovery only
+                if !advertises_eth {
+                    debug!(
+                        "Node with id {:?} doesn't advertise an eth fork id, not dialing",
+                        peer.node.public_key
+                    );
+                    return Ok(());
+                }
+
+                // We won't initiate a connection if it doesn't fit within the peer limits.
+                if !self.can_dial(&peer.node).await {
                     return Ok(());
                 }
 
@@ -531,6 +533,48 @@ impl Discv4Server {
         }
     }
 
+    /// Checks a fork id advertised in a peer's ENR against our own as described in [EIP-2124](https://eips.ethereum.org/EIPS/eip-2124)
+    /// Peers are considered compatible if our own fork id can't be computed yet
+    async fn is_fork_id_compatible(&self, remote_fork_id: ForkId) -> Result<bool, DiscoveryError> {
+        // update node_record
+        if self.ctx.set_fork_id().await.is_err() {
+            return Err(DiscoveryError::StorageAccessError(
+                "Could not set fork id".into(),
+            ));
+        };
+        let pairs = self.ctx.local_node_record.lock().await.decode_pairs();
+        let Some(fork_id) = pairs.eth else {
+            return Ok(true);
+        };
+        let Ok(block_number) = self.ctx.storage.get_latest_block_number().await else {
+            return Err(DiscoveryError::StorageAccessError(
+                "Could not get last block number".into(),
+            ));
+        };
+        let Ok(Some(block_header)) = self.ctx.storage.get_block_header(block_number) else {
+            return Err(DiscoveryError::StorageAccessError(
+                "Could not get last block number".into(),
+            ));
+        };
+        let Ok(chain_config) = self.ctx.storage.get_chain_config() else {
+            return Err(DiscoveryError::StorageAccessError(
+                "Could not get chain config".into(),
+            ));
+        };
+        let Ok(Some(genesis_header)) = self.ctx.storage.get_block_header(0) else {
+            return Err(DiscoveryError::StorageAccessError(
+                "Could not get genesis block number".into(),
+            ));
+        };
+        Ok(fork_id.is_valid(
+            remote_fork_id,
+            block_number,
+            block_header.timestamp,
+            chain_config,
+            genesis_header,
+        ))
+    }
+
     /// Starts a tokio scheduler that:
     /// - performs periodic revalidation of the current nodes (sends a ping to the old nodes).
     ///
@@ -728,7 +772,9 @@ pub(super) mod tests {
     use super::*;
     use crate::{
         network::{MAX_MESSAGES_TO_BROADCAST, public_key_from_signing_key, serve_p2p_requests},
+        peer_limits::PeerLimits,
         rlpx::message::Message as RLPxMessage,
+        tx_gossip::TxGossipPolicy,
         types::NodeRecord,
     };
     use ethrex_blockchain::Blockchain;
@@ -799,6 +845,8 @@ pub(super) mod tests {
             broadcast,
             client_version: "ethrex/test".to_string(),
             based_context: None,
+            peer_limits: PeerLimits::default(),
+            tx_gossip: TxGossipPolicy::default(),
         };
 
         let discv4 = Discv4Server::try_new(ctx.clone()).await?;
@@ -1108,4 +1156,48 @@ pub(super) mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn fork_id_compatibility_when_dialing() -> Result<(), DiscoveryError> {
+        // Ten blocks with a fork at block 1, not started so it only checks the fork ids
+        let server = start_discovery_server(8096, 10, false).await?;
+        let local_fork_id = server.ctx.storage.get_fork_id().await.unwrap();
+        let chain_config = server.ctx.storage.get_chain_config().unwrap();
+        let genesis_header = server.ctx.storage.get_block_header(0).unwrap().unwrap();
+
+        // Same fork
+        assert!(server.is_fork_id_compatible(local_fork_id.clone()).await?);
+
+        // A peer still syncing before the fork at block 1, which announces it as next
+        let syncing_fork_id = ForkId::new(chain_config, genesis_header, 0, 0);
+        assert_eq!(syncing_fork_id.fork_next, 1);
+        assert!(
+            server
+                .is_fork_id_compatible(syncing_fork_id.clone())
+                .await?
+        );
+
+        // Same past forks but a next fork at block 5 we didn't follow
+        let stale_fork_id = ForkId {
+            fork_next: 5,
+            ..syncing_fork_id
+        };
+        assert!(!server.is_fork_id_compatible(stale_fork_id).await?);
+
+        // Our fork, but with a next fork our head already passed
+        let passed_fork_id = ForkId {
+            fork_next: 2,
+            ..local_fork_id
+        };
+        assert!(!server.is_fork_id_compatible(passed_fork_id).await?);
+
+        // Another chain
+        let other_chain_fork_id = ForkId {
+            fork_hash: H32::repeat_byte(0xab),
+            fork_next: 0,
+        };
+        assert!(!server.is_fork_id_compatible(other_chain_fork_id).await?);
+
+        Ok(())
+    }
 }
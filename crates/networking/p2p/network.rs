@@ -1,22 +1,31 @@
 use crate::discv4::server::{DiscoveryError, Discv4Server};
 use crate::kademlia::{self, KademliaTable};
-use crate::rlpx::connection::server::{RLPxConnBroadcastSender, RLPxConnection};
+use crate::peer_limits::{
+    ConnectionRejection, INBOUND_ATTEMPTS_WINDOW, InboundRateLimiter, PeerLimits,
+};
+use crate::rlpx::connection::server::{
+    BroadcastRecipients, RLPxConnBroadcastSender, RLPxConnection,
+};
 use crate::rlpx::l2::l2_connection::P2PBasedContext;
 use crate::rlpx::message::Message as RLPxMessage;
 use crate::rlpx::p2p::SUPPORTED_SNAP_CAPABILITIES;
+use crate::tx_gossip::TxGossipPolicy;
 use crate::types::{Node, NodeRecord};
 use ethrex_blockchain::Blockchain;
 use ethrex_common::{H256, H512};
 use ethrex_storage::Store;
 use secp256k1::{PublicKey, SecretKey};
 
-use std::{io, net::SocketAddr, sync::Arc};
+use std::{io, net::SocketAddr, sync::Arc, time::Instant};
 use tokio::{
     net::{TcpListener, TcpSocket},
     sync::Mutex,
 };
 use tokio_util::task::TaskTracker;
-use tracing::{error, info};
+use tracing::{debug, error, info};
+
+#[cfg(feature = "metrics")]
+use ethrex_metrics::metrics_p2p::METRICS_P2P;
 
 // Totally arbitrary limit on how
 // many messages the connections can queue,
@@ -45,6 +54,8 @@ pub struct P2PContext {
     pub local_node_record: Arc<Mutex<NodeRecord>>,
     pub client_version: String,
     pub based_context: Option<P2PBasedContext>,
+    pub peer_limits: PeerLimits,
+    pub tx_gossip: TxGossipPolicy,
 }
 
 impl P2PContext {
@@ -59,10 +70,13 @@ impl P2PContext {
         blockchain: Arc<Blockchain>,
         client_version: String,
         based_context: Option<P2PBasedContext>,
+        peer_limits: PeerLimits,
+        tx_gossip: TxGossipPolicy,
     ) -> Self {
         let (channel_broadcast_send_end, _) = tokio::sync::broadcast::channel::<(
             tokio::task::Id,
             Arc<RLPxMessage>,
+            BroadcastRecipients,
         )>(MAX_MESSAGES_TO_BROADCAST);
 
         P2PContext {
@@ -76,6 +90,8 @@ impl P2PContext {
             broadcast: channel_broadcast_send_end,
             client_version,
             based_context,
+            peer_limits,
+            tx_gossip,
         }
     }
 
@@ -122,6 +138,10 @@ pub(crate) async fn serve_p2p_requests(context: P2PContext) {
             return;
         }
     };
+    let mut rate_limiter = InboundRateLimiter::new(
+        context.peer_limits.max_inbound_attempts_per_ip,
+        INBOUND_ATTEMPTS_WINDOW,
+    );
     loop {
         let (stream, peer_addr) = match listener.accept().await {
             Ok(result) => result,
@@ -131,10 +151,27 @@ pub(crate) async fn serve_p2p_requests(context: P2PContext) {
             }
         };
 
+        // Throttled connections are dropped before the handshake, we don't want to spend
+        // any resources on peers that keep reconnecting.
+        if !rate_limiter.try_register(peer_addr.ip(), Instant::now()) {
+            debug!("Dropping connection from {peer_addr}: too many connection attempts");
+            record_rejected_connection(ConnectionRejection::RateLimited, true);
+            continue;
+        }
+
         let _ = RLPxConnection::spawn_as_receiver(context.clone(), peer_addr, stream).await;
     }
 }
 
+/// Reports a connection refused by the [`PeerLimits`] in the metrics
+pub(crate) fn record_rejected_connection(reason: ConnectionRejection, inbound: bool) {
+    #[cfg(feature = "metrics")]
+    METRICS_P2P.inc_rejected_connections(reason.as_str(), inbound);
+    // Without metrics there's nothing to report, the callers already log the rejection
+    #[cfg(not(feature = "metrics"))]
+    let _ = (reason, inbound);
+}
+
 fn listener(tcp_addr: SocketAddr) -> Result<TcpListener, io::Error> {
     let tcp_socket = TcpSocket::new_v4()?;
     tcp_socket.bind(tcp_addr)?;
@@ -2,10 +2,12 @@ pub(crate) mod discv4;
 pub mod kademlia;
 pub mod network;
 pub mod peer_handler;
+pub mod peer_limits;
 pub mod rlpx;
 pub(crate) mod snap;
 pub mod sync;
 pub mod sync_manager;
+pub mod tx_gossip;
 pub mod types;
 
 pub use network::periodically_show_peer_stats;
@@ -0,0 +1,324 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    net::IpAddr,
+    time::{Duration, Instant},
+};
+
+use crate::{kademlia::KademliaTable, types::Node};
+
+/// Default maximum amount of simultaneous RLPx connections
+pub const DEFAULT_MAX_PEERS: usize = 100;
+/// Default fraction of `max_peers` that is kept free for connections started by this node
+pub const DEFAULT_RESERVED_OUTBOUND_RATIO: f64 = 0.33;
+/// Default maximum amount of connected peers sharing the same /24 (IPv4) or /64 (IPv6) subnet
+pub const DEFAULT_MAX_PEERS_PER_SUBNET: usize = 10;
+/// Default maximum amount of inbound connection attempts accepted from a single IP per window
+pub const DEFAULT_MAX_INBOUND_ATTEMPTS_PER_IP: usize = 5;
+/// Window used to rate limit inbound connection attempts
+pub const INBOUND_ATTEMPTS_WINDOW: Duration = Duration::from_secs(30);
+/// Once the rate limiter tracks this many IPs, stale entries are purged
+const RATE_LIMITER_PRUNE_THRESHOLD: usize = 1024;
+
+/// Limits applied to the RLPx connections the node accepts and initiates.
+#[derive(Debug, Clone)]
+pub struct PeerLimits {
+    /// Maximum amount of connected peers, inbound and outbound combined
+    pub max_peers: usize,
+    /// Fraction of `max_peers` that inbound connections can't take.
+    /// This guarantees we can always dial out to peers of our choosing, so a flood of
+    /// inbound connections can't isolate the node.
+    pub reserved_outbound_ratio: f64,
+    /// Maximum amount of connected peers that share the same subnet.
+    /// Loopback and private addresses are not limited.
+    pub max_peers_per_subnet: usize,
+    /// Maximum amount of inbound connection attempts per IP within [`INBOUND_ATTEMPTS_WINDOW`]
+    pub max_inbound_attempts_per_ip: usize,
+}
+
+impl Default for PeerLimits {
+    fn default() -> Self {
+        Self {
+            max_peers: DEFAULT_MAX_PEERS,
+            reserved_outbound_ratio: DEFAULT_RESERVED_OUTBOUND_RATIO,
+            max_peers_per_subnet: DEFAULT_MAX_PEERS_PER_SUBNET,
+            max_inbound_attempts_per_ip: DEFAULT_MAX_INBOUND_ATTEMPTS_PER_IP,
+        }
+    }
+}
+
+/// Reason why a connection was refused by the [`PeerLimits`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionRejection {
+    /// The total amount of peers was reached
+    MaxPeers,
+    /// All the slots available for inbound connections are taken
+    MaxInboundPeers,
+    /// Too many peers are already connected from the same subnet
+    SubnetLimit,
+    /// The remote IP exceeded the allowed inbound connection attempts
+    RateLimited,
+}
+
+impl ConnectionRejection {
+    /// Label used when reporting the rejection in metrics
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConnectionRejection::MaxPeers => "max_peers",
+            ConnectionRejection::MaxInboundPeers => "max_inbound_peers",
+            ConnectionRejection::SubnetLimit => "subnet_limit",
+            ConnectionRejection::RateLimited => "rate_limited",
+        }
+    }
+}
+
+impl std::fmt::Display for ConnectionRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl PeerLimits {
+    /// Amount of peer slots that can only be taken by outbound connections
+    pub fn reserved_outbound_slots(&self) -> usize {
+        let ratio = self.reserved_outbound_ratio.clamp(0.0, 1.0);
+        (self.max_peers as f64 * ratio).ceil() as usize
+    }
+
+    /// Maximum amount of inbound peers
+    pub fn max_inbound_peers(&self) -> usize {
+        self.max_peers
+            .saturating_sub(self.reserved_outbound_slots())
+    }
+
+    /// Checks whether a new connection with `node` fits within the limits, given the peers
+    /// currently connected in `table`.
+    /// `node` must not be counted as connected in the table yet.
+    pub fn check_new_connection(
+        &self,
+        table: &KademliaTable,
+        node: &Node,
+        inbound: bool,
+    ) -> Result<(), ConnectionRejection> {
+        let mut connected = 0;
+        let mut connected_inbound = 0;
+        let mut connected_in_subnet = 0;
+        let subnet = subnet_of(node.ip);
+        for peer in table.filter_peers(&|peer| peer.is_connected) {
+            connected += 1;
+            if peer.is_connection_inbound {
+                connected_inbound += 1;
+            }
+            if subnet.is_some() && subnet_of(peer.node.ip) == subnet {
+                connected_in_subnet += 1;
+            }
+        }
+
+        if connected >= self.max_peers {
+            return Err(ConnectionRejection::MaxPeers);
+        }
+        if inbound && connected_inbound >= self.max_inbound_peers() {
+            return Err(ConnectionRejection::MaxInboundPeers);
+        }
+        if subnet.is_some() && connected_in_subnet >= self.max_peers_per_subnet {
+            return Err(ConnectionRejection::SubnetLimit);
+        }
+        Ok(())
+    }
+}
+
+/// Returns the /24 (IPv4) or /64 (IPv6) subnet the address belongs to.
+/// Returns `None` for addresses that shouldn't be limited (loopback, private and link-local ranges),
+/// so local networks and devnets are unaffected.
+fn subnet_of(ip: IpAddr) -> Option<IpAddr> {
+    match ip {
+        IpAddr::V4(ip) => {
+            if ip.is_loopback() || ip.is_private() || ip.is_link_local() || ip.is_unspecified() {
+                return None;
+            }
+            let [a, b, c, _] = ip.octets();
+            Some(IpAddr::from([a, b, c, 0]))
+        }
+        IpAddr::V6(ip) => {
+            if ip.is_loopback() || ip.is_unspecified() {
+                return None;
+            }
+            let mut segments = ip.segments();
+            segments[4..].fill(0);
+            Some(IpAddr::from(segments))
+        }
+    }
+}
+
+/// Tracks the inbound connection attempts per IP to throttle peers that reconnect too often.
+/// Attempts are rejected before the RLPx handshake, so they cost us close to nothing.
+#[derive(Debug)]
+pub struct InboundRateLimiter {
+    max_attempts: usize,
+    window: Duration,
+    attempts: HashMap<IpAddr, VecDeque<Instant>>,
+}
+
+impl InboundRateLimiter {
+    pub fn new(max_attempts: usize, window: Duration) -> Self {
+        Self {
+            max_attempts,
+            window,
+            attempts: HashMap::new(),
+        }
+    }
+
+    /// Registers a connection attempt from `ip`.
+    /// Returns `false` if the attempt exceeds the allowed rate and should be dropped.
+    /// Loopback addresses are never limited.
+    pub fn try_register(&mut self, ip: IpAddr, now: Instant) -> bool {
+        if ip.is_loopback() {
+            return true;
+        }
+        if self.attempts.len() >= RATE_LIMITER_PRUNE_THRESHOLD {
+            self.prune(now);
+        }
+
+        let window = self.window;
+        let attempts = self.attempts.entry(ip).or_default();
+        while attempts
+            .front()
+            .is_some_and(|attempt| now.duration_since(*attempt) >= window)
+        {
+            attempts.pop_front();
+        }
+        if attempts.len() >= self.max_attempts {
+            return false;
+        }
+        attempts.push_back(now);
+        true
+    }
+
+    /// Removes the IPs that made no attempts during the last window
+    fn prune(&mut self, now: Instant) {
+        let window = self.window;
+        self.attempts.retain(|_, attempts| {
+            attempts
+                .back()
+                .is_some_and(|attempt| now.duration_since(*attempt) < window)
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::public_key_from_signing_key;
+    use ethrex_common::H256;
+    use rand::rngs::OsRng;
+    use secp256k1::SecretKey;
+    use std::net::Ipv4Addr;
+
+    fn random_node(ip: Ipv4Addr) -> Node {
+        let public_key = public_key_from_signing_key(&SecretKey::new(&mut OsRng));
+        Node::new(IpAddr::V4(ip), 30303, 30303, public_key)
+    }
+
+    fn connect(table: &mut KademliaTable, node: Node, inbound: bool) {
+        table.insert_node_forced(node.clone());
+        let peer = table
+            .get_by_node_id_mut(node.node_id())
+            .expect("Peer was just inserted");
+        peer.is_connected = true;
+        peer.is_connection_inbound = inbound;
+    }
+
+    #[test]
+    fn inbound_slots_exclude_reserved_outbound() {
+        let limits = PeerLimits {
+            max_peers: 10,
+            reserved_outbound_ratio: 0.3,
+            ..Default::default()
+        };
+        assert_eq!(limits.reserved_outbound_slots(), 3);
+        assert_eq!(limits.max_inbound_peers(), 7);
+    }
+
+    #[test]
+    fn rejects_inbound_when_inbound_slots_are_full() {
+        let limits = PeerLimits {
+            max_peers: 4,
+            reserved_outbound_ratio: 0.5,
+            ..Default::default()
+        };
+        let mut table = KademliaTable::new(H256::random());
+        connect(&mut table, random_node(Ipv4Addr::new(1, 1, 1, 1)), true);
+        connect(&mut table, random_node(Ipv4Addr::new(2, 2, 2, 2)), true);
+
+        let new_node = random_node(Ipv4Addr::new(3, 3, 3, 3));
+        assert_eq!(
+            limits.check_new_connection(&table, &new_node, true),
+            Err(ConnectionRejection::MaxInboundPeers)
+        );
+        assert_eq!(
+            limits.check_new_connection(&table, &new_node, false),
+            Ok(())
+        );
+
+        connect(&mut table, random_node(Ipv4Addr::new(4, 4, 4, 4)), false);
+        connect(&mut table, random_node(Ipv4Addr::new(5, 5, 5, 5)), false);
+        assert_eq!(
+            limits.check_new_connection(&table, &new_node, false),
+            Err(ConnectionRejection::MaxPeers)
+        );
+    }
+
+    #[test]
+    fn rejects_peers_over_the_subnet_limit() {
+        let limits = PeerLimits {
+            max_peers_per_subnet: 2,
+            ..Default::default()
+        };
+        let mut table = KademliaTable::new(H256::random());
+        connect(&mut table, random_node(Ipv4Addr::new(8, 8, 8, 1)), true);
+        connect(&mut table, random_node(Ipv4Addr::new(8, 8, 8, 2)), false);
+
+        assert_eq!(
+            limits.check_new_connection(&table, &random_node(Ipv4Addr::new(8, 8, 8, 3)), true),
+            Err(ConnectionRejection::SubnetLimit)
+        );
+        assert_eq!(
+            limits.check_new_connection(&table, &random_node(Ipv4Addr::new(8, 8, 9, 1)), true),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn private_addresses_are_not_subnet_limited() {
+        let limits = PeerLimits {
+            max_peers_per_subnet: 1,
+            ..Default::default()
+        };
+        let mut table = KademliaTable::new(H256::random());
+        connect(&mut table, random_node(Ipv4Addr::new(127, 0, 0, 1)), true);
+        connect(&mut table, random_node(Ipv4Addr::new(10, 0, 0, 1)), true);
+
+        assert_eq!(
+            limits.check_new_connection(&table, &random_node(Ipv4Addr::new(127, 0, 0, 1)), true),
+            Ok(())
+        );
+        assert_eq!(
+            limits.check_new_connection(&table, &random_node(Ipv4Addr::new(10, 0, 0, 2)), true),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn rate_limiter_throttles_repeated_attempts() {
+        let mut limiter = InboundRateLimiter::new(2, Duration::from_secs(10));
+        let ip = IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4));
+        let start = Instant::now();
+
+        assert!(limiter.try_register(ip, start));
+        assert!(limiter.try_register(ip, start + Duration::from_secs(1)));
+        assert!(!limiter.try_register(ip, start + Duration::from_secs(2)));
+        // Other IPs are tracked separately
+        assert!(limiter.try_register(IpAddr::V4(Ipv4Addr::new(1, 2, 3, 5)), start));
+        // Once the window has passed, the IP can connect again
+        assert!(limiter.try_register(ip, start + Duration::from_secs(11)));
+    }
+}
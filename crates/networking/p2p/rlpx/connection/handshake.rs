@@ -1,8 +1,4 @@
-use std::{
-    collections::{HashMap, HashSet},
-    net::SocketAddr,
-    sync::Arc,
-};
+use std::{collections::HashMap, net::SocketAddr, sync::Arc};
 
 use super::{
     codec::RLPxCodec,
@@ -18,6 +14,8 @@ use crate::{
             sha256_hmac,
         },
     },
+    snap::SnapServingQuota,
+    tx_gossip::KnownTxs,
     types::Node,
 };
 use aes::cipher::{KeyIvInit, StreamCipher};
@@ -129,13 +127,16 @@ pub(crate) async fn perform(
             negotiated_eth_capability: None,
             negotiated_snap_capability: None,
             last_block_range_update_block: 0,
-            broadcasted_txs: HashSet::new(),
+            known_txs: KnownTxs::default(),
             requested_pooled_txs: HashMap::new(),
             client_version: context.client_version.clone(),
             connection_broadcast_send: context.broadcast.clone(),
             table: context.table.clone(),
             backend_channel: None,
             inbound,
+            peer_limits: context.peer_limits.clone(),
+            tx_gossip: context.tx_gossip.clone(),
+            snap_serving_quota: SnapServingQuota::default(),
             l2_state: context
                 .based_context
                 .map_or_else(|| L2ConnState::Unsupported, L2ConnState::Disconnected),
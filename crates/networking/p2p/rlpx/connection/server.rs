@@ -2,7 +2,7 @@ use std::{
     collections::{HashMap, HashSet},
     net::SocketAddr,
     sync::Arc,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use ethrex_blockchain::Blockchain;
@@ -33,9 +33,9 @@ use tokio_util::codec::Framed;
 use tracing::{debug, error};
 
 use crate::{
-    discv4::server::MAX_PEERS_TCP_CONNECTIONS,
     kademlia::{KademliaTable, PeerChannels},
-    network::P2PContext,
+    network::{P2PContext, record_rejected_connection},
+    peer_limits::PeerLimits,
     rlpx::{
         connection::{codec::RLPxCodec, handshake},
         error::RLPxError,
@@ -49,6 +49,7 @@ use crate::{
         },
         l2::{
             self, PERIODIC_BATCH_BROADCAST_INTERVAL, PERIODIC_BLOCK_BROADCAST_INTERVAL,
+            PERIODIC_PRECONFIRMATION_BROADCAST_INTERVAL,
             l2_connection::{
                 self, L2Cast, L2ConnState, broadcast_l2_message, handle_based_capability_message,
                 handle_l2_broadcast,
@@ -59,12 +60,14 @@ use crate::{
             self, Capability, DisconnectMessage, DisconnectReason, PingMessage, PongMessage,
             SUPPORTED_ETH_CAPABILITIES, SUPPORTED_SNAP_CAPABILITIES,
         },
+        snap::{AccountRange, ByteCodes, StorageRanges, TrieNodes},
         utils::{log_peer_debug, log_peer_error, log_peer_warn},
     },
     snap::{
-        process_account_range_request, process_byte_codes_request, process_storage_ranges_request,
-        process_trie_nodes_request,
+        ServedBytes, SnapServingQuota, process_account_range_request, process_byte_codes_request,
+        process_storage_ranges_request, process_trie_nodes_request,
     },
+    tx_gossip::{KnownTxs, TxGossipPolicy, can_broadcast_in_full, full_broadcast_peers},
     types::Node,
 };
 
@@ -74,7 +77,10 @@ const BLOCK_RANGE_UPDATE_INTERVAL: Duration = Duration::from_secs(60);
 // Soft limit for the number of transaction hashes sent in a single NewPooledTransactionHashes message as per [the spec](https://github.com/ethereum/devp2p/blob/master/caps/eth.md#newpooledtransactionhashes-0x080)
 const NEW_POOLED_TRANSACTION_HASHES_SOFT_LIMIT: usize = 4096;
 
-pub(crate) type RLPxConnBroadcastSender = broadcast::Sender<(tokio::task::Id, Arc<Message>)>;
+/// Peers chosen to receive the full transactions of a broadcast, `None` if every peer should handle it
+pub(crate) type BroadcastRecipients = Option<Arc<HashSet<H256>>>;
+pub(crate) type RLPxConnBroadcastSender =
+    broadcast::Sender<(tokio::task::Id, Arc<Message>, BroadcastRecipients)>;
 
 type MsgResult = Result<OutMessage, RLPxError>;
 type RLPxConnectionHandle = GenServerHandle<RLPxConnection>;
@@ -108,7 +114,7 @@ pub struct Established {
     pub(crate) negotiated_eth_capability: Option<Capability>,
     pub(crate) negotiated_snap_capability: Option<Capability>,
     pub(crate) last_block_range_update_block: u64,
-    pub(crate) broadcasted_txs: HashSet<H256>,
+    pub(crate) known_txs: KnownTxs,
     pub(crate) requested_pooled_txs: HashMap<u64, NewPooledTransactionHashes>,
     pub(crate) client_version: String,
     //// Send end of the channel used to broadcast messages
@@ -125,6 +131,9 @@ pub struct Established {
     pub(crate) table: Arc<Mutex<KademliaTable>>,
     pub(crate) backend_channel: Option<Sender<Message>>,
     pub(crate) inbound: bool,
+    pub(crate) peer_limits: PeerLimits,
+    pub(crate) tx_gossip: TxGossipPolicy,
+    pub(crate) snap_serving_quota: SnapServingQuota,
     pub(crate) l2_state: L2ConnState,
 }
 
@@ -175,7 +184,7 @@ pub enum CastMessage {
     SendPing,
     SendNewPooledTxHashes,
     BlockRangeUpdate,
-    BroadcastMessage(task::Id, Arc<Message>),
+    BroadcastMessage(task::Id, Arc<Message>, BroadcastRecipients),
     L2(L2Cast),
 }
 
@@ -289,12 +298,12 @@ impl GenServer for RLPxConnection {
                 Self::CastMsg::SendNewPooledTxHashes => {
                     send_new_pooled_tx_hashes(&mut established_state).await
                 }
-                Self::CastMsg::BroadcastMessage(id, msg) => {
+                Self::CastMsg::BroadcastMessage(id, msg, recipients) => {
                     log_peer_debug(
                         &established_state.node,
                         &format!("Received broadcasted message: {msg}"),
                     );
-                    handle_broadcast(&mut established_state, (id, msg)).await
+                    handle_broadcast(&mut established_state, (id, msg, recipients)).await
                 }
                 Self::CastMsg::BlockRangeUpdate => {
                     log_peer_debug(&established_state.node, "Block Range Update");
@@ -309,6 +318,9 @@ impl GenServer for RLPxConnection {
                         L2Cast::BlockBroadcast => {
                             l2::l2_connection::send_new_block(&mut established_state).await
                         }
+                        L2Cast::PreconfirmationBroadcast => {
+                            l2_connection::send_new_preconfirmations(&mut established_state).await
+                        }
                     }
                 }
                 _ => Err(RLPxError::MessageNotHandled(
@@ -386,7 +398,7 @@ async fn initialize_connection<S>(
 where
     S: Unpin + Send + Stream<Item = Result<Message, RLPxError>> + 'static,
 {
-    post_handshake_checks(state.table.clone()).await?;
+    post_handshake_checks(state).await?;
 
     exchange_hello_messages(state, &mut stream).await?;
 
@@ -445,6 +457,11 @@ where
             handle.clone(),
             CastMessage::L2(L2Cast::BatchBroadcast),
         );
+        send_interval(
+            PERIODIC_PRECONFIRMATION_BROADCAST_INTERVAL,
+            handle.clone(),
+            CastMessage::L2(L2Cast::PreconfirmationBroadcast),
+        );
     }
 
     spawn_listener(
@@ -455,8 +472,9 @@ where
 
     if state.negotiated_eth_capability.is_some() {
         let stream = BroadcastStream::new(state.connection_broadcast_send.subscribe());
-        let message_builder =
-            |(id, msg): (Id, Arc<Message>)| CastMessage::BroadcastMessage(id, msg);
+        let message_builder = |(id, msg, recipients): (Id, Arc<Message>, BroadcastRecipients)| {
+            CastMessage::BroadcastMessage(id, msg, recipients)
+        };
         spawn_listener(handle.clone(), message_builder, stream);
     }
 
@@ -464,12 +482,20 @@ where
 }
 
 async fn send_new_pooled_tx_hashes(state: &mut Established) -> Result<(), RLPxError> {
+    if state.tx_gossip.no_broadcast {
+        return Ok(());
+    }
     if SUPPORTED_ETH_CAPABILITIES
         .iter()
         .any(|cap| state.capabilities.contains(cap))
     {
-        let filter =
-            |tx: &Transaction| -> bool { !state.broadcasted_txs.contains(&tx.compute_hash()) };
+        let mempool = &state.blockchain.mempool;
+        let filter = |tx: &Transaction| -> bool {
+            let tx_hash = tx.compute_hash();
+            // If the local mark can't be read, hold the transaction back rather than leak it
+            !state.known_txs.contains(&tx_hash)
+                && !(state.tx_gossip.no_locals && mempool.is_local(&tx_hash).unwrap_or(true))
+        };
         let txs: Vec<MempoolTransaction> = state
             .blockchain
             .mempool
@@ -483,7 +509,7 @@ async fn send_new_pooled_tx_hashes(state: &mut Established) -> Result<(), RLPxEr
                 let mut txs_to_send = Vec::with_capacity(tx_count);
                 for tx in tx_chunk {
                     txs_to_send.push((**tx).clone());
-                    state.broadcasted_txs.insert(tx.compute_hash());
+                    state.known_txs.insert(tx.compute_hash());
                 }
 
                 send(
@@ -565,16 +591,20 @@ where
     Ok(())
 }
 
-async fn post_handshake_checks(
-    table: Arc<Mutex<crate::kademlia::KademliaTable>>,
-) -> Result<(), RLPxError> {
-    // Check if connected peers exceed the limit
-    let peer_count = {
-        let table_lock = table.lock().await;
-        table_lock.count_connected_peers()
+async fn post_handshake_checks(state: &Established) -> Result<(), RLPxError> {
+    // Check if the new connection fits within the peer limits.
+    // The handshake is already done at this point, so the peer gets a proper
+    // `Disconnect(TooManyPeers)` instead of a dropped socket.
+    let check = {
+        let table_lock = state.table.lock().await;
+        state
+            .peer_limits
+            .check_new_connection(&table_lock, &state.node, state.inbound)
     };
 
-    if peer_count >= MAX_PEERS_TCP_CONNECTIONS {
+    if let Err(reason) = check {
+        record_rejected_connection(reason, state.inbound);
+        log_peer_debug(&state.node, &format!("Rejecting connection: {reason}"));
         return Err(RLPxError::DisconnectSent(DisconnectReason::TooManyPeers));
     }
 
@@ -767,8 +797,23 @@ async fn handle_peer_message(state: &mut Established, message: Message) -> Resul
                 backend::validate_status(msg_data, &state.storage, eth).await?
             };
         }
-        Message::GetAccountRange(req) => {
-            let response = process_account_range_request(req, state.storage.clone())?;
+        Message::GetAccountRange(mut req) => {
+            // Peers that used up their serving quota get an empty response
+            let response = match state
+                .snap_serving_quota
+                .budget(req.response_bytes, Instant::now())
+            {
+                Some(budget) => {
+                    req.response_bytes = budget;
+                    process_account_range_request(req, state.storage.clone()).await?
+                }
+                None => AccountRange {
+                    id: req.id,
+                    accounts: vec![],
+                    proof: vec![],
+                },
+            };
+            state.snap_serving_quota.consume(response.served_bytes());
             send(state, Message::AccountRange(response)).await?
         }
         Message::Transactions(txs) if peer_supports_eth => {
@@ -776,25 +821,25 @@ async fn handle_peer_message(state: &mut Established, message: Message) -> Resul
             if state.blockchain.is_synced() {
                 let mut valid_txs = vec![];
                 for tx in txs.transactions {
-                    // Mark as broadcasted for the sender so we don't include it in the next
+                    // Mark as known for the sender so we don't include it in the next
                     // `SendNewPooledTxHashes` message to this peer. Doing so violates spec.
                     // For broadcast itself, `handle_broadcast` filters by task id already.
-                    state.broadcasted_txs.insert(tx.compute_hash());
+                    state.known_txs.insert(tx.compute_hash());
                     if let Err(e) = state.blockchain.add_transaction_to_pool(tx.clone()).await {
                         log_peer_warn(&state.node, &format!("Error adding transaction: {e}"));
                         continue;
                     }
-                    valid_txs.push(tx);
+                    // Blob and large transactions are left to the periodic hash announcements
+                    if can_broadcast_in_full(&tx) {
+                        valid_txs.push(tx);
+                    }
                 }
-                // FIXME(#1131): we're supposed to send `Transaction` message only to a random
-                // subset and send only the hashes to everyone else.
-                // Consider sending to the sqrt of the number of peers like geth does.
-                if !valid_txs.is_empty() {
+                if !valid_txs.is_empty() && !state.tx_gossip.no_broadcast {
                     log_peer_debug(
                         &state.node,
                         &format!("Broadcasted {} transactions to peers", valid_txs.len()),
                     );
-                    broadcast_message(state, Message::Transactions(Transactions::new(valid_txs)))?;
+                    broadcast_transactions(state, Transactions::new(valid_txs)).await?;
                 }
             }
         }
@@ -836,6 +881,9 @@ async fn handle_peer_message(state: &mut Established, message: Message) -> Resul
             );
         }
         Message::NewPooledTransactionHashes(new_pooled_transaction_hashes) if peer_supports_eth => {
+            for hash in &new_pooled_transaction_hashes.transaction_hashes {
+                state.known_txs.insert(*hash);
+            }
             let hashes =
                 new_pooled_transaction_hashes.get_transactions_to_request(&state.blockchain)?;
 
@@ -867,16 +915,50 @@ async fn handle_peer_message(state: &mut Established, message: Message) -> Resul
                 msg.handle(&state.node, &state.blockchain).await?;
             }
         }
-        Message::GetStorageRanges(req) => {
-            let response = process_storage_ranges_request(req, state.storage.clone())?;
+        Message::GetStorageRanges(mut req) => {
+            let response = match state
+                .snap_serving_quota
+                .budget(req.response_bytes, Instant::now())
+            {
+                Some(budget) => {
+                    req.response_bytes = budget;
+                    process_storage_ranges_request(req, state.storage.clone()).await?
+                }
+                None => StorageRanges {
+                    id: req.id,
+                    slots: vec![],
+                    proof: vec![],
+                },
+            };
+            state.snap_serving_quota.consume(response.served_bytes());
             send(state, Message::StorageRanges(response)).await?
         }
-        Message::GetByteCodes(req) => {
-            let response = process_byte_codes_request(req, state.storage.clone())?;
+        Message::GetByteCodes(mut req) => {
+            let response = match state.snap_serving_quota.budget(req.bytes, Instant::now()) {
+                Some(budget) => {
+                    req.bytes = budget;
+                    process_byte_codes_request(req, state.storage.clone())?
+                }
+                None => ByteCodes {
+                    id: req.id,
+                    codes: vec![],
+                },
+            };
+            state.snap_serving_quota.consume(response.served_bytes());
             send(state, Message::ByteCodes(response)).await?
         }
-        Message::GetTrieNodes(req) => {
-            let response = process_trie_nodes_request(req, state.storage.clone())?;
+        Message::GetTrieNodes(mut req) => {
+            let response = match state.snap_serving_quota.budget(req.bytes, Instant::now()) {
+                Some(budget) => {
+                    req.bytes = budget;
+                    process_trie_nodes_request(req, state.storage.clone())?
+                }
+                None => TrieNodes {
+                    id: req.id,
+                    nodes: vec![],
+                },
+            };
+            state.snap_serving_quota.consume(response.served_bytes());
             send(state, Message::TrieNodes(response)).await?
         }
         Message::L2(req) if peer_supports_l2 => {
@@ -915,19 +997,24 @@ async fn handle_backend_message(
 
 async fn handle_broadcast(
     state: &mut Established,
-    (id, broadcasted_msg): (task::Id, Arc<Message>),
+    (id, broadcasted_msg, recipients): (task::Id, Arc<Message>, BroadcastRecipients),
 ) -> Result<(), RLPxError> {
     if id != tokio::task::id() {
         match broadcasted_msg.as_ref() {
             Message::Transactions(txs) => {
+                // Only the peers chosen by the sender get the full transactions,
+                // the rest leave them to the next hash announcement.
+                if recipients.is_some_and(|peers| !peers.contains(&state.node.node_id())) {
+                    return Ok(());
+                }
                 let mut filtered = Vec::with_capacity(txs.transactions.len());
                 for tx in &txs.transactions {
                     let tx_hash = tx.compute_hash();
-                    if state.broadcasted_txs.contains(&tx_hash) {
+                    if state.known_txs.contains(&tx_hash) {
                         continue;
                     }
                     filtered.push(tx.clone());
-                    state.broadcasted_txs.insert(tx_hash);
+                    state.known_txs.insert(tx_hash);
                 }
                 if !filtered.is_empty() {
                     log_peer_debug(
@@ -964,18 +1051,39 @@ async fn handle_block_range_update(state: &mut Established) -> Result<(), RLPxEr
     }
 }
 
+/// Broadcasts the transactions to the connected peers, only sqrt(peers) of them
+/// will send them in full, as geth does.
+async fn broadcast_transactions(state: &Established, txs: Transactions) -> Result<(), RLPxError> {
+    let node_id = state.node.node_id();
+    let peers: Vec<H256> = state
+        .table
+        .lock()
+        .await
+        .filter_peers(&|peer| {
+            peer.is_connected
+                && peer.node.node_id() != node_id
+                && SUPPORTED_ETH_CAPABILITIES
+                    .iter()
+                    .any(|cap| peer.supported_capabilities.contains(cap))
+        })
+        .map(|peer| peer.node.node_id())
+        .collect();
+    let recipients = Arc::new(full_broadcast_peers(&peers));
+    let txs = Arc::new(Message::Transactions(txs));
+    let task_id = tokio::task::id();
+    let Ok(_) = state
+        .connection_broadcast_send
+        .send((task_id, txs, Some(recipients)))
+    else {
+        let error_message = "Could not broadcast received transactions";
+        log_peer_error(&state.node, error_message);
+        return Err(RLPxError::BroadcastError(error_message.to_owned()));
+    };
+    Ok(())
+}
+
 pub(crate) fn broadcast_message(state: &Established, msg: Message) -> Result<(), RLPxError> {
     match msg {
-        txs_msg @ Message::Transactions(_) => {
-            let txs = Arc::new(txs_msg);
-            let task_id = tokio::task::id();
-            let Ok(_) = state.connection_broadcast_send.send((task_id, txs)) else {
-                let error_message = "Could not broadcast received transactions";
-                log_peer_error(&state.node, error_message);
-                return Err(RLPxError::BroadcastError(error_message.to_owned()));
-            };
-            Ok(())
-        }
         l2_msg @ Message::L2(_) => broadcast_l2_message(state, l2_msg),
         msg => {
             let error_message = format!("Broadcasting for msg: {msg} is not supported");
@@ -984,3 +1092,67 @@ pub(crate) fn broadcast_message(state: &Established, msg: Message) -> Result<(),
         }
     }
 }
+
+#[cfg(test)]
+impl Established {
+    /// Connection to a peer over a local socket, past the handshake and with the
+    /// based capability negotiated, to test the message handlers without a peer.
+    pub(crate) async fn for_tests(
+        node: Node,
+        storage: Store,
+        blockchain: Arc<Blockchain>,
+        table: Arc<Mutex<KademliaTable>>,
+        based_context: l2_connection::P2PBasedContext,
+    ) -> Self {
+        use ethrex_common::H512;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let stream = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let remote_ephemeral_key = SecretKey::new(&mut rand::thread_rng());
+        let codec = RLPxCodec::new(
+            &handshake::LocalState {
+                nonce: H256::zero(),
+                ephemeral_key: SecretKey::new(&mut rand::thread_rng()),
+                init_message: vec![],
+            },
+            &handshake::RemoteState {
+                public_key: H512::zero(),
+                nonce: H256::zero(),
+                ephemeral_key: PublicKey::from_secret_key(
+                    secp256k1::SECP256K1,
+                    &remote_ephemeral_key,
+                ),
+                init_message: vec![],
+            },
+            [0; 32],
+        )
+        .unwrap();
+        let (sink, _stream) = futures::StreamExt::split(Framed::new(stream, codec));
+        let mut l2_state = L2ConnState::Disconnected(based_context);
+        l2_state.set_established().unwrap();
+        Self {
+            signer: SecretKey::new(&mut rand::thread_rng()),
+            sink: Arc::new(Mutex::new(sink)),
+            node,
+            storage,
+            blockchain,
+            capabilities: vec![],
+            negotiated_eth_capability: None,
+            negotiated_snap_capability: None,
+            last_block_range_update_block: 0,
+            known_txs: KnownTxs::default(),
+            requested_pooled_txs: HashMap::new(),
+            client_version: "ethrex/test".to_string(),
+            connection_broadcast_send: broadcast::channel(16).0,
+            table,
+            backend_channel: None,
+            inbound: false,
+            peer_limits: PeerLimits::default(),
+            tx_gossip: TxGossipPolicy::default(),
+            snap_serving_quota: SnapServingQuota::default(),
+            l2_state,
+        }
+    }
+}
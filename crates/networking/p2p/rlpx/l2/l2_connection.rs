@@ -1,11 +1,13 @@
 use crate::rlpx::connection::server::{broadcast_message, send};
-use crate::rlpx::l2::messages::{BatchSealed, L2Message, NewBlock};
-use crate::rlpx::utils::log_peer_error;
+use crate::rlpx::l2::messages::{BatchSealed, L2Message, NewBlock, NewPreconfirmation};
+use crate::rlpx::utils::{log_peer_error, log_peer_warn};
 use crate::rlpx::{connection::server::Established, error::RLPxError, message::Message};
 use ethereum_types::Address;
+use ethereum_types::H256;
 use ethereum_types::Signature;
 use ethrex_blockchain::error::ChainError;
 use ethrex_blockchain::fork_choice::apply_fork_choice;
+use ethrex_common::types::preconfirmation::PreconfirmationId;
 use ethrex_common::types::{Block, recover_address};
 use ethrex_storage_rollup::StoreRollup;
 use secp256k1::{Message as SecpMessage, SecretKey};
@@ -14,25 +16,33 @@ use std::sync::Arc;
 use tokio::time::Instant;
 use tracing::{debug, info, warn};
 
+use super::lead_sequencers::LeadSequencers;
 use super::messages::batch_hash;
-use super::{PERIODIC_BATCH_BROADCAST_INTERVAL, PERIODIC_BLOCK_BROADCAST_INTERVAL};
+use super::{
+    PERIODIC_BATCH_BROADCAST_INTERVAL, PERIODIC_BLOCK_BROADCAST_INTERVAL,
+    PERIODIC_PRECONFIRMATION_BROADCAST_INTERVAL,
+};
 
 #[derive(Debug, Clone)]
 pub struct L2ConnectedState {
     pub latest_block_sent: u64,
     pub latest_block_added: u64,
     pub latest_batch_sent: u64,
+    pub latest_preconfirmation_sent: Option<PreconfirmationId>,
     pub blocks_on_queue: BTreeMap<u64, Arc<Block>>,
     pub store_rollup: StoreRollup,
     pub committer_key: Arc<SecretKey>,
+    pub lead_sequencers: LeadSequencers,
     pub next_block_broadcast: Instant,
     pub next_batch_broadcast: Instant,
+    pub next_preconfirmation_broadcast: Instant,
 }
 
 #[derive(Debug, Clone)]
 pub struct P2PBasedContext {
     pub store_rollup: StoreRollup,
     pub committer_key: Arc<SecretKey>,
+    pub lead_sequencers: LeadSequencers,
 }
 
 #[derive(Debug, Clone)]
@@ -46,6 +56,7 @@ pub enum L2ConnState {
 pub enum L2Cast {
     BlockBroadcast,
     BatchBroadcast,
+    PreconfirmationBroadcast,
 }
 
 impl L2ConnState {
@@ -73,10 +84,14 @@ impl L2ConnState {
                     latest_block_added: 0,
                     blocks_on_queue: BTreeMap::new(),
                     latest_batch_sent: 0,
+                    latest_preconfirmation_sent: None,
                     store_rollup: ctxt.store_rollup.clone(),
                     committer_key: ctxt.committer_key.clone(),
+                    lead_sequencers: ctxt.lead_sequencers.clone(),
                     next_block_broadcast: Instant::now() + PERIODIC_BLOCK_BROADCAST_INTERVAL,
                     next_batch_broadcast: Instant::now() + PERIODIC_BATCH_BROADCAST_INTERVAL,
+                    next_preconfirmation_broadcast: Instant::now()
+                        + PERIODIC_PRECONFIRMATION_BROADCAST_INTERVAL,
                 };
                 *self = L2ConnState::Connected(state);
                 Ok(())
@@ -86,9 +101,114 @@ impl L2ConnState {
     }
 }
 
-fn validate_signature(_recovered_lead_sequencer: Address) -> bool {
-    // Until the RPC module can be included in the P2P crate, we skip the validation
-    true
+/// Batch a block belongs to, if it's part of a sealed batch.
+async fn batch_of_block(
+    store_rollup: &StoreRollup,
+    block_number: u64,
+) -> Result<Option<u64>, RLPxError> {
+    if let Some(batch_number) = store_rollup.get_batch_number_by_block(block_number).await? {
+        return Ok(Some(batch_number));
+    }
+    // A block right after a sealed batch starts the next one
+    let Some(previous_block) = block_number.checked_sub(1) else {
+        return Ok(None);
+    };
+    Ok(store_rollup
+        .get_batch_number_by_block(previous_block)
+        .await?
+        .map(|batch_number| batch_number + 1))
+}
+
+/// Checks the signer of a message about the given block is its lead sequencer.
+/// Blocks that aren't part of a known batch can be signed by the lead of the last
+/// committed batch, not sealed locally yet, of the current batch or of an upcoming one.
+async fn validate_block_signer(
+    established: &Established,
+    block_number: u64,
+    signer: Address,
+) -> Result<bool, RLPxError> {
+    let l2_state = established.l2_state.connection_state()?;
+    match batch_of_block(&l2_state.store_rollup, block_number).await? {
+        Some(batch_number) => validate_batch_signer(established, batch_number, signer).await,
+        None => {
+            let is_valid = l2_state
+                .lead_sequencers
+                .is_unsealed_batch_lead(signer)
+                .await;
+            if !is_valid {
+                reject_signer(established, signer).await;
+            }
+            Ok(is_valid)
+        }
+    }
+}
+
+/// Checks the signer of a message about the given batch is its lead sequencer.
+async fn validate_batch_signer(
+    established: &Established,
+    batch_number: u64,
+    signer: Address,
+) -> Result<bool, RLPxError> {
+    let l2_state = established.l2_state.connection_state()?;
+    match l2_state.lead_sequencers.lead_for_batch(batch_number).await {
+        Some(lead_sequencer) if lead_sequencer == signer => Ok(true),
+        Some(_) => {
+            reject_signer(established, signer).await;
+            Ok(false)
+        }
+        None => {
+            // The lead isn't known before the state updater reads it from the L1
+            debug!("Lead sequencer of batch {batch_number} unknown, ignoring message");
+            Ok(false)
+        }
+    }
+}
+
+/// Rejects a message signed by someone other than the expected lead sequencer.
+/// The peer is only penalized if the signer never led a known batch: messages of
+/// another lead are relayed as they were signed, so an honest peer may forward
+/// them with a view of the rotation different from ours.
+async fn reject_signer(established: &Established, signer: Address) {
+    let Ok(l2_state) = established.l2_state.connection_state() else {
+        return;
+    };
+    if l2_state.lead_sequencers.is_known_lead(signer).await {
+        debug!("Message signed by {signer:#x}, not the lead sequencer of its batch, ignoring it");
+        return;
+    }
+    log_peer_warn(
+        &established.node,
+        &format!("Message signed by {signer:#x}, which is not a lead sequencer"),
+    );
+    established
+        .table
+        .lock()
+        .await
+        .penalize_peer(established.node.node_id());
+}
+
+/// Recovers the signer of a message, critically penalizing the peer if the
+/// signature is invalid.
+async fn recover_signer(
+    established: &Established,
+    signature: Signature,
+    hash: H256,
+) -> Result<Address, RLPxError> {
+    match recover_address(signature, hash) {
+        Ok(signer) => Ok(signer),
+        Err(e) => {
+            log_peer_error(
+                &established.node,
+                &format!("Failed to recover lead sequencer: {e}"),
+            );
+            established
+                .table
+                .lock()
+                .await
+                .critically_penalize_peer(established.node.node_id());
+            Err(RLPxError::CryptographyError(e.to_string()))
+        }
+    }
 }
 
 pub(crate) async fn handle_based_capability_message(
@@ -109,6 +229,12 @@ pub(crate) async fn handle_based_capability_message(
                 broadcast_message(established, msg.into())?;
             }
         }
+        L2Message::NewPreconfirmation(ref new_preconfirmation_msg) => {
+            if should_process_new_preconfirmation(established, new_preconfirmation_msg).await? {
+                process_new_preconfirmation(established, new_preconfirmation_msg)?;
+                broadcast_message(established, msg.into())?;
+            }
+        }
     }
     Ok(())
 }
@@ -120,6 +246,7 @@ pub(crate) async fn handle_l2_broadcast(
     match l2_msg {
         msg @ Message::L2(L2Message::BatchSealed(_)) => send(state, msg.clone()).await,
         msg @ Message::L2(L2Message::NewBlock(_)) => send(state, msg.clone()).await,
+        msg @ Message::L2(L2Message::NewPreconfirmation(_)) => send(state, msg.clone()).await,
         _ => Err(RLPxError::BroadcastError(format!(
             "Message {:?} is not a valid L2 message for broadcast",
             l2_msg
@@ -133,7 +260,7 @@ pub(crate) fn broadcast_l2_message(state: &Established, l2_msg: Message) -> Resu
             let task_id = tokio::task::id();
             state
                 .connection_broadcast_send
-                .send((task_id, msg.into()))
+                .send((task_id, msg.into(), None))
                 .inspect_err(|e| {
                     log_peer_error(
                         &state.node,
@@ -151,7 +278,7 @@ pub(crate) fn broadcast_l2_message(state: &Established, l2_msg: Message) -> Resu
             let task_id = tokio::task::id();
             state
                 .connection_broadcast_send
-                .send((task_id, msg.into()))
+                .send((task_id, msg.into(), None))
                 .inspect_err(|e| {
                     log_peer_error(
                         &state.node,
@@ -163,6 +290,24 @@ pub(crate) fn broadcast_l2_message(state: &Established, l2_msg: Message) -> Resu
                 })?;
             Ok(())
         }
+        msg @ Message::L2(L2Message::NewPreconfirmation(_)) => {
+            let task_id = tokio::task::id();
+            state
+                .connection_broadcast_send
+                .send((task_id, msg.into(), None))
+                .inspect_err(|e| {
+                    log_peer_error(
+                        &state.node,
+                        &format!("Could not broadcast l2 message NewPreconfirmation: {e}"),
+                    );
+                })
+                .map_err(|_| {
+                    RLPxError::BroadcastError(
+                        "Could not broadcast l2 message NewPreconfirmation".to_owned(),
+                    )
+                })?;
+            Ok(())
+        }
         _ => Err(RLPxError::BroadcastError(format!(
             "Message {:?} is not a valid L2 message for broadcast",
             l2_msg
@@ -206,21 +351,7 @@ pub(crate) async fn send_new_block(established: &mut Established) -> Result<(),
             {
                 Some(sig) => sig,
                 None => {
-                    let (recovery_id, signature) = secp256k1::SECP256K1
-                        .sign_ecdsa_recoverable(
-                            &SecpMessage::from_digest(new_block.hash().to_fixed_bytes()),
-                            &l2_state.committer_key,
-                        )
-                        .serialize_compact();
-                    let recovery_id: u8 = recovery_id.to_i32().try_into().map_err(|e| {
-                        RLPxError::InternalError(format!(
-                            "Failed to convert recovery id to u8: {e}. This is a bug."
-                        ))
-                    })?;
-                    let mut sig = [0u8; 65];
-                    sig[..64].copy_from_slice(&signature);
-                    sig[64] = recovery_id;
-                    let signature = Signature::from_slice(&sig);
+                    let signature = sign_hash(new_block.hash(), &l2_state.committer_key)?;
                     l2_state
                         .store_rollup
                         .store_signature_by_block(new_block.hash(), signature)
@@ -267,17 +398,18 @@ async fn should_process_new_block(
 
     let block_hash = msg.block.hash();
 
-    let recovered_lead_sequencer = recover_address(msg.signature, block_hash).map_err(|e| {
-        log_peer_error(
-            &established.node,
-            &format!("Failed to recover lead sequencer: {e}"),
-        );
-        RLPxError::CryptographyError(e.to_string())
-    })?;
+    let recovered_lead_sequencer = recover_signer(established, msg.signature, block_hash).await?;
 
-    if !validate_signature(recovered_lead_sequencer) {
+    if !validate_block_signer(
+        established,
+        msg.block.header.number,
+        recovered_lead_sequencer,
+    )
+    .await?
+    {
         return Ok(false);
     }
+    let l2_state = established.l2_state.connection_state()?;
     l2_state
         .store_rollup
         .store_signature_by_block(block_hash, msg.signature)
@@ -316,17 +448,12 @@ async fn should_process_batch_sealed(
 
     let hash = batch_hash(&msg.batch);
 
-    let recovered_lead_sequencer = recover_address(msg.signature, hash).map_err(|e| {
-        log_peer_error(
-            &established.node,
-            &format!("Failed to recover lead sequencer: {e}"),
-        );
-        RLPxError::CryptographyError(e.to_string())
-    })?;
+    let recovered_lead_sequencer = recover_signer(established, msg.signature, hash).await?;
 
-    if !validate_signature(recovered_lead_sequencer) {
+    if !validate_batch_signer(established, msg.batch.number, recovered_lead_sequencer).await? {
         return Ok(false);
     }
+    let l2_state = established.l2_state.connection_state()?;
     l2_state
         .store_rollup
         .store_signature_by_batch(msg.batch.number, msg.signature)
@@ -379,12 +506,135 @@ async fn process_new_block(established: &mut Established, msg: &NewBlock) -> Res
             "Added new block {} with hash {:?}",
             next_block_to_add, block_hash
         );
+        established
+            .blockchain
+            .preconfirmations
+            .remove_preconfirmations_before(next_block_to_add)?;
         l2_state.latest_block_added = next_block_to_add;
         next_block_to_add += 1;
     }
     Ok(())
 }
 
+pub(crate) async fn send_new_preconfirmations(
+    established: &mut Established,
+) -> Result<(), RLPxError> {
+    let latest_preconfirmation_sent = established
+        .l2_state
+        .connection_state()?
+        .latest_preconfirmation_sent;
+    let preconfirmations = established
+        .blockchain
+        .preconfirmations
+        .get_preconfirmations_after(latest_preconfirmation_sent)?;
+    for (preconfirmation, signature) in preconfirmations {
+        let id = preconfirmation.id();
+        let signature = match signature {
+            Some(signature) => signature,
+            None => {
+                let l2_state = established.l2_state.connection_state()?;
+                let signature = sign_hash(preconfirmation.hash(), &l2_state.committer_key)?;
+                established
+                    .blockchain
+                    .preconfirmations
+                    .set_signature(id, signature)?;
+                signature
+            }
+        };
+        debug!(
+            "Broadcasting preconfirmation {} of block {}",
+            preconfirmation.index, preconfirmation.block_number
+        );
+        let new_preconfirmation_msg = NewPreconfirmation {
+            preconfirmation: Arc::new(preconfirmation),
+            signature,
+        };
+        send(established, new_preconfirmation_msg.into()).await?;
+        established
+            .l2_state
+            .connection_state_mut()?
+            .latest_preconfirmation_sent = Some(id);
+    }
+    Ok(())
+}
+
+fn sign_hash(hash: H256, secret_key: &SecretKey) -> Result<Signature, RLPxError> {
+    let (recovery_id, signature) = secp256k1::SECP256K1
+        .sign_ecdsa_recoverable(&SecpMessage::from_digest(hash.to_fixed_bytes()), secret_key)
+        .serialize_compact();
+    let recovery_id: u8 = recovery_id.to_i32().try_into().map_err(|e| {
+        RLPxError::InternalError(format!(
+            "Failed to convert recovery id to u8: {e}. This is a bug."
+        ))
+    })?;
+    let mut sig = [0u8; 65];
+    sig[..64].copy_from_slice(&signature);
+    sig[64] = recovery_id;
+    Ok(Signature::from_slice(&sig))
+}
+
+async fn should_process_new_preconfirmation(
+    established: &mut Established,
+    msg: &NewPreconfirmation,
+) -> Result<bool, RLPxError> {
+    if !established.blockchain.is_synced() {
+        debug!("Not processing new preconfirmation, blockchain is not synced");
+        return Ok(false);
+    }
+    let preconfirmation = &msg.preconfirmation;
+    let latest_block_number = established.storage.get_latest_block_number().await?;
+    if preconfirmation.block_number <= latest_block_number
+        || established
+            .blockchain
+            .preconfirmations
+            .contains(preconfirmation.id())?
+    {
+        debug!(
+            "Preconfirmation {} of block {} already known, ignoring it",
+            preconfirmation.index, preconfirmation.block_number
+        );
+        return Ok(false);
+    }
+    // The receipts of a preconfirmation depend on the previous ones of its block
+    if preconfirmation.index > 0
+        && !established
+            .blockchain
+            .preconfirmations
+            .contains((preconfirmation.block_number, preconfirmation.index - 1))?
+    {
+        debug!(
+            "Missing the preconfirmations before {} of block {}, ignoring it",
+            preconfirmation.index, preconfirmation.block_number
+        );
+        return Ok(false);
+    }
+
+    let recovered_lead_sequencer =
+        recover_signer(established, msg.signature, preconfirmation.hash()).await?;
+
+    validate_block_signer(
+        established,
+        preconfirmation.block_number,
+        recovered_lead_sequencer,
+    )
+    .await
+}
+
+fn process_new_preconfirmation(
+    established: &mut Established,
+    msg: &NewPreconfirmation,
+) -> Result<(), RLPxError> {
+    established
+        .blockchain
+        .preconfirmations
+        .add_preconfirmation(msg.preconfirmation.as_ref().clone(), Some(msg.signature))?;
+    debug!(
+        "Added preconfirmation {} of block {}",
+        msg.preconfirmation.index, msg.preconfirmation.block_number
+    );
+    Ok(())
+}
+
 pub(crate) async fn send_sealed_batch(established: &mut Established) -> Result<(), RLPxError> {
     let batch_sealed_msg = {
         let l2_state = established.l2_state.connection_state_mut()?;
@@ -445,9 +695,192 @@ async fn process_batch_sealed(
     Ok(())
 }
 
-// These tests are disabled because they previously assumed
-// the connection used the old struct RLPxConnection, but
-// the new GenServer approach changes a lot of things,
-// this will be eventually addressed (#3563)
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+    use crate::{kademlia::KademliaTable, network::public_key_from_signing_key, types::Node};
+    use ethrex_blockchain::Blockchain;
+    use ethrex_common::types::{BlockHeader, batch::Batch};
+    use ethrex_storage::{EngineType, Store};
+    use ethrex_storage_rollup::EngineTypeRollup;
+    use std::net::{IpAddr, Ipv4Addr};
+    use tokio::sync::Mutex;
+
+    const BLOCKS_PER_BATCH: u64 = 2;
+
+    fn batch(number: u64) -> Batch {
+        Batch {
+            number,
+            first_block: (number - 1) * BLOCKS_PER_BATCH + 1,
+            last_block: number * BLOCKS_PER_BATCH,
+            ..Default::default()
+        }
+    }
+
+    /// Connection of a node that sealed up to batch 30, right after the lead
+    /// rotated from `leads[0]` to `leads[1]` at batch 32, with batch 31 committed
+    /// to the L1 but not sealed locally yet. `leads[2]` leads from batch 64.
+    async fn connection_after_rotation(leads: &[SecretKey; 3]) -> Established {
+        let store_rollup = StoreRollup::new("", EngineTypeRollup::InMemory).unwrap();
+        store_rollup.init().await.unwrap();
+        for batch_number in 1..=30 {
+            store_rollup.seal_batch(batch(batch_number)).await.unwrap();
+        }
+        let [a, b, c] = leads.each_ref().map(|key| {
+            Address::from_slice(&keccak_hash::keccak(public_key_from_signing_key(key)).0[12..])
+        });
+        let lead_sequencers = LeadSequencers::new();
+        lead_sequencers.set_lead(1, a).await;
+        lead_sequencers.set_lead(32, b).await;
+        lead_sequencers.set_lead(64, c).await;
+        lead_sequencers.set_current_batch(32).await;
+
+        let storage = Store::new("", EngineType::InMemory).unwrap();
+        let blockchain = Arc::new(Blockchain::default_with_store(storage.clone()));
+        blockchain.set_synced();
+        let peer_key = SecretKey::new(&mut rand::thread_rng());
+        let node = Node::new(
+            IpAddr::V4(Ipv4Addr::LOCALHOST),
+            30303,
+            30303,
+            public_key_from_signing_key(&peer_key),
+        );
+        let table = Arc::new(Mutex::new(KademliaTable::new(H256::random())));
+        table.lock().await.insert_node(node.clone());
+        let mut established = Established::for_tests(
+            node,
+            storage,
+            blockchain,
+            table,
+            P2PBasedContext {
+                store_rollup,
+                committer_key: Arc::new(SecretKey::new(&mut rand::thread_rng())),
+                lead_sequencers,
+            },
+        )
+        .await;
+        established
+            .l2_state
+            .connection_state_mut()
+            .unwrap()
+            .latest_block_added = batch(30).last_block;
+        established
+    }
+
+    fn new_block(number: u64, signer: &SecretKey) -> NewBlock {
+        let block = Block {
+            header: BlockHeader {
+                number,
+                ..Default::default()
+            },
+            body: Default::default(),
+        };
+        let signature = sign_hash(block.hash(), signer).unwrap();
+        NewBlock {
+            block: block.into(),
+            signature,
+        }
+    }
+
+    async fn peer_score(established: &Established) -> i32 {
+        established
+            .table
+            .lock()
+            .await
+            .get_by_node_id(established.node.node_id())
+            .unwrap()
+            .score
+    }
+
+    fn keys() -> [SecretKey; 4] {
+        std::array::from_fn(|_| SecretKey::new(&mut rand::thread_rng()))
+    }
+
+    #[tokio::test]
+    async fn blocks_around_rotation_accepted_from_their_lead() {
+        let [a, b, c, _] = keys();
+        let mut established = connection_after_rotation(&[a, b, c]).await;
+
+        // Blocks of batch 31, right after the last sealed one, signed by the previous lead
+        assert!(
+            should_process_new_block(&mut established, &new_block(61, &a))
+                .await
+                .unwrap()
+        );
+        assert!(
+            should_process_new_block(&mut established, &new_block(62, &a))
+                .await
+                .unwrap()
+        );
+        // Blocks of batch 32 signed by the new lead
+        assert!(
+            should_process_new_block(&mut established, &new_block(63, &b))
+                .await
+                .unwrap()
+        );
+        assert_eq!(peer_score(&established).await, 0);
+    }
+
+    #[tokio::test]
+    async fn blocks_of_another_lead_ignored_without_penalty() {
+        let [a, b, c, _] = keys();
+        let mut established = connection_after_rotation(&[a, b, c]).await;
+
+        // Batch 31 is known to be led by a, the peer may just relay b's message
+        assert!(
+            !should_process_new_block(&mut established, &new_block(61, &b))
+                .await
+                .unwrap()
+        );
+        assert_eq!(peer_score(&established).await, 0);
+    }
+
+    #[tokio::test]
+    async fn blocks_of_non_sequencer_penalized() {
+        let [a, b, c, d] = keys();
+        let mut established = connection_after_rotation(&[a, b, c]).await;
+
+        assert!(
+            !should_process_new_block(&mut established, &new_block(63, &d))
+                .await
+                .unwrap()
+        );
+        assert_eq!(peer_score(&established).await, -1);
+        assert!(
+            !should_process_new_block(&mut established, &new_block(61, &d))
+                .await
+                .unwrap()
+        );
+        assert_eq!(peer_score(&established).await, -2);
+    }
+
+    #[tokio::test]
+    async fn trailing_batch_sealed_by_previous_lead() {
+        let [a, b, c, d] = keys();
+        let mut established = connection_after_rotation(&[a, b, c]).await;
+        established
+            .l2_state
+            .connection_state_mut()
+            .unwrap()
+            .latest_block_added = batch(31).last_block;
+
+        let sealed_by = |key: &SecretKey| BatchSealed::from_batch_and_key(batch(31), key).unwrap();
+        assert!(
+            should_process_batch_sealed(&mut established, &sealed_by(&a))
+                .await
+                .unwrap()
+        );
+        assert!(
+            !should_process_batch_sealed(&mut established, &sealed_by(&b))
+                .await
+                .unwrap()
+        );
+        assert_eq!(peer_score(&established).await, 0);
+        assert!(
+            !should_process_batch_sealed(&mut established, &sealed_by(&d))
+                .await
+                .unwrap()
+        );
+        assert_eq!(peer_score(&established).await, -1);
+    }
+}
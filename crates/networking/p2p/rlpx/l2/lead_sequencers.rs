@@ -0,0 +1,206 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use ethereum_types::Address;
+use tokio::sync::RwLock;
+
+/// Number of batches before the current one whose lead sequencer is kept, so the
+/// batches sealed by a previous lead can still be verified after a rotation.
+const RETAINED_BATCHES: u64 = 64;
+
+/// Lead sequencers of the based rollup, as registered in the `SequencerRegistry`.
+/// It's updated by the state updater and used to verify the signer of the messages
+/// received from peers.
+#[derive(Debug, Clone, Default)]
+pub struct LeadSequencers(Arc<RwLock<LeadSequencersInner>>);
+
+#[derive(Debug, Default)]
+struct LeadSequencersInner {
+    /// First batch not committed to the L1
+    current_batch: u64,
+    /// Maps a batch number to the lead sequencer from that batch onwards
+    leads: BTreeMap<u64, Address>,
+}
+
+impl LeadSequencers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the lead sequencer of a batch, and of the following ones until the
+    /// next recorded batch.
+    pub async fn set_lead(&self, batch_number: u64, lead: Address) {
+        let mut inner = self.0.write().await;
+        if inner.lead_for_batch(batch_number) != Some(lead) {
+            inner.leads.insert(batch_number, lead);
+        }
+    }
+
+    /// Sets the first batch not committed to the L1, forgetting the leads of the
+    /// batches long before it.
+    pub async fn set_current_batch(&self, batch_number: u64) {
+        let mut inner = self.0.write().await;
+        inner.current_batch = batch_number;
+        let oldest_retained = batch_number.saturating_sub(RETAINED_BATCHES);
+        if let Some(first_key) = inner
+            .leads
+            .range(..=oldest_retained)
+            .next_back()
+            .map(|(key, _)| *key)
+        {
+            inner.leads = inner.leads.split_off(&first_key);
+        }
+    }
+
+    /// Lead sequencer of the given batch, if known.
+    pub async fn lead_for_batch(&self, batch_number: u64) -> Option<Address> {
+        self.0.read().await.lead_for_batch(batch_number)
+    }
+
+    /// Whether the address leads a batch that may not be sealed locally yet: the last
+    /// committed one, whose `BatchSealed` message may still be on its way after a
+    /// rotation, the current one or one of the following ones already known. Used
+    /// for the blocks that aren't part of a sealed batch yet.
+    pub async fn is_unsealed_batch_lead(&self, address: Address) -> bool {
+        let inner = self.0.read().await;
+        let trailing_batch = inner.current_batch.saturating_sub(1);
+        inner.lead_for_batch(trailing_batch) == Some(address)
+            || inner
+                .leads
+                .range(trailing_batch..)
+                .any(|(_, lead)| *lead == address)
+    }
+
+    /// Whether the address led or leads any of the batches known.
+    pub async fn is_known_lead(&self, address: Address) -> bool {
+        self.0
+            .read()
+            .await
+            .leads
+            .values()
+            .any(|lead| *lead == address)
+    }
+}
+
+impl LeadSequencersInner {
+    fn lead_for_batch(&self, batch_number: u64) -> Option<Address> {
+        self.leads
+            .range(..=batch_number)
+            .next_back()
+            .map(|(_, lead)| *lead)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BATCHES_PER_SEQUENCER: u64 = 32;
+
+    /// Records the leads the state updater would see with the given current batch.
+    async fn update(lead_sequencers: &LeadSequencers, current_batch: u64, sequencers: &[Address]) {
+        let lead_of = |batch: u64| {
+            let slot = usize::try_from(batch / BATCHES_PER_SEQUENCER).unwrap();
+            sequencers[slot % sequencers.len()]
+        };
+        let next_slot = (current_batch / BATCHES_PER_SEQUENCER + 1) * BATCHES_PER_SEQUENCER;
+        lead_sequencers.set_current_batch(current_batch).await;
+        lead_sequencers
+            .set_lead(current_batch, lead_of(current_batch))
+            .await;
+        lead_sequencers
+            .set_lead(next_slot, lead_of(next_slot))
+            .await;
+    }
+
+    #[tokio::test]
+    async fn unknown_lead_before_any_update() {
+        let lead_sequencers = LeadSequencers::new();
+        assert_eq!(lead_sequencers.lead_for_batch(1).await, None);
+        assert!(
+            !lead_sequencers
+                .is_unsealed_batch_lead(Address::zero())
+                .await
+        );
+    }
+
+    #[tokio::test]
+    async fn lead_changes_at_slot_boundary() {
+        let (a, b) = (Address::repeat_byte(0xa), Address::repeat_byte(0xb));
+        let lead_sequencers = LeadSequencers::new();
+        update(&lead_sequencers, 30, &[a, b]).await;
+
+        assert_eq!(lead_sequencers.lead_for_batch(30).await, Some(a));
+        assert_eq!(lead_sequencers.lead_for_batch(31).await, Some(a));
+        assert_eq!(lead_sequencers.lead_for_batch(32).await, Some(b));
+        assert_eq!(lead_sequencers.lead_for_batch(63).await, Some(b));
+        // Blocks of the batch being built may come from either side of the boundary
+        assert!(lead_sequencers.is_unsealed_batch_lead(a).await);
+        assert!(lead_sequencers.is_unsealed_batch_lead(b).await);
+        assert!(
+            !lead_sequencers
+                .is_unsealed_batch_lead(Address::repeat_byte(0xc))
+                .await
+        );
+    }
+
+    #[tokio::test]
+    async fn previous_lead_kept_for_trailing_batch() {
+        let (a, b, c) = (
+            Address::repeat_byte(0xa),
+            Address::repeat_byte(0xb),
+            Address::repeat_byte(0xc),
+        );
+        let lead_sequencers = LeadSequencers::new();
+        update(&lead_sequencers, 31, &[a, b, c]).await;
+        assert!(lead_sequencers.is_unsealed_batch_lead(a).await);
+
+        update(&lead_sequencers, 32, &[a, b, c]).await;
+        // Batches before the rotation keep their lead
+        assert_eq!(lead_sequencers.lead_for_batch(31).await, Some(a));
+        assert_eq!(lead_sequencers.lead_for_batch(32).await, Some(b));
+        assert_eq!(lead_sequencers.lead_for_batch(64).await, Some(c));
+        // The last batch of the previous lead may not be sealed locally yet
+        assert!(lead_sequencers.is_unsealed_batch_lead(a).await);
+        assert!(lead_sequencers.is_unsealed_batch_lead(b).await);
+        assert!(lead_sequencers.is_unsealed_batch_lead(c).await);
+
+        update(&lead_sequencers, 33, &[a, b, c]).await;
+        assert!(!lead_sequencers.is_unsealed_batch_lead(a).await);
+        assert!(lead_sequencers.is_known_lead(a).await);
+        assert!(
+            !lead_sequencers
+                .is_known_lead(Address::repeat_byte(0xd))
+                .await
+        );
+    }
+
+    #[tokio::test]
+    async fn single_sequencer_keeps_leading() {
+        let a = Address::repeat_byte(0xa);
+        let lead_sequencers = LeadSequencers::new();
+        for current_batch in 1..100 {
+            update(&lead_sequencers, current_batch, &[a]).await;
+        }
+        assert_eq!(lead_sequencers.lead_for_batch(1).await, Some(a));
+        assert_eq!(lead_sequencers.lead_for_batch(99).await, Some(a));
+        assert!(lead_sequencers.is_unsealed_batch_lead(a).await);
+    }
+
+    #[tokio::test]
+    async fn old_leads_are_forgotten() {
+        let (a, b) = (Address::repeat_byte(0xa), Address::repeat_byte(0xb));
+        let lead_sequencers = LeadSequencers::new();
+        for current_batch in 1..=200 {
+            update(&lead_sequencers, current_batch, &[a, b]).await;
+        }
+        // Batches 0-31 were led by a, forgotten once far enough in the past
+        assert_eq!(lead_sequencers.lead_for_batch(1).await, None);
+        assert_eq!(
+            lead_sequencers.lead_for_batch(200 - RETAINED_BATCHES).await,
+            Some(a)
+        );
+        assert_eq!(lead_sequencers.lead_for_batch(200).await, Some(a));
+        assert_eq!(lead_sequencers.lead_for_batch(192).await, Some(a));
+        assert_eq!(lead_sequencers.lead_for_batch(191).await, Some(b));
+    }
+}
@@ -6,7 +6,7 @@ use crate::rlpx::{
 use bytes::BufMut;
 use ethrex_common::{
     H256, Signature,
-    types::{Block, batch::Batch},
+    types::{Block, batch::Batch, preconfirmation::Preconfirmation},
 };
 use ethrex_rlp::error::{RLPDecodeError, RLPEncodeError};
 use ethrex_rlp::structs::{Decoder, Encoder};
@@ -156,10 +156,46 @@ impl RLPxMessage for BatchSealed {
         Ok(BatchSealed::new(batch, signature))
     }
 }
+/// Transactions the lead sequencer executed on top of the block it's building,
+/// announced before the block is sealed.
+#[derive(Debug, Clone)]
+pub struct NewPreconfirmation {
+    pub preconfirmation: Arc<Preconfirmation>,
+    pub signature: Signature,
+}
+
+impl RLPxMessage for NewPreconfirmation {
+    const CODE: u8 = 0x2;
+
+    fn encode(&self, buf: &mut dyn BufMut) -> Result<(), RLPEncodeError> {
+        let mut encoded_data = vec![];
+        Encoder::new(&mut encoded_data)
+            .encode_field(self.preconfirmation.deref())
+            .encode_field(&self.signature)
+            .finish();
+        let msg_data = snappy_compress(encoded_data)?;
+        buf.put_slice(&msg_data);
+        Ok(())
+    }
+
+    fn decode(msg_data: &[u8]) -> Result<Self, RLPDecodeError> {
+        let decompressed_data = snappy_decompress(msg_data)?;
+        let decoder = Decoder::new(&decompressed_data)?;
+        let (preconfirmation, decoder) = decoder.decode_field("preconfirmation")?;
+        let (signature, decoder) = decoder.decode_field("signature")?;
+        decoder.finish()?;
+        Ok(NewPreconfirmation {
+            preconfirmation: Arc::new(preconfirmation),
+            signature,
+        })
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum L2Message {
     BatchSealed(BatchSealed),
     NewBlock(NewBlock),
+    NewPreconfirmation(NewPreconfirmation),
 }
 
 // I don't really like doing ad-hoc 'from' implementations,
@@ -178,8 +214,69 @@ impl From<NewBlock> for crate::rlpx::message::Message {
     }
 }
 
+impl From<NewPreconfirmation> for crate::rlpx::message::Message {
+    fn from(value: NewPreconfirmation) -> Self {
+        L2Message::NewPreconfirmation(value).into()
+    }
+}
+
 impl From<L2Message> for crate::rlpx::message::Message {
     fn from(value: L2Message) -> Self {
         Message::L2(value)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use ethrex_common::{
+        Address, Signature, U256,
+        types::{
+            EIP1559Transaction, Log, Receipt, Transaction, TxKind, TxType,
+            preconfirmation::Preconfirmation,
+        },
+    };
+    use std::sync::Arc;
+
+    use crate::rlpx::{l2::messages::NewPreconfirmation, message::RLPxMessage};
+
+    #[test]
+    fn new_preconfirmation_message() {
+        let preconfirmation = Preconfirmation {
+            block_number: 7,
+            index: 3,
+            timestamp: 1_700_000_000,
+            base_fee_per_gas: 1_000_000_000,
+            transactions: vec![Transaction::EIP1559Transaction(EIP1559Transaction {
+                chain_id: 65536999,
+                nonce: 1,
+                max_fee_per_gas: 2_000_000_000,
+                gas_limit: 21_000,
+                to: TxKind::Call(Address::from_low_u64_be(1)),
+                value: U256::from(10),
+                ..Default::default()
+            })],
+            receipts: vec![Receipt::new(
+                TxType::EIP1559,
+                true,
+                21_000,
+                vec![Log {
+                    address: Address::from_low_u64_be(2),
+                    topics: Vec::new(),
+                    data: vec![1, 2, 3].into(),
+                }],
+            )],
+        };
+        let new_preconfirmation = NewPreconfirmation {
+            preconfirmation: Arc::new(preconfirmation.clone()),
+            signature: Signature::repeat_byte(0xab),
+        };
+
+        let mut buf = Vec::new();
+        new_preconfirmation.encode(&mut buf).unwrap();
+
+        let decoded = NewPreconfirmation::decode(&buf).unwrap();
+        assert_eq!(*decoded.preconfirmation, preconfirmation);
+        assert_eq!(decoded.preconfirmation.hash(), preconfirmation.hash());
+        assert_eq!(decoded.signature, new_preconfirmation.signature);
+    }
+}
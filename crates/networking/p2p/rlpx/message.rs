@@ -9,7 +9,7 @@ use super::eth::transactions::{
     GetPooledTransactions, NewPooledTransactionHashes, PooledTransactions, Transactions,
 };
 use super::eth::update::BlockRangeUpdate;
-use super::l2::messages::{BatchSealed, L2Message, NewBlock};
+use super::l2::messages::{BatchSealed, L2Message, NewBlock, NewPreconfirmation};
 use super::l2::{self, messages};
 use super::p2p::{DisconnectMessage, HelloMessage, PingMessage, PongMessage};
 use super::snap::{
@@ -105,6 +105,7 @@ impl Message {
                     match l2_msg {
                         L2Message::NewBlock(_) => NewBlock::CODE,
                         L2Message::BatchSealed(_) => BatchSealed::CODE,
+                        L2Message::NewPreconfirmation(_) => NewPreconfirmation::CODE,
                     }
                 }
             }
@@ -176,6 +177,10 @@ impl Message {
                     let decoded = l2::messages::BatchSealed::decode(data)?;
                     L2Message::BatchSealed(decoded)
                 }
+                NewPreconfirmation::CODE => {
+                    let decoded = l2::messages::NewPreconfirmation::decode(data)?;
+                    L2Message::NewPreconfirmation(decoded)
+                }
                 _ => return Err(RLPDecodeError::MalformedData),
             }))
         }
@@ -211,6 +216,7 @@ impl Message {
             Message::L2(l2_msg) => match l2_msg {
                 L2Message::BatchSealed(msg) => msg.encode(buf),
                 L2Message::NewBlock(msg) => msg.encode(buf),
+                L2Message::NewPreconfirmation(msg) => msg.encode(buf),
             },
         }
     }
@@ -246,6 +252,7 @@ impl Display for Message {
             Message::L2(l2_msg) => match l2_msg {
                 L2Message::BatchSealed(_) => "based:BatchSealed".fmt(f),
                 L2Message::NewBlock(_) => "based:NewBlock".fmt(f),
+                L2Message::NewPreconfirmation(_) => "based:NewPreconfirmation".fmt(f),
             },
         }
     }
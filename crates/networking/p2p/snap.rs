@@ -1,4 +1,7 @@
+use std::time::{Duration, Instant};
+
 use bytes::Bytes;
+use ethrex_common::{BigEndianHash, H256, U256, types::AccountState};
 use ethrex_rlp::encode::RLPEncode;
 use ethrex_storage::{Store, error::StoreError};
 
@@ -10,22 +13,63 @@ use crate::rlpx::{
     },
 };
 
+/// Hard cap on the size of a response, regardless of the amount of bytes requested by the peer
+pub const MAX_RESPONSE_BYTES: u64 = 2 * 1024 * 1024;
+/// Maximum amount of time spent building a single response
+/// Once exceeded, the response is cut short and returned with the data gathered so far
+pub const MAX_RESPONSE_TIME: Duration = Duration::from_millis(500);
+/// Maximum amount of bytecodes looked up for a single request
+const MAX_CODE_LOOKUPS: usize = 1024;
+/// Maximum amount of trie node path sets looked up for a single request
+const MAX_TRIE_NODE_LOOKUPS: usize = 1024;
+/// Default amount of bytes a single peer can be served within a [`SNAP_SERVING_QUOTA_WINDOW`]
+pub const SNAP_SERVING_QUOTA_BYTES: u64 = 64 * 1024 * 1024;
+/// Window over which the serving quota of each peer is measured
+pub const SNAP_SERVING_QUOTA_WINDOW: Duration = Duration::from_secs(10);
+
 // Request Processing
 
-pub fn process_account_range_request(
+pub async fn process_account_range_request(
     request: GetAccountRange,
     store: Store,
 ) -> Result<AccountRange, StoreError> {
+    let deadline = Instant::now() + MAX_RESPONSE_TIME;
+    let response_bytes = request.response_bytes.min(MAX_RESPONSE_BYTES);
     let mut accounts = vec![];
     let mut bytes_used = 0;
-    for (hash, account) in store.iter_accounts(request.root_hash)? {
+    // Adds the account to the response if it is within the requested range
+    // Returns false once the response is complete
+    let mut add_account = |hash: H256, account: AccountState| {
         if hash >= request.starting_hash {
             let account = AccountStateSlim::from(account);
             bytes_used += 32 + account.length() as u64;
             accounts.push(AccountRangeUnit { hash, account });
         }
-        if hash >= request.limit_hash || bytes_used >= request.response_bytes {
-            break;
+        hash < request.limit_hash && bytes_used < response_bytes && Instant::now() < deadline
+    };
+    if snapshot_matches_root(&store, request.root_hash).await? {
+        // Read the accounts straight from the snapshot, skipping the trie traversal
+        let mut start = request.starting_hash;
+        'snapshot: loop {
+            let snapshot_accounts = store.read_account_snapshot(start)?;
+            let Some(last_hash) = snapshot_accounts.last().map(|(hash, _)| *hash) else {
+                break;
+            };
+            for (hash, account) in snapshot_accounts {
+                if !add_account(hash, account) {
+                    break 'snapshot;
+                }
+            }
+            match next_hash(last_hash) {
+                Some(next) => start = next,
+                None => break,
+            }
+        }
+    } else {
+        for (hash, account) in store.iter_accounts(request.root_hash)? {
+            if !add_account(hash, account) {
+                break;
+            }
         }
     }
     let proof = proof_to_encodable(store.get_account_range_proof(
@@ -40,10 +84,13 @@ pub fn process_account_range_request(
     })
 }
 
-pub fn process_storage_ranges_request(
+pub async fn process_storage_ranges_request(
     request: GetStorageRanges,
     store: Store,
 ) -> Result<StorageRanges, StoreError> {
+    let deadline = Instant::now() + MAX_RESPONSE_TIME;
+    let response_bytes = request.response_bytes.min(MAX_RESPONSE_BYTES);
+    let from_snapshot = snapshot_matches_root(&store, request.root_hash).await?;
     let mut slots = vec![];
     let mut proof = vec![];
     let mut bytes_used = 0;
@@ -51,24 +98,53 @@ pub fn process_storage_ranges_request(
     for hashed_address in request.account_hashes {
         let mut account_slots = vec![];
         let mut res_capped = false;
-
-        if let Some(storage_iter) = store.iter_storage(request.root_hash, hashed_address)? {
-            for (hash, data) in storage_iter {
-                if hash >= request.starting_hash {
-                    bytes_used += 64_u64; // slot size
-                    account_slots.push(StorageSlot { hash, data });
+        // Adds the slot to the response if it is within the requested range
+        // Returns false once the account's range is complete
+        let mut add_slot = |hash: H256, data: U256| {
+            if hash >= request.starting_hash {
+                bytes_used += 64_u64; // slot size
+                account_slots.push(StorageSlot { hash, data });
+            }
+            if hash >= request.limit_hash || bytes_used >= response_bytes {
+                if bytes_used >= response_bytes {
+                    res_capped = true;
                 }
-                if hash >= request.limit_hash || bytes_used >= request.response_bytes {
-                    if bytes_used >= request.response_bytes {
-                        res_capped = true;
+                return false;
+            }
+            if Instant::now() >= deadline {
+                res_capped = true;
+                return false;
+            }
+            true
+        };
+
+        if from_snapshot {
+            let mut start = request.starting_hash;
+            'snapshot: loop {
+                let snapshot_slots = store.read_storage_snapshot(hashed_address, start).await?;
+                let Some(last_hash) = snapshot_slots.last().map(|(hash, _)| *hash) else {
+                    break;
+                };
+                for (hash, data) in snapshot_slots {
+                    if !add_slot(hash, data) {
+                        break 'snapshot;
                     }
+                }
+                match next_hash(last_hash) {
+                    Some(next) => start = next,
+                    None => break,
+                }
+            }
+        } else if let Some(storage_iter) = store.iter_storage(request.root_hash, hashed_address)? {
+            for (hash, data) in storage_iter {
+                if !add_slot(hash, data) {
                     break;
                 }
             }
         }
 
         // Generate proofs only if the response doesn't contain the full storage range for the account
-        // Aka if the starting hash is not zero or if the response was capped due to byte limit
+        // Aka if the starting hash is not zero or if the response was capped due to byte or time limit
         if !request.starting_hash.is_zero() || res_capped && !account_slots.is_empty() {
             proof.extend(proof_to_encodable(
                 store
@@ -86,7 +162,7 @@ pub fn process_storage_ranges_request(
             slots.push(account_slots);
         }
 
-        if bytes_used >= request.response_bytes {
+        if res_capped {
             break;
         }
     }
@@ -101,14 +177,16 @@ pub fn process_byte_codes_request(
     request: GetByteCodes,
     store: Store,
 ) -> Result<ByteCodes, StoreError> {
+    let deadline = Instant::now() + MAX_RESPONSE_TIME;
+    let response_bytes = request.bytes.min(MAX_RESPONSE_BYTES);
     let mut codes = vec![];
     let mut bytes_used = 0;
-    for code_hash in request.hashes {
+    for code_hash in request.hashes.into_iter().take(MAX_CODE_LOOKUPS) {
         if let Some(code) = store.get_account_code(code_hash)? {
             bytes_used += code.len() as u64;
             codes.push(code);
         }
-        if bytes_used >= request.bytes {
+        if bytes_used >= response_bytes || Instant::now() >= deadline {
             break;
         }
     }
@@ -122,9 +200,10 @@ pub fn process_trie_nodes_request(
     request: GetTrieNodes,
     store: Store,
 ) -> Result<TrieNodes, RLPxError> {
+    let deadline = Instant::now() + MAX_RESPONSE_TIME;
     let mut nodes = vec![];
-    let mut remaining_bytes = request.bytes;
-    for paths in request.paths {
+    let mut remaining_bytes = request.bytes.min(MAX_RESPONSE_BYTES);
+    for paths in request.paths.into_iter().take(MAX_TRIE_NODE_LOOKUPS) {
         if paths.is_empty() {
             return Err(RLPxError::BadRequest(
                 "zero-item pathset requested".to_string(),
@@ -138,7 +217,7 @@ pub fn process_trie_nodes_request(
         nodes.extend(trie_nodes.iter().map(|nodes| Bytes::copy_from_slice(nodes)));
         remaining_bytes = remaining_bytes
             .saturating_sub(trie_nodes.iter().fold(0, |acc, nodes| acc + nodes.len()) as u64);
-        if remaining_bytes == 0 {
+        if remaining_bytes == 0 || Instant::now() >= deadline {
             break;
         }
     }
@@ -149,6 +228,106 @@ pub fn process_trie_nodes_request(
     })
 }
 
+/// Returns true if the state and storage snapshots hold the whole state for the given root
+async fn snapshot_matches_root(store: &Store, root_hash: H256) -> Result<bool, StoreError> {
+    Ok(store
+        .get_state_snapshot_root()
+        .await?
+        .is_some_and(|(_, snapshot_root)| snapshot_root == root_hash))
+}
+
+/// Returns the hash right after the given one, or None if it is the highest possible hash
+fn next_hash(hash: H256) -> Option<H256> {
+    hash.into_uint()
+        .checked_add(U256::one())
+        .map(|next| H256::from_uint(&next))
+}
+
+/// Tracks the amount of snap data served to a single peer, so that no peer can monopolize our resources
+#[derive(Debug, Clone)]
+pub struct SnapServingQuota {
+    max_bytes: u64,
+    window: Duration,
+    window_start: Instant,
+    served: u64,
+}
+
+impl Default for SnapServingQuota {
+    fn default() -> Self {
+        Self::new(SNAP_SERVING_QUOTA_BYTES, SNAP_SERVING_QUOTA_WINDOW)
+    }
+}
+
+impl SnapServingQuota {
+    pub fn new(max_bytes: u64, window: Duration) -> Self {
+        Self {
+            max_bytes,
+            window,
+            window_start: Instant::now(),
+            served: 0,
+        }
+    }
+
+    /// Returns the amount of bytes that can be used to answer a request asking for `requested` bytes,
+    /// or None if the peer already used up its quota for the current window
+    pub fn budget(&mut self, requested: u64, now: Instant) -> Option<u64> {
+        if now.duration_since(self.window_start) >= self.window {
+            self.window_start = now;
+            self.served = 0;
+        }
+        let remaining = self.max_bytes.saturating_sub(self.served);
+        (remaining > 0).then_some(requested.min(remaining))
+    }
+
+    /// Registers the amount of bytes served to the peer
+    pub fn consume(&mut self, bytes: u64) {
+        self.served = self.served.saturating_add(bytes);
+    }
+}
+
+/// Amount of data held by a snap response, used to keep track of the serving quotas
+pub(crate) trait ServedBytes {
+    fn served_bytes(&self) -> u64;
+}
+
+impl ServedBytes for AccountRange {
+    fn served_bytes(&self) -> u64 {
+        let accounts = self
+            .accounts
+            .iter()
+            .map(|acc| 32 + acc.account.length() as u64)
+            .sum::<u64>();
+        accounts + bytes_len(&self.proof)
+    }
+}
+
+impl ServedBytes for StorageRanges {
+    fn served_bytes(&self) -> u64 {
+        let slots = self
+            .slots
+            .iter()
+            .map(|slots| slots.len() as u64 * 64)
+            .sum::<u64>();
+        slots + bytes_len(&self.proof)
+    }
+}
+
+impl ServedBytes for ByteCodes {
+    fn served_bytes(&self) -> u64 {
+        bytes_len(&self.codes)
+    }
+}
+
+impl ServedBytes for TrieNodes {
+    fn served_bytes(&self) -> u64 {
+        bytes_len(&self.nodes)
+    }
+}
+
+fn bytes_len(values: &[Bytes]) -> u64 {
+    values.iter().map(|value| value.len() as u64).sum()
+}
+
 // Helper method to convert proof to RLP-encodable format
 #[inline]
 pub(crate) fn proof_to_encodable(proof: Vec<Vec<u8>>) -> Vec<Bytes> {
@@ -168,6 +347,7 @@ mod tests {
     use ethrex_common::{BigEndianHash, H256, types::AccountState};
     use ethrex_rlp::{decode::RLPDecode, encode::RLPEncode};
     use ethrex_storage::EngineType;
+    use ethrex_trie::EMPTY_TRIE_HASH;
 
     use crate::rlpx::snap::AccountStateSlim;
 
@@ -196,8 +376,8 @@ mod tests {
         static ref HASH_FIRST_PLUS_ONE: H256 = H256::from_uint(&((*HASH_FIRST).into_uint() + 1));
     }
 
-    #[test]
-    fn hive_account_range_a() -> Result<(), StoreError> {
+    #[tokio::test]
+    async fn hive_account_range_a() -> Result<(), StoreError> {
         let (store, root) = setup_initial_state()?;
         let request = GetAccountRange {
             id: 0,
@@ -206,7 +386,7 @@ mod tests {
             limit_hash: *HASH_MAX,
             response_bytes: 4000,
         };
-        let res = process_account_range_request(request, store).unwrap();
+        let res = process_account_range_request(request, store).await.unwrap();
         // Check test invariants
         assert_eq!(res.accounts.len(), 86);
         assert_eq!(res.accounts.first().unwrap().hash, *HASH_FIRST);
@@ -218,8 +398,8 @@ mod tests {
         Ok(())
     }
 
-    #[test]
-    fn hive_account_range_b() -> Result<(), StoreError> {
+    #[tokio::test]
+    async fn hive_account_range_b() -> Result<(), StoreError> {
         let (store, root) = setup_initial_state()?;
         let request = GetAccountRange {
             id: 0,
@@ -228,7 +408,7 @@ mod tests {
             limit_hash: *HASH_MAX,
             response_bytes: 3000,
         };
-        let res = process_account_range_request(request, store).unwrap();
+        let res = process_account_range_request(request, store).await.unwrap();
         // Check test invariants
         assert_eq!(res.accounts.len(), 65);
         assert_eq!(res.accounts.first().unwrap().hash, *HASH_FIRST);
@@ -240,8 +420,8 @@ mod tests {
         Ok(())
     }
 
-    #[test]
-    fn hive_account_range_c() -> Result<(), StoreError> {
+    #[tokio::test]
+    async fn hive_account_range_c() -> Result<(), StoreError> {
         let (store, root) = setup_initial_state()?;
         let request = GetAccountRange {
             id: 0,
@@ -250,7 +430,7 @@ mod tests {
             limit_hash: *HASH_MAX,
             response_bytes: 2000,
         };
-        let res = process_account_range_request(request, store).unwrap();
+        let res = process_account_range_request(request, store).await.unwrap();
         // Check test invariants
         assert_eq!(res.accounts.len(), 44);
         assert_eq!(res.accounts.first().unwrap().hash, *HASH_FIRST);
@@ -262,8 +442,8 @@ mod tests {
         Ok(())
     }
 
-    #[test]
-    fn hive_account_range_d() -> Result<(), StoreError> {
+    #[tokio::test]
+    async fn hive_account_range_d() -> Result<(), StoreError> {
         let (store, root) = setup_initial_state()?;
         let request = GetAccountRange {
             id: 0,
@@ -272,7 +452,7 @@ mod tests {
             limit_hash: *HASH_MAX,
             response_bytes: 1,
         };
-        let res = process_account_range_request(request, store).unwrap();
+        let res = process_account_range_request(request, store).await.unwrap();
         // Check test invariants
         assert_eq!(res.accounts.len(), 1);
         assert_eq!(res.accounts.first().unwrap().hash, *HASH_FIRST);
@@ -280,8 +460,8 @@ mod tests {
         Ok(())
     }
 
-    #[test]
-    fn hive_account_range_e() -> Result<(), StoreError> {
+    #[tokio::test]
+    async fn hive_account_range_e() -> Result<(), StoreError> {
         let (store, root) = setup_initial_state()?;
         let request = GetAccountRange {
             id: 0,
@@ -290,7 +470,7 @@ mod tests {
             limit_hash: *HASH_MAX,
             response_bytes: 0,
         };
-        let res = process_account_range_request(request, store).unwrap();
+        let res = process_account_range_request(request, store).await.unwrap();
         // Check test invariants
         assert_eq!(res.accounts.len(), 1);
         assert_eq!(res.accounts.first().unwrap().hash, *HASH_FIRST);
@@ -298,8 +478,8 @@ mod tests {
         Ok(())
     }
 
-    #[test]
-    fn hive_account_range_f() -> Result<(), StoreError> {
+    #[tokio::test]
+    async fn hive_account_range_f() -> Result<(), StoreError> {
         // In this test, we request a range where startingHash is before the first available
         // account key, and limitHash is after. The server should return the first and second
         // account of the state (because the second account is the 'next available').
@@ -311,7 +491,7 @@ mod tests {
             limit_hash: *HASH_FIRST_PLUS_ONE,
             response_bytes: 4000,
         };
-        let res = process_account_range_request(request, store).unwrap();
+        let res = process_account_range_request(request, store).await.unwrap();
         // Check test invariants
         assert_eq!(res.accounts.len(), 2);
         assert_eq!(res.accounts.first().unwrap().hash, *HASH_FIRST);
@@ -319,8 +499,8 @@ mod tests {
         Ok(())
     }
 
-    #[test]
-    fn hive_account_range_g() -> Result<(), StoreError> {
+    #[tokio::test]
+    async fn hive_account_range_g() -> Result<(), StoreError> {
         // Here we request range where both bounds are before the first available account key.
         // This should return the first account (even though it's out of bounds).
         let (store, root) = setup_initial_state()?;
@@ -331,7 +511,7 @@ mod tests {
             limit_hash: *HASH_FIRST_MINUS_450,
             response_bytes: 4000,
         };
-        let res = process_account_range_request(request, store).unwrap();
+        let res = process_account_range_request(request, store).await.unwrap();
         // Check test invariants
         assert_eq!(res.accounts.len(), 1);
         assert_eq!(res.accounts.first().unwrap().hash, *HASH_FIRST);
@@ -339,8 +519,8 @@ mod tests {
         Ok(())
     }
 
-    #[test]
-    fn hive_account_range_h() -> Result<(), StoreError> {
+    #[tokio::test]
+    async fn hive_account_range_h() -> Result<(), StoreError> {
         // In this test, both startingHash and limitHash are zero.
         // The server should return the first available account.
         let (store, root) = setup_initial_state()?;
@@ -351,7 +531,7 @@ mod tests {
             limit_hash: *HASH_MIN,
             response_bytes: 4000,
         };
-        let res = process_account_range_request(request, store).unwrap();
+        let res = process_account_range_request(request, store).await.unwrap();
         // Check test invariants
         assert_eq!(res.accounts.len(), 1);
         assert_eq!(res.accounts.first().unwrap().hash, *HASH_FIRST);
@@ -359,8 +539,8 @@ mod tests {
         Ok(())
     }
 
-    #[test]
-    fn hive_account_range_i() -> Result<(), StoreError> {
+    #[tokio::test]
+    async fn hive_account_range_i() -> Result<(), StoreError> {
         let (store, root) = setup_initial_state()?;
         let request = GetAccountRange {
             id: 0,
@@ -369,7 +549,7 @@ mod tests {
             limit_hash: *HASH_MAX,
             response_bytes: 4000,
         };
-        let res = process_account_range_request(request, store).unwrap();
+        let res = process_account_range_request(request, store).await.unwrap();
         // Check test invariants
         assert_eq!(res.accounts.len(), 86);
         assert_eq!(res.accounts.first().unwrap().hash, *HASH_FIRST);
@@ -381,8 +561,8 @@ mod tests {
         Ok(())
     }
 
-    #[test]
-    fn hive_account_range_j() -> Result<(), StoreError> {
+    #[tokio::test]
+    async fn hive_account_range_j() -> Result<(), StoreError> {
         let (store, root) = setup_initial_state()?;
         let request = GetAccountRange {
             id: 0,
@@ -391,7 +571,7 @@ mod tests {
             limit_hash: *HASH_MAX,
             response_bytes: 4000,
         };
-        let res = process_account_range_request(request, store).unwrap();
+        let res = process_account_range_request(request, store).await.unwrap();
         // Check test invariants
         assert_eq!(res.accounts.len(), 86);
         assert_eq!(res.accounts.first().unwrap().hash, *HASH_SECOND);
@@ -407,8 +587,8 @@ mod tests {
 
     // Non-sensical requests
 
-    #[test]
-    fn hive_account_range_k() -> Result<(), StoreError> {
+    #[tokio::test]
+    async fn hive_account_range_k() -> Result<(), StoreError> {
         // In this test, the startingHash is the first available key, and limitHash is
         // a key before startingHash (wrong order). The server should return the first available key.
         let (store, root) = setup_initial_state()?;
@@ -419,7 +599,7 @@ mod tests {
             limit_hash: *HASH_FIRST_MINUS_ONE,
             response_bytes: 4000,
         };
-        let res = process_account_range_request(request, store).unwrap();
+        let res = process_account_range_request(request, store).await.unwrap();
         // Check test invariants
         assert_eq!(res.accounts.len(), 1);
         assert_eq!(res.accounts.first().unwrap().hash, *HASH_FIRST);
@@ -427,8 +607,8 @@ mod tests {
         Ok(())
     }
 
-    #[test]
-    fn hive_account_range_m() -> Result<(), StoreError> {
+    #[tokio::test]
+    async fn hive_account_range_m() -> Result<(), StoreError> {
         // In this test, the startingHash is the first available key and limitHash is zero.
         // (wrong order). The server should return the first available key.
         let (store, root) = setup_initial_state()?;
@@ -439,7 +619,7 @@ mod tests {
             limit_hash: *HASH_MIN,
             response_bytes: 4000,
         };
-        let res = process_account_range_request(request, store).unwrap();
+        let res = process_account_range_request(request, store).await.unwrap();
         // Check test invariants
         assert_eq!(res.accounts.len(), 1);
         assert_eq!(res.accounts.first().unwrap().hash, *HASH_FIRST);
@@ -447,6 +627,213 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn serving_quota_is_enforced_per_window() {
+        let start = Instant::now();
+        let mut quota = SnapServingQuota::new(1000, Duration::from_secs(10));
+        quota.window_start = start;
+
+        assert_eq!(quota.budget(600, start), Some(600));
+        quota.consume(600);
+        // Only the remaining quota can be used
+        assert_eq!(quota.budget(600, start), Some(400));
+        quota.consume(400);
+        assert_eq!(quota.budget(600, start + Duration::from_secs(1)), None);
+        // The quota is restored once the window is over
+        assert_eq!(
+            quota.budget(600, start + Duration::from_secs(10)),
+            Some(600)
+        );
+    }
+
+    #[test]
+    fn serving_quota_runs_out_until_the_window_resets() {
+        let start = Instant::now();
+        let mut quota = SnapServingQuota::new(1000, Duration::from_secs(10));
+        quota.window_start = start;
+
+        // Responses can go over the budget, the quota is used up all the same
+        assert_eq!(quota.budget(1000, start), Some(1000));
+        quota.consume(1500);
+        assert_eq!(quota.budget(1, start), None);
+        assert_eq!(quota.budget(1, start + Duration::from_millis(9999)), None);
+        // The new window starts from scratch, the excess isn't carried over
+        let reset = start + Duration::from_secs(10);
+        assert_eq!(quota.budget(2000, reset), Some(1000));
+        quota.consume(1000);
+        assert_eq!(quota.budget(1, reset + Duration::from_secs(5)), None);
+        assert_eq!(quota.budget(1, reset + Duration::from_secs(10)), Some(1));
+    }
+
+    #[tokio::test]
+    async fn snapshot_is_only_served_for_its_root() -> Result<(), StoreError> {
+        let (store, root) = setup_initial_state()?;
+        assert!(!snapshot_matches_root(&store, root).await?);
+        write_snapshot(&store, root).await?;
+        assert!(snapshot_matches_root(&store, root).await?);
+
+        // A snapshot of another state doesn't answer requests for this one
+        let other_root = H256::repeat_byte(1);
+        store.set_state_snapshot_root(0, other_root).await?;
+        assert!(!snapshot_matches_root(&store, root).await?);
+        store
+            .write_snapshot_account_batch(
+                vec![*HASH_FIRST_MINUS_ONE],
+                vec![AccountState::default()],
+            )
+            .await?;
+        let request = GetAccountRange {
+            id: 0,
+            root_hash: root,
+            starting_hash: *HASH_MIN,
+            limit_hash: *HASH_MAX,
+            response_bytes: 4000,
+        };
+        let res = process_account_range_request(request, store).await.unwrap();
+        assert_eq!(res.accounts.len(), 86);
+        assert_eq!(res.accounts.first().unwrap().hash, *HASH_FIRST);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn account_range_from_snapshot_equals_trie_response() -> Result<(), StoreError> {
+        let (store, root) = setup_initial_state()?;
+        for request in [
+            (*HASH_MIN, *HASH_MAX, 4000),
+            (*HASH_FIRST_PLUS_ONE, *HASH_MAX, 100),
+            (*HASH_MIN, *HASH_SECOND, 4000),
+            (*HASH_MAX, *HASH_MAX, 4000),
+        ]
+        .map(
+            |(starting_hash, limit_hash, response_bytes)| GetAccountRange {
+                id: 0,
+                root_hash: root,
+                starting_hash,
+                limit_hash,
+                response_bytes,
+            },
+        ) {
+            let from_trie = process_account_range_request(request.clone(), store.clone())
+                .await
+                .unwrap();
+            write_snapshot(&store, root).await?;
+            let from_snapshot = process_account_range_request(request, store.clone())
+                .await
+                .unwrap();
+            store.clear_snapshot().await?;
+            assert_eq!(
+                from_snapshot
+                    .accounts
+                    .iter()
+                    .map(|acc| (acc.hash, acc.account.encode_to_vec()))
+                    .collect::<Vec<_>>(),
+                from_trie
+                    .accounts
+                    .iter()
+                    .map(|acc| (acc.hash, acc.account.encode_to_vec()))
+                    .collect::<Vec<_>>()
+            );
+            assert_eq!(from_snapshot.proof, from_trie.proof);
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn storage_ranges_from_snapshot_equal_trie_response() -> Result<(), StoreError> {
+        let (store, root, account_hashes) = setup_storage_state()?;
+        let middle_slot = storage_slot_key(STORAGE_SLOTS / 2);
+        for (starting_hash, limit_hash, response_bytes) in [
+            // Whole storages, no proofs needed
+            (*HASH_MIN, *HASH_MAX, 100_000),
+            // Capped by the response size
+            (*HASH_MIN, *HASH_MAX, 64 * 10),
+            // Partial ranges
+            (middle_slot, *HASH_MAX, 100_000),
+            (*HASH_MIN, middle_slot, 100_000),
+        ] {
+            let request = GetStorageRanges {
+                id: 0,
+                root_hash: root,
+                account_hashes: account_hashes.clone(),
+                starting_hash,
+                limit_hash,
+                response_bytes,
+            };
+            let from_trie = process_storage_ranges_request(request.clone(), store.clone()).await?;
+            write_snapshot(&store, root).await?;
+            let from_snapshot = process_storage_ranges_request(request, store.clone()).await?;
+            store.clear_snapshot().await?;
+            let slots = |res: &StorageRanges| {
+                res.slots
+                    .iter()
+                    .map(|slots| {
+                        slots
+                            .iter()
+                            .map(|slot| (slot.hash, slot.data))
+                            .collect::<Vec<_>>()
+                    })
+                    .collect::<Vec<_>>()
+            };
+            assert!(!slots(&from_trie).is_empty());
+            assert_eq!(slots(&from_snapshot), slots(&from_trie));
+            assert_eq!(from_snapshot.proof, from_trie.proof);
+        }
+        Ok(())
+    }
+
+    /// Copies the whole state of the given root to the snapshots and marks it as the snapshot's root
+    async fn write_snapshot(store: &Store, root: H256) -> Result<(), StoreError> {
+        let (hashes, accounts): (Vec<_>, Vec<_>) = store.iter_accounts(root)?.unzip();
+        for hash in hashes.iter() {
+            if let Some(storage) = store.iter_storage(root, *hash)? {
+                let (keys, values): (Vec<_>, Vec<_>) = storage.unzip();
+                store
+                    .write_snapshot_storage_batch(*hash, keys, values)
+                    .await?;
+            }
+        }
+        store.write_snapshot_account_batch(hashes, accounts).await?;
+        store.set_state_snapshot_root(0, root).await
+    }
+
+    const STORAGE_SLOTS: u64 = 50;
+
+    fn storage_slot_key(index: u64) -> H256 {
+        keccak_hash::keccak(index.to_be_bytes())
+    }
+
+    /// Builds a state with a few accounts, each with its own storage
+    fn setup_storage_state() -> Result<(Store, H256, Vec<H256>), StoreError> {
+        let store = Store::new("null", EngineType::InMemory).unwrap();
+        let mut state_trie = store.new_state_trie_for_test()?;
+        let mut account_hashes = vec![];
+        for account in 1..=3_u64 {
+            let account_hash = keccak_hash::keccak(account.to_be_bytes());
+            let mut storage_trie = store.open_storage_trie(account_hash, *EMPTY_TRIE_HASH)?;
+            for slot in 0..STORAGE_SLOTS {
+                storage_trie
+                    .insert(
+                        storage_slot_key(slot).as_bytes().to_vec(),
+                        U256::from(account * 1000 + slot).encode_to_vec(),
+                    )
+                    .unwrap();
+            }
+            let account_state = AccountState {
+                storage_root: storage_trie.hash().unwrap(),
+                ..Default::default()
+            };
+            state_trie
+                .insert(
+                    account_hash.as_bytes().to_vec(),
+                    account_state.encode_to_vec(),
+                )
+                .unwrap();
+            account_hashes.push(account_hash);
+        }
+        account_hashes.sort();
+        Ok((store, state_trie.hash().unwrap(), account_hashes))
+    }
+
     // Initial state setup for hive snap tests
 
     fn setup_initial_state() -> Result<(Store, H256), StoreError> {
@@ -1,5 +1,6 @@
 mod bytecode_fetcher;
 mod fetcher_queue;
+mod skeleton;
 mod state_healing;
 mod state_sync;
 mod storage_fetcher;
@@ -11,11 +12,12 @@ use bytecode_fetcher::bytecode_fetcher;
 use ethrex_blockchain::{BatchBlockProcessingFailure, Blockchain, error::ChainError};
 use ethrex_common::{
     BigEndianHash, H256, U256, U512,
-    types::{Block, BlockHash, BlockHeader},
+    types::{Block, BlockHash, BlockHeader, BlockNumber},
 };
 use ethrex_rlp::error::RLPDecodeError;
 use ethrex_storage::{EngineType, STATE_TRIE_SEGMENTS, Store, error::StoreError};
 use ethrex_trie::{Nibbles, Node, TrieDB, TrieError};
+use skeleton::fill_skeleton;
 use state_healing::heal_state_trie;
 use state_sync::state_sync;
 use std::{
@@ -53,6 +55,9 @@ const MAX_CHANNEL_READS: usize = 200;
 const SHOW_PROGRESS_INTERVAL_DURATION: Duration = Duration::from_secs(30);
 /// Amount of blocks to execute in a single batch during FullSync
 const EXECUTE_BATCH_SIZE_DEFAULT: usize = 1024;
+/// Amount of blocks behind the head after which the snapshot kept from a snap sync is dropped
+/// Peers only request recent states when snap syncing, so older snapshots won't be used to serve them
+const SNAPSHOT_SERVING_DEPTH: u64 = 128;
 
 #[cfg(feature = "sync-test")]
 lazy_static::lazy_static! {
@@ -99,6 +104,8 @@ pub struct Syncer {
     // Used for cancelling long-living tasks upon shutdown
     cancel_token: CancellationToken,
     blockchain: Arc<Blockchain>,
+    /// If enabled, headers are downloaded backwards from the sync head and only processed once they link with the local chain
+    skeleton_sync: bool,
 }
 
 impl Syncer {
@@ -107,6 +114,7 @@ impl Syncer {
         snap_enabled: Arc<AtomicBool>,
         cancel_token: CancellationToken,
         blockchain: Arc<Blockchain>,
+        skeleton_sync: bool,
     ) -> Self {
         Self {
             snap_enabled,
@@ -115,6 +123,7 @@ impl Syncer {
             trie_rebuilder: None,
             cancel_token,
             blockchain,
+            skeleton_sync,
         }
     }
 
@@ -131,6 +140,7 @@ impl Syncer {
             blockchain: Arc::new(Blockchain::default_with_store(
                 Store::new("", EngineType::InMemory).expect("Failed to start Sotre Engine"),
             )),
+            skeleton_sync: false,
         }
     }
 
@@ -182,15 +192,34 @@ impl Syncer {
             Ok(res) => res,
             Err(e) => return Err(e.into()),
         };
+        // In skeleton mode all headers up to the sync head are downloaded and linked to the local chain before processing them
+        let mut skeleton = None;
+        if self.skeleton_sync {
+            let Some(filled_skeleton) =
+                fill_skeleton(sync_head, current_head, &store, &self.peers).await?
+            else {
+                return Ok(());
+            };
+            if filled_skeleton.is_empty() {
+                info!("Sync head is already part of the local chain");
+                store.clear_skeleton_sync_progress().await?;
+                return Ok(());
+            }
+            skeleton = Some(filled_skeleton);
+        }
 
         loop {
             debug!("Requesting Block Headers from {current_head}");
 
-            let Some(mut block_headers) = self
-                .peers
-                .request_block_headers(current_head, BlockRequestOrder::OldToNew)
-                .await
-            else {
+            let block_headers = match skeleton.as_mut() {
+                Some(skeleton) => skeleton.next_batch()?,
+                None => {
+                    self.peers
+                        .request_block_headers(current_head, BlockRequestOrder::OldToNew)
+                        .await
+                }
+            };
+            let Some(mut block_headers) = block_headers else {
                 warn!("Sync failed to find target block header, aborting");
                 return Ok(());
             };
@@ -276,6 +305,10 @@ impl Syncer {
                 break;
             };
         }
+        if self.skeleton_sync {
+            // All the skeleton headers were processed
+            store.clear_skeleton_sync_progress().await?;
+        }
         match sync_mode {
             SyncMode::Snap => {
                 // snap-sync: launch tasks to fetch blocks and state in parallel
@@ -298,7 +331,7 @@ impl Syncer {
                 ));
                 // Perform snap sync
                 if !self
-                    .snap_sync(pivot_header.state_root, store.clone())
+                    .snap_sync(pivot_header.state_root, pivot_header.number, store.clone())
                     .await?
                 {
                     // Snap sync was not completed, abort and resume it on the next cycle
@@ -328,6 +361,7 @@ impl Syncer {
             // Full sync stores and executes blocks as it asks for the headers
             SyncMode::Full => {}
         }
+        prune_stale_snapshot(&store).await?;
         Ok(())
     }
 
@@ -362,6 +396,18 @@ impl Syncer {
     }
 }
 
+/// Drops the snapshot kept from a previous snap sync once its state is too old to be requested by peers
+async fn prune_stale_snapshot(store: &Store) -> Result<(), SyncError> {
+    if let Some((snapshot_block, _)) = store.get_state_snapshot_root().await? {
+        let latest_block = store.get_latest_block_number().await?;
+        if latest_block.saturating_sub(snapshot_block) > SNAPSHOT_SERVING_DEPTH {
+            debug!("Dropping snapshot of block {snapshot_block}");
+            store.clear_snapshot().await?;
+        }
+    }
+    Ok(())
+}
+
 /// Fetches all block bodies for the given block hashes via p2p and stores them
 async fn store_block_bodies(
     mut block_hashes: Vec<BlockHash>,
@@ -688,7 +734,18 @@ impl Syncer {
     // Returns the success status, if it is true, then the state is fully consistent and
     // new blocks can be executed on top of it, if false then the state is still inconsistent and
     // snap sync must be resumed on the next sync cycle
-    async fn snap_sync(&mut self, state_root: H256, store: Store) -> Result<bool, SyncError> {
+    async fn snap_sync(
+        &mut self,
+        state_root: H256,
+        pivot_number: BlockNumber,
+        store: Store,
+    ) -> Result<bool, SyncError> {
+        // Retrieve storage data to check which snap sync phase we are in
+        let key_checkpoints = store.get_state_trie_key_checkpoint().await?;
+        if key_checkpoints.is_none() {
+            // Starting a new state sync, drop the snapshot kept from a previous one (if any)
+            store.clear_snapshot().await?;
+        }
         // Begin the background trie rebuild process if it is not active yet or if it crashed
         if !self
             .trie_rebuilder
@@ -712,9 +769,9 @@ impl Syncer {
             storage_healer_cancell_token.clone(),
             state_healing_ended.clone(),
         ));
+        // Whether the snapshot may hold the whole pivot state, confirmed once healing finds nothing to fetch
+        let mut snapshot_downloaded = false;
         // Perform state sync if it was not already completed on a previous cycle
-        // Retrieve storage data to check which snap sync phase we are in
-        let key_checkpoints = store.get_state_trie_key_checkpoint().await?;
         // If we have no key checkpoints or if the key checkpoints are lower than the segment boundaries we are in state sync phase
         if key_checkpoints.is_none()
             || key_checkpoints.is_some_and(|ch| {
@@ -744,6 +801,8 @@ impl Syncer {
                 storage_healer_handler.await??;
                 return Ok(false);
             }
+            // The whole state was downloaded in this cycle without the pivot going stale
+            snapshot_downloaded = key_checkpoints.is_none();
         }
         // Wait for the trie rebuilder to finish
         info!("Waiting for the trie rebuild to finish");
@@ -758,11 +817,12 @@ impl Syncer {
             "State trie rebuilt from snapshot, overtime: {}",
             rebuild_start.elapsed().as_secs()
         );
-        // Clear snapshot
-        store.clear_snapshot().await?;
+        if !snapshot_downloaded {
+            store.clear_snapshot().await?;
+        }
 
         // Perform Healing
-        let state_heal_complete =
+        let (state_heal_complete, state_healed) =
             heal_state_trie(state_root, store.clone(), self.peers.clone()).await?;
         // Wait for storage healer to end
         if state_heal_complete {
@@ -770,11 +830,20 @@ impl Syncer {
         } else {
             storage_healer_cancell_token.cancel();
         }
-        let storage_heal_complete = storage_healer_handler.await??;
-        if !(state_heal_complete && storage_heal_complete) {
+        let (storage_heal_complete, storage_healed) = storage_healer_handler.await??;
+        let heal_complete = state_heal_complete && storage_heal_complete;
+        if !heal_complete {
             warn!("Stale pivot, aborting healing");
         }
-        Ok(state_heal_complete && storage_heal_complete)
+        // Healed nodes never reach the snapshot, so it only holds the whole pivot state if nothing was healed
+        if heal_complete && snapshot_downloaded && !(state_healed || storage_healed) {
+            store
+                .set_state_snapshot_root(pivot_number, state_root)
+                .await?;
+        } else {
+            store.clear_snapshot().await?;
+        }
+        Ok(heal_complete)
     }
 }
 
@@ -840,6 +909,8 @@ enum SyncError {
     NoLatestCanonical,
     #[error("Range received is invalid")]
     InvalidRangeReceived,
+    #[error("Header skeleton reached genesis without linking to the local chain")]
+    SkeletonReachedGenesis,
 }
 
 impl<T> From<SendError<T>> for SyncError {
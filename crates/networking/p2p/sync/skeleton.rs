@@ -0,0 +1,463 @@
+//! This module contains the logic for the header skeleton used by beacon sync
+//! Instead of downloading headers forward from our current head and trusting whichever chain peers serve us,
+//! headers are downloaded backwards from the head given by the consensus layer via `engine_forkchoiceUpdated` or `engine_newPayload`
+//! Each batch must link by hash to the last accepted header, so peers can't make us store a chain the consensus layer didn't choose
+//! The head and tail of the skeleton are persisted, so an interrupted download can be resumed after a restart
+//! Once the skeleton reaches a block of our local chain the gap is closed and the headers are processed from oldest to newest
+
+use ethrex_common::{
+    H256,
+    types::{BlockHash, BlockHeader, BlockNumber},
+};
+use ethrex_storage::Store;
+use tokio::time::Instant;
+use tracing::{debug, info, warn};
+
+use crate::{
+    peer_handler::{BlockRequestOrder, PeerHandler},
+    rlpx::eth::blocks::BLOCK_HEADER_LIMIT,
+};
+
+use super::{SHOW_PROGRESS_INTERVAL_DURATION, SyncError};
+
+/// Headers of a completed skeleton, ready to be processed from oldest to newest
+/// Only the last header of each batch is kept in memory, the batches are read backwards from the store when needed
+pub(crate) struct HeaderSkeleton {
+    /// Hashes of the last header of each batch, ordered from newest to oldest
+    batch_ends: Vec<BlockHash>,
+    /// Number of the first header of the next batch
+    /// Initially the local block the skeleton links to
+    next_start: BlockNumber,
+    store: Store,
+}
+
+impl HeaderSkeleton {
+    /// Returns true if the skeleton has no headers missing from the local chain
+    pub fn is_empty(&self) -> bool {
+        self.batch_ends.is_empty()
+    }
+
+    /// Returns the next batch of headers, or None if all of them were already returned
+    /// Each batch begins with the last header of the previous one (or with the local block the skeleton links to)
+    /// so they have the same shape as the response to an OldToNew header request
+    pub fn next_batch(&mut self) -> Result<Option<Vec<BlockHeader>>, SyncError> {
+        let Some(batch_end) = self.batch_ends.pop() else {
+            return Ok(None);
+        };
+        let mut headers = Vec::new();
+        let mut current = self
+            .store
+            .get_block_header_by_hash(batch_end)?
+            .ok_or(SyncError::CorruptDB)?;
+        let end_number = current.number;
+        while current.number > self.next_start {
+            let parent_hash = current.parent_hash;
+            headers.push(current);
+            current = self
+                .store
+                .get_block_header_by_hash(parent_hash)?
+                .ok_or(SyncError::CorruptDB)?;
+        }
+        if current.number != self.next_start {
+            return Err(SyncError::CorruptDB);
+        }
+        headers.push(current);
+        headers.reverse();
+        self.next_start = end_number;
+        Ok(Some(headers))
+    }
+}
+
+/// Downloads headers backwards from `sync_head` until they link with the local chain
+/// The local chain is made up of the canonical blocks and `anchor`, the block a forward sync would resume from
+/// If a skeleton from a previous cycle was persisted it will be resumed, extended or merged with the new one
+/// Returns None if the skeleton could not be completed during this cycle, its progress is kept for the next one
+pub(crate) async fn fill_skeleton(
+    sync_head: H256,
+    anchor: H256,
+    store: &Store,
+    peers: &PeerHandler,
+) -> Result<Option<HeaderSkeleton>, SyncError> {
+    let Some(head) = fetch_head_header(sync_head, store, peers).await? else {
+        warn!("[SKELETON] Failed to obtain the sync head header {sync_head:#x}, aborting");
+        return Ok(None);
+    };
+    if is_linked(store, anchor, sync_head, head.number).await? {
+        return Ok(Some(HeaderSkeleton {
+            batch_ends: Vec::new(),
+            next_start: head.number,
+            store: store.clone(),
+        }));
+    }
+    store.add_block_header(sync_head, head.clone()).await?;
+
+    // Check if we can reuse the skeleton left by a previous cycle
+    let mut tail = head.clone();
+    let mut subchain_to_merge = None;
+    match store.get_skeleton_sync_progress().await? {
+        Some((old_head, old_tail)) if old_head == sync_head || old_head == head.parent_hash => {
+            if let Some(old_tail) = store.get_block_header_by_hash(old_tail)? {
+                info!(
+                    "[SKELETON] Resuming skeleton sync from block {}",
+                    old_tail.number
+                );
+                tail = old_tail;
+            }
+        }
+        // The new head is not a direct continuation of the old skeleton, but we may meet it while filling the new one
+        Some(old_skeleton) => subchain_to_merge = Some(old_skeleton),
+        None => {}
+    }
+    store
+        .set_skeleton_sync_progress(sync_head, tail.hash())
+        .await?;
+
+    let mut last_progress_shown = Instant::now();
+    loop {
+        let (next_hash, next_number) = (tail.parent_hash, tail.number.saturating_sub(1));
+        if tail.number == 0 {
+            return Err(SyncError::SkeletonReachedGenesis);
+        }
+        if is_linked(store, anchor, next_hash, next_number).await? {
+            break;
+        }
+        if let Some((old_head, old_tail)) = subchain_to_merge {
+            if old_head == next_hash {
+                subchain_to_merge = None;
+                if let Some(old_tail) = store.get_block_header_by_hash(old_tail)? {
+                    debug!(
+                        "[SKELETON] Merged with previous skeleton at block {next_number}, jumping to block {}",
+                        old_tail.number
+                    );
+                    tail = old_tail;
+                    store
+                        .set_skeleton_sync_progress(sync_head, tail.hash())
+                        .await?;
+                    continue;
+                }
+            }
+        }
+
+        let Some(block_headers) = peers
+            .request_block_headers(next_hash, BlockRequestOrder::NewToOld)
+            .await
+        else {
+            warn!("[SKELETON] Failed to fetch headers for block {next_number}, aborting");
+            return Ok(None);
+        };
+        if let Err(reason) = verify_skeleton_batch(next_hash, next_number, &block_headers) {
+            warn!("[SKELETON] Received invalid headers: {reason}, aborting");
+            return Ok(None);
+        }
+
+        // Only keep the headers we don't have yet, stopping once we meet the local chain or the old skeleton
+        let mut new_headers = Vec::with_capacity(block_headers.len());
+        for header in block_headers {
+            let hash = header.hash();
+            if is_linked(store, anchor, hash, header.number).await?
+                || subchain_to_merge.is_some_and(|(old_head, _)| old_head == hash)
+            {
+                break;
+            }
+            new_headers.push(header);
+        }
+        let Some(new_tail) = new_headers.last().cloned() else {
+            continue;
+        };
+        debug!(
+            "[SKELETON] Stored {} headers, new tail at block {}",
+            new_headers.len(),
+            new_tail.number
+        );
+        store.add_block_headers(new_headers).await?;
+        tail = new_tail;
+        store
+            .set_skeleton_sync_progress(sync_head, tail.hash())
+            .await?;
+
+        if last_progress_shown.elapsed() >= SHOW_PROGRESS_INTERVAL_DURATION {
+            info!(
+                "[SKELETON] Downloaded headers from block {} down to block {}",
+                head.number, tail.number
+            );
+            last_progress_shown = Instant::now();
+        }
+    }
+
+    // The gap is closed, find the first skeleton header that is not part of the local chain yet
+    // Headers processed in a previous cycle may have already become canonical, so we stop at the first local block
+    // Batches are split from the head backwards, so only the oldest one can be shorter
+    let mut batch_ends = Vec::new();
+    let mut current = head.clone();
+    loop {
+        let hash = current.hash();
+        if is_linked(store, anchor, hash, current.number).await? {
+            break;
+        }
+        if (head.number - current.number) % BLOCK_HEADER_LIMIT == 0 {
+            batch_ends.push(hash);
+        }
+        current = store
+            .get_block_header_by_hash(current.parent_hash)?
+            .ok_or(SyncError::CorruptDB)?;
+    }
+    info!(
+        "[SKELETON] Skeleton linked to the local chain at block {}, {} headers to process",
+        current.number,
+        head.number - current.number
+    );
+    Ok(Some(HeaderSkeleton {
+        batch_ends,
+        next_start: current.number,
+        store: store.clone(),
+    }))
+}
+
+/// Obtains the header of the sync head, either from the payload received via `engine_newPayload`,
+/// from the store or from our peers
+async fn fetch_head_header(
+    sync_head: H256,
+    store: &Store,
+    peers: &PeerHandler,
+) -> Result<Option<BlockHeader>, SyncError> {
+    if let Some(block) = store.get_pending_block(sync_head).await? {
+        return Ok(Some(block.header));
+    }
+    if let Some(header) = store.get_block_header_by_hash(sync_head)? {
+        return Ok(Some(header));
+    }
+    let Some(block_headers) = peers
+        .request_block_headers(sync_head, BlockRequestOrder::NewToOld)
+        .await
+    else {
+        return Ok(None);
+    };
+    // Peers are not trusted, make sure the header is the one the consensus layer asked for
+    Ok(block_headers
+        .into_iter()
+        .next()
+        .filter(|header| header.hash() == sync_head))
+}
+
+/// Returns true if the block is part of the local chain
+async fn is_linked(
+    store: &Store,
+    anchor: H256,
+    block_hash: BlockHash,
+    block_number: BlockNumber,
+) -> Result<bool, SyncError> {
+    Ok(block_hash == anchor
+        || store.get_canonical_block_hash(block_number).await? == Some(block_hash))
+}
+
+/// Checks that a batch of headers received in NewToOld order begins with the expected header and that each header
+/// is the parent of the previous one
+fn verify_skeleton_batch(
+    expected_hash: BlockHash,
+    expected_number: BlockNumber,
+    block_headers: &[BlockHeader],
+) -> Result<(), &'static str> {
+    let first = block_headers.first().ok_or("empty batch")?;
+    if first.hash() != expected_hash || first.number != expected_number {
+        return Err("batch doesn't start at the skeleton tail");
+    }
+    if !block_headers.windows(2).all(|headers| {
+        headers[0].parent_hash == headers[1].hash() && headers[0].number == headers[1].number + 1
+    }) {
+        return Err("headers are not chained");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethrex_storage::EngineType;
+
+    const CANONICAL_HEAD: BlockNumber = 10;
+    const SKELETON_HEAD: BlockNumber = 2100;
+
+    fn chain(len: u64) -> Vec<BlockHeader> {
+        let mut headers: Vec<BlockHeader> = Vec::new();
+        for number in 0..len {
+            let parent_hash = headers.last().map(|h| h.hash()).unwrap_or_default();
+            headers.push(BlockHeader {
+                number,
+                parent_hash,
+                ..Default::default()
+            });
+        }
+        headers.reverse();
+        headers
+    }
+
+    #[test]
+    fn accepts_linked_batch() {
+        let headers = chain(5);
+        assert!(verify_skeleton_batch(headers[0].hash(), 4, &headers).is_ok());
+    }
+
+    #[test]
+    fn rejects_batch_not_starting_at_tail() {
+        let headers = chain(5);
+        assert!(verify_skeleton_batch(H256::random(), 4, &headers).is_err());
+        assert!(verify_skeleton_batch(headers[0].hash(), 3, &headers).is_err());
+    }
+
+    #[test]
+    fn rejects_unchained_batch() {
+        let mut headers = chain(5);
+        headers[3] = BlockHeader {
+            number: 1,
+            parent_hash: H256::random(),
+            ..Default::default()
+        };
+        assert!(verify_skeleton_batch(headers[0].hash(), 4, &headers).is_err());
+    }
+
+    /// Returns the headers following `parent`, ordered from oldest to newest
+    fn extend(parent: &BlockHeader, len: u64) -> Vec<BlockHeader> {
+        let mut headers: Vec<BlockHeader> = Vec::new();
+        for number in parent.number + 1..=parent.number + len {
+            let parent_hash = headers.last().unwrap_or(parent).hash();
+            headers.push(BlockHeader {
+                number,
+                parent_hash,
+                ..Default::default()
+            });
+        }
+        headers
+    }
+
+    /// Store with a canonical chain up to `CANONICAL_HEAD`, returns it along with the canonical
+    /// headers and the skeleton headers built on top of them, up to `SKELETON_HEAD`
+    async fn setup_store() -> (Store, Vec<BlockHeader>, Vec<BlockHeader>) {
+        let store = Store::new("", EngineType::InMemory).unwrap();
+        let mut canonical = chain(CANONICAL_HEAD + 1);
+        canonical.reverse();
+        let head = canonical.last().unwrap().clone();
+        store.add_block_headers(canonical.clone()).await.unwrap();
+        store
+            .forkchoice_update(
+                Some(canonical.iter().map(|h| (h.number, h.hash())).collect()),
+                head.number,
+                head.hash(),
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        let skeleton = extend(&head, SKELETON_HEAD - CANONICAL_HEAD);
+        (store, canonical, skeleton)
+    }
+
+    /// Returns the hashes of every header of the skeleton, checking the shape of each batch
+    fn read_batches(mut skeleton: HeaderSkeleton) -> Vec<BlockHash> {
+        let mut hashes: Vec<BlockHash> = Vec::new();
+        while let Some(batch) = skeleton.next_batch().unwrap() {
+            assert!(batch.len() as u64 <= BLOCK_HEADER_LIMIT + 1);
+            // Each batch begins with the last header of the previous one
+            let first = batch.first().unwrap();
+            if let Some(last) = hashes.last() {
+                assert_eq!(first.hash(), *last);
+            } else {
+                assert_eq!(first.number, CANONICAL_HEAD);
+            }
+            hashes.extend(batch.iter().skip(1).map(BlockHeader::hash));
+        }
+        hashes
+    }
+
+    fn hashes(headers: &[BlockHeader]) -> Vec<BlockHash> {
+        headers.iter().map(BlockHeader::hash).collect()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn interrupted_skeleton_is_resumed() {
+        let (store, canonical, skeleton) = setup_store().await;
+        let anchor = canonical.last().unwrap().hash();
+        let head = skeleton.last().unwrap().hash();
+        let peers = PeerHandler::dummy();
+
+        // A previous cycle downloaded the newest headers before running out of peers
+        let downloaded = skeleton.len() / 2;
+        let old_tail = skeleton[downloaded].hash();
+        store
+            .add_block_headers(skeleton[downloaded..].to_vec())
+            .await
+            .unwrap();
+        store
+            .set_skeleton_sync_progress(head, old_tail)
+            .await
+            .unwrap();
+        assert!(
+            fill_skeleton(head, anchor, &store, &peers)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert_eq!(
+            store.get_skeleton_sync_progress().await.unwrap(),
+            Some((head, old_tail))
+        );
+
+        // The next cycle downloads the rest and links with the local chain
+        store
+            .add_block_headers(skeleton[..downloaded].to_vec())
+            .await
+            .unwrap();
+        store
+            .set_skeleton_sync_progress(head, skeleton[0].hash())
+            .await
+            .unwrap();
+        let filled = fill_skeleton(head, anchor, &store, &peers)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!filled.is_empty());
+        assert_eq!(read_batches(filled), hashes(&skeleton));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn new_head_extends_skeleton() {
+        let (store, canonical, mut skeleton) = setup_store().await;
+        let anchor = canonical.last().unwrap().hash();
+        let peers = PeerHandler::dummy();
+        store.add_block_headers(skeleton.clone()).await.unwrap();
+        store
+            .set_skeleton_sync_progress(skeleton.last().unwrap().hash(), skeleton[0].hash())
+            .await
+            .unwrap();
+
+        // The consensus layer moves to a child of the old skeleton head
+        let new_head = extend(skeleton.last().unwrap(), 1).remove(0);
+        let new_head_hash = new_head.hash();
+        store
+            .add_block_header(new_head_hash, new_head.clone())
+            .await
+            .unwrap();
+        skeleton.push(new_head);
+
+        let filled = fill_skeleton(new_head_hash, anchor, &store, &peers)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            store.get_skeleton_sync_progress().await.unwrap(),
+            Some((new_head_hash, skeleton[0].hash()))
+        );
+        assert_eq!(read_batches(filled), hashes(&skeleton));
+    }
+
+    #[tokio::test]
+    async fn local_head_gives_empty_skeleton() {
+        let (store, canonical, _) = setup_store().await;
+        let head = canonical.last().unwrap().hash();
+        let mut filled = fill_skeleton(head, head, &store, &PeerHandler::dummy())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(filled.is_empty());
+        assert!(filled.next_batch().unwrap().is_none());
+    }
+}
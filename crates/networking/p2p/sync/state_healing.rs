@@ -29,12 +29,15 @@ use super::SyncError;
 
 /// Heals the trie given its state_root by fetching any missing nodes in it via p2p
 /// Returns true if healing was fully completed or false if we need to resume healing on the next sync cycle
+/// Also returns whether any node besides the root was missing and had to be fetched
 pub(crate) async fn heal_state_trie(
     state_root: H256,
     store: Store,
     peers: PeerHandler,
-) -> Result<bool, SyncError> {
+) -> Result<(bool, bool), SyncError> {
     let mut paths = store.get_state_heal_paths().await?.unwrap_or_default();
+    // Paths cached by a previous cycle were already missing from the trie
+    let mut healed = !paths.is_empty();
     // Spawn a bytecode fetcher for this block
     let (bytecode_sender, bytecode_receiver) = channel::<Vec<H256>>(MAX_CHANNEL_MESSAGES);
     let bytecode_fetcher_handle = tokio::spawn(bytecode_fetcher(
@@ -72,6 +75,8 @@ pub(crate) async fn heal_state_trie(
         for res in state_tasks.join_all().await {
             let (return_paths, is_stale) = res?;
             stale |= is_stale;
+            // Either missing children or unfetched paths
+            healed |= !return_paths.is_empty();
             paths.extend(return_paths);
         }
         if stale {
@@ -87,7 +92,7 @@ pub(crate) async fn heal_state_trie(
     // Send empty batch to signal that no more batches are incoming
     bytecode_sender.send(vec![]).await?;
     bytecode_fetcher_handle.await??;
-    Ok(paths.is_empty())
+    Ok((paths.is_empty(), healed))
 }
 
 /// Receives a set of state trie paths, fetches their respective nodes, stores them,
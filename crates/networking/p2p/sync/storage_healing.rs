@@ -33,15 +33,17 @@ use ethrex_rlp::encode::RLPEncode;
 /// Also retrieves their children nodes until we have the full storage trie stored
 /// If the state becomes stale while fetching, returns its current queued account hashes
 // Returns true if there are no more pending storages in the queue (aka storage healing was completed)
+// Also returns whether any storage needed healing at all
 pub(crate) async fn storage_healer(
     state_root: H256,
     peers: PeerHandler,
     store: Store,
     cancel_token: CancellationToken,
     state_healing_ended: Arc<AtomicBool>,
-) -> Result<bool, SyncError> {
+) -> Result<(bool, bool), SyncError> {
     // List of paths in need of healing, grouped by hashed address
     let mut pending_paths = BTreeMap::<H256, Vec<Nibbles>>::new();
+    let mut healed = false;
     let mut stale = false;
     let mut last_update = Instant::now();
     while !(stale || cancel_token.is_cancelled()) {
@@ -66,6 +68,7 @@ pub(crate) async fn storage_healer(
         if pending_paths.is_empty() && state_healing_ended.load(Ordering::Relaxed) {
             break;
         }
+        healed |= !pending_paths.is_empty();
         // If we have enough pending storages to fill a batch
         // or if we have no more incoming batches, spawn a fetch process
         // If the pivot became stale don't process anything and just save incoming requests
@@ -100,7 +103,7 @@ pub(crate) async fn storage_healer(
     store
         .set_storage_heal_paths(pending_paths.into_iter().collect())
         .await?;
-    Ok(healing_complete)
+    Ok((healing_complete, healed))
 }
 
 /// Receives a set of storage trie paths (grouped by their corresponding account's state trie path),
@@ -36,6 +36,7 @@ impl SyncManager {
         cancel_token: CancellationToken,
         blockchain: Arc<Blockchain>,
         store: Store,
+        skeleton_sync: bool,
     ) -> Self {
         let snap_enabled = Arc::new(AtomicBool::new(matches!(sync_mode, SyncMode::Snap)));
         let syncer = Arc::new(Mutex::new(Syncer::new(
@@ -43,6 +44,7 @@ impl SyncManager {
             snap_enabled.clone(),
             cancel_token,
             blockchain,
+            skeleton_sync,
         )));
         let sync_manager = Self {
             snap_enabled,
@@ -56,6 +58,10 @@ impl SyncManager {
             .get_header_download_checkpoint()
             .await
             .is_ok_and(|res| res.is_some())
+            || store
+                .get_skeleton_sync_progress()
+                .await
+                .is_ok_and(|res| res.is_some())
         {
             sync_manager.start_sync();
         }
@@ -0,0 +1,126 @@
+use std::collections::{HashSet, VecDeque};
+
+use ethrex_common::{H256, types::Transaction};
+use rand::seq::SliceRandom;
+
+/// Maximum amount of transaction hashes remembered per peer
+pub const MAX_KNOWN_TXS: usize = 32768;
+/// Transactions bigger than this (in bytes) are never sent in full, only announced.
+/// Peers can still request them through `GetPooledTransactions`.
+pub const TX_MAX_BROADCAST_SIZE: usize = 4096;
+
+/// Controls how the transactions in our mempool are propagated to peers.
+#[derive(Debug, Clone, Default)]
+pub struct TxGossipPolicy {
+    /// Don't propagate any transaction, neither in full nor announcing their hashes
+    pub no_broadcast: bool,
+    /// Don't propagate the transactions submitted to this node through the RPC
+    pub no_locals: bool,
+}
+
+/// Bounded set of the transaction hashes a peer is known to have, either because
+/// we sent them or because the peer sent or announced them to us.
+/// Once full, the oldest hashes are forgotten first.
+#[derive(Debug, Clone)]
+pub(crate) struct KnownTxs {
+    hashes: HashSet<H256>,
+    insertion_order: VecDeque<H256>,
+    capacity: usize,
+}
+
+impl Default for KnownTxs {
+    fn default() -> Self {
+        Self::new(MAX_KNOWN_TXS)
+    }
+}
+
+impl KnownTxs {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            hashes: HashSet::new(),
+            insertion_order: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    /// Marks the hash as known, returns false if it already was
+    pub fn insert(&mut self, hash: H256) -> bool {
+        if !self.hashes.insert(hash) {
+            return false;
+        }
+        self.insertion_order.push_back(hash);
+        while self.insertion_order.len() > self.capacity {
+            if let Some(oldest) = self.insertion_order.pop_front() {
+                self.hashes.remove(&oldest);
+            }
+        }
+        true
+    }
+
+    pub fn contains(&self, hash: &H256) -> bool {
+        self.hashes.contains(hash)
+    }
+}
+
+/// Returns true if the transaction can be sent in full through a `Transactions` message.
+/// Blob transactions and large transactions are only announced, as per [the spec](https://github.com/ethereum/devp2p/blob/master/caps/eth.md#transactions-0x02)
+pub(crate) fn can_broadcast_in_full(tx: &Transaction) -> bool {
+    !matches!(tx, Transaction::EIP4844Transaction(_))
+        && tx.encode_canonical_to_vec().len() <= TX_MAX_BROADCAST_SIZE
+}
+
+/// Chooses which of the given peers receive the full transactions of a broadcast.
+/// As in geth, sqrt(peers) of them are picked at random, the rest receive the
+/// hashes on the next announcement.
+pub(crate) fn full_broadcast_peers(peers: &[H256]) -> HashSet<H256> {
+    let count = (peers.len() as f64).sqrt() as usize;
+    peers
+        .choose_multiple(&mut rand::thread_rng(), count)
+        .copied()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethrex_common::{
+        Bytes,
+        types::{EIP1559Transaction, EIP4844Transaction},
+    };
+
+    #[test]
+    fn known_txs_forgets_oldest_hashes() {
+        let mut known_txs = KnownTxs::new(2);
+        let hashes: Vec<H256> = (0..3).map(|_| H256::random()).collect();
+        assert!(known_txs.insert(hashes[0]));
+        assert!(known_txs.insert(hashes[1]));
+        assert!(!known_txs.insert(hashes[1]));
+        assert!(known_txs.insert(hashes[2]));
+        assert!(!known_txs.contains(&hashes[0]));
+        assert!(known_txs.contains(&hashes[1]));
+        assert!(known_txs.contains(&hashes[2]));
+    }
+
+    #[test]
+    fn blob_and_large_txs_are_only_announced() {
+        let small_tx = Transaction::EIP1559Transaction(EIP1559Transaction::default());
+        let large_tx = Transaction::EIP1559Transaction(EIP1559Transaction {
+            data: Bytes::from(vec![1; TX_MAX_BROADCAST_SIZE]),
+            ..Default::default()
+        });
+        let blob_tx = Transaction::EIP4844Transaction(EIP4844Transaction::default());
+        assert!(can_broadcast_in_full(&small_tx));
+        assert!(!can_broadcast_in_full(&large_tx));
+        assert!(!can_broadcast_in_full(&blob_tx));
+    }
+
+    #[test]
+    fn full_broadcast_reaches_sqrt_of_peers() {
+        for (peer_count, expected) in [(0, 0), (1, 1), (3, 1), (4, 2), (50, 7), (100, 10)] {
+            let peers: Vec<H256> = (0..peer_count).map(|_| H256::random()).collect();
+            let recipients = full_broadcast_peers(&peers);
+            assert_eq!(recipients.len(), expected);
+            assert!(recipients.iter().all(|peer| peers.contains(peer)));
+        }
+    }
+}
@@ -1023,6 +1023,16 @@ impl EthClient {
             .await
     }
 
+    /// L1 ERC20 used as the native token of the L2, `None` if it's ETH.
+    pub async fn get_native_token(
+        &self,
+        common_bridge_address: Address,
+    ) -> Result<Option<Address>, EthClientError> {
+        let native_token =
+            Self::_call_address_variable(self, b"NATIVE_TOKEN_L1()", common_bridge_address).await?;
+        Ok((!native_token.is_zero()).then_some(native_token))
+    }
+
     pub async fn get_pending_privileged_transactions(
         &self,
         common_bridge_address: Address,
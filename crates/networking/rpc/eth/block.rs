@@ -1,5 +1,6 @@
-use ethrex_blockchain::find_parent_header;
-use ethrex_rlp::encode::RLPEncode;
+use ethrex_blockchain::{BlockchainType, find_parent_header, validate_block_stateless};
+use ethrex_rlp::{decode::RLPDecode, encode::RLPEncode};
+use serde::Serialize;
 use serde_json::Value;
 use tracing::debug;
 
@@ -12,9 +13,14 @@ use crate::{
     },
     utils::RpcErr,
 };
-use ethrex_common::types::{
-    Block, BlockBody, BlockHash, BlockHeader, BlockNumber, Receipt, calculate_base_fee_per_blob_gas,
+use ethrex_common::{
+    H256, U256,
+    types::{
+        Block, BlockBody, BlockHash, BlockHeader, BlockNumber, Receipt,
+        block_execution_witness::ExecutionWitnessResult, calculate_base_fee_per_blob_gas,
+    },
 };
+use ethrex_levm::hooks::l2_hook::{L1_FEE_ORACLE_ADDRESS, L1_FEE_PER_BYTE_SLOT, calculate_l1_fee};
 use ethrex_storage::Store;
 
 pub struct GetBlockByNumberRequest {
@@ -56,6 +62,21 @@ pub struct ExecutionWitness {
     pub to: Option<BlockIdentifier>,
 }
 
+pub struct ValidateStatelessRequest {
+    pub block: Block,
+    pub witness: ExecutionWitnessResult,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatelessValidationResult {
+    pub valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state_root: Option<H256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub validation_error: Option<String>,
+}
+
 impl RpcHandler for GetBlockByNumberRequest {
     fn parse(params: &Option<Vec<Value>>) -> Result<GetBlockByNumberRequest, RpcErr> {
         let params = params
@@ -183,7 +204,14 @@ impl RpcHandler for GetBlockReceiptsRequest {
             // Block not found
             _ => return Ok(Value::Null),
         };
-        let receipts = get_all_block_rpc_receipts(block_number, header, body, storage).await?;
+        let receipts = get_all_block_rpc_receipts(
+            block_number,
+            header,
+            body,
+            storage,
+            &context.blockchain.r#type,
+        )
+        .await?;
 
         serde_json::to_value(&receipts).map_err(|error| RpcErr::Internal(error.to_string()))
     }
@@ -389,6 +417,22 @@ impl RpcHandler for ExecutionWitness {
             debug!("Requested execution witness for block: {from_block_number}",);
         }
 
+        // Witnesses recorded during the import of a single block can be returned without re-executing it
+        if from_block_number == to_block_number {
+            if let Some(block_hash) = context
+                .storage
+                .get_canonical_block_hash(from_block_number)
+                .await?
+            {
+                if let Some(execution_witness) =
+                    context.storage.get_execution_witness(block_hash).await?
+                {
+                    return serde_json::to_value(execution_witness)
+                        .map_err(|error| RpcErr::Internal(error.to_string()));
+                }
+            }
+        }
+
         let mut blocks = Vec::new();
         let mut block_headers = Vec::new();
         for block_number in from_block_number..=to_block_number {
@@ -426,12 +470,27 @@ pub async fn get_all_block_rpc_receipts(
     header: BlockHeader,
     body: BlockBody,
     storage: &Store,
+    blockchain_type: &BlockchainType,
 ) -> Result<Vec<RpcReceipt>, RpcErr> {
     let mut receipts = Vec::new();
     // Check if this is the genesis block
     if header.parent_hash.is_zero() {
         return Ok(receipts);
     }
+    // L2 transactions pay the L1 fee per byte set in the oracle by the end of the previous block
+    let l1_fee_per_byte = match blockchain_type {
+        BlockchainType::L1 => None,
+        BlockchainType::L2 => Some(
+            storage
+                .get_storage_at(
+                    block_number - 1,
+                    L1_FEE_ORACLE_ADDRESS,
+                    L1_FEE_PER_BYTE_SLOT,
+                )
+                .await?
+                .unwrap_or_default(),
+        ),
+    };
     // TODO: Here we are calculating the base_fee_per_blob_gas with the current header.
     // Check if we should be passing the parent header instead
     let config = storage.get_chain_config()?;
@@ -455,13 +514,21 @@ pub async fn get_all_block_rpc_receipts(
             _ => return Err(RpcErr::Internal("Could not get receipt".to_owned())),
         };
         let gas_used = receipt.cumulative_gas_used - last_cumulative_gas_used;
-        let tx_info = RpcReceiptTxInfo::from_transaction(
+        let mut tx_info = RpcReceiptTxInfo::from_transaction(
             tx.clone(),
             index,
             gas_used,
             blob_base_fee,
             base_fee_per_gas,
         )?;
+        if let Some(l1_fee_per_byte) = l1_fee_per_byte {
+            tx_info.l1_fee = Some(if tx.is_privileged() || tx_info.effective_gas_price == 0 {
+                U256::zero()
+            } else {
+                calculate_l1_fee(tx, l1_fee_per_byte)
+                    .map_err(|error| RpcErr::Internal(error.to_string()))?
+            });
+        }
         let receipt = RpcReceipt::new(
             receipt.clone(),
             tx_info,
@@ -496,3 +563,68 @@ pub async fn get_all_block_receipts(
     }
     Ok(receipts)
 }
+
+impl RpcHandler for ValidateStatelessRequest {
+    fn parse(params: &Option<Vec<Value>>) -> Result<Self, RpcErr> {
+        let params = params
+            .as_ref()
+            .ok_or(RpcErr::BadParams("No params provided".to_owned()))?;
+        if params.len() != 2 {
+            return Err(RpcErr::BadParams("Expected 2 params".to_owned()));
+        };
+
+        let raw_block = serde_json::from_value::<String>(params[0].clone())?;
+        let raw_block = raw_block
+            .strip_prefix("0x")
+            .ok_or(RpcErr::BadParams("Block is not 0x prefixed".to_owned()))?;
+        let raw_block =
+            hex::decode(raw_block).map_err(|error| RpcErr::BadParams(error.to_string()))?;
+        let block =
+            Block::decode(&raw_block).map_err(|error| RpcErr::BadParams(error.to_string()))?;
+
+        Ok(ValidateStatelessRequest {
+            block,
+            witness: serde_json::from_value(params[1].clone())?,
+        })
+    }
+
+    async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
+        debug!(
+            "Requested stateless validation of block: {}",
+            self.block.header.number
+        );
+        let block = self.block.clone();
+        let witness = ExecutionWitnessResult {
+            state_trie_nodes: self.witness.state_trie_nodes.clone(),
+            storage_trie_nodes: self.witness.storage_trie_nodes.clone(),
+            codes: self.witness.codes.clone(),
+            state_trie: None,
+            storage_tries: None,
+            block_headers: self.witness.block_headers.clone(),
+            parent_block_header: self.witness.parent_block_header.clone(),
+            chain_config: self.witness.chain_config,
+        };
+        // The block is validated against the chain of this node, not the config sent in the witness
+        let chain_config = context.storage.get_chain_config()?;
+        // Execution is CPU bound, so it's moved out of the async runtime
+        let result = tokio::task::spawn_blocking(move || {
+            validate_block_stateless(&block, witness, chain_config)
+        })
+        .await
+        .map_err(|error| RpcErr::Internal(error.to_string()))?;
+
+        let result = match result {
+            Ok(state_root) => StatelessValidationResult {
+                valid: true,
+                state_root: Some(state_root),
+                validation_error: None,
+            },
+            Err(error) => StatelessValidationResult {
+                valid: false,
+                state_root: None,
+                validation_error: Some(error.to_string()),
+            },
+        };
+        serde_json::to_value(result).map_err(|error| RpcErr::Internal(error.to_string()))
+    }
+}
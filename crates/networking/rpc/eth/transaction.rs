@@ -9,13 +9,15 @@ use crate::{
     },
     utils::RpcErr,
 };
-use ethrex_blockchain::{Blockchain, vm::StoreVmDatabase};
+use ethrex_blockchain::{Blockchain, BlockchainType, vm::StoreVmDatabase};
 use ethrex_common::{
     H256, U256,
     types::{
-        AccessListEntry, BlockHash, BlockHeader, BlockNumber, Fork, GenericTransaction, TxKind,
+        AccessListEntry, BlockHash, BlockHeader, BlockNumber, EIP1559Transaction, Fork,
+        GenericTransaction, Transaction, TxKind,
     },
 };
+use ethrex_levm::hooks::l2_hook::{L1_FEE_ORACLE_ADDRESS, L1_FEE_PER_BYTE_SLOT, flz_compress_len};
 
 use ethrex_rlp::encode::RLPEncode;
 use ethrex_storage::Store;
@@ -29,6 +31,8 @@ use tracing::debug;
 pub const ESTIMATE_ERROR_RATIO: f64 = 0.015;
 pub const CALL_STIPEND: u64 = 2_300; // Free gas given at beginning of call.
 pub const TRANSACTION_GAS: u64 = 21_000; // Per transaction not creating a contract. NOTE: Not payable on data of calls between transactions.
+/// Encoded size of the signature (r, s and y parity), added to the compressed size of an unsigned transaction since it doesn't compress.
+const SIGNATURE_ENCODED_SIZE: u64 = 67;
 
 pub struct CallRequest {
     transaction: GenericTransaction,
@@ -301,9 +305,14 @@ impl RpcHandler for GetTransactionReceiptRequest {
             Some(block) => block,
             None => return Ok(Value::Null),
         };
-        let receipts =
-            block::get_all_block_rpc_receipts(block_number, block.header, block.body, storage)
-                .await?;
+        let receipts = block::get_all_block_rpc_receipts(
+            block_number,
+            block.header,
+            block.body,
+            storage,
+            &context.blockchain.r#type,
+        )
+        .await?;
 
         serde_json::to_value(receipts.get(index as usize))
             .map_err(|error| RpcErr::Internal(error.to_string()))
@@ -436,25 +445,9 @@ impl RpcHandler for EstimateGasRequest {
     async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
         let storage = &context.storage;
         let blockchain = &context.blockchain;
-        let block = self.block.clone().unwrap_or_default();
-        debug!("Requested estimate on block: {}", block);
-        let block_header = match block.resolve_block_header(storage).await? {
-            Some(header) => header,
+        let Some((block_header, transaction)) = self.resolve(storage).await? else {
             // Block not found
-            _ => return Ok(Value::Null),
-        };
-
-        let transaction = match self.transaction.nonce {
-            Some(_nonce) => self.transaction.clone(),
-            None => {
-                let transaction_nonce = storage
-                    .get_nonce_by_account_address(block_header.number, self.transaction.from)
-                    .await?;
-
-                let mut cloned_transaction = self.transaction.clone();
-                cloned_transaction.nonce = transaction_nonce;
-                cloned_transaction
-            }
+            return Ok(Value::Null);
         };
 
         let chain_config = storage.get_chain_config()?;
@@ -490,9 +483,22 @@ impl RpcHandler for EstimateGasRequest {
         };
 
         if transaction.gas_price != 0 {
+            // L2s charge the L1 fee on top of the gas, so it isn't available to pay for it
+            let l1_fee = estimate_l1_fee(
+                &transaction,
+                highest_gas_limit,
+                &block_header,
+                storage,
+                &blockchain.r#type,
+            )
+            .await?;
+            if !l1_fee.is_zero() {
+                debug!("Estimated L1 fee: {l1_fee}");
+            }
             highest_gas_limit = recap_with_account_balances(
                 highest_gas_limit,
                 &transaction,
+                l1_fee,
                 storage,
                 block_header.number,
             )
@@ -551,9 +557,109 @@ impl RpcHandler for EstimateGasRequest {
     }
 }
 
+impl EstimateGasRequest {
+    /// L1 data fee the transaction pays on top of its gas when sent to an L2, zero on an L1.
+    /// It's estimated with the gas limit of the transaction, or the block gas limit if it has none.
+    /// Returns None if the block isn't found.
+    pub async fn l1_fee(&self, context: &RpcApiContext) -> Result<Option<U256>, RpcErr> {
+        let Some((block_header, transaction)) = self.resolve(&context.storage).await? else {
+            return Ok(None);
+        };
+        let gas_limit = transaction.gas.unwrap_or(block_header.gas_limit);
+        estimate_l1_fee(
+            &transaction,
+            gas_limit,
+            &block_header,
+            &context.storage,
+            &context.blockchain.r#type,
+        )
+        .await
+        .map(Some)
+    }
+
+    /// Returns the header of the requested block and the transaction with its nonce filled
+    async fn resolve(
+        &self,
+        storage: &Store,
+    ) -> Result<Option<(BlockHeader, GenericTransaction)>, RpcErr> {
+        let block = self.block.clone().unwrap_or_default();
+        debug!("Requested estimate on block: {}", block);
+        let block_header = match block.resolve_block_header(storage).await? {
+            Some(header) => header,
+            _ => return Ok(None),
+        };
+
+        let transaction = match self.transaction.nonce {
+            Some(_nonce) => self.transaction.clone(),
+            None => {
+                let transaction_nonce = storage
+                    .get_nonce_by_account_address(block_header.number, self.transaction.from)
+                    .await?;
+
+                let mut cloned_transaction = self.transaction.clone();
+                cloned_transaction.nonce = transaction_nonce;
+                cloned_transaction
+            }
+        };
+        Ok(Some((block_header, transaction)))
+    }
+}
+
+/// Estimates the L1 fee an L2 charges the transaction when included after the given block.
+/// The price per byte is the one in the oracle, and the size is the one of the transaction
+/// compressed without its signature plus the size of the signature.
+async fn estimate_l1_fee(
+    transaction: &GenericTransaction,
+    gas_limit: u64,
+    block_header: &BlockHeader,
+    storage: &Store,
+    blockchain_type: &BlockchainType,
+) -> Result<U256, RpcErr> {
+    if matches!(blockchain_type, BlockchainType::L1) {
+        return Ok(U256::zero());
+    }
+    let l1_fee_per_byte = storage
+        .get_storage_at(
+            block_header.number,
+            L1_FEE_ORACLE_ADDRESS,
+            L1_FEE_PER_BYTE_SLOT,
+        )
+        .await?
+        .unwrap_or_default();
+    if l1_fee_per_byte.is_zero() {
+        return Ok(U256::zero());
+    }
+
+    let unsigned_transaction = Transaction::EIP1559Transaction(EIP1559Transaction {
+        chain_id: match transaction.chain_id {
+            Some(chain_id) => chain_id,
+            None => storage.get_chain_config()?.chain_id,
+        },
+        nonce: transaction.nonce.unwrap_or_default(),
+        max_priority_fee_per_gas: transaction
+            .max_priority_fee_per_gas
+            .unwrap_or(transaction.gas_price),
+        max_fee_per_gas: transaction.max_fee_per_gas.unwrap_or(transaction.gas_price),
+        gas_limit,
+        to: transaction.to.clone(),
+        value: transaction.value,
+        data: transaction.input.clone(),
+        access_list: transaction
+            .access_list
+            .iter()
+            .map(|entry| (entry.address, entry.storage_keys.clone()))
+            .collect(),
+        ..Default::default()
+    });
+    let size =
+        flz_compress_len(&unsigned_transaction.encode_canonical_to_vec()) + SIGNATURE_ENCODED_SIZE;
+    Ok(l1_fee_per_byte.saturating_mul(U256::from(size)))
+}
+
 async fn recap_with_account_balances(
     highest_gas_limit: u64,
     transaction: &GenericTransaction,
+    l1_fee: U256,
     storage: &Store,
     block_number: BlockNumber,
 ) -> Result<u64, RpcErr> {
@@ -562,8 +668,10 @@ async fn recap_with_account_balances(
         .await?
         .map(|acc| acc.balance)
         .unwrap_or_default();
-    let account_gas =
-        account_balance.saturating_sub(transaction.value) / U256::from(transaction.gas_price);
+    let account_gas = account_balance
+        .saturating_sub(transaction.value)
+        .saturating_sub(l1_fee)
+        / U256::from(transaction.gas_price);
     Ok(highest_gas_limit.min(account_gas.as_u64()))
 }
 
@@ -604,7 +712,11 @@ impl RpcHandler for SendRawTransactionRequest {
     }
 
     async fn handle(&self, context: RpcApiContext) -> Result<Value, RpcErr> {
-        let hash = if let SendRawTransactionRequest::EIP4844(wrapped_blob_tx) = self {
+        // Mark the transaction as local before it reaches the pool, so nodes running
+        // with `--txpool.nolocals` never get to announce it to their peers
+        let tx_hash = self.to_transaction().compute_hash();
+        let newly_marked = context.blockchain.mempool.mark_as_local(tx_hash)?;
+        let result = if let SendRawTransactionRequest::EIP4844(wrapped_blob_tx) = self {
             context
                 .blockchain
                 .add_blob_transaction_to_pool(
@@ -617,7 +729,12 @@ impl RpcHandler for SendRawTransactionRequest {
                 .blockchain
                 .add_transaction_to_pool(self.to_transaction())
                 .await
-        }?;
+        };
+        // A resubmission failing (e.g. as already known) must not unmark the pooled transaction
+        if result.is_err() && newly_marked {
+            context.blockchain.mempool.unmark_as_local(&tx_hash)?;
+        }
+        let hash = result?;
         serde_json::to_value(format!("{hash:#x}"))
             .map_err(|error| RpcErr::Internal(error.to_string()))
     }
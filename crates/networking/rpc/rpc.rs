@@ -9,7 +9,7 @@ use crate::engine::{
         NewPayloadV2Request, NewPayloadV3Request, NewPayloadV4Request,
     },
 };
-use crate::eth::block::ExecutionWitness;
+use crate::eth::block::{ExecutionWitness, ValidateStatelessRequest};
 use crate::eth::{
     account::{
         GetBalanceRequest, GetCodeRequest, GetProofRequest, GetStorageAtRequest,
@@ -344,6 +344,7 @@ pub async fn map_debug_requests(req: &RpcRequest, context: RpcApiContext) -> Res
         "debug_getRawTransaction" => GetRawTransaction::call(req, context).await,
         "debug_getRawReceipts" => GetRawReceipts::call(req, context).await,
         "debug_executionWitness" => ExecutionWitness::call(req, context).await,
+        "debug_validateStateless" => ValidateStatelessRequest::call(req, context).await,
         "debug_traceTransaction" => TraceTransactionRequest::call(req, context).await,
         "debug_traceBlockByNumber" => TraceBlockByNumberRequest::call(req, context).await,
         unknown_debug_method => Err(RpcErr::MethodNotFound(unknown_debug_method.to_owned())),
@@ -437,10 +438,12 @@ where
 mod tests {
     use super::*;
     use crate::utils::test_utils::default_context_with_storage;
+    use ethrex_blockchain::payload::{BuildPayloadArgs, create_payload};
     use ethrex_common::{
-        H160,
-        types::{ChainConfig, Genesis},
+        H160, H256,
+        types::{ChainConfig, ELASTICITY_MULTIPLIER, Genesis},
     };
+    use ethrex_rlp::encode::RLPEncode;
     use ethrex_storage::{EngineType, Store};
     use sha3::{Digest, Keccak256};
     use std::fs::File;
@@ -553,6 +556,79 @@ mod tests {
         assert_eq!(response.to_string(), expected_response.to_string());
     }
 
+    #[tokio::test]
+    async fn debug_validate_stateless_uses_local_chain_config() {
+        let storage =
+            Store::new("temp.db", EngineType::InMemory).expect("Failed to create test DB");
+        storage
+            .add_initial_state(read_execution_api_genesis_file())
+            .await
+            .expect("Failed to add genesis block to DB");
+        let context = default_context_with_storage(storage.clone()).await;
+
+        // Build and import a block to generate its witness
+        let parent = storage.get_block_header(0).unwrap().unwrap();
+        let args = BuildPayloadArgs {
+            parent: parent.hash(),
+            timestamp: parent.timestamp + 12,
+            fee_recipient: H160::random(),
+            random: H256::random(),
+            withdrawals: Some(Vec::new()),
+            beacon_root: Some(H256::random()),
+            version: 1,
+            elasticity_multiplier: ELASTICITY_MULTIPLIER,
+        };
+        let payload = create_payload(&args, &storage).unwrap();
+        let block = context
+            .blockchain
+            .build_payload(payload)
+            .await
+            .unwrap()
+            .payload;
+        context.blockchain.add_block(&block).await.unwrap();
+        let mut witness = context
+            .blockchain
+            .generate_witness_for_blocks(&[block.clone()])
+            .await
+            .unwrap();
+        // The block would be invalid before Shanghai, but the config sent in the witness is ignored
+        witness.chain_config.shanghai_time = None;
+        witness.chain_config.cancun_time = None;
+        witness.chain_config.prague_time = None;
+
+        let body = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "debug_validateStateless",
+            "params": [format!("0x{}", hex::encode(block.encode_to_vec())), witness],
+        });
+        let request: RpcRequest = serde_json::from_value(body).unwrap();
+        let result = map_http_requests(&request, context.clone()).await.unwrap();
+        assert_eq!(
+            result,
+            serde_json::json!({ "valid": true, "stateRoot": block.header.state_root })
+        );
+
+        // A block claiming a different post-state is rejected
+        let witness = context
+            .blockchain
+            .generate_witness_for_blocks(&[block.clone()])
+            .await
+            .unwrap();
+        let mut invalid_block = block;
+        invalid_block.header.state_root = H256::repeat_byte(1);
+        let body = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "debug_validateStateless",
+            "params": [format!("0x{}", hex::encode(invalid_block.encode_to_vec())), witness],
+        });
+        let request: RpcRequest = serde_json::from_value(body).unwrap();
+        let result = map_http_requests(&request, context).await.unwrap();
+        assert_eq!(result["valid"], false);
+        assert!(result["validationError"].is_string());
+    }
+
     fn example_chain_config() -> ChainConfig {
         ChainConfig {
             chain_id: 3151908_u64,
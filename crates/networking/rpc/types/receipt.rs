@@ -1,5 +1,5 @@
 use ethrex_common::{
-    Address, Bloom, Bytes, H256,
+    Address, Bloom, Bytes, H256, U256,
     constants::GAS_PER_BLOB,
     serde_utils,
     types::{
@@ -164,6 +164,9 @@ pub struct RpcReceiptTxInfo {
         default = "Option::default"
     )]
     pub blob_gas_used: Option<u64>,
+    /// L1 data fee paid by L2 transactions, on top of the gas
+    #[serde(skip_serializing_if = "Option::is_none", default = "Option::default")]
+    pub l1_fee: Option<U256>,
 }
 
 impl RpcReceiptTxInfo {
@@ -205,6 +208,7 @@ impl RpcReceiptTxInfo {
             effective_gas_price,
             blob_gas_price,
             blob_gas_used,
+            l1_fee: None,
         })
     }
 }
@@ -243,6 +247,7 @@ mod tests {
                 effective_gas_price: 157,
                 blob_gas_price: None,
                 blob_gas_used: None,
+                l1_fee: None,
             },
             RpcReceiptBlockInfo {
                 block_hash: BlockHash::zero(),
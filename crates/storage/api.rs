@@ -8,7 +8,7 @@ use std::{fmt::Debug, panic::RefUnwindSafe};
 
 use crate::UpdateBatch;
 use crate::{error::StoreError, store::STATE_TRIE_SEGMENTS};
-use ethrex_trie::{Nibbles, Trie};
+use ethrex_trie::{Nibbles, Trie, TrieDB};
 
 // We need async_trait because the stabilized feature lacks support for object safety
 // (i.e. dyn StoreEngine)
@@ -80,6 +80,27 @@ pub trait StoreEngine: Debug + Send + Sync + RefUnwindSafe {
     async fn add_pending_block(&self, block: Block) -> Result<(), StoreError>;
     async fn get_pending_block(&self, block_hash: BlockHash) -> Result<Option<Block>, StoreError>;
 
+    /// Add the serialized execution witness of a block
+    async fn add_execution_witness(
+        &self,
+        block_number: BlockNumber,
+        block_hash: BlockHash,
+        witness: Vec<u8>,
+    ) -> Result<(), StoreError>;
+
+    /// Obtain the serialized execution witness of a block, if it was stored
+    async fn get_execution_witness(
+        &self,
+        block_number: BlockNumber,
+        block_hash: BlockHash,
+    ) -> Result<Option<Vec<u8>>, StoreError>;
+
+    /// Remove the execution witnesses of all the blocks up to the given number, included
+    async fn remove_execution_witnesses_up_to(
+        &self,
+        block_number: BlockNumber,
+    ) -> Result<(), StoreError>;
+
     /// Add block number for a given hash
     async fn add_block_number(
         &self,
@@ -228,6 +249,21 @@ pub trait StoreEngine: Debug + Send + Sync + RefUnwindSafe {
     /// Obtain pending block number
     async fn get_pending_block_number(&self) -> Result<Option<BlockNumber>, StoreError>;
 
+    /// Sets the head and tail hashes of the header skeleton being filled during a beacon sync
+    async fn set_skeleton_sync_progress(
+        &self,
+        head: BlockHash,
+        tail: BlockHash,
+    ) -> Result<(), StoreError>;
+
+    /// Obtain the head and tail hashes of the header skeleton left by a previous beacon sync, if any
+    async fn get_skeleton_sync_progress(
+        &self,
+    ) -> Result<Option<(BlockHash, BlockHash)>, StoreError>;
+
+    /// Removes the stored header skeleton progress
+    async fn clear_skeleton_sync_progress(&self) -> Result<(), StoreError>;
+
     /// Obtain a storage trie from the given address and storage_root
     /// Doesn't check if the account is stored
     /// Used for internal store operations
@@ -242,6 +278,10 @@ pub trait StoreEngine: Debug + Send + Sync + RefUnwindSafe {
     /// Used for internal store operations
     fn open_state_trie(&self, state_root: H256) -> Result<Trie, StoreError>;
 
+    /// Obtain the database of the binary trie nodes, kept apart from the state and storage trie nodes
+    /// Used by the binary trie prototype
+    fn open_binary_trie_db(&self) -> Result<Box<dyn TrieDB>, StoreError>;
+
     async fn forkchoice_update(
         &self,
         new_canonical_blocks: Option<Vec<(BlockNumber, BlockHash)>>,
@@ -352,7 +392,7 @@ pub trait StoreEngine: Debug + Send + Sync + RefUnwindSafe {
         &self,
     ) -> Result<Option<Vec<(H256, H256)>>, StoreError>;
 
-    /// Clears the state and storage snapshots
+    /// Clears the state and storage snapshots, along with the snapshot root
     async fn clear_snapshot(&self) -> Result<(), StoreError>;
 
     /// Reads the next `MAX_SNAPSHOT_READS` accounts from the state snapshot as from the `start` hash
@@ -361,10 +401,20 @@ pub trait StoreEngine: Debug + Send + Sync + RefUnwindSafe {
     /// Reads the next `MAX_SNAPSHOT_READS` elements from the storage snapshot as from the `start` storage key
     async fn read_storage_snapshot(
         &self,
-        start: H256,
         account_hash: H256,
+        start: H256,
     ) -> Result<Vec<(H256, U256)>, StoreError>;
 
+    /// Sets the number and state root of the block whose whole state is held by the state and storage snapshots
+    async fn set_state_snapshot_root(
+        &self,
+        block_number: BlockNumber,
+        state_root: H256,
+    ) -> Result<(), StoreError>;
+
+    /// Obtain the number and state root of the block whose whole state is held by the state and storage snapshots, if any
+    async fn get_state_snapshot_root(&self) -> Result<Option<(BlockNumber, H256)>, StoreError>;
+
     /// The `forkchoice_update` and `new_payload` methods require the `latest_valid_hash`
     /// when processing an invalid payload. To provide this, we must track invalid chains.
     ///
@@ -3,12 +3,18 @@ use std::marker::PhantomData;
 
 use bytes::Bytes;
 use ethrex_common::{
-    H256,
+    Address, H256,
     types::{
-        AccountState, Block, BlockBody, BlockHash, BlockHeader, Receipt, payload::PayloadBundle,
+        AccountState, Block, BlockBody, BlockHash, BlockHeader, ChainConfig, Receipt,
+        block_execution_witness::ExecutionWitnessResult, payload::PayloadBundle,
     },
 };
-use ethrex_rlp::{decode::RLPDecode, encode::RLPEncode};
+use ethrex_rlp::{
+    decode::RLPDecode,
+    encode::RLPEncode,
+    error::RLPDecodeError,
+    structs::{Decoder, Encoder},
+};
 use ethrex_trie::Nibbles;
 #[cfg(feature = "libmdbx")]
 use libmdbx::orm::{Decodable, Encodable};
@@ -30,6 +36,106 @@ pub type BlockHeaderRLP = Rlp<BlockHeader>;
 pub type BlockBodyRLP = Rlp<BlockBody>;
 pub type BlockRLP = Rlp<Block>;
 
+// Execution witnesses of the blocks at a given height, serialized
+pub type ExecutionWitnessesRLP = Rlp<Vec<(BlockHash, Bytes)>>;
+
+/// Stored form of an execution witness, the chain config and the rebuilt tries are not persisted
+#[derive(Clone, Debug)]
+pub struct StoredExecutionWitness {
+    pub state_trie_nodes: Vec<Bytes>,
+    pub storage_trie_nodes: Vec<(Address, Vec<Bytes>)>,
+    pub codes: Vec<(H256, Bytes)>,
+    pub block_headers: Vec<BlockHeader>,
+    pub parent_block_header: BlockHeader,
+}
+
+impl From<&ExecutionWitnessResult> for StoredExecutionWitness {
+    fn from(witness: &ExecutionWitnessResult) -> Self {
+        let to_bytes = |nodes: &Vec<Vec<u8>>| -> Vec<Bytes> {
+            nodes.iter().cloned().map(Bytes::from).collect()
+        };
+        Self {
+            state_trie_nodes: witness
+                .state_trie_nodes
+                .as_ref()
+                .map(to_bytes)
+                .unwrap_or_default(),
+            storage_trie_nodes: witness
+                .storage_trie_nodes
+                .iter()
+                .flatten()
+                .map(|(address, nodes)| (*address, to_bytes(nodes)))
+                .collect(),
+            codes: witness
+                .codes
+                .iter()
+                .map(|(hash, code)| (*hash, code.clone()))
+                .collect(),
+            block_headers: witness.block_headers.values().cloned().collect(),
+            parent_block_header: witness.parent_block_header.clone(),
+        }
+    }
+}
+
+impl StoredExecutionWitness {
+    pub fn into_witness(self, chain_config: ChainConfig) -> ExecutionWitnessResult {
+        let to_vecs =
+            |nodes: Vec<Bytes>| -> Vec<Vec<u8>> { nodes.into_iter().map(Vec::from).collect() };
+        ExecutionWitnessResult {
+            state_trie_nodes: Some(to_vecs(self.state_trie_nodes)),
+            storage_trie_nodes: Some(
+                self.storage_trie_nodes
+                    .into_iter()
+                    .map(|(address, nodes)| (address, to_vecs(nodes)))
+                    .collect(),
+            ),
+            codes: self.codes.into_iter().collect(),
+            state_trie: None,
+            storage_tries: None,
+            block_headers: self
+                .block_headers
+                .into_iter()
+                .map(|header| (header.number, header))
+                .collect(),
+            parent_block_header: self.parent_block_header,
+            chain_config,
+        }
+    }
+}
+
+impl RLPEncode for StoredExecutionWitness {
+    fn encode(&self, buf: &mut dyn bytes::BufMut) {
+        Encoder::new(buf)
+            .encode_field(&self.state_trie_nodes)
+            .encode_field(&self.storage_trie_nodes)
+            .encode_field(&self.codes)
+            .encode_field(&self.block_headers)
+            .encode_field(&self.parent_block_header)
+            .finish();
+    }
+}
+
+impl RLPDecode for StoredExecutionWitness {
+    fn decode_unfinished(rlp: &[u8]) -> Result<(Self, &[u8]), RLPDecodeError> {
+        let decoder = Decoder::new(rlp)?;
+        let (state_trie_nodes, decoder) = decoder.decode_field("state_trie_nodes")?;
+        let (storage_trie_nodes, decoder) = decoder.decode_field("storage_trie_nodes")?;
+        let (codes, decoder) = decoder.decode_field("codes")?;
+        let (block_headers, decoder) = decoder.decode_field("block_headers")?;
+        let (parent_block_header, decoder) = decoder.decode_field("parent_block_header")?;
+        Ok((
+            Self {
+                state_trie_nodes,
+                storage_trie_nodes,
+                codes,
+                block_headers,
+                parent_block_header,
+            },
+            decoder.finish()?,
+        ))
+    }
+}
+
 // Receipt types
 #[allow(unused)]
 pub type ReceiptRLP = Rlp<Receipt>;
@@ -1,5 +1,6 @@
 use crate::api::StoreEngine;
 use crate::error::StoreError;
+use crate::rlp::StoredExecutionWitness;
 use crate::store_db::in_memory::Store as InMemoryStore;
 #[cfg(feature = "libmdbx")]
 use crate::store_db::libmdbx::Store as LibmdbxStore;
@@ -13,7 +14,7 @@ use ethrex_common::{
     types::{
         AccountInfo, AccountState, AccountUpdate, Block, BlockBody, BlockHash, BlockHeader,
         BlockNumber, ChainConfig, ForkId, Genesis, GenesisAccount, Index, Receipt, Transaction,
-        code_hash, payload::PayloadBundle,
+        block_execution_witness::ExecutionWitnessResult, code_hash, payload::PayloadBundle,
     },
 };
 use ethrex_rlp::decode::RLPDecode;
@@ -40,6 +41,9 @@ pub struct Store {
     engine: Arc<dyn StoreEngine>,
     chain_config: Arc<RwLock<ChainConfig>>,
     latest_block_header: Arc<RwLock<BlockHeader>>,
+    /// Whether the genesis state is also stored in an EIP-7864 binary trie
+    #[cfg(feature = "binary-trie")]
+    binary_trie: bool,
 }
 
 #[allow(dead_code)]
@@ -88,17 +92,23 @@ impl Store {
                 engine: Arc::new(LibmdbxStore::new(_path)?),
                 chain_config: Default::default(),
                 latest_block_header: Arc::new(RwLock::new(BlockHeader::default())),
+                #[cfg(feature = "binary-trie")]
+                binary_trie: false,
             },
             EngineType::InMemory => Self {
                 engine: Arc::new(InMemoryStore::new()),
                 chain_config: Default::default(),
                 latest_block_header: Arc::new(RwLock::new(BlockHeader::default())),
+                #[cfg(feature = "binary-trie")]
+                binary_trie: false,
             },
             #[cfg(feature = "redb")]
             EngineType::RedB => Self {
                 engine: Arc::new(RedBStore::new()?),
                 chain_config: Default::default(),
                 latest_block_header: Arc::new(RwLock::new(BlockHeader::default())),
+                #[cfg(feature = "binary-trie")]
+                binary_trie: false,
             },
         };
 
@@ -106,6 +116,13 @@ impl Store {
         Ok(store)
     }
 
+    /// Also stores the genesis state in an EIP-7864 binary trie when adding it
+    #[cfg(feature = "binary-trie")]
+    pub fn with_binary_trie(mut self, binary_trie: bool) -> Self {
+        self.binary_trie = binary_trie;
+        self
+    }
+
     pub async fn new_from_genesis(
         store_path: &str,
         engine_type: EngineType,
@@ -251,6 +268,50 @@ impl Store {
         self.engine.get_pending_block(block_hash).await
     }
 
+    /// Stores the execution witness recorded while importing a block
+    pub async fn add_execution_witness(
+        &self,
+        block_number: BlockNumber,
+        block_hash: BlockHash,
+        witness: &ExecutionWitnessResult,
+    ) -> Result<(), StoreError> {
+        let witness = StoredExecutionWitness::from(witness).encode_to_vec();
+        self.engine
+            .add_execution_witness(block_number, block_hash, witness)
+            .await
+    }
+
+    /// Returns the execution witness of a block, if it was recorded during its import and is still retained
+    pub async fn get_execution_witness(
+        &self,
+        block_hash: BlockHash,
+    ) -> Result<Option<ExecutionWitnessResult>, StoreError> {
+        let Some(block_number) = self.get_block_number(block_hash).await? else {
+            return Ok(None);
+        };
+        let Some(witness) = self
+            .engine
+            .get_execution_witness(block_number, block_hash)
+            .await?
+        else {
+            return Ok(None);
+        };
+        let chain_config = self.get_chain_config()?;
+        Ok(Some(
+            StoredExecutionWitness::decode(&witness)?.into_witness(chain_config),
+        ))
+    }
+
+    /// Removes the execution witnesses of all the blocks up to the given number, included
+    pub async fn remove_execution_witnesses_up_to(
+        &self,
+        block_number: BlockNumber,
+    ) -> Result<(), StoreError> {
+        self.engine
+            .remove_execution_witnesses_up_to(block_number)
+            .await
+    }
+
     pub async fn add_block_number(
         &self,
         block_hash: BlockHash,
@@ -544,6 +605,44 @@ impl Store {
         genesis_state_trie.hash().map_err(StoreError::Trie)
     }
 
+    /// Adds all genesis accounts to an EIP-7864 binary trie and returns its root.
+    /// The nodes are stored in their own table, keyed by their SHA-256 hash.
+    #[cfg(feature = "binary-trie")]
+    pub async fn setup_genesis_binary_trie(
+        &self,
+        genesis_accounts: BTreeMap<Address, GenesisAccount>,
+    ) -> Result<H256, StoreError> {
+        use ethrex_trie::binary::{
+            BinaryTrie, basic_data_leaf, chunkify_code, get_tree_key_for_basic_data,
+            get_tree_key_for_code_chunk, get_tree_key_for_code_hash, get_tree_key_for_storage_slot,
+        };
+
+        let mut binary_trie = BinaryTrie::new(self.engine.open_binary_trie_db()?);
+        for (address, account) in genesis_accounts {
+            let code_hash = code_hash(&account.code);
+            let code_size = u32::try_from(account.code.len())
+                .map_err(|_| StoreError::Custom("Genesis account code too large".to_string()))?;
+            binary_trie.insert(
+                &get_tree_key_for_basic_data(&address),
+                basic_data_leaf(code_size, account.nonce, account.balance),
+            )?;
+            binary_trie.insert(&get_tree_key_for_code_hash(&address), code_hash.0)?;
+            for (chunk_id, chunk) in (0..).zip(chunkify_code(&account.code)) {
+                binary_trie.insert(&get_tree_key_for_code_chunk(&address, chunk_id), chunk)?;
+            }
+            for (storage_key, storage_value) in account.storage {
+                if !storage_value.is_zero() {
+                    binary_trie.insert(
+                        &get_tree_key_for_storage_slot(&address, storage_key),
+                        storage_value.to_big_endian(),
+                    )?;
+                }
+            }
+            self.add_account_code(code_hash, account.code).await?;
+        }
+        binary_trie.commit().map_err(StoreError::Trie)
+    }
+
     pub async fn add_receipt(
         &self,
         block_hash: BlockHash,
@@ -611,6 +710,14 @@ impl Store {
                     .await?
             }
         }
+        #[cfg(feature = "binary-trie")]
+        if self.binary_trie {
+            let binary_root = self
+                .setup_genesis_binary_trie(genesis.alloc.clone())
+                .await?;
+            info!("Stored the genesis state in a binary trie with root {binary_root:#x}");
+        }
+
         // Store genesis accounts
         // TODO: Should we use this root instead of computing it before the block hash check?
         let genesis_state_root = self.setup_genesis_state_trie(genesis.alloc).await?;
@@ -758,6 +865,24 @@ impl Store {
         self.engine.get_pending_block_number().await
     }
 
+    pub async fn set_skeleton_sync_progress(
+        &self,
+        head: BlockHash,
+        tail: BlockHash,
+    ) -> Result<(), StoreError> {
+        self.engine.set_skeleton_sync_progress(head, tail).await
+    }
+
+    pub async fn get_skeleton_sync_progress(
+        &self,
+    ) -> Result<Option<(BlockHash, BlockHash)>, StoreError> {
+        self.engine.get_skeleton_sync_progress().await
+    }
+
+    pub async fn clear_skeleton_sync_progress(&self) -> Result<(), StoreError> {
+        self.engine.clear_skeleton_sync_progress().await
+    }
+
     pub async fn get_canonical_block_hash(
         &self,
         block_number: BlockNumber,
@@ -1237,7 +1362,7 @@ impl Store {
         self.engine.get_storage_trie_rebuild_pending().await
     }
 
-    /// Clears the state and storage snapshots
+    /// Clears the state and storage snapshots, along with the snapshot root
     pub async fn clear_snapshot(&self) -> Result<(), StoreError> {
         self.engine.clear_snapshot().await
     }
@@ -1259,6 +1384,22 @@ impl Store {
         self.engine.read_storage_snapshot(account_hash, start).await
     }
 
+    /// Marks the state and storage snapshots as holding the whole state of the given block
+    pub async fn set_state_snapshot_root(
+        &self,
+        block_number: BlockNumber,
+        state_root: H256,
+    ) -> Result<(), StoreError> {
+        self.engine
+            .set_state_snapshot_root(block_number, state_root)
+            .await
+    }
+
+    /// Returns the number and state root of the block whose whole state is held by the snapshots, if any
+    pub async fn get_state_snapshot_root(&self) -> Result<Option<(BlockNumber, H256)>, StoreError> {
+        self.engine.get_state_snapshot_root().await
+    }
+
     /// Fetches the latest valid ancestor for a block that was previously marked as invalid
     /// Returns None if the block was never marked as invalid
     pub async fn get_latest_valid_ancestor(
@@ -1416,6 +1557,7 @@ mod tests {
         run_test(test_store_block_receipt, engine_type).await;
         run_test(test_store_account_code, engine_type).await;
         run_test(test_store_block_tags, engine_type).await;
+        run_test(test_skeleton_sync_progress, engine_type).await;
         run_test(test_chain_config_storage, engine_type).await;
         run_test(test_genesis_block, engine_type).await;
     }
@@ -1448,6 +1590,59 @@ mod tests {
         );
     }
 
+    #[cfg(feature = "binary-trie")]
+    #[tokio::test]
+    async fn test_genesis_binary_trie() {
+        use ethrex_trie::{
+            NodeHash,
+            binary::{BinaryTrie, get_tree_key_for_basic_data, verify_proof},
+        };
+
+        const GENESIS_L1_DEV: &str = include_str!("../../fixtures/genesis/l1-dev.json");
+        let genesis: Genesis =
+            serde_json::from_str(GENESIS_L1_DEV).expect("deserialize l1-dev.json");
+        let store = Store::new("", EngineType::InMemory)
+            .expect("Failed to create test db")
+            .with_binary_trie(true);
+        store
+            .add_initial_state(genesis.clone())
+            .await
+            .expect("genesis with binary trie");
+        // The nodes are already stored, building the trie again gives the same root
+        let root = store
+            .setup_genesis_binary_trie(genesis.alloc.clone())
+            .await
+            .expect("binary trie genesis");
+
+        let binary_trie = BinaryTrie::open(
+            store
+                .engine
+                .open_binary_trie_db()
+                .expect("open binary trie"),
+            root,
+        );
+        let (address, account) = genesis.alloc.first_key_value().expect("genesis accounts");
+        let key = get_tree_key_for_basic_data(address);
+        let proof = binary_trie.get_proof(&key).expect("binary trie proof");
+        let basic_data = verify_proof(root, &key, &proof)
+            .expect("valid proof")
+            .expect("account in the binary trie");
+        assert_eq!(U256::from_big_endian(&basic_data[16..]), account.balance);
+
+        // The binary trie nodes aren't mixed with the state trie ones
+        let state_trie_db = store
+            .engine
+            .open_state_trie(*EMPTY_TRIE_HASH)
+            .expect("open state trie")
+            .into_db();
+        assert!(
+            state_trie_db
+                .get(NodeHash::Hashed(root))
+                .expect("state trie node")
+                .is_none()
+        );
+    }
+
     fn remove_test_dbs(path: &str) {
         // Removes all test databases from filesystem
         if std::path::Path::new(path).exists() {
@@ -1667,6 +1862,21 @@ mod tests {
         assert_eq!(stored_code, code);
     }
 
+    async fn test_skeleton_sync_progress(store: Store) {
+        assert_eq!(store.get_skeleton_sync_progress().await.unwrap(), None);
+
+        let head = H256::random();
+        let tail = H256::random();
+        store.set_skeleton_sync_progress(head, tail).await.unwrap();
+        assert_eq!(
+            store.get_skeleton_sync_progress().await.unwrap(),
+            Some((head, tail))
+        );
+
+        store.clear_skeleton_sync_progress().await.unwrap();
+        assert_eq!(store.get_skeleton_sync_progress().await.unwrap(), None);
+    }
+
     async fn test_store_block_tags(store: Store) {
         let earliest_block_number = 0;
         let finalized_block_number = 7;
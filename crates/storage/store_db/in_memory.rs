@@ -10,7 +10,7 @@ use ethrex_common::types::{
     AccountState, Block, BlockBody, BlockHash, BlockHeader, BlockNumber, ChainConfig, Index,
     Receipt, payload::PayloadBundle,
 };
-use ethrex_trie::{InMemoryTrieDB, Nibbles, NodeHash, Trie};
+use ethrex_trie::{InMemoryTrieDB, Nibbles, NodeHash, Trie, TrieDB};
 use std::{
     collections::{BTreeMap, HashMap},
     fmt::Debug,
@@ -36,9 +36,13 @@ struct StoreInner {
     state_trie_nodes: NodeMap,
     // A storage trie for each hashed account address
     storage_trie_nodes: HashMap<H256, NodeMap>,
+    // Nodes of the binary trie prototype
+    binary_trie_nodes: NodeMap,
     // Stores local blocks by payload id
     payloads: HashMap<u64, PayloadBundle>,
     pending_blocks: HashMap<BlockHash, Block>,
+    // Serialized execution witnesses of the blocks at each height
+    execution_witnesses: HashMap<BlockNumber, HashMap<BlockHash, Vec<u8>>>,
     // Stores invalid blocks and their latest valid ancestor
     invalid_ancestors: HashMap<BlockHash, BlockHash>,
     // Stores current Snap Sate
@@ -57,6 +61,8 @@ struct ChainData {
     safe_block_number: Option<BlockNumber>,
     latest_block_number: Option<BlockNumber>,
     pending_block_number: Option<BlockNumber>,
+    skeleton_sync_progress: Option<(BlockHash, BlockHash)>,
+    state_snapshot_root: Option<(BlockNumber, H256)>,
 }
 
 // Keeps track of the state left by the latest snap attempt
@@ -217,6 +223,43 @@ impl StoreEngine for Store {
         Ok(self.inner()?.pending_blocks.get(&block_hash).cloned())
     }
 
+    async fn add_execution_witness(
+        &self,
+        block_number: BlockNumber,
+        block_hash: BlockHash,
+        witness: Vec<u8>,
+    ) -> Result<(), StoreError> {
+        self.inner()?
+            .execution_witnesses
+            .entry(block_number)
+            .or_default()
+            .insert(block_hash, witness);
+        Ok(())
+    }
+
+    async fn get_execution_witness(
+        &self,
+        block_number: BlockNumber,
+        block_hash: BlockHash,
+    ) -> Result<Option<Vec<u8>>, StoreError> {
+        Ok(self
+            .inner()?
+            .execution_witnesses
+            .get(&block_number)
+            .and_then(|witnesses| witnesses.get(&block_hash))
+            .cloned())
+    }
+
+    async fn remove_execution_witnesses_up_to(
+        &self,
+        block_number: BlockNumber,
+    ) -> Result<(), StoreError> {
+        self.inner()?
+            .execution_witnesses
+            .retain(|number, _| *number > block_number);
+        Ok(())
+    }
+
     async fn add_block_header(
         &self,
         block_hash: BlockHash,
@@ -404,6 +447,29 @@ impl StoreEngine for Store {
         Ok(self.inner()?.chain_data.pending_block_number)
     }
 
+    async fn set_skeleton_sync_progress(
+        &self,
+        head: BlockHash,
+        tail: BlockHash,
+    ) -> Result<(), StoreError> {
+        self.inner()?
+            .chain_data
+            .skeleton_sync_progress
+            .replace((head, tail));
+        Ok(())
+    }
+
+    async fn get_skeleton_sync_progress(
+        &self,
+    ) -> Result<Option<(BlockHash, BlockHash)>, StoreError> {
+        Ok(self.inner()?.chain_data.skeleton_sync_progress)
+    }
+
+    async fn clear_skeleton_sync_progress(&self) -> Result<(), StoreError> {
+        self.inner()?.chain_data.skeleton_sync_progress = None;
+        Ok(())
+    }
+
     fn open_storage_trie(
         &self,
         hashed_address: H256,
@@ -421,6 +487,11 @@ impl StoreEngine for Store {
         Ok(Trie::open(db, state_root))
     }
 
+    fn open_binary_trie_db(&self) -> Result<Box<dyn TrieDB>, StoreError> {
+        let trie_backend = self.inner()?.binary_trie_nodes.clone();
+        Ok(Box::new(InMemoryTrieDB::new(trie_backend)))
+    }
+
     async fn get_block_body_by_hash(
         &self,
         block_hash: BlockHash,
@@ -676,8 +747,12 @@ impl StoreEngine for Store {
     }
 
     async fn clear_snapshot(&self) -> Result<(), StoreError> {
-        self.inner()?.snap_state.state_trie_rebuild_checkpoint = None;
-        self.inner()?.snap_state.storage_trie_rebuild_pending = None;
+        let mut store = self.inner()?;
+        store.snap_state.state_trie_rebuild_checkpoint = None;
+        store.snap_state.storage_trie_rebuild_pending = None;
+        store.state_snapshot.clear();
+        store.storage_snapshot.clear();
+        store.chain_data.state_snapshot_root = None;
         Ok(())
     }
 
@@ -688,8 +763,7 @@ impl StoreEngine for Store {
         Ok(self
             .inner()?
             .state_snapshot
-            .iter()
-            .filter(|(hash, _)| **hash < start)
+            .range(start..)
             .take(MAX_SNAPSHOT_READS)
             .map(|(h, a)| (*h, a.clone()))
             .collect())
@@ -697,13 +771,12 @@ impl StoreEngine for Store {
 
     async fn read_storage_snapshot(
         &self,
-        start: H256,
         account_hash: H256,
+        start: H256,
     ) -> Result<Vec<(H256, U256)>, StoreError> {
         if let Some(snapshot) = self.inner()?.storage_snapshot.get(&account_hash) {
             Ok(snapshot
-                .iter()
-                .filter(|(hash, _)| **hash < start)
+                .range(start..)
                 .take(MAX_SNAPSHOT_READS)
                 .map(|(k, v)| (*k, *v))
                 .collect())
@@ -712,6 +785,22 @@ impl StoreEngine for Store {
         }
     }
 
+    async fn set_state_snapshot_root(
+        &self,
+        block_number: BlockNumber,
+        state_root: H256,
+    ) -> Result<(), StoreError> {
+        self.inner()?
+            .chain_data
+            .state_snapshot_root
+            .replace((block_number, state_root));
+        Ok(())
+    }
+
+    async fn get_state_snapshot_root(&self) -> Result<Option<(BlockNumber, H256)>, StoreError> {
+        Ok(self.inner()?.chain_data.state_snapshot_root)
+    }
+
     async fn set_storage_trie_rebuild_pending(
         &self,
         pending: Vec<(H256, H256)>,
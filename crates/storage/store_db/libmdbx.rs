@@ -3,8 +3,8 @@ use crate::api::StoreEngine;
 use crate::error::StoreError;
 use crate::rlp::{
     AccountCodeHashRLP, AccountCodeRLP, AccountHashRLP, AccountStateRLP, BlockBodyRLP,
-    BlockHashRLP, BlockHeaderRLP, BlockRLP, PayloadBundleRLP, Rlp, TransactionHashRLP,
-    TriePathsRLP, TupleRLP,
+    BlockHashRLP, BlockHeaderRLP, BlockRLP, ExecutionWitnessesRLP, PayloadBundleRLP, Rlp,
+    TransactionHashRLP, TriePathsRLP, TupleRLP,
 };
 use crate::store::{MAX_SNAPSHOT_READS, STATE_TRIE_SEGMENTS};
 use crate::trie_db::libmdbx::LibmdbxTrieDB;
@@ -21,7 +21,7 @@ use ethrex_common::utils::u256_to_big_endian;
 use ethrex_rlp::decode::RLPDecode;
 use ethrex_rlp::encode::RLPEncode;
 use ethrex_rlp::error::RLPDecodeError;
-use ethrex_trie::{Nibbles, NodeHash, Trie};
+use ethrex_trie::{Nibbles, NodeHash, Trie, TrieDB};
 use libmdbx::orm::{Decodable, DupSort, Encodable, Table};
 use libmdbx::{DatabaseOptions, Mode, PageSize, ReadWriteOptions, TransactionKind};
 use libmdbx::{
@@ -563,6 +563,40 @@ impl StoreEngine for Store {
         }
     }
 
+    async fn set_skeleton_sync_progress(
+        &self,
+        head: BlockHash,
+        tail: BlockHash,
+    ) -> Result<(), StoreError> {
+        self.write::<ChainData>(
+            ChainDataIndex::SkeletonSyncProgress,
+            (head, tail).encode_to_vec(),
+        )
+        .await
+    }
+
+    async fn get_skeleton_sync_progress(
+        &self,
+    ) -> Result<Option<(BlockHash, BlockHash)>, StoreError> {
+        self.read::<ChainData>(ChainDataIndex::SkeletonSyncProgress)
+            .await?
+            .map(|ref rlp| RLPDecode::decode(rlp))
+            .transpose()
+            .map_err(StoreError::RLPDecode)
+    }
+
+    async fn clear_skeleton_sync_progress(&self) -> Result<(), StoreError> {
+        let db = self.db.clone();
+        tokio::task::spawn_blocking(move || {
+            let txn = db.begin_readwrite().map_err(StoreError::LibmdbxError)?;
+            txn.delete::<ChainData>(ChainDataIndex::SkeletonSyncProgress, None)
+                .map_err(StoreError::LibmdbxError)?;
+            txn.commit().map_err(StoreError::LibmdbxError)
+        })
+        .await
+        .map_err(|e| StoreError::Custom(format!("task panicked: {e}")))?
+    }
+
     fn open_storage_trie(
         &self,
         hashed_address: H256,
@@ -580,6 +614,12 @@ impl StoreEngine for Store {
         Ok(Trie::open(db, state_root))
     }
 
+    fn open_binary_trie_db(&self) -> Result<Box<dyn TrieDB>, StoreError> {
+        Ok(Box::new(LibmdbxTrieDB::<BinaryTrieNodes>::new(
+            self.db.clone(),
+        )))
+    }
+
     async fn get_canonical_block_hash(
         &self,
         number: BlockNumber,
@@ -731,6 +771,75 @@ impl StoreEngine for Store {
             .map_err(StoreError::from)
     }
 
+    async fn add_execution_witness(
+        &self,
+        block_number: BlockNumber,
+        block_hash: BlockHash,
+        witness: Vec<u8>,
+    ) -> Result<(), StoreError> {
+        let db = self.db.clone();
+        tokio::task::spawn_blocking(move || {
+            let txn = db.begin_readwrite().map_err(StoreError::LibmdbxError)?;
+            let mut witnesses = txn
+                .get::<ExecutionWitnesses>(block_number)
+                .map_err(StoreError::LibmdbxError)?
+                .map(|witnesses| witnesses.to())
+                .transpose()?
+                .unwrap_or_default();
+            witnesses.retain(|(hash, _)| *hash != block_hash);
+            witnesses.push((block_hash, witness.into()));
+            txn.upsert::<ExecutionWitnesses>(block_number, witnesses.into())
+                .map_err(StoreError::LibmdbxError)?;
+            txn.commit().map_err(StoreError::LibmdbxError)
+        })
+        .await
+        .map_err(|e| StoreError::Custom(format!("task panicked: {e}")))?
+    }
+
+    async fn get_execution_witness(
+        &self,
+        block_number: BlockNumber,
+        block_hash: BlockHash,
+    ) -> Result<Option<Vec<u8>>, StoreError> {
+        let Some(witnesses) = self.read::<ExecutionWitnesses>(block_number).await? else {
+            return Ok(None);
+        };
+        Ok(witnesses
+            .to()?
+            .into_iter()
+            .find(|(hash, _)| *hash == block_hash)
+            .map(|(_, witness)| witness.to_vec()))
+    }
+
+    async fn remove_execution_witnesses_up_to(
+        &self,
+        block_number: BlockNumber,
+    ) -> Result<(), StoreError> {
+        let db = self.db.clone();
+        tokio::task::spawn_blocking(move || {
+            let txn = db.begin_readwrite().map_err(StoreError::LibmdbxError)?;
+            let mut expired = Vec::new();
+            {
+                let cursor = txn
+                    .cursor::<ExecutionWitnesses>()
+                    .map_err(StoreError::LibmdbxError)?;
+                let mut cursor_it = cursor.walk(None);
+                while let Some(Ok((number, _))) = cursor_it.next() {
+                    if number <= block_number {
+                        expired.push(number);
+                    }
+                }
+            }
+            for number in expired {
+                txn.delete::<ExecutionWitnesses>(number, None)
+                    .map_err(StoreError::LibmdbxError)?;
+            }
+            txn.commit().map_err(StoreError::LibmdbxError)
+        })
+        .await
+        .map_err(|e| StoreError::Custom(format!("task panicked: {e}")))?
+    }
+
     async fn add_transaction_locations(
         &self,
         locations: Vec<(H256, BlockNumber, BlockHash, Index)>,
@@ -1022,6 +1131,8 @@ impl StoreEngine for Store {
                 .map_err(StoreError::LibmdbxError)?;
             txn.clear_table::<StorageSnapShot>()
                 .map_err(StoreError::LibmdbxError)?;
+            txn.delete::<ChainData>(ChainDataIndex::StateSnapshotRoot, None)
+                .map_err(StoreError::LibmdbxError)?;
             txn.commit().map_err(StoreError::LibmdbxError)?;
             Ok(())
         })
@@ -1066,6 +1177,26 @@ impl StoreEngine for Store {
         Ok(iter.collect::<Vec<_>>())
     }
 
+    async fn set_state_snapshot_root(
+        &self,
+        block_number: BlockNumber,
+        state_root: H256,
+    ) -> Result<(), StoreError> {
+        self.write::<ChainData>(
+            ChainDataIndex::StateSnapshotRoot,
+            (block_number, state_root).encode_to_vec(),
+        )
+        .await
+    }
+
+    async fn get_state_snapshot_root(&self) -> Result<Option<(BlockNumber, H256)>, StoreError> {
+        self.read::<ChainData>(ChainDataIndex::StateSnapshotRoot)
+            .await?
+            .map(|ref rlp| RLPDecode::decode(rlp))
+            .transpose()
+            .map_err(StoreError::RLPDecode)
+    }
+
     async fn get_latest_valid_ancestor(
         &self,
         block: BlockHash,
@@ -1264,6 +1395,11 @@ table!(
     ( StateTrieNodes ) NodeHash => Vec<u8>
 );
 
+table!(
+    /// binary trie nodes, by their SHA-256 hash
+    ( BinaryTrieNodes ) NodeHash => Vec<u8>
+);
+
 // Local Blocks
 
 table!(
@@ -1276,6 +1412,11 @@ table!(
     ( PendingBlocks ) BlockHashRLP => BlockRLP
 );
 
+table!(
+    /// Execution witnesses recorded while importing blocks, by block number.
+    ( ExecutionWitnesses ) BlockNumber => ExecutionWitnessesRLP
+);
+
 table!(
     /// State Snapshot used by an ongoing sync process
     ( StateSnapShot ) AccountHashRLP => AccountStateRLP
@@ -1392,9 +1533,11 @@ pub fn init_db(path: Option<impl AsRef<Path>>) -> anyhow::Result<Database> {
         table_info!(ChainData),
         table_info!(StateTrieNodes),
         table_info!(StorageTriesNodes),
+        table_info!(BinaryTrieNodes),
         table_info!(CanonicalBlockHashes),
         table_info!(Payloads),
         table_info!(PendingBlocks),
+        table_info!(ExecutionWitnesses),
         table_info!(SnapState),
         table_info!(StateSnapShot),
         table_info!(StorageSnapShot),
@@ -1,8 +1,12 @@
 use crate::rlp::{
-    AccountHashRLP, AccountStateRLP, BlockRLP, Rlp, TransactionHashRLP, TriePathsRLP,
+    AccountHashRLP, AccountStateRLP, BlockRLP, ExecutionWitnessesRLP, Rlp, TransactionHashRLP,
+    TriePathsRLP,
 };
 use crate::store::MAX_SNAPSHOT_READS;
-use crate::trie_db::{redb::RedBTrie, redb_multitable::RedBMultiTableTrieDB};
+use crate::trie_db::{
+    redb::{BINARY_TRIE_NODES_TABLE, RedBTrie},
+    redb_multitable::RedBMultiTableTrieDB,
+};
 use crate::{
     error::StoreError,
     rlp::{
@@ -54,6 +58,8 @@ const PAYLOADS_TABLE: TableDefinition<BlockNumber, PayloadBundleRLP> =
     TableDefinition::new("Payloads");
 const PENDING_BLOCKS_TABLE: TableDefinition<BlockHashRLP, BlockRLP> =
     TableDefinition::new("PendingBlocks");
+const EXECUTION_WITNESSES_TABLE: TableDefinition<BlockNumber, ExecutionWitnessesRLP> =
+    TableDefinition::new("ExecutionWitnesses");
 const TRANSACTION_LOCATIONS_TABLE: MultimapTableDefinition<
     TransactionHashRLP,
     Rlp<(BlockNumber, BlockHash, Index)>,
@@ -579,6 +585,71 @@ impl StoreEngine for RedBStore {
         .map_err(StoreError::from)
     }
 
+    async fn add_execution_witness(
+        &self,
+        block_number: BlockNumber,
+        block_hash: BlockHash,
+        witness: Vec<u8>,
+    ) -> Result<(), StoreError> {
+        let db = self.db.clone();
+        tokio::task::spawn_blocking(move || {
+            let write_txn = db.begin_write().map_err(Box::new)?;
+            {
+                let mut table = write_txn.open_table(EXECUTION_WITNESSES_TABLE)?;
+                let mut witnesses = table
+                    .get(block_number)?
+                    .map(|witnesses| witnesses.value().to())
+                    .transpose()?
+                    .unwrap_or_default();
+                witnesses.retain(|(hash, _)| *hash != block_hash);
+                witnesses.push((block_hash, witness.into()));
+                table.insert(
+                    block_number,
+                    <Vec<(BlockHash, bytes::Bytes)> as Into<ExecutionWitnessesRLP>>::into(
+                        witnesses,
+                    ),
+                )?;
+            }
+            write_txn.commit()?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| StoreError::Custom(format!("task panicked: {e}")))?
+    }
+
+    async fn get_execution_witness(
+        &self,
+        block_number: BlockNumber,
+        block_hash: BlockHash,
+    ) -> Result<Option<Vec<u8>>, StoreError> {
+        let Some(witnesses) = self.read(EXECUTION_WITNESSES_TABLE, block_number).await? else {
+            return Ok(None);
+        };
+        Ok(witnesses
+            .value()
+            .to()?
+            .into_iter()
+            .find(|(hash, _)| *hash == block_hash)
+            .map(|(_, witness)| witness.to_vec()))
+    }
+
+    async fn remove_execution_witnesses_up_to(
+        &self,
+        block_number: BlockNumber,
+    ) -> Result<(), StoreError> {
+        let db = self.db.clone();
+        tokio::task::spawn_blocking(move || {
+            let write_txn = db.begin_write().map_err(Box::new)?;
+            write_txn
+                .open_table(EXECUTION_WITNESSES_TABLE)?
+                .retain_in(..=block_number, |_, _| false)?;
+            write_txn.commit()?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| StoreError::Custom(format!("task panicked: {e}")))?
+    }
+
     async fn add_block_number(
         &self,
         block_hash: BlockHash,
@@ -832,6 +903,38 @@ impl StoreEngine for RedBStore {
         }
     }
 
+    async fn set_skeleton_sync_progress(
+        &self,
+        head: BlockHash,
+        tail: BlockHash,
+    ) -> Result<(), StoreError> {
+        self.write(
+            CHAIN_DATA_TABLE,
+            ChainDataIndex::SkeletonSyncProgress,
+            (head, tail).encode_to_vec(),
+        )
+        .await
+    }
+
+    async fn get_skeleton_sync_progress(
+        &self,
+    ) -> Result<Option<(BlockHash, BlockHash)>, StoreError> {
+        self.read(CHAIN_DATA_TABLE, ChainDataIndex::SkeletonSyncProgress)
+            .await?
+            .map(|rlp| RLPDecode::decode(&rlp.value()))
+            .transpose()
+            .map_err(StoreError::RLPDecode)
+    }
+
+    async fn clear_skeleton_sync_progress(&self) -> Result<(), StoreError> {
+        let write_txn = self.db.begin_write().map_err(Box::new)?;
+        write_txn
+            .open_table(CHAIN_DATA_TABLE)?
+            .remove(ChainDataIndex::SkeletonSyncProgress)?;
+        write_txn.commit()?;
+        Ok(())
+    }
+
     fn open_storage_trie(
         &self,
         hashed_address: ethrex_common::H256,
@@ -849,6 +952,10 @@ impl StoreEngine for RedBStore {
         Ok(Trie::open(db, state_root))
     }
 
+    fn open_binary_trie_db(&self) -> Result<Box<dyn ethrex_trie::TrieDB>, StoreError> {
+        Ok(Box::new(RedBTrie::new_binary(self.db.clone())))
+    }
+
     async fn forkchoice_update(
         &self,
         new_canonical_blocks: Option<Vec<(BlockNumber, BlockHash)>>,
@@ -1243,6 +1350,9 @@ impl StoreEngine for RedBStore {
         let write_tx = self.db.begin_write().map_err(Box::new)?;
         write_tx.delete_table(STATE_SNAPSHOT_TABLE)?;
         write_tx.delete_multimap_table(STORAGE_SNAPSHOT_TABLE)?;
+        write_tx
+            .open_table(CHAIN_DATA_TABLE)?
+            .remove(ChainDataIndex::StateSnapshotRoot)?;
         write_tx.commit()?;
         Ok(())
     }
@@ -1254,11 +1364,11 @@ impl StoreEngine for RedBStore {
         let read_tx = self.db.begin_read().map_err(Box::new)?;
         let table = read_tx.open_table(STATE_SNAPSHOT_TABLE)?;
         let mut table_vec = Vec::new();
-        while let Some(Ok((key, value))) = table
+        for entry in table
             .range(<H256 as Into<AccountHashRLP>>::into(start)..)?
             .take(MAX_SNAPSHOT_READS)
-            .next()
         {
+            let (key, value) = entry?;
             table_vec.push((key.value().to()?, value.value().to()?));
         }
 
@@ -1267,27 +1377,45 @@ impl StoreEngine for RedBStore {
 
     async fn read_storage_snapshot(
         &self,
-        start: H256,
         account_hash: H256,
+        start: H256,
     ) -> Result<Vec<(H256, U256)>, StoreError> {
         let read_tx = self.db.begin_read().map_err(Box::new)?;
         let table = read_tx.open_multimap_table(STORAGE_SNAPSHOT_TABLE)?;
         Ok(table
             .get(<H256 as Into<AccountHashRLP>>::into(account_hash))?
             .map_while(|elem| {
-                elem.ok().and_then(|entry| {
+                elem.ok().map(|entry| {
                     let (key, val) = entry.value();
-                    if H256(key) < start {
-                        None
-                    } else {
-                        Some((H256(key), U256::from_big_endian(&val)))
-                    }
+                    (H256(key), U256::from_big_endian(&val))
                 })
             })
+            .skip_while(|(key, _)| *key < start)
             .take(MAX_SNAPSHOT_READS)
             .collect())
     }
 
+    async fn set_state_snapshot_root(
+        &self,
+        block_number: BlockNumber,
+        state_root: H256,
+    ) -> Result<(), StoreError> {
+        self.write(
+            CHAIN_DATA_TABLE,
+            ChainDataIndex::StateSnapshotRoot,
+            (block_number, state_root).encode_to_vec(),
+        )
+        .await
+    }
+
+    async fn get_state_snapshot_root(&self) -> Result<Option<(BlockNumber, H256)>, StoreError> {
+        self.read(CHAIN_DATA_TABLE, ChainDataIndex::StateSnapshotRoot)
+            .await?
+            .map(|rlp| RLPDecode::decode(&rlp.value()))
+            .transpose()
+            .map_err(StoreError::RLPDecode)
+    }
+
     async fn get_latest_valid_ancestor(
         &self,
         block: BlockHash,
@@ -1407,10 +1535,12 @@ pub fn init_db() -> Result<Database, StoreError> {
     table_creation_txn.open_table(CANONICAL_BLOCK_HASHES_TABLE)?;
     table_creation_txn.open_table(RECEIPTS_TABLE)?;
     table_creation_txn.open_multimap_table(STORAGE_TRIE_NODES_TABLE)?;
+    table_creation_txn.open_table(BINARY_TRIE_NODES_TABLE)?;
     table_creation_txn.open_table(CHAIN_DATA_TABLE)?;
     table_creation_txn.open_table(BLOCK_BODIES_TABLE)?;
     table_creation_txn.open_table(PAYLOADS_TABLE)?;
     table_creation_txn.open_table(PENDING_BLOCKS_TABLE)?;
+    table_creation_txn.open_table(EXECUTION_WITNESSES_TABLE)?;
     table_creation_txn.open_table(INVALID_ANCESTORS_TABLE)?;
     table_creation_txn.open_multimap_table(TRANSACTION_LOCATIONS_TABLE)?;
     table_creation_txn.open_table(SNAP_STATE_TABLE)?;
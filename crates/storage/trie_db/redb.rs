@@ -4,14 +4,25 @@ use ethrex_trie::{NodeHash, TrieDB, TrieError};
 use redb::{Database, TableDefinition};
 
 const TABLE: TableDefinition<&[u8], &[u8]> = TableDefinition::new("StateTrieNodes");
+pub const BINARY_TRIE_NODES_TABLE: TableDefinition<&[u8], &[u8]> =
+    TableDefinition::new("BinaryTrieNodes");
 
 pub struct RedBTrie {
     db: Arc<Database>,
+    table: TableDefinition<'static, &'static [u8], &'static [u8]>,
 }
 
 impl RedBTrie {
     pub fn new(db: Arc<Database>) -> Self {
-        Self { db }
+        Self { db, table: TABLE }
+    }
+
+    /// Trie database over the nodes of the binary trie prototype
+    pub fn new_binary(db: Arc<Database>) -> Self {
+        Self {
+            db,
+            table: BINARY_TRIE_NODES_TABLE,
+        }
     }
 }
 
@@ -22,7 +33,7 @@ impl TrieDB for RedBTrie {
             .begin_read()
             .map_err(|e| TrieError::DbError(e.into()))?;
         let table = read_txn
-            .open_table(TABLE)
+            .open_table(self.table)
             .map_err(|e| TrieError::DbError(e.into()))?;
         Ok(table
             .get(key.as_ref())
@@ -37,7 +48,7 @@ impl TrieDB for RedBTrie {
             .map_err(|e| TrieError::DbError(e.into()))?;
         {
             let mut table = write_txn
-                .open_table(TABLE)
+                .open_table(self.table)
                 .map_err(|e| TrieError::DbError(e.into()))?;
             for (key, value) in key_values {
                 table
@@ -8,6 +8,10 @@ pub enum ChainDataIndex {
     SafeBlockNumber = 3,
     LatestBlockNumber = 4,
     PendingBlockNumber = 5,
+    // Head and tail of the header skeleton being filled during a beacon sync
+    SkeletonSyncProgress = 6,
+    // Number and state root of the block whose whole state is held by the state and storage snapshots
+    StateSnapshotRoot = 7,
 }
 
 impl From<u8> for ChainDataIndex {
@@ -25,6 +29,10 @@ impl From<u8> for ChainDataIndex {
             x if x == ChainDataIndex::PendingBlockNumber as u8 => {
                 ChainDataIndex::PendingBlockNumber
             }
+            x if x == ChainDataIndex::SkeletonSyncProgress as u8 => {
+                ChainDataIndex::SkeletonSyncProgress
+            }
+            x if x == ChainDataIndex::StateSnapshotRoot as u8 => ChainDataIndex::StateSnapshotRoot,
             _ => panic!("Invalid value when casting to ChainDataIndex: {value}"),
         }
     }
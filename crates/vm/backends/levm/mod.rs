@@ -1,6 +1,9 @@
 pub mod db;
+mod parallel;
 mod tracing;
 
+pub use parallel::SpeculativeExecution;
+
 use super::BlockExecutionResult;
 use crate::constants::{
     BEACON_ROOTS_ADDRESS, CONSOLIDATION_REQUEST_PREDEPLOY_ADDRESS, HISTORY_STORAGE_ADDRESS,
@@ -11,15 +14,17 @@ use bytes::Bytes;
 use ethrex_common::{
     Address, H256, U256,
     types::{
-        AccessList, AccountUpdate, AuthorizationTuple, Block, BlockHeader, EIP1559Transaction,
-        EIP7702Transaction, Fork, GWEI_TO_WEI, GenericTransaction, INITIAL_BASE_FEE, Receipt,
-        Transaction, TxKind, Withdrawal, requests::Requests,
+        AccessList, AccountUpdate, AuthorizationTuple, Block, BlockHeader, ChainConfig,
+        EIP1559Transaction, EIP7702Transaction, Fork, GWEI_TO_WEI, GenericTransaction,
+        INITIAL_BASE_FEE, Receipt, Transaction, TxKind, Withdrawal, requests::Requests,
     },
 };
 use ethrex_levm::EVMConfig;
 use ethrex_levm::constants::{SYS_CALL_GAS_LIMIT, TX_BASE_COST};
 use ethrex_levm::db::gen_db::GeneralizedDatabase;
 use ethrex_levm::errors::{InternalError, TxValidationError};
+use ethrex_levm::inspector::Inspector;
+use ethrex_levm::precompile_registry::PrecompileRegistry;
 use ethrex_levm::tracing::LevmCallTracer;
 use ethrex_levm::vm::VMType;
 use ethrex_levm::{
@@ -32,6 +37,8 @@ use std::collections::BTreeMap;
 
 /// The struct implements the following functions:
 /// [LEVM::execute_block]
+/// [LEVM::execute_block_parallel]
+/// [LEVM::inspect_block]
 /// [LEVM::execute_tx]
 /// [LEVM::get_state_transitions]
 /// [LEVM::process_withdrawals]
@@ -43,16 +50,69 @@ impl LEVM {
         block: &Block,
         db: &mut GeneralizedDatabase,
         vm_type: VMType,
+    ) -> Result<BlockExecutionResult, EvmError> {
+        Self::execute_block_with_speculation(block, db, vm_type, false, None)
+    }
+
+    /// Same as [LEVM::execute_block], but the transactions are first executed speculatively in parallel.
+    /// The results are the same as the sequential execution, transactions that conflict are re-executed.
+    pub fn execute_block_parallel(
+        block: &Block,
+        db: &mut GeneralizedDatabase,
+        vm_type: VMType,
+    ) -> Result<BlockExecutionResult, EvmError> {
+        Self::execute_block_with_speculation(block, db, vm_type, true, None)
+    }
+
+    /// Same as [LEVM::execute_block], notifying the inspector of every step of the block's transactions.
+    pub fn inspect_block(
+        block: &Block,
+        db: &mut GeneralizedDatabase,
+        vm_type: VMType,
+        inspector: &mut dyn Inspector,
+    ) -> Result<BlockExecutionResult, EvmError> {
+        Self::execute_block_with_speculation(block, db, vm_type, false, Some(inspector))
+    }
+
+    fn execute_block_with_speculation(
+        block: &Block,
+        db: &mut GeneralizedDatabase,
+        vm_type: VMType,
+        speculate: bool,
+        mut inspector: Option<&mut dyn Inspector>,
     ) -> Result<BlockExecutionResult, EvmError> {
         Self::prepare_block(block, db, vm_type)?;
 
         let mut receipts = Vec::new();
         let mut cumulative_gas_used = 0;
 
-        for (tx, tx_sender) in block.body.get_transactions_with_sender().map_err(|error| {
+        let transactions = block.body.get_transactions_with_sender().map_err(|error| {
             EvmError::Transaction(format!("Couldn't recover addresses with error: {error}"))
-        })? {
-            let report = Self::execute_tx(tx, tx_sender, &block.header, db, vm_type)?;
+        })?;
+        let mut speculations = if speculate {
+            parallel::execute_speculatively(&transactions, &block.header, db, vm_type)
+        } else {
+            Vec::new()
+        }
+        .into_iter();
+
+        for (tx, tx_sender) in transactions {
+            let report = match inspector.as_deref_mut() {
+                Some(inspector) => {
+                    let env = Self::setup_env(tx, tx_sender, &block.header, db, vm_type)?;
+                    VM::new(env, db, tx, LevmCallTracer::disabled(), vm_type)?
+                        .with_inspector(Box::new(inspector))
+                        .execute()?
+                }
+                None => Self::execute_speculated_tx(
+                    tx,
+                    tx_sender,
+                    &block.header,
+                    db,
+                    vm_type,
+                    speculations.next().flatten(),
+                )?,
+            };
 
             cumulative_gas_used += report.gas_used;
             let receipt = Receipt::new(
@@ -80,11 +140,12 @@ impl LEVM {
         Ok(BlockExecutionResult { receipts, requests })
     }
 
-    fn setup_env(
+    pub fn setup_env(
         tx: &Transaction,
         tx_sender: Address,
         block_header: &BlockHeader,
         db: &mut GeneralizedDatabase,
+        vm_type: VMType,
     ) -> Result<Environment, EvmError> {
         let chain_config = db.store.get_chain_config()?;
         let gas_price: U256 = tx
@@ -94,7 +155,7 @@ impl LEVM {
             ))?
             .into();
 
-        let config = EVMConfig::new_from_chain_config(&chain_config, block_header);
+        let config = evm_config(&chain_config, block_header, vm_type);
         let env = Environment {
             origin: tx_sender,
             gas_limit: tx.gas_limit(),
@@ -131,12 +192,43 @@ impl LEVM {
         db: &mut GeneralizedDatabase,
         vm_type: VMType,
     ) -> Result<ExecutionReport, EvmError> {
-        let env = Self::setup_env(tx, tx_sender, block_header, db)?;
+        let env = Self::setup_env(tx, tx_sender, block_header, db, vm_type)?;
         let mut vm = VM::new(env, db, tx, LevmCallTracer::disabled(), vm_type)?;
 
         vm.execute().map_err(VMError::into)
     }
 
+    /// Executes the transactions in parallel on top of the current state, without modifying it.
+    /// The results are meant to be passed to [LEVM::execute_speculated_tx] in the same order.
+    pub fn execute_txs_speculatively(
+        txs: &[(&Transaction, Address)],
+        block_header: &BlockHeader,
+        db: &GeneralizedDatabase,
+        vm_type: VMType,
+    ) -> Vec<Option<SpeculativeExecution>> {
+        parallel::execute_speculatively(txs, block_header, db, vm_type)
+    }
+
+    /// Commits the speculative execution of the transaction if it's still valid for the current state,
+    /// otherwise executes the transaction as usual.
+    pub fn execute_speculated_tx(
+        tx: &Transaction,
+        tx_sender: Address,
+        block_header: &BlockHeader,
+        db: &mut GeneralizedDatabase,
+        vm_type: VMType,
+        speculation: Option<SpeculativeExecution>,
+    ) -> Result<ExecutionReport, EvmError> {
+        let committed_report = match speculation {
+            Some(speculation) => parallel::commit(speculation, db, vm_type)?,
+            None => None,
+        };
+        if let Some(report) = committed_report {
+            return Ok(report);
+        }
+        Self::execute_tx(tx, tx_sender, block_header, db, vm_type)
+    }
+
     pub fn undo_last_tx(db: &mut GeneralizedDatabase) -> Result<(), EvmError> {
         db.undo_last_transaction()?;
         Ok(())
@@ -150,7 +242,7 @@ impl LEVM {
         db: &mut GeneralizedDatabase,
         vm_type: VMType,
     ) -> Result<ExecutionResult, EvmError> {
-        let mut env = env_from_generic(tx, block_header, db)?;
+        let mut env = env_from_generic(tx, block_header, db, vm_type)?;
 
         env.block_gas_limit = u64::MAX; // disable block gas limit
 
@@ -264,6 +356,7 @@ impl LEVM {
         //TODO: These down below don't need to be cleared every time we get state transitions. Clearing them slows down execution but consumes less memory. #3946
         db.current_accounts_state.clear();
         db.codes.clear();
+        db.analyzed_codes.clear();
         Ok(account_updates)
     }
 
@@ -418,7 +511,7 @@ impl LEVM {
         db: &mut GeneralizedDatabase,
         vm_type: VMType,
     ) -> Result<(ExecutionResult, AccessList), VMError> {
-        let mut env = env_from_generic(&tx, header, db)?;
+        let mut env = env_from_generic(&tx, header, db, vm_type)?;
 
         adjust_disabled_base_fee(&mut env);
 
@@ -471,7 +564,7 @@ pub fn generic_system_contract_levm(
     vm_type: VMType,
 ) -> Result<ExecutionReport, EvmError> {
     let chain_config = db.store.get_chain_config()?;
-    let config = EVMConfig::new_from_chain_config(&chain_config, block_header);
+    let config = evm_config(&chain_config, block_header, vm_type);
     let system_account_backup = db.current_accounts_state.get(&system_address).cloned();
     let coinbase_backup = db
         .current_accounts_state
@@ -602,14 +695,30 @@ pub fn build_access_list(substate: &Substate) -> AccessList {
     access_list
 }
 
+/// Builds the EVM configuration of the block, including the custom precompiles and fee vaults of the L2.
+fn evm_config(
+    chain_config: &ChainConfig,
+    block_header: &BlockHeader,
+    vm_type: VMType,
+) -> EVMConfig {
+    let config = EVMConfig::new_from_chain_config(chain_config, block_header);
+    match vm_type {
+        VMType::L1 => config,
+        VMType::L2 => config
+            .with_precompiles(PrecompileRegistry::new(chain_config.custom_precompiles))
+            .with_fee_config(chain_config.fee_config),
+    }
+}
+
 fn env_from_generic(
     tx: &GenericTransaction,
     header: &BlockHeader,
     db: &GeneralizedDatabase,
+    vm_type: VMType,
 ) -> Result<Environment, VMError> {
     let chain_config = db.store.get_chain_config()?;
     let gas_price = calculate_gas_price(tx, header.base_fee_per_gas.unwrap_or(INITIAL_BASE_FEE));
-    let config = EVMConfig::new_from_chain_config(&chain_config, header);
+    let config = evm_config(&chain_config, header, vm_type);
     Ok(Environment {
         origin: tx.from.0.into(),
         gas_limit: tx.gas.unwrap_or(header.gas_limit), // Ensure tx doesn't fail due to gas limit
@@ -0,0 +1,702 @@
+//! Optimistic parallel execution of the transactions of a block, inspired by [Block-STM](https://arxiv.org/abs/2203.06871).
+//!
+//! The transactions are first executed speculatively in parallel. Each of them runs on top of a multi-version
+//! view of the state: it sees the writes of the lower-indexed transactions that were already executed, and the
+//! pre-block state for everything else. While doing so, every value read from the state is recorded.
+//!
+//! Then the transactions are committed in order. If every value a transaction read is still the same in the
+//! committed state, its execution is valid and its writes are applied directly. Otherwise it is re-executed
+//! sequentially on top of the committed state. This makes the results identical to a sequential execution.
+//!
+//! The fees paid at the end of each transaction, to the coinbase and to the L2 fee vaults, are credited at commit
+//! time, otherwise every transaction would conflict on their balances.
+
+use super::LEVM;
+use bytes::Bytes;
+use ethrex_common::{
+    Address, H256, U256,
+    types::{AccountInfo, BlockHeader, ChainConfig, Transaction},
+};
+use ethrex_levm::{
+    call_frame::CallFrameBackup,
+    db::{
+        Database,
+        gen_db::{CacheDB, GeneralizedDatabase},
+    },
+    errors::{DatabaseError, ExecutionReport, InternalError},
+    tracing::LevmCallTracer,
+    vm::{VM, VMType},
+};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    num::NonZeroUsize,
+    sync::{
+        Arc, Mutex, RwLock,
+        atomic::{AtomicUsize, Ordering},
+    },
+    thread,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Location {
+    Account(Address),
+    Storage(Address, H256),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Value {
+    Account(AccountInfo),
+    Storage(U256),
+}
+
+/// Result of executing a transaction over a possibly outdated state.
+/// It can only be committed through [LEVM::execute_speculated_tx], which checks it's still valid.
+#[derive(Debug)]
+pub struct SpeculativeExecution {
+    report: ExecutionReport,
+    /// Values read from the state during the execution, in the order they were read
+    reads: Vec<(Location, Value)>,
+    writes: Vec<(Location, Value)>,
+    codes: Vec<(H256, Bytes)>,
+    /// Fees deferred during the execution, by recipient
+    fees: BTreeMap<Address, U256>,
+}
+
+/// State before executing the transactions, shared by all the speculative executions
+struct BaseState {
+    store: Arc<dyn Database>,
+    accounts: CacheDB,
+    codes: BTreeMap<H256, Bytes>,
+    destroyed_accounts: HashSet<Address>,
+}
+
+impl BaseState {
+    fn new(db: &GeneralizedDatabase) -> Self {
+        Self {
+            store: db.store.clone(),
+            accounts: db.current_accounts_state.clone(),
+            codes: db.codes.clone(),
+            destroyed_accounts: db.destroyed_accounts.clone(),
+        }
+    }
+
+    fn account_info(&self, address: Address) -> Result<AccountInfo, DatabaseError> {
+        match self.accounts.get(&address) {
+            Some(account) => Ok(account.info.clone()),
+            None => self.store.get_account_info(address),
+        }
+    }
+
+    fn storage_value(&self, address: Address, key: H256) -> Result<U256, DatabaseError> {
+        if let Some(value) = self
+            .accounts
+            .get(&address)
+            .and_then(|account| account.storage.get(&key))
+        {
+            return Ok(*value);
+        }
+        // Same as `GeneralizedDatabase`, the storage of a destroyed account can't be read from the store
+        if self.destroyed_accounts.contains(&address) {
+            return Ok(U256::zero());
+        }
+        self.store.get_storage_value(address, key)
+    }
+
+    fn code(&self, code_hash: H256) -> Result<Bytes, DatabaseError> {
+        match self.codes.get(&code_hash) {
+            Some(code) => Ok(code.clone()),
+            None => self.store.get_account_code(code_hash),
+        }
+    }
+}
+
+/// Writes of the speculative executions, indexed by the position of the transaction in the block
+#[derive(Default)]
+struct MultiVersionState {
+    versions: RwLock<HashMap<Location, BTreeMap<usize, Value>>>,
+    codes: RwLock<HashMap<H256, Bytes>>,
+}
+
+impl MultiVersionState {
+    /// Returns the value written by the closest transaction before `tx_index`, if any
+    fn read(&self, location: &Location, tx_index: usize) -> Option<Value> {
+        // A poisoned lock is treated as if there was no write, the read is validated on commit anyway
+        let versions = self.versions.read().ok()?;
+        versions
+            .get(location)?
+            .range(..tx_index)
+            .next_back()
+            .map(|(_, value)| value.clone())
+    }
+
+    fn code(&self, code_hash: &H256) -> Option<Bytes> {
+        self.codes.read().ok()?.get(code_hash).cloned()
+    }
+
+    fn publish(&self, tx_index: usize, execution: &SpeculativeExecution) {
+        if let Ok(mut versions) = self.versions.write() {
+            for (location, value) in &execution.writes {
+                versions
+                    .entry(*location)
+                    .or_default()
+                    .insert(tx_index, value.clone());
+            }
+        }
+        if let Ok(mut codes) = self.codes.write() {
+            codes.extend(execution.codes.iter().cloned());
+        }
+    }
+}
+
+/// Database seen by a single speculative execution, it records every value read
+struct SpeculativeDatabase {
+    tx_index: usize,
+    base: Arc<BaseState>,
+    state: Arc<MultiVersionState>,
+    reads: Mutex<Vec<(Location, Value)>>,
+}
+
+impl SpeculativeDatabase {
+    fn record(&self, location: Location, value: Value) -> Result<(), DatabaseError> {
+        self.reads
+            .lock()
+            .map_err(|_| DatabaseError::Custom("Speculative read set lock poisoned".to_string()))?
+            .push((location, value));
+        Ok(())
+    }
+}
+
+impl Database for SpeculativeDatabase {
+    fn get_account_info(&self, address: Address) -> Result<AccountInfo, DatabaseError> {
+        let location = Location::Account(address);
+        let info = match self.state.read(&location, self.tx_index) {
+            Some(Value::Account(info)) => info,
+            _ => self.base.account_info(address)?,
+        };
+        self.record(location, Value::Account(info.clone()))?;
+        Ok(info)
+    }
+
+    fn get_storage_value(&self, address: Address, key: H256) -> Result<U256, DatabaseError> {
+        let location = Location::Storage(address, key);
+        let value = match self.state.read(&location, self.tx_index) {
+            Some(Value::Storage(value)) => value,
+            _ => self.base.storage_value(address, key)?,
+        };
+        self.record(location, Value::Storage(value))?;
+        Ok(value)
+    }
+
+    fn get_block_hash(&self, block_number: u64) -> Result<H256, DatabaseError> {
+        self.base.store.get_block_hash(block_number)
+    }
+
+    fn get_chain_config(&self) -> Result<ChainConfig, DatabaseError> {
+        self.base.store.get_chain_config()
+    }
+
+    fn get_account_code(&self, code_hash: H256) -> Result<Bytes, DatabaseError> {
+        match self.state.code(&code_hash) {
+            Some(code) => Ok(code),
+            None => self.base.code(code_hash),
+        }
+    }
+}
+
+/// Executes the transactions in parallel on top of the state of `db`, without modifying it.
+/// Returns one entry per transaction, `None` if it couldn't be executed speculatively.
+pub(crate) fn execute_speculatively(
+    txs: &[(&Transaction, Address)],
+    block_header: &BlockHeader,
+    db: &GeneralizedDatabase,
+    vm_type: VMType,
+) -> Vec<Option<SpeculativeExecution>> {
+    let base = Arc::new(BaseState::new(db));
+    let state = Arc::new(MultiVersionState::default());
+    let next_tx = AtomicUsize::new(0);
+    let workers = thread::available_parallelism()
+        .map_or(1, NonZeroUsize::get)
+        .min(txs.len());
+
+    let mut results: Vec<Option<SpeculativeExecution>> = txs.iter().map(|_| None).collect();
+    thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut executions = Vec::new();
+                    // Transactions are picked in order, so the ones before have most likely
+                    // been executed already and their writes are visible
+                    loop {
+                        let tx_index = next_tx.fetch_add(1, Ordering::Relaxed);
+                        let Some((tx, sender)) = txs.get(tx_index) else {
+                            break;
+                        };
+                        let execution = execute_tx_speculatively(
+                            tx_index,
+                            tx,
+                            *sender,
+                            block_header,
+                            &base,
+                            &state,
+                            vm_type,
+                        );
+                        if let Some(execution) = &execution {
+                            state.publish(tx_index, execution);
+                        }
+                        executions.push((tx_index, execution));
+                    }
+                    executions
+                })
+            })
+            .collect();
+        for handle in handles {
+            // If a worker panicked its transactions are executed sequentially
+            let Ok(executions) = handle.join() else {
+                continue;
+            };
+            for (tx_index, execution) in executions {
+                if let Some(result) = results.get_mut(tx_index) {
+                    *result = execution;
+                }
+            }
+        }
+    });
+    results
+}
+
+fn execute_tx_speculatively(
+    tx_index: usize,
+    tx: &Transaction,
+    sender: Address,
+    block_header: &BlockHeader,
+    base: &Arc<BaseState>,
+    state: &Arc<MultiVersionState>,
+    vm_type: VMType,
+) -> Option<SpeculativeExecution> {
+    let store = Arc::new(SpeculativeDatabase {
+        tx_index,
+        base: base.clone(),
+        state: state.clone(),
+        reads: Mutex::new(Vec::new()),
+    });
+    let mut db = GeneralizedDatabase::new(store.clone());
+
+    let env = LEVM::setup_env(tx, sender, block_header, &mut db, vm_type).ok()?;
+    let (report, fees) = {
+        let mut vm = VM::new(env, &mut db, tx, LevmCallTracer::disabled(), vm_type).ok()?;
+        vm.deferred_fees = Some(BTreeMap::new());
+        let report = vm.execute().ok()?;
+        (report, vm.deferred_fees.unwrap_or_default())
+    };
+
+    // Destroyed accounts need their whole storage cleared, which can't be expressed as a set of writes
+    if !db.destroyed_accounts.is_empty() {
+        return None;
+    }
+
+    let mut writes = Vec::new();
+    let mut codes = Vec::new();
+    for (address, account) in &db.current_accounts_state {
+        let initial_account = db.initial_accounts_state.get(address)?;
+        if account.info != initial_account.info {
+            writes.push((
+                Location::Account(*address),
+                Value::Account(account.info.clone()),
+            ));
+        }
+        if account.info.code_hash != initial_account.info.code_hash {
+            codes.push((
+                account.info.code_hash,
+                db.codes.get(&account.info.code_hash)?.clone(),
+            ));
+        }
+        for (key, value) in &account.storage {
+            if initial_account.storage.get(key) != Some(value) {
+                writes.push((Location::Storage(*address, *key), Value::Storage(*value)));
+            }
+        }
+    }
+
+    let reads = std::mem::take(&mut *store.reads.lock().ok()?);
+    Some(SpeculativeExecution {
+        report,
+        reads,
+        writes,
+        codes,
+        fees,
+    })
+}
+
+/// Applies the speculative execution on `db` if every value it read is still the same.
+/// Returns `None`, without modifying the state, if the transaction has to be re-executed.
+pub(crate) fn commit(
+    execution: SpeculativeExecution,
+    db: &mut GeneralizedDatabase,
+    vm_type: VMType,
+) -> Result<Option<ExecutionReport>, InternalError> {
+    for (location, value) in &execution.reads {
+        let committed_value = match location {
+            Location::Account(address) => Value::Account(db.get_account(*address)?.info.clone()),
+            Location::Storage(address, key) => {
+                Value::Storage(db.get_storage_value(*address, *key)?)
+            }
+        };
+        if committed_value != *value {
+            return Ok(None);
+        }
+    }
+
+    let mut backup = CallFrameBackup::default();
+    for (location, value) in execution.writes {
+        match (location, value) {
+            (Location::Account(address), Value::Account(info)) => {
+                let account = db.get_account_mut(address)?;
+                backup.backup_account_info(address, account)?;
+                account.info = info;
+            }
+            (Location::Storage(address, key), Value::Storage(value)) => {
+                let original_value = db.get_storage_value(address, key)?;
+                backup
+                    .original_account_storage_slots
+                    .entry(address)
+                    .or_default()
+                    .entry(key)
+                    .or_insert(original_value);
+                let account = db.get_account_mut(address)?;
+                backup.backup_account_info(address, account)?;
+                account.storage.insert(key, value);
+            }
+            _ => {
+                return Err(InternalError::msg(
+                    "Speculative write with mismatching location and value",
+                ));
+            }
+        }
+    }
+    for (code_hash, code) in execution.codes {
+        db.codes.entry(code_hash).or_insert(code);
+    }
+
+    for (recipient, fee) in execution.fees {
+        if fee.is_zero() {
+            continue;
+        }
+        let recipient_account = db.get_account_mut(recipient)?;
+        backup.backup_account_info(recipient, recipient_account)?;
+        recipient_account.info.balance = recipient_account
+            .info
+            .balance
+            .checked_add(fee)
+            .ok_or(InternalError::Overflow)?;
+    }
+
+    // Same as the `BackupHook`, so the transaction can be undone while building L2 blocks
+    db.tx_backup = match vm_type {
+        VMType::L1 => None,
+        VMType::L2 => Some(backup),
+    };
+
+    Ok(Some(execution.report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethrex_common::{
+        H160,
+        types::{
+            Account, Block, BlockBody, EIP1559Transaction, FeeConfig, OperatorFeeConfig, TxKind,
+            TxType,
+        },
+    };
+    use ethrex_rlp::encode::PayloadRLPEncode;
+    use secp256k1::{Message, SECP256K1, SecretKey};
+    use sha3::{Digest, Keccak256};
+
+    const COINBASE: Address = H160([0xc0; 20]);
+    const BASE_FEE_VAULT: Address = H160([0xba; 20]);
+    const OPERATOR_FEE_VAULT: Address = H160([0x0f; 20]);
+    const READER: Address = H160([0xcc; 20]);
+    const RECIPIENT: Address = H160([0xee; 20]);
+
+    /// Accounts not loaded in the cache are empty, like in a fresh chain
+    struct TestDatabase {
+        chain_config: ChainConfig,
+    }
+
+    impl Database for TestDatabase {
+        fn get_account_info(&self, _address: Address) -> Result<AccountInfo, DatabaseError> {
+            Ok(AccountInfo::default())
+        }
+
+        fn get_storage_value(&self, _address: Address, _key: H256) -> Result<U256, DatabaseError> {
+            Ok(U256::zero())
+        }
+
+        fn get_block_hash(&self, _block_number: u64) -> Result<H256, DatabaseError> {
+            Ok(H256::zero())
+        }
+
+        fn get_chain_config(&self) -> Result<ChainConfig, DatabaseError> {
+            Ok(self.chain_config)
+        }
+
+        fn get_account_code(&self, _code_hash: H256) -> Result<Bytes, DatabaseError> {
+            Ok(Bytes::new())
+        }
+    }
+
+    fn test_db(
+        fee_config: Option<FeeConfig>,
+        accounts: impl IntoIterator<Item = (Address, Account)>,
+    ) -> GeneralizedDatabase {
+        let chain_config = ChainConfig {
+            chain_id: 1,
+            shanghai_time: Some(0),
+            cancun_time: Some(0),
+            fee_config,
+            ..Default::default()
+        };
+        GeneralizedDatabase::new_with_account_state(
+            Arc::new(TestDatabase { chain_config }),
+            accounts.into_iter().collect(),
+        )
+    }
+
+    fn account(code: Bytes) -> Account {
+        Account::new(U256::from(10).pow(18.into()), code, 0, BTreeMap::new())
+    }
+
+    fn secret_key(seed: u8) -> SecretKey {
+        SecretKey::from_slice(&[seed; 32]).unwrap()
+    }
+
+    fn address(key: &SecretKey) -> Address {
+        let public_key = key.public_key(SECP256K1).serialize_uncompressed();
+        Address::from_slice(&Keccak256::digest(&public_key[1..])[12..])
+    }
+
+    fn signed_tx(key: &SecretKey, nonce: u64, to: TxKind, value: u64, data: Bytes) -> Transaction {
+        let mut tx = EIP1559Transaction {
+            chain_id: 1,
+            nonce,
+            max_priority_fee_per_gas: 2,
+            max_fee_per_gas: 10,
+            gas_limit: 200_000,
+            to,
+            value: value.into(),
+            data,
+            ..Default::default()
+        };
+        let mut payload = vec![TxType::EIP1559 as u8];
+        payload.append(&mut tx.encode_payload_to_vec());
+        let message = Message::from_digest(Keccak256::digest(payload).into());
+        let (recovery_id, signature) = SECP256K1
+            .sign_ecdsa_recoverable(&message, key)
+            .serialize_compact();
+        tx.signature_r = U256::from_big_endian(&signature[..32]);
+        tx.signature_s = U256::from_big_endian(&signature[32..]);
+        tx.signature_y_parity = recovery_id.to_i32() != 0;
+        Transaction::EIP1559Transaction(tx)
+    }
+
+    fn test_block(transactions: Vec<Transaction>) -> Block {
+        let header = BlockHeader {
+            number: 1,
+            coinbase: COINBASE,
+            gas_limit: 30_000_000,
+            timestamp: 12,
+            base_fee_per_gas: Some(1),
+            excess_blob_gas: Some(0),
+            blob_gas_used: Some(0),
+            ..Default::default()
+        };
+        let body = BlockBody {
+            transactions,
+            ommers: Vec::new(),
+            withdrawals: None,
+        };
+        Block::new(header, body)
+    }
+
+    /// Executes the block sequentially and in parallel, checking that the results are the same.
+    /// Returns whether the speculative execution of each transaction could be committed.
+    fn assert_parallel_matches_sequential(
+        block: &Block,
+        db: &GeneralizedDatabase,
+        vm_type: VMType,
+    ) -> Vec<bool> {
+        let mut sequential_db = db.clone();
+        let sequential = LEVM::execute_block(block, &mut sequential_db, vm_type).unwrap();
+        let mut parallel_db = db.clone();
+        let parallel = LEVM::execute_block_parallel(block, &mut parallel_db, vm_type).unwrap();
+
+        assert_eq!(parallel.receipts, sequential.receipts);
+        assert_eq!(
+            LEVM::get_state_transitions(&mut parallel_db).unwrap(),
+            LEVM::get_state_transitions(&mut sequential_db).unwrap()
+        );
+
+        let mut db = db.clone();
+        let txs = block.body.get_transactions_with_sender().unwrap();
+        let speculations = execute_speculatively(&txs, &block.header, &db, vm_type);
+        txs.iter()
+            .zip(speculations)
+            .map(|((tx, sender), speculation)| {
+                let committed = match speculation {
+                    Some(speculation) => commit(speculation, &mut db, vm_type).unwrap().is_some(),
+                    None => false,
+                };
+                if !committed {
+                    LEVM::execute_tx(tx, *sender, &block.header, &mut db, vm_type).unwrap();
+                }
+                committed
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fee_vault_credits_are_deferred() {
+        let fee_config = FeeConfig {
+            base_fee_vault: Some(BASE_FEE_VAULT),
+            operator_fee: Some(OperatorFeeConfig {
+                vault: OPERATOR_FEE_VAULT,
+                fixed_fee: 1000,
+                fee_per_gas: 1,
+            }),
+        };
+        let keys: Vec<_> = (1..=4).map(secret_key).collect();
+        let block = test_block(
+            keys.iter()
+                .zip(0x10_u8..)
+                .map(|(key, recipient)| {
+                    signed_tx(
+                        key,
+                        0,
+                        TxKind::Call(H160::repeat_byte(recipient)),
+                        1,
+                        Bytes::new(),
+                    )
+                })
+                .collect(),
+        );
+        let db = test_db(
+            Some(fee_config),
+            keys.iter().map(|key| (address(key), account(Bytes::new()))),
+        );
+
+        let committed = assert_parallel_matches_sequential(&block, &db, VMType::L2);
+        // Every transaction pays both vaults, they would all conflict if the credits weren't deferred
+        assert_eq!(committed, vec![true; 4]);
+    }
+
+    fn transfer(key: &SecretKey, nonce: u64, to: Address, value: u64) -> Transaction {
+        signed_tx(key, nonce, TxKind::Call(to), value, Bytes::new())
+    }
+
+    /// Code storing the balance of `address` in slot 0, the coinbase if `None`
+    fn balance_reader(address: Option<Address>) -> Bytes {
+        let mut code = match address {
+            // PUSH20 address
+            Some(address) => [&[0x73][..], address.as_bytes()].concat(),
+            // COINBASE
+            None => vec![0x41],
+        };
+        // BALANCE PUSH1 0 SSTORE STOP
+        code.extend_from_slice(&[0x31, 0x60, 0x00, 0x55, 0x00]);
+        code.into()
+    }
+
+    #[test]
+    fn same_sender_matches_sequential() {
+        let key = secret_key(1);
+        let block = test_block(
+            (0..4)
+                .map(|nonce| transfer(&key, nonce, RECIPIENT, 1))
+                .collect(),
+        );
+        let db = test_db(None, [(address(&key), account(Bytes::new()))]);
+
+        let committed = assert_parallel_matches_sequential(&block, &db, VMType::L1);
+        assert!(committed[0]);
+    }
+
+    #[test]
+    fn same_recipient_matches_sequential() {
+        let keys: Vec<_> = (1..=4).map(secret_key).collect();
+        let block = test_block(
+            keys.iter()
+                .map(|key| transfer(key, 0, RECIPIENT, 1))
+                .collect(),
+        );
+        let db = test_db(
+            None,
+            keys.iter().map(|key| (address(key), account(Bytes::new()))),
+        );
+
+        let committed = assert_parallel_matches_sequential(&block, &db, VMType::L1);
+        assert!(committed[0]);
+    }
+
+    #[test]
+    fn coinbase_read_is_reexecuted() {
+        let keys: Vec<_> = (1..=2).map(secret_key).collect();
+        let block = test_block(vec![
+            transfer(&keys[0], 0, RECIPIENT, 1),
+            transfer(&keys[1], 0, READER, 0),
+        ]);
+        let db = test_db(
+            None,
+            keys.iter()
+                .map(|key| (address(key), account(Bytes::new())))
+                .chain([(READER, account(balance_reader(None)))]),
+        );
+
+        let committed = assert_parallel_matches_sequential(&block, &db, VMType::L1);
+        // The coinbase balance read doesn't include the fee of the first transaction until it's committed
+        assert_eq!(committed, vec![true, false]);
+    }
+
+    #[test]
+    fn deferred_fee_vault_read_is_reexecuted() {
+        let fee_config = FeeConfig {
+            base_fee_vault: Some(BASE_FEE_VAULT),
+            operator_fee: None,
+        };
+        let keys: Vec<_> = (1..=2).map(secret_key).collect();
+        let block = test_block(vec![
+            transfer(&keys[0], 0, RECIPIENT, 1),
+            transfer(&keys[1], 0, READER, 0),
+        ]);
+        let db = test_db(
+            Some(fee_config),
+            keys.iter()
+                .map(|key| (address(key), account(Bytes::new())))
+                .chain([(READER, account(balance_reader(Some(BASE_FEE_VAULT))))]),
+        );
+
+        let committed = assert_parallel_matches_sequential(&block, &db, VMType::L2);
+        assert_eq!(committed, vec![true, false]);
+    }
+
+    #[test]
+    fn selfdestruct_is_reexecuted() {
+        let keys: Vec<_> = (1..=3).map(secret_key).collect();
+        // PUSH20 recipient SELFDESTRUCT, the contract is destroyed in the same transaction that creates it
+        let init_code: Bytes = [&[0x73][..], RECIPIENT.as_bytes(), &[0xff]].concat().into();
+        let block = test_block(vec![
+            signed_tx(&keys[0], 0, TxKind::Create, 5, init_code),
+            transfer(&keys[1], 0, RECIPIENT, 1),
+            transfer(&keys[2], 0, READER, 0),
+        ]);
+        let db = test_db(
+            None,
+            keys.iter()
+                .map(|key| (address(key), account(Bytes::new())))
+                .chain([(READER, account(balance_reader(Some(RECIPIENT))))]),
+        );
+
+        let committed = assert_parallel_matches_sequential(&block, &db, VMType::L1);
+        assert!(!committed[0]);
+    }
+}
@@ -1,5 +1,7 @@
 use ethrex_common::types::{Block, Transaction};
 use ethrex_common::{tracing::CallTrace, types::BlockHeader};
+use ethrex_levm::errors::ExecutionReport;
+use ethrex_levm::inspector::Inspector;
 use ethrex_levm::vm::VMType;
 use ethrex_levm::{db::gen_db::GeneralizedDatabase, tracing::LevmCallTracer, vm::VM};
 
@@ -57,6 +59,7 @@ impl LEVM {
             })?,
             block_header,
             db,
+            vm_type,
         )?;
         let mut vm = VM::new(
             env,
@@ -73,4 +76,27 @@ impl LEVM {
         // We only return the top call because a transaction only has one call with subcalls
         Ok(vec![callframe])
     }
+
+    /// Run transaction notifying the given inspector of every step of its execution.
+    pub fn inspect_tx(
+        db: &mut GeneralizedDatabase,
+        block_header: &BlockHeader,
+        tx: &Transaction,
+        inspector: Box<dyn Inspector + '_>,
+        vm_type: VMType,
+    ) -> Result<ExecutionReport, EvmError> {
+        let env = Self::setup_env(
+            tx,
+            tx.sender().map_err(|error| {
+                EvmError::Transaction(format!("Couldn't recover addresses with error: {error}"))
+            })?,
+            block_header,
+            db,
+            vm_type,
+        )?;
+        let mut vm =
+            VM::new(env, db, tx, LevmCallTracer::disabled(), vm_type)?.with_inspector(inspector);
+
+        Ok(vm.execute()?)
+    }
 }
@@ -16,7 +16,7 @@ pub use ethrex_levm::call_frame::CallFrameBackup;
 use ethrex_levm::db::Database as LevmDatabase;
 use ethrex_levm::db::gen_db::GeneralizedDatabase;
 use ethrex_levm::vm::VMType;
-use levm::LEVM;
+use levm::{LEVM, SpeculativeExecution};
 use revm::REVM;
 use revm::db::EvmState;
 use std::fmt;
@@ -28,6 +28,9 @@ pub enum EvmEngine {
     #[default]
     LEVM,
     REVM,
+    /// Executes blocks with both LEVM and REVM reporting any divergence, see [crate::differential].
+    /// The results of LEVM are the ones used.
+    Differential,
 }
 
 impl fmt::Display for EvmEngine {
@@ -35,6 +38,7 @@ impl fmt::Display for EvmEngine {
         match self {
             EvmEngine::LEVM => write!(f, "levm"),
             EvmEngine::REVM => write!(f, "revm"),
+            EvmEngine::Differential => write!(f, "differential"),
         }
     }
 }
@@ -47,6 +51,7 @@ impl TryFrom<String> for EvmEngine {
         match s.to_lowercase().as_str() {
             "revm" => Ok(EvmEngine::REVM),
             "levm" => Ok(EvmEngine::LEVM),
+            "differential" => Ok(EvmEngine::Differential),
             _ => Err(EvmError::InvalidEVM(s)),
         }
     }
@@ -84,7 +89,7 @@ impl Evm {
             EvmEngine::REVM => Evm::REVM {
                 state: evm_state(wrapped_db),
             },
-            EvmEngine::LEVM => Evm::LEVM {
+            EvmEngine::LEVM | EvmEngine::Differential => Evm::LEVM {
                 db: GeneralizedDatabase::new(Arc::new(wrapped_db)),
                 vm_type: VMType::L1,
             },
@@ -92,10 +97,10 @@ impl Evm {
     }
 
     pub fn new_for_l2(engine: EvmEngine, db: impl VmDatabase + 'static) -> Result<Self, EvmError> {
-        if let EvmEngine::REVM = engine {
-            return Err(EvmError::InvalidEVM(
-                "REVM is not supported for L2".to_string(),
-            ));
+        if let EvmEngine::REVM | EvmEngine::Differential = engine {
+            return Err(EvmError::InvalidEVM(format!(
+                "{engine} is not supported for L2"
+            )));
         }
 
         let wrapped_db: DynVmDatabase = Box::new(db);
@@ -131,6 +136,34 @@ impl Evm {
         }
     }
 
+    /// Wraps [LEVM::execute_block_parallel].
+    /// REVM doesn't support parallel execution, so the block is executed sequentially.
+    #[instrument(level = "trace", name = "Block execution", skip_all)]
+    pub fn execute_block_parallel(
+        &mut self,
+        block: &Block,
+    ) -> Result<BlockExecutionResult, EvmError> {
+        match self {
+            Evm::REVM { state } => REVM::execute_block(block, state),
+            Evm::LEVM { db, vm_type } => LEVM::execute_block_parallel(block, db, *vm_type),
+        }
+    }
+
+    /// Wraps [LEVM::execute_txs_speculatively], the results can be passed to [Evm::execute_speculated_tx].
+    /// REVM doesn't support speculative execution, so no transaction is executed.
+    pub fn execute_txs_speculatively(
+        &self,
+        txs: &[(&Transaction, Address)],
+        block_header: &BlockHeader,
+    ) -> Vec<Option<SpeculativeExecution>> {
+        match self {
+            Evm::REVM { .. } => txs.iter().map(|_| None).collect(),
+            Evm::LEVM { db, vm_type } => {
+                LEVM::execute_txs_speculatively(txs, block_header, db, *vm_type)
+            }
+        }
+    }
+
     /// Wraps [REVM::execute_tx] and [LEVM::execute_tx].
     /// The output is `(Receipt, u64)` == (transaction_receipt, gas_used).
     #[allow(clippy::too_many_arguments)]
@@ -140,6 +173,18 @@ impl Evm {
         block_header: &BlockHeader,
         remaining_gas: &mut u64,
         sender: Address,
+    ) -> Result<(Receipt, u64), EvmError> {
+        self.execute_speculated_tx(tx, block_header, remaining_gas, sender, None)
+    }
+
+    /// Same as [Evm::execute_tx], but commits the speculative execution of the transaction if it's still valid.
+    pub fn execute_speculated_tx(
+        &mut self,
+        tx: &Transaction,
+        block_header: &BlockHeader,
+        remaining_gas: &mut u64,
+        sender: Address,
+        speculation: Option<SpeculativeExecution>,
     ) -> Result<(Receipt, u64), EvmError> {
         match self {
             Evm::REVM { state } => {
@@ -164,7 +209,14 @@ impl Evm {
                 Ok((receipt, execution_result.gas_used()))
             }
             Evm::LEVM { db, vm_type } => {
-                let execution_report = LEVM::execute_tx(tx, sender, block_header, db, *vm_type)?;
+                let execution_report = LEVM::execute_speculated_tx(
+                    tx,
+                    sender,
+                    block_header,
+                    db,
+                    *vm_type,
+                    speculation,
+                )?;
 
                 *remaining_gas = remaining_gas.saturating_sub(execution_report.gas_used);
 
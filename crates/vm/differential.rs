@@ -0,0 +1,569 @@
+//! Differential execution of blocks, running them with both LEVM and REVM and comparing the results
+//! of every transaction, to find consensus bugs in one of the engines.
+use std::collections::{BTreeMap, BTreeSet};
+
+use ethrex_common::{
+    Address, H256, U256,
+    tracing::CallTrace,
+    types::{AccountInfo, AccountUpdate, Block, Receipt},
+};
+use serde::Serialize;
+
+use crate::{BlockExecutionResult, Evm, EvmEngine, EvmError, VmDatabase};
+
+/// Outcome of executing a block with both engines.
+pub struct DifferentialExecution {
+    /// Result of the LEVM execution, which is the one used by the node
+    pub levm_result: Result<(BlockExecutionResult, Vec<AccountUpdate>), EvmError>,
+    /// First point in which the engines disagree, if any
+    pub divergence: Option<Divergence>,
+}
+
+/// Minimized report of the first divergence between LEVM and REVM in a block.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Divergence {
+    pub block_number: u64,
+    pub block_hash: H256,
+    /// Index of the first divergent transaction.
+    /// None if the transactions match and the difference comes from withdrawals or requests.
+    pub tx_index: Option<usize>,
+    pub tx_hash: Option<H256>,
+    /// Human readable description of every difference found
+    pub differences: Vec<String>,
+    /// Call trace of the divergent transaction for each engine
+    pub levm_trace: Option<CallTrace>,
+    pub revm_trace: Option<CallTrace>,
+}
+
+/// Executes the block with LEVM and REVM on top of the given state.
+/// Transactions are compared as they are executed by their gas, receipts and logs, while the state changes are
+/// compared at the end of the block. If only the state differs, the block is re-executed comparing the state
+/// after every transaction to find the first divergent one.
+pub fn execute_block_differential<D: VmDatabase + Clone + 'static>(
+    block: &Block,
+    db: D,
+) -> Result<DifferentialExecution, EvmError> {
+    let mut levm = Evm::new_for_l1(EvmEngine::LEVM, db.clone());
+    let mut revm = Evm::new_for_l1(EvmEngine::REVM, db.clone());
+
+    levm.apply_system_calls(&block.header)?;
+    revm.apply_system_calls(&block.header)?;
+
+    let transactions = block.body.get_transactions_with_sender().map_err(|error| {
+        EvmError::Transaction(format!("Couldn't recover addresses with error: {error}"))
+    })?;
+
+    let mut levm_receipts = Vec::new();
+    let mut revm_receipts = Vec::new();
+    let mut levm_remaining_gas = block.header.gas_limit;
+    let mut revm_remaining_gas = block.header.gas_limit;
+
+    for (index, (tx, sender)) in transactions.into_iter().enumerate() {
+        let levm_tx = levm.execute_tx(tx, &block.header, &mut levm_remaining_gas, sender);
+        let revm_tx = revm.execute_tx(tx, &block.header, &mut revm_remaining_gas, sender);
+
+        let differences = match (&levm_tx, &revm_tx) {
+            (Ok((levm_receipt, levm_gas)), Ok((revm_receipt, revm_gas))) => {
+                compare_txs(levm_receipt, *levm_gas, revm_receipt, *revm_gas)
+            }
+            (Err(levm_error), Ok(_)) => {
+                vec![format!(
+                    "LEVM failed with `{levm_error}` while REVM succeeded"
+                )]
+            }
+            (Ok(_), Err(revm_error)) => {
+                vec![format!(
+                    "REVM failed with `{revm_error}` while LEVM succeeded"
+                )]
+            }
+            // Both consider the block invalid, but they must agree on why
+            (Err(levm_error), Err(revm_error)) => compare_errors(levm_error, revm_error),
+        };
+
+        if !differences.is_empty() {
+            let levm_result = levm_tx.and_then(|_| execute_block_levm(block, db.clone()));
+            return Ok(DifferentialExecution {
+                levm_result,
+                divergence: Some(divergence_at(block, db, Some(index), differences)),
+            });
+        }
+
+        match levm_tx {
+            Ok((receipt, _)) => levm_receipts.push(receipt),
+            Err(error) => {
+                return Ok(DifferentialExecution {
+                    levm_result: Err(error),
+                    divergence: None,
+                });
+            }
+        }
+        if let Ok((receipt, _)) = revm_tx {
+            revm_receipts.push(receipt);
+        }
+    }
+
+    if let Some(withdrawals) = &block.body.withdrawals {
+        levm.process_withdrawals(withdrawals)?;
+        revm.process_withdrawals(withdrawals)?;
+    }
+
+    let levm_requests = levm.extract_requests(&levm_receipts, &block.header)?;
+    let revm_requests = revm.extract_requests(&revm_receipts, &block.header)?;
+
+    let levm_updates = levm.get_state_transitions()?;
+    let revm_updates = revm.get_state_transitions()?;
+
+    let mut differences = Vec::new();
+    let encoded_levm_requests: Vec<_> = levm_requests.iter().map(|r| r.encode().0).collect();
+    let encoded_revm_requests: Vec<_> = revm_requests.iter().map(|r| r.encode().0).collect();
+    if encoded_levm_requests != encoded_revm_requests {
+        differences.push(format!(
+            "Requests differ: LEVM {encoded_levm_requests:?}, REVM {encoded_revm_requests:?}"
+        ));
+    }
+
+    let state_differences = compare_account_updates(&db, &levm_updates, &revm_updates)?;
+    let divergence = if !state_differences.is_empty() {
+        // Find the transaction that made the state diverge, if any
+        match first_state_divergence(block, &db)? {
+            Some((index, differences)) => Some(divergence_at(block, db, Some(index), differences)),
+            None => {
+                differences.extend(state_differences);
+                Some(divergence_at(block, db, None, differences))
+            }
+        }
+    } else if !differences.is_empty() {
+        Some(divergence_at(block, db, None, differences))
+    } else {
+        None
+    };
+
+    Ok(DifferentialExecution {
+        levm_result: Ok((
+            BlockExecutionResult {
+                receipts: levm_receipts,
+                requests: levm_requests,
+            },
+            levm_updates,
+        )),
+        divergence,
+    })
+}
+
+/// Executes the whole block with LEVM only, used once a divergent transaction stopped the differential execution
+fn execute_block_levm(
+    block: &Block,
+    db: impl VmDatabase + 'static,
+) -> Result<(BlockExecutionResult, Vec<AccountUpdate>), EvmError> {
+    let mut levm = Evm::new_for_l1(EvmEngine::LEVM, db);
+    let result = levm.execute_block(block)?;
+    let account_updates = levm.get_state_transitions()?;
+    Ok((result, account_updates))
+}
+
+/// Re-executes the block comparing the state changes after every transaction
+fn first_state_divergence<D: VmDatabase + Clone + 'static>(
+    block: &Block,
+    db: &D,
+) -> Result<Option<(usize, Vec<String>)>, EvmError> {
+    let mut levm = Evm::new_for_l1(EvmEngine::LEVM, db.clone());
+    let mut revm = Evm::new_for_l1(EvmEngine::REVM, db.clone());
+
+    levm.apply_system_calls(&block.header)?;
+    revm.apply_system_calls(&block.header)?;
+
+    let mut levm_remaining_gas = block.header.gas_limit;
+    let mut revm_remaining_gas = block.header.gas_limit;
+
+    for (index, (tx, sender)) in block
+        .body
+        .get_transactions_with_sender()
+        .map_err(|error| {
+            EvmError::Transaction(format!("Couldn't recover addresses with error: {error}"))
+        })?
+        .into_iter()
+        .enumerate()
+    {
+        levm.execute_tx(tx, &block.header, &mut levm_remaining_gas, sender)?;
+        revm.execute_tx(tx, &block.header, &mut revm_remaining_gas, sender)?;
+
+        // Getting the state transitions consumes the state, so we do it on copies
+        let levm_updates = levm.clone().get_state_transitions()?;
+        let revm_updates = revm.clone().get_state_transitions()?;
+        let differences = compare_account_updates(db, &levm_updates, &revm_updates)?;
+        if !differences.is_empty() {
+            return Ok(Some((index, differences)));
+        }
+    }
+
+    Ok(None)
+}
+
+fn divergence_at<D: VmDatabase + Clone + 'static>(
+    block: &Block,
+    db: D,
+    tx_index: Option<usize>,
+    differences: Vec<String>,
+) -> Divergence {
+    let (levm_trace, revm_trace) = match tx_index {
+        Some(index) => (
+            trace_tx(EvmEngine::LEVM, block, index, db.clone()),
+            trace_tx(EvmEngine::REVM, block, index, db),
+        ),
+        None => (None, None),
+    };
+
+    Divergence {
+        block_number: block.header.number,
+        block_hash: block.hash(),
+        tx_index,
+        tx_hash: tx_index
+            .and_then(|index| block.body.transactions.get(index))
+            .map(|tx| tx.compute_hash()),
+        differences,
+        levm_trace,
+        revm_trace,
+    }
+}
+
+/// Call trace of the transaction, the trace is best effort so it's omitted if it fails
+fn trace_tx(
+    engine: EvmEngine,
+    block: &Block,
+    index: usize,
+    db: impl VmDatabase + 'static,
+) -> Option<CallTrace> {
+    let mut vm = Evm::new_for_l1(engine, db);
+    vm.rerun_block(block, Some(index)).ok()?;
+    vm.trace_tx_calls(block, index, false, true).ok()
+}
+
+fn compare_txs(
+    levm_receipt: &Receipt,
+    levm_gas: u64,
+    revm_receipt: &Receipt,
+    revm_gas: u64,
+) -> Vec<String> {
+    let mut differences = Vec::new();
+    if levm_gas != revm_gas {
+        differences.push(format!(
+            "Gas used differs: LEVM {levm_gas}, REVM {revm_gas}"
+        ));
+    }
+    if levm_receipt.succeeded != revm_receipt.succeeded {
+        differences.push(format!(
+            "Status differs: LEVM succeeded {}, REVM succeeded {}",
+            levm_receipt.succeeded, revm_receipt.succeeded
+        ));
+    }
+    if levm_receipt.cumulative_gas_used != revm_receipt.cumulative_gas_used {
+        differences.push(format!(
+            "Cumulative gas used differs: LEVM {}, REVM {}",
+            levm_receipt.cumulative_gas_used, revm_receipt.cumulative_gas_used
+        ));
+    }
+    if levm_receipt.logs.len() != revm_receipt.logs.len() {
+        differences.push(format!(
+            "Number of logs differs: LEVM {}, REVM {}",
+            levm_receipt.logs.len(),
+            revm_receipt.logs.len()
+        ));
+    }
+    if let Some((index, (levm_log, revm_log))) = levm_receipt
+        .logs
+        .iter()
+        .zip(revm_receipt.logs.iter())
+        .enumerate()
+        .find(|(_, (levm_log, revm_log))| levm_log != revm_log)
+    {
+        differences.push(format!(
+            "Log {index} differs: LEVM {levm_log:?}, REVM {revm_log:?}"
+        ));
+    }
+    differences
+}
+
+/// Compares the kind of the errors of a transaction both engines rejected.
+/// The messages aren't compared since each engine words them differently.
+fn compare_errors(levm_error: &EvmError, revm_error: &EvmError) -> Vec<String> {
+    if std::mem::discriminant(levm_error) == std::mem::discriminant(revm_error) {
+        return Vec::new();
+    }
+    vec![format!(
+        "Both failed with different kinds of errors: LEVM `{levm_error}`, REVM `{revm_error}`"
+    )]
+}
+
+/// Changes made to an account by a list of account updates
+#[derive(Default)]
+struct AccountChanges {
+    removed: bool,
+    info: Option<AccountInfo>,
+    storage: BTreeMap<H256, U256>,
+}
+
+fn merge_account_updates(updates: &[AccountUpdate]) -> BTreeMap<Address, AccountChanges> {
+    let mut changes: BTreeMap<Address, AccountChanges> = BTreeMap::new();
+    for update in updates {
+        let account = changes.entry(update.address).or_default();
+        if update.removed {
+            *account = AccountChanges {
+                removed: true,
+                ..Default::default()
+            };
+        }
+        if let Some(info) = &update.info {
+            account.info = Some(info.clone());
+        }
+        account.storage.extend(update.added_storage.clone());
+    }
+    changes
+}
+
+/// Compares the resulting state of the accounts touched by each engine.
+/// The engines don't report updates in the same way (e.g. whether unchanged fields are included),
+/// so the values not present in the updates are read from the state before the block.
+fn compare_account_updates(
+    db: &impl VmDatabase,
+    levm_updates: &[AccountUpdate],
+    revm_updates: &[AccountUpdate],
+) -> Result<Vec<String>, EvmError> {
+    let levm_changes = merge_account_updates(levm_updates);
+    let revm_changes = merge_account_updates(revm_updates);
+    let addresses: BTreeSet<&Address> = levm_changes.keys().chain(revm_changes.keys()).collect();
+
+    let mut differences = Vec::new();
+    for address in addresses {
+        let levm_account = levm_changes.get(address);
+        let revm_account = revm_changes.get(address);
+
+        let levm_info = resulting_info(db, *address, levm_account)?;
+        let revm_info = resulting_info(db, *address, revm_account)?;
+        if levm_info != revm_info {
+            differences.push(format!(
+                "Account {address:#x} differs: LEVM {levm_info:?}, REVM {revm_info:?}"
+            ));
+        }
+
+        let keys: BTreeSet<&H256> = levm_account
+            .iter()
+            .chain(revm_account.iter())
+            .flat_map(|changes| changes.storage.keys())
+            .collect();
+        for key in keys {
+            let levm_value = resulting_storage(db, *address, *key, levm_account)?;
+            let revm_value = resulting_storage(db, *address, *key, revm_account)?;
+            if levm_value != revm_value {
+                differences.push(format!(
+                    "Storage {key:#x} of account {address:#x} differs: LEVM {levm_value}, REVM {revm_value}"
+                ));
+            }
+        }
+    }
+
+    Ok(differences)
+}
+
+/// Account info after the block, None if the account doesn't exist or is empty
+fn resulting_info(
+    db: &impl VmDatabase,
+    address: Address,
+    changes: Option<&AccountChanges>,
+) -> Result<Option<AccountInfo>, EvmError> {
+    let info = match changes {
+        Some(AccountChanges {
+            info: Some(info), ..
+        }) => Some(info.clone()),
+        Some(AccountChanges { removed: true, .. }) => None,
+        _ => db.get_account_info(address)?,
+    };
+    Ok(info.filter(|info| !info.is_empty()))
+}
+
+fn resulting_storage(
+    db: &impl VmDatabase,
+    address: Address,
+    key: H256,
+    changes: Option<&AccountChanges>,
+) -> Result<U256, EvmError> {
+    match changes {
+        Some(changes) => match changes.storage.get(&key) {
+            Some(value) => Ok(*value),
+            None if changes.removed => Ok(U256::zero()),
+            None => Ok(db.get_storage_slot(address, key)?.unwrap_or_default()),
+        },
+        None => Ok(db.get_storage_slot(address, key)?.unwrap_or_default()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+    use ethrex_common::types::{ChainConfig, Log, TxType};
+
+    /// State before the block, with a single account
+    #[derive(Clone)]
+    struct TestDatabase {
+        address: Address,
+        info: AccountInfo,
+        storage: BTreeMap<H256, U256>,
+    }
+
+    impl VmDatabase for TestDatabase {
+        fn get_account_info(&self, address: Address) -> Result<Option<AccountInfo>, EvmError> {
+            Ok((address == self.address).then(|| self.info.clone()))
+        }
+
+        fn get_storage_slot(&self, address: Address, key: H256) -> Result<Option<U256>, EvmError> {
+            Ok(self
+                .storage
+                .get(&key)
+                .copied()
+                .filter(|_| address == self.address))
+        }
+
+        fn get_block_hash(&self, _block_number: u64) -> Result<H256, EvmError> {
+            Ok(H256::zero())
+        }
+
+        fn get_chain_config(&self) -> Result<ChainConfig, EvmError> {
+            Ok(ChainConfig::default())
+        }
+
+        fn get_account_code(&self, _code_hash: H256) -> Result<Bytes, EvmError> {
+            Ok(Bytes::new())
+        }
+    }
+
+    fn info(balance: u64, nonce: u64) -> AccountInfo {
+        AccountInfo {
+            balance: balance.into(),
+            nonce,
+            ..Default::default()
+        }
+    }
+
+    fn test_db() -> TestDatabase {
+        TestDatabase {
+            address: Address::repeat_byte(1),
+            info: info(100, 1),
+            storage: BTreeMap::from([(H256::repeat_byte(1), U256::from(5))]),
+        }
+    }
+
+    fn update(
+        address: Address,
+        info: Option<AccountInfo>,
+        storage: &[(H256, U256)],
+    ) -> AccountUpdate {
+        AccountUpdate {
+            info,
+            added_storage: storage.iter().copied().collect(),
+            ..AccountUpdate::new(address)
+        }
+    }
+
+    #[test]
+    fn merge_account_updates_keeps_latest_changes() {
+        let address = Address::repeat_byte(1);
+        let slot = H256::repeat_byte(1);
+        let other_slot = H256::repeat_byte(2);
+        let changes = merge_account_updates(&[
+            update(address, Some(info(1, 1)), &[(slot, U256::one())]),
+            update(address, Some(info(2, 2)), &[(other_slot, U256::one())]),
+        ]);
+        let account = &changes[&address];
+        assert!(!account.removed);
+        assert_eq!(account.info, Some(info(2, 2)));
+        assert_eq!(account.storage.len(), 2);
+
+        // A removal discards the previous changes, but not the ones after it
+        let changes = merge_account_updates(&[
+            update(address, Some(info(1, 1)), &[(slot, U256::one())]),
+            AccountUpdate::removed(address),
+            update(address, None, &[(other_slot, U256::from(3))]),
+        ]);
+        let account = &changes[&address];
+        assert!(account.removed);
+        assert_eq!(account.info, None);
+        assert_eq!(
+            account.storage,
+            BTreeMap::from([(other_slot, U256::from(3))])
+        );
+    }
+
+    #[test]
+    fn compare_account_updates_reads_missing_values_from_db() {
+        let db = test_db();
+        let address = db.address;
+        let slot = H256::repeat_byte(1);
+
+        // Reporting unchanged values is the same as not reporting them
+        let levm_updates = [update(
+            address,
+            Some(info(100, 1)),
+            &[(slot, U256::from(5))],
+        )];
+        assert!(
+            compare_account_updates(&db, &levm_updates, &[])
+                .unwrap()
+                .is_empty()
+        );
+
+        // Removing an account is the same as leaving it empty
+        let empty = Address::repeat_byte(2);
+        let levm_updates = [AccountUpdate::removed(empty)];
+        let revm_updates = [update(empty, Some(info(0, 0)), &[])];
+        assert!(
+            compare_account_updates(&db, &levm_updates, &revm_updates)
+                .unwrap()
+                .is_empty()
+        );
+
+        let levm_updates = [update(address, Some(info(90, 2)), &[(slot, U256::from(6))])];
+        let revm_updates = [update(address, Some(info(90, 2)), &[])];
+        let differences = compare_account_updates(&db, &levm_updates, &revm_updates).unwrap();
+        assert_eq!(differences.len(), 1);
+        assert!(differences[0].starts_with("Storage"));
+
+        let revm_updates = [update(address, Some(info(80, 2)), &[(slot, U256::from(6))])];
+        let differences = compare_account_updates(&db, &levm_updates, &revm_updates).unwrap();
+        assert_eq!(differences.len(), 1);
+        assert!(differences[0].starts_with(&format!("Account {address:#x}")));
+    }
+
+    #[test]
+    fn compare_txs_reports_differences() {
+        let log = Log {
+            address: Address::repeat_byte(1),
+            topics: vec![H256::repeat_byte(1)],
+            data: Bytes::from_static(&[1]),
+        };
+        let receipt = Receipt::new(TxType::EIP1559, true, 21_000, vec![log.clone()]);
+        assert!(compare_txs(&receipt, 21_000, &receipt, 21_000).is_empty());
+
+        let other_log = Log {
+            data: Bytes::from_static(&[2]),
+            ..log
+        };
+        let other_receipt = Receipt::new(TxType::EIP1559, false, 22_000, vec![other_log]);
+        let differences = compare_txs(&receipt, 21_000, &other_receipt, 22_000);
+        assert_eq!(differences.len(), 4);
+        assert!(differences[0].starts_with("Gas used differs"));
+        assert!(differences[1].starts_with("Status differs"));
+        assert!(differences[2].starts_with("Cumulative gas used differs"));
+        assert!(differences[3].starts_with("Log 0 differs"));
+    }
+
+    #[test]
+    fn compare_errors_by_kind() {
+        let levm_error = EvmError::Transaction("Nonce mismatch".to_string());
+        let revm_error = EvmError::Transaction("nonce 1 too high, expected 0".to_string());
+        assert!(compare_errors(&levm_error, &revm_error).is_empty());
+
+        let revm_error = EvmError::DB("missing trie node".to_string());
+        assert_eq!(compare_errors(&levm_error, &revm_error).len(), 1);
+    }
+}
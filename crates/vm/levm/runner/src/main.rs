@@ -1,5 +1,5 @@
 use bytes::Bytes;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use env_logger::Env;
 use ethrex_blockchain::vm::StoreVmDatabase;
 use ethrex_common::{
@@ -19,7 +19,10 @@ use ethrex_vm::DynVmDatabase;
 use log::{debug, error, info};
 use num_bigint::BigUint;
 use num_traits::Num;
-use runner::input::{InputAccount, InputTransaction, RunnerInput};
+use runner::{
+    input::{InputAccount, InputTransaction, RunnerInput},
+    t8n::{self, T8nArgs},
+};
 use std::{collections::BTreeMap, io::Write};
 use std::{
     fs::{self, File},
@@ -46,6 +49,15 @@ struct Cli {
         help = "Converts mnemonics file into a bytecode file"
     )]
     emit_bytes: Option<String>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    #[command(about = "State transition tool, compatible with geth's `evm t8n`")]
+    T8n(T8nArgs),
 }
 
 fn main() {
@@ -56,6 +68,14 @@ fn main() {
         .format(|buf, record| writeln!(buf, "{}", record.args()))
         .init();
 
+    if let Some(Command::T8n(args)) = cli.command {
+        if let Err(e) = t8n::run(args) {
+            error!("t8n failed: {e}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
     // Subcommand for just converting mnemonics to bytecode without executing
     if let Some(mnemonics_path) = cli.emit_bytes {
         let file_content =
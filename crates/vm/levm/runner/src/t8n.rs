@@ -0,0 +1,888 @@
+//! State transition tool compatible with geth's `evm t8n`.
+//! See https://github.com/ethereum/go-ethereum/tree/master/cmd/evm#state-transition-tool-t8n
+use bytes::Bytes;
+use clap::Args;
+use ethrex_common::{
+    Address, Bloom, H160, H256, U256,
+    constants::{DEFAULT_OMMERS_HASH, GAS_PER_BLOB},
+    serde_utils,
+    types::{
+        Account, AccountInfo, AccountUpdate, Block, BlockBody, BlockHeader, ChainConfig,
+        ELASTICITY_MULTIPLIER, Fork, Genesis, GenesisAccount, Log, Receipt, Transaction, TxKind,
+        TxType, Withdrawal, bloom_from_logs, calc_excess_blob_gas, calculate_base_fee_per_gas,
+        compute_receipts_root, compute_transactions_root, compute_withdrawals_root,
+        requests::compute_requests_hash,
+    },
+};
+use ethrex_levm::{
+    db::{Database, gen_db::GeneralizedDatabase},
+    errors::{DatabaseError, ExecutionReport, TxResult},
+    tracing::LevmCallTracer,
+    vm::{VM, VMType},
+};
+use ethrex_rlp::{
+    decode::RLPDecode,
+    encode::{PayloadRLPEncode, RLPEncode},
+    structs::Encoder,
+};
+use ethrex_vm::{
+    EvmError,
+    backends::levm::{LEVM, extract_all_requests_levm},
+    create_contract_address,
+};
+use keccak_hash::keccak;
+use log::{debug, info};
+use secp256k1::{Message, SECP256K1, SecretKey};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    io::{self, Read},
+    path::Path,
+    str::FromStr,
+    sync::Arc,
+};
+
+/// Special value for the input and output paths to use stdin and stdout.
+const STDIN: &str = "stdin";
+const STDOUT: &str = "stdout";
+
+#[derive(Args, Debug)]
+pub struct T8nArgs {
+    #[arg(
+        long = "input.alloc",
+        default_value = "alloc.json",
+        help = "Path to the pre-state allocation JSON file, or `stdin`"
+    )]
+    pub input_alloc: String,
+
+    #[arg(
+        long = "input.env",
+        default_value = "env.json",
+        help = "Path to the block environment JSON file, or `stdin`"
+    )]
+    pub input_env: String,
+
+    #[arg(
+        long = "input.txs",
+        default_value = "txs.json",
+        help = "Path to the transactions file, JSON or hex encoded RLP if it ends with `.rlp`, or `stdin`"
+    )]
+    pub input_txs: String,
+
+    #[arg(
+        long = "output.basedir",
+        default_value = "",
+        help = "Directory where the output files are written"
+    )]
+    pub output_basedir: String,
+
+    #[arg(
+        long = "output.result",
+        default_value = "result.json",
+        help = "File name of the execution result, or `stdout`"
+    )]
+    pub output_result: String,
+
+    #[arg(
+        long = "output.alloc",
+        default_value = "alloc.json",
+        help = "File name of the post-state allocation, or `stdout`"
+    )]
+    pub output_alloc: String,
+
+    #[arg(
+        long = "state.fork",
+        default_value = "Prague",
+        help = "Name of the fork to apply, from Frontier to Prague"
+    )]
+    pub fork: String,
+
+    #[arg(
+        long = "state.chainid",
+        default_value_t = 1,
+        help = "Chain id used to validate transaction signatures and to sign the ones with a `secretKey`"
+    )]
+    pub chain_id: u64,
+}
+
+/// Block environment, as described in `env.json`
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct T8nEnv {
+    pub current_coinbase: Address,
+    #[serde(deserialize_with = "serde_utils::u64::deser_hex_or_dec_str")]
+    pub current_gas_limit: u64,
+    #[serde(deserialize_with = "serde_utils::u64::deser_hex_or_dec_str")]
+    pub current_number: u64,
+    #[serde(deserialize_with = "serde_utils::u64::deser_hex_or_dec_str")]
+    pub current_timestamp: u64,
+    #[serde(default)]
+    pub current_difficulty: Option<U256>,
+    #[serde(default)]
+    pub current_random: Option<H256>,
+    #[serde(default, with = "serde_utils::u64::hex_str_opt")]
+    pub current_base_fee: Option<u64>,
+    #[serde(default, with = "serde_utils::u64::hex_str_opt")]
+    pub parent_base_fee: Option<u64>,
+    #[serde(default, with = "serde_utils::u64::hex_str_opt")]
+    pub parent_gas_used: Option<u64>,
+    #[serde(default, with = "serde_utils::u64::hex_str_opt")]
+    pub parent_gas_limit: Option<u64>,
+    #[serde(default, with = "serde_utils::u64::hex_str_opt")]
+    pub current_excess_blob_gas: Option<u64>,
+    #[serde(default, with = "serde_utils::u64::hex_str_opt")]
+    pub parent_excess_blob_gas: Option<u64>,
+    #[serde(default, with = "serde_utils::u64::hex_str_opt")]
+    pub parent_blob_gas_used: Option<u64>,
+    #[serde(default)]
+    pub parent_beacon_block_root: Option<H256>,
+    #[serde(default)]
+    pub withdrawals: Option<Vec<Withdrawal>>,
+    /// Hashes of previous blocks, indexed by block number
+    #[serde(default)]
+    pub block_hashes: HashMap<String, H256>,
+}
+
+/// All the inputs in a single JSON, used when reading from stdin
+#[derive(Deserialize, Default)]
+struct T8nStdinInput {
+    #[serde(default)]
+    alloc: Option<BTreeMap<Address, GenesisAccount>>,
+    #[serde(default)]
+    env: Option<Value>,
+    #[serde(default)]
+    txs: Option<Value>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct T8nResult {
+    pub state_root: H256,
+    pub tx_root: H256,
+    pub receipts_root: H256,
+    pub logs_hash: H256,
+    pub logs_bloom: Bloom,
+    pub receipts: Vec<T8nReceipt>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rejected: Vec<RejectedTx>,
+    #[serde(with = "serde_utils::u64::hex_str")]
+    pub gas_used: u64,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        with = "serde_utils::u64::hex_str_opt"
+    )]
+    pub current_base_fee: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub withdrawals_root: Option<H256>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        with = "serde_utils::u64::hex_str_opt"
+    )]
+    pub current_excess_blob_gas: Option<u64>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        with = "serde_utils::u64::hex_str_opt"
+    )]
+    pub blob_gas_used: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub requests_hash: Option<H256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub requests: Option<Vec<String>>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct T8nReceipt {
+    #[serde(rename = "type", with = "serde_utils::u64::hex_str")]
+    pub tx_type: u64,
+    #[serde(with = "serde_utils::u64::hex_str")]
+    pub status: u64,
+    #[serde(with = "serde_utils::u64::hex_str")]
+    pub cumulative_gas_used: u64,
+    pub logs_bloom: Bloom,
+    pub logs: Vec<T8nLog>,
+    pub transaction_hash: H256,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contract_address: Option<Address>,
+    #[serde(with = "serde_utils::u64::hex_str")]
+    pub gas_used: u64,
+    pub block_hash: H256,
+    #[serde(with = "serde_utils::u64::hex_str")]
+    pub transaction_index: u64,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct T8nLog {
+    pub address: Address,
+    pub topics: Vec<H256>,
+    #[serde(with = "serde_utils::bytes")]
+    pub data: Bytes,
+    #[serde(with = "serde_utils::u64::hex_str")]
+    pub block_number: u64,
+    pub block_hash: H256,
+    pub transaction_hash: H256,
+    #[serde(with = "serde_utils::u64::hex_str")]
+    pub transaction_index: u64,
+    #[serde(with = "serde_utils::u64::hex_str")]
+    pub log_index: u64,
+}
+
+/// Transaction that couldn't be included in the block
+#[derive(Serialize, Debug)]
+pub struct RejectedTx {
+    pub index: usize,
+    pub error: String,
+}
+
+/// Pre-state accounts are loaded in the cache beforehand, the database only provides the
+/// chain config and the hashes of previous blocks.
+struct T8nDatabase {
+    chain_config: ChainConfig,
+    block_hashes: HashMap<u64, H256>,
+}
+
+impl Database for T8nDatabase {
+    fn get_account_info(&self, _address: Address) -> Result<AccountInfo, DatabaseError> {
+        Ok(AccountInfo::default())
+    }
+
+    fn get_storage_value(&self, _address: Address, _key: H256) -> Result<U256, DatabaseError> {
+        Ok(U256::zero())
+    }
+
+    fn get_block_hash(&self, block_number: u64) -> Result<H256, DatabaseError> {
+        self.block_hashes
+            .get(&block_number)
+            .copied()
+            .ok_or_else(|| {
+                DatabaseError::Custom(format!("Missing hash of block {block_number} in env"))
+            })
+    }
+
+    fn get_chain_config(&self) -> Result<ChainConfig, DatabaseError> {
+        Ok(self.chain_config)
+    }
+
+    fn get_account_code(&self, _code_hash: H256) -> Result<Bytes, DatabaseError> {
+        Ok(Bytes::new())
+    }
+}
+
+/// Runs the `t8n` subcommand: reads the inputs, applies the transactions and writes the outputs.
+pub fn run(args: T8nArgs) -> Result<(), String> {
+    let fork = parse_fork(&args.fork)?;
+    let chain_config = chain_config(fork, args.chain_id)?;
+
+    let mut stdin_input = if [&args.input_alloc, &args.input_env, &args.input_txs]
+        .iter()
+        .any(|input| *input == STDIN)
+    {
+        let mut content = String::new();
+        io::stdin()
+            .read_to_string(&mut content)
+            .map_err(|error| format!("Failed to read stdin: {error}"))?;
+        serde_json::from_str(&content)
+            .map_err(|error| format!("Failed to parse stdin input: {error}"))?
+    } else {
+        T8nStdinInput::default()
+    };
+
+    let alloc: BTreeMap<Address, GenesisAccount> = if args.input_alloc == STDIN {
+        stdin_input.alloc.take().ok_or("Missing alloc in stdin")?
+    } else {
+        read_json(&args.input_alloc)?
+    };
+    let env: T8nEnv = if args.input_env == STDIN {
+        let env = stdin_input.env.take().ok_or("Missing env in stdin")?;
+        serde_json::from_value(env).map_err(|error| format!("Failed to parse env: {error}"))?
+    } else {
+        read_json(&args.input_env)?
+    };
+    let txs = if args.input_txs == STDIN {
+        match stdin_input.txs.take().ok_or("Missing txs in stdin")? {
+            Value::String(rlp) => decode_rlp_txs(&rlp)?,
+            txs => parse_json_txs(txs, args.chain_id)?,
+        }
+    } else if args.input_txs.ends_with(".rlp") {
+        decode_rlp_txs(&read_json::<String>(&args.input_txs)?)?
+    } else {
+        parse_json_txs(read_json(&args.input_txs)?, args.chain_id)?
+    };
+
+    info!(
+        "Applying {} transactions on top of {} accounts with fork {fork:?}",
+        txs.len(),
+        alloc.len()
+    );
+    let (result, post_alloc) = transition(alloc, &env, txs, fork, chain_config)?;
+
+    let mut stdout = serde_json::Map::new();
+    for (name, path, value) in [
+        ("result", &args.output_result, to_json(&result)?),
+        ("alloc", &args.output_alloc, to_json(&post_alloc)?),
+    ] {
+        if path == STDOUT {
+            stdout.insert(name.to_string(), value);
+            continue;
+        }
+        let path = Path::new(&args.output_basedir).join(path);
+        let content = serde_json::to_string_pretty(&value)
+            .map_err(|error| format!("Failed to serialize {name}: {error}"))?;
+        fs::write(&path, content)
+            .map_err(|error| format!("Failed to write {}: {error}", path.display()))?;
+        debug!("Wrote {name} to {}", path.display());
+    }
+    if !stdout.is_empty() {
+        let content = serde_json::to_string_pretty(&stdout)
+            .map_err(|error| format!("Failed to serialize output: {error}"))?;
+        println!("{content}");
+    }
+
+    Ok(())
+}
+
+/// Executes the transactions in a block built from the environment, returning the result and the post-state.
+/// Transactions that are invalid are skipped and reported as rejected.
+pub fn transition(
+    mut alloc: BTreeMap<Address, GenesisAccount>,
+    env: &T8nEnv,
+    txs: Vec<Transaction>,
+    fork: Fork,
+    chain_config: ChainConfig,
+) -> Result<(T8nResult, BTreeMap<Address, GenesisAccount>), String> {
+    let block_hashes: HashMap<u64, H256> = env
+        .block_hashes
+        .iter()
+        .map(|(number, hash)| Ok((parse_u64(number)?, *hash)))
+        .collect::<Result<_, String>>()?;
+    let header = block_header(env, fork, &chain_config, &block_hashes)?;
+    let max_blob_gas = chain_config
+        .get_fork_blob_schedule(header.timestamp)
+        .map(|schedule| schedule.max * GAS_PER_BLOB)
+        .unwrap_or_default();
+
+    let store = T8nDatabase {
+        chain_config,
+        block_hashes,
+    };
+    let initial_state = alloc
+        .iter()
+        .map(|(address, account)| (*address, Account::from(account.clone())))
+        .collect();
+    let mut db = GeneralizedDatabase::new_with_account_state(Arc::new(store), initial_state);
+
+    let block = Block {
+        header,
+        body: BlockBody {
+            transactions: Vec::new(),
+            ommers: Vec::new(),
+            withdrawals: env.withdrawals.clone(),
+        },
+    };
+    LEVM::prepare_block(&block, &mut db, VMType::L1).map_err(|error| error.to_string())?;
+
+    let mut included_txs = Vec::new();
+    let mut receipts = Vec::new();
+    let mut t8n_receipts = Vec::new();
+    let mut rejected = Vec::new();
+    let mut cumulative_gas_used = 0_u64;
+    let mut blob_gas_used = 0_u64;
+    let mut log_index = 0_u64;
+
+    for (index, tx) in txs.into_iter().enumerate() {
+        let sender = match tx.sender() {
+            Ok(sender) => sender,
+            Err(error) => {
+                rejected.push(RejectedTx {
+                    index,
+                    error: format!("invalid signature: {error}"),
+                });
+                continue;
+            }
+        };
+        if tx.gas_limit() > block.header.gas_limit - cumulative_gas_used {
+            rejected.push(RejectedTx {
+                index,
+                error: "gas limit reached".to_string(),
+            });
+            continue;
+        }
+        let tx_blob_gas = tx.blob_versioned_hashes().len() as u64 * GAS_PER_BLOB;
+        if blob_gas_used + tx_blob_gas > max_blob_gas {
+            rejected.push(RejectedTx {
+                index,
+                error: format!(
+                    "blob gas limit reached: used {blob_gas_used}, tx {tx_blob_gas}, max {max_blob_gas}"
+                ),
+            });
+            continue;
+        }
+
+        let report = match execute_tx(&tx, sender, &block.header, &mut db, fork) {
+            Ok(report) => report,
+            Err(error) => {
+                rejected.push(RejectedTx {
+                    index,
+                    error: error.to_string(),
+                });
+                continue;
+            }
+        };
+
+        cumulative_gas_used += report.gas_used;
+        blob_gas_used += tx_blob_gas;
+
+        let transaction_hash = tx.compute_hash();
+        let transaction_index = included_txs.len() as u64;
+        let receipt = Receipt::new(
+            tx.tx_type(),
+            matches!(report.result, TxResult::Success),
+            cumulative_gas_used,
+            report.logs.clone(),
+        );
+        let logs = report
+            .logs
+            .iter()
+            .map(|log| {
+                let log = T8nLog {
+                    address: log.address,
+                    topics: log.topics.clone(),
+                    data: log.data.clone(),
+                    block_number: block.header.number,
+                    // Set once the block is built
+                    block_hash: H256::zero(),
+                    transaction_hash,
+                    transaction_index,
+                    log_index,
+                };
+                log_index += 1;
+                log
+            })
+            .collect();
+        let contract_address = match tx.to() {
+            TxKind::Create => Some(create_contract_address(sender, tx.nonce())),
+            TxKind::Call(_) => None,
+        };
+        t8n_receipts.push(T8nReceipt {
+            tx_type: tx.tx_type() as u64,
+            status: receipt.succeeded.into(),
+            cumulative_gas_used,
+            logs_bloom: bloom_from_logs(&receipt.logs),
+            logs,
+            transaction_hash,
+            contract_address,
+            gas_used: report.gas_used,
+            // Set once the block is built
+            block_hash: H256::zero(),
+            transaction_index,
+        });
+        receipts.push(receipt);
+        included_txs.push(tx);
+    }
+
+    if let Some(withdrawals) = &env.withdrawals {
+        LEVM::process_withdrawals(&mut db, withdrawals).map_err(|error| error.to_string())?;
+    }
+
+    let requests = if fork >= Fork::Prague {
+        let requests = extract_all_requests_levm(&receipts, &mut db, &block.header, VMType::L1)
+            .map_err(|error| error.to_string())?
+            .iter()
+            .map(|requests| requests.encode())
+            .collect::<Vec<_>>();
+        Some(requests)
+    } else {
+        None
+    };
+
+    let account_updates =
+        LEVM::get_state_transitions(&mut db).map_err(|error| error.to_string())?;
+    apply_account_updates(&mut alloc, account_updates);
+
+    let all_logs: Vec<Log> = receipts
+        .iter()
+        .flat_map(|receipt| receipt.logs.clone())
+        .collect();
+    let genesis = Genesis {
+        alloc: alloc.clone(),
+        ..Default::default()
+    };
+
+    // The block resulting from the transition, its hash is the one referenced by the receipts
+    let header = BlockHeader {
+        state_root: genesis.compute_state_root(),
+        transactions_root: compute_transactions_root(&included_txs),
+        receipts_root: compute_receipts_root(&receipts),
+        logs_bloom: bloom_from_logs(&all_logs),
+        gas_used: cumulative_gas_used,
+        blob_gas_used: (fork >= Fork::Cancun).then_some(blob_gas_used),
+        requests_hash: requests.as_deref().map(compute_requests_hash),
+        // Don't reuse the hash cached for the header used during the execution
+        hash: Default::default(),
+        ..block.header
+    };
+    let block_hash = header.hash();
+    for receipt in &mut t8n_receipts {
+        receipt.block_hash = block_hash;
+        for log in &mut receipt.logs {
+            log.block_hash = block_hash;
+        }
+    }
+
+    let result = T8nResult {
+        state_root: header.state_root,
+        tx_root: header.transactions_root,
+        receipts_root: header.receipts_root,
+        logs_hash: keccak(all_logs.encode_to_vec()),
+        logs_bloom: header.logs_bloom,
+        receipts: t8n_receipts,
+        rejected,
+        gas_used: cumulative_gas_used,
+        current_base_fee: header.base_fee_per_gas,
+        withdrawals_root: header.withdrawals_root,
+        current_excess_blob_gas: header.excess_blob_gas,
+        blob_gas_used: header.blob_gas_used,
+        requests_hash: header.requests_hash,
+        requests: requests.map(|requests| {
+            requests
+                .into_iter()
+                .map(|encoded| format!("0x{}", hex::encode(encoded.0)))
+                .collect()
+        }),
+    };
+
+    Ok((result, alloc))
+}
+
+/// Executes the transaction with the given fork.
+/// The chain config can't express forks before Paris, so the fork of the environment is overridden.
+fn execute_tx(
+    tx: &Transaction,
+    sender: Address,
+    header: &BlockHeader,
+    db: &mut GeneralizedDatabase,
+    fork: Fork,
+) -> Result<ExecutionReport, EvmError> {
+    let mut env = LEVM::setup_env(tx, sender, header, db, VMType::L1)?;
+    env.config.fork = fork;
+    let mut vm = VM::new(env, db, tx, LevmCallTracer::disabled(), VMType::L1)?;
+    Ok(vm.execute()?)
+}
+
+/// Builds the header of the block being applied from the environment.
+/// The base fee and excess blob gas are computed from the parent values when not given.
+fn block_header(
+    env: &T8nEnv,
+    fork: Fork,
+    chain_config: &ChainConfig,
+    block_hashes: &HashMap<u64, H256>,
+) -> Result<BlockHeader, String> {
+    let base_fee_per_gas = match (
+        env.current_base_fee,
+        env.parent_base_fee,
+        env.parent_gas_used,
+        env.parent_gas_limit,
+    ) {
+        _ if fork < Fork::London => None,
+        (Some(base_fee), _, _, _) => Some(base_fee),
+        (None, Some(parent_base_fee), Some(parent_gas_used), Some(parent_gas_limit)) => Some(
+            calculate_base_fee_per_gas(
+                env.current_gas_limit,
+                parent_gas_limit,
+                parent_gas_used,
+                parent_base_fee,
+                ELASTICITY_MULTIPLIER,
+            )
+            .ok_or("Invalid gas limit in relation to the parent's one")?,
+        ),
+        _ => return Err("Missing currentBaseFee in env".to_string()),
+    };
+
+    let excess_blob_gas = if fork >= Fork::Cancun {
+        let excess_blob_gas = match (
+            env.current_excess_blob_gas,
+            env.parent_excess_blob_gas,
+            env.parent_blob_gas_used,
+        ) {
+            (Some(excess_blob_gas), _, _) => excess_blob_gas,
+            (None, Some(parent_excess_blob_gas), Some(parent_blob_gas_used)) => {
+                let schedule = chain_config
+                    .get_fork_blob_schedule(env.current_timestamp)
+                    .ok_or("Missing blob schedule")?;
+                calc_excess_blob_gas(
+                    parent_excess_blob_gas,
+                    parent_blob_gas_used,
+                    schedule.target,
+                )
+            }
+            _ => 0,
+        };
+        Some(excess_blob_gas)
+    } else {
+        None
+    };
+
+    let parent_hash = env
+        .current_number
+        .checked_sub(1)
+        .and_then(|parent_number| block_hashes.get(&parent_number))
+        .copied()
+        .unwrap_or_default();
+
+    Ok(BlockHeader {
+        parent_hash,
+        ommers_hash: *DEFAULT_OMMERS_HASH,
+        coinbase: env.current_coinbase,
+        difficulty: env.current_difficulty.unwrap_or_default(),
+        number: env.current_number,
+        gas_limit: env.current_gas_limit,
+        timestamp: env.current_timestamp,
+        prev_randao: env.current_random.unwrap_or_default(),
+        base_fee_per_gas,
+        withdrawals_root: env
+            .withdrawals
+            .as_ref()
+            .map(|withdrawals| compute_withdrawals_root(withdrawals)),
+        blob_gas_used: (fork >= Fork::Cancun).then_some(0),
+        excess_blob_gas,
+        parent_beacon_block_root: env.parent_beacon_block_root,
+        ..Default::default()
+    })
+}
+
+/// Writes the changes made by the execution into the allocation.
+fn apply_account_updates(
+    alloc: &mut BTreeMap<Address, GenesisAccount>,
+    account_updates: Vec<AccountUpdate>,
+) {
+    for update in account_updates {
+        if update.removed {
+            alloc.remove(&update.address);
+            continue;
+        }
+        let account = alloc
+            .entry(update.address)
+            .or_insert_with(|| GenesisAccount {
+                code: Bytes::new(),
+                storage: HashMap::new(),
+                balance: U256::zero(),
+                nonce: 0,
+            });
+        if let Some(info) = update.info {
+            account.balance = info.balance;
+            account.nonce = info.nonce;
+        }
+        if let Some(code) = update.code {
+            account.code = code;
+        }
+        for (key, value) in update.added_storage {
+            let key = U256::from_big_endian(key.as_bytes());
+            if value.is_zero() {
+                account.storage.remove(&key);
+            } else {
+                account.storage.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Parses the fork by its name, accepting the aliases used by geth and the execution-spec-tests.
+/// Forks after Prague aren't implemented by LEVM yet.
+pub fn parse_fork(name: &str) -> Result<Fork, String> {
+    let fork = match name {
+        "EIP150" => Fork::Tangerine,
+        "EIP158" => Fork::SpuriousDragon,
+        "ConstantinopleFix" => Fork::Petersburg,
+        "Merge" => Fork::Paris,
+        name => serde_json::from_value(Value::String(name.to_string()))
+            .map_err(|_| format!("Unknown fork {name}"))?,
+    };
+    if fork > Fork::Prague {
+        return Err(format!("Fork {name} is not supported"));
+    }
+    Ok(fork)
+}
+
+/// Chain config in which the given fork and the previous ones are active from genesis
+pub fn chain_config(fork: Fork, chain_id: u64) -> Result<ChainConfig, String> {
+    let activation = |activation_fork: Fork| (fork >= activation_fork).then_some(0);
+    Ok(ChainConfig {
+        chain_id,
+        homestead_block: activation(Fork::Homestead),
+        dao_fork_block: activation(Fork::DaoFork),
+        dao_fork_support: true,
+        eip150_block: activation(Fork::Tangerine),
+        eip155_block: activation(Fork::SpuriousDragon),
+        eip158_block: activation(Fork::SpuriousDragon),
+        byzantium_block: activation(Fork::Byzantium),
+        constantinople_block: activation(Fork::Constantinople),
+        petersburg_block: activation(Fork::Petersburg),
+        istanbul_block: activation(Fork::Istanbul),
+        muir_glacier_block: activation(Fork::MuirGlacier),
+        berlin_block: activation(Fork::Berlin),
+        london_block: activation(Fork::London),
+        arrow_glacier_block: activation(Fork::ArrowGlacier),
+        gray_glacier_block: activation(Fork::GrayGlacier),
+        merge_netsplit_block: activation(Fork::Paris),
+        terminal_total_difficulty: activation(Fork::Paris).map(u128::from),
+        terminal_total_difficulty_passed: fork >= Fork::Paris,
+        shanghai_time: activation(Fork::Shanghai),
+        cancun_time: activation(Fork::Cancun),
+        prague_time: activation(Fork::Prague),
+        // Mainnet address
+        deposit_contract_address: H160::from_str("0x00000000219ab540356cbb839cbe05303d7705fa")
+            .map_err(|error| error.to_string())?,
+        ..Default::default()
+    })
+}
+
+/// Parses the transactions in the JSON format of geth's t8n.
+/// Like geth, transactions without a `type` are legacy ones, and the ones with a `secretKey` are signed with it.
+fn parse_json_txs(txs: Value, chain_id: u64) -> Result<Vec<Transaction>, String> {
+    let Value::Array(txs) = txs else {
+        return Err("Transactions must be a JSON array".to_string());
+    };
+    txs.into_iter()
+        .enumerate()
+        .map(|(index, tx)| {
+            parse_json_tx(tx, chain_id).map_err(|error| format!("Invalid tx {index}: {error}"))
+        })
+        .collect()
+}
+
+fn parse_json_tx(tx: Value, chain_id: u64) -> Result<Transaction, String> {
+    let Value::Object(mut fields) = tx else {
+        return Err("not a JSON object".to_string());
+    };
+    let secret_key = fields
+        .remove("secretKey")
+        .map(|secret_key| {
+            let secret_key: H256 = serde_json::from_value(secret_key)
+                .map_err(|error| format!("invalid secretKey: {error}"))?;
+            SecretKey::from_slice(secret_key.as_bytes())
+                .map_err(|error| format!("invalid secretKey: {error}"))
+        })
+        .transpose()?;
+    // Legacy transactions are replay protected with EIP-155 unless it's disabled
+    let protected = fields
+        .remove("protected")
+        .and_then(|protected| protected.as_bool())
+        .unwrap_or(true);
+
+    let zero = || Value::String("0x0".to_string());
+    fields.entry("type").or_insert_with(zero);
+    if secret_key.is_some() {
+        for field in ["v", "r", "s"] {
+            fields.entry(field).or_insert_with(zero);
+        }
+    }
+    // geth only outputs `v` for typed transactions, it's the y parity
+    if let Some(v) = fields.get("v").cloned() {
+        fields.entry("yParity").or_insert(v);
+    }
+
+    let mut tx: Transaction =
+        serde_json::from_value(Value::Object(fields)).map_err(|error| error.to_string())?;
+    if let Some(secret_key) = secret_key {
+        sign_tx(&mut tx, &secret_key, chain_id, protected)?;
+    }
+    Ok(tx)
+}
+
+/// Signs the transaction in place, like geth does for the transactions given with a `secretKey`
+fn sign_tx(
+    tx: &mut Transaction,
+    secret_key: &SecretKey,
+    chain_id: u64,
+    protected: bool,
+) -> Result<(), String> {
+    let sign = |payload: &[u8]| {
+        let message = Message::from_digest(keccak(payload).0);
+        let (recovery_id, signature) = SECP256K1
+            .sign_ecdsa_recoverable(&message, secret_key)
+            .serialize_compact();
+        (
+            U256::from_big_endian(&signature[..32]),
+            U256::from_big_endian(&signature[32..]),
+            recovery_id.to_i32() != 0,
+        )
+    };
+    let typed_payload = |tx_type: TxType, payload: Vec<u8>| [vec![tx_type as u8], payload].concat();
+
+    match tx {
+        Transaction::LegacyTransaction(tx) => {
+            let mut payload = Vec::new();
+            let encoder = Encoder::new(&mut payload)
+                .encode_field(&tx.nonce)
+                .encode_field(&tx.gas_price)
+                .encode_field(&tx.gas)
+                .encode_field(&tx.to)
+                .encode_field(&tx.value)
+                .encode_field(&tx.data);
+            if protected {
+                encoder
+                    .encode_field(&chain_id)
+                    .encode_field(&0u8)
+                    .encode_field(&0u8)
+                    .finish();
+            } else {
+                encoder.finish();
+            }
+            let (r, s, y_parity) = sign(&payload);
+            let v = if protected {
+                chain_id * 2 + 35 + u64::from(y_parity)
+            } else {
+                27 + u64::from(y_parity)
+            };
+            (tx.v, tx.r, tx.s) = (U256::from(v), r, s);
+        }
+        Transaction::EIP2930Transaction(tx) => {
+            (tx.signature_r, tx.signature_s, tx.signature_y_parity) =
+                sign(&typed_payload(TxType::EIP2930, tx.encode_payload_to_vec()));
+        }
+        Transaction::EIP1559Transaction(tx) => {
+            (tx.signature_r, tx.signature_s, tx.signature_y_parity) =
+                sign(&typed_payload(TxType::EIP1559, tx.encode_payload_to_vec()));
+        }
+        Transaction::EIP4844Transaction(tx) => {
+            (tx.signature_r, tx.signature_s, tx.signature_y_parity) =
+                sign(&typed_payload(TxType::EIP4844, tx.encode_payload_to_vec()));
+        }
+        Transaction::EIP7702Transaction(tx) => {
+            (tx.signature_r, tx.signature_s, tx.signature_y_parity) =
+                sign(&typed_payload(TxType::EIP7702, tx.encode_payload_to_vec()));
+        }
+        Transaction::PrivilegedL2Transaction(_) => {
+            return Err("privileged transactions can't be signed".to_string());
+        }
+    }
+    Ok(())
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &str) -> Result<T, String> {
+    let content =
+        fs::read_to_string(path).map_err(|error| format!("Failed to read {path}: {error}"))?;
+    serde_json::from_str(&content).map_err(|error| format!("Failed to parse {path}: {error}"))
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<Value, String> {
+    serde_json::to_value(value).map_err(|error| format!("Failed to serialize output: {error}"))
+}
+
+/// Decodes the hex encoded RLP list of transactions
+fn decode_rlp_txs(rlp: &str) -> Result<Vec<Transaction>, String> {
+    let bytes = hex::decode(rlp.trim_start_matches("0x"))
+        .map_err(|error| format!("Invalid hex in txs: {error}"))?;
+    Vec::<Transaction>::decode(&bytes).map_err(|error| format!("Invalid RLP in txs: {error}"))
+}
+
+fn parse_u64(value: &str) -> Result<u64, String> {
+    match value.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => value.parse(),
+    }
+    .map_err(|error| format!("Invalid number {value}: {error}"))
+}
@@ -0,0 +1,82 @@
+use ethrex_common::{H256, types::Fork};
+use runner::t8n::{self, T8nArgs};
+use serde_json::Value;
+use std::{fs, path::Path};
+
+/// Checks that every field in `expected` has the same value in `actual`, which can have more fields
+fn assert_subset(expected: &Value, actual: &Value, path: &str) {
+    match (expected, actual) {
+        (Value::Object(expected), Value::Object(actual)) => {
+            for (key, expected) in expected {
+                let actual = actual
+                    .get(key)
+                    .unwrap_or_else(|| panic!("Missing {path}.{key}"));
+                assert_subset(expected, actual, &format!("{path}.{key}"));
+            }
+        }
+        (Value::Array(expected), Value::Array(actual)) => {
+            assert_eq!(expected.len(), actual.len(), "Length of {path}");
+            for (index, (expected, actual)) in expected.iter().zip(actual).enumerate() {
+                assert_subset(expected, actual, &format!("{path}[{index}]"));
+            }
+        }
+        _ => assert_eq!(expected, actual, "Value of {path}"),
+    }
+}
+
+fn read_json(path: &Path) -> Value {
+    serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+}
+
+#[test]
+fn t8n_geth_fixture() {
+    let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/t8n");
+    let output = std::env::temp_dir().join(format!("ethrex_t8n_{}", std::process::id()));
+    fs::create_dir_all(&output).unwrap();
+
+    t8n::run(T8nArgs {
+        input_alloc: fixture.join("alloc.json").display().to_string(),
+        input_env: fixture.join("env.json").display().to_string(),
+        input_txs: fixture.join("txs.json").display().to_string(),
+        output_basedir: output.display().to_string(),
+        output_result: "result.json".to_string(),
+        output_alloc: "alloc.json".to_string(),
+        fork: "Cancun".to_string(),
+        chain_id: 1,
+    })
+    .unwrap();
+
+    let expected = read_json(&fixture.join("exp.json"));
+    let result = read_json(&output.join("result.json"));
+    let alloc = read_json(&output.join("alloc.json"));
+    fs::remove_dir_all(&output).unwrap();
+
+    assert_subset(&expected["alloc"], &alloc, "alloc");
+    assert_subset(&expected["result"], &result, "result");
+
+    // The receipts reference the hash of the resulting block
+    let block_hashes: Vec<&Value> = result["receipts"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|receipt| &receipt["blockHash"])
+        .collect();
+    assert_ne!(
+        block_hashes[0],
+        &Value::String(format!("{:#x}", H256::zero()))
+    );
+    assert!(block_hashes.iter().all(|hash| *hash == block_hashes[0]));
+}
+
+#[test]
+fn t8n_parse_fork() {
+    assert_eq!(t8n::parse_fork("Frontier").unwrap(), Fork::Frontier);
+    assert_eq!(t8n::parse_fork("EIP150").unwrap(), Fork::Tangerine);
+    assert_eq!(
+        t8n::parse_fork("ConstantinopleFix").unwrap(),
+        Fork::Petersburg
+    );
+    assert_eq!(t8n::parse_fork("Merge").unwrap(), Fork::Paris);
+    assert!(t8n::parse_fork("Osaka").is_err());
+    assert!(t8n::parse_fork("Unknown").is_err());
+}
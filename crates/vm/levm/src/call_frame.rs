@@ -1,9 +1,10 @@
 use crate::{
     account::LevmAccount,
+    code_cache::AnalyzedCode,
     constants::STACK_LIMIT,
     errors::{ExceptionalHalt, InternalError, VMError},
     memory::Memory,
-    utils::{JumpTargetFilter, restore_cache_state},
+    utils::restore_cache_state,
     vm::VM,
 };
 use bytes::Bytes;
@@ -12,6 +13,7 @@ use keccak_hash::H256;
 use std::{
     collections::{BTreeMap, HashMap},
     fmt,
+    sync::Arc,
 };
 
 #[derive(Clone, PartialEq, Eq)]
@@ -138,6 +140,11 @@ impl Stack {
         self.offset == self.values.len()
     }
 
+    /// Values currently in the stack, starting from the top.
+    pub fn as_slice(&self) -> &[U256] {
+        self.values.get(self.offset..).unwrap_or_default()
+    }
+
     pub fn get(&self, index: usize) -> Result<&U256, ExceptionalHalt> {
         // The following index cannot fail because `self.offset` is known to be within
         // `STACK_LIMIT`.
@@ -215,6 +222,8 @@ pub struct CallFrame {
     pub code_address: Address,
     /// Bytecode to execute
     pub bytecode: Bytes,
+    /// Analysis of the bytecode, shared with the other callframes executing it
+    pub code: Arc<AnalyzedCode>,
     /// Value sent along the transaction
     pub msg_value: U256,
     pub stack: Stack,
@@ -229,9 +238,6 @@ pub struct CallFrame {
     pub is_static: bool,
     /// Call stack current depth
     pub depth: usize,
-    /// Lazy blacklist of jump targets. Contains all offsets of 0x5B (JUMPDEST) in literals (after
-    /// push instructions).
-    pub jump_target_filter: JumpTargetFilter,
     /// This is set to true if the function that created this callframe is CREATE or CREATE2
     pub is_create: bool,
     /// Everytime we want to write an account during execution of a callframe we store the pre-write state so that we can restore if it reverts
@@ -289,7 +295,7 @@ impl CallFrame {
         msg_sender: Address,
         to: Address,
         code_address: Address,
-        bytecode: Bytes,
+        code: Arc<AnalyzedCode>,
         msg_value: U256,
         calldata: Bytes,
         is_static: bool,
@@ -309,12 +315,12 @@ impl CallFrame {
             msg_sender,
             to,
             code_address,
-            bytecode: bytecode.clone(),
+            bytecode: code.bytecode(),
+            code,
             msg_value,
             calldata,
             is_static,
             depth,
-            jump_target_filter: JumpTargetFilter::new(bytecode),
             should_transfer_value,
             is_create,
             ret_offset,
@@ -330,8 +336,7 @@ impl CallFrame {
 
     #[inline(always)]
     pub fn next_opcode(&self) -> u8 {
-        // 0 is the opcode stop.
-        self.bytecode.get(self.pc).copied().unwrap_or(0)
+        self.code.opcode_at(self.pc)
     }
 
     pub fn increment_pc_by(&mut self, count: usize) -> Result<(), VMError> {
@@ -352,9 +357,9 @@ impl CallFrame {
         Ok(())
     }
 
-    pub fn set_code(&mut self, code: Bytes) -> Result<(), VMError> {
-        self.jump_target_filter = JumpTargetFilter::new(code.clone());
-        self.bytecode = code;
+    pub fn set_code(&mut self, code: Arc<AnalyzedCode>) -> Result<(), VMError> {
+        self.bytecode = code.bytecode();
+        self.code = code;
         Ok(())
     }
 }
@@ -0,0 +1,99 @@
+//! Analysis of the bytecode shared by the callframes that execute it.
+//!
+//! The analyses are cached by code hash in the [GeneralizedDatabase](crate::db::gen_db::GeneralizedDatabase),
+//! so they live as long as the block execution and no lock is taken when a callframe is created.
+//! The static gas of basic blocks isn't precomputed and push immediates aren't pre-decoded,
+//! the opcode handlers still charge the gas and read the immediates one instruction at a time.
+
+use bytes::Bytes;
+
+use crate::opcodes::Opcode;
+
+/// Zeros appended to the bytecode so the immediates of a PUSH32 at its last byte can be read.
+const CODE_PADDING: usize = 33;
+
+/// Bytecode analysed once so that callframes executing it don't have to walk it again.
+#[derive(Debug)]
+pub struct AnalyzedCode {
+    /// Bytecode followed by zeros, the push immediates can be read past the end of the code without bounds checks
+    padded: Bytes,
+    len: usize,
+    /// Bitmap of the valid jump destinations, JUMPDEST opcodes that are not part of a push immediate
+    jumpdests: Box<[u64]>,
+}
+
+impl Default for AnalyzedCode {
+    fn default() -> Self {
+        Self::new(&Bytes::new())
+    }
+}
+
+impl AnalyzedCode {
+    pub fn new(bytecode: &Bytes) -> Self {
+        let len = bytecode.len();
+        let mut padded = Vec::with_capacity(len.saturating_add(CODE_PADDING));
+        padded.extend_from_slice(bytecode);
+        padded.resize(len.saturating_add(CODE_PADDING), 0);
+
+        let mut jumpdests = vec![0u64; len.div_ceil(64)].into_boxed_slice();
+        let push1 = u8::from(Opcode::PUSH1);
+        let push32 = u8::from(Opcode::PUSH32);
+        let jumpdest = u8::from(Opcode::JUMPDEST);
+        let mut pc = 0;
+        while let Some(&opcode) = bytecode.get(pc) {
+            if opcode == jumpdest {
+                let (index, mask) = bitmap_position(pc);
+                if let Some(word) = jumpdests.get_mut(index) {
+                    *word |= mask;
+                }
+            }
+            // Skip the immediates of push instructions, a JUMPDEST inside of them isn't valid
+            let immediates = if (push1..=push32).contains(&opcode) {
+                usize::from(opcode.saturating_sub(push1)).saturating_add(1)
+            } else {
+                0
+            };
+            pc = pc.saturating_add(immediates).saturating_add(1);
+        }
+
+        Self {
+            padded: padded.into(),
+            len,
+            jumpdests,
+        }
+    }
+
+    /// The original bytecode, without padding
+    pub fn bytecode(&self) -> Bytes {
+        self.padded.slice(..self.len)
+    }
+
+    /// The bytecode followed by at least 33 zeros
+    #[inline(always)]
+    pub fn padded(&self) -> &[u8] {
+        &self.padded
+    }
+
+    /// Opcode at the given pc, STOP if it's past the end of the code
+    #[inline(always)]
+    pub fn opcode_at(&self, pc: usize) -> u8 {
+        self.padded.get(pc).copied().unwrap_or(0)
+    }
+
+    /// Whether the pc can be the target of a JUMP or JUMPI
+    #[inline(always)]
+    pub fn is_jumpdest(&self, pc: usize) -> bool {
+        let (index, mask) = bitmap_position(pc);
+        self.jumpdests
+            .get(index)
+            .is_some_and(|word| word & mask != 0)
+    }
+}
+
+/// Index of the word in the bitmap and mask of the bit for the given pc
+#[inline(always)]
+fn bitmap_position(pc: usize) -> (usize, u64) {
+    // Dividing by a non-zero constant and shifting by less than 64 can't fail.
+    #[expect(clippy::arithmetic_side_effects)]
+    (pc / 64, 1 << (pc % 64))
+}
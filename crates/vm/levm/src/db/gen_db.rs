@@ -12,6 +12,7 @@ use keccak_hash::keccak;
 use super::Database;
 use crate::account::LevmAccount;
 use crate::call_frame::CallFrameBackup;
+use crate::code_cache::AnalyzedCode;
 use crate::errors::InternalError;
 use crate::errors::VMError;
 use crate::utils::account_to_levm_account;
@@ -27,6 +28,8 @@ pub struct GeneralizedDatabase {
     pub current_accounts_state: CacheDB,
     pub initial_accounts_state: CacheDB,
     pub codes: BTreeMap<H256, Bytes>,
+    /// Analysis of the codes executed so far, shared by the callframes running them
+    pub analyzed_codes: BTreeMap<H256, Arc<AnalyzedCode>>,
     pub tx_backup: Option<CallFrameBackup>,
     /// For keeping track of all destroyed accounts during block execution.
     /// Used in get_state_transitions for edge case in which account is destroyed and re-created afterwards
@@ -43,6 +46,7 @@ impl GeneralizedDatabase {
             tx_backup: None,
             destroyed_accounts: HashSet::new(),
             codes: BTreeMap::new(),
+            analyzed_codes: BTreeMap::new(),
         }
     }
 
@@ -66,6 +70,7 @@ impl GeneralizedDatabase {
             tx_backup: None,
             destroyed_accounts: HashSet::new(),
             codes,
+            analyzed_codes: BTreeMap::new(),
         }
     }
 
@@ -108,6 +113,14 @@ impl GeneralizedDatabase {
         }
     }
 
+    /// Gets the analysis of the code with the given hash, analysing it the first time it's executed.
+    pub fn get_analyzed_code(&mut self, code_hash: H256, bytecode: &Bytes) -> Arc<AnalyzedCode> {
+        self.analyzed_codes
+            .entry(code_hash)
+            .or_insert_with(|| Arc::new(AnalyzedCode::new(bytecode)))
+            .clone()
+    }
+
     /// Shortcut for getting the code when we only have the address of an account and we don't need anything else.
     pub fn get_account_code(&mut self, address: Address) -> Result<&Bytes, InternalError> {
         let code_hash = self.get_account(address)?.info.code_hash;
@@ -141,6 +154,22 @@ impl GeneralizedDatabase {
         Ok(value)
     }
 
+    /// Gets the current value of a storage slot, caching it if not already cached.
+    /// Same as `VM::get_storage_value` but meant to be used outside of the VM.
+    pub fn get_storage_value(
+        &mut self,
+        address: Address,
+        key: H256,
+    ) -> Result<U256, InternalError> {
+        let cached_value = self.get_account(address)?.storage.get(&key).copied();
+        if let Some(value) = cached_value {
+            return Ok(value);
+        }
+        let value = self.get_value_from_database(address, key)?;
+        self.get_account_mut(address)?.storage.insert(key, value);
+        Ok(value)
+    }
+
     /// Gets the transaction backup, if it exists.
     /// It only works if the `BackupHook` was enabled during the transaction execution.
     pub fn get_tx_backup(&self) -> Result<CallFrameBackup, InternalError> {
@@ -203,6 +232,21 @@ impl<'a> VM<'a> {
         Ok(())
     }
 
+    /// Credits a fee paid at the end of the transaction.
+    /// If `deferred_fees` is set the fee is only accumulated there, to be paid by the caller.
+    pub fn pay_fee(&mut self, recipient: Address, fee: U256) -> Result<(), InternalError> {
+        match self.deferred_fees.as_mut() {
+            Some(deferred_fees) => {
+                let deferred_fee = deferred_fees.entry(recipient).or_default();
+                *deferred_fee = deferred_fee
+                    .checked_add(fee)
+                    .ok_or(InternalError::Overflow)?;
+                Ok(())
+            }
+            None => self.increase_account_balance(recipient, fee),
+        }
+    }
+
     pub fn decrease_account_balance(
         &mut self,
         address: Address,
@@ -1,11 +1,14 @@
 use ethrex_common::{
     Address, H256, U256,
-    types::{BlockHeader, ChainConfig, Fork, ForkBlobSchedule},
+    types::{BlockHeader, ChainConfig, FeeConfig, Fork, ForkBlobSchedule},
 };
 
-use crate::constants::{
-    BLOB_BASE_FEE_UPDATE_FRACTION, BLOB_BASE_FEE_UPDATE_FRACTION_PRAGUE, MAX_BLOB_COUNT,
-    MAX_BLOB_COUNT_ELECTRA, TARGET_BLOB_GAS_PER_BLOCK, TARGET_BLOB_GAS_PER_BLOCK_PECTRA,
+use crate::{
+    constants::{
+        BLOB_BASE_FEE_UPDATE_FRACTION, BLOB_BASE_FEE_UPDATE_FRACTION_PRAGUE, MAX_BLOB_COUNT,
+        MAX_BLOB_COUNT_ELECTRA, TARGET_BLOB_GAS_PER_BLOCK, TARGET_BLOB_GAS_PER_BLOCK_PECTRA,
+    },
+    precompile_registry::PrecompileRegistry,
 };
 
 use std::collections::HashMap;
@@ -52,6 +55,10 @@ pub struct Environment {
 pub struct EVMConfig {
     pub fork: Fork,
     pub blob_schedule: ForkBlobSchedule,
+    /// Custom precompiles available on top of the standard ones
+    pub precompiles: PrecompileRegistry,
+    /// Fee vaults of the L2, only used by the `L2Hook`
+    pub fee_config: Option<FeeConfig>,
 }
 
 impl EVMConfig {
@@ -59,9 +66,21 @@ impl EVMConfig {
         EVMConfig {
             fork,
             blob_schedule,
+            precompiles: PrecompileRegistry::default(),
+            fee_config: None,
         }
     }
 
+    pub fn with_precompiles(mut self, precompiles: PrecompileRegistry) -> Self {
+        self.precompiles = precompiles;
+        self
+    }
+
+    pub fn with_fee_config(mut self, fee_config: Option<FeeConfig>) -> Self {
+        self.fee_config = fee_config;
+        self
+    }
+
     pub fn new_from_chain_config(chain_config: &ChainConfig, block_header: &BlockHeader) -> Self {
         let fork = chain_config.fork(block_header.timestamp);
 
@@ -122,6 +141,8 @@ impl Default for EVMConfig {
         EVMConfig {
             fork,
             blob_schedule: Self::canonical_values(fork),
+            precompiles: PrecompileRegistry::default(),
+            fee_config: None,
         }
     }
 }
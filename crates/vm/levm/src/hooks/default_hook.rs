@@ -1,5 +1,6 @@
 use crate::{
     account::LevmAccount,
+    code_cache::AnalyzedCode,
     constants::*,
     errors::{ContextResult, InternalError, TxValidationError, VMError},
     gas_cost::{self, STANDARD_TOKEN_COST, TOTAL_COST_FLOOR_PER_TOKEN},
@@ -11,7 +12,7 @@ use crate::{
 use bytes::Bytes;
 use ethrex_common::{Address, U256, types::Fork};
 
-use std::cmp::max;
+use std::{cmp::max, sync::Arc};
 
 pub const MAX_REFUND_QUOTIENT: u64 = 5;
 
@@ -215,7 +216,7 @@ pub fn pay_coinbase(vm: &mut VM<'_>, gas_to_pay: u64) -> Result<(), VMError> {
         .checked_mul(priority_fee_per_gas)
         .ok_or(InternalError::Overflow)?;
 
-    vm.increase_account_balance(vm.env.coinbase, coinbase_fee)?;
+    vm.pay_fee(vm.env.coinbase, coinbase_fee)?;
 
     Ok(())
 }
@@ -469,22 +470,26 @@ pub fn transfer_value(vm: &mut VM<'_>) -> Result<(), VMError> {
 /// Sets bytecode and code_address to CallFrame
 pub fn set_bytecode_and_code_address(vm: &mut VM<'_>) -> Result<(), VMError> {
     // Get bytecode and code_address for assigning those values to the callframe.
-    let (bytecode, code_address) = if vm.is_create()? {
+    let (code, code_address) = if vm.is_create()? {
         // Here bytecode is the calldata and the code_address is just the created contract address.
+        // Initcode isn't cached, it's rarely executed more than once.
         let calldata = std::mem::take(&mut vm.current_call_frame.calldata);
-        (calldata, vm.current_call_frame.to)
+        (
+            Arc::new(AnalyzedCode::new(&calldata)),
+            vm.current_call_frame.to,
+        )
     } else {
         // Here bytecode and code_address could be either from the account or from the delegated account.
         let to = vm.current_call_frame.to;
         let (_is_delegation, _eip7702_gas_consumed, code_address, bytecode) =
             eip7702_get_code(vm.db, &mut vm.substate, to)?;
 
-        (bytecode, code_address)
+        (vm.get_analyzed_code(code_address, &bytecode)?, code_address)
     };
 
     // Assign code and code_address to callframe
     vm.current_call_frame.code_address = code_address;
-    vm.current_call_frame.set_code(bytecode)?;
+    vm.current_call_frame.set_code(code)?;
 
     Ok(())
 }
@@ -1,23 +1,51 @@
 use crate::{
-    errors::{ContextResult, InternalError},
+    code_cache::AnalyzedCode,
+    errors::{ContextResult, InternalError, TxValidationError, VMError},
     hooks::{DefaultHook, default_hook, hook::Hook},
     opcodes::Opcode,
     vm::VM,
 };
 
-use ethrex_common::{Address, H160, U256};
+use ethrex_common::{Address, H160, H256, U256, types::Transaction};
+use std::sync::Arc;
 
 pub const COMMON_BRIDGE_L2_ADDRESS: Address = H160([
     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
     0x00, 0x00, 0xff, 0xff,
 ]);
 
-pub struct L2Hook {}
+// 0x000000000000000000000000000000000000fffd
+pub const L1_FEE_ORACLE_ADDRESS: Address = H160([
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0xff, 0xfd,
+]);
+
+/// Storage slots of the `L1FeeOracle` contract, see `crates/l2/contracts/src/l2/L1FeeOracle.sol`
+pub const L1_FEE_PER_BYTE_SLOT: H256 = H256::zero();
+pub const FEE_VAULT_SLOT: H256 = H256([
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1,
+]);
+pub const PREVIOUS_L1_FEE_PER_BYTE_SLOT: H256 = H256([
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2,
+]);
+pub const LAST_UPDATE_BLOCK_SLOT: H256 = H256([
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 3,
+]);
+
+#[derive(Default)]
+pub struct L2Hook {
+    /// L1 data fee charged to the sender in `prepare_execution`, paid to the fee vault at the end
+    l1_fee: U256,
+    /// Operator fee for the whole gas limit, the part for the unused gas is refunded at the end
+    operator_fee_deposit: U256,
+}
 
 impl Hook for L2Hook {
     fn prepare_execution(&mut self, vm: &mut VM<'_>) -> Result<(), crate::errors::VMError> {
         if !vm.env.is_privileged {
-            return DefaultHook.prepare_execution(vm);
+            DefaultHook.prepare_execution(vm)?;
+            self.charge_l1_fee(vm)?;
+            return self.charge_operator_fee(vm);
         }
 
         let sender_address = vm.env.origin;
@@ -25,7 +53,8 @@ impl Hook for L2Hook {
 
         let mut tx_should_fail = false;
 
-        // The bridge is allowed to mint ETH.
+        // The bridge is allowed to mint the native token, backed on the L1 by ETH
+        // or by the ERC20 the bridge was initialized with.
         // This is done by not decreasing it's balance when it's the source of a transfer.
         // For other privileged transactions, insufficient balance can't cause an error
         // since they must always be accepted, and an error would mark them as invalid
@@ -89,8 +118,9 @@ impl Hook for L2Hook {
             // If the transaction failed some validation, but it must still be included
             // To prevent it from taking effect, we force it to revert
             vm.current_call_frame.msg_value = U256::zero();
-            vm.current_call_frame
-                .set_code(vec![Opcode::INVALID.into()].into())?;
+            vm.current_call_frame.set_code(Arc::new(AnalyzedCode::new(
+                &vec![Opcode::INVALID.into()].into(),
+            )))?;
             return Ok(());
         }
 
@@ -107,7 +137,11 @@ impl Hook for L2Hook {
         ctx_result: &mut ContextResult,
     ) -> Result<(), crate::errors::VMError> {
         if !vm.env.is_privileged {
-            return DefaultHook.finalize_execution(vm, ctx_result);
+            DefaultHook.finalize_execution(vm, ctx_result)?;
+            // After the default finalization the gas used already accounts for the refunds
+            self.pay_base_fee(vm, ctx_result.gas_used)?;
+            self.pay_operator_fee(vm, ctx_result.gas_used)?;
+            return self.pay_l1_fee(vm);
         }
 
         if !ctx_result.is_success() && vm.env.origin != COMMON_BRIDGE_L2_ADDRESS {
@@ -121,3 +155,253 @@ impl Hook for L2Hook {
         Ok(())
     }
 }
+
+impl L2Hook {
+    /// Deducts the cost of posting the transaction to the L1 from the sender.
+    /// Transactions without gas price, like the ones simulated by `eth_call`, don't pay it.
+    fn charge_l1_fee(&mut self, vm: &mut VM<'_>) -> Result<(), VMError> {
+        if vm.env.gas_price.is_zero() {
+            return Ok(());
+        }
+
+        let l1_fee_per_byte = get_l1_fee_per_byte(vm)?;
+        self.l1_fee = calculate_l1_fee(&vm.tx, l1_fee_per_byte)?;
+        if self.l1_fee.is_zero() {
+            return Ok(());
+        }
+
+        vm.decrease_account_balance(vm.env.origin, self.l1_fee)
+            .map_err(|_| TxValidationError::InsufficientAccountFunds)?;
+
+        Ok(())
+    }
+
+    /// Sends the L1 fee to the vault set in the oracle, or to the coinbase if there's none.
+    fn pay_l1_fee(&mut self, vm: &mut VM<'_>) -> Result<(), VMError> {
+        let l1_fee = std::mem::take(&mut self.l1_fee);
+        if l1_fee.is_zero() {
+            return Ok(());
+        }
+
+        let fee_vault = vm
+            .db
+            .get_storage_value(L1_FEE_ORACLE_ADDRESS, FEE_VAULT_SLOT)?;
+        let fee_vault =
+            Address::from_slice(fee_vault.to_big_endian().get(12..).unwrap_or_default());
+
+        let fee_vault = if fee_vault.is_zero() {
+            vm.env.coinbase
+        } else {
+            fee_vault
+        };
+        vm.pay_fee(fee_vault, l1_fee)?;
+
+        Ok(())
+    }
+
+    /// Deducts the operator fee for the whole gas limit from the sender, like the gas itself.
+    fn charge_operator_fee(&mut self, vm: &mut VM<'_>) -> Result<(), VMError> {
+        let Some(operator_fee) = vm
+            .env
+            .config
+            .fee_config
+            .and_then(|config| config.operator_fee)
+        else {
+            return Ok(());
+        };
+        if vm.env.gas_price.is_zero() {
+            return Ok(());
+        }
+
+        self.operator_fee_deposit = operator_fee_for_gas(
+            operator_fee.fixed_fee,
+            operator_fee.fee_per_gas,
+            vm.env.gas_limit,
+        )
+        .ok_or(TxValidationError::InsufficientAccountFunds)?;
+
+        vm.decrease_account_balance(vm.env.origin, self.operator_fee_deposit)
+            .map_err(|_| TxValidationError::InsufficientAccountFunds)?;
+
+        Ok(())
+    }
+
+    /// Pays the operator fee of the gas used to the operator vault, refunding the rest to the sender.
+    fn pay_operator_fee(&mut self, vm: &mut VM<'_>, gas_used: u64) -> Result<(), VMError> {
+        let deposit = std::mem::take(&mut self.operator_fee_deposit);
+        let Some(operator_fee) = vm
+            .env
+            .config
+            .fee_config
+            .and_then(|config| config.operator_fee)
+        else {
+            return Ok(());
+        };
+        if deposit.is_zero() {
+            return Ok(());
+        }
+
+        let fee = operator_fee_for_gas(operator_fee.fixed_fee, operator_fee.fee_per_gas, gas_used)
+            .ok_or(InternalError::Overflow)?;
+        let refund = deposit.checked_sub(fee).ok_or(InternalError::Underflow)?;
+
+        vm.increase_account_balance(vm.env.origin, refund)?;
+        vm.pay_fee(operator_fee.vault, fee)?;
+
+        Ok(())
+    }
+
+    /// Sends the base fee to the base fee vault, it's burned when there's none.
+    fn pay_base_fee(&mut self, vm: &mut VM<'_>, gas_used: u64) -> Result<(), VMError> {
+        let Some(base_fee_vault) = vm
+            .env
+            .config
+            .fee_config
+            .and_then(|config| config.base_fee_vault)
+        else {
+            return Ok(());
+        };
+
+        let base_fee = vm
+            .env
+            .base_fee_per_gas
+            .checked_mul(U256::from(gas_used))
+            .ok_or(InternalError::Overflow)?;
+        if base_fee.is_zero() {
+            return Ok(());
+        }
+
+        vm.pay_fee(base_fee_vault, base_fee)?;
+
+        Ok(())
+    }
+}
+
+fn operator_fee_for_gas(fixed_fee: u64, fee_per_gas: u64, gas: u64) -> Option<U256> {
+    U256::from(fee_per_gas)
+        .checked_mul(U256::from(gas))?
+        .checked_add(U256::from(fixed_fee))
+}
+
+/// Price per compressed byte in effect for the current block.
+/// Updates made by the sequencer only apply from the next block, like `L1FeeOracle.currentL1FeePerByte`.
+fn get_l1_fee_per_byte(vm: &mut VM<'_>) -> Result<U256, VMError> {
+    let last_update_block = vm
+        .db
+        .get_storage_value(L1_FEE_ORACLE_ADDRESS, LAST_UPDATE_BLOCK_SLOT)?;
+    let slot = if last_update_block == vm.env.block_number {
+        PREVIOUS_L1_FEE_PER_BYTE_SLOT
+    } else {
+        L1_FEE_PER_BYTE_SLOT
+    };
+    Ok(vm.db.get_storage_value(L1_FEE_ORACLE_ADDRESS, slot)?)
+}
+
+/// L1 data fee of a transaction: the L1 fee per byte times the size of the transaction once compressed.
+pub fn calculate_l1_fee(tx: &Transaction, l1_fee_per_byte: U256) -> Result<U256, VMError> {
+    if l1_fee_per_byte.is_zero() {
+        return Ok(U256::zero());
+    }
+    let compressed_size = flz_compress_len(&tx.encode_canonical_to_vec());
+    Ok(l1_fee_per_byte
+        .checked_mul(compressed_size.into())
+        .ok_or(InternalError::Overflow)?)
+}
+
+/// Length of the data compressed with FastLZ (level 1), without actually building the output.
+/// It's a cheap estimation of the size of the transaction in the compressed batch, the same
+/// used by the OP stack (`FlzCompressLen`), so the fee can be checked against their tooling.
+pub fn flz_compress_len(data: &[u8]) -> u64 {
+    const HASH_TABLE_SIZE: usize = 8192;
+    const HASH_SHIFT: u32 = 19;
+    const HASH_MASK: u32 = 0x1fff;
+    const MAX_DISTANCE: usize = 0x1fff;
+    const MAX_LITERALS: u64 = 0x20;
+    const MAX_MATCH: u64 = 262;
+
+    let byte = |i: usize| data.get(i).copied().unwrap_or_default();
+    let u24 = |i: usize| {
+        u32::from_le_bytes([byte(i), byte(i.wrapping_add(1)), byte(i.wrapping_add(2)), 0])
+    };
+    let hash = |v: u32| {
+        usize::try_from(v.wrapping_mul(2654435769).wrapping_shr(HASH_SHIFT) & HASH_MASK)
+            .unwrap_or_default()
+    };
+
+    let mut len: u64 = 0;
+    let literals = |len: &mut u64, count: usize| {
+        let count = u64::try_from(count).unwrap_or(u64::MAX);
+        let runs = count / MAX_LITERALS;
+        let rest = count % MAX_LITERALS;
+        *len = len.saturating_add(runs.saturating_mul(MAX_LITERALS.saturating_add(1)));
+        if rest != 0 {
+            *len = len.saturating_add(rest.saturating_add(1));
+        }
+    };
+    let matched = |len: &mut u64, match_len: usize| {
+        let match_len = u64::try_from(match_len)
+            .unwrap_or(u64::MAX)
+            .saturating_sub(1);
+        *len = len.saturating_add(3u64.saturating_mul(match_len / MAX_MATCH));
+        *len = len.saturating_add(if match_len % MAX_MATCH >= 6 { 3 } else { 2 });
+    };
+
+    let mut hash_table = vec![0usize; HASH_TABLE_SIZE];
+    let set_next_hash = |hash_table: &mut [usize], ip: usize| {
+        if let Some(entry) = hash_table.get_mut(hash(u24(ip))) {
+            *entry = ip;
+        }
+        ip.saturating_add(1)
+    };
+
+    let ip_limit = data.len().saturating_sub(13);
+    let mut anchor = 0;
+    let mut ip = 2;
+    while ip < ip_limit {
+        let mut reference;
+        loop {
+            let sequence = u24(ip);
+            let entry = hash_table
+                .get_mut(hash(sequence))
+                .map(|entry| std::mem::replace(entry, ip));
+            reference = entry.unwrap_or_default();
+            let distance = ip.wrapping_sub(reference);
+            if ip >= ip_limit {
+                break;
+            }
+            ip = ip.saturating_add(1);
+            if distance <= MAX_DISTANCE && sequence == u24(reference) {
+                break;
+            }
+        }
+        if ip >= ip_limit {
+            break;
+        }
+        ip = ip.saturating_sub(1);
+        if ip > anchor {
+            literals(&mut len, ip.saturating_sub(anchor));
+        }
+
+        // Length of the match, counting the first mismatching byte like the reference implementation
+        let start = reference.saturating_add(3);
+        let end = ip_limit
+            .saturating_add(9)
+            .saturating_sub(ip.saturating_add(3));
+        let mut match_len = 0;
+        while match_len < end {
+            let current = data.get(ip.saturating_add(3).saturating_add(match_len));
+            let previous = data.get(start.saturating_add(match_len));
+            match_len = match_len.saturating_add(1);
+            if current != previous {
+                break;
+            }
+        }
+        matched(&mut len, match_len);
+
+        ip = set_next_hash(&mut hash_table, ip.saturating_add(match_len));
+        ip = set_next_hash(&mut hash_table, ip);
+        anchor = ip;
+    }
+    literals(&mut len, data.len().saturating_sub(anchor));
+    len
+}
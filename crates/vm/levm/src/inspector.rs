@@ -0,0 +1,113 @@
+use std::time::Duration;
+
+use bytes::Bytes;
+use ethrex_common::{Address, H256, U256, tracing::CallType, types::Log};
+
+use crate::{call_frame::CallFrame, opcodes::Opcode};
+
+/// Observer of the VM execution, meant for embedding LEVM in tools like coverage collectors, fuzzers or custom tracers.
+///
+/// Every callback has an empty default implementation so implementors only override the ones they need.
+/// Install it with [`crate::vm::VM::with_inspector`]. When no inspector is installed the VM runs its regular
+/// interpreter loop, so there is no overhead.
+pub trait Inspector {
+    /// Called before executing an opcode.
+    /// The callframe exposes the pc, gas remaining, depth, stack (see [`crate::call_frame::Stack::as_slice`])
+    /// and memory (see [`crate::memory::Memory::as_bytes`]).
+    fn step(&mut self, _call_frame: &CallFrame, _opcode: Opcode) {}
+
+    /// Called right after executing an opcode, before advancing the pc.
+    /// If the opcode started a new call or create, the callframe is the one of the callee.
+    fn step_end(&mut self, _call_frame: &CallFrame, _opcode: Opcode) {}
+
+    /// Called when entering a call or create, including the one of the external transaction.
+    fn call_enter(
+        &mut self,
+        _call_type: CallType,
+        _from: Address,
+        _to: Address,
+        _value: U256,
+        _gas: u64,
+        _input: &Bytes,
+    ) {
+    }
+
+    /// Called when exiting the last entered call or create.
+    /// `success` is false if it reverted or halted exceptionally.
+    fn call_exit(&mut self, _gas_used: u64, _output: &Bytes, _success: bool) {}
+
+    /// Called when a LOG opcode is executed. Logs of calls that later revert are reported as well.
+    fn log(&mut self, _log: &Log) {}
+
+    /// Called after executing a precompile, with the gas it consumed and how long it took.
+    fn precompile(&mut self, _address: Address, _gas_used: u64, _elapsed: Duration) {}
+
+    /// Called when SELFDESTRUCT is executed.
+    fn selfdestruct(&mut self, _address: Address, _beneficiary: Address, _balance: U256) {}
+
+    /// Called when SLOAD reads a storage slot.
+    fn storage_read(&mut self, _address: Address, _key: H256, _value: U256) {}
+
+    /// Called when SSTORE writes a storage slot, even if the value doesn't change.
+    fn storage_write(
+        &mut self,
+        _address: Address,
+        _key: H256,
+        _previous_value: U256,
+        _new_value: U256,
+    ) {
+    }
+}
+
+/// Allows lending an inspector to the VM and reading its results once the execution finishes.
+impl<I: Inspector + ?Sized> Inspector for &mut I {
+    fn step(&mut self, call_frame: &CallFrame, opcode: Opcode) {
+        (**self).step(call_frame, opcode)
+    }
+
+    fn step_end(&mut self, call_frame: &CallFrame, opcode: Opcode) {
+        (**self).step_end(call_frame, opcode)
+    }
+
+    fn call_enter(
+        &mut self,
+        call_type: CallType,
+        from: Address,
+        to: Address,
+        value: U256,
+        gas: u64,
+        input: &Bytes,
+    ) {
+        (**self).call_enter(call_type, from, to, value, gas, input)
+    }
+
+    fn call_exit(&mut self, gas_used: u64, output: &Bytes, success: bool) {
+        (**self).call_exit(gas_used, output, success)
+    }
+
+    fn log(&mut self, log: &Log) {
+        (**self).log(log)
+    }
+
+    fn precompile(&mut self, address: Address, gas_used: u64, elapsed: Duration) {
+        (**self).precompile(address, gas_used, elapsed)
+    }
+
+    fn selfdestruct(&mut self, address: Address, beneficiary: Address, balance: U256) {
+        (**self).selfdestruct(address, beneficiary, balance)
+    }
+
+    fn storage_read(&mut self, address: Address, key: H256, value: U256) {
+        (**self).storage_read(address, key, value)
+    }
+
+    fn storage_write(
+        &mut self,
+        address: Address,
+        key: H256,
+        previous_value: U256,
+        new_value: U256,
+    ) {
+        (**self).storage_write(address, key, previous_value, new_value)
+    }
+}
@@ -1,5 +1,8 @@
 use bytes::Bytes;
-use ethrex_common::{Address, H160, types::Fork};
+use ethrex_common::{
+    Address, H160,
+    types::{CustomPrecompile, Fork},
+};
 use p256::{
     EncodedPoint, FieldElement as P256FieldElement, NistP256,
     ecdsa::{Signature as P256Signature, VerifyingKey, signature::hazmat::PrehashVerifier},
@@ -8,8 +11,11 @@ use p256::{
 
 use crate::{
     errors::{InternalError, PrecompileError, VMError},
+    gas_cost,
+    precompile_registry::Precompile,
     precompiles::{self},
 };
+use sha2::Digest;
 
 pub const P256VERIFY_ADDRESS: Address = H160([
     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
@@ -18,6 +24,11 @@ pub const P256VERIFY_ADDRESS: Address = H160([
 
 pub const RIP_PRECOMPILES: [H160; 1] = [P256VERIFY_ADDRESS];
 
+pub const SHA2_512_ADDRESS: Address = H160([
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x02, 0x00,
+]);
+
 // Secp256r1 curve parameters
 // See https://neuromancer.sk/std/secg/secp256r1
 const P256_P: P256Uint = P256Uint::from_be_hex(P256FieldElement::MODULUS);
@@ -47,6 +58,34 @@ pub fn is_precompile(address: &Address, fork: Fork) -> bool {
     precompiles::is_precompile(address, fork) || RIP_PRECOMPILES.contains(address)
 }
 
+/// Implementation of a custom precompile that an L2 can enable in its chain config
+pub fn custom_precompile(precompile: CustomPrecompile) -> &'static dyn Precompile {
+    match precompile {
+        CustomPrecompile::Sha512 => &Sha2512,
+    }
+}
+
+/// Returns the calldata hashed by sha2-512, priced like sha2-256
+struct Sha2512;
+
+impl Precompile for Sha2512 {
+    fn address(&self) -> Address {
+        SHA2_512_ADDRESS
+    }
+
+    fn activation_fork(&self) -> Fork {
+        Fork::Frontier
+    }
+
+    fn gas_cost(&self, calldata: &Bytes) -> Result<u64, VMError> {
+        gas_cost::sha2_256(calldata.len())
+    }
+
+    fn execute(&self, calldata: &Bytes) -> Result<Bytes, VMError> {
+        Ok(Bytes::from(sha2::Sha512::digest(calldata).to_vec()))
+    }
+}
+
 /// Signature verification in the “secp256r1” elliptic curve
 /// If the verification succeeds, returns 1 in a 32-bit big-endian format.
 /// If the verification fails, returns an empty `Bytes` object.
@@ -1,4 +1,5 @@
 pub mod call_frame;
+pub mod code_cache;
 pub mod constants;
 pub mod db;
 pub mod debug;
@@ -7,10 +8,13 @@ pub mod errors;
 pub mod execution_handlers;
 pub mod gas_cost;
 pub mod hooks;
+pub mod inspector;
 pub mod memory;
 pub mod opcode_handlers;
 pub mod opcodes;
+pub mod precompile_registry;
 pub mod precompiles;
+pub mod profiler;
 pub mod tracing;
 pub mod utils;
 pub mod vm;
@@ -1,4 +1,7 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{
+    cell::{Ref, RefCell},
+    rc::Rc,
+};
 
 use crate::{
     constants::{MEMORY_EXPANSION_QUOTIENT, WORD_SIZE_IN_BYTES_USIZE},
@@ -65,6 +68,15 @@ impl Memory {
         self.len() == 0
     }
 
+    /// Returns the contents of the current memory, from the current base.
+    pub fn as_bytes(&self) -> Ref<'_, [u8]> {
+        Ref::map(self.buffer.borrow(), |buffer| {
+            buffer
+                .get(self.current_base..self.current_base.wrapping_add(self.len))
+                .unwrap_or_default()
+        })
+    }
+
     /// Resizes the from the current base to fit the memory specified at new_memory_size.
     ///
     /// Note: new_memory_size is increased to the next 32 byte multiple.
@@ -43,6 +43,9 @@ impl<'a> VM<'a> {
         };
 
         self.tracer.log(&log)?;
+        if let Some(inspector) = self.inspector.as_mut() {
+            inspector.log(&log);
+        }
 
         self.substate.logs.push(log);
 
@@ -28,8 +28,10 @@ impl<'a> VM<'a> {
             // bytecode
             .wrapping_add(1);
 
+        // The code is padded, so immediates past its end are read as zeros
         let value = if let Some(slice) = current_call_frame
-            .bytecode
+            .code
+            .padded()
             .get(pc_offset..pc_offset.wrapping_add(N))
         {
             u256_from_big_endian_const(
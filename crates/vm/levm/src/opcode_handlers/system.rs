@@ -1,5 +1,6 @@
 use crate::{
     call_frame::CallFrame,
+    code_cache::AnalyzedCode,
     constants::{FAIL, INIT_CODE_MAX_SIZE, SUCCESS},
     errors::{ContextResult, ExceptionalHalt, InternalError, OpcodeResult, TxResult, VMError},
     gas_cost::{self, max_message_call_gas},
@@ -12,6 +13,7 @@ use ethrex_common::tracing::CallType::{
     self, CALL, CALLCODE, DELEGATECALL, SELFDESTRUCT, STATICCALL,
 };
 use ethrex_common::{Address, U256, types::Fork};
+use std::sync::Arc;
 
 // System Operations (10)
 // Opcodes: CREATE, CALL, CALLCODE, RETURN, DELEGATECALL, CREATE2, STATICCALL, REVERT, INVALID, SELFDESTRUCT
@@ -103,6 +105,9 @@ impl<'a> VM<'a> {
         let data = self.get_calldata(args_offset, args_size)?;
 
         self.tracer.enter(CALL, from, to, value, gas_limit, &data);
+        if let Some(inspector) = self.inspector.as_mut() {
+            inspector.call_enter(CALL, from, to, value, gas_limit, &data);
+        }
 
         self.generic_call(
             gas_limit,
@@ -201,6 +206,9 @@ impl<'a> VM<'a> {
 
         self.tracer
             .enter(CALLCODE, from, code_address, value, gas_limit, &data);
+        if let Some(inspector) = self.inspector.as_mut() {
+            inspector.call_enter(CALLCODE, from, code_address, value, gas_limit, &data);
+        }
 
         self.generic_call(
             gas_limit,
@@ -319,6 +327,9 @@ impl<'a> VM<'a> {
         // In this trace the `from` is the current contract, we don't want the `from` to be, for example, the EOA that sent the transaction
         self.tracer
             .enter(DELEGATECALL, to, code_address, value, gas_limit, &data);
+        if let Some(inspector) = self.inspector.as_mut() {
+            inspector.call_enter(DELEGATECALL, to, code_address, value, gas_limit, &data);
+        }
 
         self.generic_call(
             gas_limit,
@@ -414,6 +425,9 @@ impl<'a> VM<'a> {
 
         self.tracer
             .enter(STATICCALL, from, to, value, gas_limit, &data);
+        if let Some(inspector) = self.inspector.as_mut() {
+            inspector.call_enter(STATICCALL, from, to, value, gas_limit, &data);
+        }
 
         self.generic_call(
             gas_limit,
@@ -577,6 +591,10 @@ impl<'a> VM<'a> {
 
         self.tracer.exit_early(0, None)?;
 
+        if let Some(inspector) = self.inspector.as_mut() {
+            inspector.selfdestruct(to, beneficiary, balance);
+        }
+
         Ok(OpcodeResult::Halt)
     }
 
@@ -637,6 +655,9 @@ impl<'a> VM<'a> {
         };
         self.tracer
             .enter(call_type, deployer, new_address, value, gas_limit, &code);
+        if let Some(inspector) = self.inspector.as_mut() {
+            inspector.call_enter(call_type, deployer, new_address, value, gas_limit, &code);
+        }
 
         let new_depth = self
             .current_call_frame
@@ -669,6 +690,9 @@ impl<'a> VM<'a> {
             self.current_call_frame.stack.push1(FAIL)?;
             self.tracer
                 .exit_early(gas_limit, Some("CreateAccExists".to_string()))?;
+            if let Some(inspector) = self.inspector.as_mut() {
+                inspector.call_exit(gas_limit, &Bytes::new(), false);
+            }
             return Ok(OpcodeResult::Continue { pc_increment: 1 });
         }
 
@@ -681,7 +705,7 @@ impl<'a> VM<'a> {
             deployer,
             new_address,
             new_address,
-            code,
+            Arc::new(AnalyzedCode::new(&code)),
             value,
             Bytes::new(),
             false,
@@ -755,13 +779,8 @@ impl<'a> VM<'a> {
 
         if self.is_precompile(&code_address) && !is_delegation_7702 {
             let mut gas_remaining = gas_limit;
-            let ctx_result = Self::execute_precompile(
-                self.vm_type,
-                code_address,
-                &calldata,
-                gas_limit,
-                &mut gas_remaining,
-            )?;
+            let ctx_result =
+                self.inspect_precompile(code_address, &calldata, gas_limit, &mut gas_remaining)?;
 
             let call_frame = &mut self.current_call_frame;
 
@@ -806,17 +825,25 @@ impl<'a> VM<'a> {
             }
 
             self.tracer.exit_context(&ctx_result, false)?;
+            if let Some(inspector) = self.inspector.as_mut() {
+                inspector.call_exit(
+                    ctx_result.gas_used,
+                    &ctx_result.output,
+                    ctx_result.is_success(),
+                );
+            }
         } else {
             let mut stack = self.stack_pool.pop().unwrap_or_default();
             stack.clear();
 
             let next_memory = self.current_call_frame.memory.next_memory();
+            let code = self.get_analyzed_code(code_address, &bytecode)?;
 
             let new_call_frame = CallFrame::new(
                 msg_sender,
                 to,
                 code_address,
-                bytecode,
+                code,
                 value,
                 calldata,
                 is_static,
@@ -926,6 +953,13 @@ impl<'a> VM<'a> {
         };
 
         self.tracer.exit_context(ctx_result, false)?;
+        if let Some(inspector) = self.inspector.as_mut() {
+            inspector.call_exit(
+                ctx_result.gas_used,
+                &ctx_result.output,
+                ctx_result.is_success(),
+            );
+        }
 
         let mut stack = executed_call_frame.stack;
         stack.clear();
@@ -977,6 +1011,13 @@ impl<'a> VM<'a> {
         };
 
         self.tracer.exit_context(ctx_result, false)?;
+        if let Some(inspector) = self.inspector.as_mut() {
+            inspector.call_exit(
+                ctx_result.gas_used,
+                &ctx_result.output,
+                ctx_result.is_success(),
+            );
+        }
 
         let mut stack = executed_call_frame.stack;
         stack.clear();
@@ -1036,6 +1077,9 @@ impl<'a> VM<'a> {
         callframe.stack.push1(FAIL)?; // It's the same as revert for CREATE
 
         self.tracer.exit_early(0, Some(reason))?;
+        if let Some(inspector) = self.inspector.as_mut() {
+            inspector.call_exit(0, &Bytes::new(), false);
+        }
         Ok(())
     }
 }
@@ -0,0 +1,122 @@
+use std::fmt;
+
+use bytes::Bytes;
+use ethrex_common::{
+    Address,
+    types::{CustomPrecompiles, Fork},
+};
+
+use crate::{
+    errors::{InternalError, VMError},
+    l2_precompiles::{RIP_PRECOMPILES, custom_precompile},
+    precompiles::{self, PRECOMPILES, PRECOMPILES_POST_CANCUN},
+};
+
+/// A precompile that can be added to the VM without modifying LEVM, e.g. to provide custom functionality in an L2.
+pub trait Precompile: Send + Sync {
+    /// Address the precompile is called at
+    fn address(&self) -> Address;
+
+    /// First fork in which the precompile can be called
+    fn activation_fork(&self) -> Fork;
+
+    /// Gas charged for calling the precompile with the given calldata
+    fn gas_cost(&self, calldata: &Bytes) -> Result<u64, VMError>;
+
+    /// Executes the precompile, the gas was already charged when this is called
+    fn execute(&self, calldata: &Bytes) -> Result<Bytes, VMError>;
+}
+
+/// Set of custom precompiles available to the VM, on top of the standard ones.
+/// It's made of the precompiles implemented by LEVM that the chain config enables, see
+/// [`ethrex_common::types::ChainConfig::custom_precompiles`], and of the ones implemented by
+/// the embedder of LEVM. It's `Copy` so it can be carried in the `EVMConfig` of every transaction.
+#[derive(Clone, Copy, Default)]
+pub struct PrecompileRegistry {
+    enabled: CustomPrecompiles,
+    external: &'static [&'static dyn Precompile],
+}
+
+impl fmt::Debug for PrecompileRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set()
+            .entries(self.precompiles().map(|precompile| precompile.address()))
+            .finish()
+    }
+}
+
+impl PrecompileRegistry {
+    /// Registry with the precompiles implemented by LEVM that are enabled in the chain config
+    pub fn new(enabled: CustomPrecompiles) -> Self {
+        Self {
+            enabled,
+            external: &[],
+        }
+    }
+
+    /// Adds precompiles implemented outside of LEVM.
+    /// Fails if one of their addresses is already used by a standard precompile or by another one in the registry.
+    pub fn with_external(
+        mut self,
+        external: &'static [&'static dyn Precompile],
+    ) -> Result<Self, InternalError> {
+        for (index, precompile) in external.iter().enumerate() {
+            let address = precompile.address();
+            if PRECOMPILES.contains(&address)
+                || PRECOMPILES_POST_CANCUN.contains(&address)
+                || RIP_PRECOMPILES.contains(&address)
+                || self.precompiles().any(|other| other.address() == address)
+                || external
+                    .iter()
+                    .take(index)
+                    .any(|other| other.address() == address)
+            {
+                return Err(InternalError::Custom(format!(
+                    "Precompile address {address:#x} is already in use"
+                )));
+            }
+        }
+        self.external = external;
+        Ok(self)
+    }
+
+    fn precompiles(&self) -> impl Iterator<Item = &'static dyn Precompile> + '_ {
+        self.enabled
+            .iter()
+            .map(custom_precompile)
+            .chain(self.external.iter().copied())
+    }
+
+    /// Returns the precompile at the given address, if it's active in the fork
+    pub fn get(&self, address: &Address, fork: Fork) -> Option<&'static dyn Precompile> {
+        self.precompiles().find(|precompile| {
+            precompile.address() == *address && fork >= precompile.activation_fork()
+        })
+    }
+
+    pub fn is_precompile(&self, address: &Address, fork: Fork) -> bool {
+        self.get(address, fork).is_some()
+    }
+
+    /// Addresses of the precompiles active in the fork, they are warm from the start of every transaction
+    pub fn active_addresses(&self, fork: Fork) -> impl Iterator<Item = Address> + '_ {
+        self.precompiles()
+            .filter(move |precompile| fork >= precompile.activation_fork())
+            .map(|precompile| precompile.address())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.enabled.is_empty() && self.external.is_empty()
+    }
+}
+
+/// Charges the gas of the precompile and executes it
+pub fn execute_precompile(
+    precompile: &dyn Precompile,
+    calldata: &Bytes,
+    gas_remaining: &mut u64,
+) -> Result<Bytes, VMError> {
+    let gas_cost = precompile.gas_cost(calldata)?;
+    precompiles::increase_precompile_consumed_gas(gas_cost, gas_remaining)?;
+    precompile.execute(calldata)
+}
@@ -0,0 +1,235 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::{Duration, Instant},
+};
+
+use bytes::Bytes;
+use ethrex_common::{Address, H256, U256, tracing::CallType};
+use keccak_hash::keccak;
+
+use crate::{call_frame::CallFrame, inspector::Inspector, opcodes::Opcode};
+
+/// Amount of executions, gas consumed and wall time spent by an opcode, precompile or contract.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ProfileEntry {
+    pub count: u64,
+    pub gas: u64,
+    pub time: Duration,
+}
+
+impl ProfileEntry {
+    fn add_execution(&mut self, time: Duration) {
+        self.count = self.count.saturating_add(1);
+        self.time = self.time.saturating_add(time);
+    }
+
+    fn add_gas(&mut self, gas: u64) {
+        self.gas = self.gas.saturating_add(gas);
+    }
+
+    fn merge(&mut self, other: &ProfileEntry) {
+        self.count = self.count.saturating_add(other.count);
+        self.gas = self.gas.saturating_add(other.gas);
+        self.time = self.time.saturating_add(other.time);
+    }
+}
+
+/// Aggregated profile of one or more executions, see [Profiler].
+#[derive(Debug, Clone)]
+pub struct Profile {
+    opcodes: Vec<ProfileEntry>,
+    /// Precompiles by address
+    pub precompiles: BTreeMap<Address, ProfileEntry>,
+    /// Opcodes executed by each contract, by code hash
+    pub contracts: HashMap<H256, ProfileEntry>,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            opcodes: vec![ProfileEntry::default(); 256],
+            precompiles: BTreeMap::new(),
+            contracts: HashMap::new(),
+        }
+    }
+}
+
+impl Profile {
+    /// Opcodes executed at least once
+    pub fn opcodes(&self) -> impl Iterator<Item = (Opcode, &ProfileEntry)> {
+        (0..=u8::MAX)
+            .zip(self.opcodes.iter())
+            .filter(|(_, entry)| entry.count > 0)
+            .map(|(opcode, entry)| (Opcode::from(opcode), entry))
+    }
+
+    pub fn opcode(&self, opcode: Opcode) -> ProfileEntry {
+        self.opcodes
+            .get(usize::from(u8::from(opcode)))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Adds the entries of another profile to this one
+    pub fn merge(&mut self, other: &Profile) {
+        for (entry, other_entry) in self.opcodes.iter_mut().zip(other.opcodes.iter()) {
+            entry.merge(other_entry);
+        }
+        for (address, entry) in &other.precompiles {
+            self.precompiles.entry(*address).or_default().merge(entry);
+        }
+        for (code_hash, entry) in &other.contracts {
+            self.contracts.entry(*code_hash).or_default().merge(entry);
+        }
+    }
+
+    fn opcode_mut(&mut self, opcode: u8) -> Option<&mut ProfileEntry> {
+        self.opcodes.get_mut(usize::from(opcode))
+    }
+}
+
+/// Opcode being executed
+struct Step {
+    opcode: u8,
+    depth: usize,
+    gas_remaining: u64,
+    start: Instant,
+}
+
+/// Call or create whose gas is known once the execution goes back to its callframe
+struct PendingCall {
+    opcode: u8,
+    depth: usize,
+    gas_remaining: u64,
+    code_hash: H256,
+}
+
+/// Inspector that aggregates, per opcode and per precompile, the execution count, gas consumed and wall time.
+/// The opcodes are also attributed to the contract executing them, by code hash.
+///
+/// The time of a call or create doesn't include the execution of the callframe it creates, while its gas does.
+/// Calls to precompiles are fully included in both, besides being profiled on their own.
+/// It can be reused for several transactions, accumulating the results in its [Profile].
+/// The transactions have to be executed one after the other, so blocks are executed sequentially while profiling.
+#[derive(Default)]
+pub struct Profiler {
+    profile: Profile,
+    current_step: Option<Step>,
+    pending_calls: Vec<PendingCall>,
+    /// Code hash of the callframe at each depth
+    code_hashes: Vec<H256>,
+    open_calls: usize,
+}
+
+impl Profiler {
+    pub fn profile(&self) -> &Profile {
+        &self.profile
+    }
+
+    pub fn into_profile(self) -> Profile {
+        self.profile
+    }
+
+    fn code_hash(&self, depth: usize) -> H256 {
+        self.code_hashes.get(depth).copied().unwrap_or_default()
+    }
+
+    fn add_gas(&mut self, opcode: u8, code_hash: H256, gas: u64) {
+        if let Some(entry) = self.profile.opcode_mut(opcode) {
+            entry.add_gas(gas);
+        }
+        self.profile
+            .contracts
+            .entry(code_hash)
+            .or_default()
+            .add_gas(gas);
+    }
+}
+
+impl Inspector for Profiler {
+    fn step(&mut self, call_frame: &CallFrame, opcode: Opcode) {
+        // Back from a call, its gas can be computed now
+        while let Some(pending) = self
+            .pending_calls
+            .pop_if(|call| call.depth >= call_frame.depth)
+        {
+            if pending.depth == call_frame.depth {
+                let gas = pending
+                    .gas_remaining
+                    .saturating_sub(call_frame.gas_remaining);
+                self.add_gas(pending.opcode, pending.code_hash, gas);
+            }
+        }
+
+        if self.code_hashes.len() <= call_frame.depth {
+            self.code_hashes.truncate(call_frame.depth);
+            self.code_hashes.push(keccak(&call_frame.bytecode));
+        }
+
+        self.current_step = Some(Step {
+            opcode: opcode.into(),
+            depth: call_frame.depth,
+            gas_remaining: call_frame.gas_remaining,
+            start: Instant::now(),
+        });
+    }
+
+    fn step_end(&mut self, call_frame: &CallFrame, _opcode: Opcode) {
+        let Some(step) = self.current_step.take() else {
+            return;
+        };
+        let elapsed = step.start.elapsed();
+        let code_hash = self.code_hash(step.depth);
+
+        if let Some(entry) = self.profile.opcode_mut(step.opcode) {
+            entry.add_execution(elapsed);
+        }
+        self.profile
+            .contracts
+            .entry(code_hash)
+            .or_default()
+            .add_execution(elapsed);
+
+        if call_frame.depth == step.depth {
+            let gas = step.gas_remaining.saturating_sub(call_frame.gas_remaining);
+            self.add_gas(step.opcode, code_hash, gas);
+        } else {
+            // A new callframe was pushed, the callframe we get is the one of the callee
+            self.pending_calls.push(PendingCall {
+                opcode: step.opcode,
+                depth: step.depth,
+                gas_remaining: step.gas_remaining,
+                code_hash,
+            });
+            self.code_hashes.truncate(call_frame.depth);
+            self.code_hashes.push(keccak(&call_frame.bytecode));
+        }
+    }
+
+    fn call_enter(
+        &mut self,
+        _call_type: CallType,
+        _from: Address,
+        _to: Address,
+        _value: U256,
+        _gas: u64,
+        _input: &Bytes,
+    ) {
+        // A new transaction starts
+        if self.open_calls == 0 {
+            self.code_hashes.clear();
+            self.pending_calls.clear();
+        }
+        self.open_calls = self.open_calls.saturating_add(1);
+    }
+
+    fn call_exit(&mut self, _gas_used: u64, _output: &Bytes, _success: bool) {
+        self.open_calls = self.open_calls.saturating_sub(1);
+    }
+
+    fn precompile(&mut self, address: Address, gas_used: u64, elapsed: Duration) {
+        let entry = self.profile.precompiles.entry(address).or_default();
+        entry.add_execution(elapsed);
+        entry.add_gas(gas_used);
+    }
+}
@@ -2,6 +2,7 @@ use crate::{
     EVMConfig, Environment,
     account::{AccountStatus, LevmAccount},
     call_frame::CallFrameBackup,
+    code_cache::AnalyzedCode,
     constants::*,
     db::gen_db::GeneralizedDatabase,
     errors::{ExceptionalHalt, InternalError, TxValidationError, VMError},
@@ -11,14 +12,13 @@ use crate::{
         TOTAL_COST_FLOOR_PER_TOKEN, WARM_ADDRESS_ACCESS_COST, fake_exponential,
     },
     l2_precompiles,
-    opcodes::Opcode,
     precompiles::{
         self, SIZE_PRECOMPILES_CANCUN, SIZE_PRECOMPILES_PRAGUE, SIZE_PRECOMPILES_PRE_CANCUN,
     },
     vm::{Substate, VM, VMType},
 };
 use ExceptionalHalt::OutOfGas;
-use bytes::{Bytes, buf::IntoIter};
+use bytes::Bytes;
 use ethrex_common::{
     Address, H256, U256,
     types::{Account, Fork, Transaction, tx_fields::*},
@@ -35,7 +35,7 @@ use secp256k1::{
 use sha3::{Digest, Keccak256};
 use std::{
     collections::{BTreeMap, BTreeSet, HashMap, HashSet},
-    iter::Enumerate,
+    sync::Arc,
 };
 pub type Storage = HashMap<U256, H256>;
 
@@ -97,69 +97,6 @@ pub fn calculate_create2_address(
     Ok(generated_address)
 }
 
-/// # Filter for jump target offsets.
-///
-/// Used to filter which program offsets are not valid jump targets. Implemented as a sorted list of
-/// offsets of bytes `0x5B` (`JUMPDEST`) within push constants.
-#[derive(Debug)]
-pub struct JumpTargetFilter {
-    /// The list of invalid jump target offsets.
-    filter: Vec<usize>,
-    /// The last processed offset, plus one.
-    offset: usize,
-
-    /// Program bytecode iterator.
-    iter: Enumerate<IntoIter<Bytes>>,
-    /// Number of bytes remaining to process from the last push instruction.
-    partial: usize,
-}
-
-impl JumpTargetFilter {
-    /// Create an empty `JumpTargetFilter`.
-    pub fn new(bytecode: Bytes) -> Self {
-        Self {
-            filter: Vec::new(),
-            offset: 0,
-
-            iter: bytecode.into_iter().enumerate(),
-            partial: 0,
-        }
-    }
-
-    /// Check whether a target jump address is blacklisted or not.
-    ///
-    /// This method may potentially grow the filter if the requested address is out of range.
-    pub fn is_blacklisted(&mut self, address: usize) -> bool {
-        if let Some(delta) = address.checked_sub(self.offset) {
-            // It is not realistic to expect a bytecode offset to overflow an `usize`.
-            #[expect(clippy::arithmetic_side_effects)]
-            for (offset, value) in (&mut self.iter).take(delta + 1) {
-                match self.partial.checked_sub(1) {
-                    None => {
-                        // Neither the `as` conversions nor the subtraction can fail here.
-                        #[expect(clippy::as_conversions)]
-                        if (Opcode::PUSH1..=Opcode::PUSH32).contains(&Opcode::from(value)) {
-                            self.partial = value as usize - Opcode::PUSH0 as usize;
-                        }
-                    }
-                    Some(partial) => {
-                        self.partial = partial;
-
-                        #[expect(clippy::as_conversions)]
-                        if value == Opcode::JUMPDEST as u8 {
-                            self.filter.push(offset);
-                        }
-                    }
-                }
-            }
-
-            self.filter.last() == Some(&address)
-        } else {
-            self.filter.binary_search(&address).is_ok()
-        }
-    }
-}
-
 // ================== Backup related functions =======================
 
 /// Restore the state of the cache to the state it in the callframe backup.
@@ -386,6 +323,16 @@ pub fn eip7702_get_code(
 }
 
 impl<'a> VM<'a> {
+    /// Returns the analysis of the account's code, shared with the other callframes executing it in the block.
+    pub fn get_analyzed_code(
+        &mut self,
+        address: Address,
+        bytecode: &Bytes,
+    ) -> Result<Arc<AnalyzedCode>, VMError> {
+        let code_hash = self.db.get_account(address)?.info.code_hash;
+        Ok(self.db.get_analyzed_code(code_hash, bytecode))
+    }
+
     /// Sets the account code as the EIP7702 determines.
     pub fn eip7702_set_access_code(&mut self) -> Result<(), VMError> {
         let mut refunded_gas: u64 = 0;
@@ -580,10 +527,12 @@ impl<'a> VM<'a> {
     }
 
     pub fn is_precompile(&self, address: &Address) -> bool {
-        match self.vm_type {
-            VMType::L1 => precompiles::is_precompile(address, self.env.config.fork),
-            VMType::L2 => l2_precompiles::is_precompile(address, self.env.config.fork),
-        }
+        let fork = self.env.config.fork;
+        let is_standard_precompile = match self.vm_type {
+            VMType::L1 => precompiles::is_precompile(address, fork),
+            VMType::L2 => l2_precompiles::is_precompile(address, fork),
+        };
+        is_standard_precompile || self.env.config.precompiles.is_precompile(address, fork)
     }
 
     /// Backup of Substate, a copy of the current substate to restore if sub-context is reverted
@@ -615,6 +564,12 @@ impl<'a> VM<'a> {
         for i in 1..=max_precompile_address {
             initial_accessed_addresses.insert(Address::from_low_u64_be(i));
         }
+        initial_accessed_addresses.extend(
+            self.env
+                .config
+                .precompiles
+                .active_addresses(self.env.config.fork),
+        );
 
         // Add access lists contents to accessed accounts and accessed storage slots.
         for (address, keys) in self.tx.access_list().clone() {
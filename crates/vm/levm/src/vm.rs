@@ -3,14 +3,17 @@ use crate::{
     call_frame::{CallFrame, Stack},
     db::gen_db::GeneralizedDatabase,
     debug::DebugMode,
-    environment::Environment,
+    environment::{EVMConfig, Environment},
     errors::{ContextResult, ExecutionReport, InternalError, OpcodeResult, VMError},
     hooks::{
         backup_hook::BackupHook,
         hook::{Hook, get_hooks},
     },
+    inspector::Inspector,
     l2_precompiles,
     memory::Memory,
+    opcodes::Opcode,
+    precompile_registry,
     precompiles::{
         self, SIZE_PRECOMPILES_CANCUN, SIZE_PRECOMPILES_PRAGUE, SIZE_PRECOMPILES_PRE_CANCUN,
     },
@@ -26,6 +29,8 @@ use std::{
     cell::RefCell,
     collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     rc::Rc,
+    sync::Arc,
+    time::Instant,
 };
 
 pub type Storage = HashMap<U256, H256>;
@@ -69,6 +74,12 @@ pub struct VM<'a> {
     /// A pool of stacks to avoid reallocating too much when creating new call frames.
     pub stack_pool: Vec<Stack>,
     pub vm_type: VMType,
+    /// When set, the fees paid at the end of the transaction (see [`VM::pay_fee`]) are accumulated here
+    /// by recipient instead of being paid. Used by parallel execution so that every transaction doesn't
+    /// conflict on the balances of the coinbase and the fee vaults.
+    pub deferred_fees: Option<BTreeMap<Address, U256>>,
+    /// Observer of the execution, see [`Inspector`].
+    pub inspector: Option<Box<dyn Inspector + 'a>>,
 }
 
 impl<'a> VM<'a> {
@@ -97,11 +108,13 @@ impl<'a> VM<'a> {
             debug_mode: DebugMode::disabled(),
             stack_pool: Vec::new(),
             vm_type,
+            deferred_fees: None,
+            inspector: None,
             current_call_frame: CallFrame::new(
                 env.origin,
                 callee,
                 Address::default(), // Will be assigned at the end of prepare_execution
-                Bytes::new(),       // Will be assigned at the end of prepare_execution
+                Arc::default(),     // Will be assigned at the end of prepare_execution
                 tx.value(),
                 tx.data().clone(),
                 false,
@@ -140,6 +153,12 @@ impl<'a> VM<'a> {
         Ok(vm)
     }
 
+    /// Installs an inspector that will be notified of every step of the execution.
+    pub fn with_inspector(mut self, inspector: Box<dyn Inspector + 'a>) -> Self {
+        self.inspector = Some(inspector);
+        self
+    }
+
     fn add_hook(&mut self, hook: impl Hook + 'static) {
         self.hooks.push(Rc::new(RefCell::new(hook)));
     }
@@ -156,6 +175,22 @@ impl<'a> VM<'a> {
         // We want to apply these changes even if the Tx reverts. E.g. Incrementing sender nonce
         self.current_call_frame.call_frame_backup.clear();
 
+        if let Some(inspector) = self.inspector.as_mut() {
+            let call_type = if self.current_call_frame.is_create {
+                CallType::CREATE
+            } else {
+                CallType::CALL
+            };
+            inspector.call_enter(
+                call_type,
+                self.env.origin,
+                self.current_call_frame.to,
+                self.current_call_frame.msg_value,
+                self.env.gas_limit,
+                &self.current_call_frame.calldata,
+            );
+        }
+
         if self.is_create()? {
             // Create contract, reverting the Tx if address is already occupied.
             if let Some(context_result) = self.handle_create_transaction()? {
@@ -175,26 +210,46 @@ impl<'a> VM<'a> {
     /// Main execution loop.
     pub fn run_execution(&mut self) -> Result<ContextResult, VMError> {
         if self.is_precompile(&self.current_call_frame.to) {
-            let vm_type = self.vm_type;
-            let call_frame = &mut self.current_call_frame;
-
-            return Self::execute_precompile(
-                vm_type,
-                call_frame.code_address,
-                &call_frame.calldata,
-                call_frame.gas_limit,
-                &mut call_frame.gas_remaining,
-            );
+            let code_address = self.current_call_frame.code_address;
+            let calldata = self.current_call_frame.calldata.clone();
+            let gas_limit = self.current_call_frame.gas_limit;
+            let mut gas_remaining = self.current_call_frame.gas_remaining;
+
+            let result =
+                self.inspect_precompile(code_address, &calldata, gas_limit, &mut gas_remaining);
+            self.current_call_frame.gas_remaining = gas_remaining;
+            return result;
+        }
+
+        // The loop is duplicated for inspection so that it has no cost when there is no inspector.
+        if self.inspector.is_some() {
+            self.interpreter_loop::<true>()
+        } else {
+            self.interpreter_loop::<false>()
         }
+    }
 
+    fn interpreter_loop<const INSPECT: bool>(&mut self) -> Result<ContextResult, VMError> {
         loop {
             let opcode = self.current_call_frame.next_opcode();
 
+            if INSPECT {
+                if let Some(inspector) = self.inspector.as_mut() {
+                    inspector.step(&self.current_call_frame, Opcode::from(opcode));
+                }
+            }
+
             // Call the opcode, using the opcode function lookup table.
             // Indexing will not panic as all the opcode values fit within the table.
             #[allow(clippy::indexing_slicing, clippy::as_conversions)]
             let op_result = VM::OPCODE_TABLE[opcode as usize].call(self);
 
+            if INSPECT {
+                if let Some(inspector) = self.inspector.as_mut() {
+                    inspector.step_end(&self.current_call_frame, Opcode::from(opcode));
+                }
+            }
+
             let result = match op_result {
                 Ok(OpcodeResult::Continue { pc_increment }) => {
                     self.increment_pc_by(pc_increment)?;
@@ -219,21 +274,52 @@ impl<'a> VM<'a> {
     /// Executes precompile and handles the output that it returns, generating a report.
     pub fn execute_precompile(
         vm_type: VMType,
+        config: &EVMConfig,
         code_address: H160,
         calldata: &Bytes,
         gas_limit: u64,
         gas_remaining: &mut u64,
     ) -> Result<ContextResult, VMError> {
-        let execute_precompile = match vm_type {
-            VMType::L1 => precompiles::execute_precompile,
-            VMType::L2 => l2_precompiles::execute_precompile,
+        let result = match config.precompiles.get(&code_address, config.fork) {
+            Some(precompile) => {
+                precompile_registry::execute_precompile(precompile, calldata, gas_remaining)
+            }
+            None => match vm_type {
+                VMType::L1 => {
+                    precompiles::execute_precompile(code_address, calldata, gas_remaining)
+                }
+                VMType::L2 => {
+                    l2_precompiles::execute_precompile(code_address, calldata, gas_remaining)
+                }
+            },
         };
 
-        Self::handle_precompile_result(
-            execute_precompile(code_address, calldata, gas_remaining),
+        Self::handle_precompile_result(result, gas_limit, *gas_remaining)
+    }
+
+    /// Same as [VM::execute_precompile], notifying the inspector if there is one.
+    pub fn inspect_precompile(
+        &mut self,
+        code_address: H160,
+        calldata: &Bytes,
+        gas_limit: u64,
+        gas_remaining: &mut u64,
+    ) -> Result<ContextResult, VMError> {
+        // Only measure the time if someone is going to use it
+        let start = self.inspector.is_some().then(Instant::now);
+        let result = Self::execute_precompile(
+            self.vm_type,
+            &self.env.config,
+            code_address,
+            calldata,
             gas_limit,
-            *gas_remaining,
-        )
+            gas_remaining,
+        )?;
+
+        if let (Some(inspector), Some(start)) = (self.inspector.as_mut(), start) {
+            inspector.precompile(code_address, result.gas_used, start.elapsed());
+        }
+        Ok(result)
     }
 
     /// True if external transaction is a contract creation
@@ -269,6 +355,13 @@ impl<'a> VM<'a> {
         }
 
         self.tracer.exit_context(&ctx_result, true)?;
+        if let Some(inspector) = self.inspector.as_mut() {
+            inspector.call_exit(
+                ctx_result.gas_used,
+                &ctx_result.output,
+                ctx_result.is_success(),
+            );
+        }
 
         let report = ExecutionReport {
             result: ctx_result.result.clone(),
@@ -307,6 +400,7 @@ impl Substate {
         for i in 1..=max_precompile_address {
             initial_accessed_addresses.insert(Address::from_low_u64_be(i));
         }
+        initial_accessed_addresses.extend(env.config.precompiles.active_addresses(env.config.fork));
 
         // Add access lists contents to accessed accounts and accessed storage slots.
         for (address, keys) in tx.access_list().clone() {
@@ -2,8 +2,33 @@
 #![allow(clippy::unwrap_used)]
 
 use bytes::Bytes;
+use ethrex_common::types::Log;
+use ethrex_common::types::{
+    Account, AccountInfo, ChainConfig, CustomPrecompile, CustomPrecompiles, EIP1559Transaction,
+    FeeConfig, Fork, OperatorFeeConfig, Transaction, TxKind,
+};
+use ethrex_common::{Address, H160, H256, U256, tracing::CallType};
+use ethrex_levm::call_frame::{CallFrame, Stack};
+use ethrex_levm::code_cache::AnalyzedCode;
+use ethrex_levm::db::{Database, gen_db::GeneralizedDatabase};
+use ethrex_levm::environment::{EVMConfig, Environment};
+use ethrex_levm::errors::{DatabaseError, ExecutionReport, TxValidationError, VMError};
+use ethrex_levm::hooks::l2_hook::{
+    FEE_VAULT_SLOT, L1_FEE_ORACLE_ADDRESS, L1_FEE_PER_BYTE_SLOT, flz_compress_len,
+};
+use ethrex_levm::inspector::Inspector;
 use ethrex_levm::l2_precompiles::p_256_verify;
-use ethrex_levm::precompiles::bls12_pairing_check;
+use ethrex_levm::memory::Memory;
+use ethrex_levm::opcodes::Opcode;
+use ethrex_levm::precompile_registry::{Precompile, PrecompileRegistry, execute_precompile};
+use ethrex_levm::precompiles::{IDENTITY_ADDRESS, bls12_pairing_check};
+use ethrex_levm::profiler::{Profile, ProfileEntry, Profiler};
+use ethrex_levm::tracing::LevmCallTracer;
+use ethrex_levm::vm::{VM, VMType};
+use sha2::{Digest, Sha512};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
 
 #[test]
 fn pairing_infinity() {
@@ -76,3 +101,654 @@ fn p_256_verify_test() {
         );
     }
 }
+
+struct ReversePrecompile;
+
+impl Precompile for ReversePrecompile {
+    fn address(&self) -> Address {
+        Address::from_low_u64_be(0x0200)
+    }
+
+    fn activation_fork(&self) -> Fork {
+        Fork::Prague
+    }
+
+    fn gas_cost(&self, _calldata: &Bytes) -> Result<u64, VMError> {
+        Ok(100)
+    }
+
+    fn execute(&self, calldata: &Bytes) -> Result<Bytes, VMError> {
+        Ok(calldata.iter().rev().copied().collect())
+    }
+}
+
+struct IdentityOverride;
+
+impl Precompile for IdentityOverride {
+    fn address(&self) -> Address {
+        IDENTITY_ADDRESS
+    }
+
+    fn activation_fork(&self) -> Fork {
+        Fork::Frontier
+    }
+
+    fn gas_cost(&self, _calldata: &Bytes) -> Result<u64, VMError> {
+        Ok(0)
+    }
+
+    fn execute(&self, calldata: &Bytes) -> Result<Bytes, VMError> {
+        Ok(calldata.clone())
+    }
+}
+
+static EXTERNAL_PRECOMPILES: [&dyn Precompile; 1] = [&ReversePrecompile];
+static STANDARD_ADDRESS_PRECOMPILES: [&dyn Precompile; 1] = [&IdentityOverride];
+static DUPLICATED_PRECOMPILES: [&dyn Precompile; 2] = [&ReversePrecompile, &ReversePrecompile];
+
+#[test]
+fn custom_precompile_registry() {
+    let registry = PrecompileRegistry::default()
+        .with_external(&EXTERNAL_PRECOMPILES)
+        .unwrap();
+    // Standard precompile addresses and already registered ones can't be taken
+    assert!(
+        PrecompileRegistry::default()
+            .with_external(&STANDARD_ADDRESS_PRECOMPILES)
+            .is_err()
+    );
+    assert!(
+        PrecompileRegistry::default()
+            .with_external(&DUPLICATED_PRECOMPILES)
+            .is_err()
+    );
+    let mut enabled = CustomPrecompiles::default();
+    enabled.insert(CustomPrecompile::Sha512);
+    assert!(
+        PrecompileRegistry::new(enabled)
+            .with_external(&EXTERNAL_PRECOMPILES)
+            .is_err()
+    );
+
+    let address = ReversePrecompile.address();
+    assert!(!registry.is_precompile(&address, Fork::Cancun));
+    assert!(registry.is_precompile(&address, Fork::Prague));
+    assert_eq!(registry.active_addresses(Fork::Cancun).count(), 0);
+    assert_eq!(
+        registry.active_addresses(Fork::Prague).collect::<Vec<_>>(),
+        vec![address]
+    );
+
+    let precompile = registry.get(&address, Fork::Prague).unwrap();
+    let calldata = Bytes::from(vec![1, 2, 3]);
+    let mut remaining_gas = 1000;
+    let result = execute_precompile(precompile, &calldata, &mut remaining_gas).unwrap();
+    assert_eq!(result, Bytes::from(vec![3, 2, 1]));
+    assert_eq!(remaining_gas, 900);
+
+    let mut remaining_gas = 50;
+    assert!(execute_precompile(precompile, &calldata, &mut remaining_gas).is_err());
+}
+
+/// Accounts not loaded in the cache are empty, like in a fresh chain
+struct TestDatabase {
+    chain_config: ChainConfig,
+}
+
+impl Database for TestDatabase {
+    fn get_account_info(&self, _address: Address) -> Result<AccountInfo, DatabaseError> {
+        Ok(AccountInfo::default())
+    }
+
+    fn get_storage_value(&self, _address: Address, _key: H256) -> Result<U256, DatabaseError> {
+        Ok(U256::zero())
+    }
+
+    fn get_block_hash(&self, _block_number: u64) -> Result<H256, DatabaseError> {
+        Ok(H256::zero())
+    }
+
+    fn get_chain_config(&self) -> Result<ChainConfig, DatabaseError> {
+        Ok(self.chain_config)
+    }
+
+    fn get_account_code(&self, _code_hash: H256) -> Result<Bytes, DatabaseError> {
+        Ok(Bytes::new())
+    }
+}
+
+const TEST_SENDER: Address = H160([0x11; 20]);
+const TEST_CONTRACT: Address = H160([0x22; 20]);
+const TEST_COINBASE: Address = H160([0x33; 20]);
+const TEST_FEE_VAULT: Address = H160([0x44; 20]);
+const TEST_BASE_FEE_VAULT: Address = H160([0x55; 20]);
+const TEST_OPERATOR_FEE_VAULT: Address = H160([0x66; 20]);
+
+/// Database with a funded sender and a contract with the given code
+fn test_db(contract_code: Bytes) -> GeneralizedDatabase {
+    test_db_with_accounts(contract_code, [])
+}
+
+/// Like `test_db`, with some extra accounts
+fn test_db_with_accounts(
+    contract_code: Bytes,
+    extra_accounts: impl IntoIterator<Item = (Address, Account)>,
+) -> GeneralizedDatabase {
+    let chain_config = ChainConfig {
+        prague_time: Some(0),
+        ..Default::default()
+    };
+    let mut accounts = BTreeMap::from([
+        (
+            TEST_SENDER,
+            Account::new(
+                U256::from(10).pow(18.into()),
+                Bytes::new(),
+                0,
+                BTreeMap::new(),
+            ),
+        ),
+        (
+            TEST_CONTRACT,
+            Account::new(U256::zero(), contract_code, 1, BTreeMap::new()),
+        ),
+    ]);
+    accounts.extend(extra_accounts);
+    GeneralizedDatabase::new_with_account_state(Arc::new(TestDatabase { chain_config }), accounts)
+}
+
+fn test_env(config: EVMConfig, tx: &Transaction) -> Environment {
+    Environment {
+        origin: TEST_SENDER,
+        gas_limit: tx.gas_limit(),
+        config,
+        block_number: U256::one(),
+        coinbase: TEST_COINBASE,
+        timestamp: U256::one(),
+        prev_randao: Some(H256::zero()),
+        chain_id: U256::one(),
+        base_fee_per_gas: U256::from(tx.max_fee_per_gas().unwrap_or_default()),
+        gas_price: U256::from(tx.max_fee_per_gas().unwrap_or_default()),
+        block_excess_blob_gas: Some(U256::zero()),
+        block_blob_gas_used: Some(U256::zero()),
+        tx_max_priority_fee_per_gas: tx.max_priority_fee().map(U256::from),
+        tx_max_fee_per_gas: tx.max_fee_per_gas().map(U256::from),
+        tx_nonce: tx.nonce(),
+        block_gas_limit: 30_000_000,
+        ..Default::default()
+    }
+}
+
+fn call_tx(data: Bytes, max_fee_per_gas: u64) -> Transaction {
+    Transaction::EIP1559Transaction(EIP1559Transaction {
+        chain_id: 1,
+        max_fee_per_gas,
+        gas_limit: 1_000_000,
+        to: TxKind::Call(TEST_CONTRACT),
+        data,
+        ..Default::default()
+    })
+}
+
+/// Runs the transaction from the test sender to the test contract
+fn execute_tx(
+    db: &mut GeneralizedDatabase,
+    config: EVMConfig,
+    tx: &Transaction,
+    vm_type: VMType,
+) -> Result<ExecutionReport, VMError> {
+    let env = test_env(config, tx);
+    VM::new(env, db, tx, LevmCallTracer::disabled(), vm_type)?.execute()
+}
+
+/// L1 fee oracle with the given price per byte and fee vault
+fn l1_fee_oracle(l1_fee_per_byte: U256, fee_vault: Address) -> (Address, Account) {
+    let storage = BTreeMap::from([
+        (L1_FEE_PER_BYTE_SLOT, l1_fee_per_byte),
+        (FEE_VAULT_SLOT, U256::from_big_endian(fee_vault.as_bytes())),
+    ]);
+    (
+        L1_FEE_ORACLE_ADDRESS,
+        Account::new(U256::zero(), Bytes::new(), 1, storage),
+    )
+}
+
+fn balance(db: &mut GeneralizedDatabase, address: Address) -> U256 {
+    db.get_account(address).unwrap().info.balance
+}
+
+#[test]
+fn custom_precompile_execution() {
+    // Copies the calldata to memory, STATICCALLs the SHA-512 precompile with it
+    // and returns the 64 bytes written at offset 0x80
+    let code = Bytes::from(vec![
+        0x36, 0x60, 0x00, 0x60, 0x00, 0x37, // CALLDATACOPY(0, 0, CALLDATASIZE)
+        0x60, 0x40, 0x60, 0x80, 0x36, 0x60, 0x00, // retSize, retOffset, argsSize, argsOffset
+        0x61, 0x02, 0x00, 0x5a, 0xfa, 0x50, // STATICCALL(GAS, 0x0200, ...) POP
+        0x60, 0x40, 0x60, 0x80, 0xf3, // RETURN(0x80, 0x40)
+    ]);
+    let calldata = Bytes::from(vec![1, 2, 3]);
+    let tx = call_tx(calldata.clone(), 0);
+
+    let mut enabled = CustomPrecompiles::default();
+    enabled.insert(CustomPrecompile::Sha512);
+    let config = EVMConfig::new(Fork::Prague, EVMConfig::canonical_values(Fork::Prague))
+        .with_precompiles(PrecompileRegistry::new(enabled));
+    let report = execute_tx(&mut test_db(code.clone()), config, &tx, VMType::L2).unwrap();
+    assert!(report.is_success());
+    assert_eq!(report.output.as_ref(), Sha512::digest(&calldata).as_slice());
+
+    // Without enabling it the address is an empty account
+    let config = EVMConfig::new(Fork::Prague, EVMConfig::canonical_values(Fork::Prague));
+    let report = execute_tx(&mut test_db(code), config, &tx, VMType::L2).unwrap();
+    assert!(report.is_success());
+    assert_eq!(report.output, Bytes::from(vec![0; 64]));
+}
+
+#[test]
+fn l2_hook_charges_l1_fee() {
+    let config = EVMConfig::new(Fork::Prague, EVMConfig::canonical_values(Fork::Prague));
+    let tx = call_tx(Bytes::from(vec![1, 2, 3]), 1);
+    let l1_fee_per_byte = U256::from(10);
+    let l1_fee = l1_fee_per_byte * flz_compress_len(&tx.encode_canonical_to_vec());
+
+    // The fee is taken from the sender on top of the gas and sent to the vault set in the oracle
+    let mut db = test_db_with_accounts(
+        Bytes::new(),
+        [l1_fee_oracle(l1_fee_per_byte, TEST_FEE_VAULT)],
+    );
+    let initial_balance = balance(&mut db, TEST_SENDER);
+    let report = execute_tx(&mut db, config, &tx, VMType::L2).unwrap();
+    assert!(report.is_success());
+    assert_eq!(
+        balance(&mut db, TEST_SENDER),
+        initial_balance - U256::from(report.gas_used) - l1_fee
+    );
+    assert_eq!(balance(&mut db, TEST_FEE_VAULT), l1_fee);
+    assert!(balance(&mut db, TEST_COINBASE).is_zero());
+
+    // Without a vault it goes to the coinbase
+    let mut db = test_db_with_accounts(
+        Bytes::new(),
+        [l1_fee_oracle(l1_fee_per_byte, Address::zero())],
+    );
+    execute_tx(&mut db, config, &tx, VMType::L2).unwrap();
+    assert_eq!(balance(&mut db, TEST_COINBASE), l1_fee);
+
+    // Transactions without gas price, like the ones of eth_call, don't pay it
+    let tx = call_tx(Bytes::from(vec![1, 2, 3]), 0);
+    let mut db = test_db_with_accounts(
+        Bytes::new(),
+        [l1_fee_oracle(l1_fee_per_byte, TEST_FEE_VAULT)],
+    );
+    execute_tx(&mut db, config, &tx, VMType::L2).unwrap();
+    assert_eq!(balance(&mut db, TEST_SENDER), initial_balance);
+    assert!(balance(&mut db, TEST_FEE_VAULT).is_zero());
+}
+
+#[test]
+fn l2_hook_rejects_senders_that_cant_pay_l1_fee() {
+    let config = EVMConfig::new(Fork::Prague, EVMConfig::canonical_values(Fork::Prague));
+    let tx = call_tx(Bytes::new(), 1);
+    // The sender has 1 ether, enough for the gas but not for the L1 fee
+    let l1_fee_per_byte = U256::from(10).pow(18.into());
+    let mut db = test_db_with_accounts(
+        Bytes::new(),
+        [l1_fee_oracle(l1_fee_per_byte, TEST_FEE_VAULT)],
+    );
+    let result = execute_tx(&mut db, config, &tx, VMType::L2);
+    assert!(matches!(
+        result,
+        Err(VMError::TxValidation(
+            TxValidationError::InsufficientAccountFunds
+        ))
+    ));
+}
+
+#[test]
+fn l2_hook_pays_fee_vaults() {
+    let operator_fee = OperatorFeeConfig {
+        vault: TEST_OPERATOR_FEE_VAULT,
+        fixed_fee: 1000,
+        fee_per_gas: 10,
+    };
+    let config = EVMConfig::new(Fork::Prague, EVMConfig::canonical_values(Fork::Prague))
+        .with_fee_config(Some(FeeConfig {
+            base_fee_vault: Some(TEST_BASE_FEE_VAULT),
+            operator_fee: Some(operator_fee),
+        }));
+    // The whole gas price is base fee, the coinbase doesn't get a tip
+    let tx = call_tx(Bytes::from(vec![1, 2, 3]), 7);
+
+    let mut db = test_db(Bytes::new());
+    let initial_balance = balance(&mut db, TEST_SENDER);
+    let report = execute_tx(&mut db, config, &tx, VMType::L2).unwrap();
+    assert!(report.is_success());
+    let gas_used = U256::from(report.gas_used);
+    let base_fee = gas_used * 7;
+    // The deposit for the unused gas is refunded
+    let operator_fee = gas_used * 10 + 1000;
+    assert_eq!(balance(&mut db, TEST_BASE_FEE_VAULT), base_fee);
+    assert_eq!(balance(&mut db, TEST_OPERATOR_FEE_VAULT), operator_fee);
+    assert_eq!(
+        balance(&mut db, TEST_SENDER),
+        initial_balance - base_fee - operator_fee
+    );
+    assert!(balance(&mut db, TEST_COINBASE).is_zero());
+
+    // Without a fee config the base fee is burned and there's no operator fee
+    let config = config.with_fee_config(None);
+    let mut db = test_db(Bytes::new());
+    let report = execute_tx(&mut db, config, &tx, VMType::L2).unwrap();
+    assert!(balance(&mut db, TEST_BASE_FEE_VAULT).is_zero());
+    assert!(balance(&mut db, TEST_OPERATOR_FEE_VAULT).is_zero());
+    assert_eq!(
+        balance(&mut db, TEST_SENDER),
+        initial_balance - U256::from(report.gas_used) * 7
+    );
+}
+
+#[test]
+fn l2_hook_rejects_senders_that_cant_pay_operator_fee() {
+    // The sender has 1 ether, enough for the gas but not for the operator fee
+    let config = EVMConfig::new(Fork::Prague, EVMConfig::canonical_values(Fork::Prague))
+        .with_fee_config(Some(FeeConfig {
+            base_fee_vault: None,
+            operator_fee: Some(OperatorFeeConfig {
+                vault: TEST_OPERATOR_FEE_VAULT,
+                fixed_fee: u64::MAX,
+                fee_per_gas: 0,
+            }),
+        }));
+    let tx = call_tx(Bytes::new(), 1);
+    let result = execute_tx(&mut test_db(Bytes::new()), config, &tx, VMType::L2);
+    assert!(matches!(
+        result,
+        Err(VMError::TxValidation(
+            TxValidationError::InsufficientAccountFunds
+        ))
+    ));
+}
+
+#[test]
+fn analyzed_code_jumpdests() {
+    // PUSH1 0x5b JUMPDEST PUSH2 0x5b
+    let bytecode = Bytes::from(vec![0x60, 0x5b, 0x5b, 0x61, 0x5b]);
+    let code = AnalyzedCode::new(&bytecode);
+    assert_eq!(code.bytecode(), bytecode);
+    // A JUMPDEST inside a push immediate isn't a valid destination
+    assert!(!code.is_jumpdest(1));
+    assert!(code.is_jumpdest(2));
+    assert!(!code.is_jumpdest(4));
+    assert!(!code.is_jumpdest(100));
+    // The truncated PUSH2 reads zeros past the end of the code
+    assert_eq!(code.padded().get(4..6), Some(&[0x5b, 0x00][..]));
+    assert_eq!(code.opcode_at(5), 0);
+}
+
+#[test]
+fn analyzed_code_is_shared_by_code_hash() {
+    let code = Bytes::from(vec![0x60, 0x5b, 0x5b]);
+    let code_hash = keccak_hash::keccak(&code);
+    let mut db = test_db(code.clone());
+
+    let first = db.get_analyzed_code(code_hash, &code);
+    let second = db.get_analyzed_code(code_hash, &code);
+    assert!(Arc::ptr_eq(&first, &second));
+    assert_eq!(first.bytecode(), code);
+    assert!(first.is_jumpdest(2));
+
+    let other = db.get_analyzed_code(H256::zero(), &Bytes::new());
+    assert!(!Arc::ptr_eq(&first, &other));
+    assert_eq!(db.analyzed_codes.len(), 2);
+}
+
+#[test]
+fn flz_compress_len_matches_reference() {
+    assert_eq!(flz_compress_len(&[]), 0);
+    // Too short to look for matches, it's a single run of literals
+    assert_eq!(flz_compress_len(&[1]), 2);
+    assert_eq!(flz_compress_len(&[0; 100]), 12);
+    assert_eq!(
+        flz_compress_len(b"hello world hello world hello world hello world"),
+        22
+    );
+    let incompressible: Vec<u8> = (0..200u32)
+        .map(|i| u8::try_from(i * 7919 % 251).unwrap())
+        .collect();
+    assert_eq!(flz_compress_len(&incompressible), 207);
+}
+
+#[test]
+fn profiler_aggregates_precompiles() {
+    let mut profiler = Profiler::default();
+    profiler.precompile(IDENTITY_ADDRESS, 18, Duration::from_micros(2));
+    profiler.precompile(IDENTITY_ADDRESS, 21, Duration::from_micros(3));
+
+    let mut profile = Profile::default();
+    profile.merge(profiler.profile());
+    profile.merge(profiler.profile());
+    assert_eq!(
+        profile.precompiles[&IDENTITY_ADDRESS],
+        ProfileEntry {
+            count: 4,
+            gas: 78,
+            time: Duration::from_micros(10),
+        }
+    );
+    assert_eq!(profile.opcodes().count(), 0);
+}
+
+#[test]
+fn inspector_callframe_views() {
+    let mut stack = Stack::default();
+    assert!(stack.as_slice().is_empty());
+    stack.push1(U256::from(1)).unwrap();
+    stack.push1(U256::from(2)).unwrap();
+    // The top of the stack comes first
+    assert_eq!(stack.as_slice(), &[U256::from(2), U256::from(1)]);
+
+    let mut memory = Memory::new();
+    memory.store_data(0, &[0xaa, 0xbb]).unwrap();
+    assert_eq!(memory.as_bytes().len(), 32);
+    assert_eq!(memory.as_bytes()[..2], [0xaa, 0xbb]);
+
+    // The memory of a child callframe only sees its own region of the shared buffer
+    let mut child_memory = memory.next_memory();
+    assert!(child_memory.as_bytes().is_empty());
+    child_memory.store_data(0, &[0xcc]).unwrap();
+    assert_eq!(child_memory.as_bytes()[0], 0xcc);
+    assert_eq!(memory.as_bytes()[0], 0xaa);
+}
+
+/// Records what the VM reports, to check the hooks are called during execution
+#[derive(Default)]
+struct RecordingInspector {
+    steps: Vec<(usize, Opcode)>,
+    step_ends: usize,
+    calls: Vec<(CallType, Address, Address)>,
+    call_exits: Vec<bool>,
+    logs: Vec<Log>,
+    precompiles: Vec<Address>,
+    storage_reads: Vec<(H256, U256)>,
+    storage_writes: Vec<(H256, U256, U256)>,
+}
+
+impl Inspector for RecordingInspector {
+    fn step(&mut self, call_frame: &CallFrame, opcode: Opcode) {
+        self.steps.push((call_frame.pc, opcode));
+    }
+
+    fn step_end(&mut self, _call_frame: &CallFrame, _opcode: Opcode) {
+        self.step_ends += 1;
+    }
+
+    fn call_enter(
+        &mut self,
+        call_type: CallType,
+        from: Address,
+        to: Address,
+        _value: U256,
+        _gas: u64,
+        _input: &Bytes,
+    ) {
+        self.calls.push((call_type, from, to));
+    }
+
+    fn call_exit(&mut self, _gas_used: u64, _output: &Bytes, success: bool) {
+        self.call_exits.push(success);
+    }
+
+    fn log(&mut self, log: &Log) {
+        self.logs.push(log.clone());
+    }
+
+    fn precompile(&mut self, address: Address, _gas_used: u64, _elapsed: Duration) {
+        self.precompiles.push(address);
+    }
+
+    fn storage_read(&mut self, _address: Address, key: H256, value: U256) {
+        self.storage_reads.push((key, value));
+    }
+
+    fn storage_write(
+        &mut self,
+        _address: Address,
+        key: H256,
+        previous_value: U256,
+        new_value: U256,
+    ) {
+        self.storage_writes.push((key, previous_value, new_value));
+    }
+}
+
+#[test]
+fn profiler_records_execution() {
+    // Two zero-length STATICCALLs to the identity precompile
+    let static_call = [
+        0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60,
+        0x00, // retSize, retOffset, argsSize, argsOffset
+        0x60, 0x04, 0x5a, 0xfa, 0x50, // STATICCALL(GAS, IDENTITY, ...) POP
+    ];
+    let code = Bytes::from([&static_call[..], &static_call[..], &[0x00]].concat());
+    let code_hash = keccak_hash::keccak(&code);
+    let tx = call_tx(Bytes::new(), 0);
+    let mut db = test_db(code);
+    let config = EVMConfig::new(Fork::Prague, EVMConfig::canonical_values(Fork::Prague));
+    let mut profiler = Profiler::default();
+    let report = VM::new(
+        test_env(config, &tx),
+        &mut db,
+        &tx,
+        LevmCallTracer::disabled(),
+        VMType::L1,
+    )
+    .unwrap()
+    .with_inspector(Box::new(&mut profiler))
+    .execute()
+    .unwrap();
+    assert!(report.is_success());
+
+    let profile = profiler.into_profile();
+    let counts: Vec<(Opcode, u64, u64)> = profile
+        .opcodes()
+        .map(|(opcode, entry)| (opcode, entry.count, entry.gas))
+        .collect();
+    // The gas of the STATICCALLs includes the warm access and the precompile execution
+    assert_eq!(
+        counts,
+        vec![
+            (Opcode::STOP, 1, 0),
+            (Opcode::POP, 2, 4),
+            (Opcode::GAS, 2, 4),
+            (Opcode::PUSH1, 10, 30),
+            (Opcode::STATICCALL, 2, 2 * (100 + 15)),
+        ]
+    );
+
+    // Identity costs 15 gas for empty input
+    assert_eq!(profile.precompiles.len(), 1);
+    let identity = profile.precompiles[&IDENTITY_ADDRESS];
+    assert_eq!((identity.count, identity.gas), (2, 30));
+
+    let contract = profile.contracts[&code_hash];
+    assert_eq!(contract.count, 17);
+    assert_eq!(contract.gas, 4 + 4 + 30 + 2 * (100 + 15));
+}
+
+#[test]
+fn inspector_records_execution() {
+    let code = Bytes::from(vec![
+        0x60, 0x01, 0x60, 0x00, 0x55, // SSTORE(0, 1)
+        0x60, 0x00, 0x54, 0x50, // SLOAD(0) POP
+        0x60, 0x00, 0x60, 0x00, 0xa0, // LOG0(0, 0)
+        0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60,
+        0x00, // retSize, retOffset, argsSize, argsOffset
+        0x60, 0x04, 0x5a, 0xfa, 0x50, // STATICCALL(GAS, IDENTITY, ...) POP
+        0x00, // STOP
+    ]);
+    let tx = call_tx(Bytes::new(), 0);
+    let mut db = test_db(code);
+    let config = EVMConfig::new(Fork::Prague, EVMConfig::canonical_values(Fork::Prague));
+    let mut inspector = RecordingInspector::default();
+    let report = VM::new(
+        test_env(config, &tx),
+        &mut db,
+        &tx,
+        LevmCallTracer::disabled(),
+        VMType::L1,
+    )
+    .unwrap()
+    .with_inspector(Box::new(&mut inspector))
+    .execute()
+    .unwrap();
+    assert!(report.is_success());
+
+    let opcodes: Vec<Opcode> = inspector.steps.iter().map(|(_, opcode)| *opcode).collect();
+    assert_eq!(
+        opcodes,
+        vec![
+            Opcode::PUSH1,
+            Opcode::PUSH1,
+            Opcode::SSTORE,
+            Opcode::PUSH1,
+            Opcode::SLOAD,
+            Opcode::POP,
+            Opcode::PUSH1,
+            Opcode::PUSH1,
+            Opcode::LOG0,
+            Opcode::PUSH1,
+            Opcode::PUSH1,
+            Opcode::PUSH1,
+            Opcode::PUSH1,
+            Opcode::PUSH1,
+            Opcode::GAS,
+            Opcode::STATICCALL,
+            Opcode::POP,
+            Opcode::STOP,
+        ]
+    );
+    assert_eq!(inspector.steps[2].0, 4);
+    assert_eq!(inspector.step_ends, inspector.steps.len());
+
+    assert_eq!(
+        inspector.calls,
+        vec![
+            (CallType::CALL, TEST_SENDER, TEST_CONTRACT),
+            (CallType::STATICCALL, TEST_CONTRACT, IDENTITY_ADDRESS),
+        ]
+    );
+    // The precompile call exits before the transaction
+    assert_eq!(inspector.call_exits, vec![true, true]);
+    assert_eq!(inspector.precompiles, vec![IDENTITY_ADDRESS]);
+
+    assert_eq!(inspector.logs.len(), 1);
+    assert_eq!(inspector.logs[0].address, TEST_CONTRACT);
+    assert_eq!(inspector.logs, report.logs);
+
+    assert_eq!(
+        inspector.storage_writes,
+        vec![(H256::zero(), U256::zero(), U256::one())]
+    );
+    assert_eq!(inspector.storage_reads, vec![(H256::zero(), U256::one())]);
+}
@@ -1,5 +1,6 @@
 mod constants;
 mod db;
+pub mod differential;
 mod errors;
 mod execution_result;
 mod helpers;
@@ -8,9 +9,11 @@ mod witness_db;
 
 pub mod backends;
 
+pub use backends::levm::SpeculativeExecution;
 pub use backends::{BlockExecutionResult, Evm, EvmEngine};
 pub use db::{DynVmDatabase, VmDatabase};
 pub use errors::{EvmError, ProverDBError};
+pub use ethrex_levm::profiler::{Profile, Profiler};
 pub use execution_result::ExecutionResult;
 pub use helpers::{SpecId, create_contract_address, fork_to_spec_id};
 pub use witness_db::ExecutionWitnessWrapper;
@@ -1,8 +1,9 @@
 use ethrex_common::tracing::CallTrace;
 use ethrex_common::types::Block;
+use ethrex_levm::{errors::ExecutionReport, inspector::Inspector};
 
 use crate::backends::levm::LEVM;
-use crate::{Evm, EvmError, backends::revm::REVM};
+use crate::{BlockExecutionResult, Evm, EvmError, backends::revm::REVM};
 
 impl Evm {
     /// Runs a single tx with the call tracer and outputs its trace
@@ -34,6 +35,49 @@ impl Evm {
         }
     }
 
+    /// Runs a single tx notifying the inspector of every step of its execution
+    /// Asumes that the received state already contains changes from previous blocks and other
+    /// transactions within its block
+    /// Wraps [LEVM::inspect_tx], not supported by REVM.
+    pub fn inspect_tx(
+        &mut self,
+        block: &Block,
+        tx_index: usize,
+        inspector: Box<dyn Inspector + '_>,
+    ) -> Result<ExecutionReport, EvmError> {
+        let tx = block
+            .body
+            .transactions
+            .get(tx_index)
+            .ok_or(EvmError::Custom(
+                "Missing Transaction for Inspection".to_string(),
+            ))?;
+
+        match self {
+            Evm::REVM { .. } => Err(EvmError::Custom(
+                "Inspectors are only supported by LEVM".to_string(),
+            )),
+            Evm::LEVM { db, vm_type } => {
+                LEVM::inspect_tx(db, &block.header, tx, inspector, *vm_type)
+            }
+        }
+    }
+
+    /// Executes the block notifying the inspector of every step of its transactions.
+    /// Wraps [LEVM::inspect_block], not supported by REVM.
+    pub fn inspect_block(
+        &mut self,
+        block: &Block,
+        inspector: &mut dyn Inspector,
+    ) -> Result<BlockExecutionResult, EvmError> {
+        match self {
+            Evm::REVM { .. } => Err(EvmError::Custom(
+                "Inspectors are only supported by LEVM".to_string(),
+            )),
+            Evm::LEVM { db, vm_type } => LEVM::inspect_block(block, db, *vm_type, inspector),
+        }
+    }
+
     /// Reruns the given block, saving the changes on the state, doesn't output any results or receipts
     /// If the optional argument `stop_index` is set, the run will stop just before executing the transaction at that index
     /// and won't process the withdrawals afterwards